@@ -50,6 +50,7 @@ function CreateTopicModal({ visible, onClose, onCreated }: CreateTopicModalProps
     try {
       await getApiClient().createTopic({
         name: trimmedName,
+        group_id: null,
         description: description.trim() || null,
         everyone_read: everyoneRead,
         everyone_write: everyoneWrite,
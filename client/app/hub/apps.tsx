@@ -68,6 +68,7 @@ export default function AppsScreen() {
     const ok = await mutate(() =>
       api.createApplication({
         name: newAppName.trim(),
+        group_id: null,
         description: newAppDesc.trim() || null,
         default_priority: null,
       }),
@@ -100,6 +101,7 @@ export default function AppsScreen() {
     const api = getApiClient();
     const ok = await mutate(() =>
       api.updateApplication(editApp.id, {
+        group_id: null,
         name: editName.trim(),
         description: editDesc.trim() || null,
         default_priority: parseInt(editPriority) || 5,
@@ -97,6 +97,13 @@ export default function UsersScreen() {
     ]);
   };
 
+  const isLocked = (u: UserResponse) => !!u.locked_until && new Date(u.locked_until) > new Date();
+
+  const handleUnlockUser = async (u: UserResponse) => {
+    const api = getApiClient();
+    await mutateUsers(() => api.unlockUser(u.id));
+  };
+
   const handleDeletePermission = (p: TopicPermission) => {
     Alert.alert('Delete Permission', `Delete permission for pattern "${p.topic_pattern}"?`, [
       { text: 'Cancel', style: 'cancel' },
@@ -141,6 +148,7 @@ export default function UsersScreen() {
     const api = getApiClient();
     const ok = await mutatePerms(() => api.createPermission({
       user_id: userId,
+      group_id: null,
       topic_pattern: newPermPattern.trim(),
       can_read: newPermRead,
       can_write: newPermWrite,
@@ -152,7 +160,8 @@ export default function UsersScreen() {
     }
   };
 
-  const resolveUsername = (userId: number): string => {
+  const resolveUsername = (userId: number | null, groupId?: number | null): string => {
+    if (userId == null) return `Group #${groupId}`;
     const found = users.find((u) => u.id === userId);
     return found?.username ?? `User #${userId}`;
   };
@@ -185,8 +194,16 @@ export default function UsersScreen() {
                       {u.email ? (
                         <Text className="text-xs text-slate-500 dark:text-slate-400 mt-0.5">{u.email}</Text>
                       ) : null}
+                      {isLocked(u) ? (
+                        <Text className="text-xs text-red-500 mt-0.5">Locked after failed logins</Text>
+                      ) : null}
                     </View>
                     <View className="flex-row items-center gap-3">
+                      {isLocked(u) && (
+                        <Pressable onPress={() => handleUnlockUser(u)} hitSlop={8}>
+                          <Ionicons name="lock-open-outline" size={16} color="#0052FF" />
+                        </Pressable>
+                      )}
                       <View className="flex-row items-center gap-1">
                         <Text className="text-[11px] text-slate-400 dark:text-slate-500">Admin</Text>
                         <Switch
@@ -231,7 +248,7 @@ export default function UsersScreen() {
                   >
                     <View className="flex-1">
                       <Text className="text-base font-semibold text-slate-900 dark:text-slate-100">
-                        {resolveUsername(p.user_id)} -- {p.topic_pattern}
+                        {resolveUsername(p.user_id, p.group_id)} -- {p.topic_pattern}
                       </Text>
                       <Text className="text-xs text-slate-500 dark:text-slate-400 mt-0.5">
                         {p.can_read ? 'Read' : ''}{p.can_read && p.can_write ? ' + ' : ''}{p.can_write ? 'Write' : ''}
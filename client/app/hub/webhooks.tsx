@@ -216,6 +216,7 @@ export default function WebhooksScreen() {
         followRedirects: direction === 'outgoing' ? createFollowRedirects : null,
         groupName: createGroupName.trim() || null,
         secret: direction === 'incoming' && createSecret.trim() ? createSecret.trim() : null,
+        notifyUrl: null,
       });
       const secretUsed = direction === 'incoming' ? createSecret.trim() : '';
       resetForm();
@@ -238,6 +239,7 @@ export default function WebhooksScreen() {
       targetUrl: null, httpMethod: null, headers: null,
       bodyTemplate: null, maxRetries: null, retryDelaySecs: null,
       timeoutSecs: null, followRedirects: null, groupName: null, secret: null,
+      notifyUrl: null,
     }));
   };
 
@@ -263,6 +265,7 @@ export default function WebhooksScreen() {
             timeoutSecs: webhook.timeout_secs, followRedirects: webhook.follow_redirects,
             groupName: webhook.group_name ?? null,
             secret: null,
+            notifyUrl: webhook.notify_url ?? null,
           }));
         },
       },
@@ -344,6 +347,8 @@ export default function WebhooksScreen() {
       retryDelaySecs: isOutgoing ? (parseInt(editRetryDelay, 10) || 60) : null,
       timeoutSecs: isOutgoing ? (parseInt(editTimeout, 10) || 15) : null,
       followRedirects: isOutgoing ? editFollowRedirects : null,
+      // Notification URLs are edited on the web.
+      notifyUrl: null,
     }));
     if (ok) setEditWebhook(null);
   };
@@ -157,6 +157,10 @@ export class RstifyClient {
     await this.request("DELETE", `/user/${id}`);
   }
 
+  async unlockUser(id: number): Promise<UserResponse> {
+    return this.request("POST", `/user/${id}/unlock`);
+  }
+
   async createUser(req: CreateUser): Promise<UserResponse> {
     return this.request("POST", "/api/user", req);
   }
@@ -44,6 +44,10 @@ function resolveAttachments(message: MessageResponse): AttachmentInfo[] {
         type: attachmentExtra.type ?? null,
         size: attachmentExtra.size ?? 0,
         url: attachmentExtra.url,
+        sha256: null,
+        width: null,
+        height: null,
+        thumbnail_url: null,
       },
     ];
   }
@@ -107,7 +111,8 @@ export const MessageAttachments = React.memo(function MessageAttachments({
           {isImage(att.type) ? (
             <Image
               source={{
-                uri: getFullUrl(att.url),
+                // Previews use the server-generated thumbnail when there is one.
+                uri: getFullUrl(att.thumbnail_url ?? att.url),
                 headers: token ? { Authorization: `Bearer ${token}` } : undefined,
               }}
               style={{ width: "100%", height: 160 }}
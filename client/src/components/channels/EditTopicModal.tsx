@@ -67,6 +67,7 @@ export function EditTopicModal({ visible, topic, onClose, onUpdated }: EditTopic
     setIsSubmitting(true);
     try {
       await getApiClient().updateTopic(topic.name, {
+        group_id: null,
         description: description.trim() || null,
         everyone_read: everyoneRead,
         everyone_write: everyoneWrite,
@@ -41,6 +41,7 @@ export function PublishModal({ visible, topicName, onClose }: PublishModalProps)
         icon_url: null,
         actions: null,
         scheduled_for: null,
+        attachments: null,
       });
 
       Toast.show({
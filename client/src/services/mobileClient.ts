@@ -21,7 +21,12 @@ export async function ensureMobileClient(): Promise<{ id: number; token: string
     }
   }
   if (!client) {
-    const created = await api.createClient({ name: "rstify-mobile", scopes: null });
+    const created = await api.createClient({
+      name: "rstify-mobile",
+      scopes: null,
+      expires_at: null,
+      allowed_ips: null,
+    });
     client = { id: created.id, token: created.token };
     mobileClientCache.save(client);
   }
@@ -0,0 +1,133 @@
+//! Signed, expiring attachment URLs.
+//!
+//! A link grants read access to one attachment without an auth token, so it can
+//! be shared or put in an email or push payload. It carries its expiry and an
+//! HMAC over the attachment id and that expiry; the key is derived from the JWT
+//! secret, so rotating the secret revokes every outstanding link.
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rstify_core::models::MessageResponse;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Lifetime of links put into notifications (email, push).
+pub const NOTIFICATION_LINK_TTL: Duration = Duration::days(7);
+/// Lifetime of a minted link when the caller doesn't ask for one.
+pub const DEFAULT_LINK_TTL: Duration = Duration::hours(24);
+/// Longest lifetime a minted link may have.
+pub const MAX_LINK_TTL: Duration = Duration::days(30);
+
+#[derive(Clone)]
+pub struct AttachmentLinks {
+    key: Vec<u8>,
+    base_url: Option<String>,
+}
+
+impl AttachmentLinks {
+    pub fn new(jwt_secret: &str) -> Self {
+        // A derived key keeps link signatures apart from JWT signatures.
+        let mut mac = HmacSha256::new_from_slice(jwt_secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(b"rstify attachment links v1");
+        Self {
+            key: mac.finalize().into_bytes().to_vec(),
+            base_url: None,
+        }
+    }
+
+    /// Public URL of the server, without a trailing slash. Links are relative
+    /// to the server until one is set.
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    fn signature(&self, attachment_id: i64, expires: i64) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}", attachment_id, expires).as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// A link to the attachment valid until `expires_at`: absolute when a base
+    /// URL is configured, otherwise a path on this server.
+    pub fn url(&self, attachment_id: i64, expires_at: DateTime<Utc>) -> String {
+        let expires = expires_at.timestamp();
+        format!(
+            "{}/api/attachments/{}/public?expires={}&sig={}",
+            self.base_url.as_deref().unwrap_or(""),
+            attachment_id,
+            expires,
+            self.signature(attachment_id, expires)
+        )
+    }
+
+    /// Whether `sig` is this server's signature for the attachment and expiry,
+    /// and the expiry hasn't passed.
+    pub fn verify(&self, attachment_id: i64, expires: i64, sig: &str, now: DateTime<Utc>) -> bool {
+        if expires < now.timestamp() {
+            return false;
+        }
+        let expected = self.signature(attachment_id, expires);
+        expected.len() == sig.len()
+            && expected
+                .bytes()
+                .zip(sig.bytes())
+                .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+                == 0
+    }
+
+    /// A copy of the message whose attachment URLs are signed links, for
+    /// payloads read outside an authenticated session.
+    pub fn sign_message(&self, message: &MessageResponse) -> MessageResponse {
+        let mut signed = message.clone();
+        if let Some(attachments) = signed.attachments.as_mut() {
+            let expires_at = Utc::now() + NOTIFICATION_LINK_TTL;
+            for attachment in attachments {
+                attachment.url = self.url(attachment.id, expires_at);
+            }
+        }
+        signed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_verify_until_they_expire() {
+        let links = AttachmentLinks::new("secret-secret-secret-secret-secret")
+            .with_base_url(Some("https://push.example.com".to_string()));
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(1);
+        let url = links.url(42, expires_at);
+        assert!(url.starts_with("https://push.example.com/api/attachments/42/public?expires="));
+
+        let sig = url.rsplit("sig=").next().unwrap();
+        let expires = expires_at.timestamp();
+        assert!(links.verify(42, expires, sig, now));
+        assert!(
+            !links.verify(43, expires, sig, now),
+            "bound to the attachment"
+        );
+        assert!(
+            !links.verify(42, expires + 1, sig, now),
+            "bound to the expiry"
+        );
+        assert!(!links.verify(42, expires, sig, now + Duration::hours(2)));
+
+        let other = AttachmentLinks::new("another-secret-another-secret-12");
+        assert!(!other.verify(42, expires, sig, now), "bound to the secret");
+    }
+}
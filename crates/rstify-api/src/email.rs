@@ -0,0 +1,203 @@
+//! Email for messages in a user's inbox, under the user's own
+//! [`EmailPreferences`]. Separate from the ntfy `X-Email` header, which mails
+//! an address the publisher chooses.
+
+use crate::helpers::publish::{can_read, DeliveryTarget};
+use crate::state::AppState;
+use chrono::Utc;
+use rstify_auth::acl::topic_matches;
+use rstify_core::models::{EmailPreferences, MessageResponse, Topic};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Default cap on preference-driven emails per user per hour.
+pub const DEFAULT_MAX_PER_HOUR: u32 = 20;
+
+const WINDOW: Duration = Duration::from_secs(3600);
+
+/// Per-user sliding-hour cap, so a chatty topic can't flood a mailbox.
+pub struct EmailRateCap {
+    max_per_hour: u32,
+    sent: Mutex<HashMap<i64, VecDeque<Instant>>>,
+}
+
+impl EmailRateCap {
+    pub fn new(max_per_hour: u32) -> Self {
+        Self {
+            max_per_hour,
+            sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count one email for `user_id`, or return false if the cap is reached.
+    pub fn try_acquire(&self, user_id: i64) -> bool {
+        let now = Instant::now();
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        let times = sent.entry(user_id).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+        {
+            times.pop_front();
+        }
+        if times.len() >= self.max_per_hour as usize {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+/// Users a message should be emailed to, with their addresses.
+///
+/// An application message goes to its owner. A topic message goes to the
+/// topic owner (if inbox-routed) when they have not narrowed their topics,
+/// and to every user whose topic list matches the topic and who may read it.
+pub async fn email_recipients(
+    state: &AppState,
+    target: &DeliveryTarget<'_>,
+    response: &MessageResponse,
+) -> Vec<(i64, String)> {
+    let now = Utc::now();
+    let candidates: Vec<EmailPreferences> = match target {
+        DeliveryTarget::User(user_id) => match state.user_repo.email_preferences(*user_id).await {
+            Ok(prefs) => prefs.into_iter().collect(),
+            Err(e) => {
+                warn!(
+                    "Failed to load email preferences for user {}: {}",
+                    user_id, e
+                );
+                Vec::new()
+            }
+        },
+        DeliveryTarget::Topic(_) => match state.user_repo.list_email_enabled().await {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                warn!("Failed to load email preferences: {}", e);
+                Vec::new()
+            }
+        },
+    };
+
+    let mut recipients = Vec::new();
+    for prefs in candidates {
+        if !rstify_core::policy::should_email(&prefs, response) {
+            continue;
+        }
+        // User-wide quiet hours cover email too; device schedules don't.
+        match state.client_repo.list_quiet_hours(prefs.user_id).await {
+            Ok(schedules)
+                if rstify_core::policy::should_suppress(
+                    &schedules,
+                    None,
+                    response.priority,
+                    now,
+                ) =>
+            {
+                continue;
+            }
+            Ok(_) => {}
+            Err(e) => warn!(
+                "Failed to load quiet hours for user {}: {}",
+                prefs.user_id, e
+            ),
+        }
+        if let DeliveryTarget::Topic(topic) = target {
+            if !wants_topic(state, &prefs, topic, response).await {
+                continue;
+            }
+        }
+        let Ok(Some(user)) = state.user_repo.find_by_id(prefs.user_id).await else {
+            continue;
+        };
+        if let Some(address) = user.email.filter(|e| !e.trim().is_empty()) {
+            recipients.push((user.id, address));
+        }
+    }
+    recipients
+}
+
+async fn wants_topic(
+    state: &AppState,
+    prefs: &EmailPreferences,
+    topic: &Topic,
+    response: &MessageResponse,
+) -> bool {
+    match prefs.topic_patterns() {
+        None => response.inbox && topic.owner_id == Some(prefs.user_id),
+        Some(patterns) => {
+            patterns.iter().any(|p| topic_matches(p, &topic.name))
+                && can_read(state, prefs.user_id, None, topic).await
+        }
+    }
+}
+
+/// Queue email for everyone who opted in to a message (no-op without SMTP).
+pub async fn notify(state: &AppState, target: &DeliveryTarget<'_>, response: &MessageResponse) {
+    let Some(ref outbox) = state.email else {
+        return;
+    };
+    let recipients = email_recipients(state, target, response).await;
+    if recipients.is_empty() {
+        return;
+    }
+    let subject = subject(response, target);
+    let text = text_body(state, &response.message, response);
+    let html = rstify_jobs::email::render_html(&text, response.is_markdown());
+    for (user_id, address) in recipients {
+        if !state.email_rate_cap.try_acquire(user_id) {
+            warn!(
+                "Email cap reached for user {}; not emailing message {}",
+                user_id, response.id
+            );
+            continue;
+        }
+        if let Err(e) = outbox.enqueue(&address, &subject, &text, Some(&html)).await {
+            warn!("Failed to queue email for user {}: {}", user_id, e);
+        }
+    }
+}
+
+fn subject(response: &MessageResponse, target: &DeliveryTarget<'_>) -> String {
+    match (&response.title, target) {
+        (Some(title), _) if !title.trim().is_empty() => title.clone(),
+        (_, DeliveryTarget::Topic(topic)) => format!("Notification from {}", topic.name),
+        _ => "New notification".to_string(),
+    }
+}
+
+/// The message text, followed by signed download links for its attachments.
+/// Links need the server's public `BASE_URL`; without it only names are listed.
+pub(crate) fn text_body(state: &AppState, text: &str, response: &MessageResponse) -> String {
+    let Some(attachments) = response.attachments.as_ref().filter(|a| !a.is_empty()) else {
+        return text.to_string();
+    };
+    let mut body = format!("{}\n\nAttachments:", text);
+    if state.attachment_links.base_url().is_some() {
+        let signed = state.attachment_links.sign_message(response);
+        for attachment in signed.attachments.iter().flatten() {
+            body.push_str(&format!("\n- {}: {}", attachment.name, attachment.url));
+        }
+    } else {
+        for attachment in attachments {
+            body.push_str(&format!("\n- {}", attachment.name));
+        }
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_cap() {
+        let cap = EmailRateCap::new(2);
+        assert!(cap.try_acquire(1));
+        assert!(cap.try_acquire(1));
+        assert!(!cap.try_acquire(1));
+        assert!(cap.try_acquire(2));
+    }
+}
@@ -0,0 +1,235 @@
+//! Escalation steps for unacknowledged topic messages. The state machine runs
+//! in [`rstify_jobs::escalation`]; this module starts escalations on delivery
+//! and supplies the callback that carries out each step.
+
+use crate::extractors::auth::AuthUser;
+use crate::helpers::publish::{can_read, has_push, push_to_clients, topic_push_targets};
+use crate::state::AppState;
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    EscalationPolicy, EscalationStep, MessageResponse, Topic, ESCALATION_ACTIONS,
+};
+use tracing::warn;
+
+/// Most steps one policy may have.
+pub const MAX_STEPS: usize = 10;
+
+/// Longest wait before a step: a day.
+const MAX_AFTER_MINUTES: i32 = 24 * 60;
+
+/// Start escalating a delivered topic message if its topic asks for it.
+pub async fn begin(state: &AppState, topic: &Topic, response: &MessageResponse) {
+    if topic.escalation_policy.is_none() {
+        return;
+    }
+    let repos = state.repositories();
+    if let Err(e) =
+        rstify_jobs::escalation::begin(&repos, topic, response, chrono::Utc::now()).await
+    {
+        warn!(
+            "Failed to start escalation for message {}: {}",
+            response.id, e
+        );
+    }
+}
+
+/// Check a policy the caller wants to put on `topic`. Secondary users must be
+/// able to read the topic, and only members of a group may escalate to it.
+pub(crate) async fn validate_policy(
+    state: &AppState,
+    auth: &AuthUser,
+    topic: &Topic,
+    policy: &EscalationPolicy,
+) -> Result<(), CoreError> {
+    if !(0..=10).contains(&policy.min_priority) {
+        return Err(CoreError::Validation(
+            "min_priority must be between 0 and 10".to_string(),
+        ));
+    }
+    if policy.steps.len() > MAX_STEPS {
+        return Err(CoreError::Validation(format!(
+            "An escalation policy has at most {} steps",
+            MAX_STEPS
+        )));
+    }
+    for (i, step) in policy.steps.iter().enumerate() {
+        let n = i + 1;
+        if !(1..=MAX_AFTER_MINUTES).contains(&step.after_minutes) {
+            return Err(CoreError::Validation(format!(
+                "Step {}: after_minutes must be between 1 and {}",
+                n, MAX_AFTER_MINUTES
+            )));
+        }
+        if !ESCALATION_ACTIONS.contains(&step.action.as_str()) {
+            return Err(CoreError::Validation(format!(
+                "Step {}: action must be one of: {}",
+                n,
+                ESCALATION_ACTIONS.join(", ")
+            )));
+        }
+        match (step.user_id, step.group_id) {
+            (Some(_), Some(_)) => {
+                return Err(CoreError::Validation(format!(
+                    "Step {}: set user_id or group_id, not both",
+                    n
+                )));
+            }
+            (Some(user_id), None) => {
+                if !can_read(state, user_id, None, topic).await {
+                    return Err(CoreError::Validation(format!(
+                        "Step {}: user {} cannot read topic '{}'",
+                        n, user_id, topic.name
+                    )));
+                }
+            }
+            (None, Some(group_id)) => {
+                if state.group_repo.find_by_id(group_id).await?.is_none()
+                    || !(auth.user.is_admin || auth.is_group_member(group_id))
+                {
+                    return Err(CoreError::Validation(format!(
+                        "Step {}: group {} not found",
+                        n, group_id
+                    )));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Carry out one step, returning what it did for the message's record.
+pub async fn run_step(
+    state: &AppState,
+    topic: &Topic,
+    step: &EscalationStep,
+    response: &MessageResponse,
+) -> String {
+    match step.action.as_str() {
+        "push" => push_step(state, topic, step, response).await,
+        "email" => email_step(state, topic, step, response).await,
+        other => format!("unknown action '{}'", other),
+    }
+}
+
+/// Pushes go to the step's user or group, or else to the devices that got the
+/// original push. Quiet hours don't hold them back: an escalation is exactly
+/// what should break through.
+async fn push_step(
+    state: &AppState,
+    topic: &Topic,
+    step: &EscalationStep,
+    response: &MessageResponse,
+) -> String {
+    if state.fcm.is_none() && state.webpush.is_none() {
+        return "push is not configured".to_string();
+    }
+    let targets = match step_users(state, topic, step).await {
+        Some(users) => {
+            let mut targets = Vec::new();
+            for user_id in users {
+                match state.client_repo.list_by_user(user_id).await {
+                    Ok(clients) => targets.extend(
+                        clients
+                            .into_iter()
+                            .filter(|c| !c.is_expired() && has_push(c)),
+                    ),
+                    Err(e) => warn!("Failed to fetch clients for user {}: {}", user_id, e),
+                }
+            }
+            targets
+        }
+        None => topic_push_targets(state, topic, response).await,
+    };
+    if targets.is_empty() {
+        return "no devices to push to".to_string();
+    }
+    push_to_clients(state, &targets, response).await;
+    match targets.len() {
+        1 => "pushed to 1 device".to_string(),
+        n => format!("pushed to {} devices", n),
+    }
+}
+
+/// Email goes to the step's user or group, or else to the topic's owner (or
+/// its owning group's members), whatever their email preferences.
+async fn email_step(
+    state: &AppState,
+    topic: &Topic,
+    step: &EscalationStep,
+    response: &MessageResponse,
+) -> String {
+    let Some(ref outbox) = state.email else {
+        return "email is not configured".to_string();
+    };
+    let users = match step_users(state, topic, step).await {
+        Some(users) => users,
+        None => match topic.group_id {
+            Some(group_id) => group_users(state, group_id).await,
+            None => topic.owner_id.into_iter().collect(),
+        },
+    };
+
+    let subject = format!(
+        "Unacknowledged: {}",
+        response
+            .title
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or(&topic.name)
+    );
+    let text = crate::email::text_body(state, &response.message, response);
+    let html = rstify_jobs::email::render_html(&text, response.is_markdown());
+    let mut sent = 0;
+    for user_id in users {
+        let Ok(Some(user)) = state.user_repo.find_by_id(user_id).await else {
+            continue;
+        };
+        let Some(address) = user.email.filter(|e| !e.trim().is_empty()) else {
+            continue;
+        };
+        if !state.email_rate_cap.try_acquire(user.id) {
+            warn!(
+                "Email cap reached for user {}; not escalating message {}",
+                user.id, response.id
+            );
+            continue;
+        }
+        match outbox.enqueue(&address, &subject, &text, Some(&html)).await {
+            Ok(_) => sent += 1,
+            Err(e) => warn!("Failed to queue email for user {}: {}", user.id, e),
+        }
+    }
+    match sent {
+        0 => "no one to email".to_string(),
+        1 => "emailed 1 user".to_string(),
+        n => format!("emailed {} users", n),
+    }
+}
+
+/// The users a step names, narrowed to those who may still read the topic;
+/// `None` when it names nobody and goes to the usual recipients.
+async fn step_users(state: &AppState, topic: &Topic, step: &EscalationStep) -> Option<Vec<i64>> {
+    let named = match (step.user_id, step.group_id) {
+        (Some(user_id), _) => vec![user_id],
+        (None, Some(group_id)) => group_users(state, group_id).await,
+        (None, None) => return None,
+    };
+    let mut users = Vec::new();
+    for user_id in named {
+        if can_read(state, user_id, None, topic).await {
+            users.push(user_id);
+        }
+    }
+    Some(users)
+}
+
+async fn group_users(state: &AppState, group_id: i64) -> Vec<i64> {
+    match state.group_repo.list_members(group_id).await {
+        Ok(members) => members.into_iter().map(|m| m.user_id).collect(),
+        Err(e) => {
+            warn!("Failed to fetch members of group {}: {}", group_id, e);
+            Vec::new()
+        }
+    }
+}
@@ -3,12 +3,16 @@ use axum::http::request::Parts;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
+use rstify_auth::acl::topic_matches;
 use rstify_auth::tokens::{classify_token, validate_jwt, Claims, TokenType};
-use rstify_core::models::{Application, Client, User};
-use rstify_core::repositories::{ApplicationRepository, ClientRepository, UserRepository};
+use rstify_core::models::{Application, Client, GroupMember, User};
 use serde_json::json;
+use std::convert::Infallible;
+use std::net::IpAddr;
 use tracing::warn;
 
+use crate::error::ApiError;
+use crate::middleware::rate_limit::{peer_ip, RateLimiter};
 use crate::state::AppState;
 
 /// Authenticated user from JWT or client token
@@ -17,6 +21,10 @@ pub struct AuthUser {
     pub claims: Option<Claims>,
     /// Present when auth'd via client token — use for scope checks
     pub client: Option<Client>,
+    /// The user's group memberships, for group ownership and ACL checks
+    pub groups: Vec<GroupMember>,
+    /// Caller address (see [`ClientIp`]), recorded in the audit log
+    pub ip: Option<String>,
 }
 
 impl AuthUser {
@@ -37,6 +45,28 @@ impl AuthUser {
         }
     }
 
+    /// Check if this auth context may `access` ("read" or "write") a topic
+    /// under the token's `topic:` scopes. JWT users are unrestricted; this does
+    /// not replace the user's own topic ACL, it narrows it.
+    pub fn can_access_topic(&self, access: &str, topic: &str) -> bool {
+        match self.client.as_ref().and_then(|c| c.topic_patterns(access)) {
+            None => true,
+            Some(patterns) => patterns.iter().any(|p| topic_matches(p, topic)),
+        }
+    }
+
+    /// Check if the user belongs to a group (any role).
+    pub fn is_group_member(&self, group_id: i64) -> bool {
+        self.groups.iter().any(|m| m.group_id == group_id)
+    }
+
+    /// Check if the user is an admin of a group.
+    pub fn is_group_admin(&self, group_id: i64) -> bool {
+        self.groups
+            .iter()
+            .any(|m| m.group_id == group_id && m.is_admin())
+    }
+
     /// Return a Forbidden error if scope is missing
     pub fn require_scope(&self, scope: &str) -> Result<(), crate::error::ApiError> {
         if self.has_scope(scope) {
@@ -104,6 +134,62 @@ fn extract_token(parts: &Parts) -> Option<String> {
     None
 }
 
+/// The caller's address, resolved with the rate limiter's proxy-trust policy
+/// (forwarded headers are only honored behind a trusted proxy). `None` when the
+/// peer is unknown, e.g. in tests that drive the router without connect info.
+pub struct ClientIp(pub Option<String>);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(ClientIp(request_ip(parts)))
+    }
+}
+
+fn request_ip(parts: &Parts) -> Option<String> {
+    match parts.extensions.get::<RateLimiter>() {
+        Some(limiter) => limiter.client_ip(&parts.headers, &parts.extensions),
+        None => peer_ip(&parts.extensions),
+    }
+}
+
+/// Enforce a client token's expiry and network allow-list, then record the use.
+/// Shared by every path that authenticates a `CL_` token.
+pub async fn check_client_token(
+    state: &AppState,
+    client: &Client,
+    ip: Option<&str>,
+) -> Result<(), ApiError> {
+    if client.is_expired() {
+        warn!(client_id = client.id, "Auth rejected: client token expired");
+        return Err(ApiError::from(rstify_core::error::CoreError::Unauthorized(
+            "Client token expired".to_string(),
+        )));
+    }
+    let parsed = ip.and_then(|s| s.parse::<IpAddr>().ok());
+    if !client.allows_ip(parsed) {
+        warn!(
+            client_id = client.id,
+            ip = ip.unwrap_or("unknown"),
+            "Auth rejected: client token used from a disallowed address"
+        );
+        return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
+            "Client token is not allowed from this address".to_string(),
+        )));
+    }
+    if let Err(e) = state.client_repo.touch_last_used(client.id, ip).await {
+        warn!(
+            client_id = client.id,
+            "Failed to record client token use: {}", e
+        );
+    }
+    Ok(())
+}
+
 fn unauthorized(msg: &str) -> Response {
     (StatusCode::UNAUTHORIZED, Json(json!({"error": msg}))).into_response()
 }
@@ -116,6 +202,14 @@ fn internal_error() -> Response {
         .into_response()
 }
 
+async fn load_groups(state: &AppState, user_id: i64) -> Result<Vec<GroupMember>, Response> {
+    state
+        .group_repo
+        .list_memberships_for_user(user_id)
+        .await
+        .map_err(|_| internal_error())
+}
+
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = Response;
 
@@ -144,10 +238,13 @@ impl FromRequestParts<AppState> for AuthUser {
                         warn!(path = %uri, user_id = claims.sub, "Auth rejected: JWT user not found");
                         unauthorized("User not found")
                     })?;
+                let groups = load_groups(state, user.id).await?;
                 Ok(AuthUser {
                     user,
                     claims: Some(claims),
                     client: None,
+                    groups,
+                    ip: request_ip(parts),
                 })
             }
             TokenType::ClientToken => {
@@ -160,16 +257,23 @@ impl FromRequestParts<AppState> for AuthUser {
                         warn!(path = %uri, "Auth rejected: invalid client token");
                         unauthorized("Invalid client token")
                     })?;
+                let ip = request_ip(parts);
+                check_client_token(state, &client, ip.as_deref())
+                    .await
+                    .map_err(IntoResponse::into_response)?;
                 let user = state
                     .user_repo
                     .find_by_id(client.user_id)
                     .await
                     .map_err(|_| internal_error())?
                     .ok_or_else(|| unauthorized("User not found"))?;
+                let groups = load_groups(state, user.id).await?;
                 Ok(AuthUser {
                     user,
                     claims: None,
                     client: Some(client),
+                    groups,
+                    ip,
                 })
             }
             _ => {
@@ -224,6 +328,9 @@ impl FromRequestParts<AppState> for AuthClient {
             .await
             .map_err(|_| internal_error())?
             .ok_or_else(|| unauthorized("Invalid client token"))?;
+        check_client_token(state, &client, request_ip(parts).as_deref())
+            .await
+            .map_err(IntoResponse::into_response)?;
 
         let user = state
             .user_repo
@@ -0,0 +1,200 @@
+use axum::extract::multipart::Field;
+use axum::extract::{FromRequest, Multipart, Request};
+use axum::http::header;
+use axum::Json;
+use rstify_core::error::CoreError;
+use rstify_storage::ContentHasher;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::utils::{sanitize_filename, sniff_content_type, SNIFF_LEN};
+
+/// Files accepted with a single multipart message.
+pub const MAX_FILES_PER_MESSAGE: usize = 10;
+
+/// A file part of a multipart upload, streamed to a staging file under
+/// `{upload_dir}/incoming`. The staging file is removed when this is dropped,
+/// unless the attachment store has already moved it into place.
+pub struct StagedFile {
+    pub filename: String,
+    /// Sniffed from the content; the part's own `Content-Type` is ignored.
+    pub content_type: String,
+    pub size: u64,
+    pub sha256: String,
+    path: PathBuf,
+}
+
+impl StagedFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// A message body sent either as JSON or as `multipart/form-data` with files.
+///
+/// In a multipart body, every part with a filename is an attachment. The
+/// message itself is either a `payload` part holding the same JSON as the JSON
+/// form, or plain form fields (`message`, `title`, `priority`, ...).
+pub struct MessageUpload<T> {
+    pub body: T,
+    pub files: Vec<StagedFile>,
+}
+
+impl<T> FromRequest<AppState> for MessageUpload<T>
+where
+    T: DeserializeOwned + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let is_multipart = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("multipart/form-data"));
+        if !is_multipart {
+            let Json(body) = Json::<T>::from_request(req, state)
+                .await
+                .map_err(|e| ApiError {
+                    status: e.status(),
+                    message: e.body_text(),
+                })?;
+            return Ok(Self {
+                body,
+                files: Vec::new(),
+            });
+        }
+
+        let mut multipart = Multipart::from_request(req, state)
+            .await
+            .map_err(|e| ApiError {
+                status: e.status(),
+                message: e.body_text(),
+            })?;
+
+        let mut payload = None;
+        let mut fields = Vec::new();
+        let mut files = Vec::new();
+        let mut total = 0u64;
+        while let Some(field) = multipart.next_field().await.map_err(|e| ApiError {
+            status: e.status(),
+            message: e.body_text(),
+        })? {
+            let name = field.name().unwrap_or_default().to_string();
+            if let Some(filename) = field.file_name().map(sanitize_filename) {
+                if files.len() == MAX_FILES_PER_MESSAGE {
+                    return Err(validation(format!(
+                        "At most {} files per message",
+                        MAX_FILES_PER_MESSAGE
+                    )));
+                }
+                let staged = stage_file(state, field, filename, &mut total).await?;
+                // Empty parts are what browsers send for an unused file input.
+                if staged.size > 0 {
+                    files.push(staged);
+                }
+                continue;
+            }
+            let value = field.text().await.map_err(|e| ApiError {
+                status: e.status(),
+                message: e.body_text(),
+            })?;
+            if name == "payload" {
+                payload = Some(value);
+            } else {
+                fields.push((name, value));
+            }
+        }
+
+        let body = match payload {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| validation(format!("Invalid payload JSON: {e}")))?,
+            None => {
+                let encoded = serde_urlencoded::to_string(&fields)
+                    .map_err(|e| validation(format!("Invalid form fields: {e}")))?;
+                serde_urlencoded::from_str(&encoded)
+                    .map_err(|e| validation(format!("Invalid form fields: {e}")))?
+            }
+        };
+        Ok(Self { body, files })
+    }
+}
+
+fn validation(message: String) -> ApiError {
+    ApiError::from(CoreError::Validation(message))
+}
+
+/// Stream one file part to disk, hashing it on the way and enforcing the
+/// per-file and per-message size limits before each chunk is written.
+async fn stage_file(
+    state: &AppState,
+    mut field: Field<'_>,
+    filename: String,
+    total: &mut u64,
+) -> Result<StagedFile, ApiError> {
+    let dir = Path::new(&state.upload_dir).join("incoming");
+    tokio::fs::create_dir_all(&dir).await.map_err(|e| {
+        ApiError::from(CoreError::Internal(format!(
+            "Failed to create staging dir: {e}"
+        )))
+    })?;
+
+    // The guard exists before the file does, so every early return cleans up.
+    let mut staged = StagedFile {
+        filename,
+        content_type: String::new(),
+        size: 0,
+        sha256: String::new(),
+        path: dir.join(format!("{}.part", Uuid::new_v4())),
+    };
+    let mut file = tokio::fs::File::create(&staged.path)
+        .await
+        .map_err(|e| ApiError::from(CoreError::Internal(format!("Failed to stage upload: {e}"))))?;
+
+    let mut hasher = ContentHasher::default();
+    let mut head = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(|e| ApiError {
+        status: e.status(),
+        message: e.body_text(),
+    })? {
+        staged.size += chunk.len() as u64;
+        *total += chunk.len() as u64;
+        if staged.size > state.max_upload_size as u64 {
+            return Err(validation(format!(
+                "Attachment '{}' too large (max {} bytes)",
+                staged.filename, state.max_upload_size
+            )));
+        }
+        if *total > state.max_upload_total as u64 {
+            return Err(validation(format!(
+                "Attachments too large in total (max {} bytes)",
+                state.max_upload_total
+            )));
+        }
+        if head.len() < SNIFF_LEN {
+            let take = chunk.len().min(SNIFF_LEN - head.len());
+            head.extend_from_slice(&chunk[..take]);
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(|e| {
+            ApiError::from(CoreError::Internal(format!("Failed to stage upload: {e}")))
+        })?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::from(CoreError::Internal(format!("Failed to stage upload: {e}"))))?;
+
+    staged.content_type = sniff_content_type(&head, &staged.filename);
+    staged.sha256 = hasher.finish();
+    Ok(staged)
+}
@@ -1,17 +1,30 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{debug, error, warn};
 
 use rstify_core::models::MessageResponse;
-use rstify_core::repositories::ClientRepository;
+use rstify_core::repositories::{ClientRepository, NewPushDelivery};
+
+/// Default FCM API endpoint.
+pub const DEFAULT_FCM_API_URL: &str = "https://fcm.googleapis.com";
+
+/// Sends per token, including the first one.
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled for each further one.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Upper bound on a server-requested `Retry-After`.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
 
 /// FCM configuration loaded from environment
 #[derive(Debug, Clone)]
 pub struct FcmConfig {
     pub project_id: String,
     pub service_account: ServiceAccount,
+    /// Base URL of the FCM v1 API, without a trailing slash.
+    pub api_url: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -71,6 +84,103 @@ struct AndroidNotification {
     click_action: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct FcmErrorResponse {
+    error: FcmErrorBody,
+}
+
+#[derive(Deserialize)]
+struct FcmErrorBody {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    details: Vec<FcmErrorDetail>,
+}
+
+#[derive(Deserialize)]
+struct FcmErrorDetail {
+    #[serde(rename = "errorCode")]
+    error_code: Option<String>,
+}
+
+/// A failed send, with the FCM error code when the API returned one.
+#[derive(Debug, Clone)]
+pub struct FcmError {
+    /// HTTP status, or `None` if no response was received.
+    pub status: Option<u16>,
+    /// `errorCode` from the FCM error details, falling back to the RPC status
+    /// (`UNREGISTERED`, `INVALID_ARGUMENT`, `QUOTA_EXCEEDED`, ...).
+    pub code: Option<String>,
+    pub message: String,
+    /// Server-requested delay before retrying.
+    pub retry_after: Option<Duration>,
+}
+
+impl FcmError {
+    fn transport(message: String) -> Self {
+        Self {
+            status: None,
+            code: None,
+            message,
+            retry_after: None,
+        }
+    }
+
+    /// Build from an FCM v1 error response.
+    pub fn from_response(status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        match serde_json::from_str::<FcmErrorResponse>(body) {
+            Ok(resp) => Self {
+                status: Some(status),
+                code: resp
+                    .error
+                    .details
+                    .into_iter()
+                    .find_map(|d| d.error_code)
+                    .or(resp.error.status),
+                message: resp.error.message,
+                retry_after,
+            },
+            Err(_) => Self {
+                status: Some(status),
+                code: None,
+                message: body.chars().take(200).collect(),
+                retry_after,
+            },
+        }
+    }
+
+    /// The token will never be accepted again and should be dropped.
+    /// `INVALID_ARGUMENT` also covers malformed payloads, so it only counts
+    /// when FCM says the registration token itself is at fault.
+    pub fn is_dead_token(&self) -> bool {
+        match self.code.as_deref() {
+            Some("UNREGISTERED") | Some("SENDER_ID_MISMATCH") => true,
+            Some("INVALID_ARGUMENT") => self.message.contains("registration token"),
+            _ => false,
+        }
+    }
+
+    /// Worth sending again: throttling, server errors and lost connections.
+    pub fn is_retryable(&self) -> bool {
+        match self.status {
+            None => true,
+            Some(status) => status == 429 || status >= 500,
+        }
+    }
+}
+
+impl std::fmt::Display for FcmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.code, self.status) {
+            (Some(code), _) => write!(f, "{}: {}", code, self.message),
+            (None, Some(status)) => write!(f, "HTTP {}: {}", status, self.message),
+            (None, None) => f.write_str(&self.message),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct GoogleTokenResponse {
     access_token: String,
@@ -107,8 +217,15 @@ impl FcmConfig {
         Some(FcmConfig {
             project_id,
             service_account: sa,
+            api_url: DEFAULT_FCM_API_URL.to_string(),
         })
     }
+
+    /// Send to a different FCM endpoint, e.g. a local mock in tests.
+    pub fn with_api_url(mut self, api_url: &str) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
 }
 
 impl FcmClient {
@@ -196,8 +313,8 @@ impl FcmClient {
         fcm_token: &str,
         msg: &MessageResponse,
         image_url: Option<&str>,
-    ) -> Result<(), String> {
-        let access_token = self.get_access_token().await?;
+    ) -> Result<(), FcmError> {
+        let access_token = self.get_access_token().await.map_err(FcmError::transport)?;
 
         let channel_id = if msg.priority >= 8 {
             "high"
@@ -219,6 +336,25 @@ impl FcmClient {
         if let Some(ref topic) = msg.topic {
             data.insert("topic".to_string(), topic.clone());
         }
+        // FCM data values are strings, and the payload is capped at 4 KB, so
+        // only what the app needs to show and fetch each file.
+        if let Some(ref attachments) = msg.attachments {
+            let list: Vec<_> = attachments
+                .iter()
+                .map(|a| {
+                    serde_json::json!({
+                        "name": a.name,
+                        "type": a.content_type,
+                        "size": a.size,
+                        "url": a.url,
+                    })
+                })
+                .collect();
+            data.insert(
+                "attachments".to_string(),
+                serde_json::Value::from(list).to_string(),
+            );
+        }
 
         let request = FcmRequest {
             message: FcmMessage {
@@ -243,8 +379,8 @@ impl FcmClient {
         };
 
         let url = format!(
-            "https://fcm.googleapis.com/v1/projects/{}/messages:send",
-            self.config.project_id
+            "{}/v1/projects/{}/messages:send",
+            self.config.api_url, self.config.project_id
         );
 
         let resp = self
@@ -254,7 +390,7 @@ impl FcmClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("FCM send failed: {}", e))?;
+            .map_err(|e| FcmError::transport(format!("FCM send failed: {}", e)))?;
 
         if resp.status().is_success() {
             debug!(
@@ -263,14 +399,47 @@ impl FcmClient {
             );
             Ok(())
         } else {
+            let status = resp.status().as_u16();
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
             let body = resp.text().await.unwrap_or_default();
-            Err(format!("FCM error: {}", body))
+            Err(FcmError::from_response(status, &body, retry_after))
+        }
+    }
+
+    /// Send with retries for throttling and server errors, backing off
+    /// exponentially or as long as the server asks. Returns the outcome and
+    /// the number of attempts made.
+    async fn send_with_retry(
+        &self,
+        fcm_token: &str,
+        msg: &MessageResponse,
+        image_url: Option<&str>,
+    ) -> (Result<(), FcmError>, u32) {
+        let mut attempt = 1;
+        loop {
+            match self.send_to_token(fcm_token, msg, image_url).await {
+                Err(e) if e.is_retryable() && attempt < MAX_ATTEMPTS => {
+                    let delay = e
+                        .retry_after
+                        .map(|d| d.min(MAX_RETRY_DELAY))
+                        .unwrap_or(RETRY_BASE_DELAY * 2u32.pow(attempt - 1));
+                    debug!("FCM send failed ({}), retrying in {:?}", e, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return (result, attempt),
+            }
         }
     }
 
     /// Send push notifications to all of a user's registered FCM devices.
     /// `image_url` is an optional public URL for a large notification image (e.g., app icon).
-    pub async fn notify_user<R: ClientRepository>(
+    pub async fn notify_user<R: ClientRepository + ?Sized>(
         &self,
         client_repo: &R,
         user_id: i64,
@@ -294,11 +463,97 @@ impl FcmClient {
             tokens.len(),
             user_id
         );
+        self.notify_tokens(client_repo, &tokens, msg, image_url)
+            .await;
+    }
 
-        for token in &tokens {
-            if let Err(e) = self.send_to_token(token, msg, image_url).await {
-                warn!("FCM send error: {}", e);
+    /// Send a push notification to each of the given FCM tokens, logging the
+    /// outcome per device and removing tokens FCM no longer accepts.
+    pub async fn notify_tokens<R: ClientRepository + ?Sized>(
+        &self,
+        client_repo: &R,
+        tokens: &[String],
+        msg: &MessageResponse,
+        image_url: Option<&str>,
+    ) {
+        for token in tokens {
+            let (result, attempts) = self.send_with_retry(token, msg, image_url).await;
+            let mut delivery = NewPushDelivery {
+                channel: "fcm",
+                token,
+                message_id: (msg.id > 0).then_some(msg.id),
+                outcome: "sent",
+                attempts: attempts as i32,
+                ..Default::default()
+            };
+            let error = result.as_ref().err().map(|e| e.to_string());
+            if let Err(ref e) = result {
+                delivery.outcome = if e.is_dead_token() {
+                    "pruned"
+                } else {
+                    "failed"
+                };
+                delivery.status_code = e.status.map(i32::from);
+                delivery.error_code = e.code.as_deref();
+                delivery.error = Some(&e.message);
+                warn!("FCM send error: {}", error.as_deref().unwrap_or_default());
+            }
+            if let Err(e) = client_repo.record_push_delivery(&delivery).await {
+                warn!("Failed to record push delivery: {}", e);
+            }
+            if delivery.outcome == "pruned" {
+                match client_repo.clear_fcm_token(token).await {
+                    Ok(n) => debug!("Removed rejected FCM token from {} client(s)", n),
+                    Err(e) => warn!("Failed to remove rejected FCM token: {}", e),
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(status: u16, code: &str, message: &str) -> FcmError {
+        let body = serde_json::json!({"error": {
+            "code": status,
+            "message": message,
+            "status": "INVALID_ARGUMENT",
+            "details": [{"errorCode": code}],
+        }});
+        FcmError::from_response(status, &body.to_string(), None)
+    }
+
+    #[test]
+    fn error_code_comes_from_details() {
+        let e = error(404, "UNREGISTERED", "Requested entity was not found.");
+        assert_eq!(e.code.as_deref(), Some("UNREGISTERED"));
+        assert!(e.is_dead_token());
+        assert!(!e.is_retryable());
+    }
+
+    #[test]
+    fn invalid_argument_prunes_only_for_bad_tokens() {
+        let token = error(
+            400,
+            "INVALID_ARGUMENT",
+            "The registration token is not a valid FCM registration token",
+        );
+        assert!(token.is_dead_token());
+        let payload = error(400, "INVALID_ARGUMENT", "Invalid value at 'message.data'");
+        assert!(!payload.is_dead_token());
+    }
+
+    #[test]
+    fn throttling_and_server_errors_are_retried() {
+        assert!(error(429, "QUOTA_EXCEEDED", "").is_retryable());
+        assert!(error(503, "UNAVAILABLE", "").is_retryable());
+        assert!(FcmError::transport("connection reset".into()).is_retryable());
+
+        let e = FcmError::from_response(502, "<html>Bad Gateway</html>", None);
+        assert!(e.code.is_none());
+        assert!(e.is_retryable());
+        assert_eq!(e.to_string(), "HTTP 502: <html>Bad Gateway</html>");
+    }
+}
@@ -0,0 +1,116 @@
+use rstify_core::models::{Attachment, AttachmentInfo};
+use std::path::Path;
+
+use crate::error::ApiError;
+use crate::extractors::upload::StagedFile;
+use crate::state::AppState;
+
+/// Where the content of a new attachment comes from.
+pub enum AttachmentSource<'a> {
+    Bytes(&'a [u8]),
+    /// A file staged on disk while a multipart body was streamed in.
+    Staged(&'a Path),
+}
+
+/// An upload that has been validated and hashed, ready to be stored.
+pub struct NewAttachment<'a> {
+    pub filename: &'a str,
+    pub content_type: &'a str,
+    pub size: i64,
+    pub sha256: &'a str,
+    pub source: AttachmentSource<'a>,
+}
+
+/// Record an attachment on a message and write its blob to the primary store.
+/// Content-addressed: identical uploads share one blob, keyed by `sha256`.
+pub async fn store_attachment(
+    state: &AppState,
+    message_id: i64,
+    upload: NewAttachment<'_>,
+) -> Result<Attachment, ApiError> {
+    // The row is written before the blob, both under the write guard, so that
+    // deleting another attachment with the same content concurrently either
+    // sees this reference and keeps the blob or finishes before it is written.
+    let store = state.blob_stores.primary();
+    let writes = state.blob_stores.write_guard().await;
+    let attachment = state
+        .message_repo
+        .create_attachment(
+            message_id,
+            upload.filename,
+            Some(upload.content_type),
+            upload.size,
+            store.kind(),
+            upload.sha256,
+            Some(upload.sha256),
+            None,
+        )
+        .await
+        .map_err(ApiError::from)?;
+
+    // Always write, even if the blob exists: it may be an orphan about to be swept.
+    let written = match upload.source {
+        AttachmentSource::Bytes(data) => {
+            store
+                .put(upload.sha256, data, Some(upload.content_type))
+                .await
+        }
+        AttachmentSource::Staged(path) => {
+            store
+                .put_file(upload.sha256, path, Some(upload.content_type))
+                .await
+        }
+    };
+    if let Err(e) = written {
+        if let Err(e) = state.message_repo.delete_attachment(attachment.id).await {
+            tracing::warn!("Failed to remove attachment {}: {}", attachment.id, e);
+        }
+        return Err(ApiError::from(e));
+    }
+    drop(writes);
+
+    // Previews are generated off the request path by the thumbnail worker.
+    if rstify_jobs::thumbnails::is_thumbnailable(Some(upload.content_type)) {
+        state
+            .message_repo
+            .update_attachment_thumbnail(attachment.id, "pending", None, None, None)
+            .await
+            .map_err(ApiError::from)?;
+        state.thumbnail_trigger.notify_one();
+    }
+
+    Ok(attachment)
+}
+
+/// Store the files of a multipart upload on a freshly created message. If any
+/// fails, the message is deleted again so it never advertises attachments that
+/// were not stored.
+pub async fn attach_staged_files(
+    state: &AppState,
+    message_id: i64,
+    files: Vec<StagedFile>,
+) -> Result<Vec<AttachmentInfo>, ApiError> {
+    let mut infos = Vec::with_capacity(files.len());
+    for file in &files {
+        let stored = store_attachment(
+            state,
+            message_id,
+            NewAttachment {
+                filename: &file.filename,
+                content_type: &file.content_type,
+                size: file.size as i64,
+                sha256: &file.sha256,
+                source: AttachmentSource::Staged(file.path()),
+            },
+        )
+        .await;
+        match stored {
+            Ok(attachment) => infos.push(AttachmentInfo::from_attachment(&attachment)),
+            Err(e) => {
+                let _ = state.message_repo.delete_by_id(message_id).await;
+                return Err(e);
+            }
+        }
+    }
+    Ok(infos)
+}
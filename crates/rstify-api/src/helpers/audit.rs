@@ -0,0 +1,105 @@
+use rstify_core::models::{audit_diff, audit_redact, NewAuditEntry, User};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::extractors::auth::AuthUser;
+use crate::state::AppState;
+
+/// JSON snapshot of a resource for the `before`/`after` side of an audit entry.
+pub fn snapshot<T: Serialize>(value: &T) -> Option<Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// Record an action taken by an authenticated user. When both `before` and
+/// `after` are given only the changed fields are kept; secret fields are
+/// always redacted.
+///
+/// Never fails: a write error is logged, because the audited change has
+/// already been committed and must not turn into an error response.
+pub async fn record(
+    state: &AppState,
+    auth: &AuthUser,
+    action: &str,
+    target_type: &str,
+    target_id: impl ToString,
+    before: Option<Value>,
+    after: Option<Value>,
+) {
+    write(
+        state,
+        NewAuditEntry {
+            actor_id: Some(auth.user.id),
+            actor_name: Some(auth.user.username.clone()),
+            action: action.to_string(),
+            target_type: target_type.to_string(),
+            target_id: Some(target_id.to_string()),
+            ip: auth.ip.clone(),
+            before,
+            after,
+        },
+    )
+    .await;
+}
+
+/// Record a login attempt. `user` is `None` when the username is unknown, in
+/// which case the attempted name is kept as the actor name.
+pub async fn record_login(
+    state: &AppState,
+    user: Option<&User>,
+    username: &str,
+    ip: Option<String>,
+    success: bool,
+) {
+    let action = if success {
+        "auth.login"
+    } else {
+        "auth.login_failed"
+    };
+    write(
+        state,
+        NewAuditEntry {
+            actor_id: user.map(|u| u.id),
+            actor_name: Some(user.map_or(username, |u| u.username.as_str()).to_string()),
+            action: action.to_string(),
+            target_type: "user".to_string(),
+            target_id: user.map(|u| u.id.to_string()),
+            ip,
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+/// Record an account being locked after repeated failed logins.
+pub async fn record_lockout(state: &AppState, user: &User, ip: Option<String>, until: &str) {
+    write(
+        state,
+        NewAuditEntry {
+            actor_id: Some(user.id),
+            actor_name: Some(user.username.clone()),
+            action: "auth.lockout".to_string(),
+            target_type: "user".to_string(),
+            target_id: Some(user.id.to_string()),
+            ip,
+            after: Some(serde_json::json!({ "locked_until": until })),
+            ..Default::default()
+        },
+    )
+    .await;
+}
+
+async fn write(state: &AppState, mut entry: NewAuditEntry) {
+    let before = entry.before.take().map(audit_redact);
+    let after = entry.after.take().map(audit_redact);
+    (entry.before, entry.after) = match (before, after) {
+        (Some(b), Some(a)) => {
+            let (b, a) = audit_diff(b, a);
+            (Some(b), Some(a))
+        }
+        other => other,
+    };
+    if let Err(e) = state.audit_repo.record(&entry).await {
+        warn!(action = %entry.action, "Failed to write audit log entry: {}", e);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod attachments;
+pub mod audit;
 pub mod json;
 pub mod ownership;
 pub mod publish;
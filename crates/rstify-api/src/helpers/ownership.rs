@@ -2,6 +2,7 @@ use std::future::Future;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::state::AppState;
 use rstify_core::error::CoreError;
 
 /// Generic async helper that fetches a resource by calling `fetch()`, maps DB errors
@@ -26,50 +27,76 @@ where
     }
 }
 
-/// Checks that the authenticated user owns the resource (by comparing user IDs).
-/// Admins bypass ownership checks.
+/// Checks that the authenticated user owns the resource, either directly (by
+/// comparing user IDs) or as an admin of the owning group. Admins bypass
+/// ownership checks.
 ///
 /// Returns `Forbidden` if the user does not own the resource and is not an admin.
 pub fn verify_ownership(
     auth: &AuthUser,
     resource_user_id: i64,
+    resource_group_id: Option<i64>,
     resource_name: &str,
 ) -> Result<(), ApiError> {
-    if resource_user_id != auth.user.id && !auth.user.is_admin {
-        return Err(ApiError::from(CoreError::Forbidden(format!(
-            "Not your {resource_name}"
-        ))));
-    }
-    Ok(())
+    verify_optional_ownership(
+        auth,
+        Some(resource_user_id),
+        resource_group_id,
+        resource_name,
+    )
 }
 
 /// Ownership check for resources where the owner is optional (e.g. `Topic.owner_id`).
 ///
-/// - `Some(owner_id)` delegates to [`verify_ownership`].
-/// - `None` (no owner) means only admins can modify.
+/// - The owning user, admins of the owning group, and site admins pass.
+/// - No owner and no group means only admins can modify.
 pub fn verify_optional_ownership(
     auth: &AuthUser,
     resource_owner_id: Option<i64>,
+    resource_group_id: Option<i64>,
     resource_name: &str,
 ) -> Result<(), ApiError> {
-    match resource_owner_id {
-        Some(owner_id) => verify_ownership(auth, owner_id, resource_name),
-        None => {
-            if !auth.user.is_admin {
-                return Err(ApiError::from(CoreError::Forbidden(format!(
-                    "Not your {resource_name}"
-                ))));
-            }
-            Ok(())
-        }
+    let owns = auth.user.is_admin
+        || resource_owner_id == Some(auth.user.id)
+        || resource_group_id.is_some_and(|g| auth.is_group_admin(g));
+    if !owns {
+        return Err(ApiError::from(CoreError::Forbidden(format!(
+            "Not your {resource_name}"
+        ))));
     }
+    Ok(())
+}
+
+/// Checks that the authenticated user administers a group. Admins bypass.
+pub fn verify_group_admin(auth: &AuthUser, group_id: i64) -> Result<(), ApiError> {
+    if !auth.user.is_admin && !auth.is_group_admin(group_id) {
+        return Err(ApiError::from(CoreError::Forbidden(
+            "Group admin role required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+/// Validates a requested `group_id` for a new or transferred resource: the
+/// group must exist and the caller must administer it.
+pub async fn verify_group_assignment(
+    state: &AppState,
+    auth: &AuthUser,
+    group_id: Option<i64>,
+) -> Result<Option<i64>, ApiError> {
+    let Some(group_id) = group_id else {
+        return Ok(None);
+    };
+    fetch_or_not_found("Group", || state.group_repo.find_by_id(group_id)).await?;
+    verify_group_admin(auth, group_id)?;
+    Ok(Some(group_id))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum::http::StatusCode;
-    use rstify_core::models::User;
+    use rstify_core::models::{GroupMember, User};
 
     /// Helper to build an AuthUser for testing.
     fn make_auth_user(id: i64, is_admin: bool) -> AuthUser {
@@ -82,25 +109,40 @@ mod tests {
                 is_admin,
                 created_at: "2026-01-01 00:00:00".to_string(),
                 updated_at: "2026-01-01 00:00:00".to_string(),
+                failed_logins: 0,
+                locked_until: None,
             },
             claims: None,
             client: None,
+            groups: Vec::new(),
+            ip: None,
         }
     }
 
+    fn with_group(mut auth: AuthUser, group_id: i64, role: &str) -> AuthUser {
+        auth.groups.push(GroupMember {
+            group_id,
+            user_id: auth.user.id,
+            username: auth.user.username.clone(),
+            role: role.to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+        });
+        auth
+    }
+
     // ── verify_ownership ──────────────────────────────────────────
 
     #[test]
     fn verify_ownership_owner_matches() {
         let auth = make_auth_user(42, false);
-        let result = verify_ownership(&auth, 42, "client");
+        let result = verify_ownership(&auth, 42, None, "client");
         assert!(result.is_ok());
     }
 
     #[test]
     fn verify_ownership_different_user_forbidden() {
         let auth = make_auth_user(42, false);
-        let result = verify_ownership(&auth, 99, "client");
+        let result = verify_ownership(&auth, 99, None, "client");
         let err = result.unwrap_err();
         assert_eq!(err.status, StatusCode::FORBIDDEN);
         assert_eq!(err.message, "Not your client");
@@ -109,7 +151,7 @@ mod tests {
     #[test]
     fn verify_ownership_admin_bypasses() {
         let auth = make_auth_user(42, true);
-        let result = verify_ownership(&auth, 99, "client");
+        let result = verify_ownership(&auth, 99, None, "client");
         assert!(result.is_ok());
     }
 
@@ -118,14 +160,14 @@ mod tests {
     #[test]
     fn verify_optional_ownership_some_owner_matches() {
         let auth = make_auth_user(10, false);
-        let result = verify_optional_ownership(&auth, Some(10), "topic");
+        let result = verify_optional_ownership(&auth, Some(10), None, "topic");
         assert!(result.is_ok());
     }
 
     #[test]
     fn verify_optional_ownership_some_owner_denied() {
         let auth = make_auth_user(10, false);
-        let result = verify_optional_ownership(&auth, Some(99), "topic");
+        let result = verify_optional_ownership(&auth, Some(99), None, "topic");
         let err = result.unwrap_err();
         assert_eq!(err.status, StatusCode::FORBIDDEN);
         assert_eq!(err.message, "Not your topic");
@@ -134,19 +176,40 @@ mod tests {
     #[test]
     fn verify_optional_ownership_none_admin_ok() {
         let auth = make_auth_user(10, true);
-        let result = verify_optional_ownership(&auth, None, "topic");
+        let result = verify_optional_ownership(&auth, None, None, "topic");
         assert!(result.is_ok());
     }
 
     #[test]
     fn verify_optional_ownership_none_non_admin_forbidden() {
         let auth = make_auth_user(10, false);
-        let result = verify_optional_ownership(&auth, None, "topic");
+        let result = verify_optional_ownership(&auth, None, None, "topic");
         let err = result.unwrap_err();
         assert_eq!(err.status, StatusCode::FORBIDDEN);
         assert_eq!(err.message, "Not your topic");
     }
 
+    #[test]
+    fn verify_ownership_group_admin_passes() {
+        let auth = with_group(make_auth_user(42, false), 7, "admin");
+        assert!(verify_ownership(&auth, 99, Some(7), "application").is_ok());
+    }
+
+    #[test]
+    fn verify_ownership_group_member_forbidden() {
+        let auth = with_group(make_auth_user(42, false), 7, "member");
+        let err = verify_ownership(&auth, 99, Some(7), "application").unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn verify_optional_ownership_group_only_admin_ok() {
+        let auth = with_group(make_auth_user(10, false), 3, "admin");
+        assert!(verify_optional_ownership(&auth, None, Some(3), "topic").is_ok());
+        let other = with_group(make_auth_user(10, false), 4, "admin");
+        assert!(verify_optional_ownership(&other, None, Some(3), "topic").is_err());
+    }
+
     // ── fetch_or_not_found ────────────────────────────────────────
 
     #[tokio::test]
@@ -9,15 +9,24 @@
 //! Do NOT call this for scheduled messages — their delivery happens later, from
 //! the scheduled-delivery job.
 
+use crate::extractors::auth::AuthUser;
 use crate::state::AppState;
-use rstify_core::models::{MessageResponse, Topic};
+use rstify_auth::acl::topic_matches;
+use rstify_core::models::{
+    Client, GroupMember, MessageResponse, QuietHours, Subscription, Topic, User,
+};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::warn;
 
 /// Where an immediate message should be delivered.
 pub enum DeliveryTarget<'a> {
-    /// Gotify-style application message → the owning user's stream + push.
+    /// Gotify-style application message → the owning user's stream + push
+    /// (FCM and Web Push) to their devices not in quiet hours.
     User(i64),
-    /// Topic message → topic subscribers, outgoing webhooks, and the topic
-    /// owner's push (gated by the topic's inbox + notify policy).
+    /// Topic message → topic subscribers, outgoing webhooks, push to
+    /// subscribed devices and the topic owner (see [`topic_push_tokens`]),
+    /// and escalation if the topic has a policy.
     Topic(&'a Topic),
 }
 
@@ -35,7 +44,8 @@ pub async fn deliver_message(
                 .connections
                 .broadcast_to_user(user_id, response.clone())
                 .await;
-            spawn_fcm(state, user_id, response);
+            spawn_push(state, user_id, response);
+            spawn_email(state, DeliveryTarget::User(user_id), response);
         }
         DeliveryTarget::Topic(topic) => {
             state
@@ -43,36 +53,324 @@ pub async fn deliver_message(
                 .broadcast_to_topic(&topic.name, response.clone())
                 .await;
             spawn_outgoing_webhooks(state, &topic.name, response);
-            // Push to the owner only if the message is inbox-routed and the
-            // topic's notify policy allows it.
-            if response.inbox && rstify_core::policy::should_notify(topic, response) {
-                if let Some(owner_id) = topic.owner_id {
-                    spawn_fcm(state, owner_id, response);
-                }
+            if state.fcm.is_some() || state.webpush.is_some() {
+                let state = state.clone();
+                let topic = topic.clone();
+                let response = response.clone();
+                tokio::spawn(async move { push_topic_message(&state, &topic, &response).await });
             }
+            spawn_email(state, DeliveryTarget::Topic(topic), response);
+            crate::escalation::begin(state, topic, response).await;
         }
     }
 }
 
-/// Fire push notifications to a user's registered devices (no-op if FCM is off).
-fn spawn_fcm(state: &AppState, user_id: i64, response: &MessageResponse) {
+/// Push a topic message to every device it should reach, over FCM and Web
+/// Push (each a no-op if not configured). Shared by immediate and scheduled
+/// delivery.
+pub async fn push_topic_message(state: &AppState, topic: &Topic, response: &MessageResponse) {
+    if state.fcm.is_none() && state.webpush.is_none() {
+        return;
+    }
+    let targets = topic_push_targets(state, topic, response).await;
+    let targets = hold_for_quiet_hours(state, targets, response).await;
+    push_to_clients(state, &targets, response).await;
+}
+
+/// Push a message to the given devices over FCM and Web Push (each a no-op
+/// if not configured).
+pub async fn push_to_clients(state: &AppState, targets: &[Client], response: &MessageResponse) {
+    if targets.is_empty() {
+        return;
+    }
+    // Push payloads are opened outside the app session, so attachment links
+    // must work without a token.
+    let resp = state.attachment_links.sign_message(response);
     if let Some(ref fcm) = state.fcm {
-        let fcm = fcm.clone();
-        let client_repo = state.client_repo.clone();
-        let resp = response.clone();
-        tokio::spawn(async move {
-            fcm.notify_user(&client_repo, user_id, &resp, resp.icon_url.as_deref())
-                .await;
-        });
+        let tokens = fcm_tokens(targets);
+        if !tokens.is_empty() {
+            fcm.notify_tokens(
+                state.client_repo.as_ref(),
+                &tokens,
+                &resp,
+                resp.icon_url.as_deref(),
+            )
+            .await;
+        }
+    }
+    if let Some(ref webpush) = state.webpush {
+        webpush
+            .notify_clients(state.client_repo.as_ref(), targets, &resp)
+            .await;
+    }
+}
+
+/// Drop devices whose quiet hours hold the message back, recording it for
+/// the summary they get when the window ends (see
+/// [`rstify_jobs::quiet_hours`]).
+pub async fn hold_for_quiet_hours(
+    state: &AppState,
+    clients: Vec<Client>,
+    response: &MessageResponse,
+) -> Vec<Client> {
+    let now = chrono::Utc::now();
+    let mut schedules: HashMap<i64, Vec<QuietHours>> = HashMap::new();
+    let mut targets = Vec::new();
+    for client in clients {
+        let user_schedules = match schedules.entry(client.user_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let loaded = state
+                    .client_repo
+                    .list_quiet_hours(client.user_id)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(
+                            "Failed to load quiet hours for user {}: {}",
+                            client.user_id, e
+                        );
+                        Vec::new()
+                    });
+                entry.insert(loaded)
+            }
+        };
+        let quiet = rstify_core::policy::should_suppress(
+            user_schedules,
+            Some(client.id),
+            response.priority,
+            now,
+        );
+        if !quiet {
+            targets.push(client);
+            continue;
+        }
+        if response.id > 0 {
+            if let Err(e) = state
+                .client_repo
+                .record_suppressed_push(client.id, response.id)
+                .await
+            {
+                warn!("Failed to hold push for client {}: {}", client.id, e);
+            }
+        }
+    }
+    targets
+}
+
+/// The FCM tokens a topic message goes to; see [`topic_push_targets`].
+pub async fn topic_push_tokens(
+    state: &AppState,
+    topic: &Topic,
+    response: &MessageResponse,
+) -> Vec<String> {
+    fcm_tokens(&topic_push_targets(state, topic, response).await)
+}
+
+fn fcm_tokens(clients: &[Client]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    clients
+        .iter()
+        .filter_map(|c| c.fcm_token.clone())
+        .filter(|t| seen.insert(t.clone()))
+        .collect()
+}
+
+pub(crate) fn has_push(client: &Client) -> bool {
+    client.fcm_token.is_some() || client.webpush_endpoint.is_some()
+}
+
+/// The devices a topic message is pushed to:
+///
+/// - devices subscribed to the topic (or a matching pattern) whose user may
+///   read it, each under its own subscription's notify preference; a device
+///   with several matching subscriptions uses the exact-name one if any;
+/// - the topic owner's other devices, if the message is inbox-routed and the
+///   topic's notify policy allows it.
+pub async fn topic_push_targets(
+    state: &AppState,
+    topic: &Topic,
+    response: &MessageResponse,
+) -> Vec<Client> {
+    let subscriptions = match state.client_repo.list_push_subscriptions(&topic.name).await {
+        Ok(subscriptions) => subscriptions,
+        Err(e) => {
+            warn!("Failed to load push subscriptions: {}", e);
+            Vec::new()
+        }
+    };
+    let mut by_client: BTreeMap<i64, (Subscription, Client)> = BTreeMap::new();
+    for (subscription, client) in subscriptions {
+        if !topic_matches(&subscription.topic_pattern, &topic.name) {
+            continue;
+        }
+        let exact = subscription.topic_pattern == topic.name;
+        match by_client.get(&subscription.client_id) {
+            Some((current, _)) if !exact || current.topic_pattern == topic.name => {}
+            _ => {
+                by_client.insert(subscription.client_id, (subscription, client));
+            }
+        }
+    }
+
+    // Subscribed devices are decided by their subscription alone, even when
+    // it mutes them.
+    let mut handled = HashSet::new();
+    let mut targets = Vec::new();
+    // Devices of the same user share one user and membership lookup.
+    let mut readers: HashMap<i64, Option<Reader>> = HashMap::new();
+    for (client_id, (subscription, client)) in by_client {
+        handled.insert(client_id);
+        if client.is_expired()
+            || !has_push(&client)
+            || !rstify_core::policy::should_notify_subscriber(topic, &subscription, response)
+        {
+            continue;
+        }
+        let reader = match readers.entry(client.user_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(load_reader(state, client.user_id).await),
+        };
+        let Some((user, groups)) = reader.clone() else {
+            continue;
+        };
+        if can_read_as(state, user, groups, Some(client.clone()), topic).await {
+            targets.push(client);
+        }
+    }
+
+    if response.inbox && rstify_core::policy::should_notify(topic, response) {
+        if let Some(owner_id) = topic.owner_id {
+            match state.client_repo.list_by_user(owner_id).await {
+                Ok(clients) => targets.extend(
+                    clients
+                        .into_iter()
+                        .filter(|c| !handled.contains(&c.id) && !c.is_expired() && has_push(c)),
+                ),
+                Err(e) => warn!("Failed to fetch clients for user {}: {}", owner_id, e),
+            }
+        }
+    }
+    targets
+}
+
+/// A user and their group memberships, as needed for a read check.
+type Reader = (User, Vec<GroupMember>);
+
+async fn load_reader(state: &AppState, user_id: i64) -> Option<Reader> {
+    let user = state.user_repo.find_by_id(user_id).await.ok()??;
+    let groups = state
+        .group_repo
+        .list_memberships_for_user(user.id)
+        .await
+        .ok()?;
+    Some((user, groups))
+}
+
+/// Whether a user (or one of their devices) may read the topic, under the
+/// same rules as reading it through the API with that login or device token.
+pub(crate) async fn can_read(
+    state: &AppState,
+    user_id: i64,
+    client: Option<Client>,
+    topic: &Topic,
+) -> bool {
+    let Some((user, groups)) = load_reader(state, user_id).await else {
+        return false;
+    };
+    can_read_as(state, user, groups, client, topic).await
+}
+
+async fn can_read_as(
+    state: &AppState,
+    user: User,
+    groups: Vec<GroupMember>,
+    client: Option<Client>,
+    topic: &Topic,
+) -> bool {
+    let auth = AuthUser {
+        user,
+        claims: None,
+        client,
+        groups,
+        ip: None,
+    };
+    crate::routes::topics::check_read_permission(state, &auth, topic)
+        .await
+        .is_ok()
+}
+
+/// Push an application message to the user's devices, FCM and Web Push
+/// alike, minus those in quiet hours (no-op if neither is configured).
+fn spawn_push(state: &AppState, user_id: i64, response: &MessageResponse) {
+    if state.fcm.is_none() && state.webpush.is_none() {
+        return;
+    }
+    let state = state.clone();
+    let response = response.clone();
+    tokio::spawn(async move {
+        let clients = match state.client_repo.list_by_user(user_id).await {
+            Ok(clients) => clients,
+            Err(e) => {
+                warn!("Failed to fetch clients for user {}: {}", user_id, e);
+                return;
+            }
+        };
+        let clients = clients
+            .into_iter()
+            .filter(|c| !c.is_expired() && has_push(c))
+            .collect();
+        let targets = hold_for_quiet_hours(&state, clients, &response).await;
+        push_to_clients(&state, &targets, &response).await;
+    });
+}
+
+/// The message as chat and relay webhooks see it: with a public `BASE_URL`,
+/// attachment URLs become signed links that chat services can open.
+pub fn outgoing_webhook_message(state: &AppState, response: &MessageResponse) -> MessageResponse {
+    if state.attachment_links.base_url().is_some() {
+        state.attachment_links.sign_message(response)
+    } else {
+        response.clone()
+    }
+}
+
+/// Email the message to users who opted in (no-op without SMTP).
+fn spawn_email(state: &AppState, target: DeliveryTarget<'_>, response: &MessageResponse) {
+    if state.email.is_none() {
+        return;
+    }
+    let state = state.clone();
+    let response = response.clone();
+    match target {
+        DeliveryTarget::User(user_id) => {
+            tokio::spawn(async move {
+                crate::email::notify(&state, &DeliveryTarget::User(user_id), &response).await;
+            });
+        }
+        DeliveryTarget::Topic(topic) => {
+            let topic = topic.clone();
+            tokio::spawn(async move {
+                crate::email::notify(&state, &DeliveryTarget::Topic(&topic), &response).await;
+            });
+        }
     }
 }
 
 /// Fire any outgoing webhooks bound to the topic.
 fn spawn_outgoing_webhooks(state: &AppState, topic_name: &str, response: &MessageResponse) {
-    let pool = state.pool.clone();
+    let repos = state.repositories();
     let topic_name = topic_name.to_string();
     let resp = response.clone();
+    let signed = outgoing_webhook_message(state, response);
+    let email = state.email.clone();
     tokio::spawn(async move {
-        rstify_jobs::outgoing_webhooks::fire_outgoing_webhooks(&pool, &topic_name, &resp).await;
+        rstify_jobs::outgoing_webhooks::fire_outgoing_webhooks(
+            &repos,
+            email.as_ref(),
+            &topic_name,
+            &resp,
+            &signed,
+        )
+        .await;
     });
 }
@@ -2,8 +2,10 @@ use crate::error::ApiError;
 use rstify_core::error::CoreError;
 
 pub const NOTIFY_POLICIES: &[&str] = &["always", "never", "threshold", "on_change", "digest"];
+pub const SUBSCRIPTION_NOTIFY_POLICIES: &[&str] = &["always", "never", "threshold"];
 pub const STORE_POLICIES: &[&str] = &["all", "on_change", "interval"];
 pub const INBOX_OVERRIDES: &[&str] = &["always", "never", "threshold"];
+pub const GROUP_ROLES: &[&str] = &["admin", "member"];
 
 /// Validates that a string field's length is within the given bounds (inclusive).
 /// Returns `BAD_REQUEST` if the length is outside `[min, max]`.
@@ -115,6 +117,86 @@ pub fn validate_positive(field_name: &str, value: i32) -> Result<(), ApiError> {
     Ok(())
 }
 
+/// Validates client token scopes: `read`, `write`, `admin`, `app:<id>`,
+/// `topic:read:<pattern>` and `topic:write:<pattern>`, where a pattern is a
+/// dotted topic name whose segments may be `*` or `**` wildcards.
+pub fn validate_scopes(scopes: &[String]) -> Result<(), ApiError> {
+    for scope in scopes {
+        let valid = match scope.as_str() {
+            "read" | "write" | "admin" => true,
+            s if s.starts_with("app:") => s[4..].parse::<i64>().is_ok(),
+            s if s.starts_with("topic:read:") || s.starts_with("topic:write:") => {
+                let pattern = s.split_once(':').map(|(_, r)| r).unwrap_or_default();
+                let pattern = pattern.split_once(':').map(|(_, p)| p).unwrap_or_default();
+                is_valid_topic_pattern(pattern)
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(ApiError::from(CoreError::Validation(format!(
+                "invalid scope '{scope}': expected read, write, admin, app:<id>, \
+                 topic:read:<pattern> or topic:write:<pattern>"
+            ))));
+        }
+    }
+    Ok(())
+}
+
+/// Validates a topic name or a pattern with `*` / `**` segments.
+pub fn validate_topic_pattern(field_name: &str, pattern: &str) -> Result<(), ApiError> {
+    if !is_valid_topic_pattern(pattern) {
+        return Err(ApiError::from(CoreError::Validation(format!(
+            "{field_name} must be a topic name or pattern of alphanumeric, '-' or '_' \
+             segments separated by '.', where a segment may be '*' or '**'"
+        ))));
+    }
+    Ok(())
+}
+
+fn is_valid_topic_pattern(pattern: &str) -> bool {
+    !pattern.is_empty()
+        && pattern.len() <= 128
+        && pattern.split('.').all(|seg| {
+            seg == "*"
+                || seg == "**"
+                || (!seg.is_empty()
+                    && seg
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        })
+}
+
+/// Validates a future point in time given as a duration (`30d`), RFC 3339, or
+/// `YYYY-MM-DD HH:MM:SS` (UTC), returning it in SQLite's datetime format.
+pub fn validate_future_datetime(field_name: &str, value: &str) -> Result<String, ApiError> {
+    let normalized = crate::ntfy_headers::parse_schedule(value.trim()).ok_or_else(|| {
+        ApiError::from(CoreError::Validation(format!(
+            "{field_name} must be a duration (e.g. 30d), RFC 3339 or 'YYYY-MM-DD HH:MM:SS'"
+        )))
+    })?;
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    if normalized <= now {
+        return Err(ApiError::from(CoreError::Validation(format!(
+            "{field_name} must be in the future"
+        ))));
+    }
+    Ok(normalized)
+}
+
+/// Validates a list of CIDRs or bare addresses and returns it as a JSON array.
+pub fn validate_cidrs(field_name: &str, values: &[String]) -> Result<String, ApiError> {
+    let mut nets = Vec::with_capacity(values.len());
+    for v in values {
+        let net = rstify_core::models::parse_ip_net(v).ok_or_else(|| {
+            ApiError::from(CoreError::Validation(format!(
+                "{field_name}: '{v}' is not a valid IP address or CIDR"
+            )))
+        })?;
+        nets.push(net.to_string());
+    }
+    crate::helpers::json::to_json_string(&nets)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -279,4 +361,87 @@ mod tests {
         assert_eq!(err.status, StatusCode::BAD_REQUEST);
         assert!(err.message.contains("priority"));
     }
+
+    // ---- validate_scopes ----
+
+    #[test]
+    fn validate_scopes_valid() {
+        let scopes: Vec<String> = [
+            "read",
+            "write",
+            "admin",
+            "app:5",
+            "topic:read:alerts.**",
+            "topic:write:home.sensors.*",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert!(validate_scopes(&scopes).is_ok());
+    }
+
+    #[test]
+    fn validate_scopes_invalid() {
+        for bad in [
+            "root",
+            "app:abc",
+            "topic:read:",
+            "topic:delete:x",
+            "topic:write:a..b",
+            "topic:write:bad name",
+        ] {
+            let err = validate_scopes(&[bad.to_string()]).unwrap_err();
+            assert_eq!(err.status, StatusCode::BAD_REQUEST, "{bad}");
+        }
+    }
+
+    // ---- validate_future_datetime ----
+
+    #[test]
+    fn validate_topic_pattern_accepts_names_and_wildcards() {
+        assert!(validate_topic_pattern("topic", "alerts").is_ok());
+        assert!(validate_topic_pattern("topic", "alerts.*.disk").is_ok());
+        assert!(validate_topic_pattern("topic", "alerts.**").is_ok());
+        assert!(validate_topic_pattern("topic", "").is_err());
+        assert!(validate_topic_pattern("topic", "alerts..x").is_err());
+        assert!(validate_topic_pattern("topic", "al erts").is_err());
+    }
+
+    #[test]
+    fn validate_future_datetime_accepts_durations_and_timestamps() {
+        assert!(validate_future_datetime("expires_at", "30d").is_ok());
+        assert_eq!(
+            validate_future_datetime("expires_at", "2999-01-01T00:00:00Z").unwrap(),
+            "2999-01-01 00:00:00"
+        );
+    }
+
+    #[test]
+    fn validate_future_datetime_rejects_past_and_garbage() {
+        let err = validate_future_datetime("expires_at", "2000-01-01 00:00:00").unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert!(err.message.contains("future"));
+
+        let err = validate_future_datetime("expires_at", "someday").unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+    }
+
+    // ---- validate_cidrs ----
+
+    #[test]
+    fn validate_cidrs_normalizes() {
+        let json = validate_cidrs(
+            "allowed_ips",
+            &["10.0.0.0/8".to_string(), "192.168.1.7".to_string()],
+        )
+        .unwrap();
+        assert_eq!(json, r#"["10.0.0.0/8","192.168.1.7/32"]"#);
+    }
+
+    #[test]
+    fn validate_cidrs_rejects_invalid() {
+        let err = validate_cidrs("allowed_ips", &["10.0.0.0/33".to_string()]).unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert!(err.message.contains("allowed_ips"));
+    }
 }
@@ -1,4 +1,7 @@
+pub mod attachment_links;
+pub mod email;
 pub mod error;
+pub mod escalation;
 pub mod extractors;
 pub mod fcm;
 pub mod helpers;
@@ -11,6 +14,7 @@ pub mod state;
 pub mod utils;
 pub mod web_ui;
 pub mod webhooks;
+pub mod webpush;
 pub mod websocket;
 
 use axum::http::header::HeaderValue;
@@ -21,10 +25,13 @@ use tower_http::compression::CompressionLayer;
 use tower_http::limit::RequestBodyLimitLayer;
 
 pub fn build_router(state: AppState, limiter: RateLimiter) -> Router {
-    // Global request-body cap must accommodate the largest attachment (plus a
+    // Global request-body cap must accommodate the largest upload (plus a
     // little multipart overhead); otherwise uploads are rejected here before the
-    // per-attachment size check ever runs.
-    let max_body = state.max_upload_size.saturating_add(1024 * 1024);
+    // per-attachment size checks ever run.
+    let max_body = state
+        .max_upload_size
+        .max(state.max_upload_total)
+        .saturating_add(1024 * 1024);
 
     let api_routes = routes::api_routes(state.clone());
     let gotify_routes = routes::gotify_routes(state.clone());
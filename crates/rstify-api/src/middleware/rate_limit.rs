@@ -1,5 +1,5 @@
 use axum::body::Body;
-use axum::http::{Request, StatusCode};
+use axum::http::{Extensions, HeaderMap, Request, StatusCode};
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use std::collections::HashMap;
@@ -46,38 +46,39 @@ impl RateLimiter {
 
     /// Derive the rate-limit bucket key for a request.
     fn request_key(&self, req: &Request<Body>) -> String {
+        self.client_ip(req.headers(), req.extensions())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// The client address for a request, using the same proxy-trust policy as
+    /// rate limiting. Also used by the auth extractors for client-token IP
+    /// restrictions, so both agree on who the caller is.
+    pub fn client_ip(&self, headers: &HeaderMap, extensions: &Extensions) -> Option<String> {
         if self.trust_forwarded_for {
             // Cloudflare (and similar) set the true client IP here even when
             // several proxies are chained, so prefer it over X-Forwarded-For
             // whose rightmost value would be the nearest edge, not the client.
-            if let Some(ip) = req
-                .headers()
+            if let Some(ip) = headers
                 .get("cf-connecting-ip")
                 .and_then(|v| v.to_str().ok())
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
             {
-                return ip;
+                return Some(ip);
             }
             // Otherwise the rightmost X-Forwarded-For value — set by the nearest
             // trusted proxy; leftmost values are client-supplied and spoofable.
-            if let Some(ip) = req
-                .headers()
+            if let Some(ip) = headers
                 .get("x-forwarded-for")
                 .and_then(|v| v.to_str().ok())
                 .and_then(|s| s.rsplit(',').next())
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
             {
-                return ip;
+                return Some(ip);
             }
         }
-        // The real TCP peer, present because the server is served with
-        // `into_make_service_with_connect_info`. "unknown" should be unreachable.
-        req.extensions()
-            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
-            .map(|ci| ci.0.ip().to_string())
-            .unwrap_or_else(|| "unknown".to_string())
+        peer_ip(extensions)
     }
 
     pub async fn check(&self, key: &str) -> bool {
@@ -133,6 +134,15 @@ impl RateLimiter {
     }
 }
 
+/// The real TCP peer, present because the server is served with
+/// `into_make_service_with_connect_info`. `None` only in tests that drive the
+/// router directly.
+pub fn peer_ip(extensions: &Extensions) -> Option<String> {
+    extensions
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        .map(|ci| ci.0.ip().to_string())
+}
+
 /// Axum middleware function for rate limiting.
 /// Uses the `RateLimiter` from request extensions (set via Extension layer).
 pub async fn rate_limit_middleware(req: Request<Body>, next: Next) -> Response {
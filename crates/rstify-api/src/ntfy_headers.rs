@@ -174,7 +174,9 @@ fn parse_ntfy_actions(s: &str) -> String {
     serde_json::to_string(&actions).unwrap_or_else(|_| "[]".to_string())
 }
 
-fn parse_schedule(s: &str) -> Option<String> {
+/// Parse a relative duration (`30m`, `2h30m`), RFC 3339 timestamp, or bare
+/// `YYYY-MM-DD HH:MM:SS` (UTC) into SQLite's UTC datetime format.
+pub(crate) fn parse_schedule(s: &str) -> Option<String> {
     // Try parsing as duration (e.g., "30m", "1h", "2h30m")
     if let Ok(duration) = humantime::parse_duration(s) {
         let scheduled = Utc::now() + Duration::from_std(duration).ok()?;
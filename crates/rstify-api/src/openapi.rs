@@ -23,10 +23,17 @@ use crate::state::AppState;
         // Users
         routes::users::current_user,
         routes::users::change_password,
+        routes::users::get_email_preferences,
+        routes::users::update_email_preferences,
+        routes::users::list_quiet_hours,
+        routes::users::create_quiet_hours,
+        routes::users::update_quiet_hours,
+        routes::users::delete_quiet_hours,
         routes::users::list_users,
         routes::users::create_user,
         routes::users::update_user,
         routes::users::delete_user,
+        routes::users::unlock_user,
         // Applications
         routes::applications::list_applications,
         routes::applications::create_application,
@@ -42,12 +49,20 @@ use crate::state::AppState;
         routes::clients::delete_client,
         routes::clients::register_fcm_token,
         routes::clients::remove_fcm_token,
+        routes::clients::vapid_public_key,
+        routes::clients::register_web_push,
+        routes::clients::remove_web_push,
+        routes::clients::list_subscriptions,
+        routes::clients::subscribe,
+        routes::clients::unsubscribe,
+        routes::clients::list_push_deliveries,
         // Messages
         routes::messages::create_app_message,
         routes::messages::list_messages,
         routes::messages::list_application_messages,
         routes::messages::search_messages,
         routes::messages::update_message,
+        routes::messages::acknowledge_message,
         routes::messages::delete_message,
         routes::messages::delete_all_messages,
         routes::messages::delete_batch_messages,
@@ -68,8 +83,21 @@ use crate::state::AppState;
         routes::topics::create_permission,
         routes::topics::list_permissions,
         routes::topics::delete_permission,
+        // Groups
+        routes::groups::list_groups,
+        routes::groups::create_group,
+        routes::groups::get_group,
+        routes::groups::update_group,
+        routes::groups::delete_group,
+        routes::groups::list_group_members,
+        routes::groups::add_group_member,
+        routes::groups::update_group_member,
+        routes::groups::remove_group_member,
         // Attachments
         routes::attachments::download_attachment,
+        routes::attachments::download_thumbnail,
+        routes::attachments::create_attachment_link,
+        routes::attachments::download_signed_attachment,
         routes::attachments::list_message_attachments,
         routes::attachments::delete_attachment,
         // Webhooks
@@ -89,6 +117,20 @@ use crate::state::AppState;
         // Settings
         routes::settings::list_settings,
         routes::settings::update_setting,
+        // Audit log
+        routes::audit::list_audit_log,
+        // Backups
+        routes::backups::list_backups,
+        routes::backups::create_backup,
+        routes::backups::download_backup,
+        // Email
+        routes::email::send_test_email,
+        routes::email::list_email_deliveries,
+        // Export / import
+        routes::export::admin_export,
+        routes::export::admin_import,
+        routes::export::export_own,
+        routes::export::import_own,
         // Stats
         routes::stats::get_stats,
         // ntfy-style publish
@@ -98,6 +140,10 @@ use crate::state::AppState;
         UserResponse,
         CreateUser,
         ChangePassword,
+        EmailPreferences,
+        UpdateEmailPreferences,
+        QuietHours,
+        CreateQuietHours,
         UpdateUser,
         Application,
         CreateApplication,
@@ -106,19 +152,36 @@ use crate::state::AppState;
         CreateClient,
         UpdateClient,
         RegisterFcmToken,
+        RegisterWebPush,
+        WebPushKeys,
+        VapidPublicKey,
+        Subscription,
+        CreateSubscription,
+        PushDelivery,
         Topic,
         CreateTopic,
         UpdateTopic,
+        EscalationPolicy,
+        EscalationStep,
         TopicPermission,
         CreateTopicPermission,
+        Group,
+        GroupMember,
+        CreateGroup,
+        UpdateGroup,
+        AddGroupMember,
+        UpdateGroupMember,
         MessageResponse,
         CreateAppMessage,
         CreateTopicMessage,
+        RemoteAttachment,
         UpdateMessage,
         PagedMessages,
         Paging,
         MessageAction,
         AttachmentInfo,
+        AttachmentLink,
+        CreateAttachmentLink,
         Attachment,
         WebhookConfig,
         CreateWebhookConfig,
@@ -134,6 +197,24 @@ use crate::state::AppState;
         routes::health::VersionResponse,
         routes::settings::Setting,
         routes::settings::UpdateSetting,
+        AuditEntry,
+        AuditFilter,
+        BackupInfo,
+        EmailDelivery,
+        SendTestEmail,
+        EmailTestResult,
+        ExportDocument,
+        ExportUser,
+        ExportFile,
+        ExportApplication,
+        ExportClient,
+        ExportTopic,
+        ExportPermission,
+        ExportWebhook,
+        ExportWebhookVariable,
+        ExportMessage,
+        ImportCount,
+        ImportReport,
         routes::webhooks::WebhookConfigWithHealth,
         routes::webhooks::TestWebhookPayload,
         routes::webhooks::WebhookTestResult,
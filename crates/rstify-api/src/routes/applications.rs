@@ -4,13 +4,14 @@ use axum::response::IntoResponse;
 use axum::Json;
 use rstify_auth::tokens::generate_app_token;
 use rstify_core::models::{Application, CreateApplication, UpdateApplication};
-use rstify_core::repositories::ApplicationRepository;
 use tokio::fs;
 use uuid::Uuid;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
-use crate::helpers::ownership::{fetch_or_not_found, verify_ownership};
+use crate::helpers::ownership::{
+    fetch_or_not_found, verify_group_admin, verify_group_assignment, verify_ownership,
+};
 use crate::helpers::validation::validate_length;
 use crate::state::AppState;
 use crate::utils::sanitize_filename;
@@ -29,7 +30,7 @@ pub async fn list_applications(
 ) -> Result<Json<Vec<Application>>, ApiError> {
     let apps = state
         .app_repo
-        .list_by_user(auth.user.id)
+        .list_accessible(auth.user.id)
         .await
         .map_err(ApiError::from)?;
     Ok(Json(apps))
@@ -48,12 +49,14 @@ pub async fn create_application(
 ) -> Result<Json<Application>, ApiError> {
     let name = req.name.trim();
     validate_length("Application name", name, 1, 128)?;
+    let group_id = verify_group_assignment(&state, &auth, req.group_id).await?;
 
     let token = generate_app_token();
     let app = state
         .app_repo
         .create(
             auth.user.id,
+            group_id,
             name,
             req.description.as_deref(),
             &token,
@@ -77,7 +80,30 @@ pub async fn update_application(
     Json(req): Json<UpdateApplication>,
 ) -> Result<Json<Application>, ApiError> {
     let existing = fetch_or_not_found("Application", || state.app_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "application")?;
+    verify_ownership(&auth, existing.user_id, existing.group_id, "application")?;
+
+    match req.group_id {
+        Some(Some(group_id)) => {
+            verify_group_assignment(&state, &auth, Some(group_id)).await?;
+            state
+                .app_repo
+                .set_group(id, Some(group_id))
+                .await
+                .map_err(ApiError::from)?;
+        }
+        // Taking it out of a group needs the same right as handing it over.
+        Some(None) => {
+            if let Some(current) = existing.group_id {
+                verify_group_admin(&auth, current)?;
+                state
+                    .app_repo
+                    .set_group(id, None)
+                    .await
+                    .map_err(ApiError::from)?;
+            }
+        }
+        None => {}
+    }
 
     let app = state
         .app_repo
@@ -100,7 +126,7 @@ pub async fn delete_application(
     Path(id): Path<i64>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let existing = fetch_or_not_found("Application", || state.app_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "application")?;
+    verify_ownership(&auth, existing.user_id, existing.group_id, "application")?;
 
     // Clean up icon file if present
     if let Some(ref image) = existing.image {
@@ -124,7 +150,7 @@ pub async fn upload_icon(
     mut multipart: Multipart,
 ) -> Result<Json<Application>, ApiError> {
     let existing = fetch_or_not_found("Application", || state.app_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "application")?;
+    verify_ownership(&auth, existing.user_id, existing.group_id, "application")?;
 
     let icons_dir = format!("{}/icons", state.upload_dir);
     fs::create_dir_all(&icons_dir).await.map_err(|e| {
@@ -285,7 +311,7 @@ pub async fn delete_icon(
     Path(id): Path<i64>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let existing = fetch_or_not_found("Application", || state.app_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "application")?;
+    verify_ownership(&auth, existing.user_id, existing.group_id, "application")?;
 
     if let Some(ref image) = existing.image {
         let icon_path = format!("{}/{}", state.upload_dir, image);
@@ -1,15 +1,25 @@
-use axum::extract::{Path, State};
-use axum::http::header;
-use axum::response::IntoResponse;
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::Json;
-use rstify_core::models::AttachmentInfo;
-use rstify_core::repositories::{ApplicationRepository, MessageRepository};
-use tokio::fs;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rstify_core::models::{Attachment, AttachmentInfo, AttachmentLink, CreateAttachmentLink};
+use rstify_storage::{blob_key, content_disposition, DownloadHeaders};
+use serde::Deserialize;
+use std::time::Duration;
 
+use crate::attachment_links::{DEFAULT_LINK_TTL, MAX_LINK_TTL};
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
 use crate::state::AppState;
-use crate::utils::sanitize_filename;
+use crate::utils::{
+    http_date, parse_db_timestamp, parse_http_date, parse_range, sanitize_filename, RangeRequest,
+};
+
+/// How long a presigned download URL stays valid. Short, since it is handed out
+/// only after the caller has been authorized.
+const PRESIGNED_URL_TTL: Duration = Duration::from_secs(300);
 
 /// Verify the authenticated user owns the message an attachment belongs to.
 async fn verify_attachment_ownership(
@@ -49,12 +59,12 @@ async fn verify_attachment_ownership(
     Ok(())
 }
 
-#[utoipa::path(get, path = "/api/attachments/{id}", responses((status = 200)))]
-pub async fn download_attachment(
-    State(state): State<AppState>,
-    auth: AuthUser,
-    Path(id): Path<i64>,
-) -> Result<impl IntoResponse, ApiError> {
+/// Look up an attachment and check the caller may access it.
+async fn find_authorized_attachment(
+    state: &AppState,
+    auth: &AuthUser,
+    id: i64,
+) -> Result<Attachment, ApiError> {
     let attachment = state
         .message_repo
         .find_attachment(id)
@@ -66,41 +76,284 @@ pub async fn download_attachment(
             ))
         })?;
 
-    verify_attachment_ownership(&state, &auth, attachment.message_id).await?;
+    verify_attachment_ownership(state, auth, attachment.message_id).await?;
+    Ok(attachment)
+}
+
+/// How to serve one blob of an attachment.
+struct BlobDownload<'a> {
+    key: &'a str,
+    filename: String,
+    content_type: String,
+    /// `attachment` or `inline`, for proxied responses.
+    disposition: &'static str,
+    etag: Option<String>,
+    last_modified: Option<DateTime<Utc>>,
+    /// Size of the blob, when recorded; range requests need it.
+    size: Option<u64>,
+}
+
+impl BlobDownload<'_> {
+    /// Whether the client's cached copy is current (`If-None-Match`, or
+    /// `If-Modified-Since` when no tag was sent).
+    fn not_modified(&self, request_headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = request_headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|v| v.to_str().ok())
+        {
+            return self.etag.as_ref().is_some_and(|etag| {
+                if_none_match
+                    .split(',')
+                    .any(|t| t.trim() == etag || t.trim() == "*")
+            });
+        }
+        match (
+            self.last_modified,
+            request_headers
+                .get(header::IF_MODIFIED_SINCE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_http_date),
+        ) {
+            (Some(modified), Some(since)) => modified.timestamp() <= since.timestamp(),
+            _ => false,
+        }
+    }
+
+    /// Whether a `Range` request still applies, given its `If-Range` validator.
+    /// Only a strong ETag or the exact modification date count as a match.
+    fn if_range_matches(&self, request_headers: &HeaderMap) -> bool {
+        let Some(validator) = request_headers
+            .get(header::IF_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+        else {
+            return true;
+        };
+        if validator.starts_with('"') {
+            return self.etag.as_deref() == Some(validator);
+        }
+        match (self.last_modified, parse_http_date(validator)) {
+            (Some(modified), Some(date)) => modified.timestamp() == date.timestamp(),
+            _ => false,
+        }
+    }
 
-    let data = fs::read(&attachment.storage_path).await.map_err(|e| {
+    fn validator_headers(&self, response: &mut Response) {
+        let headers = response.headers_mut();
+        if let Some(etag) = self.etag.as_ref().and_then(|e| e.parse().ok()) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Some(date) = self.last_modified.and_then(|d| http_date(d).parse().ok()) {
+            headers.insert(header::LAST_MODIFIED, date);
+        }
+    }
+}
+
+/// Answer with 304 if the client already has the blob, a redirect to a
+/// presigned URL when the store supports one, or else stream the bytes
+/// (honouring `Range` when the size is known).
+async fn serve_blob(
+    state: &AppState,
+    storage_type: &str,
+    download: BlobDownload<'_>,
+    request_headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let store = state.blob_stores.for_type(storage_type).ok_or_else(|| {
         ApiError::from(rstify_core::error::CoreError::Internal(format!(
-            "Failed to read file: {}",
-            e
+            "No '{}' attachment store configured",
+            storage_type
         )))
     })?;
 
-    let content_type = attachment
-        .content_type
-        .unwrap_or_else(|| "application/octet-stream".to_string());
+    if download.not_modified(request_headers) {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        download.validator_headers(&mut response);
+        return Ok(response);
+    }
 
     // Sanitize filename for Content-Disposition to prevent header injection
-    let safe_filename = sanitize_filename(&attachment.filename);
-
-    Ok((
-        [
-            (header::CONTENT_TYPE, content_type),
-            (
-                header::CONTENT_DISPOSITION,
-                format!("attachment; filename=\"{}\"", safe_filename),
-            ),
-        ],
-        data,
-    ))
+    let safe_filename = sanitize_filename(&download.filename);
+
+    if state.presigned_downloads {
+        let headers = DownloadHeaders {
+            filename: &safe_filename,
+            content_type: &download.content_type,
+        };
+        if let Some(url) = store.presigned_get(download.key, headers, PRESIGNED_URL_TTL) {
+            return Ok(Redirect::temporary(&url).into_response());
+        }
+    }
+
+    let range = match (download.size, request_headers.get(header::RANGE)) {
+        (Some(size), Some(value)) if download.if_range_matches(request_headers) => {
+            match value.to_str().map(|v| parse_range(v, size)) {
+                Ok(RangeRequest::Partial(range)) => Some(range),
+                Ok(RangeRequest::Unsatisfiable) => {
+                    let mut response = (
+                        StatusCode::RANGE_NOT_SATISFIABLE,
+                        [(header::CONTENT_RANGE, format!("bytes */{}", size))],
+                    )
+                        .into_response();
+                    download.validator_headers(&mut response);
+                    return Ok(response);
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    let stream = store
+        .open(download.key, range)
+        .await
+        .map_err(ApiError::from)?;
+
+    let mut response = Body::from_stream(stream).into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&download.content_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    if let Ok(value) =
+        HeaderValue::from_str(&content_disposition(download.disposition, &safe_filename))
+    {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    if let Some(size) = download.size {
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        let length = range.map_or(size, |r| r.length());
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    if let (Some(range), Some(size)) = (range, download.size) {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        response.headers_mut().insert(
+            header::CONTENT_RANGE,
+            HeaderValue::from_str(&format!("bytes {}-{}/{}", range.start, range.end, size))
+                .expect("numeric header value"),
+        );
+    }
+    download.validator_headers(&mut response);
+    Ok(response)
 }
 
-/// DELETE /api/attachments/{id} - Delete an attachment
-#[utoipa::path(delete, path = "/api/attachments/{id}", responses((status = 204)))]
-pub async fn delete_attachment(
+/// GET /api/attachments/{id} - Download an attachment, or redirect to a
+/// presigned URL when the attachment store supports one
+#[utoipa::path(
+    get,
+    path = "/api/attachments/{id}",
+    responses(
+        (status = 200),
+        (status = 206, description = "The part of the file asked for with `Range`"),
+        (status = 304, description = "Content matches the `If-None-Match` checksum"),
+        (status = 307, description = "Redirect to a presigned store URL"),
+        (status = 416, description = "`Range` starts beyond the end of the file")
+    )
+)]
+pub async fn download_attachment(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(id): Path<i64>,
-) -> Result<impl IntoResponse, ApiError> {
+    request_headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let attachment = find_authorized_attachment(&state, &auth, id).await?;
+    serve_attachment(&state, &attachment, &request_headers).await
+}
+
+async fn serve_attachment(
+    state: &AppState,
+    attachment: &Attachment,
+    request_headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let download = BlobDownload {
+        key: blob_key(&attachment.storage_path),
+        filename: attachment.filename.clone(),
+        content_type: attachment
+            .content_type
+            .clone()
+            .unwrap_or_else(|| "application/octet-stream".to_string()),
+        disposition: "attachment",
+        // Attachment content never changes, so its checksum is a strong ETag.
+        etag: attachment.sha256.as_ref().map(|sum| format!("\"{}\"", sum)),
+        last_modified: parse_db_timestamp(&attachment.created_at),
+        size: u64::try_from(attachment.size_bytes).ok(),
+    };
+    serve_blob(state, &attachment.storage_type, download, request_headers).await
+}
+
+/// POST /api/attachments/{id}/link - Mint a signed link that downloads the
+/// attachment without an auth token until it expires
+#[utoipa::path(
+    post,
+    path = "/api/attachments/{id}/link",
+    request_body = CreateAttachmentLink,
+    responses((status = 200, body = AttachmentLink))
+)]
+pub async fn create_attachment_link(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    req: Option<Json<CreateAttachmentLink>>,
+) -> Result<Json<AttachmentLink>, ApiError> {
+    let req = req.map(|r| r.0).unwrap_or_default();
+    let attachment = find_authorized_attachment(&state, &auth, id).await?;
+
+    let ttl = match req.expires_in {
+        None => DEFAULT_LINK_TTL,
+        Some(secs) if secs > 0 && secs <= MAX_LINK_TTL.num_seconds() => {
+            chrono::Duration::seconds(secs)
+        }
+        Some(_) => {
+            return Err(ApiError::from(rstify_core::error::CoreError::Validation(
+                format!(
+                    "expires_in must be between 1 and {} seconds",
+                    MAX_LINK_TTL.num_seconds()
+                ),
+            )))
+        }
+    };
+    let expires_at = Utc::now() + ttl;
+    Ok(Json(AttachmentLink {
+        url: state.attachment_links.url(attachment.id, expires_at),
+        expires_at: expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignedLinkParams {
+    pub expires: i64,
+    pub sig: String,
+}
+
+/// GET /api/attachments/{id}/public - Download an attachment through a signed
+/// link; no auth token needed
+#[utoipa::path(
+    get,
+    path = "/api/attachments/{id}/public",
+    params(
+        ("expires" = i64, Query, description = "Unix time the link expires at"),
+        ("sig" = String, Query, description = "Link signature")
+    ),
+    responses(
+        (status = 200),
+        (status = 206, description = "The part of the file asked for with `Range`"),
+        (status = 403, description = "Invalid or expired link")
+    )
+)]
+pub async fn download_signed_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<SignedLinkParams>,
+    request_headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if !state
+        .attachment_links
+        .verify(id, params.expires, &params.sig, Utc::now())
+    {
+        return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
+            "Invalid or expired link".to_string(),
+        )));
+    }
     let attachment = state
         .message_repo
         .find_attachment(id)
@@ -111,11 +364,65 @@ pub async fn delete_attachment(
                 "Attachment not found".to_string(),
             ))
         })?;
+    serve_attachment(&state, &attachment, &request_headers).await
+}
 
-    verify_attachment_ownership(&state, &auth, attachment.message_id).await?;
+/// GET /api/attachments/{id}/thumbnail - Downscaled preview of an image
+/// attachment (JPEG, or WebP for images with transparency)
+#[utoipa::path(
+    get,
+    path = "/api/attachments/{id}/thumbnail",
+    responses(
+        (status = 200),
+        (status = 304, description = "Preview matches the `If-None-Match` tag"),
+        (status = 307, description = "Redirect to a presigned store URL"),
+        (status = 404, description = "Not an image, or the preview isn't ready yet")
+    )
+)]
+pub async fn download_thumbnail(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    request_headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let attachment = find_authorized_attachment(&state, &auth, id).await?;
 
-    // Delete file from disk (ignore errors if file already gone)
-    let _ = fs::remove_file(&attachment.storage_path).await;
+    let key = match (&attachment.thumbnail_status, &attachment.thumbnail_path) {
+        (Some(status), Some(key)) if status == "ready" => key,
+        _ => {
+            return Err(ApiError::from(rstify_core::error::CoreError::NotFound(
+                "No thumbnail for this attachment".to_string(),
+            )))
+        }
+    };
+    let content_type = mime_guess::from_path(key)
+        .first()
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| "image/jpeg".to_string());
+
+    let download = BlobDownload {
+        key,
+        filename: format!("thumbnail-{}", attachment.filename),
+        content_type,
+        disposition: "inline",
+        etag: attachment
+            .sha256
+            .as_ref()
+            .map(|sum| format!("\"{}-thumb\"", sum)),
+        last_modified: None,
+        size: None,
+    };
+    serve_blob(&state, &attachment.storage_type, download, &request_headers).await
+}
+
+/// DELETE /api/attachments/{id} - Delete an attachment
+#[utoipa::path(delete, path = "/api/attachments/{id}", responses((status = 204)))]
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, ApiError> {
+    let attachment = find_authorized_attachment(&state, &auth, id).await?;
 
     state
         .message_repo
@@ -123,6 +430,16 @@ pub async fn delete_attachment(
         .await
         .map_err(ApiError::from)?;
 
+    // Delete the blob unless other attachments share it (the orphan sweep
+    // covers failures)
+    if let Err(e) = state
+        .blob_stores
+        .release(state.message_repo.as_ref(), &attachment)
+        .await
+    {
+        tracing::warn!("Failed to delete attachment blob {}: {}", attachment.id, e);
+    }
+
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
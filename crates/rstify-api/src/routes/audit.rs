@@ -0,0 +1,38 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use rstify_core::models::{AuditEntry, AuditFilter};
+
+use crate::error::ApiError;
+use crate::extractors::auth::AuthUser;
+use crate::state::AppState;
+
+/// GET /api/audit - Audit log, newest first (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    params(
+        ("actor_id" = Option<i64>, Query, description = "Acting user id"),
+        ("action" = Option<String>, Query, description = "Exact action, or a prefix ending in '.'"),
+        ("target_type" = Option<String>, Query, description = "Target type, e.g. user"),
+        ("target_id" = Option<String>, Query, description = "Target id"),
+        ("since" = Option<String>, Query, description = "Inclusive lower bound on created_at"),
+        ("until" = Option<String>, Query, description = "Inclusive upper bound on created_at"),
+        ("before_id" = Option<i64>, Query, description = "Only entries older than this id"),
+        ("limit" = Option<i64>, Query, description = "Max entries (default 100, max 500)"),
+    ),
+    responses((status = 200, body = Vec<AuditEntry>))
+)]
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(filter): Query<AuditFilter>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    auth.require_admin()?;
+    let limit = filter.limit.unwrap_or(100).clamp(1, 500);
+    let entries = state
+        .audit_repo
+        .list(&filter, limit)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(entries))
+}
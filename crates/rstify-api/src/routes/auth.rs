@@ -1,27 +1,95 @@
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::Json;
-use rstify_auth::password::{hash_password, verify_password};
+use rstify_auth::password::{hash_password, needs_rehash, verify_password};
 use rstify_auth::tokens::create_jwt;
-use rstify_core::repositories::UserRepository;
+use rstify_core::models::User;
 use serde::{Deserialize, Serialize};
-use std::sync::OnceLock;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use tokio::sync::OnceCell;
 use tracing::{info, warn};
 use ts_rs::TS;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
-use crate::middleware::rate_limit::RateLimiter;
+use crate::extractors::auth::ClientIp;
+use crate::helpers::audit;
 use crate::state::AppState;
 
-/// Per-username login throttle: ~5 rapid failures, then one attempt per 5s.
-/// Only failed attempts consume budget, so it never penalizes legitimate logins.
-/// Keyed on username so it survives the global per-IP limiter being fronted by a
-/// proxy, and works independently of it.
-fn login_throttle() -> &'static RateLimiter {
-    static THROTTLE: OnceLock<RateLimiter> = OnceLock::new();
-    THROTTLE.get_or_init(|| RateLimiter::new(5, 0.2))
+/// Consecutive failures allowed before each further one forces a wait.
+const FREE_LOGIN_FAILURES: i64 = 3;
+/// Consecutive failures that lock the account outright.
+const LOCKOUT_THRESHOLD: i64 = 10;
+const LOCKOUT_SECS: i64 = 15 * 60;
+/// Unknown usernames tracked at once; past this, entries whose wait is over
+/// are dropped.
+const MAX_UNKNOWN_LOGINS: usize = 10_000;
+
+/// How long an account must wait after its `failures`-th consecutive failed
+/// login: nothing at first, then doubling from 1s up to 64s, then a lockout.
+fn failure_delay_secs(failures: i64) -> Option<i64> {
+    if failures < FREE_LOGIN_FAILURES {
+        None
+    } else if failures < LOCKOUT_THRESHOLD {
+        Some(1 << (failures - FREE_LOGIN_FAILURES))
+    } else {
+        Some(LOCKOUT_SECS)
+    }
+}
+
+/// The `locked_until` timestamp for the `failures`-th failure at `now`, if
+/// any. Timestamps have whole-second resolution, so one extra second makes
+/// the wait last at least the full delay.
+fn lock_deadline(failures: i64, now: chrono::DateTime<chrono::Utc>) -> Option<String> {
+    failure_delay_secs(failures).map(|delay| {
+        (now + chrono::Duration::seconds(delay + 1))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    })
+}
+
+fn timestamp(now: chrono::DateTime<chrono::Utc>) -> String {
+    now.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Failure count and `locked_until`, as stored on a real account.
+type LoginFailures = (i64, Option<String>);
+
+/// Failure count and `locked_until` for usernames that don't exist. They run
+/// through the same curve as the persistent per-account counter, so the
+/// attempt that first gets a 429 doesn't reveal which usernames exist.
+fn unknown_logins() -> &'static Mutex<HashMap<String, LoginFailures>> {
+    static UNKNOWN: OnceLock<Mutex<HashMap<String, LoginFailures>>> = OnceLock::new();
+    UNKNOWN.get_or_init(Default::default)
+}
+
+fn unknown_login_locked(username: &str, now: &str) -> bool {
+    let logins = unknown_logins().lock().unwrap_or_else(|e| e.into_inner());
+    logins
+        .get(username)
+        .and_then(|(_, until)| until.as_deref())
+        .is_some_and(|t| t > now)
+}
+
+fn record_unknown_failure(username: &str, now: chrono::DateTime<chrono::Utc>) {
+    let mut logins = unknown_logins().lock().unwrap_or_else(|e| e.into_inner());
+    if logins.len() >= MAX_UNKNOWN_LOGINS && !logins.contains_key(username) {
+        let now = timestamp(now);
+        logins.retain(|_, (_, until)| until.as_deref().is_some_and(|t| t > now.as_str()));
+    }
+    let entry = logins.entry(username.to_string()).or_default();
+    entry.0 += 1;
+    if let Some(until) = lock_deadline(entry.0, now) {
+        entry.1 = Some(until);
+    }
+}
+
+fn too_many_attempts() -> ApiError {
+    ApiError {
+        status: StatusCode::TOO_MANY_REQUESTS,
+        message: "Too many login attempts — please wait and try again".to_string(),
+    }
 }
 
 /// A valid Argon2 hash verified against on the unknown-user path so login timing
@@ -59,18 +127,9 @@ pub struct LoginResponse {
 )]
 pub async fn login(
     State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>, ApiError> {
-    // Throttle brute force per-username (only failures below consume budget).
-    let username_key = req.username.to_lowercase();
-    if !login_throttle().peek(&username_key).await {
-        warn!(username = %req.username, "Login throttled: too many failed attempts");
-        return Err(ApiError {
-            status: StatusCode::TOO_MANY_REQUESTS,
-            message: "Too many login attempts — please wait and try again".to_string(),
-        });
-    }
-
     let user = match state
         .user_repo
         .find_by_username(&req.username)
@@ -79,17 +138,27 @@ pub async fn login(
     {
         Some(u) => u,
         None => {
+            if unknown_login_locked(&req.username, &timestamp(chrono::Utc::now())) {
+                warn!(username = %req.username, "Login throttled: too many failed attempts");
+                return Err(too_many_attempts());
+            }
             // Equalize timing with the wrong-password path (Argon2 verify).
             let _ = verify_password(req.password.clone(), dummy_hash().await).await;
-            login_throttle().penalize(&username_key).await;
+            record_unknown_failure(&req.username, chrono::Utc::now());
             warn!(username = %req.username, "Login failed: unknown username");
+            audit::record_login(&state, None, &req.username, ip, false).await;
             return Err(ApiError::from(rstify_core::error::CoreError::Unauthorized(
                 "Invalid credentials".to_string(),
             )));
         }
     };
 
-    let valid = verify_password(req.password, user.password_hash.clone())
+    if user.is_locked(&timestamp(chrono::Utc::now())) {
+        warn!(username = %req.username, "Login refused: account locked");
+        return Err(too_many_attempts());
+    }
+
+    let valid = verify_password(req.password.clone(), user.password_hash.clone())
         .await
         .map_err(|_| {
             ApiError::from(rstify_core::error::CoreError::Internal(
@@ -98,13 +167,34 @@ pub async fn login(
         })?;
 
     if !valid {
-        login_throttle().penalize(&username_key).await;
         warn!(username = %req.username, "Login failed: wrong password");
+        audit::record_login(&state, Some(&user), &req.username, ip.clone(), false).await;
+        record_failure(&state, &user, ip, chrono::Utc::now()).await?;
         return Err(ApiError::from(rstify_core::error::CoreError::Unauthorized(
             "Invalid credentials".to_string(),
         )));
     }
 
+    if user.failed_logins > 0 || user.locked_until.is_some() {
+        state
+            .user_repo
+            .clear_login_failures(user.id)
+            .await
+            .map_err(ApiError::from)?;
+    }
+
+    // Upgrade imported (bcrypt) hashes now that we have the password.
+    if needs_rehash(&user.password_hash) {
+        match hash_password(req.password).await {
+            Ok(hash) => {
+                if let Err(e) = state.user_repo.update_password(user.id, &hash).await {
+                    warn!(user_id = user.id, "Failed to upgrade password hash: {}", e);
+                }
+            }
+            Err(e) => warn!(user_id = user.id, "Failed to upgrade password hash: {}", e),
+        }
+    }
+
     let token =
         create_jwt(user.id, &user.username, user.is_admin, &state.jwt_secret).map_err(|e| {
             ApiError::from(rstify_core::error::CoreError::Internal(format!(
@@ -114,5 +204,57 @@ pub async fn login(
         })?;
 
     info!(username = %req.username, user_id = user.id, "Login successful");
+    audit::record_login(&state, Some(&user), &req.username, ip, true).await;
     Ok(Json(LoginResponse { token }))
 }
+
+/// Count a failed login against the account and apply the resulting delay.
+/// Reaching the lockout threshold notifies the user and is audited.
+async fn record_failure(
+    state: &AppState,
+    user: &User,
+    ip: Option<String>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), ApiError> {
+    let failures = state
+        .user_repo
+        .record_login_failure(user.id)
+        .await
+        .map_err(ApiError::from)?;
+    let Some(until) = lock_deadline(failures, now) else {
+        return Ok(());
+    };
+    state
+        .user_repo
+        .lock_until(user.id, &until)
+        .await
+        .map_err(ApiError::from)?;
+
+    if failures == LOCKOUT_THRESHOLD {
+        warn!(username = %user.username, "Account locked after {} failed logins", failures);
+        audit::record_lockout(state, user, ip.clone(), &until).await;
+        let message = format!(
+            "Your account was locked for {} minutes after {} failed login attempts{}. \
+             If this wasn't you, consider changing your password.",
+            LOCKOUT_SECS / 60,
+            failures,
+            ip.map(|ip| format!(" (last from {ip})"))
+                .unwrap_or_default(),
+        );
+        if let Err(e) = rstify_jobs::notify::notify_user(
+            &state.repositories(),
+            user.id,
+            "Account locked",
+            &message,
+            8,
+        )
+        .await
+        {
+            warn!(
+                user_id = user.id,
+                "Failed to send lockout notification: {}", e
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use rstify_core::error::CoreError;
+use rstify_core::models::BackupInfo;
+use rstify_jobs::backup;
+use rstify_storage::{content_disposition, BlobStore, LocalBlobStore};
+
+use crate::error::ApiError;
+use crate::extractors::auth::AuthUser;
+use crate::helpers::audit;
+use crate::state::AppState;
+
+/// GET /api/backups - Backup archives, newest first (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/backups",
+    responses((status = 200, body = Vec<BackupInfo>))
+)]
+pub async fn list_backups(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<BackupInfo>>, ApiError> {
+    auth.require_admin()?;
+    let backups = backup::list_backups(std::path::Path::new(&state.backup_dir))
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(backups))
+}
+
+/// POST /api/backups - Back up the database and uploads while running (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/backups",
+    responses(
+        (status = 200, body = BackupInfo),
+        (status = 400, description = "The database is not SQLite"),
+    )
+)]
+pub async fn create_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<BackupInfo>, ApiError> {
+    auth.require_admin()?;
+    let info = backup::create_backup(
+        &state.db,
+        std::path::Path::new(&state.upload_dir),
+        std::path::Path::new(&state.backup_dir),
+    )
+    .await
+    .map_err(ApiError::from)?;
+
+    audit::record(
+        &state,
+        &auth,
+        "backup.create",
+        "backup",
+        &info.name,
+        None,
+        Some(serde_json::json!({ "size_bytes": info.size_bytes })),
+    )
+    .await;
+
+    Ok(Json(info))
+}
+
+/// GET /api/backups/{name} - Download a backup archive (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/backups/{name}",
+    params(("name" = String, Path, description = "Archive name")),
+    responses(
+        (status = 200, description = "The gzipped tarball", content_type = "application/gzip"),
+        (status = 404, description = "No such backup"),
+    )
+)]
+pub async fn download_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(name): Path<String>,
+) -> Result<Response, ApiError> {
+    auth.require_admin()?;
+    // Only names the backup job produces, so the path stays in the directory.
+    if !backup::is_backup_name(&name) {
+        return Err(ApiError::from(CoreError::NotFound(
+            "Backup not found".into(),
+        )));
+    }
+    let size = tokio::fs::metadata(std::path::Path::new(&state.backup_dir).join(&name))
+        .await
+        .map_err(|_| ApiError::from(CoreError::NotFound("Backup not found".into())))?
+        .len();
+    let stream = LocalBlobStore::new(&state.backup_dir)
+        .open(&name, None)
+        .await
+        .map_err(ApiError::from)?;
+
+    let mut response = Body::from_stream(stream).into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/gzip"),
+    );
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(size));
+    if let Ok(value) = HeaderValue::from_str(&content_disposition("attachment", &name)) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
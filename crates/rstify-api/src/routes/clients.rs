@@ -1,12 +1,21 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::Json;
 use rstify_auth::tokens::generate_client_token;
-use rstify_core::models::{Client, CreateClient, RegisterFcmToken, UpdateClient};
-use rstify_core::repositories::ClientRepository;
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    Client, CreateClient, CreateSubscription, PushDelivery, RegisterFcmToken, RegisterWebPush,
+    Subscription, UpdateClient, VapidPublicKey,
+};
+use serde::Deserialize;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::helpers::audit::{self, snapshot};
 use crate::helpers::ownership::{fetch_or_not_found, verify_ownership};
+use crate::helpers::validation::{
+    validate_cidrs, validate_future_datetime, validate_policy, validate_scopes,
+    validate_topic_pattern, SUBSCRIPTION_NOTIFY_POLICIES,
+};
 use crate::state::AppState;
 
 #[utoipa::path(get, path = "/client", responses((status = 200, body = Vec<Client>)))]
@@ -37,12 +46,40 @@ pub async fn create_client(
     let scopes = req
         .scopes
         .unwrap_or_else(|| vec!["read".into(), "write".into()]);
+    validate_scopes(&scopes)?;
     let scopes_json = crate::helpers::json::to_json_string(&scopes)?;
+    let expires_at = req
+        .expires_at
+        .as_deref()
+        .map(|v| validate_future_datetime("expires_at", v))
+        .transpose()?;
+    let allowed_ips = req
+        .allowed_ips
+        .as_deref()
+        .map(|v| validate_cidrs("allowed_ips", v))
+        .transpose()?;
     let client = state
         .client_repo
-        .create(auth.user.id, &req.name, &token, &scopes_json)
+        .create(
+            auth.user.id,
+            &req.name,
+            &token,
+            &scopes_json,
+            expires_at.as_deref(),
+            allowed_ips.as_deref(),
+        )
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "client.create",
+        "client",
+        client.id,
+        None,
+        snapshot(&client),
+    )
+    .await;
     Ok(Json(client))
 }
 
@@ -59,17 +96,47 @@ pub async fn update_client(
     Json(req): Json<UpdateClient>,
 ) -> Result<Json<Client>, ApiError> {
     let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "client")?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
 
+    if let Some(ref scopes) = req.scopes {
+        validate_scopes(scopes)?;
+    }
     let scopes_json = req
         .scopes
         .map(|s| crate::helpers::json::to_json_string(&s))
         .transpose()?;
+    // Empty values clear the restriction; None keeps the current one.
+    let expires_at = match req.expires_at.as_deref() {
+        None => None,
+        Some("") => Some(None),
+        Some(v) => Some(Some(validate_future_datetime("expires_at", v)?)),
+    };
+    let allowed_ips = match req.allowed_ips.as_deref() {
+        None => None,
+        Some([]) => Some(None),
+        Some(v) => Some(Some(validate_cidrs("allowed_ips", v)?)),
+    };
     let client = state
         .client_repo
-        .update(id, req.name.as_deref(), scopes_json.as_deref())
+        .update(
+            id,
+            req.name.as_deref(),
+            scopes_json.as_deref(),
+            expires_at.as_ref().map(|e| e.as_deref()),
+            allowed_ips.as_ref().map(|a| a.as_deref()),
+        )
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "client.update",
+        "client",
+        id,
+        snapshot(&existing),
+        snapshot(&client),
+    )
+    .await;
     Ok(Json(client))
 }
 
@@ -79,10 +146,20 @@ pub async fn delete_client(
     auth: AuthUser,
     Path(id): Path<i64>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
-    let _existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, _existing.user_id, "client")?;
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
 
     state.client_repo.delete(id).await.map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "client.delete",
+        "client",
+        id,
+        snapshot(&existing),
+        None,
+    )
+    .await;
     Ok(Json(serde_json::json!({"success": true})))
 }
 
@@ -100,7 +177,7 @@ pub async fn register_fcm_token(
     Json(req): Json<RegisterFcmToken>,
 ) -> Result<Json<Client>, ApiError> {
     let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "client")?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
 
     let client = state
         .client_repo
@@ -118,7 +195,7 @@ pub async fn remove_fcm_token(
     Path(id): Path<i64>,
 ) -> Result<Json<Client>, ApiError> {
     let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
-    verify_ownership(&auth, existing.user_id, "client")?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
 
     let client = state
         .client_repo
@@ -127,3 +204,216 @@ pub async fn remove_fcm_token(
         .map_err(ApiError::from)?;
     Ok(Json(client))
 }
+
+/// GET /api/web-push/vapid-key - The server's VAPID public key for browsers
+#[utoipa::path(
+    get,
+    path = "/api/web-push/vapid-key",
+    responses(
+        (status = 200, body = VapidPublicKey),
+        (status = 404, description = "Web Push is disabled"),
+    )
+)]
+pub async fn vapid_public_key(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+) -> Result<Json<VapidPublicKey>, ApiError> {
+    let webpush = state
+        .webpush
+        .as_ref()
+        .ok_or_else(|| ApiError::from(CoreError::NotFound("Web Push is disabled".into())))?;
+    Ok(Json(VapidPublicKey {
+        public_key: webpush.public_key(),
+    }))
+}
+
+/// PUT /client/{id}/web-push - Register a browser's Web Push subscription
+#[utoipa::path(
+    put,
+    path = "/client/{id}/web-push",
+    request_body = RegisterWebPush,
+    responses((status = 200, body = Client))
+)]
+pub async fn register_web_push(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<RegisterWebPush>,
+) -> Result<Json<Client>, ApiError> {
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
+
+    crate::webpush::validate_subscription(&req.endpoint, &req.keys.p256dh, &req.keys.auth)
+        .map_err(|e| ApiError::from(CoreError::Validation(e)))?;
+    let client = state
+        .client_repo
+        .update_web_push(id, Some((&req.endpoint, &req.keys.p256dh, &req.keys.auth)))
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(client))
+}
+
+/// DELETE /client/{id}/web-push - Remove a client's Web Push subscription
+#[utoipa::path(delete, path = "/client/{id}/web-push", responses((status = 200, body = Client)))]
+pub async fn remove_web_push(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Client>, ApiError> {
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
+
+    let client = state
+        .client_repo
+        .update_web_push(id, None)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(client))
+}
+
+/// GET /client/{id}/subscriptions - List the topics a client gets pushes for
+#[utoipa::path(
+    get,
+    path = "/client/{id}/subscriptions",
+    responses((status = 200, body = Vec<Subscription>))
+)]
+pub async fn list_subscriptions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Subscription>>, ApiError> {
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
+
+    let subscriptions = state
+        .client_repo
+        .list_subscriptions(id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(subscriptions))
+}
+
+/// POST /client/{id}/subscriptions - Subscribe a client to a topic or pattern.
+/// Subscribing again to the same pattern replaces its notify preference.
+#[utoipa::path(
+    post,
+    path = "/client/{id}/subscriptions",
+    request_body = CreateSubscription,
+    responses(
+        (status = 200, body = Subscription),
+        (status = 403, description = "Topic not readable"),
+    )
+)]
+pub async fn subscribe(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<CreateSubscription>,
+) -> Result<Json<Subscription>, ApiError> {
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
+
+    validate_topic_pattern("topic", &req.topic)?;
+    if let Some(ref policy) = req.notify_policy {
+        validate_policy("notify_policy", policy, SUBSCRIPTION_NOTIFY_POLICIES)?;
+    }
+    // Patterns may cover topics created later, so readability is checked per
+    // message at push time; a plain name can be checked up front.
+    if let Some(topic) = state
+        .topic_repo
+        .find_by_name(&req.topic)
+        .await
+        .map_err(ApiError::from)?
+    {
+        crate::routes::topics::check_read_permission(&state, &auth, &topic).await?;
+    }
+
+    let subscription = state
+        .client_repo
+        .upsert_subscription(
+            id,
+            &req.topic,
+            req.notify_policy.as_deref(),
+            req.notify_priority_min,
+        )
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "client.subscribe",
+        "client",
+        id,
+        None,
+        snapshot(&subscription),
+    )
+    .await;
+    Ok(Json(subscription))
+}
+
+/// DELETE /client/{id}/subscriptions/{subscription_id} - Unsubscribe a client
+#[utoipa::path(
+    delete,
+    path = "/client/{id}/subscriptions/{subscription_id}",
+    responses((status = 200))
+)]
+pub async fn unsubscribe(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, subscription_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
+
+    state
+        .client_repo
+        .delete_subscription(id, subscription_id)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "client.unsubscribe",
+        "client",
+        id,
+        Some(serde_json::json!({ "subscription_id": subscription_id })),
+        None,
+    )
+    .await;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+#[derive(Deserialize)]
+pub struct PushDeliveryParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /client/{id}/push-deliveries - List recent push attempts to a client
+#[utoipa::path(
+    get,
+    path = "/client/{id}/push-deliveries",
+    params(
+        ("limit" = Option<i64>, Query, description = "Max entries (default 20, max 100)"),
+        ("offset" = Option<i64>, Query, description = "Entries to skip"),
+    ),
+    responses((status = 200, body = Vec<PushDelivery>))
+)]
+pub async fn list_push_deliveries(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Query(params): Query<PushDeliveryParams>,
+) -> Result<Json<Vec<PushDelivery>>, ApiError> {
+    let existing = fetch_or_not_found("Client", || state.client_repo.find_by_id(id)).await?;
+    verify_ownership(&auth, existing.user_id, None, "client")?;
+
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let deliveries = state
+        .client_repo
+        .list_push_deliveries(id, limit, offset)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(deliveries))
+}
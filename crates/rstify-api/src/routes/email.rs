@@ -0,0 +1,115 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use rstify_core::error::CoreError;
+use rstify_core::models::{EmailDelivery, EmailTestResult, SendTestEmail};
+use serde::Deserialize;
+
+use crate::error::ApiError;
+use crate::extractors::auth::AuthUser;
+use crate::helpers::audit;
+use crate::state::AppState;
+
+/// POST /api/admin/email/test - Send a test email right away and report the result (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/admin/email/test",
+    request_body = SendTestEmail,
+    responses(
+        (status = 200, body = EmailTestResult),
+        (status = 400, description = "SMTP is not configured, or there is no recipient"),
+    )
+)]
+pub async fn send_test_email(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<SendTestEmail>,
+) -> Result<Json<EmailTestResult>, ApiError> {
+    auth.require_admin()?;
+    let Some(ref outbox) = state.email else {
+        return Err(ApiError::from(CoreError::Validation(
+            "SMTP is not configured".to_string(),
+        )));
+    };
+    let recipient = match req.to.filter(|to| !to.trim().is_empty()) {
+        Some(to) => to,
+        None => auth
+            .user
+            .email
+            .clone()
+            .filter(|e| !e.trim().is_empty())
+            .ok_or_else(|| {
+                ApiError::from(CoreError::Validation(
+                    "No recipient given and your account has no email address".to_string(),
+                ))
+            })?,
+    };
+
+    let result = outbox
+        .mailer()
+        .send(
+            &recipient,
+            "rstify test email",
+            "This is a test email from rstify. If you can read it, SMTP is set up correctly.",
+            None,
+        )
+        .await;
+
+    audit::record(
+        &state,
+        &auth,
+        "email.test",
+        "email",
+        &recipient,
+        None,
+        Some(serde_json::json!({ "success": result.is_ok() })),
+    )
+    .await;
+
+    Ok(Json(EmailTestResult {
+        success: result.is_ok(),
+        recipient,
+        error: result.err().map(|e| e.message),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct EmailDeliveryParams {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /api/admin/email/deliveries - Queued and sent emails, newest first (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/admin/email/deliveries",
+    params(
+        ("status" = Option<String>, Query, description = "pending, sent or failed"),
+        ("limit" = Option<i64>, Query, description = "Max entries (default 20, max 100)"),
+        ("offset" = Option<i64>, Query, description = "Entries to skip"),
+    ),
+    responses((status = 200, body = Vec<EmailDelivery>))
+)]
+pub async fn list_email_deliveries(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<EmailDeliveryParams>,
+) -> Result<Json<Vec<EmailDelivery>>, ApiError> {
+    auth.require_admin()?;
+    if let Some(ref status) = params.status {
+        if !matches!(status.as_str(), "pending" | "sent" | "failed") {
+            return Err(ApiError::from(CoreError::Validation(
+                "status must be pending, sent or failed".to_string(),
+            )));
+        }
+    }
+
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let deliveries = state
+        .email_repo
+        .list(params.status.as_deref(), limit, offset)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(deliveries))
+}
@@ -0,0 +1,212 @@
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use rstify_core::models::{ExportDocument, ImportReport};
+use rstify_jobs::export::{self, ExportOptions};
+use rstify_storage::content_disposition;
+use serde::Deserialize;
+
+use crate::error::ApiError;
+use crate::extractors::auth::AuthUser;
+use crate::helpers::audit;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub user_id: Option<i64>,
+    pub attachments: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportParams {
+    pub user_id: Option<i64>,
+}
+
+async fn export_response(
+    state: &AppState,
+    auth: &AuthUser,
+    options: ExportOptions,
+) -> Result<Response, ApiError> {
+    let doc = export::export_data(
+        &state.repositories(),
+        &state.blob_stores,
+        std::path::Path::new(&state.upload_dir),
+        options,
+    )
+    .await
+    .map_err(ApiError::from)?;
+
+    audit::record(
+        state,
+        auth,
+        "data.export",
+        "user",
+        &options
+            .user_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "*".to_string()),
+        None,
+        Some(serde_json::json!({
+            "users": doc.users.len(),
+            "messages": doc.messages.len(),
+            "attachments": options.include_files,
+        })),
+    )
+    .await;
+
+    let name = format!(
+        "rstify-export-{}.json",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let mut response = Json(doc).into_response();
+    if let Ok(value) = HeaderValue::from_str(&content_disposition("attachment", &name)) {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+async fn import_document(
+    state: &AppState,
+    auth: &AuthUser,
+    doc: &ExportDocument,
+    into_user: Option<i64>,
+) -> Result<ImportReport, ApiError> {
+    let report = export::import_data(
+        &state.repositories(),
+        &state.blob_stores,
+        std::path::Path::new(&state.upload_dir),
+        doc,
+        into_user,
+    )
+    .await
+    .map_err(ApiError::from)?;
+
+    audit::record(
+        state,
+        auth,
+        "data.import",
+        "user",
+        &into_user
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "*".to_string()),
+        None,
+        serde_json::to_value(&report).ok(),
+    )
+    .await;
+
+    Ok(report)
+}
+
+/// GET /api/admin/export - Export all data, or one user's (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/admin/export",
+    params(
+        ("user_id" = Option<i64>, Query, description = "Only this user's data"),
+        ("attachments" = Option<bool>, Query, description = "Embed attachments and icons (default true)"),
+    ),
+    responses(
+        (status = 200, body = ExportDocument),
+        (status = 404, description = "No such user"),
+    )
+)]
+pub async fn admin_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, ApiError> {
+    auth.require_admin()?;
+    let options = ExportOptions {
+        user_id: params.user_id,
+        include_files: params.attachments.unwrap_or(true),
+    };
+    export_response(&state, &auth, options).await
+}
+
+/// POST /api/admin/import - Import an export document (admin only). Without
+/// `user_id` users are created or matched by username; with it everything is
+/// assigned to that user.
+#[utoipa::path(
+    post,
+    path = "/api/admin/import",
+    params(("user_id" = Option<i64>, Query, description = "Import into this user")),
+    request_body = ExportDocument,
+    responses(
+        (status = 200, body = ImportReport),
+        (status = 400, description = "Not an export, or from a newer version"),
+    )
+)]
+pub async fn admin_import(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<ImportParams>,
+    Json(doc): Json<ExportDocument>,
+) -> Result<Json<ImportReport>, ApiError> {
+    auth.require_admin()?;
+    if let Some(id) = params.user_id {
+        state
+            .user_repo
+            .find_by_id(id)
+            .await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| {
+                ApiError::from(rstify_core::error::CoreError::NotFound(format!(
+                    "User {} not found",
+                    id
+                )))
+            })?;
+    }
+    Ok(Json(
+        import_document(&state, &auth, &doc, params.user_id).await?,
+    ))
+}
+
+/// GET /api/export - Export the current user's own data, tokens included
+/// (session or full-access client token)
+#[utoipa::path(
+    get,
+    path = "/api/export",
+    params(("attachments" = Option<bool>, Query, description = "Embed attachments and icons (default true)")),
+    responses((status = 200, body = ExportDocument))
+)]
+pub async fn export_own(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, ApiError> {
+    // The document carries every token and webhook secret of the account, so
+    // a client token needs full access (the `admin` scope) to fetch it.
+    auth.require_scope("admin")?;
+    let options = ExportOptions {
+        user_id: Some(auth.user.id),
+        include_files: params.attachments.unwrap_or(true),
+    };
+    export_response(&state, &auth, options).await
+}
+
+/// POST /api/import - Import an export document into the current user's account
+/// (session or full-access client token)
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    request_body = ExportDocument,
+    responses(
+        (status = 200, body = ImportReport),
+        (status = 400, description = "Not an export, or from a newer version"),
+    )
+)]
+pub async fn import_own(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(doc): Json<ExportDocument>,
+) -> Result<Json<ImportReport>, ApiError> {
+    // Imported clients keep their scopes, so this could mint a full-access token.
+    auth.require_scope("admin")?;
+    let user_id = auth.user.id;
+    Ok(Json(
+        import_document(&state, &auth, &doc, Some(user_id)).await?,
+    ))
+}
@@ -0,0 +1,301 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    AddGroupMember, CreateGroup, Group, GroupMember, UpdateGroup, UpdateGroupMember,
+    GROUP_ROLE_ADMIN, GROUP_ROLE_MEMBER,
+};
+
+use crate::error::ApiError;
+use crate::extractors::auth::AuthUser;
+use crate::helpers::audit::{self, snapshot};
+use crate::helpers::ownership::{fetch_or_not_found, verify_group_admin};
+use crate::helpers::validation::{validate_length, validate_policy, GROUP_ROLES};
+use crate::state::AppState;
+
+/// Members and admins may view a group; everyone else gets Forbidden.
+fn verify_group_member(auth: &AuthUser, group_id: i64) -> Result<(), ApiError> {
+    if !auth.user.is_admin && !auth.is_group_member(group_id) {
+        return Err(ApiError::from(CoreError::Forbidden(
+            "Not a member of this group".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+/// Refuse to demote or remove a group's last admin, which would leave the
+/// group manageable only by server admins.
+async fn ensure_other_admin(state: &AppState, group_id: i64, user_id: i64) -> Result<(), ApiError> {
+    let members = state
+        .group_repo
+        .list_members(group_id)
+        .await
+        .map_err(ApiError::from)?;
+    let target_is_admin = members.iter().any(|m| m.user_id == user_id && m.is_admin());
+    let other_admins = members.iter().any(|m| m.user_id != user_id && m.is_admin());
+    if target_is_admin && !other_admins {
+        return Err(ApiError::from(CoreError::Validation(
+            "A group must keep at least one admin".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+/// GET /api/groups - Admins see every group; others see their own
+#[utoipa::path(get, path = "/api/groups", responses((status = 200, body = Vec<Group>)))]
+pub async fn list_groups(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<Group>>, ApiError> {
+    let groups = if auth.user.is_admin {
+        state.group_repo.list_all().await
+    } else {
+        state.group_repo.list_for_user(auth.user.id).await
+    }
+    .map_err(ApiError::from)?;
+    Ok(Json(groups))
+}
+
+/// POST /api/groups - Create a group; the creator becomes its first admin
+#[utoipa::path(
+    post,
+    path = "/api/groups",
+    request_body = CreateGroup,
+    responses((status = 201, body = Group))
+)]
+pub async fn create_group(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<CreateGroup>,
+) -> Result<Json<Group>, ApiError> {
+    let name = req.name.trim();
+    validate_length("Group name", name, 1, 128)?;
+
+    let group = state
+        .group_repo
+        .create(name, req.description.as_deref())
+        .await
+        .map_err(ApiError::from)?;
+    state
+        .group_repo
+        .add_member(group.id, auth.user.id, GROUP_ROLE_ADMIN)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "group.create",
+        "group",
+        group.id,
+        None,
+        snapshot(&group),
+    )
+    .await;
+    Ok(Json(group))
+}
+
+#[utoipa::path(get, path = "/api/groups/{id}", responses((status = 200, body = Group)))]
+pub async fn get_group(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Group>, ApiError> {
+    let group = fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    verify_group_member(&auth, id)?;
+    Ok(Json(group))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/groups/{id}",
+    request_body = UpdateGroup,
+    responses((status = 200, body = Group))
+)]
+pub async fn update_group(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateGroup>,
+) -> Result<Json<Group>, ApiError> {
+    let existing = fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    verify_group_admin(&auth, id)?;
+
+    let name = req.name.as_deref().map(str::trim);
+    if let Some(name) = name {
+        validate_length("Group name", name, 1, 128)?;
+    }
+
+    let group = state
+        .group_repo
+        .update(id, name, req.description.as_deref())
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "group.update",
+        "group",
+        id,
+        snapshot(&existing),
+        snapshot(&group),
+    )
+    .await;
+    Ok(Json(group))
+}
+
+/// DELETE /api/groups/{id} - Group-owned topics and applications fall back to
+/// their creating user; group permission grants are removed.
+#[utoipa::path(delete, path = "/api/groups/{id}", responses((status = 200)))]
+pub async fn delete_group(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let existing = fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    verify_group_admin(&auth, id)?;
+
+    state.group_repo.delete(id).await.map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "group.delete",
+        "group",
+        id,
+        snapshot(&existing),
+        None,
+    )
+    .await;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/groups/{id}/members",
+    responses((status = 200, body = Vec<GroupMember>))
+)]
+pub async fn list_group_members(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<GroupMember>>, ApiError> {
+    fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    verify_group_member(&auth, id)?;
+
+    let members = state
+        .group_repo
+        .list_members(id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(members))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/groups/{id}/members",
+    request_body = AddGroupMember,
+    responses((status = 201, body = GroupMember))
+)]
+pub async fn add_group_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<AddGroupMember>,
+) -> Result<Json<GroupMember>, ApiError> {
+    fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    verify_group_admin(&auth, id)?;
+
+    let role = req.role.as_deref().unwrap_or(GROUP_ROLE_MEMBER);
+    validate_policy("role", role, GROUP_ROLES)?;
+    fetch_or_not_found("User", || state.user_repo.find_by_id(req.user_id)).await?;
+
+    let member = state
+        .group_repo
+        .add_member(id, req.user_id, role)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "group.member_add",
+        "group",
+        id,
+        None,
+        snapshot(&member),
+    )
+    .await;
+    Ok(Json(member))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/groups/{id}/members/{user_id}",
+    request_body = UpdateGroupMember,
+    responses((status = 200, body = GroupMember))
+)]
+pub async fn update_group_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(i64, i64)>,
+    Json(req): Json<UpdateGroupMember>,
+) -> Result<Json<GroupMember>, ApiError> {
+    fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    verify_group_admin(&auth, id)?;
+    validate_policy("role", &req.role, GROUP_ROLES)?;
+
+    if req.role != GROUP_ROLE_ADMIN {
+        ensure_other_admin(&state, id, user_id).await?;
+    }
+
+    let member = state
+        .group_repo
+        .update_member_role(id, user_id, &req.role)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "group.member_update",
+        "group",
+        id,
+        None,
+        snapshot(&member),
+    )
+    .await;
+    Ok(Json(member))
+}
+
+/// DELETE /api/groups/{id}/members/{user_id} - Group admins remove members;
+/// any member may remove themselves.
+#[utoipa::path(
+    delete,
+    path = "/api/groups/{id}/members/{user_id}",
+    responses((status = 200))
+)]
+pub async fn remove_group_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(i64, i64)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    fetch_or_not_found("Group", || state.group_repo.find_by_id(id)).await?;
+    if user_id != auth.user.id {
+        verify_group_admin(&auth, id)?;
+    }
+    ensure_other_admin(&state, id, user_id).await?;
+
+    state
+        .group_repo
+        .remove_member(id, user_id)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "group.member_remove",
+        "group",
+        id,
+        None,
+        Some(serde_json::json!({ "user_id": user_id })),
+    )
+    .await;
+    Ok(Json(serde_json::json!({"success": true})))
+}
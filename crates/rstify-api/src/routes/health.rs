@@ -28,10 +28,7 @@ pub struct VersionResponse {
 
 #[utoipa::path(get, path = "/health", responses((status = 200, body = HealthResponse)))]
 pub async fn health(State(state): State<AppState>) -> Json<HealthResponse> {
-    let db_status = match sqlx::query_scalar::<_, i32>("SELECT 1")
-        .fetch_one(&state.pool)
-        .await
-    {
+    let db_status = match state.db.ping().await {
         Ok(_) => "ok",
         Err(_) => "error",
     };
@@ -6,14 +6,14 @@ use rstify_core::models::{
     AttachmentInfo, CreateAppMessage, Message, MessageResponse, PagedMessages, Paging,
     UpdateMessage,
 };
-use rstify_core::repositories::{
-    ApplicationRepository, ClientRepository, MessageRepository, TopicRepository, UserRepository,
-};
 use serde::Deserialize;
 use std::collections::HashMap;
 
 use crate::error::ApiError;
-use crate::extractors::auth::{AuthApp, AuthUser};
+use crate::extractors::auth::{check_client_token, AuthApp, AuthUser, ClientIp};
+use crate::extractors::upload::MessageUpload;
+use crate::helpers::attachments::attach_staged_files;
+use crate::helpers::ownership::fetch_or_not_found;
 use crate::state::AppState;
 
 /// Enrich message responses with attachment info via a single batch query
@@ -56,17 +56,23 @@ pub struct ListParams {
     pub inbox: Option<bool>,
 }
 
-/// POST /message - Send message via app token (Gotify compat)
+/// POST /message - Send message via app token (Gotify compat). Also accepts
+/// multipart/form-data with files attached
 #[utoipa::path(
     post,
     path = "/message",
-    request_body = CreateAppMessage,
+    request_body(
+        content(
+            (CreateAppMessage = "application/json"),
+            (CreateAppMessage = "multipart/form-data")
+        )
+    ),
     responses((status = 200, body = MessageResponse))
 )]
 pub async fn create_app_message(
     State(state): State<AppState>,
     auth: AuthApp,
-    Json(req): Json<CreateAppMessage>,
+    MessageUpload { body: req, files }: MessageUpload<CreateAppMessage>,
 ) -> Result<Json<MessageResponse>, ApiError> {
     if req.message.is_empty() || req.message.len() > 65536 {
         return Err(ApiError::from(rstify_core::error::CoreError::Validation(
@@ -94,7 +100,10 @@ pub async fn create_app_message(
         .await
         .map_err(ApiError::from)?;
 
-    let response = msg.to_response(None);
+    let mut response = msg.to_response(None);
+    if !files.is_empty() {
+        response.attachments = Some(attach_staged_files(&state, msg.id, files).await?);
+    }
 
     // Broadcast to the user's stream + push (shared delivery path).
     crate::helpers::publish::deliver_message(
@@ -107,6 +116,26 @@ pub async fn create_app_message(
     Ok(Json(response))
 }
 
+/// Forbidden unless the token's `topic:` scopes allow `access` to the
+/// message's topic. Application messages aren't topic-scoped.
+async fn check_topic_scope(
+    state: &AppState,
+    auth: &AuthUser,
+    msg: &Message,
+    access: &str,
+) -> Result<(), ApiError> {
+    let Some(topic_id) = msg.topic_id else {
+        return Ok(());
+    };
+    let topic = fetch_or_not_found("Message", || state.topic_repo.find_by_id(topic_id)).await?;
+    if !auth.can_access_topic(access, &topic.name) {
+        return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
+            format!("Token not scoped to {} topic '{}'", access, topic.name),
+        )));
+    }
+    Ok(())
+}
+
 /// GET /message - List all messages for user (Gotify compat)
 #[utoipa::path(get, path = "/message", responses((status = 200, body = PagedMessages)))]
 pub async fn list_messages(
@@ -123,11 +152,14 @@ pub async fn list_messages(
     // Gotify-pure (application messages only) so Gotify clients see exactly
     // what they expect.
     let (messages, topic_names) = if params.inbox.is_some() {
-        let visible = state
+        let visible: Vec<_> = state
             .topic_repo
             .list_visible(auth.user.id)
             .await
-            .map_err(ApiError::from)?;
+            .map_err(ApiError::from)?
+            .into_iter()
+            .filter(|t| auth.can_access_topic("read", &t.name))
+            .collect();
         let topic_ids: Vec<i64> = visible.iter().map(|t| t.id).collect();
         let names: std::collections::HashMap<i64, String> =
             visible.into_iter().map(|t| (t.id, t.name)).collect();
@@ -344,11 +376,20 @@ pub async fn update_message(
             )));
         }
     }
-
-    let extras_json = req
-        .extras
-        .as_ref()
-        .map(|e| serde_json::to_string(e).unwrap_or_default());
+    check_topic_scope(&state, &auth, &msg, "write").await?;
+
+    let extras_json = match req.extras {
+        Some(extras) => {
+            let merged = msg.merged_extras(extras).ok_or_else(|| {
+                ApiError::from(rstify_core::error::CoreError::Validation(
+                    "extras must be an object on acknowledged, escalated or relayed messages"
+                        .to_string(),
+                ))
+            })?;
+            Some(serde_json::to_string(&merged).unwrap_or_default())
+        }
+        None => None,
+    };
 
     let updated = state
         .message_repo
@@ -407,6 +448,7 @@ pub async fn delete_message(
             )));
         }
     }
+    check_topic_scope(&state, &auth, &msg, "write").await?;
 
     state
         .message_repo
@@ -416,11 +458,76 @@ pub async fn delete_message(
     Ok(Json(serde_json::json!({"success": true})))
 }
 
+/// POST /api/messages/{id}/ack - Acknowledge a message, stopping any
+/// escalation. Anyone who may read the message may acknowledge it; only the
+/// first acknowledgement is recorded.
+#[utoipa::path(
+    post,
+    path = "/api/messages/{id}/ack",
+    responses((status = 200, body = MessageResponse))
+)]
+pub async fn acknowledge_message(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    auth.require_scope("write")?;
+    let msg = fetch_or_not_found("Message", || state.message_repo.find_by_id(id)).await?;
+
+    let topic_name = match msg.topic_id {
+        Some(topic_id) => {
+            let topic =
+                fetch_or_not_found("Message", || state.topic_repo.find_by_id(topic_id)).await?;
+            crate::routes::topics::check_read_permission(&state, &auth, &topic).await?;
+            Some(topic.name)
+        }
+        None => {
+            let is_owner = match msg.application_id {
+                Some(app_id) => state
+                    .app_repo
+                    .find_by_id(app_id)
+                    .await
+                    .map_err(ApiError::from)?
+                    .is_some_and(|app| app.user_id == auth.user.id),
+                None => msg.user_id == Some(auth.user.id),
+            };
+            if !is_owner && !auth.user.is_admin {
+                return Err(ApiError::from(rstify_core::error::CoreError::NotFound(
+                    "Message not found".to_string(),
+                )));
+            }
+            None
+        }
+    };
+
+    state
+        .message_repo
+        .stop_escalation(id)
+        .await
+        .map_err(ApiError::from)?;
+    let ack = serde_json::json!({
+        "at": chrono::Utc::now().to_rfc3339(),
+        "user_id": auth.user.id,
+        "username": auth.user.username,
+    });
+    state
+        .message_repo
+        .record_acknowledgement(id, &ack.to_string())
+        .await
+        .map_err(ApiError::from)?;
+    let msg = fetch_or_not_found("Message", || state.message_repo.find_by_id(id)).await?;
+
+    let mut responses = enrich_with_attachments(&state, &[msg], topic_name).await?;
+    Ok(Json(responses.remove(0)))
+}
+
 /// GET /stream - WebSocket stream for authenticated user (Gotify compat)
 #[utoipa::path(get, path = "/stream", responses((status = 101, description = "WebSocket upgrade")))]
+#[allow(clippy::collapsible_match)]
 pub async fn websocket_stream(
     State(state): State<AppState>,
     ws: WebSocketUpgrade,
+    ClientIp(ip): ClientIp,
     Query(params): Query<TokenQuery>,
 ) -> Result<impl IntoResponse, ApiError> {
     // Authenticate via query token (supports both JWT and client tokens)
@@ -461,6 +568,7 @@ pub async fn websocket_stream(
                         "Invalid client token".to_string(),
                     ))
                 })?;
+            check_client_token(&state, &client, ip.as_deref()).await?;
             if !client.has_scope("read") {
                 return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
                     "Token missing required scope: read".to_string(),
@@ -1,8 +1,8 @@
 use axum::extract::{Query, State};
 use axum::Json;
 use rstify_core::models::MessageResponse;
-use rstify_core::repositories::MessageRepository;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
@@ -51,6 +51,32 @@ pub async fn search_messages(
         .await
         .map_err(ApiError::from)?;
 
+    // Drop topic messages the token's `topic:` scopes don't cover.
+    let mut readable: HashMap<i64, bool> = HashMap::new();
+    let mut visible = Vec::with_capacity(messages.len());
+    for msg in messages {
+        if let Some(topic_id) = msg.topic_id {
+            let allowed = match readable.get(&topic_id) {
+                Some(allowed) => *allowed,
+                None => {
+                    let allowed = state
+                        .topic_repo
+                        .find_by_id(topic_id)
+                        .await
+                        .map_err(ApiError::from)?
+                        .is_some_and(|t| auth.can_access_topic("read", &t.name));
+                    readable.insert(topic_id, allowed);
+                    allowed
+                }
+            };
+            if !allowed {
+                continue;
+            }
+        }
+        visible.push(msg);
+    }
+    let messages = visible;
+
     let responses = enrich_with_attachments(&state, &messages, None).await?;
     Ok(Json(responses))
 }
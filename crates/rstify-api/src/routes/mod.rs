@@ -1,7 +1,12 @@
 pub mod applications;
 pub mod attachments;
+pub mod audit;
 pub mod auth;
+pub mod backups;
 pub mod clients;
+pub mod email;
+pub mod export;
+pub mod groups;
 pub mod health;
 pub mod messages;
 pub mod ntfy_publish;
@@ -12,6 +17,7 @@ pub mod users;
 pub mod webhook_variables;
 pub mod webhooks;
 
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{delete, get, post, put};
 use axum::Router;
 
@@ -47,9 +53,41 @@ pub fn gotify_routes(_state: AppState) -> Router<AppState> {
         .route("/client/{id}", delete(clients::delete_client))
         .route("/client/{id}/fcm-token", put(clients::register_fcm_token))
         .route("/client/{id}/fcm-token", delete(clients::remove_fcm_token))
+        .route("/client/{id}/web-push", put(clients::register_web_push))
+        .route("/client/{id}/web-push", delete(clients::remove_web_push))
+        .route("/api/web-push/vapid-key", get(clients::vapid_public_key))
+        .route(
+            "/client/{id}/subscriptions",
+            get(clients::list_subscriptions),
+        )
+        .route("/client/{id}/subscriptions", post(clients::subscribe))
+        .route(
+            "/client/{id}/subscriptions/{subscription_id}",
+            delete(clients::unsubscribe),
+        )
+        .route(
+            "/client/{id}/push-deliveries",
+            get(clients::list_push_deliveries),
+        )
         // Current user
         .route("/current/user", get(users::current_user))
         .route("/current/user/password", post(users::change_password))
+        .route(
+            "/current/user/email-preferences",
+            get(users::get_email_preferences),
+        )
+        .route(
+            "/current/user/email-preferences",
+            put(users::update_email_preferences),
+        )
+        .route(
+            "/current/user/quiet-hours",
+            get(users::list_quiet_hours).post(users::create_quiet_hours),
+        )
+        .route(
+            "/current/user/quiet-hours/{id}",
+            put(users::update_quiet_hours).delete(users::delete_quiet_hours),
+        )
         // Application messages
         .route(
             "/application/{id}/messages",
@@ -60,6 +98,7 @@ pub fn gotify_routes(_state: AppState) -> Router<AppState> {
         .route("/user", post(users::create_user))
         .route("/user/{id}", put(users::update_user))
         .route("/user/{id}", delete(users::delete_user))
+        .route("/user/{id}/unlock", post(users::unlock_user))
         // WebSocket stream
         .route("/stream", get(messages::websocket_stream))
         // Health & version
@@ -69,11 +108,15 @@ pub fn gotify_routes(_state: AppState) -> Router<AppState> {
 }
 
 /// Enhanced API routes (ntfy-inspired)
-pub fn api_routes(_state: AppState) -> Router<AppState> {
+pub fn api_routes(state: AppState) -> Router<AppState> {
     Router::new()
         // Auth
         .route("/api/auth/login", post(auth::login))
         // Topics
+        .route(
+            "/api/messages/{id}/ack",
+            post(messages::acknowledge_message),
+        )
         .route("/api/topics", post(topics::create_topic))
         .route("/api/topics", get(topics::list_topics))
         .route("/api/topics/{name}", get(topics::get_topic))
@@ -89,7 +132,7 @@ pub fn api_routes(_state: AppState) -> Router<AppState> {
         .route("/api/topics/{name}/json", get(topics::topic_json_stream))
         // Stats
         .route("/api/stats", get(stats::get_stats))
-        // Attachments (read-only; files are attached at publish time via ntfy headers)
+        // Attachments (read-only; files are attached at publish time via ntfy or multipart)
         .route(
             "/api/messages/{id}/attachments",
             get(attachments::list_message_attachments),
@@ -98,6 +141,18 @@ pub fn api_routes(_state: AppState) -> Router<AppState> {
             "/api/attachments/{id}",
             get(attachments::download_attachment).delete(attachments::delete_attachment),
         )
+        .route(
+            "/api/attachments/{id}/thumbnail",
+            get(attachments::download_thumbnail),
+        )
+        .route(
+            "/api/attachments/{id}/link",
+            post(attachments::create_attachment_link),
+        )
+        .route(
+            "/api/attachments/{id}/public",
+            get(attachments::download_signed_attachment),
+        )
         // Webhooks
         .route("/api/webhooks", post(webhooks::create_webhook))
         .route("/api/webhooks", get(webhooks::list_webhooks))
@@ -126,9 +181,53 @@ pub fn api_routes(_state: AppState) -> Router<AppState> {
         .route("/api/permissions", post(topics::create_permission))
         .route("/api/permissions", get(topics::list_permissions))
         .route("/api/permissions/{id}", delete(topics::delete_permission))
+        // Groups
+        .route(
+            "/api/groups",
+            get(groups::list_groups).post(groups::create_group),
+        )
+        .route(
+            "/api/groups/{id}",
+            get(groups::get_group)
+                .put(groups::update_group)
+                .delete(groups::delete_group),
+        )
+        .route(
+            "/api/groups/{id}/members",
+            get(groups::list_group_members).post(groups::add_group_member),
+        )
+        .route(
+            "/api/groups/{id}/members/{user_id}",
+            put(groups::update_group_member).delete(groups::remove_group_member),
+        )
         // Settings
         .route("/api/settings", get(settings::list_settings))
         .route("/api/settings/{key}", put(settings::update_setting))
+        // Audit log
+        .route("/api/audit", get(audit::list_audit_log))
+        // Backups
+        .route(
+            "/api/backups",
+            get(backups::list_backups).post(backups::create_backup),
+        )
+        .route("/api/backups/{name}", get(backups::download_backup))
+        // Export / import
+        // Email
+        .route("/api/admin/email/test", post(email::send_test_email))
+        .route(
+            "/api/admin/email/deliveries",
+            get(email::list_email_deliveries),
+        )
+        .route("/api/admin/export", get(export::admin_export))
+        .route(
+            "/api/admin/import",
+            post(export::admin_import).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/api/export", get(export::export_own))
+        .route(
+            "/api/import",
+            post(export::import_own).layer(DefaultBodyLimit::max(state.max_upload_total)),
+        )
 }
 
 /// ntfy-style catch-all publish routes.
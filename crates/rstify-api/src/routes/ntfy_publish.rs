@@ -3,15 +3,14 @@ use axum::extract::{Path, State};
 use axum::http::HeaderMap;
 use axum::Json;
 use rstify_core::models::{AttachmentInfo, MessageResponse};
-use rstify_core::repositories::{MessageRepository, TopicRepository};
-use tokio::fs;
-use uuid::Uuid;
+use rstify_storage::content_hash;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::helpers::attachments::{store_attachment, AttachmentSource, NewAttachment};
 use crate::ntfy_headers::NtfyHeaders;
 use crate::state::AppState;
-use crate::utils::sanitize_filename;
+use crate::utils::{sanitize_filename, sniff_content_type, SNIFF_LEN};
 
 /// POST /{topic} or PUT /{topic} - ntfy-style publish where body is the message
 /// and HTTP headers carry metadata.
@@ -40,22 +39,7 @@ pub async fn ntfy_publish(
             )))
         })?;
 
-    // Check write permission
-    if !auth.user.is_admin && !topic.everyone_write && topic.owner_id != Some(auth.user.id) {
-        let permissions = state
-            .topic_repo
-            .list_permissions_for_user(auth.user.id)
-            .await
-            .map_err(ApiError::from)?;
-        let has_write = permissions
-            .iter()
-            .any(|p| p.can_write && rstify_auth::acl::topic_matches(&p.topic_pattern, &topic.name));
-        if !has_write {
-            return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
-                "No write permission for this topic".to_string(),
-            )));
-        }
-    }
+    crate::routes::topics::check_write_permission(&state, &auth, &topic).await?;
 
     let h = NtfyHeaders::from_headers(&headers);
 
@@ -89,6 +73,9 @@ pub async fn ntfy_publish(
         .inbox_threshold
         .load(std::sync::atomic::Ordering::Relaxed);
     let inbox = rstify_core::policy::should_inbox(&topic, h.priority.unwrap_or(3), threshold);
+    let relay_extras = get_header_str(&headers, rstify_jobs::relay::HOPS_HEADER)
+        .and_then(|v| rstify_jobs::relay::parse_hops_header(&v))
+        .map(rstify_jobs::relay::hops_extras);
 
     let msg = state
         .message_repo
@@ -108,6 +95,7 @@ pub async fn ntfy_publish(
             actions: h.actions.as_deref(),
             content_type: h.content_type.as_deref(),
             scheduled_for: h.scheduled_for.as_deref(),
+            extras: relay_extras.as_deref(),
             source: Some("ntfy"),
             inbox,
             ..Default::default()
@@ -179,17 +167,15 @@ pub async fn ntfy_publish(
 
     // Send email notification if Email header present and SMTP configured
     if let Some(ref email_to) = h.email {
-        if let Some(ref email_config) = state.email_config {
-            let email_config = email_config.clone();
-            let email_to = email_to.clone();
+        if let Some(ref outbox) = state.email {
             let subject = h
                 .title
                 .clone()
                 .unwrap_or_else(|| format!("Notification from {}", topic_name));
-            let body = message_text.clone();
-            tokio::spawn(async move {
-                rstify_jobs::email::send_email(&email_config, &email_to, &subject, &body).await;
-            });
+            let body = crate::email::text_body(&state, &message_text, &response);
+            if let Err(e) = outbox.enqueue(email_to, &subject, &body, None).await {
+                tracing::warn!("Failed to queue email to {}: {}", email_to, e);
+            }
         }
     }
 
@@ -224,45 +210,27 @@ async fn save_attachment(
         )));
     }
 
-    let upload_dir = &state.upload_dir;
-    fs::create_dir_all(upload_dir).await.map_err(|e| {
-        ApiError::from(rstify_core::error::CoreError::Internal(format!(
-            "Failed to create upload dir: {e}"
-        )))
-    })?;
-
     let filename = sanitize_filename(raw_filename);
-    let content_type = mime_guess::from_path(&filename)
-        .first()
-        .map(|m| m.to_string());
-    let storage_filename = format!("{}_{}", Uuid::new_v4(), filename);
-    let storage_path = format!("{}/{}", upload_dir, storage_filename);
-
-    fs::write(&storage_path, data).await.map_err(|e| {
-        ApiError::from(rstify_core::error::CoreError::Internal(format!(
-            "Failed to write file: {e}"
-        )))
-    })?;
-
-    let attachment = state
-        .message_repo
-        .create_attachment(
-            message_id,
-            &filename,
-            content_type.as_deref(),
-            data.len() as i64,
-            "local",
-            &storage_path,
-            None,
-        )
-        .await
-        .map_err(ApiError::from)?;
+    let content_type = sniff_content_type(&data[..data.len().min(SNIFF_LEN)], &filename);
+    let sha256 = content_hash(data);
+    let attachment = store_attachment(
+        state,
+        message_id,
+        NewAttachment {
+            filename: &filename,
+            content_type: &content_type,
+            size: data.len() as i64,
+            sha256: &sha256,
+            source: AttachmentSource::Bytes(data),
+        },
+    )
+    .await?;
 
     Ok(Some(AttachmentInfo::from_attachment(&attachment)))
 }
 
 /// Download a file from a URL and save it as an attachment.
-async fn download_and_attach(
+pub(crate) async fn download_and_attach(
     state: &AppState,
     message_id: i64,
     url: &str,
@@ -6,6 +6,7 @@ use utoipa::ToSchema;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::helpers::audit;
 use crate::state::AppState;
 
 #[derive(Debug, Serialize, ToSchema, TS)]
@@ -27,12 +28,10 @@ pub async fn list_settings(
     auth: AuthUser,
 ) -> Result<Json<Vec<Setting>>, ApiError> {
     auth.require_admin()?;
-    let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
-        .fetch_all(&state.pool)
-        .await
-        .map_err(|e| ApiError::from(rstify_db::map_sqlx_err(e)))?;
+    let rows = state.settings_repo.list().await.map_err(ApiError::from)?;
     let settings = rows
         .into_iter()
+        .filter(|(key, _)| key != crate::webpush::VAPID_KEY_SETTING)
         .map(|(key, value)| Setting { key, value })
         .collect();
     Ok(Json(settings))
@@ -51,12 +50,22 @@ pub async fn update_setting(
     Json(req): Json<UpdateSetting>,
 ) -> Result<Json<Setting>, ApiError> {
     auth.require_admin()?;
-    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
-        .bind(&key)
-        .bind(&req.value)
-        .execute(&state.pool)
+    // Replacing the VAPID key would silently break every browser subscription.
+    if key == crate::webpush::VAPID_KEY_SETTING {
+        return Err(ApiError::from(rstify_core::error::CoreError::Validation(
+            format!("{} is managed by the server", key),
+        )));
+    }
+    let previous = state
+        .settings_repo
+        .get(&key)
         .await
-        .map_err(|e| ApiError::from(rstify_db::map_sqlx_err(e)))?;
+        .map_err(ApiError::from)?;
+    state
+        .settings_repo
+        .set(&key, &req.value)
+        .await
+        .map_err(ApiError::from)?;
 
     // Update in-memory cache
     if key == "inbox_priority_threshold" {
@@ -67,6 +76,17 @@ pub async fn update_setting(
         }
     }
 
+    audit::record(
+        &state,
+        &auth,
+        "setting.update",
+        "setting",
+        &key,
+        previous.map(|v| serde_json::json!({ "value": v })),
+        Some(serde_json::json!({ "value": req.value })),
+    )
+    .await;
+
     Ok(Json(Setting {
         key,
         value: req.value,
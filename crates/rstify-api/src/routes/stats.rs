@@ -1,6 +1,5 @@
 use axum::extract::State;
 use axum::Json;
-use rstify_core::repositories::{MessageRepository, TopicRepository, UserRepository};
 use serde::Serialize;
 use ts_rs::TS;
 use utoipa::ToSchema;
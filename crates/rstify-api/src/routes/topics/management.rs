@@ -4,11 +4,13 @@ use rstify_auth::acl::topic_matches;
 use rstify_core::models::{
     CreateTopic, MessageResponse, PagedMessages, Paging, Topic, UpdateTopic,
 };
-use rstify_core::repositories::{MessageRepository, TopicRepository};
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
-use crate::helpers::ownership::{fetch_or_not_found, verify_optional_ownership};
+use crate::helpers::audit::{self, snapshot};
+use crate::helpers::ownership::{
+    fetch_or_not_found, verify_group_admin, verify_group_assignment, verify_optional_ownership,
+};
 use crate::helpers::validation::{
     validate_json, validate_policy, validate_positive, validate_topic_name, INBOX_OVERRIDES,
     NOTIFY_POLICIES, STORE_POLICIES,
@@ -29,18 +31,30 @@ pub async fn create_topic(
 ) -> Result<Json<Topic>, ApiError> {
     let name = req.name.trim();
     validate_topic_name(name)?;
+    let group_id = verify_group_assignment(&state, &auth, req.group_id).await?;
 
     let topic = state
         .topic_repo
         .create(
             name,
             Some(auth.user.id),
+            group_id,
             req.description.as_deref(),
             req.everyone_read.unwrap_or(true),
             req.everyone_write.unwrap_or(true),
         )
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "topic.create",
+        "topic",
+        topic.id,
+        None,
+        snapshot(&topic),
+    )
+    .await;
     Ok(Json(topic))
 }
 
@@ -50,18 +64,23 @@ pub async fn list_topics(
     auth: AuthUser,
 ) -> Result<Json<Vec<Topic>>, ApiError> {
     // Admins see everything; others see only readable topics
-    if auth.user.is_admin {
-        let all_topics = state.topic_repo.list_all().await.map_err(ApiError::from)?;
-        return Ok(Json(all_topics));
-    }
+    let topics = if auth.user.is_admin {
+        state.topic_repo.list_all().await.map_err(ApiError::from)?
+    } else {
+        state
+            .topic_repo
+            .list_visible(auth.user.id)
+            .await
+            .map_err(ApiError::from)?
+    };
 
-    let visible = state
-        .topic_repo
-        .list_visible(auth.user.id)
-        .await
-        .map_err(ApiError::from)?;
-
-    Ok(Json(visible))
+    // A topic-scoped token only lists the topics it may read.
+    Ok(Json(
+        topics
+            .into_iter()
+            .filter(|t| auth.can_access_topic("read", &t.name))
+            .collect(),
+    ))
 }
 
 #[utoipa::path(get, path = "/api/topics/{name}", responses((status = 200, body = Topic)))]
@@ -82,7 +101,7 @@ pub async fn get_topic(
             )))
         })?;
 
-    check_read_permission(&state, &auth.user, &topic).await?;
+    check_read_permission(&state, &auth, &topic).await?;
 
     Ok(Json(topic))
 }
@@ -101,7 +120,7 @@ pub async fn update_topic(
     Json(req): Json<UpdateTopic>,
 ) -> Result<Json<Topic>, ApiError> {
     let topic = fetch_or_not_found("Topic", || state.topic_repo.find_by_name(&name)).await?;
-    verify_optional_ownership(&auth, topic.owner_id, "topic")?;
+    verify_optional_ownership(&auth, topic.owner_id, topic.group_id, "topic")?;
 
     // Validate notification/store policy fields
     if let Some(ref policy) = req.notify_policy {
@@ -127,6 +146,49 @@ pub async fn update_topic(
         }
     }
 
+    // A policy with no steps removes it.
+    let escalation_policy = match req.escalation_policy {
+        Some(ref policy) if policy.steps.is_empty() => Some(None),
+        Some(ref policy) => {
+            crate::escalation::validate_policy(&state, &auth, &topic, policy).await?;
+            Some(Some(serde_json::to_string(policy).map_err(|e| {
+                ApiError::from(rstify_core::error::CoreError::Internal(e.to_string()))
+            })?))
+        }
+        None => None,
+    };
+
+    match req.group_id {
+        Some(Some(group_id)) => {
+            verify_group_assignment(&state, &auth, Some(group_id)).await?;
+            state
+                .topic_repo
+                .set_group(topic.id, Some(group_id))
+                .await
+                .map_err(ApiError::from)?;
+        }
+        // Taking it out of a group needs the same right as handing it over.
+        Some(None) => {
+            if let Some(current) = topic.group_id {
+                verify_group_admin(&auth, current)?;
+                state
+                    .topic_repo
+                    .set_group(topic.id, None)
+                    .await
+                    .map_err(ApiError::from)?;
+            }
+        }
+        None => {}
+    }
+
+    if let Some(policy) = escalation_policy {
+        state
+            .topic_repo
+            .set_escalation_policy(topic.id, policy.as_deref())
+            .await
+            .map_err(ApiError::from)?;
+    }
+
     let updated = state
         .topic_repo
         .update(
@@ -146,6 +208,16 @@ pub async fn update_topic(
         .await
         .map_err(ApiError::from)?;
 
+    audit::record(
+        &state,
+        &auth,
+        "topic.update",
+        "topic",
+        topic.id,
+        snapshot(&topic),
+        snapshot(&updated),
+    )
+    .await;
     Ok(Json(updated))
 }
 
@@ -156,50 +228,69 @@ pub async fn delete_topic(
     Path(name): Path<String>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let topic = fetch_or_not_found("Topic", || state.topic_repo.find_by_name(&name)).await?;
-    verify_optional_ownership(&auth, topic.owner_id, "topic")?;
+    verify_optional_ownership(&auth, topic.owner_id, topic.group_id, "topic")?;
 
     state
         .topic_repo
         .delete(topic.id)
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "topic.delete",
+        "topic",
+        topic.id,
+        snapshot(&topic),
+        None,
+    )
+    .await;
     Ok(Json(serde_json::json!({"success": true})))
 }
 
-/// Check if user has read permission to a topic
-pub(super) async fn check_read_permission(
+/// Check if the caller may read a topic: the token's `topic:read:` scopes
+/// (if any) first, then the user's ownership, group membership, public flag,
+/// or ACL grants (their own or their groups').
+pub(crate) async fn check_read_permission(
     state: &AppState,
-    user: &rstify_core::models::User,
+    auth: &AuthUser,
     topic: &Topic,
 ) -> Result<(), ApiError> {
-    if user.is_admin || topic.everyone_read || topic.owner_id == Some(user.id) {
-        return Ok(());
-    }
-
-    let permissions = state
-        .topic_repo
-        .list_permissions_for_user(user.id)
-        .await
-        .map_err(ApiError::from)?;
-
-    for perm in &permissions {
-        if perm.can_read && topic_matches(&perm.topic_pattern, &topic.name) {
-            return Ok(());
-        }
-    }
+    check_topic_access(state, auth, topic, "read").await
+}
 
-    Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
-        "No read permission for this topic".to_string(),
-    )))
+/// Check if the caller may write to a topic: the token's `topic:write:` scopes
+/// (if any) first, then the user's ownership, group membership, public flag,
+/// or ACL grants (their own or their groups').
+pub(crate) async fn check_write_permission(
+    state: &AppState,
+    auth: &AuthUser,
+    topic: &Topic,
+) -> Result<(), ApiError> {
+    check_topic_access(state, auth, topic, "write").await
 }
 
-/// Check if user has write permission to a topic
-pub(super) async fn check_write_permission(
+async fn check_topic_access(
     state: &AppState,
-    user: &rstify_core::models::User,
+    auth: &AuthUser,
     topic: &Topic,
+    access: &str,
 ) -> Result<(), ApiError> {
-    if user.is_admin || topic.everyone_write || topic.owner_id == Some(user.id) {
+    // Token scopes narrow access even for admins and topic owners.
+    if !auth.can_access_topic(access, &topic.name) {
+        return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
+            format!("Token not scoped to {} topic '{}'", access, topic.name),
+        )));
+    }
+
+    let user = &auth.user;
+    let public = if access == "read" {
+        topic.everyone_read
+    } else {
+        topic.everyone_write
+    };
+    let in_group = topic.group_id.is_some_and(|g| auth.is_group_member(g));
+    if user.is_admin || public || in_group || topic.owner_id == Some(user.id) {
         return Ok(());
     }
 
@@ -209,14 +300,20 @@ pub(super) async fn check_write_permission(
         .await
         .map_err(ApiError::from)?;
 
-    for perm in &permissions {
-        if perm.can_write && topic_matches(&perm.topic_pattern, &topic.name) {
-            return Ok(());
-        }
+    let granted = permissions.iter().any(|perm| {
+        let allowed = if access == "read" {
+            perm.can_read
+        } else {
+            perm.can_write
+        };
+        allowed && topic_matches(&perm.topic_pattern, &topic.name)
+    });
+    if granted {
+        return Ok(());
     }
 
     Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
-        "No write permission for this topic".to_string(),
+        format!("No {} permission for this topic", access),
     )))
 }
 
@@ -244,7 +341,7 @@ pub async fn list_topic_messages(
             )))
         })?;
 
-    check_read_permission(&state, &auth.user, &topic).await?;
+    check_read_permission(&state, &auth, &topic).await?;
 
     let limit = params.limit.unwrap_or(100).clamp(1, 500);
     let since = params.since.unwrap_or(0).max(0);
@@ -1,10 +1,10 @@
 use axum::extract::{Path, State};
 use axum::Json;
 use rstify_core::models::{CreateTopicPermission, TopicPermission};
-use rstify_core::repositories::TopicRepository;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::helpers::audit::{self, snapshot};
 use crate::state::AppState;
 
 #[utoipa::path(
@@ -24,16 +24,33 @@ pub async fn create_permission(
         )));
     }
 
+    if req.user_id.is_some() == req.group_id.is_some() {
+        return Err(ApiError::from(rstify_core::error::CoreError::Validation(
+            "Set exactly one of user_id or group_id".to_string(),
+        )));
+    }
+
     let perm = state
         .topic_repo
         .create_permission(
             req.user_id,
+            req.group_id,
             &req.topic_pattern,
             req.can_read.unwrap_or(false),
             req.can_write.unwrap_or(false),
         )
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "permission.create",
+        "permission",
+        perm.id,
+        None,
+        snapshot(&perm),
+    )
+    .await;
     Ok(Json(perm))
 }
 
@@ -70,10 +87,27 @@ pub async fn delete_permission(
         )));
     }
 
+    let existing = state
+        .topic_repo
+        .list_all_permissions()
+        .await
+        .map_err(ApiError::from)?
+        .into_iter()
+        .find(|p| p.id == id);
     state
         .topic_repo
         .delete_permission(id)
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "permission.delete",
+        "permission",
+        id,
+        existing.as_ref().and_then(snapshot),
+        None,
+    )
+    .await;
     Ok(Json(serde_json::json!({"success": true})))
 }
@@ -1,31 +1,42 @@
 use axum::extract::{Path, Query, State, WebSocketUpgrade};
+use axum::http::HeaderMap;
 use axum::response::IntoResponse;
 use axum::Json;
 use rstify_core::models::{CreateTopicMessage, MessageResponse, Topic};
-use rstify_core::repositories::{MessageRepository, TopicRepository};
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::extractors::upload::{MessageUpload, MAX_FILES_PER_MESSAGE};
+use crate::helpers::attachments::attach_staged_files;
 use crate::routes::messages::ListParams;
+use crate::routes::ntfy_publish::download_and_attach;
 use crate::state::AppState;
 
 use super::management::{check_read_permission, check_write_permission};
 
+/// POST /api/topics/{name}/publish - Publish to a topic, as JSON or as
+/// multipart/form-data with files attached
 #[utoipa::path(
     post,
     path = "/api/topics/{name}/publish",
-    request_body = CreateTopicMessage,
+    request_body(
+        content(
+            (CreateTopicMessage = "application/json"),
+            (CreateTopicMessage = "multipart/form-data")
+        )
+    ),
     responses((status = 200, body = MessageResponse))
 )]
 pub async fn publish_to_topic(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(name): Path<String>,
-    Json(req): Json<CreateTopicMessage>,
+    headers: HeaderMap,
+    MessageUpload { body: req, files }: MessageUpload<CreateTopicMessage>,
 ) -> Result<Json<MessageResponse>, ApiError> {
     let topic = find_topic_by_name(&state, &name).await?;
 
-    check_write_permission(&state, &auth.user, &topic).await?;
+    check_write_permission(&state, &auth, &topic).await?;
 
     if req.message.is_empty() || req.message.len() > 65536 {
         return Err(ApiError::from(rstify_core::error::CoreError::Validation(
@@ -46,6 +57,13 @@ pub async fn publish_to_topic(
         .inbox_threshold
         .load(std::sync::atomic::Ordering::Relaxed);
     let inbox = rstify_core::policy::should_inbox(&topic, req.priority.unwrap_or(5), threshold);
+    // Messages relayed from another server carry their hop count, so our own
+    // relays can tell when they are part of a loop.
+    let relay_extras = headers
+        .get(rstify_jobs::relay::HOPS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(rstify_jobs::relay::parse_hops_header)
+        .map(rstify_jobs::relay::hops_extras);
 
     let msg = state
         .message_repo
@@ -60,13 +78,34 @@ pub async fn publish_to_topic(
             icon_url: req.icon_url.as_deref(),
             actions: actions_json.as_deref(),
             scheduled_for: req.scheduled_for.as_deref(),
+            extras: relay_extras.as_deref(),
             inbox,
             ..Default::default()
         })
         .await
         .map_err(ApiError::from)?;
 
-    let response = msg.to_response(Some(name.clone()));
+    let mut response = msg.to_response(Some(name.clone()));
+    let remote = req.attachments.unwrap_or_default();
+    if !files.is_empty() || !remote.is_empty() {
+        let mut attachments = attach_staged_files(&state, msg.id, files).await?;
+        // Remote attachments are best effort, like ntfy's X-Attach: a dead
+        // link shouldn't lose the message itself.
+        let room = MAX_FILES_PER_MESSAGE.saturating_sub(attachments.len());
+        for remote in remote.iter().take(room) {
+            match download_and_attach(&state, msg.id, &remote.url, remote.name.as_deref()).await {
+                Ok(att) => attachments.push(att),
+                Err(e) => tracing::warn!(
+                    "Failed to download attachment from {}: {}",
+                    remote.url,
+                    e.message
+                ),
+            }
+        }
+        if !attachments.is_empty() {
+            response.attachments = Some(attachments);
+        }
+    }
 
     // Immediate messages deliver now (broadcast + push + outgoing webhooks via the
     // shared path); scheduled messages are delivered later by the scheduled job, so
@@ -85,6 +124,7 @@ pub async fn publish_to_topic(
 
 /// WebSocket for topic subscription
 #[utoipa::path(get, path = "/api/topics/{name}/ws", responses((status = 101, description = "WebSocket upgrade")))]
+#[allow(clippy::collapsible_match)]
 pub async fn topic_websocket(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -93,7 +133,7 @@ pub async fn topic_websocket(
 ) -> Result<impl IntoResponse, ApiError> {
     let topic = find_topic_by_name(&state, &name).await?;
 
-    check_read_permission(&state, &auth.user, &topic).await?;
+    check_read_permission(&state, &auth, &topic).await?;
 
     if !state.connections.can_accept(None).await {
         return Err(ApiError {
@@ -161,7 +201,7 @@ pub async fn topic_json_stream(
 ) -> Result<Json<Vec<MessageResponse>>, ApiError> {
     let topic = find_topic_by_name(&state, &name).await?;
 
-    check_read_permission(&state, &auth.user, &topic).await?;
+    check_read_permission(&state, &auth, &topic).await?;
 
     let limit = params.limit.unwrap_or(100).clamp(1, 500);
     let since = params.since.unwrap_or(0).max(0);
@@ -1,11 +1,19 @@
 use axum::extract::{Path, State};
 use axum::Json;
+use chrono::{NaiveTime, Weekday};
 use rstify_auth::password::{hash_password, verify_password};
-use rstify_core::models::{ChangePassword, CreateUser, UpdateUser, UserResponse};
-use rstify_core::repositories::UserRepository;
+use rstify_core::models::{
+    ChangePassword, CreateQuietHours, CreateUser, EmailPreferences, QuietHours,
+    UpdateEmailPreferences, UpdateUser, UserResponse,
+};
+
+use rstify_core::error::CoreError;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::helpers::audit::{self, snapshot};
+use crate::helpers::ownership::{fetch_or_not_found, verify_ownership};
+use crate::helpers::validation::validate_topic_pattern;
 use crate::state::AppState;
 
 #[utoipa::path(get, path = "/current/user", responses((status = 200, body = UserResponse)))]
@@ -13,6 +21,296 @@ pub async fn current_user(auth: AuthUser) -> Result<Json<UserResponse>, ApiError
     Ok(Json(UserResponse::from(auth.user)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/current/user/email-preferences",
+    responses((status = 200, body = EmailPreferences))
+)]
+pub async fn get_email_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<EmailPreferences>, ApiError> {
+    auth.require_scope("read")?;
+    let prefs = state
+        .user_repo
+        .email_preferences(auth.user.id)
+        .await
+        .map_err(ApiError::from)?
+        .unwrap_or_else(|| EmailPreferences::disabled(auth.user.id));
+    Ok(Json(prefs))
+}
+
+#[utoipa::path(
+    put,
+    path = "/current/user/email-preferences",
+    request_body = UpdateEmailPreferences,
+    responses((status = 200, body = EmailPreferences))
+)]
+pub async fn update_email_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<UpdateEmailPreferences>,
+) -> Result<Json<EmailPreferences>, ApiError> {
+    auth.require_scope("write")?;
+    let invalid = |msg: &str| ApiError::from(CoreError::Validation(msg.to_string()));
+    if req.enabled
+        && auth
+            .user
+            .email
+            .as_deref()
+            .is_none_or(|e| e.trim().is_empty())
+    {
+        return Err(invalid("Set an email address on your account first"));
+    }
+    let min_priority = req.min_priority.unwrap_or(5);
+    if !(0..=10).contains(&min_priority) {
+        return Err(invalid("min_priority must be between 0 and 10"));
+    }
+    for topic in req.topics.iter().flatten() {
+        validate_topic_pattern("topics", topic)?;
+    }
+    for &app_id in req.application_ids.iter().flatten() {
+        let app = fetch_or_not_found("Application", || state.app_repo.find_by_id(app_id)).await?;
+        verify_ownership(&auth, app.user_id, None, "application")?;
+    }
+    let existing = state
+        .user_repo
+        .email_preferences(auth.user.id)
+        .await
+        .map_err(ApiError::from)?;
+    let prefs = EmailPreferences {
+        user_id: auth.user.id,
+        enabled: req.enabled,
+        min_priority,
+        topics: req
+            .topics
+            .as_ref()
+            .map(crate::helpers::json::to_json_string)
+            .transpose()?,
+        application_ids: req
+            .application_ids
+            .as_ref()
+            .map(crate::helpers::json::to_json_string)
+            .transpose()?,
+        updated_at: String::new(),
+    };
+    let saved = state
+        .user_repo
+        .set_email_preferences(&prefs)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "user.email_preferences",
+        "user",
+        auth.user.id,
+        existing.as_ref().and_then(snapshot),
+        snapshot(&saved),
+    )
+    .await;
+    Ok(Json(saved))
+}
+
+#[utoipa::path(
+    get,
+    path = "/current/user/quiet-hours",
+    responses((status = 200, body = Vec<QuietHours>))
+)]
+pub async fn list_quiet_hours(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<QuietHours>>, ApiError> {
+    auth.require_scope("read")?;
+    let schedules = state
+        .client_repo
+        .list_quiet_hours(auth.user.id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(schedules))
+}
+
+#[utoipa::path(
+    post,
+    path = "/current/user/quiet-hours",
+    request_body = CreateQuietHours,
+    responses((status = 200, body = QuietHours))
+)]
+pub async fn create_quiet_hours(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<CreateQuietHours>,
+) -> Result<Json<QuietHours>, ApiError> {
+    auth.require_scope("write")?;
+    let schedule = quiet_hours_from_request(&state, &auth, 0, req).await?;
+    let saved = state
+        .client_repo
+        .save_quiet_hours(&schedule)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "user.quiet_hours.create",
+        "quiet_hours",
+        saved.id,
+        None,
+        snapshot(&saved),
+    )
+    .await;
+    Ok(Json(saved))
+}
+
+#[utoipa::path(
+    put,
+    path = "/current/user/quiet-hours/{id}",
+    request_body = CreateQuietHours,
+    responses((status = 200, body = QuietHours))
+)]
+pub async fn update_quiet_hours(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<CreateQuietHours>,
+) -> Result<Json<QuietHours>, ApiError> {
+    auth.require_scope("write")?;
+    let existing = own_quiet_hours(&state, &auth, id).await?;
+    let schedule = quiet_hours_from_request(&state, &auth, id, req).await?;
+    let saved = state
+        .client_repo
+        .save_quiet_hours(&schedule)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "user.quiet_hours.update",
+        "quiet_hours",
+        id,
+        snapshot(&existing),
+        snapshot(&saved),
+    )
+    .await;
+    Ok(Json(saved))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/current/user/quiet-hours/{id}",
+    responses((status = 200))
+)]
+pub async fn delete_quiet_hours(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    auth.require_scope("write")?;
+    let existing = own_quiet_hours(&state, &auth, id).await?;
+    state
+        .client_repo
+        .delete_quiet_hours(id)
+        .await
+        .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "user.quiet_hours.delete",
+        "quiet_hours",
+        id,
+        snapshot(&existing),
+        None,
+    )
+    .await;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+/// A schedule of the current user's; anyone else's is reported missing.
+async fn own_quiet_hours(
+    state: &AppState,
+    auth: &AuthUser,
+    id: i64,
+) -> Result<QuietHours, ApiError> {
+    let schedule =
+        fetch_or_not_found("Quiet hours", || state.client_repo.find_quiet_hours(id)).await?;
+    if schedule.user_id != auth.user.id {
+        return Err(ApiError::from(CoreError::NotFound(
+            "Quiet hours not found".to_string(),
+        )));
+    }
+    Ok(schedule)
+}
+
+/// Validate a schedule request and build the row to save under `id`
+/// (0 for a new one). Weekdays are stored as `mon`..`sun`.
+async fn quiet_hours_from_request(
+    state: &AppState,
+    auth: &AuthUser,
+    id: i64,
+    req: CreateQuietHours,
+) -> Result<QuietHours, ApiError> {
+    let invalid = |msg: &str| ApiError::from(CoreError::Validation(msg.to_string()));
+    let parse = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").is_ok();
+    if !parse(&req.start_time) || !parse(&req.end_time) {
+        return Err(invalid("start_time and end_time must be times as HH:MM"));
+    }
+    if req.start_time == req.end_time {
+        return Err(invalid("start_time and end_time must differ"));
+    }
+    let timezone = req.timezone.unwrap_or_else(|| "UTC".to_string());
+    if !rstify_core::policy::is_valid_timezone(&timezone) {
+        return Err(invalid(
+            "timezone must be an IANA timezone name such as Europe/Berlin",
+        ));
+    }
+    if req
+        .override_priority
+        .is_some_and(|p| !(0..=10).contains(&p))
+    {
+        return Err(invalid("override_priority must be between 0 and 10"));
+    }
+    let days = match req.days {
+        None => None,
+        Some(days) => {
+            let mut parsed = Vec::new();
+            for day in &days {
+                let weekday: Weekday = day.parse().map_err(|_| {
+                    invalid("days must be weekday names such as mon, tue or wednesday")
+                })?;
+                let name = weekday.to_string().to_lowercase();
+                if !parsed.contains(&name) {
+                    parsed.push(name);
+                }
+            }
+            if parsed.is_empty() {
+                return Err(invalid(
+                    "days must name at least one weekday; omit it for every day",
+                ));
+            }
+            Some(crate::helpers::json::to_json_string(&parsed)?)
+        }
+    };
+    if let Some(client_id) = req.client_id {
+        let client =
+            fetch_or_not_found("Client", || state.client_repo.find_by_id(client_id)).await?;
+        if client.user_id != auth.user.id {
+            return Err(invalid("client_id must be one of your own clients"));
+        }
+    }
+    Ok(QuietHours {
+        id,
+        user_id: auth.user.id,
+        client_id: req.client_id,
+        enabled: req.enabled.unwrap_or(true),
+        start_time: req.start_time,
+        end_time: req.end_time,
+        days,
+        timezone,
+        override_priority: req.override_priority,
+        created_at: String::new(),
+        updated_at: String::new(),
+    })
+}
+
 #[utoipa::path(
     post,
     path = "/current/user/password",
@@ -56,6 +354,16 @@ pub async fn change_password(
         .update_password(auth.user.id, &new_hash)
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "user.password_change",
+        "user",
+        auth.user.id,
+        None,
+        None,
+    )
+    .await;
 
     Ok(Json(serde_json::json!({"success": true})))
 }
@@ -115,7 +423,18 @@ pub async fn create_user(
         .await
         .map_err(ApiError::from)?;
 
-    Ok(Json(UserResponse::from(user)))
+    let response = UserResponse::from(user);
+    audit::record(
+        &state,
+        &auth,
+        "user.create",
+        "user",
+        response.id,
+        None,
+        snapshot(&response),
+    )
+    .await;
+    Ok(Json(response))
 }
 
 #[utoipa::path(
@@ -131,6 +450,7 @@ pub async fn update_user(
     Json(req): Json<UpdateUser>,
 ) -> Result<Json<UserResponse>, ApiError> {
     auth.require_admin()?;
+    let existing = fetch_or_not_found("User", || state.user_repo.find_by_id(id)).await?;
 
     let user = state
         .user_repo
@@ -143,7 +463,18 @@ pub async fn update_user(
         .await
         .map_err(ApiError::from)?;
 
-    Ok(Json(UserResponse::from(user)))
+    let response = UserResponse::from(user);
+    audit::record(
+        &state,
+        &auth,
+        "user.update",
+        "user",
+        id,
+        snapshot(&UserResponse::from(existing)),
+        snapshot(&response),
+    )
+    .await;
+    Ok(Json(response))
 }
 
 #[utoipa::path(delete, path = "/user/{id}", responses((status = 200)))]
@@ -160,6 +491,48 @@ pub async fn delete_user(
         )));
     }
 
+    let existing = fetch_or_not_found("User", || state.user_repo.find_by_id(id)).await?;
     state.user_repo.delete(id).await.map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "user.delete",
+        "user",
+        id,
+        snapshot(&UserResponse::from(existing)),
+        None,
+    )
+    .await;
     Ok(Json(serde_json::json!({"success": true})))
 }
+
+/// POST /user/{id}/unlock - Lift a login lockout and reset the failure count
+#[utoipa::path(post, path = "/user/{id}/unlock", responses((status = 200, body = UserResponse)))]
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<UserResponse>, ApiError> {
+    auth.require_admin()?;
+    let existing = fetch_or_not_found("User", || state.user_repo.find_by_id(id)).await?;
+
+    state
+        .user_repo
+        .clear_login_failures(id)
+        .await
+        .map_err(ApiError::from)?;
+    let user = fetch_or_not_found("User", || state.user_repo.find_by_id(id)).await?;
+
+    let response = UserResponse::from(user);
+    audit::record(
+        &state,
+        &auth,
+        "user.unlock",
+        "user",
+        id,
+        snapshot(&UserResponse::from(existing)),
+        snapshot(&response),
+    )
+    .await;
+    Ok(Json(response))
+}
@@ -1,7 +1,6 @@
 use axum::extract::{Path, State};
 use axum::Json;
 use rstify_core::models::{CreateWebhookVariable, UpdateWebhookVariable, WebhookVariable};
-use rstify_core::repositories::WebhookVariableRepository;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
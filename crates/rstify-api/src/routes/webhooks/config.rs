@@ -4,13 +4,14 @@ use axum::http::HeaderMap;
 use axum::Json;
 use rstify_auth::tokens::generate_webhook_token;
 use rstify_core::error::CoreError;
-use rstify_core::models::{CreateWebhookConfig, UpdateWebhookConfig, WebhookConfig};
-use rstify_core::repositories::{MessageRepository, TopicRepository};
+use rstify_core::models::{CreateWebhookConfig, UpdateWebhookConfig, WebhookConfig, WebhookHealth};
+use rstify_core::repositories::NewWebhookDelivery;
 use serde::Serialize;
 use ts_rs::TS;
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
+use crate::helpers::audit::{self, snapshot};
 use crate::state::AppState;
 
 #[utoipa::path(
@@ -64,6 +65,28 @@ pub async fn create_webhook(
 
     let direction = req.direction.as_deref().unwrap_or("incoming");
 
+    // Outgoing webhooks either send the raw message / body template (`custom`)
+    // or render a chat service's own payload.
+    if direction == "outgoing" {
+        let types = rstify_jobs::chat::OUTGOING_WEBHOOK_TYPES;
+        if !types.contains(&req.webhook_type.as_str()) {
+            return Err(ApiError::from(CoreError::Validation(format!(
+                "Unknown outgoing webhook type '{}'; expected one of: {}",
+                req.webhook_type,
+                types.join(", ")
+            ))));
+        }
+        validate_chat_url(&req.webhook_type, req.target_url.as_deref())?;
+    }
+    validate_notify_url(
+        &state,
+        auth.user.id,
+        direction,
+        &req.webhook_type,
+        req.notify_url.as_deref().unwrap_or(""),
+    )
+    .await?;
+
     // Incoming webhooks must deliver to exactly one target: the messages
     // table CHECK requires exactly one of application_id/topic_id, so a
     // config with neither (or both) would 500 on every delivery.
@@ -106,12 +129,85 @@ pub async fn create_webhook(
             req.follow_redirects,
             req.group_name.as_deref(),
             req.secret.as_deref(),
+            req.notify_url.as_deref().filter(|u| !u.is_empty()),
         )
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "webhook.create",
+        "webhook",
+        config.id,
+        None,
+        snapshot(&config),
+    )
+    .await;
     Ok(Json(config))
 }
 
+/// Check a notification URL parses once the owner's variables are filled in,
+/// and that its transport is usable: email needs SMTP, and a literal HTTP
+/// destination must pass the SSRF guard. An empty URL clears it.
+async fn validate_notify_url(
+    state: &AppState,
+    user_id: i64,
+    direction: &str,
+    webhook_type: &str,
+    notify_url: &str,
+) -> Result<(), ApiError> {
+    let relay = direction == "outgoing" && webhook_type == "relay";
+    if notify_url.is_empty() {
+        if relay {
+            return Err(ApiError::from(CoreError::Validation(
+                "relay webhooks need an ntfy, gotify or rstify notify_url".to_string(),
+            )));
+        }
+        return Ok(());
+    }
+    if direction != "outgoing" {
+        return Err(ApiError::from(CoreError::Validation(
+            "notify_url only applies to outgoing webhooks".to_string(),
+        )));
+    }
+    let target = rstify_jobs::outgoing_webhooks::resolve_notify_url(
+        &state.repositories(),
+        user_id,
+        notify_url,
+    )
+    .await
+    .map_err(|e| ApiError::from(CoreError::Validation(e)))?;
+    if relay && !target.is_relay() {
+        return Err(ApiError::from(CoreError::Validation(
+            "relay webhooks forward to ntfy://, gotify:// or rstify:// URLs".to_string(),
+        )));
+    }
+    match target.target_url() {
+        None if state.email.is_none() => Err(ApiError::from(CoreError::Validation(
+            "mailto:// notification URLs need SMTP to be configured".to_string(),
+        ))),
+        Some(url) if !notify_url.contains("{{") => rstify_jobs::ssrf::validate_outbound_url(url)
+            .await
+            .map(|_| ())
+            .map_err(|e| ApiError::from(CoreError::Validation(e.0))),
+        _ => Ok(()),
+    }
+}
+
+/// Check a chat webhook's URL has what the service needs. Templated URLs are
+/// only known at delivery time.
+fn validate_chat_url(webhook_type: &str, url: Option<&str>) -> Result<(), ApiError> {
+    match (
+        rstify_jobs::chat::ChatKind::from_webhook_type(webhook_type),
+        url,
+    ) {
+        (Some(kind), Some(url)) if !url.contains("{{") => kind
+            .validate_url(url)
+            .map_err(|e| ApiError::from(CoreError::Validation(e))),
+        _ => Ok(()),
+    }
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema, TS)]
 #[ts(export)]
 pub struct WebhookConfigWithHealth {
@@ -123,20 +219,6 @@ pub struct WebhookConfigWithHealth {
     pub recent_durations: Option<Vec<i64>>,
 }
 
-#[derive(sqlx::FromRow)]
-struct DurationRow {
-    webhook_config_id: i64,
-    duration_ms: i64,
-}
-
-#[derive(sqlx::FromRow)]
-struct WebhookHealthRow {
-    webhook_config_id: i64,
-    last_delivery_at: Option<String>,
-    last_delivery_success: Option<bool>,
-    recent_success_rate: Option<f64>,
-}
-
 #[utoipa::path(get, path = "/api/webhooks", responses((status = 200, body = Vec<WebhookConfigWithHealth>)))]
 pub async fn list_webhooks(
     State(state): State<AppState>,
@@ -148,67 +230,29 @@ pub async fn list_webhooks(
         .await
         .map_err(ApiError::from)?;
 
-    // Fetch health data for all webhooks in one query
+    // Fetch health over the last 10 deliveries and durations of the last 20
     let config_ids: Vec<i64> = configs.iter().map(|c| c.id).collect();
-    let health_data: Vec<WebhookHealthRow> = if config_ids.is_empty() {
-        vec![]
-    } else {
-        let placeholders: Vec<String> = config_ids.iter().map(|_| "?".to_string()).collect();
-        let sql = format!(
-            r#"SELECT
-                webhook_config_id,
-                MAX(attempted_at) as last_delivery_at,
-                (SELECT success FROM webhook_delivery_log w2 WHERE w2.webhook_config_id = w1.webhook_config_id ORDER BY attempted_at DESC LIMIT 1) as last_delivery_success,
-                CAST(SUM(CASE WHEN success THEN 1 ELSE 0 END) AS REAL) / COUNT(*) as recent_success_rate
-            FROM (
-                SELECT webhook_config_id, attempted_at, success,
-                    ROW_NUMBER() OVER (PARTITION BY webhook_config_id ORDER BY attempted_at DESC) as rn
-                FROM webhook_delivery_log
-                WHERE webhook_config_id IN ({})
-            ) w1
-            WHERE rn <= 10
-            GROUP BY webhook_config_id"#,
-            placeholders.join(",")
-        );
-        let mut query = sqlx::query_as::<_, WebhookHealthRow>(&sql);
-        for id in &config_ids {
-            query = query.bind(id);
-        }
-        query.fetch_all(&state.pool).await.unwrap_or_default()
-    };
-
-    // Fetch recent durations (last 20 per webhook)
-    let duration_data: Vec<DurationRow> = if config_ids.is_empty() {
-        vec![]
-    } else {
-        let placeholders: Vec<String> = config_ids.iter().map(|_| "?".to_string()).collect();
-        let sql = format!(
-            r#"SELECT webhook_config_id, duration_ms FROM (
-                SELECT webhook_config_id, duration_ms,
-                    ROW_NUMBER() OVER (PARTITION BY webhook_config_id ORDER BY attempted_at DESC) as rn
-                FROM webhook_delivery_log
-                WHERE webhook_config_id IN ({})
-            ) WHERE rn <= 20
-            ORDER BY webhook_config_id, rn"#,
-            placeholders.join(",")
-        );
-        let mut query = sqlx::query_as::<_, DurationRow>(&sql);
-        for id in &config_ids {
-            query = query.bind(id);
-        }
-        query.fetch_all(&state.pool).await.unwrap_or_default()
-    };
+    let health_data = state
+        .webhook_delivery_repo
+        .health(&config_ids, 10)
+        .await
+        .unwrap_or_default();
+    let duration_data = state
+        .webhook_delivery_repo
+        .recent_durations(&config_ids, 20)
+        .await
+        .unwrap_or_default();
 
     let mut durations_map: std::collections::HashMap<i64, Vec<i64>> =
         std::collections::HashMap::new();
-    for row in &duration_data {
+    for (webhook_config_id, duration_ms) in duration_data {
         durations_map
-            .entry(row.webhook_config_id)
+            .entry(webhook_config_id)
             .or_default()
-            .push(row.duration_ms);
+            .push(duration_ms);
     }
 
-    let health_map: std::collections::HashMap<i64, &WebhookHealthRow> = health_data
+    let health_map: std::collections::HashMap<i64, &WebhookHealth> = health_data
         .iter()
         .map(|h| (h.webhook_config_id, h))
         .collect();
@@ -270,6 +314,20 @@ pub async fn update_webhook(
         }
     }
 
+    if existing.direction == "outgoing" {
+        validate_chat_url(&existing.webhook_type, req.target_url.as_deref())?;
+    }
+    if let Some(ref notify_url) = req.notify_url {
+        validate_notify_url(
+            &state,
+            existing.user_id,
+            &existing.direction,
+            &existing.webhook_type,
+            notify_url,
+        )
+        .await?;
+    }
+
     let template_json = req
         .template
         .as_ref()
@@ -315,9 +373,20 @@ pub async fn update_webhook(
             req.follow_redirects,
             req.group_name.as_deref(),
             req.secret.as_deref(),
+            req.notify_url.as_deref(),
         )
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "webhook.update",
+        "webhook",
+        id,
+        snapshot(&existing),
+        snapshot(&config),
+    )
+    .await;
     Ok(Json(config))
 }
 
@@ -349,6 +418,16 @@ pub async fn delete_webhook(
         .delete_webhook_config(id)
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "webhook.delete",
+        "webhook",
+        id,
+        snapshot(&existing),
+        None,
+    )
+    .await;
     Ok(Json(serde_json::json!({"success": true})))
 }
 
@@ -356,7 +435,7 @@ pub async fn delete_webhook(
 /// answer "is GitHub actually reaching my server?" — including rejections,
 /// which are the debugging gold. `note` lands in response_body_preview.
 async fn log_incoming_delivery(
-    pool: &sqlx::SqlitePool,
+    state: &AppState,
     webhook_config_id: i64,
     message_id: Option<i64>,
     status_code: i32,
@@ -364,18 +443,15 @@ async fn log_incoming_delivery(
     started: std::time::Instant,
     success: bool,
 ) {
-    if let Err(e) = sqlx::query(
-        "INSERT INTO webhook_delivery_log (webhook_config_id, message_id, status_code, response_body_preview, duration_ms, success) VALUES (?, ?, ?, ?, ?, ?)",
-    )
-    .bind(webhook_config_id)
-    .bind(message_id)
-    .bind(status_code)
-    .bind(note)
-    .bind(started.elapsed().as_millis() as i64)
-    .bind(success)
-    .execute(pool)
-    .await
-    {
+    let delivery = NewWebhookDelivery {
+        webhook_config_id,
+        message_id,
+        status_code: Some(status_code),
+        response_body_preview: Some(note),
+        duration_ms: started.elapsed().as_millis() as i64,
+        success,
+    };
+    if let Err(e) = state.webhook_delivery_repo.record(&delivery).await {
         tracing::error!("Failed to log incoming webhook delivery: {}", e);
     }
 }
@@ -403,7 +479,7 @@ pub async fn receive_webhook(
 
     if !config.enabled {
         log_incoming_delivery(
-            &state.pool,
+            &state,
             config.id,
             None,
             403,
@@ -421,7 +497,7 @@ pub async fn receive_webhook(
     // one of application_id/topic_id, so fail clearly instead of a DB 500.
     if config.target_topic_id.is_some() == config.target_application_id.is_some() {
         log_incoming_delivery(
-            &state.pool,
+            &state,
             config.id,
             None,
             422,
@@ -467,7 +543,7 @@ pub async fn receive_webhook(
         };
         if !verified {
             log_incoming_delivery(
-                &state.pool,
+                &state,
                 config.id,
                 None,
                 403,
@@ -486,7 +562,7 @@ pub async fn receive_webhook(
         Ok(v) => v,
         Err(_) => {
             log_incoming_delivery(
-                &state.pool,
+                &state,
                 config.id,
                 None,
                 400,
@@ -636,7 +712,7 @@ pub async fn receive_webhook(
     }
 
     log_incoming_delivery(
-        &state.pool,
+        &state,
         config.id,
         Some(msg.id),
         200,
@@ -684,6 +760,16 @@ pub async fn regenerate_webhook_token(
         .regenerate_webhook_token(id, &new_token)
         .await
         .map_err(ApiError::from)?;
+    audit::record(
+        &state,
+        &auth,
+        "webhook.regenerate_token",
+        "webhook",
+        id,
+        None,
+        None,
+    )
+    .await;
 
     Ok(Json(updated))
 }
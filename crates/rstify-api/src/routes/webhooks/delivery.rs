@@ -2,7 +2,6 @@ use axum::extract::{Path, Query, State};
 use axum::http::HeaderMap;
 use axum::Json;
 use rstify_core::models::{MessageResponse, WebhookDeliveryLog};
-use rstify_core::repositories::MessageRepository;
 use rstify_jobs::outgoing_webhooks::fire_single_outgoing_webhook;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
@@ -53,32 +52,11 @@ pub async fn list_webhook_deliveries(
     let limit = params.limit.unwrap_or(20).clamp(1, 100);
     let offset = params.offset.unwrap_or(0).max(0);
 
-    // Build dynamic WHERE clause
-    let mut where_clauses = vec!["webhook_config_id = ?".to_string()];
-    if params.success.is_some() {
-        where_clauses.push("success = ?".to_string());
-    }
-    if params.since.is_some() {
-        where_clauses.push("attempted_at >= ?".to_string());
-    }
-    let sql = format!(
-        "SELECT * FROM webhook_delivery_log WHERE {} ORDER BY attempted_at DESC LIMIT ? OFFSET ?",
-        where_clauses.join(" AND ")
-    );
-
-    let mut query = sqlx::query_as::<_, WebhookDeliveryLog>(&sql).bind(id);
-    if let Some(success) = params.success {
-        query = query.bind(success);
-    }
-    if let Some(ref since) = params.since {
-        query = query.bind(since);
-    }
-    let logs = query
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.pool)
+    let logs = state
+        .webhook_delivery_repo
+        .list_for_config(id, params.success, params.since.as_deref(), limit, offset)
         .await
-        .map_err(|e| ApiError::from(rstify_db::map_sqlx_err(e)))?;
+        .map_err(ApiError::from)?;
 
     Ok(Json(logs))
 }
@@ -169,7 +147,13 @@ pub async fn test_webhook(
             date: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
         };
 
-        let result = fire_single_outgoing_webhook(&state.pool, &existing, &test_message).await;
+        let result = fire_single_outgoing_webhook(
+            &state.repositories(),
+            state.email.as_ref(),
+            &existing,
+            &test_message,
+        )
+        .await;
 
         match result {
             Ok(detail) => {
@@ -1,8 +1,6 @@
 use axum::extract::{Path, State};
 use axum::response::sse::{Event, KeepAlive, Sse};
 use futures::stream::Stream;
-use rstify_auth::acl::topic_matches;
-use rstify_core::repositories::TopicRepository;
 use std::convert::Infallible;
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
@@ -29,24 +27,7 @@ pub async fn topic_sse(
             )))
         })?;
 
-    // Check read permission
-    if !auth.user.is_admin && !topic.everyone_read && topic.owner_id != Some(auth.user.id) {
-        let permissions = state
-            .topic_repo
-            .list_permissions_for_user(auth.user.id)
-            .await
-            .map_err(ApiError::from)?;
-
-        let has_read = permissions
-            .iter()
-            .any(|p| p.can_read && topic_matches(&p.topic_pattern, &topic.name));
-
-        if !has_read {
-            return Err(ApiError::from(rstify_core::error::CoreError::Forbidden(
-                "No read permission for this topic".to_string(),
-            )));
-        }
-    }
+    crate::routes::topics::check_read_permission(&state, &auth, &topic).await?;
 
     if !state.connections.can_accept(None).await {
         return Err(ApiError {
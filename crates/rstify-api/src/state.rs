@@ -1,12 +1,18 @@
-use rstify_db::repositories::{
-    SqliteApplicationRepo, SqliteClientRepo, SqliteMessageRepo, SqliteTopicRepo, SqliteUserRepo,
-    SqliteWebhookVariableRepo,
+use rstify_core::repositories::{
+    ApplicationRepository, AuditRepository, ClientRepository, EmailRepository, GroupRepository,
+    MessageRepository, Repositories, SettingsRepository, TopicRepository, UserRepository,
+    WebhookDeliveryRepository, WebhookVariableRepository,
 };
-use sqlx::SqlitePool;
+use rstify_db::pool::Database;
+use rstify_storage::BlobStores;
 use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::Notify;
 
+use crate::attachment_links::AttachmentLinks;
+use crate::email::EmailRateCap;
 use crate::fcm::FcmClient;
+use crate::webpush::WebPushClient;
 use crate::websocket::manager::ConnectionManager;
 
 #[derive(Default)]
@@ -26,46 +32,99 @@ impl Metrics {
 
 #[derive(Clone)]
 pub struct AppState {
-    pub user_repo: SqliteUserRepo,
-    pub app_repo: SqliteApplicationRepo,
-    pub client_repo: SqliteClientRepo,
-    pub topic_repo: SqliteTopicRepo,
-    pub message_repo: SqliteMessageRepo,
-    pub webhook_variable_repo: SqliteWebhookVariableRepo,
+    pub user_repo: Arc<dyn UserRepository>,
+    pub app_repo: Arc<dyn ApplicationRepository>,
+    pub client_repo: Arc<dyn ClientRepository>,
+    pub topic_repo: Arc<dyn TopicRepository>,
+    pub group_repo: Arc<dyn GroupRepository>,
+    pub message_repo: Arc<dyn MessageRepository>,
+    pub webhook_variable_repo: Arc<dyn WebhookVariableRepository>,
+    pub webhook_delivery_repo: Arc<dyn WebhookDeliveryRepository>,
+    pub audit_repo: Arc<dyn AuditRepository>,
+    pub settings_repo: Arc<dyn SettingsRepository>,
+    pub email_repo: Arc<dyn EmailRepository>,
     pub jwt_secret: String,
     pub upload_dir: String,
     pub max_upload_size: usize,
+    /// Combined limit for all files of one multipart message.
+    pub max_upload_total: usize,
+    /// Where backup archives are written and listed from.
+    pub backup_dir: String,
+    /// Where attachment bytes live; defaults to files under `upload_dir`.
+    pub blob_stores: BlobStores,
+    /// Redirect attachment downloads to presigned store URLs when the store
+    /// supports them, instead of proxying the bytes.
+    pub presigned_downloads: bool,
+    /// Signs token-free attachment links for sharing, email and push.
+    pub attachment_links: AttachmentLinks,
+    /// Wakes the thumbnail worker after an image upload.
+    pub thumbnail_trigger: Arc<Notify>,
     pub connections: Arc<ConnectionManager>,
-    pub pool: SqlitePool,
+    pub db: Database,
     pub fcm: Option<Arc<FcmClient>>,
+    pub webpush: Option<Arc<WebPushClient>>,
     pub metrics: Arc<Metrics>,
     pub inbox_threshold: Arc<AtomicI32>,
-    pub email_config: Option<rstify_jobs::email::EmailConfig>,
+    /// Outgoing mail queue; `None` when SMTP is not configured.
+    pub email: Option<rstify_jobs::email::EmailOutbox>,
+    /// Caps preference-driven emails per user.
+    pub email_rate_cap: Arc<EmailRateCap>,
 }
 
 impl AppState {
     pub fn new(
-        pool: SqlitePool,
+        db: Database,
         jwt_secret: String,
         upload_dir: String,
         max_upload_size: usize,
     ) -> Self {
+        let repos = db.repositories();
         Self {
-            user_repo: SqliteUserRepo::new(pool.clone()),
-            app_repo: SqliteApplicationRepo::new(pool.clone()),
-            client_repo: SqliteClientRepo::new(pool.clone()),
-            topic_repo: SqliteTopicRepo::new(pool.clone()),
-            message_repo: SqliteMessageRepo::new(pool.clone()),
-            webhook_variable_repo: SqliteWebhookVariableRepo::new(pool.clone()),
+            user_repo: repos.users,
+            app_repo: repos.applications,
+            client_repo: repos.clients,
+            topic_repo: repos.topics,
+            group_repo: repos.groups,
+            message_repo: repos.messages,
+            webhook_variable_repo: repos.webhook_variables,
+            webhook_delivery_repo: repos.webhook_deliveries,
+            audit_repo: repos.audit,
+            settings_repo: repos.settings,
+            email_repo: repos.emails,
+            attachment_links: AttachmentLinks::new(&jwt_secret),
             jwt_secret,
+            blob_stores: BlobStores::local(&upload_dir),
+            presigned_downloads: true,
+            thumbnail_trigger: Arc::new(Notify::new()),
             upload_dir,
             max_upload_size,
+            max_upload_total: max_upload_size.saturating_mul(4),
+            backup_dir: "./backups".to_string(),
             connections: Arc::new(ConnectionManager::new()),
-            pool,
+            db,
             fcm: None,
+            webpush: None,
             metrics: Arc::new(Metrics::default()),
             inbox_threshold: Arc::new(AtomicI32::new(5)),
-            email_config: None,
+            email: None,
+            email_rate_cap: Arc::new(EmailRateCap::new(crate::email::DEFAULT_MAX_PER_HOUR)),
+        }
+    }
+
+    /// The repositories as one bundle, for the background jobs.
+    pub fn repositories(&self) -> Repositories {
+        Repositories {
+            users: self.user_repo.clone(),
+            applications: self.app_repo.clone(),
+            clients: self.client_repo.clone(),
+            topics: self.topic_repo.clone(),
+            groups: self.group_repo.clone(),
+            messages: self.message_repo.clone(),
+            webhook_variables: self.webhook_variable_repo.clone(),
+            webhook_deliveries: self.webhook_delivery_repo.clone(),
+            audit: self.audit_repo.clone(),
+            settings: self.settings_repo.clone(),
+            emails: self.email_repo.clone(),
         }
     }
 
@@ -74,8 +133,45 @@ impl AppState {
         self
     }
 
-    pub fn with_email_config(mut self, config: rstify_jobs::email::EmailConfig) -> Self {
-        self.email_config = Some(config);
+    pub fn with_web_push(mut self, webpush: WebPushClient) -> Self {
+        self.webpush = Some(Arc::new(webpush));
+        self
+    }
+
+    pub fn with_blob_stores(mut self, stores: BlobStores, presigned_downloads: bool) -> Self {
+        self.blob_stores = stores;
+        self.presigned_downloads = presigned_downloads;
+        self
+    }
+
+    pub fn with_max_upload_total(mut self, max_upload_total: usize) -> Self {
+        self.max_upload_total = max_upload_total;
+        self
+    }
+
+    /// Public URL of the server, so signed attachment links are absolute.
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.attachment_links = self.attachment_links.with_base_url(base_url);
+        self
+    }
+
+    pub fn with_backup_dir(mut self, backup_dir: String) -> Self {
+        self.backup_dir = backup_dir;
+        self
+    }
+
+    pub fn with_thumbnail_trigger(mut self, trigger: Arc<Notify>) -> Self {
+        self.thumbnail_trigger = trigger;
+        self
+    }
+
+    pub fn with_email(mut self, outbox: rstify_jobs::email::EmailOutbox) -> Self {
+        self.email = Some(outbox);
+        self
+    }
+
+    pub fn with_email_rate_cap(mut self, max_per_hour: u32) -> Self {
+        self.email_rate_cap = Arc::new(EmailRateCap::new(max_per_hour));
         self
     }
 }
@@ -1,3 +1,5 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rstify_storage::ByteRange;
 use uuid::Uuid;
 
 // SSRF validation for outbound URLs lives in `rstify_jobs::ssrf` so that it can
@@ -21,3 +23,178 @@ pub fn sanitize_filename(raw: &str) -> String {
         sanitized
     }
 }
+
+/// How many leading bytes of an upload are kept for content sniffing.
+pub const SNIFF_LEN: usize = 8192;
+
+/// Determine an upload's content type from its leading bytes rather than
+/// trusting the client. Binary formats are recognised by their signature. For
+/// text, the filename may narrow `text/plain` to a more specific text type, but
+/// never turns text into something a browser would execute or render.
+pub fn sniff_content_type(head: &[u8], filename: &str) -> String {
+    // Text-like signatures (HTML, XML, shell scripts) are served as plain text.
+    if let Some(kind) = infer::get(head).filter(|k| k.matcher_type() != infer::MatcherType::Text) {
+        return kind.mime_type().to_string();
+    }
+    if looks_like_text(head) {
+        let guessed = mime_guess::from_path(filename).first();
+        return match guessed {
+            Some(m)
+                if matches!(
+                    m.essence_str(),
+                    "text/csv" | "text/markdown" | "text/x-markdown" | "application/json"
+                ) =>
+            {
+                m.essence_str().to_string()
+            }
+            _ => "text/plain".to_string(),
+        };
+    }
+    "application/octet-stream".to_string()
+}
+
+/// UTF-8 without NUL bytes, allowing a multi-byte character cut off at the end
+/// of a truncated sniff buffer.
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && head.len() - e.valid_up_to() < 4,
+    }
+}
+
+/// What a `Range` request header asks for, against a blob of known size.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable range: serve the whole blob. Malformed and multi-range
+    /// headers land here too, which RFC 9110 permits.
+    Full,
+    Partial(ByteRange),
+    /// Well-formed, but starts beyond the end; answer 416.
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=` range (`a-b`, `a-` or `-suffix`).
+pub fn parse_range(header: &str, size: u64) -> RangeRequest {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return RangeRequest::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        // Suffix range: the last `end` bytes.
+        return match end.parse::<u64>() {
+            Ok(0) => RangeRequest::Unsatisfiable,
+            Ok(_) if size == 0 => RangeRequest::Unsatisfiable,
+            Ok(n) => RangeRequest::Partial(ByteRange {
+                start: size.saturating_sub(n),
+                end: size - 1,
+            }),
+            Err(_) => RangeRequest::Full,
+        };
+    }
+    let Ok(start) = start.parse::<u64>() else {
+        return RangeRequest::Full;
+    };
+    let end = match end {
+        "" => u64::MAX,
+        end => match end.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return RangeRequest::Full,
+        },
+    };
+    if start >= size {
+        return RangeRequest::Unsatisfiable;
+    }
+    RangeRequest::Partial(ByteRange {
+        start,
+        end: end.min(size - 1),
+    })
+}
+
+/// Parse a timestamp as stored in the database: SQLite's bare
+/// `YYYY-MM-DD HH:MM:SS` (UTC) or RFC 3339.
+pub fn parse_db_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+/// Format a time as an HTTP date (`Sun, 06 Nov 1994 08:49:37 GMT`).
+pub fn http_date(dt: DateTime<Utc>) -> String {
+    dt.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parse an HTTP date from a request header.
+pub fn parse_http_date(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(s.trim())
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffing_ignores_misleading_names() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(sniff_content_type(png, "notes.txt"), "image/png");
+        assert_eq!(
+            sniff_content_type(b"<html><script>", "page.html"),
+            "text/plain"
+        );
+        assert_eq!(
+            sniff_content_type(b"{\"a\": 1}", "data.json"),
+            "application/json"
+        );
+        assert_eq!(
+            sniff_content_type(b"\0\x01\x02", "photo.jpg"),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn truncated_utf8_is_still_text() {
+        let text = "grüße".as_bytes();
+        assert!(looks_like_text(&text[..text.len() - 1]));
+        assert!(!looks_like_text(b"\xff\xfe junk"));
+    }
+
+    #[test]
+    fn range_forms() {
+        let partial = |start, end| RangeRequest::Partial(ByteRange { start, end });
+        assert_eq!(parse_range("bytes=0-99", 1000), partial(0, 99));
+        assert_eq!(parse_range("bytes=900-", 1000), partial(900, 999));
+        assert_eq!(parse_range("bytes=-100", 1000), partial(900, 999));
+        assert_eq!(parse_range("bytes=-5000", 1000), partial(0, 999));
+        assert_eq!(parse_range("bytes=990-2000", 1000), partial(990, 999));
+        assert_eq!(
+            parse_range("bytes=1000-", 1000),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(parse_range("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=5-1", 1000), RangeRequest::Full);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), RangeRequest::Full);
+        assert_eq!(parse_range("items=0-1", 1000), RangeRequest::Full);
+    }
+
+    #[test]
+    fn http_dates_round_trip() {
+        let stored = parse_db_timestamp("2026-03-29 04:31:12").unwrap();
+        assert_eq!(http_date(stored), "Sun, 29 Mar 2026 04:31:12 GMT");
+        assert_eq!(parse_http_date(&http_date(stored)), Some(stored));
+        assert_eq!(parse_db_timestamp("2026-03-29T04:31:12Z"), Some(stored));
+    }
+}
@@ -0,0 +1,437 @@
+//! Web Push (RFC 8030) for browsers: VAPID authentication (RFC 8292) and
+//! `aes128gcm` payload encryption (RFC 8291).
+//!
+//! The VAPID key pair is generated on first start and kept in the settings
+//! table, so it survives restarts and travels with backups. Rotating it would
+//! invalidate every browser subscription.
+
+use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hkdf::Hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use ring::{aead, agreement};
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use rstify_core::error::CoreError;
+use rstify_core::models::{Client, MessageResponse};
+use rstify_core::repositories::{ClientRepository, NewPushDelivery, SettingsRepository};
+use rstify_jobs::ssrf;
+
+/// Settings key holding the VAPID private key (PKCS#8, base64url).
+pub const VAPID_KEY_SETTING: &str = "vapid_private_key";
+
+/// How long the push service keeps an undelivered message.
+const TTL_SECS: u64 = 24 * 60 * 60;
+/// RFC 8188 record size; payloads always fit in one record.
+const RECORD_SIZE: u32 = 4096;
+/// Upper bound on the message text in a payload. Push services accept about
+/// 4 KB of ciphertext, and the rest of the JSON needs room too.
+const MAX_MESSAGE_BYTES: usize = 3000;
+const SEND_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The server's VAPID signing key.
+#[derive(Clone)]
+pub struct VapidKeys {
+    pkcs8: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+impl VapidKeys {
+    /// Generate a fresh P-256 key pair.
+    pub fn generate() -> Result<Self, String> {
+        let pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                .map_err(|_| "VAPID key generation failed".to_string())?;
+        Self::from_pkcs8(pkcs8.as_ref())
+    }
+
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, String> {
+        let pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            pkcs8,
+            &SystemRandom::new(),
+        )
+        .map_err(|e| format!("invalid VAPID key: {}", e))?;
+        Ok(Self {
+            pkcs8: pkcs8.to_vec(),
+            public_key: pair.public_key().as_ref().to_vec(),
+        })
+    }
+
+    /// Load the key pair from settings, generating and storing one if absent.
+    pub async fn load_or_create(settings: &dyn SettingsRepository) -> Result<Self, CoreError> {
+        if let Some(stored) = settings.get(VAPID_KEY_SETTING).await? {
+            let pkcs8 = URL_SAFE_NO_PAD
+                .decode(stored.trim())
+                .map_err(|e| CoreError::Internal(format!("invalid stored VAPID key: {}", e)))?;
+            return Self::from_pkcs8(&pkcs8).map_err(CoreError::Internal);
+        }
+        let keys = Self::generate().map_err(CoreError::Internal)?;
+        settings
+            .set(VAPID_KEY_SETTING, &URL_SAFE_NO_PAD.encode(&keys.pkcs8))
+            .await?;
+        Ok(keys)
+    }
+
+    /// The public key as browsers expect it for `applicationServerKey`.
+    pub fn public_key(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.public_key)
+    }
+
+    /// A VAPID JWT for the push service at `audience` (its origin).
+    fn token(&self, audience: &str, subject: &str) -> Result<String, String> {
+        let claims = serde_json::json!({
+            "aud": audience,
+            "exp": (chrono::Utc::now() + chrono::Duration::hours(12)).timestamp(),
+            "sub": subject,
+        });
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::ES256),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_ec_der(&self.pkcs8),
+        )
+        .map_err(|e| format!("VAPID signing failed: {}", e))
+    }
+}
+
+/// Decode base64url with or without padding, as browsers vary.
+fn decode_key(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim();
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .or_else(|_| URL_SAFE.decode(value))
+        .ok()
+}
+
+/// Check a browser subscription before storing it.
+pub fn validate_subscription(endpoint: &str, p256dh: &str, auth: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(endpoint).map_err(|_| "endpoint must be a URL".to_string())?;
+    if !matches!(url.scheme(), "https" | "http") || url.host_str().is_none() {
+        return Err("endpoint must be an http(s) URL".to_string());
+    }
+    match decode_key(p256dh) {
+        Some(key) if key.len() == 65 && key[0] == 4 => {}
+        _ => return Err("keys.p256dh must be an uncompressed P-256 public key".to_string()),
+    }
+    match decode_key(auth) {
+        Some(secret) if secret.len() == 16 => Ok(()),
+        _ => Err("keys.auth must be a 16-byte secret".to_string()),
+    }
+}
+
+/// Encrypt `plaintext` for a subscription with the `aes128gcm` content coding
+/// (RFC 8291 section 3.4, single record).
+pub fn encrypt(p256dh: &[u8], auth: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let rng = SystemRandom::new();
+    let fail = |what: &str| format!("Web Push encryption failed: {}", what);
+
+    let as_private = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+        .map_err(|_| fail("key generation"))?;
+    let as_public = as_private
+        .compute_public_key()
+        .map_err(|_| fail("public key"))?
+        .as_ref()
+        .to_vec();
+    let ua_public = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, p256dh);
+    let ecdh_secret = agreement::agree_ephemeral(as_private, &ua_public, |s| s.to_vec())
+        .map_err(|_| fail("key agreement"))?;
+
+    // IKM = HKDF(auth_secret, ecdh_secret, "WebPush: info" || 0x00 || ua_public || as_public)
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(p256dh);
+    key_info.extend_from_slice(&as_public);
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(auth), &ecdh_secret)
+        .expand(&key_info, &mut ikm)
+        .map_err(|_| fail("key derivation"))?;
+
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt).map_err(|_| fail("salt"))?;
+    let hk = Hkdf::<Sha256>::new(Some(&salt), &ikm);
+    let mut cek = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    hk.expand(b"Content-Encoding: aes128gcm\0", &mut cek)
+        .map_err(|_| fail("key derivation"))?;
+    hk.expand(b"Content-Encoding: nonce\0", &mut nonce)
+        .map_err(|_| fail("key derivation"))?;
+
+    // The last (and only) record ends with the 0x02 delimiter.
+    let mut record = plaintext.to_vec();
+    record.push(2);
+    let key = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_128_GCM, &cek).map_err(|_| fail("cipher"))?,
+    );
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::empty(),
+        &mut record,
+    )
+    .map_err(|_| fail("seal"))?;
+
+    // Header: salt || record size || key id length || key id (as_public).
+    let mut body = Vec::with_capacity(16 + 4 + 1 + as_public.len() + record.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(&as_public);
+    body.extend_from_slice(&record);
+    Ok(body)
+}
+
+/// The JSON a service worker receives: enough to show the notification and
+/// open the message.
+fn payload(msg: &MessageResponse) -> Vec<u8> {
+    let mut text = msg.message.as_str();
+    if text.len() > MAX_MESSAGE_BYTES {
+        let mut end = MAX_MESSAGE_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text = &text[..end];
+    }
+    serde_json::json!({
+        "id": msg.id,
+        "appid": msg.appid,
+        "topic": msg.topic,
+        "title": msg.title,
+        "message": text,
+        "priority": msg.priority,
+        "click_url": msg.click_url,
+        "icon_url": msg.icon_url,
+        "date": msg.date,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// A failed send.
+#[derive(Debug, Clone)]
+pub struct WebPushError {
+    /// HTTP status, or `None` if no response was received.
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl WebPushError {
+    fn local(message: String) -> Self {
+        Self {
+            status: None,
+            message,
+        }
+    }
+
+    /// The push service no longer knows the subscription (RFC 8030 section 7.3).
+    pub fn is_gone(&self) -> bool {
+        matches!(self.status, Some(404) | Some(410))
+    }
+}
+
+impl std::fmt::Display for WebPushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "HTTP {}: {}", status, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Sends Web Push messages signed with the server's VAPID key.
+#[derive(Clone)]
+pub struct WebPushClient {
+    keys: VapidKeys,
+    /// `mailto:` or `https:` contact for push service operators.
+    subject: String,
+}
+
+impl WebPushClient {
+    pub fn new(keys: VapidKeys, subject: String) -> Self {
+        Self { keys, subject }
+    }
+
+    pub fn public_key(&self) -> String {
+        self.keys.public_key()
+    }
+
+    /// Encrypt and deliver one message to one subscription.
+    pub async fn send(
+        &self,
+        endpoint: &str,
+        p256dh: &str,
+        auth: &str,
+        msg: &MessageResponse,
+    ) -> Result<(), WebPushError> {
+        let (Some(p256dh), Some(auth)) = (decode_key(p256dh), decode_key(auth)) else {
+            return Err(WebPushError::local("malformed subscription keys".into()));
+        };
+        let body = encrypt(&p256dh, &auth, &payload(msg)).map_err(WebPushError::local)?;
+
+        let url = reqwest::Url::parse(endpoint)
+            .map_err(|e| WebPushError::local(format!("invalid endpoint: {}", e)))?;
+        let token = self
+            .keys
+            .token(&url.origin().ascii_serialization(), &self.subject)
+            .map_err(WebPushError::local)?;
+
+        // Endpoints come from browsers, so they get the same SSRF checks as
+        // outgoing webhooks, pinned to the vetted address.
+        let target = ssrf::validate_outbound_url(endpoint)
+            .await
+            .map_err(|e| WebPushError::local(e.to_string()))?;
+        let http = reqwest::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve_to_addrs(&target.host, &target.addrs)
+            .build()
+            .map_err(|e| WebPushError::local(format!("failed to build HTTP client: {}", e)))?;
+
+        let urgency = if msg.priority >= 8 {
+            "high"
+        } else if msg.priority >= 4 {
+            "normal"
+        } else {
+            "low"
+        };
+        let resp = http
+            .post(url)
+            .header(
+                "Authorization",
+                format!("vapid t={}, k={}", token, self.keys.public_key()),
+            )
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .header("TTL", TTL_SECS.to_string())
+            .header("Urgency", urgency)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| WebPushError::local(format!("Web Push send failed: {}", e)))?;
+
+        let status = resp.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let body = resp.text().await.unwrap_or_default();
+            Err(WebPushError {
+                status: Some(status.as_u16()),
+                message: body.chars().take(200).collect(),
+            })
+        }
+    }
+
+    /// Push a message to each client's browser subscription, logging the
+    /// outcome per device and dropping subscriptions the push service has
+    /// forgotten. Clients without a subscription are skipped.
+    pub async fn notify_clients<R: ClientRepository + ?Sized>(
+        &self,
+        client_repo: &R,
+        clients: &[Client],
+        msg: &MessageResponse,
+    ) {
+        for client in clients {
+            let (Some(endpoint), Some(p256dh), Some(auth)) = (
+                client.webpush_endpoint.as_deref(),
+                client.webpush_p256dh.as_deref(),
+                client.webpush_auth.as_deref(),
+            ) else {
+                continue;
+            };
+            let result = self.send(endpoint, p256dh, auth, msg).await;
+            let mut delivery = NewPushDelivery {
+                channel: "webpush",
+                token: endpoint,
+                message_id: (msg.id > 0).then_some(msg.id),
+                outcome: "sent",
+                attempts: 1,
+                ..Default::default()
+            };
+            if let Err(ref e) = result {
+                warn!("Web Push send error for client {}: {}", client.id, e);
+                delivery.outcome = if e.is_gone() { "pruned" } else { "failed" };
+                delivery.status_code = e.status.map(i32::from);
+                delivery.error = Some(&e.message);
+            }
+            if let Err(e) = client_repo.record_push_delivery(&delivery).await {
+                warn!("Failed to record push delivery: {}", e);
+            }
+            if delivery.outcome == "pruned" {
+                match client_repo.clear_web_push_endpoint(endpoint).await {
+                    Ok(n) => debug!("Removed expired Web Push subscription from {} client(s)", n),
+                    Err(e) => warn!("Failed to remove expired Web Push subscription: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Push a message to all of a user's browser subscriptions.
+    pub async fn notify_user<R: ClientRepository + ?Sized>(
+        &self,
+        client_repo: &R,
+        user_id: i64,
+        msg: &MessageResponse,
+    ) {
+        match client_repo.list_by_user(user_id).await {
+            Ok(clients) => {
+                let clients: Vec<_> = clients.into_iter().filter(|c| !c.is_expired()).collect();
+                self.notify_clients(client_repo, &clients, msg).await;
+            }
+            Err(e) => warn!("Failed to fetch clients for user {}: {}", user_id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vapid_keys_round_trip_through_pkcs8() {
+        let keys = VapidKeys::generate().unwrap();
+        assert_eq!(keys.public_key.len(), 65);
+        let again = VapidKeys::from_pkcs8(&keys.pkcs8).unwrap();
+        assert_eq!(again.public_key(), keys.public_key());
+        let token = keys
+            .token("https://push.example.net", "mailto:ops@example.com")
+            .unwrap();
+        assert_eq!(token.split('.').count(), 3);
+    }
+
+    #[test]
+    fn subscriptions_are_checked() {
+        let p256dh = URL_SAFE_NO_PAD.encode(VapidKeys::generate().unwrap().public_key);
+        let auth = URL_SAFE_NO_PAD.encode([7u8; 16]);
+        let endpoint = "https://push.example.net/send/abc";
+        assert!(validate_subscription(endpoint, &p256dh, &auth).is_ok());
+        // Padded base64url is accepted too.
+        assert!(validate_subscription(endpoint, &p256dh, &URL_SAFE.encode([7u8; 16])).is_ok());
+        assert!(validate_subscription("ftp://push.example.net", &p256dh, &auth).is_err());
+        assert!(validate_subscription(endpoint, &auth, &auth).is_err());
+        assert!(validate_subscription(endpoint, &p256dh, &p256dh).is_err());
+    }
+
+    #[test]
+    fn payload_text_is_capped() {
+        let msg = MessageResponse {
+            id: 1,
+            appid: None,
+            topic: None,
+            title: None,
+            message: "é".repeat(MAX_MESSAGE_BYTES),
+            priority: 5,
+            tags: None,
+            click_url: None,
+            icon_url: None,
+            actions: None,
+            extras: None,
+            content_type: None,
+            source: None,
+            inbox: true,
+            attachments: None,
+            date: String::new(),
+        };
+        let json: serde_json::Value = serde_json::from_slice(&payload(&msg)).unwrap();
+        assert!(json["message"].as_str().unwrap().len() <= MAX_MESSAGE_BYTES);
+    }
+}
@@ -0,0 +1,543 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::body::Body;
+use axum::http::{self, Request, StatusCode};
+use http_body_util::BodyExt;
+use rstify_storage::{BlobStore, BlobStores, LocalBlobStore, MemoryBlobStore};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// PUT a file to a topic the way `ntfy publish --file` does.
+fn upload(topic: &str, token: &str, filename: &str, data: &'static [u8]) -> Request<Body> {
+    Request::builder()
+        .method(http::Method::PUT)
+        .uri(format!("/{}", topic))
+        .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+        .header("Filename", filename)
+        .body(Body::from(data))
+        .unwrap()
+}
+
+/// Create a topic and upload a file to it as the regular user. Returns the
+/// attachment id.
+async fn upload_attachment(app: &common::TestApp, topic: &str) -> i64 {
+    common::seed::create_topic(&app.pool, 2, topic).await;
+    upload_again(app, topic).await
+}
+
+/// Upload the same file to an existing topic.
+async fn upload_again(app: &common::TestApp, topic: &str) -> i64 {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(upload(
+            topic,
+            &app.user_token,
+            "report.txt",
+            b"quarterly numbers",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    body["attachments"][0]["id"].as_i64().unwrap()
+}
+
+fn memory_stores(store: Arc<MemoryBlobStore>) -> BlobStores {
+    let local: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new("/tmp/rstify-test-uploads"));
+    BlobStores::new(store, local)
+}
+
+#[tokio::test]
+async fn local_attachment_round_trip() {
+    let app = common::setup().await;
+    let id = upload_attachment(&app, "local-files").await;
+
+    let (storage_type, storage_path): (String, String) =
+        sqlx::query_as("SELECT storage_type, storage_path FROM attachments WHERE id = ?")
+            .bind(id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(storage_type, "local");
+    assert!(!storage_path.contains('/'), "rows store the key only");
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_string(resp).await, "quarterly numbers");
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert!(!std::path::Path::new("/tmp/rstify-test-uploads")
+        .join(&storage_path)
+        .exists());
+}
+
+#[tokio::test]
+async fn object_store_download_redirects_to_presigned_url() {
+    let store = Arc::new(MemoryBlobStore::new().with_presign_base("https://bucket.example"));
+    let stores = memory_stores(store.clone());
+    let app = common::setup_with(|state| state.with_blob_stores(stores, true)).await;
+    let id = upload_attachment(&app, "bucket-files").await;
+    assert_eq!(store.len(), 1);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TEMPORARY_REDIRECT);
+    let location = resp.headers()[http::header::LOCATION].to_str().unwrap();
+    assert!(location.starts_with("https://bucket.example/"));
+
+    // Other users are refused before any URL is handed out.
+    let other = common::seed::create_user(&app.pool, "mallory").await;
+    let token = rstify_auth::tokens::create_jwt(other, "mallory", false, &app.jwt_secret).unwrap();
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(&format!("/api/attachments/{}", id), &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert!(store.is_empty());
+}
+
+#[tokio::test]
+async fn object_store_download_proxied_when_presigning_disabled() {
+    let store = Arc::new(MemoryBlobStore::new().with_presign_base("https://bucket.example"));
+    let stores = memory_stores(store.clone());
+    let app = common::setup_with(|state| state.with_blob_stores(stores, false)).await;
+    let id = upload_attachment(&app, "proxied-files").await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_string(resp).await, "quarterly numbers");
+}
+
+#[tokio::test]
+async fn identical_uploads_share_one_blob() {
+    let store = Arc::new(MemoryBlobStore::new());
+    let stores = memory_stores(store.clone());
+    let app = common::setup_with(|state| state.with_blob_stores(stores, false)).await;
+    let first = upload_attachment(&app, "dedup-files").await;
+    let second = upload_again(&app, "dedup-files").await;
+    assert_ne!(first, second);
+    assert_eq!(store.len(), 1, "same content is stored once");
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/attachments/{}", first),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    let etag = resp.headers()[http::header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        etag,
+        format!("\"{}\"", rstify_storage::content_hash(b"quarterly numbers"))
+    );
+
+    let req = Request::builder()
+        .uri(format!("/api/attachments/{}", second))
+        .header(
+            http::header::AUTHORIZATION,
+            format!("Bearer {}", app.user_token),
+        )
+        .header(http::header::IF_NONE_MATCH, &etag)
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.router.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+    // The blob survives until its last reference is deleted.
+    let delete = |id: i64| common::delete(&format!("/api/attachments/{}", id), &app.user_token);
+    let resp = app.router.clone().oneshot(delete(first)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(store.len(), 1);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/attachments/{}", second),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_string(resp).await, "quarterly numbers");
+
+    let resp = app.router.clone().oneshot(delete(second)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert!(store.is_empty());
+}
+
+#[tokio::test]
+async fn image_uploads_get_a_thumbnail() {
+    let store = Arc::new(MemoryBlobStore::new());
+    let stores = memory_stores(store.clone());
+    let app = common::setup_with({
+        let stores = stores.clone();
+        |state| state.with_blob_stores(stores, false)
+    })
+    .await;
+    common::seed::create_topic(&app.pool, 2, "photos").await;
+
+    let mut png = Vec::new();
+    image::DynamicImage::from(image::RgbImage::new(800, 600))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    let req = Request::builder()
+        .method(http::Method::PUT)
+        .uri("/photos")
+        .header(
+            http::header::AUTHORIZATION,
+            format!("Bearer {}", app.user_token),
+        )
+        .header("Filename", "photo.png")
+        .body(Body::from(png))
+        .unwrap();
+    let resp = app.router.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    let id = body["attachments"][0]["id"].as_i64().unwrap();
+    let message_id = body["id"].as_i64().unwrap();
+    assert!(body["attachments"][0]["thumbnail_url"].is_null());
+
+    let thumbnail_uri = format!("/api/attachments/{}/thumbnail", id);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(&thumbnail_uri, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "not generated yet");
+
+    let generated =
+        rstify_jobs::thumbnails::process_pending_thumbnails(&app.repos, &stores, 10 * 1024 * 1024)
+            .await
+            .unwrap();
+    assert_eq!(generated, 1);
+    assert_eq!(store.len(), 2, "thumbnail stored beside the original");
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/messages/{}/attachments", message_id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    let list = common::body_json(resp).await;
+    assert_eq!(list[0]["width"], 800);
+    assert_eq!(list[0]["height"], 600);
+    assert_eq!(list[0]["thumbnail_url"], thumbnail_uri.as_str());
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(&thumbnail_uri, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[http::header::CONTENT_TYPE], "image/jpeg");
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let preview = image::load_from_memory(&bytes).unwrap();
+    assert_eq!((preview.width(), preview.height()), (320, 240));
+
+    // Deleting the attachment removes its thumbnail too.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert!(store.is_empty());
+}
+
+#[tokio::test]
+async fn non_images_are_not_queued_for_thumbnails() {
+    let app = common::setup().await;
+    let id = upload_attachment(&app, "plain-files").await;
+    let status: Option<String> =
+        sqlx::query_scalar("SELECT thumbnail_status FROM attachments WHERE id = ?")
+            .bind(id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(status, None);
+}
+
+/// GET an attachment with extra request headers.
+async fn download_with(
+    app: &common::TestApp,
+    id: i64,
+    headers: &[(http::HeaderName, &str)],
+) -> axum::response::Response {
+    let mut req = Request::builder()
+        .uri(format!("/api/attachments/{}", id))
+        .header(
+            http::header::AUTHORIZATION,
+            format!("Bearer {}", app.user_token),
+        );
+    for (name, value) in headers {
+        req = req.header(name, *value);
+    }
+    app.router
+        .clone()
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn range_requests_resume_downloads() {
+    let app = common::setup().await;
+    let id = upload_attachment(&app, "ranged-files").await;
+
+    let resp = download_with(&app, id, &[]).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[http::header::ACCEPT_RANGES], "bytes");
+    assert_eq!(resp.headers()[http::header::CONTENT_LENGTH], "17");
+    assert_eq!(
+        resp.headers()[http::header::CONTENT_DISPOSITION],
+        "attachment; filename=\"report.txt\"; filename*=UTF-8''report.txt"
+    );
+    let etag = resp.headers()[http::header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let last_modified = resp.headers()[http::header::LAST_MODIFIED]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // "quarterly numbers": bytes 10.. are "numbers".
+    let resp = download_with(&app, id, &[(http::header::RANGE, "bytes=10-")]).await;
+    assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        resp.headers()[http::header::CONTENT_RANGE],
+        "bytes 10-16/17"
+    );
+    assert_eq!(resp.headers()[http::header::CONTENT_LENGTH], "7");
+    assert_eq!(common::body_string(resp).await, "numbers");
+
+    let resp = download_with(&app, id, &[(http::header::RANGE, "bytes=-7")]).await;
+    assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(common::body_string(resp).await, "numbers");
+
+    // If-Range with the current validators keeps the range...
+    for validator in [etag.as_str(), last_modified.as_str()] {
+        let resp = download_with(
+            &app,
+            id,
+            &[
+                (http::header::RANGE, "bytes=0-8"),
+                (http::header::IF_RANGE, validator),
+            ],
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(common::body_string(resp).await, "quarterly");
+    }
+
+    // ...while a stale one gets the whole file.
+    let resp = download_with(
+        &app,
+        id,
+        &[
+            (http::header::RANGE, "bytes=0-8"),
+            (http::header::IF_RANGE, "\"stale\""),
+        ],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_string(resp).await, "quarterly numbers");
+
+    let resp = download_with(&app, id, &[(http::header::RANGE, "bytes=17-")]).await;
+    assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(resp.headers()[http::header::CONTENT_RANGE], "bytes */17");
+
+    let resp = download_with(
+        &app,
+        id,
+        &[(http::header::IF_MODIFIED_SINCE, last_modified.as_str())],
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn ranges_served_from_object_store() {
+    let store = Arc::new(MemoryBlobStore::new());
+    let stores = memory_stores(store);
+    let app = common::setup_with(|state| state.with_blob_stores(stores, false)).await;
+    let id = upload_attachment(&app, "ranged-memory").await;
+
+    let resp = download_with(&app, id, &[(http::header::RANGE, "bytes=0-8")]).await;
+    assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(common::body_string(resp).await, "quarterly");
+}
+
+/// Mint a signed link to an attachment as the regular user.
+async fn mint_link(
+    app: &common::TestApp,
+    id: i64,
+    body: serde_json::Value,
+) -> axum::response::Response {
+    app.router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/attachments/{}/link", id),
+            &app.user_token,
+            body,
+        ))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn signed_links_download_without_a_token() {
+    let app = common::setup_with(|state| {
+        state.with_base_url(Some("https://push.example.com".to_string()))
+    })
+    .await;
+    let id = upload_attachment(&app, "shared-files").await;
+
+    let resp = mint_link(&app, id, serde_json::json!({ "expires_in": 600 })).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    let url = body["url"].as_str().unwrap();
+    assert!(body["expires_at"].as_str().unwrap().ends_with('Z'));
+    let path = url
+        .strip_prefix("https://push.example.com")
+        .expect("links are absolute once BASE_URL is set");
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::unauthed_get(path))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_string(resp).await, "quarterly numbers");
+
+    // Changing the attachment or expiry invalidates the signature.
+    let forged = path.replace(&format!("/{}/public", id), &format!("/{}/public", id + 1));
+    let expires: i64 = path
+        .split("expires=")
+        .nth(1)
+        .and_then(|rest| rest.split('&').next())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let extended = path.replace(
+        &format!("expires={}", expires),
+        &format!("expires={}", expires + 3600),
+    );
+    for bad in [forged, extended] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::unauthed_get(&bad))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    // Expired links are refused even with a valid signature.
+    let links = rstify_api::attachment_links::AttachmentLinks::new(&app.jwt_secret);
+    let stale = links.url(id, chrono::Utc::now() - chrono::Duration::minutes(1));
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::unauthed_get(&stale))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn minting_links_checks_access_and_lifetime() {
+    let app = common::setup().await;
+    let id = upload_attachment(&app, "private-files").await;
+
+    // Without BASE_URL the link is a path on this server.
+    let resp = mint_link(&app, id, serde_json::json!({})).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert!(body["url"]
+        .as_str()
+        .unwrap()
+        .starts_with(&format!("/api/attachments/{}/public?", id)));
+
+    let resp = mint_link(&app, id, serde_json::json!({ "expires_in": 0 })).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = mint_link(&app, id, serde_json::json!({ "expires_in": 31 * 86400 })).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let other = common::seed::create_user(&app.pool, "outsider").await;
+    let token = rstify_auth::tokens::create_jwt(other, "outsider", false, &app.jwt_secret).unwrap();
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/attachments/{}/link", id),
+            &token,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,202 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::http::StatusCode;
+use tower::ServiceExt;
+
+async fn audit_entries(app: &common::TestApp, query: &str) -> Vec<serde_json::Value> {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/audit{}", query),
+            &app.admin_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    common::body_json(resp).await.as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn audit_log_requires_admin() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/audit", &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn user_changes_are_recorded_with_diff() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/user",
+            &app.admin_token,
+            serde_json::json!({"username": "carol", "password": "carol-password"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let user_id = common::body_json(resp).await["id"].as_i64().unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/user/{}", user_id),
+            &app.admin_token,
+            serde_json::json!({"email": "carol@example.com"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let entries = audit_entries(&app, "?action=user.").await;
+    assert_eq!(entries.len(), 2);
+
+    // Newest first
+    let update = &entries[0];
+    assert_eq!(update["action"], "user.update");
+    assert_eq!(update["actor_id"], 1);
+    assert_eq!(update["actor_name"], "admin");
+    assert_eq!(update["target_type"], "user");
+    assert_eq!(update["target_id"], user_id.to_string());
+    // Only changed fields are kept
+    assert!(update["before"]["email"].is_null());
+    assert_eq!(update["after"]["email"], "carol@example.com");
+    assert!(update["before"].get("username").is_none());
+    assert!(update["after"].get("username").is_none());
+
+    let create = &entries[1];
+    assert_eq!(create["action"], "user.create");
+    assert!(create["before"].is_null());
+    assert_eq!(create["after"]["username"], "carol");
+}
+
+#[tokio::test]
+async fn setting_change_records_old_and_new_value() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            "/api/settings/audit_retention_days",
+            &app.admin_token,
+            serde_json::json!({"value": "90"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let entries = audit_entries(&app, "?target_type=setting").await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "setting.update");
+    assert_eq!(entries[0]["target_id"], "audit_retention_days");
+    assert_eq!(entries[0]["before"]["value"], "365");
+    assert_eq!(entries[0]["after"]["value"], "90");
+}
+
+#[tokio::test]
+async fn client_token_is_redacted() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/client",
+            &app.user_token,
+            serde_json::json!({"name": "phone"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let token = common::body_json(resp).await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let entries = audit_entries(&app, "?action=client.create").await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["actor_name"], "testuser");
+    let recorded = entries[0]["after"]["token"].as_str().unwrap();
+    assert_ne!(recorded, token);
+    assert!(recorded.starts_with("[redacted"));
+}
+
+#[tokio::test]
+async fn failed_login_is_recorded() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::unauthed_post_json(
+            "/api/auth/login",
+            serde_json::json!({"username": "testuser", "password": "wrong"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::unauthed_post_json(
+            "/api/auth/login",
+            serde_json::json!({"username": "nobody", "password": "whatever"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let entries = audit_entries(&app, "?action=auth.login_failed").await;
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0]["actor_id"].is_null());
+    assert_eq!(entries[0]["actor_name"], "nobody");
+    assert_eq!(entries[1]["actor_id"], 2);
+    assert_eq!(entries[1]["target_id"], "2");
+}
+
+#[tokio::test]
+async fn retention_purges_old_entries() {
+    let app = common::setup().await;
+
+    sqlx::query(
+        "INSERT INTO audit_log (action, target_type, created_at) VALUES \
+         ('user.create', 'user', datetime('now', '-400 days')), \
+         ('user.delete', 'user', datetime('now', '-1 days'))",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let purged = rstify_jobs::cleanup::purge_old_audit_entries(&app.repos)
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    // 0 keeps the log forever
+    sqlx::query("UPDATE settings SET value = '0' WHERE key = 'audit_retention_days'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE audit_log SET created_at = datetime('now', '-5000 days')")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let purged = rstify_jobs::cleanup::purge_old_audit_entries(&app.repos)
+        .await
+        .unwrap();
+    assert_eq!(purged, 0);
+}
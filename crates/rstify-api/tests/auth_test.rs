@@ -66,6 +66,163 @@ async fn login_with_unknown_user() {
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 }
 
+// ---------------------------------------------------------------------------
+// Per-account lockout tests
+// ---------------------------------------------------------------------------
+
+async fn login(app: &common::TestApp, username: &str, password: &str) -> StatusCode {
+    app.router
+        .clone()
+        .oneshot(common::unauthed_post_json(
+            "/api/auth/login",
+            serde_json::json!({ "username": username, "password": password }),
+        ))
+        .await
+        .unwrap()
+        .status()
+}
+
+async fn set_failed_logins(app: &common::TestApp, user_id: i64, count: i64) {
+    sqlx::query("UPDATE users SET failed_logins = ? WHERE id = ?")
+        .bind(count)
+        .bind(user_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+}
+
+async fn lock_state(app: &common::TestApp, user_id: i64) -> (i64, Option<String>) {
+    sqlx::query_as("SELECT failed_logins, locked_until FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn failed_logins_are_counted_per_account() {
+    let app = common::setup().await;
+
+    assert_eq!(
+        login(&app, "testuser", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        login(&app, "testuser", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(lock_state(&app, 2).await, (2, None));
+
+    // A successful login resets the counter
+    assert_eq!(login(&app, "testuser", "user123").await, StatusCode::OK);
+    assert_eq!(lock_state(&app, 2).await, (0, None));
+}
+
+#[tokio::test]
+async fn repeated_failures_delay_further_attempts() {
+    let app = common::setup().await;
+    set_failed_logins(&app, 2, 5).await;
+
+    // The 6th failure imposes an 8s wait, during which even the right
+    // password is refused
+    assert_eq!(
+        login(&app, "testuser", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    let (failures, locked_until) = lock_state(&app, 2).await;
+    assert_eq!(failures, 6);
+    assert!(locked_until.is_some());
+    assert_eq!(
+        login(&app, "testuser", "user123").await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // Other accounts are unaffected
+    assert_eq!(login(&app, "admin", "admin123").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn lockout_notifies_user_and_admin_can_unlock() {
+    let app = common::setup().await;
+    set_failed_logins(&app, 2, 9).await;
+
+    assert_eq!(
+        login(&app, "testuser", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        login(&app, "testuser", "user123").await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let notified: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM messages WHERE user_id = 2 AND title = 'Account locked'",
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(notified, 1);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            "/api/audit?action=auth.lockout",
+            &app.admin_token,
+        ))
+        .await
+        .unwrap();
+    let entries = common::body_json(resp).await;
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+    assert_eq!(entries[0]["target_id"], "2");
+
+    // Only admins may unlock
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/user/2/unlock",
+            &app.user_token,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/user/2/unlock",
+            &app.admin_token,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert_eq!(body["failed_logins"], 0);
+    assert!(body["locked_until"].is_null());
+
+    assert_eq!(login(&app, "testuser", "user123").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn unknown_usernames_are_throttled_like_real_accounts() {
+    let app = common::setup().await;
+
+    let mut real = Vec::new();
+    let mut missing = Vec::new();
+    for _ in 0..5 {
+        real.push(login(&app, "testuser", "wrong").await);
+    }
+    for _ in 0..5 {
+        missing.push(login(&app, "no-such-user-throttle", "wrong").await);
+    }
+    assert_eq!(real, missing);
+    assert_eq!(real[3], StatusCode::TOO_MANY_REQUESTS);
+}
+
 // ---------------------------------------------------------------------------
 // Token-based auth guard tests
 // ---------------------------------------------------------------------------
@@ -108,3 +265,81 @@ async fn authenticated_endpoint_with_invalid_token() {
 
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 }
+
+// ---------------------------------------------------------------------------
+// Accounts and tokens imported from Gotify
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn bcrypt_password_is_accepted_and_upgraded() {
+    let app = common::setup().await;
+    let user_id = common::seed::create_user(&app.pool, "gotify-user").await;
+    let hash = bcrypt::hash("gotify123", 4).unwrap();
+    sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        .bind(&hash)
+        .bind(user_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        login(&app, "gotify-user", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        login(&app, "gotify-user", "gotify123").await,
+        StatusCode::OK
+    );
+
+    let (stored,): (String,) = sqlx::query_as("SELECT password_hash FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert!(stored.starts_with("$argon2"), "{}", stored);
+    assert_eq!(
+        login(&app, "gotify-user", "gotify123").await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn gotify_tokens_authenticate() {
+    let app = common::setup().await;
+    sqlx::query(
+        "INSERT INTO applications (user_id, name, token, default_priority, created_at, updated_at) \
+         VALUES (2, 'imported', 'AGi5.tYpG-w_3Fe', 5, datetime('now'), datetime('now'))",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO clients (user_id, name, token, created_at) \
+         VALUES (2, 'phone', 'CvXq0v2qN9M.7aD', datetime('now'))",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/message",
+            "AGi5.tYpG-w_3Fe",
+            serde_json::json!({ "message": "still works" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/message", "CvXq0v2qN9M.7aD"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert_eq!(body["messages"][0]["message"], "still works");
+}
@@ -0,0 +1,119 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::http::{header, StatusCode};
+use tower::ServiceExt;
+
+fn backup_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("rstify-test-backups-{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.to_string_lossy().into_owned()
+}
+
+#[tokio::test]
+async fn admin_creates_lists_and_downloads_backups() {
+    let dir = backup_dir("api");
+    let app = common::setup_with(|state| state.with_backup_dir(dir.clone())).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/backups",
+            &app.admin_token,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+    let status = resp.status();
+    let created = common::body_json(resp).await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let name = created["name"].as_str().unwrap().to_string();
+    assert!(name.starts_with("rstify-backup-") && name.ends_with(".tar.gz"));
+    assert!(created["size_bytes"].as_i64().unwrap() > 0);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/backups", &app.admin_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let listed = common::body_json(resp).await;
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["name"], name.as_str());
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/backups/{}", name),
+            &app.admin_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/gzip");
+    assert!(resp.headers()[header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .contains(&name));
+    let body = http_body_util::BodyExt::collect(resp.into_body())
+        .await
+        .unwrap()
+        .to_bytes();
+    assert_eq!(body.len() as i64, created["size_bytes"].as_i64().unwrap());
+    assert_eq!(&body[..2], b"\x1f\x8b", "gzip magic");
+
+    let (audited,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM audit_log WHERE action = 'backup.create'")
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(audited, 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn backups_are_admin_only_and_names_are_checked() {
+    let dir = backup_dir("access");
+    let app = common::setup_with(|state| state.with_backup_dir(dir.clone())).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/backups",
+            &app.user_token,
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/backups", &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    for name in [
+        "rstify-backup-20260101-000000.tar.gz",
+        "..%2Fsecrets",
+        "rstify.db",
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::get(
+                &format!("/api/backups/{}", name),
+                &app.admin_token,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", name);
+    }
+}
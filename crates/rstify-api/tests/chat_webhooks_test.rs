@@ -0,0 +1,246 @@
+//! Outgoing webhooks of the chat kinds, delivered to an in-process stand-in
+//! for the chat service.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::mock::start_mock;
+use common::seed;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn create(app: &common::TestApp, body: Value) -> (StatusCode, Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json("/api/webhooks", &app.user_token, body))
+        .await
+        .unwrap();
+    let status = resp.status();
+    (status, common::body_json(resp).await)
+}
+
+#[tokio::test]
+async fn chat_webhooks_are_validated() {
+    let (host, _received) = start_mock().await;
+    let base = format!("http://{}", host);
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "chat-validate").await;
+
+    for (webhook_type, url) in [
+        ("slak", format!("{}/hook", base)),
+        ("telegram", format!("{}/bot1:abc/sendMessage", base)),
+        (
+            "matrix",
+            format!("{}/_matrix/client/v3/rooms/!r/messages", base),
+        ),
+    ] {
+        let (status, _) = create(
+            &app,
+            json!({
+                "name": "chat",
+                "webhookType": webhook_type,
+                "direction": "outgoing",
+                "targetTopicId": topic_id,
+                "targetUrl": url,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", webhook_type);
+    }
+
+    let (status, webhook) = create(
+        &app,
+        json!({
+            "name": "telegram",
+            "webhookType": "telegram",
+            "direction": "outgoing",
+            "targetTopicId": topic_id,
+            "targetUrl": format!("{}/bot1:abc/sendMessage?chat_id=42", base),
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Dropping the chat_id later is caught too.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/api/webhooks/{}", webhook["id"]),
+            &app.user_token,
+            json!({"targetUrl": format!("{}/bot1:abc/sendMessage", base)}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn published_messages_are_rendered_for_slack() {
+    let (host, received) = start_mock().await;
+    let base = format!("http://{}", host);
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "chat-slack").await;
+
+    let (status, _) = create(
+        &app,
+        json!({
+            "name": "slack",
+            "webhookType": "slack",
+            "direction": "outgoing",
+            "targetTopicId": topic_id,
+            "targetUrl": format!("{}/services/T0/B0/x", base),
+            // Chat kinds pick their own method.
+            "httpMethod": "GET",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics/chat-slack/publish",
+            &app.user_token,
+            json!({
+                "title": "Disk full",
+                "message": "/var at 99%",
+                "priority": 9,
+                "actions": [{"action": "view", "label": "Dashboard", "url": "https://grafana.example.com"}],
+            }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for _ in 0..100 {
+        if !received.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let (method, path, headers, body) = received.lock().unwrap().remove(0);
+    assert_eq!(method, Method::POST);
+    assert_eq!(path, "/services/T0/B0/x");
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(body["text"], "Disk full: /var at 99%");
+    let attachment = &body["attachments"][0];
+    assert_eq!(attachment["color"], "#E53935");
+    let blocks = attachment["blocks"].as_array().unwrap();
+    assert_eq!(blocks[0]["text"]["text"], "Disk full");
+    let button = &blocks.last().unwrap()["elements"][0];
+    assert_eq!(button["url"], "https://grafana.example.com");
+}
+
+#[tokio::test]
+async fn matrix_test_fire_puts_an_html_event() {
+    let (host, received) = start_mock().await;
+    let base = format!("http://{}", host);
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "chat-matrix").await;
+
+    let (status, webhook) = create(
+        &app,
+        json!({
+            "name": "matrix",
+            "webhookType": "matrix",
+            "direction": "outgoing",
+            "targetTopicId": topic_id,
+            "targetUrl": format!("{}/_matrix/client/v3/rooms/!room:example.org/send/m.room.message", base),
+            "headers": {"Authorization": "Bearer syt_token"},
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/webhooks/{}/test", webhook["id"]),
+            &app.user_token,
+            json!({"title": "Deploy <done>", "priority": 2}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_json(resp).await["success"], true);
+
+    let (method, path, headers, body) = received.lock().unwrap().remove(0);
+    assert_eq!(method, Method::PUT);
+    assert!(path.starts_with(&format!(
+        "/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/rstify-{}-0-",
+        webhook["id"]
+    )));
+    assert_eq!(headers["authorization"], "Bearer syt_token");
+    assert_eq!(body["msgtype"], "m.notice");
+    assert_eq!(body["format"], "org.matrix.custom.html");
+    let html = body["formatted_body"].as_str().unwrap();
+    assert!(html.contains("Deploy &lt;done&gt;"));
+    assert!(html.contains("data-mx-color=\"#9E9E9E\""));
+    assert!(body["body"]
+        .as_str()
+        .unwrap()
+        .starts_with("Deploy <done>\n"));
+}
+
+#[tokio::test]
+async fn only_chat_webhooks_get_signed_attachment_links() {
+    let (host, received) = start_mock().await;
+    let base = format!("http://{}", host);
+    let app = common::setup_with(|state| {
+        state.with_base_url(Some("https://push.example.com".to_string()))
+    })
+    .await;
+    let topic_id = seed::create_topic(&app.pool, 2, "chat-files").await;
+    for (name, webhook_type) in [("slack", "slack"), ("custom", "custom")] {
+        let (status, _) = create(
+            &app,
+            json!({
+                "name": name,
+                "webhookType": webhook_type,
+                "direction": "outgoing",
+                "targetTopicId": topic_id,
+                "targetUrl": format!("{}/{}", base, name),
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let upload = axum::http::Request::builder()
+        .method(Method::PUT)
+        .uri("/chat-files")
+        .header("Authorization", format!("Bearer {}", app.user_token))
+        .header("Filename", "report.txt")
+        .body(axum::body::Body::from("quarterly numbers"))
+        .unwrap();
+    let resp = app.router.clone().oneshot(upload).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for _ in 0..100 {
+        if received.lock().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let received = received.lock().unwrap();
+    let body = |path: &str| {
+        received
+            .iter()
+            .find(|(_, p, _, _)| p == path)
+            .map(|(_, _, _, body)| body.to_string())
+            .unwrap()
+    };
+    assert!(body("/slack").contains("https://push.example.com/api/attachments/"));
+    assert!(body("/slack").contains("sig="));
+    // The custom webhook gets the message as stored, without a link anyone
+    // holding the payload could open.
+    let custom = body("/custom");
+    assert!(custom.contains("report.txt"));
+    assert!(!custom.contains("sig="), "{custom}");
+}
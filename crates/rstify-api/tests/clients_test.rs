@@ -176,3 +176,450 @@ async fn admin_can_manage_any_client() {
         "Deleted client should not appear in admin's list"
     );
 }
+
+// ---------------------------------------------------------------------------
+// Expiry and network restrictions
+// ---------------------------------------------------------------------------
+
+/// GET with a client token, as if sent from `ip` (the router is driven
+/// directly, so the peer address is injected the way `serve` would).
+fn get_from(uri: &str, token: &str, ip: &str) -> axum::http::Request<axum::body::Body> {
+    let addr: std::net::SocketAddr = format!("{}:40000", ip).parse().unwrap();
+    axum::http::Request::builder()
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", token))
+        .extension(axum::extract::ConnectInfo(addr))
+        .body(axum::body::Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn create_client_with_expiry_and_allowed_ips() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/client",
+            &app.user_token,
+            serde_json::json!({
+                "name": "ci-runner",
+                "expires_at": "2999-01-01T00:00:00Z",
+                "allowed_ips": ["10.0.0.0/8", "192.168.1.7"]
+            }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert_eq!(body["expires_at"], "2999-01-01 00:00:00Z");
+    assert_eq!(body["allowed_ips"], r#"["10.0.0.0/8","192.168.1.7/32"]"#);
+}
+
+#[tokio::test]
+async fn create_client_rejects_invalid_restrictions() {
+    let app = common::setup().await;
+
+    for body in [
+        serde_json::json!({ "name": "bad-cidr", "allowed_ips": ["10.0.0.0/33"] }),
+        serde_json::json!({ "name": "past", "expires_at": "2000-01-01 00:00:00" }),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json("/client", &app.user_token, body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn expired_client_token_is_rejected() {
+    let app = common::setup().await;
+    let (client_id, token) = common::seed::create_client(&app.pool, 2, "stale").await;
+    sqlx::query("UPDATE clients SET expires_at = datetime('now', '-1 minute') WHERE id = ?")
+        .bind(client_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/current/user", &token))
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn ip_restricted_token_enforced_and_last_use_recorded() {
+    let app = common::setup().await;
+    let (client_id, token) = common::seed::create_client(&app.pool, 2, "pi").await;
+    sqlx::query("UPDATE clients SET allowed_ips = '[\"10.0.0.0/8\"]' WHERE id = ?")
+        .bind(client_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(get_from("/current/user", &token, "192.168.1.1"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Without a known peer address the allow-list fails closed.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/current/user", &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(get_from("/current/user", &token, "10.1.2.3"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let (last_ip, last_at): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT last_used_ip, last_used_at FROM clients WHERE id = ?")
+            .bind(client_id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(last_ip.as_deref(), Some("10.1.2.3"));
+    assert!(last_at.is_some());
+}
+
+#[tokio::test]
+async fn update_client_clears_restrictions() {
+    let app = common::setup().await;
+    let (client_id, _) = common::seed::create_client(&app.pool, 2, "temp").await;
+    sqlx::query(
+        "UPDATE clients SET expires_at = '2999-01-01 00:00:00', allowed_ips = '[\"10.0.0.0/8\"]' \
+         WHERE id = ?",
+    )
+    .bind(client_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/client/{}", client_id),
+            &app.user_token,
+            serde_json::json!({ "expires_at": "", "allowed_ips": [] }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert!(body["expires_at"].is_null());
+    assert!(body["allowed_ips"].is_null());
+}
+
+#[tokio::test]
+async fn cleanup_warns_before_expiry_and_purges_after() {
+    let app = common::setup().await;
+    let (soon_id, _) = common::seed::create_client(&app.pool, 2, "expiring").await;
+    let (gone_id, _) = common::seed::create_client(&app.pool, 2, "expired").await;
+    sqlx::query("UPDATE clients SET expires_at = datetime('now', '+1 day') WHERE id = ?")
+        .bind(soon_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE clients SET expires_at = datetime('now', '-1 day') WHERE id = ?")
+        .bind(gone_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    let warned = rstify_jobs::cleanup::notify_expiring_client_tokens(&app.repos)
+        .await
+        .unwrap();
+    assert_eq!(warned, 1);
+    // A second run must not warn again.
+    let warned = rstify_jobs::cleanup::notify_expiring_client_tokens(&app.repos)
+        .await
+        .unwrap();
+    assert_eq!(warned, 0);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/message", &app.user_token))
+        .await
+        .unwrap();
+    let body = common::body_json(resp).await;
+    let messages = body["messages"].as_array().unwrap();
+    assert!(messages
+        .iter()
+        .any(|m| m["message"].as_str().unwrap().contains("'expiring'")));
+
+    let purged = rstify_jobs::cleanup::purge_expired_client_tokens(&app.repos)
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM clients WHERE id IN (?, ?)")
+        .bind(soon_id)
+        .bind(gone_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 1);
+}
+
+// ---------------------------------------------------------------------------
+// Topic-scoped tokens
+// ---------------------------------------------------------------------------
+
+async fn scoped_client(app: &common::TestApp, scopes: serde_json::Value) -> String {
+    let (client_id, token) = common::seed::create_client(&app.pool, 2, "scoped").await;
+    sqlx::query("UPDATE clients SET scopes = ? WHERE id = ?")
+        .bind(scopes.to_string())
+        .bind(client_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    token
+}
+
+#[tokio::test]
+async fn topic_scoped_token_limited_to_matching_topics() {
+    let app = common::setup().await;
+    common::seed::create_topic(&app.pool, 2, "home.sensors.temp").await;
+    common::seed::create_topic(&app.pool, 2, "billing").await;
+    let token = scoped_client(
+        &app,
+        serde_json::json!(["read", "write", "topic:write:home.sensors.*"]),
+    )
+    .await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics/home.sensors.temp/publish",
+            &token,
+            serde_json::json!({"message": "21.5"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics/billing/publish",
+            &token,
+            serde_json::json!({"message": "nope"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // No topic:read scope, so reading is denied even on the writable topic.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            "/api/topics/home.sensors.temp/messages",
+            &token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/topics", &token))
+        .await
+        .unwrap();
+    let body = common::body_json(resp).await;
+    assert_eq!(body.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn topic_scoped_token_can_read_matching_topics() {
+    let app = common::setup().await;
+    common::seed::create_topic(&app.pool, 2, "alerts.db.disk").await;
+    common::seed::create_topic(&app.pool, 2, "billing").await;
+    let token = scoped_client(&app, serde_json::json!(["read", "topic:read:alerts.**"])).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/topics/alerts.db.disk/messages", &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/topics", &token))
+        .await
+        .unwrap();
+    let body = common::body_json(resp).await;
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["alerts.db.disk"]);
+}
+
+async fn publish_to(app: &common::TestApp, topic: &str, message: &str) -> i64 {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/topics/{}/publish", topic),
+            &app.user_token,
+            serde_json::json!({ "message": message }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    common::body_json(resp).await["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn topic_scoped_token_searches_only_matching_topics() {
+    let app = common::setup().await;
+    common::seed::create_topic(&app.pool, 2, "alerts.db").await;
+    common::seed::create_topic(&app.pool, 2, "billing").await;
+    publish_to(&app, "alerts.db", "disk full").await;
+    publish_to(&app, "billing", "invoice overdue").await;
+    let token = scoped_client(&app, serde_json::json!(["read", "topic:read:alerts.**"])).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/message/search", &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    let found: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(found, vec!["disk full"]);
+}
+
+#[tokio::test]
+async fn topic_scoped_token_edits_only_matching_topics() {
+    let app = common::setup().await;
+    common::seed::create_topic(&app.pool, 2, "home.sensors.temp").await;
+    common::seed::create_topic(&app.pool, 2, "billing").await;
+    let reading = publish_to(&app, "home.sensors.temp", "21.5").await;
+    let invoice = publish_to(&app, "billing", "invoice overdue").await;
+    let token = scoped_client(
+        &app,
+        serde_json::json!(["read", "write", "topic:write:home.sensors.*"]),
+    )
+    .await;
+
+    let edit = |id: i64| {
+        common::put_json(
+            &format!("/message/{}", id),
+            &token,
+            serde_json::json!({"message": "edited"}),
+        )
+    };
+    let resp = app.router.clone().oneshot(edit(invoice)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = app.router.clone().oneshot(edit(reading)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(&format!("/message/{}", invoice), &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(&format!("/message/{}", reading), &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn admin_scope_does_not_lift_topic_scopes() {
+    let app = common::setup().await;
+    common::seed::create_topic(&app.pool, 2, "alerts.db").await;
+    common::seed::create_topic(&app.pool, 2, "billing").await;
+    let token = scoped_client(&app, serde_json::json!(["admin", "topic:read:alerts.**"])).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/topics/alerts.db/messages", &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/topics/billing/messages", &token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics/alerts.db/publish",
+            &token,
+            serde_json::json!({"message": "nope"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn create_client_rejects_invalid_scopes() {
+    let app = common::setup().await;
+
+    for scopes in [
+        serde_json::json!(["superuser"]),
+        serde_json::json!(["topic:write:"]),
+        serde_json::json!(["topic:publish:alerts"]),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json(
+                "/client",
+                &app.user_token,
+                serde_json::json!({"name": "bad", "scopes": scopes}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{scopes}");
+    }
+}
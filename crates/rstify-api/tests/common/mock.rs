@@ -0,0 +1,43 @@
+//! An in-process HTTP server standing in for webhook targets and other
+//! notification services.
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::Router;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+/// (method, path, headers, JSON body) of every request the mock received.
+pub type Received = Arc<Mutex<Vec<(Method, String, HeaderMap, Value)>>>;
+
+/// Records everything but GETs, which get a small file back as attachment
+/// downloads expect.
+async fn record(
+    State(received): State<Received>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, &'static str) {
+    if method == Method::GET {
+        return (StatusCode::OK, "boot ok\n");
+    }
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    received
+        .lock()
+        .unwrap()
+        .push((method, uri.path().to_string(), headers, body));
+    (StatusCode::OK, "{}")
+}
+
+/// Start the mock and allow webhooks to reach it. Returns its `host:port`.
+pub async fn start_mock() -> (String, Received) {
+    rstify_jobs::ssrf::set_allow_private_targets(true);
+    let received = Received::default();
+    let router = Router::new().fallback(record).with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (addr.to_string(), received)
+}
@@ -1,3 +1,4 @@
+pub mod mock;
 pub mod seed;
 
 use axum::body::Body;
@@ -9,6 +10,7 @@ use rstify_api::middleware::rate_limit::RateLimiter;
 use rstify_api::state::AppState;
 use rstify_auth::password::hash_password;
 use rstify_auth::tokens::create_jwt;
+use rstify_core::repositories::Repositories;
 use rstify_db::pool::Database;
 use sqlx::SqlitePool;
 
@@ -16,6 +18,7 @@ use sqlx::SqlitePool;
 pub struct TestApp {
     pub router: Router,
     pub pool: SqlitePool,
+    pub repos: Repositories,
     pub admin_token: String,
     pub user_token: String,
     pub jwt_secret: String,
@@ -24,6 +27,12 @@ pub struct TestApp {
 /// Spin up a fresh in-memory database, apply all migrations, seed two users,
 /// and return a ready-to-use `TestApp`.
 pub async fn setup() -> TestApp {
+    setup_with(|state| state).await
+}
+
+/// Like [`setup`], but lets the test adjust the `AppState` (e.g. swap the
+/// attachment store) before the router is built.
+pub async fn setup_with(configure: impl FnOnce(AppState) -> AppState) -> TestApp {
     let jwt_secret = "test-jwt-secret-for-integration-tests".to_string();
     let upload_dir = "/tmp/rstify-test-uploads".to_string();
     let max_upload_size: usize = 10 * 1024 * 1024; // 10 MB
@@ -36,7 +45,7 @@ pub async fn setup() -> TestApp {
         .await
         .expect("Failed to apply migrations to in-memory DB");
 
-    let pool = db.pool().clone();
+    let pool = db.sqlite_pool().expect("test database is SQLite").clone();
 
     // 2. Seed admin user (id=1)
     let admin_hash = hash_password("admin123".to_string())
@@ -75,20 +84,22 @@ pub async fn setup() -> TestApp {
         create_jwt(2, "testuser", false, &jwt_secret).expect("Failed to create user JWT");
 
     // 5. Build AppState and production router
-    let state = AppState::new(
-        pool.clone(),
+    let state = configure(AppState::new(
+        db.clone(),
         jwt_secret.clone(),
         upload_dir,
         max_upload_size,
-    );
+    ));
 
     // Use a generous rate limiter so tests never get throttled
     let limiter = RateLimiter::new(10_000, 10_000.0);
+    let repos = state.repositories();
     let router = rstify_api::build_router(state, limiter);
 
     TestApp {
         router,
         pool,
+        repos,
         admin_token,
         user_token,
         jwt_secret,
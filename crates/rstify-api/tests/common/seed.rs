@@ -96,3 +96,64 @@ pub async fn grant_topic_permission(
     .await
     .expect("Failed to seed topic permission")
 }
+
+/// Create a regular (non-admin) user. Returns the user id.
+pub async fn create_user(pool: &SqlitePool, username: &str) -> i64 {
+    sqlx::query_scalar(
+        "INSERT INTO users (username, password_hash, is_admin, created_at, updated_at) \
+         VALUES (?, 'x', FALSE, datetime('now'), datetime('now')) RETURNING id",
+    )
+    .bind(username)
+    .fetch_one(pool)
+    .await
+    .expect("Failed to seed user")
+}
+
+/// Create a group with the given `(user_id, role)` members. Returns the group id.
+pub async fn create_group(pool: &SqlitePool, name: &str, members: &[(i64, &str)]) -> i64 {
+    let id: i64 = sqlx::query_scalar("INSERT INTO user_groups (name) VALUES (?) RETURNING id")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to seed group");
+    for (user_id, role) in members {
+        sqlx::query("INSERT INTO group_members (group_id, user_id, role) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(user_id)
+            .bind(role)
+            .execute(pool)
+            .await
+            .expect("Failed to seed group member");
+    }
+    id
+}
+
+/// Create a topic only its owner, and the members of `group_id` if set, can
+/// read or write. Returns the topic id.
+pub async fn create_private_topic(
+    pool: &SqlitePool,
+    owner_id: i64,
+    name: &str,
+    group_id: Option<i64>,
+) -> i64 {
+    sqlx::query_scalar(
+        "INSERT INTO topics (name, owner_id, group_id, everyone_read, everyone_write, created_at) \
+         VALUES (?, ?, ?, FALSE, FALSE, datetime('now')) RETURNING id",
+    )
+    .bind(name)
+    .bind(owner_id)
+    .bind(group_id)
+    .fetch_one(pool)
+    .await
+    .expect("Failed to seed private topic")
+}
+
+/// Give a user an email address.
+pub async fn set_email(pool: &SqlitePool, user_id: i64, email: &str) {
+    sqlx::query("UPDATE users SET email = ? WHERE id = ?")
+        .bind(email)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .expect("Failed to set email");
+}
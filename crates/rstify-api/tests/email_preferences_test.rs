@@ -0,0 +1,199 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::http::StatusCode;
+use common::seed;
+use rstify_api::email::email_recipients;
+use rstify_api::helpers::publish::DeliveryTarget;
+use rstify_api::state::AppState;
+use rstify_core::models::MessageResponse;
+use serde_json::json;
+use tower::ServiceExt;
+
+const URI: &str = "/current/user/email-preferences";
+
+fn message(appid: Option<i64>, topic: Option<&str>, priority: i32) -> MessageResponse {
+    MessageResponse {
+        id: 1,
+        appid,
+        topic: topic.map(str::to_string),
+        title: None,
+        message: "disk full".to_string(),
+        priority,
+        tags: None,
+        click_url: None,
+        icon_url: None,
+        actions: None,
+        extras: None,
+        content_type: None,
+        source: None,
+        inbox: true,
+        attachments: None,
+        date: "2026-01-01T00:00:00Z".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn preferences_are_validated_and_saved() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(URI, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let prefs = common::body_json(resp).await;
+    assert_eq!(prefs["enabled"], false);
+    assert_eq!(prefs["min_priority"], 5);
+
+    // Email needs an address on the account.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            URI,
+            &app.user_token,
+            json!({"enabled": true}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    seed::set_email(&app.pool, 2, "test@example.com").await;
+
+    let (admin_app, _) = seed::create_application(&app.pool, 1, "admin-app").await;
+    for (body, status) in [
+        (
+            json!({"enabled": true, "topics": ["alerts..x"]}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"enabled": true, "min_priority": 11}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"enabled": true, "application_ids": [admin_app]}),
+            StatusCode::FORBIDDEN,
+        ),
+        // Quiet windows are quiet hours schedules now.
+        (
+            json!({"enabled": true, "quiet_start": "22:00", "quiet_end": "07:00"}),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::put_json(URI, &app.user_token, body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), status);
+    }
+
+    let (own_app, _) = seed::create_application(&app.pool, 2, "backup").await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            URI,
+            &app.user_token,
+            json!({
+                "enabled": true,
+                "min_priority": 7,
+                "topics": ["alerts.*"],
+                "application_ids": [own_app],
+            }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(URI, &app.user_token))
+        .await
+        .unwrap();
+    let prefs = common::body_json(resp).await;
+    assert_eq!(prefs["enabled"], true);
+    assert_eq!(prefs["min_priority"], 7);
+    assert_eq!(prefs["topics"], r#"["alerts.*"]"#);
+    assert_eq!(prefs["application_ids"], format!("[{}]", own_app));
+}
+
+#[tokio::test]
+async fn recipients_follow_preferences_and_permissions() {
+    let mut state: Option<AppState> = None;
+    let app = common::setup_with(|s| {
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    let state = state.unwrap();
+
+    seed::set_email(&app.pool, 1, "admin@example.com").await;
+    seed::set_email(&app.pool, 2, "test@example.com").await;
+    let carol = seed::create_user(&app.pool, "carol").await;
+    seed::set_email(&app.pool, carol, "carol@example.com").await;
+
+    seed::create_topic(&app.pool, 1, "alerts.disk").await;
+    sqlx::query("UPDATE topics SET everyone_read = FALSE WHERE name = 'alerts.disk'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let topic = state
+        .topic_repo
+        .find_by_name("alerts.disk")
+        .await
+        .unwrap()
+        .unwrap();
+    seed::grant_topic_permission(&app.pool, 2, "alerts.**", true, false).await;
+
+    // The admin owns the topic and takes the defaults; testuser and carol ask
+    // for alerts, but carol can't read them.
+    let (own_app, _) = seed::create_application(&app.pool, 2, "backup").await;
+    let (other_app, _) = seed::create_application(&app.pool, 2, "cron").await;
+    for (token, body) in [
+        (&app.admin_token, json!({"enabled": true})),
+        (
+            &app.user_token,
+            json!({"enabled": true, "min_priority": 4, "topics": ["alerts.*"], "application_ids": [own_app]}),
+        ),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::put_json(URI, token, body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    sqlx::query(
+        "INSERT INTO email_preferences (user_id, enabled, topics) VALUES (?, TRUE, '[\"alerts.*\"]')",
+    )
+    .bind(carol)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let target = DeliveryTarget::Topic(&topic);
+    let recipients =
+        email_recipients(&state, &target, &message(None, Some("alerts.disk"), 5)).await;
+    assert_eq!(
+        recipients,
+        [
+            (1, "admin@example.com".to_string()),
+            (2, "test@example.com".to_string())
+        ]
+    );
+    let recipients =
+        email_recipients(&state, &target, &message(None, Some("alerts.disk"), 4)).await;
+    assert_eq!(recipients, [(2, "test@example.com".to_string())]);
+
+    let target = DeliveryTarget::User(2);
+    let recipients = email_recipients(&state, &target, &message(Some(own_app), None, 5)).await;
+    assert_eq!(recipients, [(2, "test@example.com".to_string())]);
+    let recipients = email_recipients(&state, &target, &message(Some(other_app), None, 9)).await;
+    assert!(recipients.is_empty());
+}
@@ -0,0 +1,330 @@
+//! Sends mail through the queue to an in-process SMTP sink.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::body::Body;
+use axum::http::{self, Request, StatusCode};
+use common::seed;
+use rstify_api::state::AppState;
+use rstify_jobs::email::{
+    process_queue, EmailConfig, EmailOutbox, Mailer, SmtpSecurity, MAX_ATTEMPTS,
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tower::ServiceExt;
+
+/// (recipients, raw message) for every message the sink accepted.
+type Inbox = Arc<Mutex<Vec<(Vec<String>, String)>>>;
+
+/// A minimal SMTP server. Recipients containing `tempfail` get a 451 and
+/// those containing `reject` a 550; everything else is accepted.
+async fn start_sink() -> (u16, Inbox) {
+    let inbox = Inbox::default();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let accepted = inbox.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let inbox = accepted.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+                let mut rcpt = Vec::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let upper = line.to_ascii_uppercase();
+                    let reply: &[u8] = if upper.starts_with("EHLO") || upper.starts_with("HELO") {
+                        b"250 sink\r\n"
+                    } else if upper.starts_with("MAIL FROM") || upper.starts_with("RSET") {
+                        rcpt.clear();
+                        b"250 OK\r\n"
+                    } else if upper.starts_with("RCPT TO") {
+                        if line.contains("tempfail") {
+                            b"451 4.3.0 Try again later\r\n"
+                        } else if line.contains("reject") {
+                            b"550 5.1.1 No such user\r\n"
+                        } else {
+                            rcpt.push(line[8..].trim().to_string());
+                            b"250 OK\r\n"
+                        }
+                    } else if upper == "DATA" {
+                        write.write_all(b"354 Go ahead\r\n").await.unwrap();
+                        let mut data = String::new();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if line == "." {
+                                break;
+                            }
+                            data.push_str(&line);
+                            data.push('\n');
+                        }
+                        inbox
+                            .lock()
+                            .unwrap()
+                            .push((std::mem::take(&mut rcpt), data));
+                        b"250 OK queued\r\n"
+                    } else if upper == "QUIT" {
+                        let _ = write.write_all(b"221 Bye\r\n").await;
+                        return;
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    if write.write_all(reply).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    (port, inbox)
+}
+
+async fn setup() -> (common::TestApp, AppState, EmailOutbox, Inbox) {
+    let (port, inbox) = start_sink().await;
+    let mut state: Option<AppState> = None;
+    let mut outbox: Option<EmailOutbox> = None;
+    let app = common::setup_with(|s| {
+        let mailer = Mailer::new(&EmailConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::Plain,
+            credentials: None,
+            from: "rstify@example.com".to_string(),
+        })
+        .unwrap();
+        let queue = EmailOutbox::new(s.email_repo.clone(), mailer);
+        let s = s.with_email(queue.clone());
+        outbox = Some(queue);
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    (app, state.unwrap(), outbox.unwrap(), inbox)
+}
+
+async fn deliveries(app: &common::TestApp, query: &str) -> Vec<serde_json::Value> {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/admin/email/deliveries{}", query),
+            &app.admin_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    common::body_json(resp).await.as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn preference_emails_are_queued_and_delivered() {
+    let (app, state, outbox, inbox) = setup().await;
+    seed::set_email(&app.pool, 2, "test@example.com").await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            "/current/user/email-preferences",
+            &app.user_token,
+            json!({"enabled": true}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let (_, app_token) = seed::create_application(&app.pool, 2, "backup").await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(http::Method::POST)
+                .uri("/message")
+                .header("X-Gotify-Key", &app_token)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({"title": "Backup", "message": "finished", "priority": 8}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Email is queued off the request path.
+    let mut queued = Vec::new();
+    for _ in 0..100 {
+        queued = state.email_repo.list(Some("pending"), 10, 0).await.unwrap();
+        if !queued.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].recipient, "test@example.com");
+    assert_eq!(queued[0].subject, "Backup");
+
+    assert_eq!(process_queue(&outbox).await.unwrap(), 1);
+    {
+        let inbox = inbox.lock().unwrap();
+        assert_eq!(inbox.len(), 1);
+        let (rcpt, data) = &inbox[0];
+        assert_eq!(rcpt, &["<test@example.com>"]);
+        assert!(data.contains("Subject: Backup"));
+        assert!(data.contains("multipart/alternative"));
+        assert!(data.contains("text/html"));
+    }
+
+    let log = deliveries(&app, "?status=sent").await;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0]["recipient"], "test@example.com");
+    assert_eq!(log[0]["attempts"], 1);
+    assert!(log[0]["sent_at"].is_string());
+    assert!(log[0].get("text_body").is_none());
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/admin/email/deliveries", &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn transient_failures_retry_and_permanent_ones_fail() {
+    let (app, state, outbox, inbox) = setup().await;
+    let retry = outbox
+        .enqueue("tempfail@example.com", "Later", "body", None)
+        .await
+        .unwrap();
+    let reject = outbox
+        .enqueue("reject@example.com", "Never", "body", None)
+        .await
+        .unwrap();
+
+    assert_eq!(process_queue(&outbox).await.unwrap(), 0);
+    let rejected = state
+        .email_repo
+        .find_by_id(reject.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(rejected.status, "failed");
+    assert_eq!(rejected.attempts, 1);
+    assert!(rejected.last_error.unwrap().contains("550"));
+
+    let retried = state
+        .email_repo
+        .find_by_id(retry.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(retried.status, "pending");
+    assert_eq!(retried.attempts, 1);
+    assert!(retried.last_error.unwrap().contains("451"));
+
+    // Not due yet, so nothing is tried.
+    assert_eq!(process_queue(&outbox).await.unwrap(), 0);
+    let unchanged = state
+        .email_repo
+        .find_by_id(retry.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(unchanged.attempts, 1);
+
+    // The last allowed attempt gives up.
+    sqlx::query(
+        "UPDATE email_queue SET next_attempt_at = datetime('now', '-1 minute'), attempts = ? \
+         WHERE id = ?",
+    )
+    .bind(MAX_ATTEMPTS - 1)
+    .bind(retry.id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(process_queue(&outbox).await.unwrap(), 0);
+    let failed = state
+        .email_repo
+        .find_by_id(retry.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(failed.status, "failed");
+    assert_eq!(failed.attempts, MAX_ATTEMPTS);
+
+    assert!(inbox.lock().unwrap().is_empty());
+    assert_eq!(deliveries(&app, "?status=failed").await.len(), 2);
+    assert!(deliveries(&app, "?status=pending").await.is_empty());
+}
+
+#[tokio::test]
+async fn admin_test_email_reports_the_result() {
+    let (app, _state, _outbox, inbox) = setup().await;
+    let uri = "/api/admin/email/test";
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(uri, &app.user_token, json!({})))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // The admin has no address of their own yet.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(uri, &app.admin_token, json!({})))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    seed::set_email(&app.pool, 1, "admin@example.com").await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(uri, &app.admin_token, json!({})))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = common::body_json(resp).await;
+    assert_eq!(result["success"], true);
+    assert_eq!(result["recipient"], "admin@example.com");
+    assert_eq!(inbox.lock().unwrap().len(), 1);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            uri,
+            &app.admin_token,
+            json!({"to": "reject@example.com"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let result = common::body_json(resp).await;
+    assert_eq!(result["success"], false);
+    assert!(result["error"].as_str().unwrap().contains("550"));
+
+    // Test sends bypass the queue.
+    assert!(deliveries(&app, "").await.is_empty());
+
+    let plain = common::setup().await;
+    let resp = plain
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            uri,
+            &plain.admin_token,
+            json!({"to": "admin@example.com"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
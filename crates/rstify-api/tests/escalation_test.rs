@@ -0,0 +1,314 @@
+//! Escalation policies: configuring them on topics, the step-by-step
+//! escalation of unacknowledged messages, and acknowledging.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::seed;
+use rstify_core::models::{EscalationStep, MessageResponse};
+use rstify_jobs::escalation::{run_due_escalations, EscalationStepFn};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+async fn set_policy(app: &common::TestApp, topic: &str, policy: Value) -> (StatusCode, Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/api/topics/{}", topic),
+            &app.user_token,
+            json!({ "escalation_policy": policy }),
+        ))
+        .await
+        .unwrap();
+    let status = resp.status();
+    (status, common::body_json(resp).await)
+}
+
+async fn publish(app: &common::TestApp, topic: &str, priority: i32) -> i64 {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/topics/{}/publish", topic),
+            &app.user_token,
+            json!({"title": "Database down", "message": "primary unreachable", "priority": priority}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    common::body_json(resp).await["id"].as_i64().unwrap()
+}
+
+async fn ack(app: &common::TestApp, id: i64, token: &str) -> (StatusCode, Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/messages/{}/ack", id),
+            token,
+            json!({}),
+        ))
+        .await
+        .unwrap();
+    let status = resp.status();
+    (status, common::body_json(resp).await)
+}
+
+#[tokio::test]
+async fn policies_are_validated() {
+    let app = common::setup().await;
+    seed::create_private_topic(&app.pool, 2, "oncall-validate", None).await;
+    let outsider = seed::create_user(&app.pool, "outsider").await;
+
+    for policy in [
+        json!({"steps": [{"after_minutes": 5, "action": "page"}]}),
+        json!({"steps": [{"after_minutes": 0, "action": "push"}]}),
+        json!({"min_priority": 11, "steps": [{"after_minutes": 5, "action": "push"}]}),
+        json!({"steps": [{"after_minutes": 5, "action": "push", "user_id": outsider}]}),
+        json!({"steps": [{"after_minutes": 5, "action": "push", "user_id": 1, "group_id": 1}]}),
+        json!({"steps": [{"after_minutes": 5, "action": "email", "group_id": 999}]}),
+    ] {
+        let (status, body) = set_policy(&app, "oncall-validate", policy.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", policy, body);
+    }
+
+    let (status, topic) = set_policy(
+        &app,
+        "oncall-validate",
+        json!({"steps": [{"after_minutes": 5, "action": "push"}]}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", topic);
+    let policy: Value = serde_json::from_str(topic["escalation_policy"].as_str().unwrap()).unwrap();
+    assert_eq!(policy["min_priority"], 8);
+
+    // No steps removes it.
+    let (status, topic) = set_policy(&app, "oncall-validate", json!({"steps": []})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(topic.get("escalation_policy").is_none());
+}
+
+#[tokio::test]
+async fn unacknowledged_messages_escalate_until_acked() {
+    let app = common::setup().await;
+    seed::create_private_topic(&app.pool, 2, "oncall", None).await;
+    let (status, body) = set_policy(
+        &app,
+        "oncall",
+        json!({
+            "min_priority": 8,
+            "steps": [
+                {"after_minutes": 5, "action": "push"},
+                {"after_minutes": 10, "action": "push", "user_id": 1},
+                {"after_minutes": 15, "action": "email"},
+            ],
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let routine = publish(&app, "oncall", 5).await;
+    let urgent = publish(&app, "oncall", 9).await;
+    assert!(app
+        .repos
+        .messages
+        .find_escalation(routine)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(app
+        .repos
+        .messages
+        .find_escalation(urgent)
+        .await
+        .unwrap()
+        .is_some());
+
+    let ran: Arc<Mutex<Vec<(EscalationStep, MessageResponse)>>> = Arc::default();
+    let sink = ran.clone();
+    let run_step: EscalationStepFn = Arc::new(move |_topic, step, message| {
+        let sink = sink.clone();
+        Box::pin(async move {
+            sink.lock().unwrap().push((step, message));
+            "done".to_string()
+        })
+    });
+
+    let now = Utc::now();
+    assert_eq!(
+        run_due_escalations(&app.repos, &run_step, now)
+            .await
+            .unwrap(),
+        0
+    );
+
+    // First step after 5 minutes; the second is then 10 minutes further on.
+    let t1 = now + Duration::minutes(6);
+    assert_eq!(
+        run_due_escalations(&app.repos, &run_step, t1)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        run_due_escalations(&app.repos, &run_step, t1)
+            .await
+            .unwrap(),
+        0
+    );
+    let t2 = t1 + Duration::minutes(11);
+    assert_eq!(
+        run_due_escalations(&app.repos, &run_step, t2)
+            .await
+            .unwrap(),
+        1
+    );
+    {
+        let ran = ran.lock().unwrap();
+        assert_eq!(ran.len(), 2);
+        assert_eq!(ran[0].1.id, urgent);
+        assert_eq!(ran[0].1.topic.as_deref(), Some("oncall"));
+        assert_eq!(ran[0].0.user_id, None);
+        assert_eq!(ran[1].0.user_id, Some(1));
+    }
+
+    // Each step is recorded on the message.
+    let message = app
+        .repos
+        .messages
+        .find_by_id(urgent)
+        .await
+        .unwrap()
+        .unwrap();
+    let extras: Value = serde_json::from_str(message.extras.as_deref().unwrap()).unwrap();
+    let steps = extras["rstify::escalation"]["steps"].as_array().unwrap();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[1]["step"], 2);
+    assert_eq!(steps[1]["user_id"], 1);
+    assert_eq!(steps[1]["result"], "done");
+
+    // Someone who can't read the topic can't acknowledge it.
+    let outsider = seed::create_user(&app.pool, "outsider").await;
+    let (_, outsider_token) = seed::create_client(&app.pool, outsider, "laptop").await;
+    let (status, _) = ack(&app, urgent, &outsider_token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, acked) = ack(&app, urgent, &app.user_token).await;
+    assert_eq!(status, StatusCode::OK, "{}", acked);
+    assert_eq!(acked["extras"]["rstify::ack"]["user_id"], 2);
+    assert_eq!(
+        acked["extras"]["rstify::escalation"]["steps"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    // The first acknowledgement stands.
+    let (status, again) = ack(&app, urgent, &app.admin_token).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again["extras"]["rstify::ack"]["user_id"], 2);
+
+    let later = t2 + Duration::hours(1);
+    assert_eq!(
+        run_due_escalations(&app.repos, &run_step, later)
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(ran.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn concurrent_steps_and_acks_keep_each_other() {
+    let app = common::setup().await;
+    seed::create_private_topic(&app.pool, 2, "oncall", None).await;
+    let id = publish(&app, "oncall", 9).await;
+
+    let steps = (1..=10).map(|n| {
+        let repos = app.repos.clone();
+        tokio::spawn(async move {
+            repos
+                .messages
+                .record_escalation_step(id, &json!({ "step": n }).to_string())
+                .await
+                .unwrap();
+        })
+    });
+    let steps: Vec<_> = steps.collect();
+    let (status, _) = ack(&app, id, &app.user_token).await;
+    assert_eq!(status, StatusCode::OK);
+    for step in steps {
+        step.await.unwrap();
+    }
+
+    let message = app.repos.messages.find_by_id(id).await.unwrap().unwrap();
+    let extras: Value = serde_json::from_str(message.extras.as_deref().unwrap()).unwrap();
+    assert_eq!(extras["rstify::ack"]["user_id"], 2);
+    assert_eq!(
+        extras["rstify::escalation"]["steps"]
+            .as_array()
+            .unwrap()
+            .len(),
+        10
+    );
+}
+
+#[tokio::test]
+async fn editing_a_message_keeps_its_acknowledgement() {
+    let app = common::setup().await;
+    seed::create_private_topic(&app.pool, 2, "oncall", None).await;
+    let id = publish(&app, "oncall", 9).await;
+    app.repos
+        .messages
+        .record_escalation_step(id, &json!({ "step": 1 }).to_string())
+        .await
+        .unwrap();
+    let (status, _) = ack(&app, id, &app.user_token).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/message/{}", id),
+            &app.user_token,
+            json!({
+                "message": "primary back up",
+                "extras": {"client::display": {"contentType": "text/plain"}, "rstify::ack": null},
+            }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let edited = common::body_json(resp).await;
+    assert_eq!(edited["message"], "primary back up");
+    assert_eq!(
+        edited["extras"]["client::display"]["contentType"],
+        "text/plain"
+    );
+    assert_eq!(edited["extras"]["rstify::ack"]["user_id"], 2);
+    assert_eq!(
+        edited["extras"]["rstify::escalation"]["steps"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Extras that can't hold the records are refused.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/message/{}", id),
+            &app.user_token,
+            json!({"extras": [1, 2]}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,318 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::http::{header, StatusCode};
+use common::seed;
+use tower::ServiceExt;
+
+/// testuser owns an application with two messages and a topic with one;
+/// carol has a grant and the admin an application of their own.
+async fn seeded_source() -> common::TestApp {
+    let app = common::setup().await;
+    let (alerts, _) = seed::create_application(&app.pool, 2, "alerts").await;
+    seed::create_message(&app.pool, alerts, 2, "first").await;
+    seed::create_message(&app.pool, alerts, 2, "second").await;
+    sqlx::query("UPDATE messages SET created_at = '2024-01-02 03:04:05' WHERE message = 'first'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let news = seed::create_topic(&app.pool, 2, "news").await;
+    sqlx::query(
+        "INSERT INTO messages (topic_id, message, priority, created_at) \
+         VALUES (?, 'headline', 3, datetime('now'))",
+    )
+    .bind(news)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    seed::create_webhook(&app.pool, 2, "hook").await;
+
+    let carol = seed::create_user(&app.pool, "carol").await;
+    seed::grant_topic_permission(&app.pool, carol, "news", true, false).await;
+    seed::create_application(&app.pool, 1, "admin-app").await;
+    app
+}
+
+async fn export(app: &common::TestApp, uri: &str, token: &str) -> serde_json::Value {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(uri, token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers()[header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .contains("rstify-export-"));
+    common::body_json(resp).await
+}
+
+async fn import(
+    app: &common::TestApp,
+    uri: &str,
+    token: &str,
+    doc: &serde_json::Value,
+) -> serde_json::Value {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(uri, token, doc.clone()))
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = common::body_json(resp).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+#[tokio::test]
+async fn full_export_imports_into_another_instance() {
+    let source = seeded_source().await;
+    let doc = export(&source, "/api/admin/export", &source.admin_token).await;
+    assert_eq!(doc["format"], "rstify-export");
+    assert_eq!(doc["users"].as_array().unwrap().len(), 3);
+    assert!(doc["users"][0]["password_hash"].is_string());
+    assert_eq!(doc["messages"].as_array().unwrap().len(), 3);
+    assert_eq!(doc["permissions"].as_array().unwrap().len(), 1);
+
+    // Give the target an application first so the imported ids must move.
+    let target = common::setup().await;
+    seed::create_application(&target.pool, 1, "existing").await;
+    seed::create_application(&target.pool, 1, "admin-app").await;
+
+    let report = import(&target, "/api/admin/import", &target.admin_token, &doc).await;
+    assert_eq!(report["users"]["created"], 1);
+    assert_eq!(report["users"]["matched"], 2);
+    assert_eq!(report["applications"]["created"], 1);
+    assert_eq!(report["applications"]["matched"], 1);
+    assert_eq!(report["topics"]["created"], 1);
+    assert_eq!(report["permissions"]["created"], 1);
+    assert_eq!(report["webhooks"]["created"], 1);
+    assert_eq!(report["messages"], 3);
+    // The matched admin-app keeps the target's token, which is reported.
+    let skipped = report["skipped"].as_array().unwrap();
+    assert_eq!(skipped.len(), 1);
+    assert!(skipped[0]
+        .as_str()
+        .unwrap()
+        .contains("application 'admin-app'"));
+
+    let exported = doc["applications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["name"] == "alerts")
+        .unwrap();
+    let (app_id, token): (i64, String) =
+        sqlx::query_as("SELECT id, token FROM applications WHERE name = 'alerts' AND user_id = 2")
+            .fetch_one(&target.pool)
+            .await
+            .unwrap();
+    assert_eq!(token, exported["token"].as_str().unwrap());
+    assert_ne!(app_id, exported["id"].as_i64().unwrap());
+    let (created_at,): (String,) = sqlx::query_as(
+        "SELECT created_at FROM messages WHERE application_id = ? AND message = 'first'",
+    )
+    .bind(app_id)
+    .fetch_one(&target.pool)
+    .await
+    .unwrap();
+    assert_eq!(created_at, "2024-01-02 03:04:05");
+    let (grants,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM topic_permissions p JOIN users u ON u.id = p.user_id \
+         WHERE u.username = 'carol' AND p.topic_pattern = 'news'",
+    )
+    .fetch_one(&target.pool)
+    .await
+    .unwrap();
+    assert_eq!(grants, 1);
+
+    // Named records are matched on a second run; messages are added again.
+    let again = import(&target, "/api/admin/import", &target.admin_token, &doc).await;
+    assert_eq!(again["users"]["created"], 0);
+    assert_eq!(again["applications"]["created"], 0);
+    assert_eq!(again["topics"]["matched"], 1);
+    assert_eq!(again["webhooks"]["matched"], 1);
+    assert_eq!(again["permissions"]["matched"], 1);
+    assert_eq!(again["messages"], 3);
+}
+
+#[tokio::test]
+async fn users_export_and_import_their_own_data() {
+    let source = seeded_source().await;
+    let doc = export(&source, "/api/export?attachments=false", &source.user_token).await;
+    assert_eq!(doc["user"], "testuser");
+    let users = doc["users"].as_array().unwrap();
+    assert_eq!(users.len(), 1);
+    assert!(users[0].get("password_hash").is_none());
+    let apps: Vec<&str> = doc["applications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(apps, ["alerts"]);
+    assert!(doc["permissions"].as_array().unwrap().is_empty());
+    assert_eq!(doc["messages"].as_array().unwrap().len(), 3);
+
+    // Into the admin of another instance: everything changes hands.
+    let target = common::setup().await;
+    let report = import(&target, "/api/import", &target.admin_token, &doc).await;
+    assert_eq!(report["users"]["created"], 0);
+    assert_eq!(report["applications"]["created"], 1);
+    let (owner,): (i64,) = sqlx::query_as("SELECT user_id FROM applications WHERE name = 'alerts'")
+        .fetch_one(&target.pool)
+        .await
+        .unwrap();
+    assert_eq!(owner, 1);
+    let (topic_owner,): (i64,) = sqlx::query_as("SELECT owner_id FROM topics WHERE name = 'news'")
+        .fetch_one(&target.pool)
+        .await
+        .unwrap();
+    assert_eq!(topic_owner, 1);
+
+    // A topic name taken by someone else is not merged into.
+    let other = common::setup().await;
+    seed::create_topic(&other.pool, 1, "news").await;
+    let report = import(&other, "/api/import", &other.user_token, &doc).await;
+    assert_eq!(report["topics"]["created"], 0);
+    assert_eq!(report["messages"], 2);
+    assert_eq!(report["skipped"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn admin_routes_are_admin_only_and_documents_are_checked() {
+    let app = common::setup().await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/admin/export", &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let doc = export(&app, "/api/export", &app.user_token).await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/admin/import",
+            &app.user_token,
+            doc.clone(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let mut newer = doc.clone();
+    newer["version"] = serde_json::json!(99);
+    let mut foreign = doc;
+    foreign["format"] = serde_json::json!("something-else");
+    for bad in [newer, foreign] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json("/api/import", &app.user_token, bad))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn self_service_imports_are_size_limited() {
+    let source = seeded_source().await;
+    let doc = export(&source, "/api/export?attachments=false", &source.user_token).await;
+    assert!(doc.to_string().len() > 512);
+
+    let target = common::setup_with(|state| state.with_max_upload_total(512)).await;
+    let resp = target
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/import",
+            &target.user_token,
+            doc.clone(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    import(&target, "/api/admin/import", &target.admin_token, &doc).await;
+}
+
+#[tokio::test]
+async fn scoped_client_tokens_cannot_export_secrets() {
+    let app = seeded_source().await;
+    let (_, reader) = seed::create_client(&app.pool, 2, "reader").await;
+    let (_, full) = seed::create_client(&app.pool, 2, "full").await;
+    sqlx::query("UPDATE clients SET scopes = '[\"read\"]' WHERE name = 'reader'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE clients SET scopes = '[\"admin\"]' WHERE name = 'full'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/export", &reader))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body = common::body_string(resp).await;
+    assert!(!body.contains(&full), "no client token leaks");
+    assert!(!body.contains("AP_"), "no application token leaks");
+
+    let doc = export(&app, "/api/export?attachments=false", &full).await;
+    assert!(!doc["clients"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn tokens_that_cannot_be_kept_are_reported() {
+    let source = seeded_source().await;
+    let (_, phone_token) = seed::create_client(&source.pool, 2, "phone").await;
+    let doc = export(
+        &source,
+        "/api/admin/export?attachments=false",
+        &source.admin_token,
+    )
+    .await;
+
+    // testuser already has an "alerts" application with its own token, and the
+    // phone's token belongs to someone else's client.
+    let target = common::setup().await;
+    seed::create_application(&target.pool, 2, "alerts").await;
+    sqlx::query(
+        "INSERT INTO clients (user_id, name, token, created_at) \
+         VALUES (1, 'tablet', ?, datetime('now'))",
+    )
+    .bind(&phone_token)
+    .execute(&target.pool)
+    .await
+    .unwrap();
+
+    let report = import(&target, "/api/admin/import", &target.admin_token, &doc).await;
+    let skipped: Vec<&str> = report["skipped"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s.as_str().unwrap())
+        .collect();
+    assert!(
+        skipped
+            .iter()
+            .any(|s| s.contains("application 'alerts'") && s.contains("reconfigured")),
+        "{skipped:?}"
+    );
+    assert!(
+        skipped
+            .iter()
+            .any(|s| s.contains("client 'phone'") && s.contains("new one was issued")),
+        "{skipped:?}"
+    );
+    // The admin-app was imported with its token intact, so it isn't listed.
+    assert!(!skipped.iter().any(|s| s.contains("admin-app")));
+}
@@ -0,0 +1,222 @@
+//! Drives `FcmClient` against an in-process stand-in for the Google OAuth2
+//! token endpoint and the FCM v1 send API.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::{Json, Router};
+use common::seed;
+use rstify_api::fcm::{FcmClient, FcmConfig, ServiceAccount};
+use rstify_api::state::AppState;
+use rstify_core::models::MessageResponse;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+/// Send attempts per token.
+type Attempts = Arc<Mutex<HashMap<String, u32>>>;
+
+async fn token() -> Json<Value> {
+    Json(json!({"access_token": "mock-access", "expires_in": 3600}))
+}
+
+fn fcm_error(status: StatusCode, code: &str, message: &str) -> (StatusCode, Json<Value>) {
+    (
+        status,
+        Json(json!({"error": {
+            "code": status.as_u16(),
+            "message": message,
+            "status": if status == StatusCode::NOT_FOUND { "NOT_FOUND" } else { code },
+            "details": [{
+                "@type": "type.googleapis.com/google.firebase.fcm.v1.FcmError",
+                "errorCode": code,
+            }],
+        }})),
+    )
+}
+
+async fn send(
+    State(attempts): State<Attempts>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> impl IntoResponse {
+    if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer mock-access") {
+        return (StatusCode::UNAUTHORIZED, HeaderMap::new(), Json(json!({}))).into_response();
+    }
+    let token = body["message"]["token"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let attempt = {
+        let mut attempts = attempts.lock().unwrap();
+        let n = attempts.entry(token.clone()).or_default();
+        *n += 1;
+        *n
+    };
+    let mut retry = HeaderMap::new();
+    retry.insert("retry-after", "0".parse().unwrap());
+    match token.as_str() {
+        "tok-dead" => fcm_error(
+            StatusCode::NOT_FOUND,
+            "UNREGISTERED",
+            "Requested entity was not found.",
+        )
+        .into_response(),
+        "tok-invalid" => fcm_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_ARGUMENT",
+            "The registration token is not a valid FCM registration token",
+        )
+        .into_response(),
+        "tok-flaky" if attempt == 1 => (
+            retry,
+            fcm_error(StatusCode::SERVICE_UNAVAILABLE, "UNAVAILABLE", "Try again"),
+        )
+            .into_response(),
+        "tok-throttled" => (
+            retry,
+            fcm_error(StatusCode::TOO_MANY_REQUESTS, "QUOTA_EXCEEDED", "Slow down"),
+        )
+            .into_response(),
+        _ => Json(json!({"name": "projects/test/messages/1"})).into_response(),
+    }
+}
+
+async fn start_mock() -> (String, Attempts) {
+    let attempts = Attempts::default();
+    let router = Router::new()
+        .route("/token", post(token))
+        .route("/v1/projects/test/messages:send", post(send))
+        .with_state(attempts.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}", addr), attempts)
+}
+
+fn fcm_client(base: &str) -> FcmClient {
+    FcmClient::new(FcmConfig {
+        project_id: "test".to_string(),
+        service_account: ServiceAccount {
+            client_email: "push@test.iam.gserviceaccount.com".to_string(),
+            private_key: include_str!("fixtures/fcm_test_key.pem").to_string(),
+            token_uri: format!("{}/token", base),
+        },
+        api_url: base.to_string(),
+    })
+}
+
+fn message() -> MessageResponse {
+    MessageResponse {
+        id: 42,
+        appid: None,
+        topic: None,
+        title: Some("Backup".to_string()),
+        message: "finished".to_string(),
+        priority: 5,
+        tags: None,
+        click_url: None,
+        icon_url: None,
+        actions: None,
+        extras: None,
+        content_type: None,
+        source: None,
+        inbox: true,
+        attachments: None,
+        date: "2026-01-01T00:00:00Z".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn outcomes_are_logged_and_dead_tokens_pruned() {
+    let (base, attempts) = start_mock().await;
+    let mut state: Option<AppState> = None;
+    let app = common::setup_with(|s| {
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    let state = state.unwrap();
+
+    let mut clients = HashMap::new();
+    for token in [
+        "tok-ok",
+        "tok-dead",
+        "tok-invalid",
+        "tok-flaky",
+        "tok-throttled",
+    ] {
+        let (id, _) = seed::create_client(&app.pool, 2, token).await;
+        sqlx::query("UPDATE clients SET fcm_token = ? WHERE id = ?")
+            .bind(token)
+            .bind(id)
+            .execute(&app.pool)
+            .await
+            .unwrap();
+        clients.insert(token, id);
+    }
+
+    fcm_client(&base)
+        .notify_user(state.client_repo.as_ref(), 2, &message(), None)
+        .await;
+
+    let attempts = attempts.lock().unwrap().clone();
+    assert_eq!(attempts["tok-ok"], 1);
+    assert_eq!(attempts["tok-dead"], 1);
+    assert_eq!(attempts["tok-flaky"], 2);
+    assert_eq!(attempts["tok-throttled"], 3);
+
+    let mut remaining = state.client_repo.list_fcm_tokens_by_user(2).await.unwrap();
+    remaining.sort();
+    assert_eq!(remaining, ["tok-flaky", "tok-ok", "tok-throttled"]);
+
+    let mut outcomes = HashMap::new();
+    for (token, id) in &clients {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::get(
+                &format!("/client/{}/push-deliveries", id),
+                &app.user_token,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let log = common::body_json(resp).await;
+        let log = log.as_array().unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0]["message_id"], 42);
+        outcomes.insert(*token, log[0].clone());
+    }
+    assert_eq!(outcomes["tok-ok"]["outcome"], "sent");
+    assert_eq!(outcomes["tok-dead"]["outcome"], "pruned");
+    assert_eq!(outcomes["tok-dead"]["error_code"], "UNREGISTERED");
+    assert_eq!(outcomes["tok-dead"]["status_code"], 404);
+    assert_eq!(outcomes["tok-invalid"]["outcome"], "pruned");
+    assert_eq!(outcomes["tok-flaky"]["outcome"], "sent");
+    assert_eq!(outcomes["tok-flaky"]["attempts"], 2);
+    assert_eq!(outcomes["tok-throttled"]["outcome"], "failed");
+    assert_eq!(outcomes["tok-throttled"]["error_code"], "QUOTA_EXCEEDED");
+    assert_eq!(outcomes["tok-throttled"]["attempts"], 3);
+}
+
+#[tokio::test]
+async fn push_log_belongs_to_the_client_owner() {
+    let app = common::setup().await;
+    let (admin_client, _) = seed::create_client(&app.pool, 1, "admin-phone").await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/client/{}/push-deliveries", admin_client),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
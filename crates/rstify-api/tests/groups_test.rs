@@ -0,0 +1,443 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::http::StatusCode;
+use rstify_auth::tokens::create_jwt;
+use tower::ServiceExt;
+
+/// Seed a third, non-admin user and return `(id, jwt)`.
+async fn third_user(app: &common::TestApp) -> (i64, String) {
+    let id = common::seed::create_user(&app.pool, "oncall").await;
+    let token = create_jwt(id, "oncall", false, &app.jwt_secret).unwrap();
+    (id, token)
+}
+
+// ---------------------------------------------------------------------------
+// Group management
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn create_group_makes_creator_admin() {
+    let app = common::setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/groups",
+            &app.user_token,
+            serde_json::json!({"name": "on-call", "description": "Pager rotation"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let group = common::body_json(resp).await;
+    let id = group["id"].as_i64().unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/groups/{id}/members"),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    let members = common::body_json(resp).await;
+    assert_eq!(members.as_array().unwrap().len(), 1);
+    assert_eq!(members[0]["user_id"], 2);
+    assert_eq!(members[0]["username"], "testuser");
+    assert_eq!(members[0]["role"], "admin");
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/groups", &app.user_token))
+        .await
+        .unwrap();
+    let groups = common::body_json(resp).await;
+    assert_eq!(groups.as_array().unwrap().len(), 1);
+    assert_eq!(groups[0]["name"], "on-call");
+}
+
+#[tokio::test]
+async fn only_group_admins_manage_members() {
+    let app = common::setup().await;
+    let (oncall_id, oncall_token) = third_user(&app).await;
+    let group_id =
+        common::seed::create_group(&app.pool, "ops", &[(2, "admin"), (oncall_id, "member")]).await;
+
+    // A plain member cannot add people.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/groups/{group_id}/members"),
+            &oncall_token,
+            serde_json::json!({"user_id": 1}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // The group admin can, and an unknown role is rejected.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/groups/{group_id}/members"),
+            &app.user_token,
+            serde_json::json!({"user_id": 1, "role": "owner"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/groups/{group_id}/members"),
+            &app.user_token,
+            serde_json::json!({"user_id": 1}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let member = common::body_json(resp).await;
+    assert_eq!(member["role"], "member");
+
+    // The last admin cannot be demoted or removed.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/api/groups/{group_id}/members/2"),
+            &app.user_token,
+            serde_json::json!({"role": "member"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // A member may leave on their own.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(
+            &format!("/api/groups/{group_id}/members/{oncall_id}"),
+            &oncall_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+// ---------------------------------------------------------------------------
+// Group-owned topics and group grants
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn group_members_can_use_group_topics() {
+    let app = common::setup().await;
+    let (_, oncall_token) = third_user(&app).await;
+    let group_id = common::seed::create_group(&app.pool, "ops", &[(2, "member")]).await;
+    common::seed::create_private_topic(&app.pool, 1, "ops.alerts", Some(group_id)).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics/ops.alerts/publish",
+            &app.user_token,
+            serde_json::json!({"message": "disk full"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/topics", &app.user_token))
+        .await
+        .unwrap();
+    let topics = common::body_json(resp).await;
+    assert_eq!(topics.as_array().unwrap().len(), 1);
+
+    // Members cannot reconfigure the topic; only group admins can.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            "/api/topics/ops.alerts",
+            &app.user_token,
+            serde_json::json!({"description": "changed"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Outsiders are shut out.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            "/api/topics/ops.alerts/messages",
+            &oncall_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn group_permission_applies_to_every_member() {
+    let app = common::setup().await;
+    let (oncall_id, oncall_token) = third_user(&app).await;
+    let group_id =
+        common::seed::create_group(&app.pool, "ops", &[(2, "member"), (oncall_id, "member")]).await;
+    common::seed::create_private_topic(&app.pool, 1, "db.primary", None).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/permissions",
+            &app.admin_token,
+            serde_json::json!({"group_id": group_id, "topic_pattern": "db.*", "can_read": true}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let perm = common::body_json(resp).await;
+    assert_eq!(perm["group_id"], group_id);
+    assert!(perm["user_id"].is_null());
+
+    for token in [&app.user_token, &oncall_token] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::get("/api/topics/db.primary/messages", token))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json(
+                "/api/topics/db.primary/publish",
+                token,
+                serde_json::json!({"message": "read-only grant"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    // Members see the group grant in their own permission list.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/permissions", &oncall_token))
+        .await
+        .unwrap();
+    let perms = common::body_json(resp).await;
+    assert_eq!(perms.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn create_permission_requires_one_grantee() {
+    let app = common::setup().await;
+    let group_id = common::seed::create_group(&app.pool, "ops", &[]).await;
+
+    for body in [
+        serde_json::json!({"topic_pattern": "x", "can_read": true}),
+        serde_json::json!({"user_id": 2, "group_id": group_id, "topic_pattern": "x"}),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json(
+                "/api/permissions",
+                &app.admin_token,
+                body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Group-owned applications
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn group_applications_shared_and_managed_by_group_admins() {
+    let app = common::setup().await;
+    let (oncall_id, oncall_token) = third_user(&app).await;
+    let group_id =
+        common::seed::create_group(&app.pool, "ops", &[(2, "admin"), (oncall_id, "member")]).await;
+
+    // Only group admins may create applications for the group.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/application",
+            &oncall_token,
+            serde_json::json!({"name": "grafana", "group_id": group_id}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/application",
+            &app.user_token,
+            serde_json::json!({"name": "grafana", "group_id": group_id}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let created = common::body_json(resp).await;
+    let app_id = created["id"].as_i64().unwrap();
+    assert_eq!(created["group_id"], group_id);
+
+    // Members see it ...
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/application", &oncall_token))
+        .await
+        .unwrap();
+    let apps = common::body_json(resp).await;
+    assert_eq!(apps.as_array().unwrap().len(), 1);
+
+    // ... but cannot change it.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/application/{app_id}"),
+            &oncall_token,
+            serde_json::json!({"name": "renamed"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Promote the member; now the update goes through.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/api/groups/{group_id}/members/{oncall_id}"),
+            &app.user_token,
+            serde_json::json!({"role": "admin"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/application/{app_id}"),
+            &oncall_token,
+            serde_json::json!({"name": "renamed"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn null_group_id_takes_resources_out_of_the_group() {
+    let app = common::setup().await;
+    let (oncall_id, oncall_token) = third_user(&app).await;
+    let group_id =
+        common::seed::create_group(&app.pool, "ops", &[(2, "admin"), (oncall_id, "member")]).await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/application",
+            &app.user_token,
+            serde_json::json!({"name": "grafana", "group_id": group_id}),
+        ))
+        .await
+        .unwrap();
+    let app_id = common::body_json(resp).await["id"].as_i64().unwrap();
+
+    // Leaving group_id out keeps the assignment.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/application/{app_id}"),
+            &app.user_token,
+            serde_json::json!({"name": "grafana2"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(common::body_json(resp).await["group_id"], group_id);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/application/{app_id}"),
+            &app.user_token,
+            serde_json::json!({"group_id": null}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(common::body_json(resp).await["group_id"].is_null());
+
+    // Topics: a plain member cannot pull the topic out, a group admin can.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics",
+            &app.user_token,
+            serde_json::json!({"name": "ops.alerts", "group_id": group_id}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            "/api/topics/ops.alerts",
+            &oncall_token,
+            serde_json::json!({"group_id": null}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            "/api/topics/ops.alerts",
+            &app.user_token,
+            serde_json::json!({"group_id": null}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(common::body_json(resp).await["group_id"].is_null());
+}
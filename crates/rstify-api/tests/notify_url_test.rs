@@ -0,0 +1,244 @@
+//! Outgoing webhooks addressed by Apprise-style notification URLs.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::mock::start_mock;
+use common::seed;
+use rstify_api::state::AppState;
+use rstify_jobs::email::{EmailConfig, EmailOutbox, Mailer, SmtpSecurity};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn create(app: &common::TestApp, body: Value) -> (StatusCode, Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json("/api/webhooks", &app.user_token, body))
+        .await
+        .unwrap();
+    let status = resp.status();
+    (status, common::body_json(resp).await)
+}
+
+async fn publish(app: &common::TestApp, topic: &str, body: Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/topics/{}/publish", topic),
+            &app.user_token,
+            body,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn notify_urls_are_validated_at_create_time() {
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "notify-validate").await;
+
+    for url in [
+        "pushover://user/token",
+        "discord://1234",
+        "gotifys://push.example.com/{{env.MISSING}}",
+        // No SMTP in this setup.
+        "mailto://ops@example.com",
+        "ntfys://",
+    ] {
+        let (status, body) = create(
+            &app,
+            json!({
+                "name": "notify",
+                "webhookType": "custom",
+                "direction": "outgoing",
+                "targetTopicId": topic_id,
+                "notifyUrl": url,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", url, body);
+    }
+
+    let (status, _) = create(
+        &app,
+        json!({
+            "name": "incoming",
+            "webhookType": "json",
+            "direction": "incoming",
+            "targetTopicId": topic_id,
+            "notifyUrl": "ntfy://alerts",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn gotify_url_with_a_variable_token_relays_messages() {
+    let (host, received) = start_mock().await;
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "notify-gotify").await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/webhook-variables",
+            &app.user_token,
+            json!({"key": "GOTIFY_TOKEN", "value": "AppTok3n"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let (status, webhook) = create(
+        &app,
+        json!({
+            "name": "relay",
+            "webhookType": "custom",
+            "direction": "outgoing",
+            "targetTopicId": topic_id,
+            "notifyUrl": format!("gotify://{}/{{{{env.GOTIFY_TOKEN}}}}", host),
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", webhook);
+    // The stored URL keeps the placeholder, not the secret.
+    assert!(webhook["notify_url"]
+        .as_str()
+        .unwrap()
+        .ends_with("/{{env.GOTIFY_TOKEN}}"));
+
+    publish(
+        &app,
+        "notify-gotify",
+        json!({
+            "title": "Deploy",
+            "message": "v2 is live",
+            "priority": 6,
+            "click_url": "https://example.com/releases/2",
+        }),
+    )
+    .await;
+
+    for _ in 0..100 {
+        if !received.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let (method, path, headers, body) = received.lock().unwrap().remove(0);
+    assert_eq!(method, Method::POST);
+    assert_eq!(path, "/message");
+    assert_eq!(headers["x-gotify-key"], "AppTok3n");
+    assert_eq!(body["title"], "Deploy");
+    assert_eq!(body["message"], "v2 is live");
+    assert_eq!(body["priority"], 6);
+    assert_eq!(
+        body["extras"]["client::notification"]["click"]["url"],
+        "https://example.com/releases/2"
+    );
+}
+
+#[tokio::test]
+async fn ntfy_url_test_fire_publishes_json() {
+    let (host, received) = start_mock().await;
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "notify-ntfy").await;
+
+    let (status, webhook) = create(
+        &app,
+        json!({
+            "name": "ntfy",
+            "webhookType": "custom",
+            "direction": "outgoing",
+            "targetTopicId": topic_id,
+            "notifyUrl": format!("ntfy://{}/alerts?token=tk_secret", host),
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &format!("/api/webhooks/{}/test", webhook["id"]),
+            &app.user_token,
+            json!({"title": "Ping", "priority": 10}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_json(resp).await["success"], true);
+
+    let (method, path, headers, body) = received.lock().unwrap().remove(0);
+    assert_eq!(method, Method::POST);
+    assert_eq!(path, "/");
+    assert_eq!(headers["authorization"], "Bearer tk_secret");
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(body["topic"], "alerts");
+    assert_eq!(body["title"], "Ping");
+    assert_eq!(body["priority"], 5);
+}
+
+#[tokio::test]
+async fn mailto_url_queues_email() {
+    let mut state: Option<AppState> = None;
+    let app = common::setup_with(|s| {
+        // Nothing listens here; the queue is only inspected, not sent.
+        let mailer = Mailer::new(&EmailConfig {
+            host: "127.0.0.1".to_string(),
+            port: 9,
+            security: SmtpSecurity::Plain,
+            credentials: None,
+            from: "rstify@example.com".to_string(),
+        })
+        .unwrap();
+        let outbox = EmailOutbox::new(s.email_repo.clone(), mailer);
+        let s = s.with_email(outbox);
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    let state = state.unwrap();
+    let topic_id = seed::create_topic(&app.pool, 2, "notify-mail").await;
+
+    let (status, _) = create(
+        &app,
+        json!({
+            "name": "mail",
+            "webhookType": "custom",
+            "direction": "outgoing",
+            "targetTopicId": topic_id,
+            "notifyUrl": "mailto://ops@example.com?to=oncall@example.com",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    publish(
+        &app,
+        "notify-mail",
+        json!({"title": "Disk full", "message": "/var at 99%"}),
+    )
+    .await;
+
+    let mut queued = Vec::new();
+    for _ in 0..100 {
+        queued = state.email_repo.list(Some("pending"), 10, 0).await.unwrap();
+        if queued.len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let mut recipients: Vec<_> = queued.iter().map(|e| e.recipient.as_str()).collect();
+    recipients.sort();
+    assert_eq!(recipients, ["oncall@example.com", "ops@example.com"]);
+    assert!(queued.iter().all(|e| e.subject == "Disk full"));
+}
@@ -0,0 +1,224 @@
+//! Quiet-hours schedules: the API, holding pushes back, and the summary
+//! sent when a window ends.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use common::seed;
+use rstify_api::helpers::publish::hold_for_quiet_hours;
+use rstify_api::state::AppState;
+use rstify_core::models::{Client, MessageResponse};
+use rstify_jobs::quiet_hours::{send_due_summaries, SummaryPushFn};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+async fn create(app: &common::TestApp, body: Value) -> (StatusCode, Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/current/user/quiet-hours",
+            &app.user_token,
+            body,
+        ))
+        .await
+        .unwrap();
+    let status = resp.status();
+    (status, common::body_json(resp).await)
+}
+
+fn hh_mm(time: DateTime<Utc>) -> String {
+    time.format("%H:%M").to_string()
+}
+
+#[tokio::test]
+async fn schedules_are_validated_and_owned() {
+    let app = common::setup().await;
+    let (other_client, _) = seed::create_client(&app.pool, 1, "admin phone").await;
+
+    for body in [
+        json!({"start_time": "22:00", "end_time": "7:00pm"}),
+        json!({"start_time": "22:00", "end_time": "22:00"}),
+        json!({"start_time": "22:00", "end_time": "07:00", "timezone": "Mars/Olympus"}),
+        json!({"start_time": "22:00", "end_time": "07:00", "days": ["funday"]}),
+        json!({"start_time": "22:00", "end_time": "07:00", "days": []}),
+        json!({"start_time": "22:00", "end_time": "07:00", "override_priority": 11}),
+        json!({"start_time": "22:00", "end_time": "07:00", "client_id": other_client}),
+    ] {
+        let (status, resp) = create(&app, body.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", body, resp);
+    }
+
+    let (status, schedule) = create(
+        &app,
+        json!({
+            "start_time": "22:00",
+            "end_time": "07:00",
+            "days": ["Friday", "sat", "fri"],
+            "timezone": "Europe/Berlin",
+            "override_priority": 8,
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", schedule);
+    assert_eq!(schedule["days"], r#"["fri","sat"]"#);
+    assert_eq!(schedule["enabled"], true);
+    let id = schedule["id"].as_i64().unwrap();
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/current/user/quiet-hours/{}", id),
+            &app.user_token,
+            json!({"start_time": "23:00", "end_time": "06:00", "enabled": false}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let updated = common::body_json(resp).await;
+    assert_eq!(updated["start_time"], "23:00");
+    assert_eq!(updated["days"], Value::Null);
+    assert_eq!(updated["enabled"], false);
+
+    // Someone else's schedule doesn't exist as far as the admin is concerned.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(
+            &format!("/current/user/quiet-hours/{}", id),
+            &app.admin_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(
+            &format!("/current/user/quiet-hours/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/current/user/quiet-hours", &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(common::body_json(resp).await, json!([]));
+}
+
+#[tokio::test]
+async fn held_pushes_are_summarized_when_the_window_ends() {
+    let mut state: Option<AppState> = None;
+    let app = common::setup_with(|s| {
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    let state = state.unwrap();
+    let (phone_id, _) = seed::create_client(&app.pool, 2, "phone").await;
+    let (laptop_id, _) = seed::create_client(&app.pool, 2, "laptop").await;
+    let (app_id, _) = seed::create_application(&app.pool, 2, "backups").await;
+
+    // Only the phone is quiet, and priority 8 and up still gets through.
+    // Pushes are held by the real clock, so the window is built around it.
+    let now = Utc::now();
+    let (status, _) = create(
+        &app,
+        json!({
+            "client_id": phone_id,
+            "start_time": hh_mm(now - Duration::hours(1)),
+            "end_time": hh_mm(now + Duration::hours(1)),
+            "override_priority": 8,
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let phone = state
+        .client_repo
+        .find_by_id(phone_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let laptop = state
+        .client_repo
+        .find_by_id(laptop_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut held = Vec::new();
+    for (text, priority) in [
+        ("backup finished", 5),
+        ("disk failing", 9),
+        ("backup pruned", 4),
+    ] {
+        let id = seed::create_message(&app.pool, app_id, 2, text).await;
+        let mut message = state
+            .message_repo
+            .find_by_id(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .to_response(None);
+        message.priority = priority;
+        let targets =
+            hold_for_quiet_hours(&state, vec![phone.clone(), laptop.clone()], &message).await;
+        let ids: Vec<i64> = targets.iter().map(|c| c.id).collect();
+        if priority >= 8 {
+            assert_eq!(ids, [phone_id, laptop_id]);
+        } else {
+            assert_eq!(ids, [laptop_id]);
+            held.push(text);
+        }
+    }
+
+    let pushed: Arc<Mutex<Vec<(Client, MessageResponse)>>> = Arc::default();
+    let sink = pushed.clone();
+    let push: SummaryPushFn = Arc::new(move |client, summary| {
+        let sink = sink.clone();
+        Box::pin(async move { sink.lock().unwrap().push((client, summary)) })
+    });
+    let repos = state.repositories();
+
+    // Still quiet: nothing goes out yet.
+    assert_eq!(send_due_summaries(&repos, &push, now).await.unwrap(), 0);
+    assert!(pushed.lock().unwrap().is_empty());
+
+    assert_eq!(
+        send_due_summaries(&repos, &push, now + Duration::hours(2))
+            .await
+            .unwrap(),
+        1
+    );
+    let (client, summary) = pushed.lock().unwrap().remove(0);
+    assert_eq!(client.id, phone_id);
+    assert_eq!(
+        summary.title.as_deref(),
+        Some("2 notifications during quiet hours")
+    );
+    assert_eq!(summary.message, format!("- {}\n- {}", held[0], held[1]));
+    assert_eq!(summary.priority, 5);
+
+    // The backlog is cleared once summarized.
+    assert!(state
+        .client_repo
+        .list_suppressed_pushes()
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        send_due_summaries(&repos, &push, now + Duration::hours(2))
+            .await
+            .unwrap(),
+        0
+    );
+}
@@ -2,6 +2,7 @@
 // router. Guards against two bugs found in the P0 security audit — the
 // `Extension(limiter)`/middleware layer-ordering bug (middleware ran before the
 // limiter extension was inserted and silently no-op'd) and unwired ConnectInfo.
+#[allow(dead_code)]
 mod common;
 
 use axum::body::Body;
@@ -17,7 +18,7 @@ async fn rate_limiter_fires_through_router() {
         .unwrap();
     db.migrate().await.unwrap();
     let state = rstify_api::state::AppState::new(
-        db.pool().clone(),
+        db,
         jwt_secret,
         "/tmp/rstify-probe-uploads".into(),
         1024 * 1024,
@@ -0,0 +1,179 @@
+//! Relaying messages to other notification servers, and receiving them.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::http::{HeaderValue, Method, StatusCode};
+use common::mock::start_mock;
+use common::seed;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn create_relay(
+    app: &common::TestApp,
+    topic_id: i64,
+    notify_url: &str,
+) -> (StatusCode, Value) {
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/webhooks",
+            &app.user_token,
+            json!({
+                "name": "relay",
+                "webhookType": "relay",
+                "direction": "outgoing",
+                "targetTopicId": topic_id,
+                "notifyUrl": notify_url,
+            }),
+        ))
+        .await
+        .unwrap();
+    let status = resp.status();
+    (status, common::body_json(resp).await)
+}
+
+async fn publish(app: &common::TestApp, topic: &str, hops: Option<&str>, body: Value) -> Value {
+    let mut req = common::post_json(
+        &format!("/api/topics/{}/publish", topic),
+        &app.user_token,
+        body,
+    );
+    if let Some(hops) = hops {
+        req.headers_mut()
+            .insert("X-Rstify-Hops", HeaderValue::from_str(hops).unwrap());
+    }
+    let resp = app.router.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    common::body_json(resp).await
+}
+
+#[tokio::test]
+async fn relay_webhooks_need_a_relay_url() {
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "relay-validate").await;
+
+    for url in ["", "json://hooks.example.com/in", "discord://1/tok"] {
+        let (status, body) = create_relay(&app, topic_id, url).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{:?}: {}", url, body);
+    }
+    let (host, _) = start_mock().await;
+    let (status, body) = create_relay(&app, topic_id, &format!("gotify://{}/AppTok", host)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn relays_to_rstify_with_hop_count_and_fields() {
+    let (host, received) = start_mock().await;
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "relay-out").await;
+
+    let (status, webhook) = create_relay(
+        &app,
+        topic_id,
+        &format!("rstify://CL_central@{}/alerts", host),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", webhook);
+
+    // Arrived from one relay already, so it leaves with two.
+    publish(
+        &app,
+        "relay-out",
+        Some("1"),
+        json!({
+            "title": "Backup",
+            "message": "nightly backup failed",
+            "priority": 8,
+            "tags": ["backup", "error"],
+            "click_url": "https://nas.example.com",
+            "actions": [{"action": "view", "label": "Logs", "url": "https://nas.example.com/logs"}],
+        }),
+    )
+    .await;
+
+    for _ in 0..100 {
+        if !received.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let (method, path, headers, body) = received.lock().unwrap().remove(0);
+    assert_eq!(method, Method::POST);
+    assert_eq!(path, "/api/topics/alerts/publish");
+    assert_eq!(headers["authorization"], "Bearer CL_central");
+    assert_eq!(headers["x-rstify-hops"], "2");
+    assert_eq!(body["title"], "Backup");
+    assert_eq!(body["priority"], 8);
+    assert_eq!(body["tags"], json!(["backup", "error"]));
+    assert_eq!(body["click_url"], "https://nas.example.com");
+    assert_eq!(body["actions"][0]["label"], "Logs");
+}
+
+#[tokio::test]
+async fn relays_stop_at_the_hop_limit() {
+    let (host, received) = start_mock().await;
+    let app = common::setup().await;
+    let topic_id = seed::create_topic(&app.pool, 2, "relay-loop").await;
+
+    let (status, webhook) = create_relay(&app, topic_id, &format!("ntfy://{}/loop", host)).await;
+    assert_eq!(status, StatusCode::OK, "{}", webhook);
+
+    let hops = rstify_jobs::relay::MAX_HOPS.to_string();
+    let message = publish(&app, "relay-loop", Some(&hops), json!({"message": "ping"})).await;
+    assert_eq!(message["extras"]["rstify::relay"]["hops"], 3);
+
+    let mut logs = Value::Null;
+    for _ in 0..100 {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::get(
+                &format!("/api/webhooks/{}/deliveries", webhook["id"]),
+                &app.user_token,
+            ))
+            .await
+            .unwrap();
+        logs = common::body_json(resp).await;
+        if !logs.as_array().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let log = &logs[0];
+    assert_eq!(log["success"], false);
+    assert!(log["response_body_preview"]
+        .as_str()
+        .unwrap()
+        .contains("relay loop"));
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn topic_publish_downloads_remote_attachments() {
+    let (host, _) = start_mock().await;
+    let app = common::setup().await;
+    seed::create_topic(&app.pool, 2, "relay-in").await;
+
+    let message = publish(
+        &app,
+        "relay-in",
+        None,
+        json!({
+            "message": "see attached",
+            "attachments": [
+                {"url": format!("http://{}/f/7?sig=abc", host), "name": "boot.log"},
+                {"url": "http://127.0.0.1:9/gone.txt"},
+            ],
+        }),
+    )
+    .await;
+    // The unreachable one is skipped rather than failing the publish.
+    let attachments = message["attachments"].as_array().unwrap();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0]["name"], "boot.log");
+    assert_eq!(attachments[0]["size"], 8);
+    assert!(message["extras"].is_null());
+}
@@ -0,0 +1,245 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::http::StatusCode;
+use common::seed;
+use rstify_api::helpers::publish::topic_push_tokens;
+use rstify_api::state::AppState;
+use rstify_core::models::MessageResponse;
+use serde_json::json;
+use tower::ServiceExt;
+
+async fn set_fcm_token(pool: &sqlx::SqlitePool, client_id: i64, token: &str) {
+    sqlx::query("UPDATE clients SET fcm_token = ? WHERE id = ?")
+        .bind(token)
+        .bind(client_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+async fn subscribe_directly(pool: &sqlx::SqlitePool, client_id: i64, pattern: &str) {
+    sqlx::query(
+        "INSERT INTO subscriptions (client_id, topic_pattern, created_at) \
+         VALUES (?, ?, datetime('now'))",
+    )
+    .bind(client_id)
+    .bind(pattern)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+fn message(topic: &str, priority: i32, inbox: bool) -> MessageResponse {
+    MessageResponse {
+        id: 1,
+        appid: None,
+        topic: Some(topic.to_string()),
+        title: None,
+        message: "disk full".to_string(),
+        priority,
+        tags: None,
+        click_url: None,
+        icon_url: None,
+        actions: None,
+        extras: None,
+        content_type: None,
+        source: None,
+        inbox,
+        attachments: None,
+        date: "2026-01-01T00:00:00Z".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn subscribe_list_and_unsubscribe() {
+    let app = common::setup().await;
+    seed::create_topic(&app.pool, 1, "alerts").await;
+    sqlx::query("UPDATE topics SET everyone_read = FALSE WHERE name = 'alerts'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let (client_id, _) = seed::create_client(&app.pool, 2, "phone").await;
+    let uri = format!("/client/{}/subscriptions", client_id);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &uri,
+            &app.user_token,
+            json!({"topic": "alerts"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    seed::grant_topic_permission(&app.pool, 2, "alerts", true, false).await;
+    for body in [
+        json!({"topic": "alerts"}),
+        json!({"topic": "alerts", "notify_policy": "threshold", "notify_priority_min": 7}),
+        json!({"topic": "builds.*"}),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json(&uri, &app.user_token, body))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(&uri, &app.user_token))
+        .await
+        .unwrap();
+    let list = common::body_json(resp).await;
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0]["topic_pattern"], "alerts");
+    assert_eq!(list[0]["notify_policy"], "threshold");
+    assert_eq!(list[0]["notify_priority_min"], 7);
+
+    for bad in [
+        json!({"topic": "alerts..x"}),
+        json!({"topic": "alerts", "notify_policy": "digest"}),
+    ] {
+        let resp = app
+            .router
+            .clone()
+            .oneshot(common::post_json(&uri, &app.user_token, bad))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let delete_uri = format!("{}/{}", uri, list[0]["id"]);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(&delete_uri, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(&delete_uri, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn subscriptions_belong_to_the_client_owner() {
+    let app = common::setup().await;
+    let (admin_client, _) = seed::create_client(&app.pool, 1, "admin-phone").await;
+    let uri = format!("/client/{}/subscriptions", admin_client);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(&uri, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            &uri,
+            &app.user_token,
+            json!({"topic": "news"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn push_fans_out_to_subscribed_readers() {
+    let mut state: Option<AppState> = None;
+    let app = common::setup_with(|s| {
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    let state = state.unwrap();
+
+    seed::create_topic(&app.pool, 1, "alerts.disk").await;
+    sqlx::query("UPDATE topics SET everyone_read = FALSE WHERE name = 'alerts.disk'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let topic = state
+        .topic_repo
+        .find_by_name("alerts.disk")
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The owner gets pushes without subscribing.
+    let (owner, _) = seed::create_client(&app.pool, 1, "owner-phone").await;
+    set_fcm_token(&app.pool, owner, "tok-owner").await;
+
+    // testuser can read through a grant; one device follows the default
+    // policy, one only wants urgent messages and one is muted.
+    seed::grant_topic_permission(&app.pool, 2, "alerts.**", true, false).await;
+    let (reader, _) = seed::create_client(&app.pool, 2, "phone").await;
+    set_fcm_token(&app.pool, reader, "tok-reader").await;
+    subscribe_directly(&app.pool, reader, "alerts.*").await;
+    let (urgent, _) = seed::create_client(&app.pool, 2, "watch").await;
+    set_fcm_token(&app.pool, urgent, "tok-urgent").await;
+    subscribe_directly(&app.pool, urgent, "alerts.disk").await;
+    sqlx::query(
+        "UPDATE subscriptions SET notify_policy = 'threshold', notify_priority_min = 8 \
+         WHERE client_id = ?",
+    )
+    .bind(urgent)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let (muted, _) = seed::create_client(&app.pool, 2, "tablet").await;
+    set_fcm_token(&app.pool, muted, "tok-muted").await;
+    subscribe_directly(&app.pool, muted, "alerts.**").await;
+    sqlx::query("UPDATE subscriptions SET notify_policy = 'never' WHERE client_id = ?")
+        .bind(muted)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    // Subscriptions to other topics, by name or pattern, don't match.
+    let (other, _) = seed::create_client(&app.pool, 2, "laptop").await;
+    set_fcm_token(&app.pool, other, "tok-other").await;
+    subscribe_directly(&app.pool, other, "alerts.cpu").await;
+    subscribe_directly(&app.pool, other, "builds.*").await;
+
+    // carol subscribed without being able to read the topic.
+    let carol = seed::create_user(&app.pool, "carol").await;
+    let (outsider, _) = seed::create_client(&app.pool, carol, "phone").await;
+    set_fcm_token(&app.pool, outsider, "tok-outsider").await;
+    subscribe_directly(&app.pool, outsider, "alerts.disk").await;
+
+    let mut tokens = topic_push_tokens(&state, &topic, &message("alerts.disk", 5, true)).await;
+    tokens.sort();
+    assert_eq!(tokens, ["tok-owner", "tok-reader"]);
+
+    let mut tokens = topic_push_tokens(&state, &topic, &message("alerts.disk", 9, true)).await;
+    tokens.sort();
+    assert_eq!(tokens, ["tok-owner", "tok-reader", "tok-urgent"]);
+
+    // Messages kept out of the inbox only reach explicit opt-ins.
+    let tokens = topic_push_tokens(&state, &topic, &message("alerts.disk", 9, false)).await;
+    assert_eq!(tokens, ["tok-urgent"]);
+
+    // A subscription on the owner's device replaces the default.
+    subscribe_directly(&app.pool, owner, "alerts.disk").await;
+    sqlx::query("UPDATE subscriptions SET notify_policy = 'never' WHERE client_id = ?")
+        .bind(owner)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let tokens = topic_push_tokens(&state, &topic, &message("alerts.disk", 5, true)).await;
+    assert_eq!(tokens, ["tok-reader"]);
+}
@@ -0,0 +1,238 @@
+#[allow(dead_code)]
+mod common;
+
+use axum::body::Body;
+use axum::http::{self, Request, StatusCode};
+use tower::ServiceExt;
+
+const BOUNDARY: &str = "rstify-test-boundary";
+
+/// A multipart part: `(name, filename, content type, data)`.
+type Part<'a> = (&'a str, Option<&'a str>, Option<&'a str>, &'a [u8]);
+
+fn multipart_body(parts: &[Part]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, filename, content_type, data) in parts {
+        body.extend_from_slice(format!("--{}\r\n", BOUNDARY).as_bytes());
+        let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"", name);
+        if let Some(filename) = filename {
+            disposition.push_str(&format!("; filename=\"{}\"", filename));
+        }
+        body.extend_from_slice(disposition.as_bytes());
+        body.extend_from_slice(b"\r\n");
+        if let Some(content_type) = content_type {
+            body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+        }
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+fn multipart(uri: &str, auth: (&str, String), parts: &[Part]) -> Request<Body> {
+    Request::builder()
+        .method(http::Method::POST)
+        .uri(uri)
+        .header(auth.0, auth.1)
+        .header(
+            http::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .body(Body::from(multipart_body(parts)))
+        .unwrap()
+}
+
+const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0";
+
+/// Each test stages into its own directory so it can check nothing is left.
+fn staging_dir(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("rstify-test-staging-{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.to_string_lossy().into_owned()
+}
+
+fn assert_staging_empty(dir: &str) {
+    let incoming = std::path::Path::new(dir).join("incoming");
+    let left = std::fs::read_dir(incoming)
+        .map(|entries| entries.count())
+        .unwrap_or(0);
+    assert_eq!(left, 0, "staged files should be cleaned up");
+}
+
+#[tokio::test]
+async fn app_message_with_several_files() {
+    let dir = staging_dir("app-files");
+    let app = common::setup_with(|mut state| {
+        state.upload_dir = dir.clone();
+        state
+    })
+    .await;
+    let (_, app_token) = common::seed::create_application(&app.pool, 2, "uploader").await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(multipart(
+            "/message",
+            ("X-Gotify-Key", app_token),
+            &[
+                ("message", None, None, b"Nightly report"),
+                ("title", None, None, b"Reports"),
+                ("priority", None, None, b"7"),
+                ("file", Some("report.csv"), Some("text/csv"), b"a,b\n1,2\n"),
+                // The declared type is ignored in favour of the content.
+                ("file", Some("chart.png"), Some("text/plain"), PNG_HEADER),
+            ],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert_eq!(body["message"], "Nightly report");
+    assert_eq!(body["title"], "Reports");
+    assert_eq!(body["priority"], 7);
+
+    let attachments = body["attachments"].as_array().unwrap();
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0]["name"], "report.csv");
+    assert_eq!(attachments[0]["type"], "text/csv");
+    assert_eq!(attachments[0]["size"], 8);
+    assert_eq!(attachments[1]["name"], "chart.png");
+    assert_eq!(attachments[1]["type"], "image/png");
+
+    let id = attachments[0]["id"].as_i64().unwrap();
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/api/attachments/{}", id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(common::body_string(resp).await, "a,b\n1,2\n");
+
+    assert_staging_empty(&dir);
+}
+
+#[tokio::test]
+async fn topic_publish_with_payload_part() {
+    let dir = staging_dir("topic-payload");
+    let app = common::setup_with(|mut state| {
+        state.upload_dir = dir.clone();
+        state
+    })
+    .await;
+    common::seed::create_topic(&app.pool, 2, "uploads").await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(multipart(
+            "/api/topics/uploads/publish",
+            ("Authorization", format!("Bearer {}", app.user_token)),
+            &[
+                (
+                    "payload",
+                    None,
+                    Some("application/json"),
+                    br#"{"message":"Logs attached","tags":["ops"]}"#,
+                ),
+                ("file", Some("app.log"), None, b"line one\nline two\n"),
+                ("file", Some("notes.md"), None, b"# Notes\n"),
+                // An unused file input in a browser form.
+                ("file", Some(""), Some("application/octet-stream"), b""),
+            ],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert_eq!(body["message"], "Logs attached");
+    assert_eq!(body["topic"], "uploads");
+
+    let attachments = body["attachments"].as_array().unwrap();
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0]["type"], "text/plain");
+    assert_eq!(attachments[1]["type"], "text/markdown");
+
+    assert_staging_empty(&dir);
+}
+
+#[tokio::test]
+async fn json_publish_still_works() {
+    let app = common::setup().await;
+    common::seed::create_topic(&app.pool, 2, "plain").await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::post_json(
+            "/api/topics/plain/publish",
+            &app.user_token,
+            serde_json::json!({ "message": "no files" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = common::body_json(resp).await;
+    assert!(body["attachments"].is_null());
+}
+
+#[tokio::test]
+async fn per_file_and_total_limits() {
+    let dir = staging_dir("limits");
+    let app = common::setup_with(|mut state| {
+        state.upload_dir = dir.clone();
+        state.max_upload_size = 16;
+        state.with_max_upload_total(24)
+    })
+    .await;
+    common::seed::create_topic(&app.pool, 2, "limited").await;
+    let auth = || ("Authorization", format!("Bearer {}", app.user_token));
+
+    // One file over the per-file limit.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(multipart(
+            "/api/topics/limited/publish",
+            auth(),
+            &[
+                ("message", None, None, b"too big"),
+                ("file", Some("big.bin"), None, &[0u8; 17]),
+            ],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = common::body_json(resp).await;
+    assert!(body["error"].as_str().unwrap().contains("big.bin"));
+
+    // Each file fits, but together they exceed the total.
+    let resp = app
+        .router
+        .clone()
+        .oneshot(multipart(
+            "/api/topics/limited/publish",
+            auth(),
+            &[
+                ("message", None, None, b"too much"),
+                ("file", Some("a.bin"), None, &[1u8; 16]),
+                ("file", Some("b.bin"), None, &[2u8; 16]),
+            ],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let (messages,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM messages")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(messages, 0, "rejected uploads must not create messages");
+    assert_staging_empty(&dir);
+}
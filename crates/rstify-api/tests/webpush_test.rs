@@ -0,0 +1,364 @@
+//! Delivers Web Push messages to an in-process stand-in for a browser push
+//! service and decrypts them the way a user agent would.
+
+#[allow(dead_code)]
+mod common;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, State};
+use axum::http::{self, HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use common::seed;
+use hkdf::Hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, agreement, signature};
+use rstify_api::state::AppState;
+use rstify_api::webpush::{VapidKeys, WebPushClient, VAPID_KEY_SETTING};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+
+/// Requests received by the mock push service.
+type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+async fn push(State(received): State<Received>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    received.lock().unwrap().push((headers, body));
+    StatusCode::CREATED
+}
+
+async fn gone(Path(_id): Path<String>) -> StatusCode {
+    StatusCode::GONE
+}
+
+async fn start_mock() -> (String, Received) {
+    rstify_jobs::ssrf::set_allow_private_targets(true);
+    let received = Received::default();
+    let router = Router::new()
+        .route("/push/{id}", post(push))
+        .route("/gone/{id}", post(gone))
+        .with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}", addr), received)
+}
+
+async fn setup() -> (common::TestApp, AppState) {
+    let mut state: Option<AppState> = None;
+    let app = common::setup_with(|s| {
+        let s = s.with_web_push(WebPushClient::new(
+            VapidKeys::generate().unwrap(),
+            "mailto:ops@example.com".to_string(),
+        ));
+        state = Some(s.clone());
+        s
+    })
+    .await;
+    (app, state.unwrap())
+}
+
+/// A browser's side of a subscription.
+struct UserAgent {
+    private: agreement::EphemeralPrivateKey,
+    public: Vec<u8>,
+    auth: [u8; 16],
+}
+
+impl UserAgent {
+    fn new() -> Self {
+        let rng = SystemRandom::new();
+        let private =
+            agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).unwrap();
+        let public = private.compute_public_key().unwrap().as_ref().to_vec();
+        let mut auth = [0u8; 16];
+        rng.fill(&mut auth).unwrap();
+        Self {
+            private,
+            public,
+            auth,
+        }
+    }
+
+    fn subscription(&self, endpoint: &str) -> Value {
+        json!({
+            "endpoint": endpoint,
+            "keys": {
+                "p256dh": URL_SAFE_NO_PAD.encode(&self.public),
+                "auth": URL_SAFE_NO_PAD.encode(self.auth),
+            },
+        })
+    }
+
+    /// Decrypt an `aes128gcm` body (RFC 8291 section 3.4).
+    fn decrypt(self, body: &[u8]) -> Value {
+        let salt = &body[..16];
+        assert_eq!(u32::from_be_bytes(body[16..20].try_into().unwrap()), 4096);
+        let id_len = body[20] as usize;
+        let as_public = &body[21..21 + id_len];
+        let mut record = body[21 + id_len..].to_vec();
+
+        let peer = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, as_public);
+        let ecdh = agreement::agree_ephemeral(self.private, &peer, |s| s.to_vec()).unwrap();
+        let mut key_info = b"WebPush: info\0".to_vec();
+        key_info.extend_from_slice(&self.public);
+        key_info.extend_from_slice(as_public);
+        let mut ikm = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(&self.auth), &ecdh)
+            .expand(&key_info, &mut ikm)
+            .unwrap();
+        let hk = Hkdf::<Sha256>::new(Some(salt), &ikm);
+        let mut cek = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        hk.expand(b"Content-Encoding: aes128gcm\0", &mut cek)
+            .unwrap();
+        hk.expand(b"Content-Encoding: nonce\0", &mut nonce).unwrap();
+
+        let key = aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, &cek).unwrap());
+        let plain = key
+            .open_in_place(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::empty(),
+                &mut record,
+            )
+            .unwrap();
+        assert_eq!(plain.last(), Some(&2), "last record delimiter");
+        serde_json::from_slice(&plain[..plain.len() - 1]).unwrap()
+    }
+}
+
+/// Check the `vapid t=<jwt>, k=<key>` header and return the JWT claims.
+fn verify_vapid(header: &str, public_key: &str) -> Value {
+    let (token, key) = header
+        .strip_prefix("vapid t=")
+        .and_then(|rest| rest.split_once(", k="))
+        .expect("vapid authorization header");
+    assert_eq!(key, public_key);
+    let (signed, sig) = token.rsplit_once('.').unwrap();
+    signature::UnparsedPublicKey::new(
+        &signature::ECDSA_P256_SHA256_FIXED,
+        URL_SAFE_NO_PAD.decode(key).unwrap(),
+    )
+    .verify(signed.as_bytes(), &URL_SAFE_NO_PAD.decode(sig).unwrap())
+    .expect("VAPID signature");
+    let claims = signed.split('.').nth(1).unwrap();
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).unwrap()).unwrap()
+}
+
+fn post_with_app_token(uri: &str, app_token: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(http::Method::POST)
+        .uri(uri)
+        .header("X-Gotify-Key", app_token)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap()
+}
+
+async fn wait_for(received: &Received, n: usize) {
+    for _ in 0..100 {
+        if received.lock().unwrap().len() >= n {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("push service did not receive {} request(s)", n);
+}
+
+#[tokio::test]
+async fn messages_reach_registered_browsers_encrypted() {
+    let (base, received) = start_mock().await;
+    let (app, _state) = setup().await;
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/web-push/vapid-key", &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let public_key = common::body_json(resp).await["public_key"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (client_id, _) = seed::create_client(&app.pool, 2, "browser").await;
+    let uri = format!("/client/{}/web-push", client_id);
+    let ua = UserAgent::new();
+    let endpoint = format!("{}/push/abc", base);
+
+    let mut bad = ua.subscription(&endpoint);
+    bad["keys"]["p256dh"] = json!("AAAA");
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(&uri, &app.user_token, bad))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &uri,
+            &app.user_token,
+            ua.subscription(&endpoint),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let client = common::body_json(resp).await;
+    assert_eq!(client["webpush_endpoint"], endpoint.as_str());
+    assert!(client.get("webpush_p256dh").is_none());
+    assert!(client.get("webpush_auth").is_none());
+
+    let (_, app_token) = seed::create_application(&app.pool, 2, "backup").await;
+    let resp = app
+        .router
+        .clone()
+        .oneshot(post_with_app_token(
+            "/message",
+            &app_token,
+            json!({"title": "Backup", "message": "finished", "priority": 8}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    wait_for(&received, 1).await;
+    let (headers, body) = received.lock().unwrap().remove(0);
+    assert_eq!(headers["content-encoding"], "aes128gcm");
+    assert_eq!(headers["urgency"], "high");
+    assert!(headers.contains_key("ttl"));
+    let claims = verify_vapid(headers["authorization"].to_str().unwrap(), &public_key);
+    assert_eq!(claims["aud"], base.as_str());
+    assert_eq!(claims["sub"], "mailto:ops@example.com");
+
+    let payload = ua.decrypt(&body);
+    assert_eq!(payload["title"], "Backup");
+    assert_eq!(payload["message"], "finished");
+    assert_eq!(payload["priority"], 8);
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::delete(&uri, &app.user_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(common::body_json(resp)
+        .await
+        .get("webpush_endpoint")
+        .is_none());
+}
+
+#[tokio::test]
+async fn expired_subscriptions_are_pruned() {
+    let (base, _received) = start_mock().await;
+    let (app, state) = setup().await;
+
+    let (client_id, _) = seed::create_client(&app.pool, 2, "old-browser").await;
+    let ua = UserAgent::new();
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/client/{}/web-push", client_id),
+            &app.user_token,
+            ua.subscription(&format!("{}/gone/abc", base)),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let msg = rstify_core::models::MessageResponse {
+        id: 7,
+        appid: None,
+        topic: None,
+        title: None,
+        message: "hello".to_string(),
+        priority: 5,
+        tags: None,
+        click_url: None,
+        icon_url: None,
+        actions: None,
+        extras: None,
+        content_type: None,
+        source: None,
+        inbox: true,
+        attachments: None,
+        date: "2026-01-01T00:00:00Z".to_string(),
+    };
+    state
+        .webpush
+        .as_ref()
+        .unwrap()
+        .notify_user(state.client_repo.as_ref(), 2, &msg)
+        .await;
+
+    let client = state
+        .client_repo
+        .find_by_id(client_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(client.webpush_endpoint.is_none());
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get(
+            &format!("/client/{}/push-deliveries", client_id),
+            &app.user_token,
+        ))
+        .await
+        .unwrap();
+    let log = common::body_json(resp).await;
+    let log = log.as_array().unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0]["channel"], "webpush");
+    assert_eq!(log[0]["outcome"], "pruned");
+    assert_eq!(log[0]["status_code"], 410);
+    assert_eq!(log[0]["message_id"], 7);
+}
+
+#[tokio::test]
+async fn vapid_key_is_hidden_from_the_settings_api() {
+    let (app, state) = setup().await;
+    VapidKeys::load_or_create(state.settings_repo.as_ref())
+        .await
+        .unwrap();
+    assert!(state
+        .settings_repo
+        .get(VAPID_KEY_SETTING)
+        .await
+        .unwrap()
+        .is_some());
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::get("/api/settings", &app.admin_token))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let settings = common::body_json(resp).await;
+    assert!(!settings.to_string().contains(VAPID_KEY_SETTING));
+
+    let resp = app
+        .router
+        .clone()
+        .oneshot(common::put_json(
+            &format!("/api/settings/{}", VAPID_KEY_SETTING),
+            &app.admin_token,
+            json!({"value": "x"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
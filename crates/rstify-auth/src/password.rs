@@ -22,6 +22,10 @@ fn hash_password_sync(password: &str) -> Result<String, PasswordError> {
 }
 
 fn verify_password_sync(password: &str, hash: &str) -> Result<bool, PasswordError> {
+    // bcrypt hashes come from accounts imported from Gotify.
+    if is_bcrypt(hash) {
+        return bcrypt::verify(password, hash).map_err(|e| PasswordError::HashError(e.to_string()));
+    }
     let parsed_hash =
         PasswordHash::new(hash).map_err(|e| PasswordError::HashError(e.to_string()))?;
     Ok(Argon2::default()
@@ -29,6 +33,16 @@ fn verify_password_sync(password: &str, hash: &str) -> Result<bool, PasswordErro
         .is_ok())
 }
 
+fn is_bcrypt(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"].iter().any(|p| hash.starts_with(p))
+}
+
+/// Whether a hash should be replaced with an Argon2 one the next time the
+/// password is known, i.e. on a successful login.
+pub fn needs_rehash(hash: &str) -> bool {
+    is_bcrypt(hash)
+}
+
 /// Hash a password using Argon2 on a blocking thread.
 pub async fn hash_password(password: String) -> Result<String, PasswordError> {
     tokio::task::spawn_blocking(move || hash_password_sync(&password))
@@ -56,4 +70,20 @@ mod tests {
             .await
             .unwrap());
     }
+
+    #[tokio::test]
+    async fn test_verify_bcrypt() {
+        // As written by Gotify (cost 10).
+        let hash = bcrypt::hash("gotify_password", 10).unwrap();
+        assert!(needs_rehash(&hash));
+        assert!(verify_password("gotify_password".to_string(), hash.clone())
+            .await
+            .unwrap());
+        assert!(!verify_password("wrong_password".to_string(), hash)
+            .await
+            .unwrap());
+
+        let argon = hash_password("gotify_password".to_string()).await.unwrap();
+        assert!(!needs_rehash(&argon));
+    }
 }
@@ -81,10 +81,20 @@ pub enum TokenType {
     Jwt,
 }
 
+/// Gotify tokens are `A` (applications) or `C` (clients) followed by 14
+/// characters. Imported applications and clients keep them.
+fn is_gotify_token(token: &str, prefix: char) -> bool {
+    token.len() == 15
+        && token.starts_with(prefix)
+        && token[1..]
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b".-_".contains(&b))
+}
+
 pub fn classify_token(token: &str) -> TokenType {
-    if token.starts_with("AP_") {
+    if token.starts_with("AP_") || is_gotify_token(token, 'A') {
         TokenType::AppToken
-    } else if token.starts_with("CL_") {
+    } else if token.starts_with("CL_") || is_gotify_token(token, 'C') {
         TokenType::ClientToken
     } else if token.starts_with("WH_") {
         TokenType::WebhookToken
@@ -107,6 +117,21 @@ mod tests {
         assert!(client.starts_with("CL_"));
     }
 
+    #[test]
+    fn test_classify_gotify_tokens() {
+        assert!(matches!(
+            classify_token("AGi5.tYpG-w_3Fe"),
+            TokenType::AppToken
+        ));
+        assert!(matches!(
+            classify_token("CvXq0v2qN9M.7aD"),
+            TokenType::ClientToken
+        ));
+        assert!(matches!(classify_token("Cshort"), TokenType::Jwt));
+        let jwt = create_jwt(1, "admin", true, "secret").unwrap();
+        assert!(matches!(classify_token(&jwt), TokenType::Jwt));
+    }
+
     #[test]
     fn test_jwt_roundtrip() {
         let secret = "test-secret-key";
@@ -7,6 +7,8 @@ use utoipa::ToSchema;
 pub struct Application {
     pub id: i64,
     pub user_id: i64,
+    /// Owning group; its admins can manage the application.
+    pub group_id: Option<i64>,
     pub name: String,
     pub description: Option<String>,
     pub token: String,
@@ -23,6 +25,8 @@ pub struct Application {
 #[ts(export)]
 pub struct CreateApplication {
     pub name: String,
+    /// Create the application on behalf of a group the caller administers.
+    pub group_id: Option<i64>,
     pub description: Option<String>,
     pub default_priority: Option<i32>,
 }
@@ -30,6 +34,12 @@ pub struct CreateApplication {
 #[derive(Debug, Deserialize, ToSchema, TS)]
 #[ts(export)]
 pub struct UpdateApplication {
+    /// Hand the application over to a group the caller administers; `null`
+    /// takes it back out of its current group.
+    #[serde(default, deserialize_with = "crate::models::de_nullable")]
+    #[schema(value_type = Option<i64>)]
+    #[ts(type = "number | null", optional)]
+    pub group_id: Option<Option<i64>>,
     pub name: Option<String>,
     pub description: Option<String>,
     pub default_priority: Option<i32>,
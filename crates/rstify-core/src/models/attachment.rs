@@ -12,8 +12,35 @@ pub struct Attachment {
     pub size_bytes: i64,
     pub storage_type: String,
     pub storage_path: String,
+    /// Hex SHA-256 of the content; `None` for attachments stored before
+    /// content addressing.
+    pub sha256: Option<String>,
+    /// Pixel dimensions of image attachments, once the thumbnail worker has read them.
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// Key of the preview blob, stored beside the original in the same store.
+    pub thumbnail_path: Option<String>,
+    /// `pending`, `ready`, `skipped` or `failed`; `None` if not an image.
+    pub thumbnail_status: Option<String>,
     #[serde(serialize_with = "crate::models::ser_utc_z_opt")]
     pub expires_at: Option<String>,
     #[serde(serialize_with = "crate::models::ser_utc_z")]
     pub created_at: String,
 }
+
+/// Request to mint a signed attachment link.
+#[derive(Debug, Default, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct CreateAttachmentLink {
+    /// Lifetime in seconds; defaults to a day and is capped at 30 days.
+    pub expires_in: Option<i64>,
+}
+
+/// A signed link that downloads one attachment without an auth token.
+#[derive(Debug, Serialize, ToSchema, TS)]
+#[ts(export)]
+pub struct AttachmentLink {
+    /// Absolute when the server's `BASE_URL` is set, otherwise a path.
+    pub url: String,
+    pub expires_at: String,
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// `None` once the acting user has been deleted, or for anonymous actions
+    /// such as a failed login for an unknown username.
+    pub actor_id: Option<i64>,
+    /// Username at the time of the action.
+    pub actor_name: Option<String>,
+    /// Dotted verb, e.g. `user.create`, `setting.update`, `auth.login_failed`.
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    /// Changed fields before the action; absent for creations.
+    #[serde(serialize_with = "ser_json_opt")]
+    #[ts(as = "Option<serde_json::Value>")]
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<String>,
+    /// Changed fields after the action; absent for deletions.
+    #[serde(serialize_with = "ser_json_opt")]
+    #[ts(as = "Option<serde_json::Value>")]
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<String>,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub created_at: String,
+}
+
+/// Emit a stored JSON column as JSON rather than as an escaped string.
+fn ser_json_opt<S: serde::Serializer>(s: &Option<String>, ser: S) -> Result<S::Ok, S::Error> {
+    match s.as_deref().map(serde_json::from_str::<serde_json::Value>) {
+        Some(Ok(v)) => ser.serialize_some(&v),
+        Some(Err(_)) => ser.serialize_some(s),
+        None => ser.serialize_none(),
+    }
+}
+
+/// An audit record to be written.
+#[derive(Debug, Clone, Default)]
+pub struct NewAuditEntry {
+    pub actor_id: Option<i64>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// Filters for listing the audit log; all optional and combined with AND.
+#[derive(Debug, Default, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct AuditFilter {
+    pub actor_id: Option<i64>,
+    /// Exact action, or a prefix ending in `.` (e.g. `user.`).
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    /// Inclusive lower bound on `created_at` (`YYYY-MM-DD HH:MM:SS`, UTC).
+    pub since: Option<String>,
+    /// Inclusive upper bound on `created_at`.
+    pub until: Option<String>,
+    /// Only entries with an id lower than this, for paging backwards.
+    pub before_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// Reduce a before/after pair of JSON objects to the keys whose values differ,
+/// so an audit entry shows what an action changed rather than two snapshots.
+/// Non-object values are returned unchanged.
+pub fn audit_diff(
+    before: serde_json::Value,
+    after: serde_json::Value,
+) -> (serde_json::Value, serde_json::Value) {
+    match (before, after) {
+        (serde_json::Value::Object(mut b), serde_json::Value::Object(mut a)) => {
+            let keys: Vec<String> = b.keys().chain(a.keys()).cloned().collect();
+            for key in keys {
+                if b.get(&key) == a.get(&key) {
+                    b.remove(&key);
+                    a.remove(&key);
+                }
+            }
+            (serde_json::Value::Object(b), serde_json::Value::Object(a))
+        }
+        (b, a) => (b, a),
+    }
+}
+
+/// Field names whose values are secrets; audit entries record that they
+/// changed but never their contents. Webhook `headers` usually carry an
+/// `Authorization` value and chat `target_url`s embed bot tokens.
+const REDACTED_FIELDS: &[&str] = &[
+    "password",
+    "password_hash",
+    "token",
+    "secret",
+    "fcm_token",
+    "headers",
+    "target_url",
+    "notify_url",
+];
+
+/// Replace secret fields in a JSON object with a fixed marker.
+pub fn audit_redact(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = value.as_object_mut() {
+        for (key, v) in obj.iter_mut() {
+            if REDACTED_FIELDS.contains(&key.as_str()) && !v.is_null() {
+                // Keep a short hash prefix so a rotation still shows up as a
+                // diff without leaking any of the secret itself.
+                let raw = match v.as_str() {
+                    Some(s) => s.to_string(),
+                    None => v.to_string(),
+                };
+                *v = serde_json::Value::String(format!("[redacted:{}]", fingerprint(&raw)));
+            }
+        }
+    }
+    value
+}
+
+/// First 8 hex digits of the SHA-256 of `secret`.
+fn fingerprint(secret: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .take(4)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_keeps_only_changed_keys() {
+        let (b, a) = audit_diff(
+            json!({"name": "a", "is_admin": false, "email": null}),
+            json!({"name": "a", "is_admin": true, "email": null}),
+        );
+        assert_eq!(b, json!({"is_admin": false}));
+        assert_eq!(a, json!({"is_admin": true}));
+    }
+
+    #[test]
+    fn redact_hides_secret_values() {
+        let v = audit_redact(json!({"name": "hook", "token": "abcdef123456", "secret": null}));
+        assert_eq!(v["name"], "hook");
+        let token = v["token"].as_str().unwrap();
+        assert!(token.starts_with("[redacted:"));
+        assert!(!token.contains("3456"));
+        assert!(v["secret"].is_null());
+    }
+
+    #[test]
+    fn redact_fingerprint_tracks_rotation() {
+        let a = audit_redact(json!({"token": "abcdef123456"}));
+        let b = audit_redact(json!({"token": "abcdef123456"}));
+        let c = audit_redact(json!({"token": "abcdef654321"}));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn redact_hides_webhook_headers_and_urls() {
+        let v = audit_redact(json!({
+            "headers": "{\"Authorization\":\"Bearer s3cr3t\"}",
+            "target_url": "https://hooks.slack.com/services/T000/B000/XXXX",
+            "notify_url": "gotify://push.example.com/AbCdEfGh",
+        }));
+        assert!(!v["headers"].as_str().unwrap().contains("s3cr3t"));
+        assert!(!v["target_url"].as_str().unwrap().contains("XXXX"));
+        assert!(!v["notify_url"].as_str().unwrap().contains("AbCdEfGh"));
+    }
+
+    #[test]
+    fn stored_json_serializes_as_object() {
+        let entry = AuditEntry {
+            id: 1,
+            actor_id: Some(1),
+            actor_name: Some("admin".into()),
+            action: "setting.update".into(),
+            target_type: "setting".into(),
+            target_id: Some("k".into()),
+            ip: None,
+            before: Some(r#"{"value":"1"}"#.into()),
+            after: None,
+            created_at: "2026-01-01 00:00:00".into(),
+        };
+        let v = serde_json::to_value(&entry).unwrap();
+        assert_eq!(v["before"], json!({"value": "1"}));
+        assert!(v["after"].is_null());
+    }
+}
@@ -0,0 +1,13 @@
+use serde::Serialize;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// A backup archive in the backup directory.
+#[derive(Debug, Clone, Serialize, ToSchema, TS)]
+#[ts(export)]
+pub struct BackupInfo {
+    /// File name, e.g. `rstify-backup-20260101-030000.tar.gz`.
+    pub name: String,
+    pub size_bytes: i64,
+    pub created_at: String,
+}
@@ -1,4 +1,7 @@
+use chrono::{NaiveDateTime, Utc};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use ts_rs::TS;
 use utoipa::ToSchema;
 
@@ -11,10 +14,31 @@ pub struct Client {
     pub token: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fcm_token: Option<String>,
-    /// JSON array of scopes: "read", "write", "admin", "app:<id>"
+    /// JSON array of scopes: "read", "write", "admin", "app:<id>",
+    /// "topic:read:<pattern>", "topic:write:<pattern>"
     pub scopes: String,
     #[serde(serialize_with = "crate::models::ser_utc_z")]
     pub created_at: String,
+    /// When set, the token stops authenticating at this time and is later purged.
+    #[serde(serialize_with = "crate::models::ser_utc_z_opt")]
+    pub expires_at: Option<String>,
+    /// JSON array of CIDRs the token may be used from; `None` = any address.
+    pub allowed_ips: Option<String>,
+    #[serde(serialize_with = "crate::models::ser_utc_z_opt")]
+    pub last_used_at: Option<String>,
+    pub last_used_ip: Option<String>,
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub expiry_notified_at: Option<String>,
+    /// Web Push endpoint of the browser subscription, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webpush_endpoint: Option<String>,
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub webpush_p256dh: Option<String>,
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub webpush_auth: Option<String>,
 }
 
 impl Client {
@@ -48,6 +72,79 @@ impl Client {
         // Check for specific app scope
         app_scopes.iter().any(|s| *s == &format!("app:{}", app_id))
     }
+
+    /// Topic patterns this client is limited to for `access` ("read" or
+    /// "write"). `None` means the token carries no `topic:` scopes and keeps the
+    /// user's full topic rights (backward compat), like `app:` scopes. Once any
+    /// `topic:` scope is present, directions without a pattern get no access,
+    /// even with the `admin` scope.
+    pub fn topic_patterns(&self, access: &str) -> Option<Vec<String>> {
+        let scopes = self.scope_list();
+        if !scopes.iter().any(|s| s.starts_with("topic:")) {
+            return None;
+        }
+        let prefix = format!("topic:{}:", access);
+        Some(
+            scopes
+                .iter()
+                .filter_map(|s| s.strip_prefix(&prefix).map(str::to_string))
+                .collect(),
+        )
+    }
+
+    /// True once `expires_at` has passed. Tokens without an expiry never expire.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok())
+            .map(|exp| exp <= Utc::now().naive_utc())
+            .unwrap_or(false)
+    }
+
+    /// Parse the allowed-network JSON into a list of CIDRs. Unparseable entries
+    /// are dropped (they are rejected at create/update time).
+    pub fn allowed_networks(&self) -> Vec<IpNet> {
+        let raw: Vec<String> = self
+            .allowed_ips
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        raw.iter().filter_map(|s| parse_ip_net(s)).collect()
+    }
+
+    /// Check whether a request from `ip` may use this token. Without an
+    /// allow-list every address is accepted; with one, an unknown peer
+    /// address (`None`) is refused so the restriction fails closed.
+    pub fn allows_ip(&self, ip: Option<IpAddr>) -> bool {
+        if self.allowed_ips.is_none() {
+            return true;
+        }
+        let nets = self.allowed_networks();
+        match ip {
+            Some(ip) => nets.iter().any(|n| n.contains(&normalize_ip(ip))),
+            None => false,
+        }
+    }
+}
+
+/// Parse a CIDR (`10.0.0.0/8`, `fd00::/8`) or a bare address, which is treated
+/// as a single-host network.
+pub fn parse_ip_net(s: &str) -> Option<IpNet> {
+    let s = s.trim();
+    s.parse::<IpNet>()
+        .ok()
+        .or_else(|| s.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// Unwrap IPv4-mapped IPv6 peers (`::ffff:10.0.0.1`) so they match IPv4 CIDRs.
+fn normalize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        v4 => v4,
+    }
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
@@ -57,6 +154,12 @@ pub struct CreateClient {
     /// Scopes for this client token. Default: ["read", "write"]
     #[serde(default)]
     pub scopes: Option<Vec<String>>,
+    /// Expiry as RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a duration like `30d`.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// CIDRs or addresses the token may be used from. Omit for no restriction.
+    #[serde(default)]
+    pub allowed_ips: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
@@ -66,6 +169,12 @@ pub struct UpdateClient {
     /// Update scopes for this client token
     #[serde(default)]
     pub scopes: Option<Vec<String>>,
+    /// New expiry; an empty string removes it.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// New allow-list; an empty list removes the restriction.
+    #[serde(default)]
+    pub allowed_ips: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
@@ -74,6 +183,80 @@ pub struct RegisterFcmToken {
     pub fcm_token: String,
 }
 
+/// A browser `PushSubscription`, in the shape of its `toJSON()`.
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct RegisterWebPush {
+    pub endpoint: String,
+    pub keys: WebPushKeys,
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct WebPushKeys {
+    /// The browser's P-256 public key, base64url.
+    pub p256dh: String,
+    /// The 16-byte authentication secret, base64url.
+    pub auth: String,
+}
+
+/// The server's VAPID public key, for `pushManager.subscribe()`.
+#[derive(Debug, Serialize, ToSchema, TS)]
+#[ts(export)]
+pub struct VapidPublicKey {
+    /// Uncompressed P-256 point, base64url without padding.
+    pub public_key: String,
+}
+
+/// A device's subscription to a topic or topic pattern, for push.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct Subscription {
+    pub id: i64,
+    pub client_id: i64,
+    pub topic_pattern: String,
+    /// `always`, `never` or `threshold`; `None` follows the topic's policy.
+    pub notify_policy: Option<String>,
+    /// Minimum priority for `threshold`.
+    pub notify_priority_min: Option<i32>,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct CreateSubscription {
+    /// Topic name or pattern (`alerts.*`, `alerts.**`).
+    pub topic: String,
+    pub notify_policy: Option<String>,
+    pub notify_priority_min: Option<i32>,
+}
+
+/// One push notification attempt to a device.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct PushDelivery {
+    pub id: i64,
+    pub client_id: i64,
+    /// `fcm` or `webpush`.
+    pub channel: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<i64>,
+    /// `sent`, `failed`, or `pruned` when the token was rejected for good
+    /// and removed from the client.
+    pub outcome: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<i32>,
+    /// FCM error code, e.g. `UNREGISTERED` or `QUOTA_EXCEEDED`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub attempts: i32,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub attempted_at: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,6 +270,14 @@ mod tests {
             fcm_token: None,
             scopes: scopes.into(),
             created_at: "".into(),
+            expires_at: None,
+            allowed_ips: None,
+            last_used_at: None,
+            last_used_ip: None,
+            expiry_notified_at: None,
+            webpush_endpoint: None,
+            webpush_p256dh: None,
+            webpush_auth: None,
         }
     }
 
@@ -127,4 +318,46 @@ mod tests {
         assert!(!c.has_scope("read"));
         assert!(c.has_scope("write"));
     }
+
+    #[test]
+    fn test_topic_patterns() {
+        let c = make_client(r#"["read","write"]"#);
+        assert!(c.topic_patterns("write").is_none());
+
+        let c = make_client(r#"["write","topic:write:home.sensors.*"]"#);
+        assert_eq!(
+            c.topic_patterns("write"),
+            Some(vec!["home.sensors.*".to_string()])
+        );
+        assert_eq!(c.topic_patterns("read"), Some(vec![]));
+
+        let c = make_client(r#"["admin","topic:read:x"]"#);
+        assert_eq!(c.topic_patterns("read"), Some(vec!["x".to_string()]));
+        assert_eq!(c.topic_patterns("write"), Some(vec![]));
+        assert!(make_client(r#"["admin"]"#).topic_patterns("read").is_none());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut c = make_client(r#"["read"]"#);
+        assert!(!c.is_expired());
+        c.expires_at = Some("2000-01-01 00:00:00".into());
+        assert!(c.is_expired());
+        c.expires_at = Some("2999-01-01 00:00:00".into());
+        assert!(!c.is_expired());
+    }
+
+    #[test]
+    fn test_allowed_ips() {
+        let mut c = make_client(r#"["read"]"#);
+        assert!(c.allows_ip(None), "no allow-list accepts unknown peers");
+
+        c.allowed_ips = Some(r#"["10.0.0.0/8","192.168.1.7","fd00::/8"]"#.into());
+        assert!(c.allows_ip(Some("10.1.2.3".parse().unwrap())));
+        assert!(c.allows_ip(Some("192.168.1.7".parse().unwrap())));
+        assert!(!c.allows_ip(Some("192.168.1.8".parse().unwrap())));
+        assert!(c.allows_ip(Some("fd00::1".parse().unwrap())));
+        assert!(c.allows_ip(Some("::ffff:10.0.0.1".parse().unwrap())));
+        assert!(!c.allows_ip(None), "allow-list fails closed without a peer");
+    }
 }
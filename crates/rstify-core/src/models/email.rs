@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// One queued or sent email.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct EmailDelivery {
+    pub id: i64,
+    pub recipient: String,
+    pub subject: String,
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub text_body: String,
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub html_body: Option<String>,
+    /// `pending`, `sent` or `failed`.
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub next_attempt_at: String,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub created_at: String,
+    #[serde(serialize_with = "crate::models::ser_utc_z_opt")]
+    pub sent_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct SendTestEmail {
+    /// Defaults to the caller's own address.
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema, TS)]
+#[ts(export)]
+pub struct EmailTestResult {
+    pub success: bool,
+    pub recipient: String,
+    pub error: Option<String>,
+}
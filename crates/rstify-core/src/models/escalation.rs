@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Extras key holding `{"steps": [...]}`, one entry per escalation step taken.
+pub const ESCALATION_EXTRA: &str = "rstify::escalation";
+
+/// Extras key holding `{"at", "user_id", "username"}` once acknowledged.
+pub const ACK_EXTRA: &str = "rstify::ack";
+
+/// Step actions an [`EscalationStep`] may take.
+pub const ESCALATION_ACTIONS: &[&str] = &["push", "email"];
+
+/// What a topic does with high-priority messages nobody acknowledges:
+/// each step fires in turn, `after_minutes` after the one before, until the
+/// message is acknowledged or the steps run out. Stored as JSON on the topic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct EscalationPolicy {
+    /// Messages below this priority never escalate. Default 8.
+    #[serde(default = "default_min_priority")]
+    pub min_priority: i32,
+    /// An empty list removes the policy.
+    pub steps: Vec<EscalationStep>,
+}
+
+fn default_min_priority() -> i32 {
+    8
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct EscalationStep {
+    /// Minutes without acknowledgement before this step runs, counted from
+    /// the previous step (or from publishing, for the first).
+    pub after_minutes: i32,
+    /// `push` or `email`.
+    pub action: String,
+    /// Send to this user instead of the message's usual recipients.
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    /// Send to every member of this group instead.
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
+
+/// A message's place in its topic's escalation policy.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MessageEscalation {
+    pub message_id: i64,
+    pub topic_id: i64,
+    /// The topic's policy as it was when the message was published.
+    pub policy: String,
+    /// Index of the next step to run.
+    pub next_step: i32,
+    /// When the next step is due; `None` once acknowledged or finished.
+    pub next_at: Option<String>,
+    pub created_at: String,
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Value of [`ExportDocument::format`].
+pub const EXPORT_FORMAT: &str = "rstify-export";
+/// Current [`ExportDocument::version`]. Imports refuse newer versions.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A portable copy of an instance's data, or of one user's. Ids are those of
+/// the exporting instance and only link records within the document; imports
+/// assign new ones.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportDocument {
+    /// Always `rstify-export`.
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    /// Username, when the document holds a single user's data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub users: Vec<ExportUser>,
+    #[serde(default)]
+    pub applications: Vec<ExportApplication>,
+    #[serde(default)]
+    pub clients: Vec<ExportClient>,
+    #[serde(default)]
+    pub topics: Vec<ExportTopic>,
+    /// Per-user topic grants; only in full exports.
+    #[serde(default)]
+    pub permissions: Vec<ExportPermission>,
+    #[serde(default)]
+    pub webhooks: Vec<ExportWebhook>,
+    #[serde(default)]
+    pub webhook_variables: Vec<ExportWebhookVariable>,
+    /// Oldest first.
+    #[serde(default)]
+    pub messages: Vec<ExportMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportUser {
+    pub id: i64,
+    pub username: String,
+    pub email: Option<String>,
+    pub is_admin: bool,
+    /// Only in full exports, so migrated users keep their passwords.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+}
+
+/// A file carried inline, base64-encoded.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportFile {
+    pub filename: String,
+    pub content_type: Option<String>,
+    /// Hex SHA-256 of the content, checked on import.
+    pub sha256: String,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportApplication {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub token: String,
+    pub default_priority: i32,
+    pub retention_days: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<ExportFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportClient {
+    pub user_id: i64,
+    pub name: String,
+    pub token: String,
+    /// JSON array of scopes; `app:<id>` scopes refer to exported application ids.
+    pub scopes: String,
+    pub expires_at: Option<String>,
+    pub allowed_ips: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportTopic {
+    pub id: i64,
+    pub name: String,
+    pub owner_id: Option<i64>,
+    pub description: Option<String>,
+    pub everyone_read: bool,
+    pub everyone_write: bool,
+    pub notify_policy: String,
+    pub notify_priority_min: Option<i32>,
+    pub notify_condition: Option<String>,
+    pub notify_digest_interval: Option<i32>,
+    pub store_policy: String,
+    pub store_interval: Option<i32>,
+    pub inbox_override: Option<String>,
+    pub inbox_priority_min: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportPermission {
+    pub user_id: i64,
+    pub topic_pattern: String,
+    pub can_read: bool,
+    pub can_write: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportWebhook {
+    pub user_id: i64,
+    pub name: String,
+    pub token: String,
+    pub webhook_type: String,
+    pub target_topic_id: Option<i64>,
+    pub target_application_id: Option<i64>,
+    pub template: String,
+    pub enabled: bool,
+    pub direction: String,
+    pub target_url: Option<String>,
+    pub http_method: String,
+    pub headers: Option<String>,
+    pub body_template: Option<String>,
+    pub max_retries: i32,
+    pub retry_delay_secs: i32,
+    pub timeout_secs: i32,
+    pub follow_redirects: bool,
+    pub group_name: Option<String>,
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub notify_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportWebhookVariable {
+    pub user_id: i64,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ExportMessage {
+    pub application_id: Option<i64>,
+    pub topic_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub title: Option<String>,
+    pub message: String,
+    pub priority: i32,
+    pub tags: Option<String>,
+    pub click_url: Option<String>,
+    pub icon_url: Option<String>,
+    pub actions: Option<String>,
+    pub extras: Option<String>,
+    pub content_type: Option<String>,
+    /// Only for scheduled messages not yet delivered.
+    pub scheduled_for: Option<String>,
+    pub source: Option<String>,
+    pub inbox: bool,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<ExportFile>,
+}
+
+/// Records created and records that already existed (matched by name).
+#[derive(Debug, Clone, Default, Serialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ImportCount {
+    pub created: i64,
+    pub matched: i64,
+}
+
+/// What an import did.
+#[derive(Debug, Clone, Default, Serialize, ToSchema, TS)]
+#[ts(export)]
+pub struct ImportReport {
+    pub users: ImportCount,
+    pub applications: ImportCount,
+    pub clients: ImportCount,
+    pub topics: ImportCount,
+    pub permissions: ImportCount,
+    pub webhooks: ImportCount,
+    pub webhook_variables: ImportCount,
+    pub messages: i64,
+    pub attachments: i64,
+    /// Records left out, with the reason.
+    pub skipped: Vec<String>,
+}
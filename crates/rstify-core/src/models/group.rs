@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Group role that may manage members and group-owned resources.
+pub const GROUP_ROLE_ADMIN: &str = "admin";
+/// Group role with read/write access to group-owned topics.
+pub const GROUP_ROLE_MEMBER: &str = "member";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct Group {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct GroupMember {
+    pub group_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub role: String,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub created_at: String,
+}
+
+impl GroupMember {
+    pub fn is_admin(&self) -> bool {
+        self.role == GROUP_ROLE_ADMIN
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct CreateGroup {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct UpdateGroup {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct AddGroupMember {
+    pub user_id: i64,
+    /// `admin` or `member` (default).
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct UpdateGroupMember {
+    pub role: String,
+}
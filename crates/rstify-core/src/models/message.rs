@@ -49,6 +49,17 @@ pub struct CreateTopicMessage {
     pub icon_url: Option<String>,
     pub actions: Option<Vec<MessageAction>>,
     pub scheduled_for: Option<String>,
+    /// Files the server downloads and attaches, as relayed by another rstify.
+    #[serde(default)]
+    pub attachments: Option<Vec<RemoteAttachment>>,
+}
+
+/// An attachment given by URL rather than uploaded
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct RemoteAttachment {
+    pub url: String,
+    pub name: Option<String>,
 }
 
 /// Update an existing message
@@ -58,6 +69,7 @@ pub struct UpdateMessage {
     pub title: Option<String>,
     pub message: Option<String>,
     pub priority: Option<i32>,
+    /// Replaces the extras, except the `rstify::` keys rstify maintains.
     pub extras: Option<serde_json::Value>,
 }
 
@@ -71,6 +83,12 @@ pub struct AttachmentInfo {
     pub content_type: Option<String>,
     pub size: i64,
     pub url: String,
+    /// Hex SHA-256 of the content, usable as a cache key across messages.
+    pub sha256: Option<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// Downscaled preview for images, once it has been generated.
+    pub thumbnail_url: Option<String>,
 }
 
 impl AttachmentInfo {
@@ -81,6 +99,11 @@ impl AttachmentInfo {
             content_type: a.content_type.clone(),
             size: a.size_bytes,
             url: format!("/api/attachments/{}", a.id),
+            sha256: a.sha256.clone(),
+            width: a.width,
+            height: a.height,
+            thumbnail_url: (a.thumbnail_status.as_deref() == Some("ready"))
+                .then(|| format!("/api/attachments/{}/thumbnail", a.id)),
         }
     }
 }
@@ -108,6 +131,20 @@ pub struct MessageResponse {
     pub date: String,
 }
 
+impl MessageResponse {
+    /// Whether the body is markdown, set by ntfy's `Markdown` header or
+    /// Gotify's `client::display` extra.
+    pub fn is_markdown(&self) -> bool {
+        self.content_type.as_deref() == Some("text/markdown")
+            || self
+                .extras
+                .as_ref()
+                .and_then(|e| e.pointer("/client::display/contentType"))
+                .and_then(|v| v.as_str())
+                == Some("text/markdown")
+    }
+}
+
 impl Message {
     pub fn to_response(&self, topic_name: Option<String>) -> MessageResponse {
         MessageResponse {
@@ -155,8 +192,40 @@ impl Message {
         }
         resp
     }
+
+    /// Replacement extras for an edit: the `rstify::` keys rstify maintains
+    /// itself (acknowledgement, escalation history, relay hops) are kept from
+    /// the stored extras, and any the client sent are ignored. `None` when
+    /// there are keys to keep but `extras` isn't an object that could hold
+    /// them.
+    pub fn merged_extras(&self, extras: serde_json::Value) -> Option<serde_json::Value> {
+        let kept: serde_json::Map<String, serde_json::Value> = self
+            .extras
+            .as_deref()
+            .and_then(|e| serde_json::from_str::<serde_json::Value>(e).ok())
+            .and_then(|e| match e {
+                serde_json::Value::Object(map) => Some(map),
+                _ => None,
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(SERVER_EXTRAS_PREFIX))
+            .collect();
+        match extras {
+            serde_json::Value::Object(mut map) => {
+                map.retain(|key, _| !key.starts_with(SERVER_EXTRAS_PREFIX));
+                map.extend(kept);
+                Some(serde_json::Value::Object(map))
+            }
+            other if kept.is_empty() => Some(other),
+            _ => None,
+        }
+    }
 }
 
+/// Prefix of the extras keys rstify maintains itself.
+pub const SERVER_EXTRAS_PREFIX: &str = "rstify::";
+
 #[derive(Debug, Serialize, ToSchema, TS)]
 #[ts(export)]
 pub struct PagedMessages {
@@ -1,8 +1,15 @@
 pub mod action;
 pub mod application;
 pub mod attachment;
+pub mod audit;
+pub mod backup;
 pub mod client;
+pub mod email;
+pub mod escalation;
+pub mod export;
+pub mod group;
 pub mod message;
+pub mod quiet_hours;
 pub mod topic;
 pub mod user;
 pub mod webhook;
@@ -12,8 +19,15 @@ pub mod webhook_variable;
 pub use action::*;
 pub use application::*;
 pub use attachment::*;
+pub use audit::*;
+pub use backup::*;
 pub use client::*;
+pub use email::*;
+pub use escalation::*;
+pub use export::*;
+pub use group::*;
 pub use message::*;
+pub use quiet_hours::*;
 pub use topic::*;
 pub use user::*;
 pub use webhook::*;
@@ -45,6 +59,17 @@ pub fn ser_utc_z_opt<S: serde::Serializer>(s: &Option<String>, ser: S) -> Result
     }
 }
 
+/// `#[serde(default, deserialize_with = "crate::models::de_nullable")]` for
+/// update fields where an explicit `null` clears the value: a missing field
+/// is `None` (keep current), `null` is `Some(None)`.
+pub fn de_nullable<'de, D, T>(de: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    <Option<T> as serde::Deserialize>::deserialize(de).map(Some)
+}
+
 #[cfg(test)]
 mod utc_z_tests {
     use super::*;
@@ -69,6 +94,7 @@ mod utc_z_tests {
         let app = Application {
             id: 1,
             user_id: 1,
+            group_id: None,
             name: "n".into(),
             description: None,
             token: "t".into(),
@@ -0,0 +1,80 @@
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// A do-not-disturb schedule. Push (and, for user-wide schedules, email) is
+/// held back while it is active; held pushes are summarized when it ends.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct QuietHours {
+    pub id: i64,
+    pub user_id: i64,
+    /// The one device this applies to; `None` = all of the user's devices
+    /// and their email.
+    pub client_id: Option<i64>,
+    pub enabled: bool,
+    /// Start of the window, `HH:MM` in `timezone`.
+    pub start_time: String,
+    /// End of the window, `HH:MM` in `timezone`. Before `start_time` means
+    /// the window runs past midnight.
+    pub end_time: String,
+    /// JSON array of the weekdays the window starts on (`mon`..`sun`);
+    /// `None` = every day.
+    pub days: Option<String>,
+    /// IANA timezone name, e.g. `Europe/Berlin`.
+    pub timezone: String,
+    /// Messages at or above this priority break through; `None` = none do.
+    pub override_priority: Option<i32>,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub created_at: String,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub updated_at: String,
+}
+
+impl QuietHours {
+    /// The weekdays the window starts on, or `None` for every day.
+    /// Unrecognized names are dropped (they are rejected when saving).
+    pub fn weekdays(&self) -> Option<Vec<Weekday>> {
+        self.days.as_deref().map(|d| {
+            serde_json::from_str::<Vec<String>>(d)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|day| day.parse().ok())
+                .collect()
+        })
+    }
+}
+
+/// Body of `POST /current/user/quiet-hours`, and of `PUT` on one schedule,
+/// which replaces it.
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[ts(export)]
+pub struct CreateQuietHours {
+    /// Limit the schedule to one of your devices; omit for all of them.
+    #[serde(default)]
+    pub client_id: Option<i64>,
+    /// Default true.
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    pub start_time: String,
+    pub end_time: String,
+    /// Weekdays the window starts on, e.g. `["mon", "tue"]`; omit for every day.
+    #[serde(default)]
+    pub days: Option<Vec<String>>,
+    /// Default `UTC`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Let messages at or above this priority through.
+    #[serde(default)]
+    pub override_priority: Option<i32>,
+}
+
+/// A push held back by quiet hours, waiting for the end-of-window summary.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SuppressedPush {
+    pub id: i64,
+    pub client_id: i64,
+    pub message_id: i64,
+    pub created_at: String,
+}
@@ -2,12 +2,16 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use utoipa::ToSchema;
 
+use super::escalation::EscalationPolicy;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
 #[ts(export)]
 pub struct Topic {
     pub id: i64,
     pub name: String,
     pub owner_id: Option<i64>,
+    /// Owning group; its members share access with the owner.
+    pub group_id: Option<i64>,
     pub description: Option<String>,
     pub everyone_read: bool,
     pub everyone_write: bool,
@@ -27,12 +31,26 @@ pub struct Topic {
     pub inbox_override: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inbox_priority_min: Option<i32>,
+    /// JSON [`EscalationPolicy`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalation_policy: Option<String>,
+}
+
+impl Topic {
+    /// The topic's escalation policy, if it has a usable one.
+    pub fn escalation(&self) -> Option<EscalationPolicy> {
+        self.escalation_policy
+            .as_deref()
+            .and_then(|p| serde_json::from_str(p).ok())
+    }
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
 #[ts(export)]
 pub struct CreateTopic {
     pub name: String,
+    /// Create the topic on behalf of a group the caller administers.
+    pub group_id: Option<i64>,
     pub description: Option<String>,
     pub everyone_read: Option<bool>,
     pub everyone_write: Option<bool>,
@@ -41,6 +59,12 @@ pub struct CreateTopic {
 #[derive(Debug, Deserialize, ToSchema, TS)]
 #[ts(export)]
 pub struct UpdateTopic {
+    /// Hand the topic over to a group the caller administers; `null`
+    /// takes it back out of its current group.
+    #[serde(default, deserialize_with = "crate::models::de_nullable")]
+    #[schema(value_type = Option<i64>)]
+    #[ts(type = "number | null", optional)]
+    pub group_id: Option<Option<i64>>,
     pub description: Option<String>,
     pub everyone_read: Option<bool>,
     pub everyone_write: Option<bool>,
@@ -52,13 +76,17 @@ pub struct UpdateTopic {
     pub store_interval: Option<i32>,
     pub inbox_override: Option<String>,
     pub inbox_priority_min: Option<i32>,
+    /// Replace the escalation policy; one with no steps removes it.
+    pub escalation_policy: Option<EscalationPolicy>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
 #[ts(export)]
 pub struct TopicPermission {
     pub id: i64,
-    pub user_id: i64,
+    /// Exactly one of `user_id` and `group_id` is set.
+    pub user_id: Option<i64>,
+    pub group_id: Option<i64>,
     pub topic_pattern: String,
     pub can_read: bool,
     pub can_write: bool,
@@ -67,7 +95,9 @@ pub struct TopicPermission {
 #[derive(Debug, Deserialize, ToSchema, TS)]
 #[ts(export)]
 pub struct CreateTopicPermission {
-    pub user_id: i64,
+    /// Grantee: set exactly one of `user_id` and `group_id`.
+    pub user_id: Option<i64>,
+    pub group_id: Option<i64>,
     pub topic_pattern: String,
     pub can_read: Option<bool>,
     pub can_write: Option<bool>,
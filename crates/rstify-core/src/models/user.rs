@@ -12,6 +12,17 @@ pub struct User {
     pub is_admin: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Consecutive failed logins since the last successful one.
+    pub failed_logins: i64,
+    /// Logins are refused until this time (UTC, SQLite datetime format).
+    pub locked_until: Option<String>,
+}
+
+impl User {
+    /// Whether logins are currently refused; `now` is in SQLite datetime format.
+    pub fn is_locked(&self, now: &str) -> bool {
+        self.locked_until.as_deref().is_some_and(|t| t > now)
+    }
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
@@ -47,6 +58,10 @@ pub struct UserResponse {
     pub is_admin: bool,
     pub created_at: String,
     pub updated_at: String,
+    pub failed_logins: i64,
+    /// Set while the account is (or was last) locked after repeated failed logins.
+    #[serde(serialize_with = "crate::models::ser_utc_z_opt")]
+    pub locked_until: Option<String>,
 }
 
 impl From<User> for UserResponse {
@@ -58,6 +73,66 @@ impl From<User> for UserResponse {
             is_admin: u.is_admin,
             created_at: crate::models::to_utc_z(&u.created_at),
             updated_at: crate::models::to_utc_z(&u.updated_at),
+            failed_logins: u.failed_logins,
+            locked_until: u.locked_until,
+        }
+    }
+}
+
+/// A user's opt-in to email for messages in their own inbox.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, TS)]
+#[ts(export)]
+pub struct EmailPreferences {
+    pub user_id: i64,
+    pub enabled: bool,
+    /// Messages below this priority are not emailed.
+    pub min_priority: i32,
+    /// JSON array of topic names or patterns; `None` = the user's own topics.
+    pub topics: Option<String>,
+    /// JSON array of application ids; `None` = all of the user's applications.
+    pub application_ids: Option<String>,
+    #[serde(serialize_with = "crate::models::ser_utc_z")]
+    pub updated_at: String,
+}
+
+impl EmailPreferences {
+    /// What a user gets before saving any preferences: no email.
+    pub fn disabled(user_id: i64) -> Self {
+        Self {
+            user_id,
+            enabled: false,
+            min_priority: 5,
+            topics: None,
+            application_ids: None,
+            updated_at: String::new(),
         }
     }
+
+    pub fn topic_patterns(&self) -> Option<Vec<String>> {
+        self.topics
+            .as_deref()
+            .map(|t| serde_json::from_str(t).unwrap_or_default())
+    }
+
+    pub fn application_id_list(&self) -> Option<Vec<i64>> {
+        self.application_ids
+            .as_deref()
+            .map(|a| serde_json::from_str(a).unwrap_or_default())
+    }
+}
+
+/// Replaces a user's email preferences; omitted fields take their defaults.
+/// Unknown fields are rejected, so the quiet window older clients send
+/// (now a quiet hours schedule) isn't silently lost.
+#[derive(Debug, Deserialize, ToSchema, TS)]
+#[serde(deny_unknown_fields)]
+#[ts(export)]
+pub struct UpdateEmailPreferences {
+    pub enabled: bool,
+    /// Default 5.
+    pub min_priority: Option<i32>,
+    /// Topic names or patterns to email about; omit for the user's own topics.
+    pub topics: Option<Vec<String>>,
+    /// Applications to email about; omit for all of the user's applications.
+    pub application_ids: Option<Vec<i64>>,
 }
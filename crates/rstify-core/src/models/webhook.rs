@@ -28,6 +28,9 @@ pub struct WebhookConfig {
     pub follow_redirects: bool,
     pub group_name: Option<String>,
     pub secret: Option<String>,
+    /// Apprise-style destination such as `discord://id/token`; when set it
+    /// replaces `target_url` and picks the transport.
+    pub notify_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
@@ -64,6 +67,8 @@ pub struct CreateWebhookConfig {
     pub group_name: Option<String>,
     #[serde(alias = "secret")]
     pub secret: Option<String>,
+    #[serde(alias = "notify_url")]
+    pub notify_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize, ToSchema, TS)]
@@ -93,4 +98,6 @@ pub struct UpdateWebhookConfig {
     pub group_name: Option<String>,
     #[serde(alias = "secret")]
     pub secret: Option<String>,
+    #[serde(alias = "notify_url")]
+    pub notify_url: Option<String>,
 }
@@ -18,3 +18,12 @@ pub struct WebhookDeliveryLog {
     pub attempted_at: String,
     pub success: bool,
 }
+
+/// Delivery health of one webhook over its most recent attempts.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WebhookHealth {
+    pub webhook_config_id: i64,
+    pub last_delivery_at: Option<String>,
+    pub last_delivery_success: Option<bool>,
+    pub recent_success_rate: Option<f64>,
+}
@@ -1,4 +1,5 @@
-use crate::models::{MessageResponse, Topic};
+use crate::models::{EmailPreferences, MessageResponse, QuietHours, Subscription, Topic};
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
 
 /// Evaluate whether a notification should be sent for a message on a given topic.
 pub fn should_notify(topic: &Topic, msg: &MessageResponse) -> bool {
@@ -15,6 +16,21 @@ pub fn should_notify(topic: &Topic, msg: &MessageResponse) -> bool {
     }
 }
 
+/// Evaluate whether a subscribed device should be pushed a topic message. A
+/// subscription without its own policy gets what the topic owner gets.
+pub fn should_notify_subscriber(
+    topic: &Topic,
+    subscription: &Subscription,
+    msg: &MessageResponse,
+) -> bool {
+    match subscription.notify_policy.as_deref() {
+        Some("always") => true,
+        Some("never") => false,
+        Some("threshold") => msg.priority >= subscription.notify_priority_min.unwrap_or(0),
+        _ => msg.inbox && should_notify(topic, msg),
+    }
+}
+
 /// Evaluate whether a message should be stored based on topic storage policy.
 pub fn should_store(
     topic: &Topic,
@@ -52,6 +68,105 @@ pub fn should_inbox(topic: &Topic, priority: i32, global_threshold: i32) -> bool
     }
 }
 
+/// Whether `now` falls in the daily window from `start` to `end` (`HH:MM`,
+/// local to `timezone`). A window that ends before it starts runs past
+/// midnight. Unparseable times or timezones never match.
+pub fn in_quiet_hours(start: &str, end: &str, timezone: &str, now: DateTime<Utc>) -> bool {
+    let (Ok(start), Ok(end), Ok(tz)) = (
+        NaiveTime::parse_from_str(start, "%H:%M"),
+        NaiveTime::parse_from_str(end, "%H:%M"),
+        timezone.parse::<chrono_tz::Tz>(),
+    ) else {
+        return false;
+    };
+    let local = now.with_timezone(&tz).time();
+    if start <= end {
+        start <= local && local < end
+    } else {
+        local >= start || local < end
+    }
+}
+
+/// Whether `now` falls in a quiet-hours schedule. The part of an overnight
+/// window after midnight belongs to the weekday it started on, so a `fri`
+/// window from 22:00 to 08:00 covers Saturday morning but not Friday's.
+pub fn in_quiet_schedule(schedule: &QuietHours, now: DateTime<Utc>) -> bool {
+    if !in_quiet_hours(
+        &schedule.start_time,
+        &schedule.end_time,
+        &schedule.timezone,
+        now,
+    ) {
+        return false;
+    }
+    let Some(days) = schedule.weekdays() else {
+        return true;
+    };
+    let (Ok(start), Ok(tz)) = (
+        NaiveTime::parse_from_str(&schedule.start_time, "%H:%M"),
+        schedule.timezone.parse::<chrono_tz::Tz>(),
+    ) else {
+        return false;
+    };
+    let local = now.with_timezone(&tz);
+    let started = if local.time() < start {
+        local.weekday().pred()
+    } else {
+        local.weekday()
+    };
+    days.contains(&started)
+}
+
+fn quiet_schedules(
+    schedules: &[QuietHours],
+    client_id: Option<i64>,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = &QuietHours> {
+    schedules.iter().filter(move |s| {
+        s.enabled
+            && (s.client_id.is_none() || s.client_id == client_id)
+            && in_quiet_schedule(s, now)
+    })
+}
+
+/// Whether a device (or, with `client_id` `None`, the user's email) is in
+/// quiet hours under any of the user's schedules: those for all devices and
+/// those for this device.
+pub fn is_quiet(schedules: &[QuietHours], client_id: Option<i64>, now: DateTime<Utc>) -> bool {
+    quiet_schedules(schedules, client_id, now).next().is_some()
+}
+
+/// Whether a message should be held back from a device (or the user's email)
+/// by quiet hours: some active schedule doesn't let its priority through.
+pub fn should_suppress(
+    schedules: &[QuietHours],
+    client_id: Option<i64>,
+    priority: i32,
+    now: DateTime<Utc>,
+) -> bool {
+    quiet_schedules(schedules, client_id, now)
+        .any(|s| s.override_priority.is_none_or(|min| priority < min))
+}
+
+/// Whether `timezone` is an IANA timezone name known to the server.
+pub fn is_valid_timezone(timezone: &str) -> bool {
+    timezone.parse::<chrono_tz::Tz>().is_ok()
+}
+
+/// Evaluate whether a message in a user's inbox should be emailed to them.
+/// Which topics reach the user, and quiet hours, are up to the caller.
+pub fn should_email(prefs: &EmailPreferences, msg: &MessageResponse) -> bool {
+    if !prefs.enabled || msg.priority < prefs.min_priority {
+        return false;
+    }
+    if let (Some(appid), Some(apps)) = (msg.appid, prefs.application_id_list()) {
+        if !apps.contains(&appid) {
+            return false;
+        }
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,6 +181,7 @@ mod tests {
             id: 1,
             name: "test".to_string(),
             owner_id: None,
+            group_id: None,
             description: None,
             everyone_read: true,
             everyone_write: true,
@@ -78,6 +194,7 @@ mod tests {
             store_interval,
             inbox_override: None,
             inbox_priority_min: None,
+            escalation_policy: None,
         }
     }
 
@@ -166,4 +283,154 @@ mod tests {
         let topic = make_topic("always", None, "on_change", None);
         assert!(!should_store(&topic, Some("same body"), None));
     }
+
+    fn make_subscription(policy: Option<&str>, priority_min: Option<i32>) -> Subscription {
+        Subscription {
+            id: 1,
+            client_id: 1,
+            topic_pattern: "test".to_string(),
+            notify_policy: policy.map(str::to_string),
+            notify_priority_min: priority_min,
+            created_at: "2024-01-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_subscriber_policy_overrides_topic() {
+        let topic = make_topic("never", None, "all", None);
+        let msg = make_msg(6);
+        assert!(should_notify_subscriber(
+            &topic,
+            &make_subscription(Some("always"), None),
+            &msg
+        ));
+        assert!(should_notify_subscriber(
+            &topic,
+            &make_subscription(Some("threshold"), Some(5)),
+            &msg
+        ));
+        assert!(!should_notify_subscriber(
+            &topic,
+            &make_subscription(Some("threshold"), Some(7)),
+            &msg
+        ));
+        // Without its own policy the subscriber follows the topic.
+        assert!(!should_notify_subscriber(
+            &topic,
+            &make_subscription(None, None),
+            &msg
+        ));
+        let topic = make_topic("always", None, "all", None);
+        assert!(should_notify_subscriber(
+            &topic,
+            &make_subscription(None, None),
+            &msg
+        ));
+        assert!(!should_notify_subscriber(
+            &topic,
+            &make_subscription(Some("never"), None),
+            &msg
+        ));
+    }
+
+    fn at(hh_mm: &str) -> DateTime<Utc> {
+        format!("2026-03-02T{}:00Z", hh_mm).parse().unwrap()
+    }
+
+    #[test]
+    fn test_in_quiet_hours() {
+        assert!(in_quiet_hours("09:00", "17:00", "UTC", at("12:00")));
+        assert!(!in_quiet_hours("09:00", "17:00", "UTC", at("17:00")));
+        // Overnight window.
+        assert!(in_quiet_hours("22:00", "07:00", "UTC", at("23:30")));
+        assert!(in_quiet_hours("22:00", "07:00", "UTC", at("06:59")));
+        assert!(!in_quiet_hours("22:00", "07:00", "UTC", at("12:00")));
+        // 21:30 UTC is 22:30 in Berlin (CET).
+        assert!(in_quiet_hours(
+            "22:00",
+            "07:00",
+            "Europe/Berlin",
+            at("21:30")
+        ));
+        assert!(!in_quiet_hours(
+            "22:00",
+            "07:00",
+            "Nowhere/Else",
+            at("23:30")
+        ));
+    }
+
+    fn make_quiet(start: &str, end: &str, days: Option<&str>) -> QuietHours {
+        QuietHours {
+            id: 1,
+            user_id: 1,
+            client_id: None,
+            enabled: true,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            days: days.map(str::to_string),
+            timezone: "UTC".to_string(),
+            override_priority: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_schedule_weekdays() {
+        // 2026-03-02 is a Monday.
+        let weekdays = make_quiet("09:00", "17:00", Some(r#"["mon","tue"]"#));
+        assert!(in_quiet_schedule(&weekdays, at("12:00")));
+        let weekend = make_quiet("09:00", "17:00", Some(r#"["sat","sun"]"#));
+        assert!(!in_quiet_schedule(&weekend, at("12:00")));
+
+        // Sunday night's window still covers Monday morning...
+        let sunday_night = make_quiet("22:00", "07:00", Some(r#"["sun"]"#));
+        assert!(in_quiet_schedule(&sunday_night, at("06:00")));
+        // ...but Monday night's doesn't start until 22:00.
+        let monday_night = make_quiet("22:00", "07:00", Some(r#"["mon"]"#));
+        assert!(!in_quiet_schedule(&monday_night, at("06:00")));
+        assert!(in_quiet_schedule(&monday_night, at("23:00")));
+    }
+
+    #[test]
+    fn test_should_suppress() {
+        let mut all_devices = make_quiet("11:00", "13:00", None);
+        all_devices.override_priority = Some(8);
+        let mut phone = make_quiet("13:00", "15:00", None);
+        phone.client_id = Some(7);
+        let schedules = [all_devices, phone];
+
+        assert!(should_suppress(&schedules, Some(7), 5, at("12:00")));
+        assert!(!should_suppress(&schedules, Some(7), 8, at("12:00")));
+        assert!(should_suppress(&schedules, Some(7), 10, at("14:00")));
+        // The phone's schedule doesn't cover other devices or email.
+        assert!(!should_suppress(&schedules, Some(9), 5, at("14:00")));
+        assert!(!should_suppress(&schedules, None, 5, at("14:00")));
+        assert!(is_quiet(&schedules, Some(7), at("14:00")));
+        assert!(!is_quiet(&schedules, Some(7), at("16:00")));
+
+        let mut disabled = schedules.clone();
+        disabled[0].enabled = false;
+        assert!(!should_suppress(&disabled, None, 5, at("12:00")));
+    }
+
+    #[test]
+    fn test_should_email() {
+        let mut prefs = EmailPreferences::disabled(1);
+        let mut msg = make_msg(5);
+        assert!(!should_email(&prefs, &msg));
+
+        prefs.enabled = true;
+        assert!(should_email(&prefs, &msg));
+        prefs.min_priority = 6;
+        assert!(!should_email(&prefs, &msg));
+        prefs.min_priority = 5;
+
+        prefs.application_ids = Some("[3]".to_string());
+        msg.appid = Some(4);
+        assert!(!should_email(&prefs, &msg));
+        msg.appid = Some(3);
+        assert!(should_email(&prefs, &msg));
+    }
 }
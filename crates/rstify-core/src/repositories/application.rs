@@ -7,6 +7,7 @@ pub trait ApplicationRepository: Send + Sync {
     async fn create(
         &self,
         user_id: i64,
+        group_id: Option<i64>,
         name: &str,
         description: Option<&str>,
         token: &str,
@@ -14,7 +15,15 @@ pub trait ApplicationRepository: Send + Sync {
     ) -> Result<Application, CoreError>;
     async fn find_by_id(&self, id: i64) -> Result<Option<Application>, CoreError>;
     async fn find_by_token(&self, token: &str) -> Result<Option<Application>, CoreError>;
+    /// The user's oldest application with this name.
+    async fn find_by_user_and_name(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> Result<Option<Application>, CoreError>;
     async fn list_by_user(&self, user_id: i64) -> Result<Vec<Application>, CoreError>;
+    /// The user's own applications plus those owned by their groups.
+    async fn list_accessible(&self, user_id: i64) -> Result<Vec<Application>, CoreError>;
     async fn update(
         &self,
         id: i64,
@@ -24,6 +33,7 @@ pub trait ApplicationRepository: Send + Sync {
         retention_days: Option<Option<i32>>,
     ) -> Result<Application, CoreError>;
     async fn list_with_retention(&self) -> Result<Vec<Application>, CoreError>;
+    async fn set_group(&self, id: i64, group_id: Option<i64>) -> Result<Application, CoreError>;
     async fn update_image(&self, id: i64, image: Option<&str>) -> Result<Application, CoreError>;
     async fn delete(&self, id: i64) -> Result<(), CoreError>;
 }
@@ -0,0 +1,11 @@
+use crate::error::CoreError;
+use crate::models::{AuditEntry, AuditFilter, NewAuditEntry};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn record(&self, entry: &NewAuditEntry) -> Result<(), CoreError>;
+    /// Newest first, at most `limit` entries.
+    async fn list(&self, filter: &AuditFilter, limit: i64) -> Result<Vec<AuditEntry>, CoreError>;
+    async fn purge_older_than(&self, days: i64) -> Result<u64, CoreError>;
+}
@@ -1,7 +1,22 @@
 use crate::error::CoreError;
-use crate::models::Client;
+use crate::models::{Client, PushDelivery, QuietHours, Subscription, SuppressedPush};
 use async_trait::async_trait;
 
+/// One recorded push attempt, logged against every client holding the token.
+#[derive(Debug, Default, Clone)]
+pub struct NewPushDelivery<'a> {
+    /// `fcm` or `webpush`.
+    pub channel: &'a str,
+    /// The FCM token, or the Web Push endpoint.
+    pub token: &'a str,
+    pub message_id: Option<i64>,
+    pub outcome: &'a str,
+    pub status_code: Option<i32>,
+    pub error_code: Option<&'a str>,
+    pub error: Option<&'a str>,
+    pub attempts: i32,
+}
+
 #[async_trait]
 pub trait ClientRepository: Send + Sync {
     async fn create(
@@ -10,6 +25,8 @@ pub trait ClientRepository: Send + Sync {
         name: &str,
         token: &str,
         scopes: &str,
+        expires_at: Option<&str>,
+        allowed_ips: Option<&str>,
     ) -> Result<Client, CoreError>;
     async fn find_by_id(&self, id: i64) -> Result<Option<Client>, CoreError>;
     async fn find_by_token(&self, token: &str) -> Result<Option<Client>, CoreError>;
@@ -19,9 +36,79 @@ pub trait ClientRepository: Send + Sync {
         id: i64,
         name: Option<&str>,
         scopes: Option<&str>,
+        expires_at: Option<Option<&str>>,
+        allowed_ips: Option<Option<&str>>,
     ) -> Result<Client, CoreError>;
+    /// Record a successful authentication. Implementations may skip the write
+    /// when the last recorded use is recent and from the same address.
+    async fn touch_last_used(&self, id: i64, ip: Option<&str>) -> Result<(), CoreError>;
     async fn update_fcm_token(&self, id: i64, fcm_token: Option<&str>)
         -> Result<Client, CoreError>;
     async fn list_fcm_tokens_by_user(&self, user_id: i64) -> Result<Vec<String>, CoreError>;
+    /// Remove a push token from every client that registered it.
+    async fn clear_fcm_token(&self, fcm_token: &str) -> Result<u64, CoreError>;
+    /// Set or, with `None`, remove a client's Web Push subscription as
+    /// `(endpoint, p256dh, auth)`.
+    async fn update_web_push(
+        &self,
+        id: i64,
+        subscription: Option<(&str, &str, &str)>,
+    ) -> Result<Client, CoreError>;
+    /// Remove a Web Push subscription from every client that registered it.
+    async fn clear_web_push_endpoint(&self, endpoint: &str) -> Result<u64, CoreError>;
     async fn delete(&self, id: i64) -> Result<(), CoreError>;
+    async fn delete_expired(&self) -> Result<u64, CoreError>;
+    /// Unexpired tokens expiring within `days` whose owner hasn't been warned yet.
+    async fn list_expiring_unnotified(&self, days: i64) -> Result<Vec<Client>, CoreError>;
+    async fn mark_expiry_notified(&self, id: i64) -> Result<(), CoreError>;
+
+    /// Subscribe a client to a topic pattern, or update the preferences of an
+    /// existing subscription to the same pattern.
+    async fn upsert_subscription(
+        &self,
+        client_id: i64,
+        topic_pattern: &str,
+        notify_policy: Option<&str>,
+        notify_priority_min: Option<i32>,
+    ) -> Result<Subscription, CoreError>;
+    async fn list_subscriptions(&self, client_id: i64) -> Result<Vec<Subscription>, CoreError>;
+    async fn delete_subscription(&self, client_id: i64, id: i64) -> Result<(), CoreError>;
+    /// Subscriptions of clients with an FCM token or Web Push subscription whose
+    /// pattern is `topic` itself or contains a wildcard, each with its client.
+    /// Wildcard patterns still have to be checked against the topic.
+    async fn list_push_subscriptions(
+        &self,
+        topic: &str,
+    ) -> Result<Vec<(Subscription, Client)>, CoreError>;
+
+    async fn record_push_delivery(&self, delivery: &NewPushDelivery<'_>) -> Result<(), CoreError>;
+    /// Newest first.
+    async fn list_push_deliveries(
+        &self,
+        client_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<PushDelivery>, CoreError>;
+    async fn purge_push_deliveries_older_than(&self, days: i64) -> Result<u64, CoreError>;
+
+    /// All of a user's quiet-hours schedules, user-wide and per device.
+    async fn list_quiet_hours(&self, user_id: i64) -> Result<Vec<QuietHours>, CoreError>;
+    async fn find_quiet_hours(&self, id: i64) -> Result<Option<QuietHours>, CoreError>;
+    /// Insert a schedule, or replace the one with its `id` when nonzero
+    /// (`updated_at` is set here).
+    async fn save_quiet_hours(&self, schedule: &QuietHours) -> Result<QuietHours, CoreError>;
+    async fn delete_quiet_hours(&self, id: i64) -> Result<(), CoreError>;
+    async fn record_suppressed_push(
+        &self,
+        client_id: i64,
+        message_id: i64,
+    ) -> Result<(), CoreError>;
+    /// Oldest first.
+    async fn list_suppressed_pushes(&self) -> Result<Vec<SuppressedPush>, CoreError>;
+    /// Drop a client's held pushes up to and including `up_to_id`, once summarized.
+    async fn delete_suppressed_pushes(
+        &self,
+        client_id: i64,
+        up_to_id: i64,
+    ) -> Result<u64, CoreError>;
 }
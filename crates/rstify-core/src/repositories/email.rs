@@ -0,0 +1,39 @@
+use crate::error::CoreError;
+use crate::models::EmailDelivery;
+use async_trait::async_trait;
+
+/// An email to queue for sending.
+#[derive(Debug, Clone)]
+pub struct NewEmail<'a> {
+    pub recipient: &'a str,
+    pub subject: &'a str,
+    pub text_body: &'a str,
+    pub html_body: Option<&'a str>,
+}
+
+#[async_trait]
+pub trait EmailRepository: Send + Sync {
+    async fn enqueue(&self, email: &NewEmail<'_>) -> Result<EmailDelivery, CoreError>;
+    /// Oldest pending emails whose next attempt is due, up to `limit`.
+    async fn list_due(&self, limit: i64) -> Result<Vec<EmailDelivery>, CoreError>;
+    async fn mark_sent(&self, id: i64, attempts: i32) -> Result<(), CoreError>;
+    /// Keep the email pending and try again in `delay_secs`.
+    async fn mark_retry(
+        &self,
+        id: i64,
+        attempts: i32,
+        error: &str,
+        delay_secs: i64,
+    ) -> Result<(), CoreError>;
+    async fn mark_failed(&self, id: i64, attempts: i32, error: &str) -> Result<(), CoreError>;
+    /// Newest first, optionally filtered by status.
+    async fn list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<EmailDelivery>, CoreError>;
+    async fn find_by_id(&self, id: i64) -> Result<Option<EmailDelivery>, CoreError>;
+    /// Delete sent and failed emails older than `days`.
+    async fn purge_older_than(&self, days: i64) -> Result<u64, CoreError>;
+}
@@ -0,0 +1,34 @@
+use crate::error::CoreError;
+use crate::models::{Group, GroupMember};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait GroupRepository: Send + Sync {
+    async fn create(&self, name: &str, description: Option<&str>) -> Result<Group, CoreError>;
+    async fn find_by_id(&self, id: i64) -> Result<Option<Group>, CoreError>;
+    async fn list_all(&self) -> Result<Vec<Group>, CoreError>;
+    async fn list_for_user(&self, user_id: i64) -> Result<Vec<Group>, CoreError>;
+    async fn update(
+        &self,
+        id: i64,
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<Group, CoreError>;
+    async fn delete(&self, id: i64) -> Result<(), CoreError>;
+
+    async fn list_members(&self, group_id: i64) -> Result<Vec<GroupMember>, CoreError>;
+    async fn list_memberships_for_user(&self, user_id: i64) -> Result<Vec<GroupMember>, CoreError>;
+    async fn add_member(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        role: &str,
+    ) -> Result<GroupMember, CoreError>;
+    async fn update_member_role(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        role: &str,
+    ) -> Result<GroupMember, CoreError>;
+    async fn remove_member(&self, group_id: i64, user_id: i64) -> Result<(), CoreError>;
+}
@@ -1,5 +1,5 @@
 use crate::error::CoreError;
-use crate::models::{Attachment, Message, WebhookConfig};
+use crate::models::{Attachment, Message, MessageEscalation, WebhookConfig};
 use async_trait::async_trait;
 
 /// Parameters for creating a message. A parameter struct (vs 15 positional args)
@@ -23,6 +23,8 @@ pub struct NewMessage<'a> {
     pub scheduled_for: Option<&'a str>,
     pub source: Option<&'a str>,
     pub inbox: bool,
+    /// Keep an existing timestamp (imports); `None` means now.
+    pub created_at: Option<&'a str>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -91,9 +93,14 @@ pub trait MessageRepository: Send + Sync {
 
     async fn set_expires_at(&self, id: i64, expires_at: &str) -> Result<(), CoreError>;
     async fn delete_expired_messages(&self) -> Result<u64, CoreError>;
+    /// Delete application messages older than their application's retention_days.
+    async fn delete_past_retention(&self) -> Result<u64, CoreError>;
 
     async fn list_scheduled_due(&self) -> Result<Vec<Message>, CoreError>;
     async fn mark_delivered(&self, id: i64) -> Result<(), CoreError>;
+    /// Atomically mark every due scheduled message delivered and return them, so
+    /// concurrent workers never deliver the same message twice.
+    async fn claim_scheduled_due(&self) -> Result<Vec<Message>, CoreError>;
 
     // Attachments
     async fn create_attachment(
@@ -104,6 +111,7 @@ pub trait MessageRepository: Send + Sync {
         size_bytes: i64,
         storage_type: &str,
         storage_path: &str,
+        sha256: Option<&str>,
         expires_at: Option<&str>,
     ) -> Result<Attachment, CoreError>;
     async fn find_attachment(&self, id: i64) -> Result<Option<Attachment>, CoreError>;
@@ -116,7 +124,35 @@ pub trait MessageRepository: Send + Sync {
         message_ids: &[i64],
     ) -> Result<Vec<Attachment>, CoreError>;
     async fn list_expired_attachments(&self) -> Result<Vec<Attachment>, CoreError>;
+    async fn list_attachments_by_storage_type(
+        &self,
+        storage_type: &str,
+    ) -> Result<Vec<Attachment>, CoreError>;
+    /// Repoint an attachment at a blob in another store.
+    async fn update_attachment_storage(
+        &self,
+        id: i64,
+        storage_type: &str,
+        storage_path: &str,
+    ) -> Result<(), CoreError>;
     async fn delete_attachment(&self, id: i64) -> Result<(), CoreError>;
+    /// Oldest attachments waiting for a thumbnail, up to `limit`.
+    async fn list_pending_thumbnails(&self, limit: i64) -> Result<Vec<Attachment>, CoreError>;
+    async fn update_attachment_thumbnail(
+        &self,
+        id: i64,
+        status: &str,
+        width: Option<i64>,
+        height: Option<i64>,
+        thumbnail_path: Option<&str>,
+    ) -> Result<(), CoreError>;
+    /// Number of attachment rows pointing at a blob. Identical uploads share a
+    /// blob, so it may only be deleted once this drops to zero.
+    async fn count_attachment_references(
+        &self,
+        storage_type: &str,
+        storage_path: &str,
+    ) -> Result<i64, CoreError>;
 
     // Webhook configs
     async fn create_webhook_config(
@@ -140,6 +176,7 @@ pub trait MessageRepository: Send + Sync {
         follow_redirects: Option<bool>,
         group_name: Option<&str>,
         secret: Option<&str>,
+        notify_url: Option<&str>,
     ) -> Result<WebhookConfig, CoreError>;
     async fn find_webhook_config_by_id(&self, id: i64) -> Result<Option<WebhookConfig>, CoreError>;
     async fn find_webhook_config_by_token(
@@ -166,11 +203,57 @@ pub trait MessageRepository: Send + Sync {
         follow_redirects: Option<bool>,
         group_name: Option<&str>,
         secret: Option<&str>,
+        notify_url: Option<&str>,
     ) -> Result<WebhookConfig, CoreError>;
+    /// Enabled outgoing webhooks targeting the named topic.
+    async fn list_outgoing_webhooks_for_topic(
+        &self,
+        topic_name: &str,
+    ) -> Result<Vec<WebhookConfig>, CoreError>;
     async fn delete_webhook_config(&self, id: i64) -> Result<(), CoreError>;
     async fn regenerate_webhook_token(
         &self,
         id: i64,
         new_token: &str,
     ) -> Result<WebhookConfig, CoreError>;
+
+    // Escalations. Timestamps are `YYYY-MM-DD HH:MM:SS` UTC.
+    /// Start escalating a message; a no-op if it already is.
+    async fn start_escalation(
+        &self,
+        message_id: i64,
+        topic_id: i64,
+        policy: &str,
+        next_at: &str,
+    ) -> Result<(), CoreError>;
+    async fn find_escalation(
+        &self,
+        message_id: i64,
+    ) -> Result<Option<MessageEscalation>, CoreError>;
+    /// Escalations whose next step is due at `now`.
+    async fn list_due_escalations(&self, now: &str) -> Result<Vec<MessageEscalation>, CoreError>;
+    /// Move past step `step`, scheduling the next one for `next_at` (`None`
+    /// when it was the last). False if the message was acknowledged or the
+    /// step already taken, in which case it must not run.
+    async fn claim_escalation_step(
+        &self,
+        message_id: i64,
+        step: i32,
+        next_at: Option<&str>,
+    ) -> Result<bool, CoreError>;
+    /// Stop escalating. False if there is no escalation or it had already
+    /// stopped.
+    async fn stop_escalation(&self, message_id: i64) -> Result<bool, CoreError>;
+    /// Append `step`, a JSON object, to the steps under [`ESCALATION_EXTRA`]
+    /// in the message's extras. One statement, so concurrent writers to
+    /// other keys aren't lost.
+    ///
+    /// [`ESCALATION_EXTRA`]: crate::models::ESCALATION_EXTRA
+    async fn record_escalation_step(&self, message_id: i64, step: &str) -> Result<(), CoreError>;
+    /// Store `ack`, a JSON object, under [`ACK_EXTRA`] in the message's
+    /// extras in one statement. False if the message was already
+    /// acknowledged, in which case the first acknowledgement is kept.
+    ///
+    /// [`ACK_EXTRA`]: crate::models::ACK_EXTRA
+    async fn record_acknowledgement(&self, message_id: i64, ack: &str) -> Result<bool, CoreError>;
 }
@@ -1,13 +1,42 @@
 pub mod application;
+pub mod audit;
 pub mod client;
+pub mod email;
+pub mod group;
 pub mod message;
+pub mod settings;
 pub mod topic;
 pub mod user;
+pub mod webhook_delivery;
 pub mod webhook_variable;
 
 pub use application::ApplicationRepository;
-pub use client::ClientRepository;
+pub use audit::AuditRepository;
+pub use client::{ClientRepository, NewPushDelivery};
+pub use email::{EmailRepository, NewEmail};
+pub use group::GroupRepository;
 pub use message::{MessageRepository, NewMessage};
+pub use settings::SettingsRepository;
 pub use topic::TopicRepository;
 pub use user::UserRepository;
+pub use webhook_delivery::{NewWebhookDelivery, WebhookDeliveryRepository};
 pub use webhook_variable::WebhookVariableRepository;
+
+use std::sync::Arc;
+
+/// One handle per repository, backed by whichever database the server was
+/// started with. Cheap to clone.
+#[derive(Clone)]
+pub struct Repositories {
+    pub users: Arc<dyn UserRepository>,
+    pub applications: Arc<dyn ApplicationRepository>,
+    pub clients: Arc<dyn ClientRepository>,
+    pub topics: Arc<dyn TopicRepository>,
+    pub groups: Arc<dyn GroupRepository>,
+    pub messages: Arc<dyn MessageRepository>,
+    pub webhook_variables: Arc<dyn WebhookVariableRepository>,
+    pub webhook_deliveries: Arc<dyn WebhookDeliveryRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub settings: Arc<dyn SettingsRepository>,
+    pub emails: Arc<dyn EmailRepository>,
+}
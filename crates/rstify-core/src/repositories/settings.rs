@@ -0,0 +1,11 @@
+use crate::error::CoreError;
+use async_trait::async_trait;
+
+/// Server-wide key/value settings (e.g. `inbox_priority_threshold`).
+#[async_trait]
+pub trait SettingsRepository: Send + Sync {
+    async fn list(&self) -> Result<Vec<(String, String)>, CoreError>;
+    async fn get(&self, key: &str) -> Result<Option<String>, CoreError>;
+    /// Insert or overwrite a setting.
+    async fn set(&self, key: &str, value: &str) -> Result<(), CoreError>;
+}
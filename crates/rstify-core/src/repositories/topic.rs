@@ -9,6 +9,7 @@ pub trait TopicRepository: Send + Sync {
         &self,
         name: &str,
         owner_id: Option<i64>,
+        group_id: Option<i64>,
         description: Option<&str>,
         everyone_read: bool,
         everyone_write: bool,
@@ -32,16 +33,26 @@ pub trait TopicRepository: Send + Sync {
         inbox_override: Option<&str>,
         inbox_priority_min: Option<i32>,
     ) -> Result<Topic, CoreError>;
+    async fn set_group(&self, id: i64, group_id: Option<i64>) -> Result<Topic, CoreError>;
+    /// Replace the JSON escalation policy; `None` removes it.
+    async fn set_escalation_policy(
+        &self,
+        id: i64,
+        policy: Option<&str>,
+    ) -> Result<Topic, CoreError>;
     async fn delete(&self, id: i64) -> Result<(), CoreError>;
     async fn count(&self) -> Result<i64, CoreError>;
 
+    /// Grant to exactly one of `user_id` or `group_id`.
     async fn create_permission(
         &self,
-        user_id: i64,
+        user_id: Option<i64>,
+        group_id: Option<i64>,
         topic_pattern: &str,
         can_read: bool,
         can_write: bool,
     ) -> Result<TopicPermission, CoreError>;
+    /// Grants that apply to a user: their own plus those of their groups.
     async fn list_permissions_for_user(
         &self,
         user_id: i64,
@@ -1,5 +1,5 @@
 use crate::error::CoreError;
-use crate::models::User;
+use crate::models::{EmailPreferences, User};
 use async_trait::async_trait;
 
 #[async_trait]
@@ -22,6 +22,21 @@ pub trait UserRepository: Send + Sync {
         is_admin: Option<bool>,
     ) -> Result<User, CoreError>;
     async fn update_password(&self, id: i64, password_hash: &str) -> Result<(), CoreError>;
+    /// Count a failed login and return the new consecutive failure count.
+    async fn record_login_failure(&self, id: i64) -> Result<i64, CoreError>;
+    /// Refuse logins until `until` (SQLite datetime format).
+    async fn lock_until(&self, id: i64, until: &str) -> Result<(), CoreError>;
+    /// Reset the failure count and lift any lock.
+    async fn clear_login_failures(&self, id: i64) -> Result<(), CoreError>;
     async fn delete(&self, id: i64) -> Result<(), CoreError>;
     async fn count(&self) -> Result<i64, CoreError>;
+    /// The user's saved email preferences, if any.
+    async fn email_preferences(&self, user_id: i64) -> Result<Option<EmailPreferences>, CoreError>;
+    /// Insert or replace a user's email preferences (`updated_at` is set here).
+    async fn set_email_preferences(
+        &self,
+        prefs: &EmailPreferences,
+    ) -> Result<EmailPreferences, CoreError>;
+    /// Preferences of every user with email enabled.
+    async fn list_email_enabled(&self) -> Result<Vec<EmailPreferences>, CoreError>;
 }
@@ -0,0 +1,43 @@
+use crate::error::CoreError;
+use crate::models::{WebhookDeliveryLog, WebhookHealth};
+use async_trait::async_trait;
+
+/// One recorded webhook attempt, incoming or outgoing.
+#[derive(Debug, Default, Clone)]
+pub struct NewWebhookDelivery<'a> {
+    pub webhook_config_id: i64,
+    pub message_id: Option<i64>,
+    pub status_code: Option<i32>,
+    pub response_body_preview: Option<&'a str>,
+    pub duration_ms: i64,
+    pub success: bool,
+}
+
+#[async_trait]
+pub trait WebhookDeliveryRepository: Send + Sync {
+    async fn record(&self, delivery: &NewWebhookDelivery<'_>) -> Result<(), CoreError>;
+    /// Newest first, optionally filtered by outcome and start time.
+    async fn list_for_config(
+        &self,
+        webhook_config_id: i64,
+        success: Option<bool>,
+        since: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookDeliveryLog>, CoreError>;
+    /// Health over the last `window` attempts of each webhook. Webhooks that
+    /// never fired are absent from the result.
+    async fn health(
+        &self,
+        webhook_config_ids: &[i64],
+        window: i64,
+    ) -> Result<Vec<WebhookHealth>, CoreError>;
+    /// `(webhook_config_id, duration_ms)` of the last `per_webhook` attempts,
+    /// grouped by webhook and newest first within each group.
+    async fn recent_durations(
+        &self,
+        webhook_config_ids: &[i64],
+        per_webhook: i64,
+    ) -> Result<Vec<(i64, i64)>, CoreError>;
+    async fn purge_older_than(&self, days: i64) -> Result<u64, CoreError>;
+}
@@ -1,4 +1,5 @@
 pub mod pool;
+pub mod postgres;
 pub mod repositories;
 
 use rstify_core::error::CoreError;
@@ -1,16 +1,57 @@
+use crate::postgres;
+use crate::repositories::{
+    SqliteApplicationRepo, SqliteAuditRepo, SqliteClientRepo, SqliteEmailRepo, SqliteGroupRepo,
+    SqliteMessageRepo, SqliteSettingsRepo, SqliteTopicRepo, SqliteUserRepo,
+    SqliteWebhookDeliveryRepo, SqliteWebhookVariableRepo,
+};
+use rstify_core::repositories::Repositories;
+use sqlx::postgres::PgPoolOptions;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::SqlitePool;
+use sqlx::{PgPool, SqlitePool};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::info;
 
+/// Connection pool for the configured backend. `DATABASE_URL` values starting
+/// with `postgres://` or `postgresql://` select PostgreSQL; anything else is
+/// treated as a SQLite URL.
 #[derive(Clone)]
-pub struct Database {
-    pool: SqlitePool,
+pub enum Database {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+/// True if the URL names a PostgreSQL server rather than a SQLite file.
+pub fn is_postgres_url(url: &str) -> bool {
+    url.starts_with("postgres://") || url.starts_with("postgresql://")
 }
 
 impl Database {
     pub async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        if is_postgres_url(url) {
+            Self::connect_postgres(url).await
+        } else {
+            Self::connect_sqlite(url).await
+        }
+    }
+
+    async fn connect_postgres(url: &str) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(20)
+            .min_connections(2)
+            .acquire_timeout(Duration::from_secs(3))
+            .idle_timeout(Duration::from_secs(600))
+            .max_lifetime(Duration::from_secs(1800))
+            .connect(url)
+            .await?;
+
+        info!("Connected to PostgreSQL database (max: 20, min: 2)");
+        Ok(Self::Postgres(pool))
+    }
+
+    async fn connect_sqlite(url: &str) -> Result<Self, sqlx::Error> {
         let opts = SqliteConnectOptions::from_str(url)?
             .create_if_missing(true)
             .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
@@ -33,172 +74,383 @@ impl Database {
             .await?;
 
         info!("Connected to SQLite database with optimized pool (max: 20, min: 2)");
-        Ok(Self { pool })
+        Ok(Self::Sqlite(pool))
     }
 
     pub async fn migrate(&self) -> Result<(), sqlx::Error> {
-        // Create migration tracking table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS _migrations (
-                name TEXT PRIMARY KEY,
-                applied_at TEXT NOT NULL DEFAULT (datetime('now'))
-            )",
-        )
-        .execute(&self.pool)
-        .await?;
+        match self {
+            Self::Sqlite(pool) => migrate_sqlite(pool).await,
+            Self::Postgres(pool) => postgres::migrate(pool).await,
+        }
+    }
 
-        let migrations: &[(&str, &str)] = &[
-            (
-                "001_users",
-                include_str!("../../../migrations/001_users.sql"),
-            ),
-            (
-                "002_applications",
-                include_str!("../../../migrations/002_applications.sql"),
-            ),
-            (
-                "003_clients",
-                include_str!("../../../migrations/003_clients.sql"),
-            ),
-            (
-                "004_topics",
-                include_str!("../../../migrations/004_topics.sql"),
-            ),
-            (
-                "005_topic_permissions",
-                include_str!("../../../migrations/005_topic_permissions.sql"),
-            ),
-            (
-                "006_messages",
-                include_str!("../../../migrations/006_messages.sql"),
-            ),
-            (
-                "007_attachments",
-                include_str!("../../../migrations/007_attachments.sql"),
-            ),
-            (
-                "008_webhook_configs",
-                include_str!("../../../migrations/008_webhook_configs.sql"),
-            ),
-            (
-                "009_indexes",
-                include_str!("../../../migrations/009_indexes.sql"),
-            ),
-            (
-                "010_message_expiry",
-                include_str!("../../../migrations/010_message_expiry.sql"),
-            ),
-            (
-                "011_outgoing_webhooks",
-                include_str!("../../../migrations/011_outgoing_webhooks.sql"),
-            ),
-            (
-                "012_unified_push",
-                include_str!("../../../migrations/012_unified_push.sql"),
-            ),
-            (
-                "013_additional_indexes",
-                include_str!("../../../migrations/013_additional_indexes.sql"),
-            ),
-            (
-                "014_fcm_tokens",
-                include_str!("../../../migrations/014_fcm_tokens.sql"),
-            ),
-            (
-                "015_fts5_messages",
-                include_str!("../../../migrations/015_fts5_messages.sql"),
-            ),
-            (
-                "016_retention_days",
-                include_str!("../../../migrations/016_retention_days.sql"),
-            ),
-            (
-                "017_client_scopes",
-                include_str!("../../../migrations/017_client_scopes.sql"),
-            ),
-            (
-                "018_webhook_delivery_log",
-                include_str!("../../../migrations/018_webhook_delivery_log.sql"),
-            ),
-            (
-                "019_message_source",
-                include_str!("../../../migrations/019_message_source.sql"),
-            ),
-            (
-                "020_topic_notification_policy",
-                include_str!("../../../migrations/020_topic_notification_policy.sql"),
-            ),
-            (
-                "021_mqtt_bridges",
-                include_str!("../../../migrations/021_mqtt_bridges.sql"),
-            ),
-            (
-                "022_webhook_timeout",
-                include_str!("../../../migrations/022_webhook_timeout.sql"),
-            ),
-            (
-                "023_webhook_redirects",
-                include_str!("../../../migrations/023_webhook_redirects.sql"),
-            ),
-            (
-                "024_webhook_groups",
-                include_str!("../../../migrations/024_webhook_groups.sql"),
-            ),
-            (
-                "025_webhook_variables",
-                include_str!("../../../migrations/025_webhook_variables.sql"),
-            ),
-            (
-                "026_webhook_secret",
-                include_str!("../../../migrations/026_webhook_secret.sql"),
-            ),
-            (
-                "027_message_inbox",
-                include_str!("../../../migrations/027_message_inbox.sql"),
-            ),
-            (
-                "028_topic_inbox_override",
-                include_str!("../../../migrations/028_topic_inbox_override.sql"),
-            ),
-            (
-                "029_settings_table",
-                include_str!("../../../migrations/029_settings_table.sql"),
-            ),
-            (
-                "030_index_optimizations",
-                include_str!("../../../migrations/030_index_optimizations.sql"),
-            ),
-        ];
-
-        for (name, sql) in migrations {
-            let applied: bool =
-                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM _migrations WHERE name = ?)")
-                    .bind(name)
-                    .fetch_one(&self.pool)
-                    .await?;
+    /// Repository handles backed by this database.
+    pub fn repositories(&self) -> Repositories {
+        match self {
+            Self::Sqlite(pool) => Repositories {
+                users: Arc::new(SqliteUserRepo::new(pool.clone())),
+                applications: Arc::new(SqliteApplicationRepo::new(pool.clone())),
+                clients: Arc::new(SqliteClientRepo::new(pool.clone())),
+                topics: Arc::new(SqliteTopicRepo::new(pool.clone())),
+                groups: Arc::new(SqliteGroupRepo::new(pool.clone())),
+                messages: Arc::new(SqliteMessageRepo::new(pool.clone())),
+                webhook_variables: Arc::new(SqliteWebhookVariableRepo::new(pool.clone())),
+                webhook_deliveries: Arc::new(SqliteWebhookDeliveryRepo::new(pool.clone())),
+                audit: Arc::new(SqliteAuditRepo::new(pool.clone())),
+                settings: Arc::new(SqliteSettingsRepo::new(pool.clone())),
+                emails: Arc::new(SqliteEmailRepo::new(pool.clone())),
+            },
+            Self::Postgres(pool) => postgres::repositories(pool),
+        }
+    }
 
-            if !applied {
-                // Execute each statement separately for multi-statement migrations.
-                // Use a simple state machine to avoid splitting inside BEGIN...END blocks
-                // (e.g., trigger definitions contain semicolons in their body).
-                for stmt in split_sql_statements(sql) {
-                    sqlx::query(&stmt).execute(&self.pool).await?;
-                }
-                sqlx::query("INSERT INTO _migrations (name) VALUES (?)")
-                    .bind(name)
-                    .execute(&self.pool)
+    /// Round-trip a trivial query, for health checks.
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        match self {
+            Self::Sqlite(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+            Self::Postgres(pool) => sqlx::query("SELECT 1").execute(pool).await.map(|_| ()),
+        }
+    }
+
+    /// Write a consistent copy of a SQLite database to `path` (which must not
+    /// exist) without taking it offline. PostgreSQL has `pg_dump` for this.
+    pub async fn snapshot_to(&self, path: &Path) -> Result<(), sqlx::Error> {
+        match self {
+            Self::Sqlite(pool) => {
+                // As a URI with an explicit mode, or an in-memory source would
+                // pass its memory flag on and the copy would never reach disk.
+                let target: String = path
+                    .to_string_lossy()
+                    .chars()
+                    .map(|c| match c {
+                        '%' => "%25".to_string(),
+                        '?' => "%3f".to_string(),
+                        '#' => "%23".to_string(),
+                        c => c.to_string(),
+                    })
+                    .collect();
+                sqlx::query("VACUUM INTO ?")
+                    .bind(format!("file:{}?mode=rwc", target))
+                    .execute(pool)
                     .await?;
-                info!("Applied migration: {}", name);
+                Ok(())
             }
+            Self::Postgres(_) => Err(sqlx::Error::Configuration(
+                "snapshots are only supported for SQLite; use pg_dump for PostgreSQL".into(),
+            )),
         }
+    }
+
+    /// Names of the migrations applied to this database.
+    pub async fn applied_migrations(&self) -> Result<Vec<String>, sqlx::Error> {
+        match self {
+            Self::Sqlite(pool) => applied_migrations(pool).await,
+            Self::Postgres(pool) => {
+                sqlx::query_scalar("SELECT name FROM _migrations ORDER BY name")
+                    .fetch_all(pool)
+                    .await
+            }
+        }
+    }
+
+    /// The SQLite pool, if this is a SQLite database.
+    pub fn sqlite_pool(&self) -> Option<&SqlitePool> {
+        match self {
+            Self::Sqlite(pool) => Some(pool),
+            Self::Postgres(_) => None,
+        }
+    }
+}
+
+/// SQLite schema migrations in the order they are applied, by name. A database
+/// is compatible with this build if every migration it records is listed here.
+pub const SQLITE_MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "001_users",
+        include_str!("../../../migrations/001_users.sql"),
+    ),
+    (
+        "002_applications",
+        include_str!("../../../migrations/002_applications.sql"),
+    ),
+    (
+        "003_clients",
+        include_str!("../../../migrations/003_clients.sql"),
+    ),
+    (
+        "004_topics",
+        include_str!("../../../migrations/004_topics.sql"),
+    ),
+    (
+        "005_topic_permissions",
+        include_str!("../../../migrations/005_topic_permissions.sql"),
+    ),
+    (
+        "006_messages",
+        include_str!("../../../migrations/006_messages.sql"),
+    ),
+    (
+        "007_attachments",
+        include_str!("../../../migrations/007_attachments.sql"),
+    ),
+    (
+        "008_webhook_configs",
+        include_str!("../../../migrations/008_webhook_configs.sql"),
+    ),
+    (
+        "009_indexes",
+        include_str!("../../../migrations/009_indexes.sql"),
+    ),
+    (
+        "010_message_expiry",
+        include_str!("../../../migrations/010_message_expiry.sql"),
+    ),
+    (
+        "011_outgoing_webhooks",
+        include_str!("../../../migrations/011_outgoing_webhooks.sql"),
+    ),
+    (
+        "012_unified_push",
+        include_str!("../../../migrations/012_unified_push.sql"),
+    ),
+    (
+        "013_additional_indexes",
+        include_str!("../../../migrations/013_additional_indexes.sql"),
+    ),
+    (
+        "014_fcm_tokens",
+        include_str!("../../../migrations/014_fcm_tokens.sql"),
+    ),
+    (
+        "015_fts5_messages",
+        include_str!("../../../migrations/015_fts5_messages.sql"),
+    ),
+    (
+        "016_retention_days",
+        include_str!("../../../migrations/016_retention_days.sql"),
+    ),
+    (
+        "017_client_scopes",
+        include_str!("../../../migrations/017_client_scopes.sql"),
+    ),
+    (
+        "018_webhook_delivery_log",
+        include_str!("../../../migrations/018_webhook_delivery_log.sql"),
+    ),
+    (
+        "019_message_source",
+        include_str!("../../../migrations/019_message_source.sql"),
+    ),
+    (
+        "020_topic_notification_policy",
+        include_str!("../../../migrations/020_topic_notification_policy.sql"),
+    ),
+    (
+        "021_mqtt_bridges",
+        include_str!("../../../migrations/021_mqtt_bridges.sql"),
+    ),
+    (
+        "022_webhook_timeout",
+        include_str!("../../../migrations/022_webhook_timeout.sql"),
+    ),
+    (
+        "023_webhook_redirects",
+        include_str!("../../../migrations/023_webhook_redirects.sql"),
+    ),
+    (
+        "024_webhook_groups",
+        include_str!("../../../migrations/024_webhook_groups.sql"),
+    ),
+    (
+        "025_webhook_variables",
+        include_str!("../../../migrations/025_webhook_variables.sql"),
+    ),
+    (
+        "026_webhook_secret",
+        include_str!("../../../migrations/026_webhook_secret.sql"),
+    ),
+    (
+        "027_message_inbox",
+        include_str!("../../../migrations/027_message_inbox.sql"),
+    ),
+    (
+        "028_topic_inbox_override",
+        include_str!("../../../migrations/028_topic_inbox_override.sql"),
+    ),
+    (
+        "029_settings_table",
+        include_str!("../../../migrations/029_settings_table.sql"),
+    ),
+    (
+        "030_index_optimizations",
+        include_str!("../../../migrations/030_index_optimizations.sql"),
+    ),
+    (
+        "031_client_token_restrictions",
+        include_str!("../../../migrations/031_client_token_restrictions.sql"),
+    ),
+    (
+        "032_groups",
+        include_str!("../../../migrations/032_groups.sql"),
+    ),
+    (
+        "033_audit_log",
+        include_str!("../../../migrations/033_audit_log.sql"),
+    ),
+    (
+        "034_login_lockout",
+        include_str!("../../../migrations/034_login_lockout.sql"),
+    ),
+    (
+        "035_attachment_checksums",
+        include_str!("../../../migrations/035_attachment_checksums.sql"),
+    ),
+    (
+        "036_attachment_thumbnails",
+        include_str!("../../../migrations/036_attachment_thumbnails.sql"),
+    ),
+    (
+        "037_subscriptions",
+        include_str!("../../../migrations/037_subscriptions.sql"),
+    ),
+    (
+        "038_push_delivery_log",
+        include_str!("../../../migrations/038_push_delivery_log.sql"),
+    ),
+    (
+        "039_web_push",
+        include_str!("../../../migrations/039_web_push.sql"),
+    ),
+    (
+        "040_email_preferences",
+        include_str!("../../../migrations/040_email_preferences.sql"),
+    ),
+    (
+        "041_email_queue",
+        include_str!("../../../migrations/041_email_queue.sql"),
+    ),
+    (
+        "042_webhook_notify_url",
+        include_str!("../../../migrations/042_webhook_notify_url.sql"),
+    ),
+    (
+        "043_quiet_hours",
+        include_str!("../../../migrations/043_quiet_hours.sql"),
+    ),
+    (
+        "044_escalations",
+        include_str!("../../../migrations/044_escalations.sql"),
+    ),
+    (
+        "045_email_quiet_hours",
+        include_str!("../../../migrations/045_email_quiet_hours.sql"),
+    ),
+    (
+        "046_escalation_acks",
+        include_str!("../../../migrations/046_escalation_acks.sql"),
+    ),
+];
+
+/// Migrations recorded in `applied` that this build doesn't know, meaning the
+/// database was last used by a newer version.
+pub fn unknown_migrations(applied: &[String]) -> Vec<String> {
+    applied
+        .iter()
+        .filter(|name| !SQLITE_MIGRATIONS.iter().any(|(known, _)| known == name))
+        .cloned()
+        .collect()
+}
 
-        info!("Database migrations applied successfully");
-        Ok(())
+/// Migrations recorded in a SQLite database's `_migrations` table. A database
+/// without one has none applied.
+pub async fn applied_migrations(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !has_table {
+        return Ok(Vec::new());
     }
+    sqlx::query_scalar("SELECT name FROM _migrations ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
 
-    pub fn pool(&self) -> &SqlitePool {
-        &self.pool
+/// Open a SQLite file read-only, check its integrity and return the migrations
+/// it records. Used to vet a database before it replaces the live one.
+pub async fn inspect_sqlite_file(path: &Path) -> Result<Vec<String>, sqlx::Error> {
+    let opts = SqliteConnectOptions::new().filename(path).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await?;
+    let result = async {
+        let status: String = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_one(&pool)
+            .await?;
+        if status != "ok" {
+            return Err(sqlx::Error::Protocol(format!(
+                "integrity check failed: {}",
+                status
+            )));
+        }
+        applied_migrations(&pool).await
     }
+    .await;
+    pool.close().await;
+    result
+}
+
+/// The file behind a SQLite URL; `None` for PostgreSQL and in-memory databases.
+pub fn sqlite_file(url: &str) -> Option<PathBuf> {
+    if is_postgres_url(url) {
+        return None;
+    }
+    let opts = SqliteConnectOptions::from_str(url).ok()?;
+    let path = opts.get_filename();
+    let name = path.to_string_lossy();
+    if name.is_empty() || name == ":memory:" || url.contains(":memory:") {
+        return None;
+    }
+    Some(path.to_path_buf())
+}
+
+async fn migrate_sqlite(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // Create migration tracking table
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS _migrations (
+                name TEXT PRIMARY KEY,
+                applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+    )
+    .execute(pool)
+    .await?;
+
+    for (name, sql) in SQLITE_MIGRATIONS {
+        let applied: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM _migrations WHERE name = ?)")
+                .bind(name)
+                .fetch_one(pool)
+                .await?;
+
+        if !applied {
+            // Execute each statement separately for multi-statement migrations.
+            // Use a simple state machine to avoid splitting inside BEGIN...END blocks
+            // (e.g., trigger definitions contain semicolons in their body).
+            for stmt in split_sql_statements(sql) {
+                sqlx::query(&stmt).execute(pool).await?;
+            }
+            sqlx::query("INSERT INTO _migrations (name) VALUES (?)")
+                .bind(name)
+                .execute(pool)
+                .await?;
+            info!("Applied migration: {}", name);
+        }
+    }
+
+    info!("Database migrations applied successfully");
+    Ok(())
 }
 
 /// Split SQL text into individual statements, respecting BEGIN...END blocks
@@ -251,6 +503,112 @@ fn split_sql_statements(sql: &str) -> Vec<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn sqlite_file_from_urls() {
+        assert_eq!(
+            sqlite_file("sqlite://rstify.db"),
+            Some(PathBuf::from("rstify.db"))
+        );
+        assert_eq!(
+            sqlite_file("sqlite:///data/app.db?mode=rwc"),
+            Some(PathBuf::from("/data/app.db"))
+        );
+        assert_eq!(sqlite_file("sqlite::memory:"), None);
+        assert_eq!(sqlite_file("postgres://localhost/rstify"), None);
+    }
+
+    #[test]
+    fn unknown_migrations_are_reported() {
+        let applied = vec!["001_users".to_string(), "999_from_the_future".to_string()];
+        assert_eq!(unknown_migrations(&applied), vec!["999_from_the_future"]);
+    }
+
+    #[tokio::test]
+    async fn snapshot_copies_an_online_database() {
+        let dir = std::env::temp_dir().join(format!("rstify-snapshot-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::connect(&format!("sqlite://{}", dir.join("live.db").display()))
+            .await
+            .unwrap();
+        db.migrate().await.unwrap();
+
+        let path = dir.join("copy.db");
+        db.snapshot_to(&path).await.unwrap();
+        let copy = Database::connect(&format!("sqlite://{}", path.display()))
+            .await
+            .unwrap();
+        let applied = copy.applied_migrations().await.unwrap();
+        assert_eq!(applied.len(), SQLITE_MIGRATIONS.len());
+        assert!(unknown_migrations(&applied).is_empty());
+        drop(copy);
+        assert_eq!(inspect_sqlite_file(&path).await.unwrap(), applied);
+
+        std::fs::write(dir.join("garbage.db"), b"not a database").unwrap();
+        assert!(inspect_sqlite_file(&dir.join("garbage.db")).await.is_err());
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn email_quiet_windows_become_quiet_hours() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        migrate_sqlite(&pool).await.unwrap();
+        // Back to the schema before 045, with a window saved in it.
+        for stmt in [
+            "DELETE FROM _migrations WHERE name = '045_email_quiet_hours'",
+            "ALTER TABLE email_preferences ADD COLUMN quiet_start TEXT",
+            "ALTER TABLE email_preferences ADD COLUMN quiet_end TEXT",
+            "ALTER TABLE email_preferences ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC'",
+            "INSERT INTO users (username, password_hash) VALUES ('night', 'x'), ('day', 'x'), \
+             ('off', 'x')",
+            "INSERT INTO email_preferences (user_id, enabled, quiet_start, quiet_end, timezone) \
+             VALUES (1, TRUE, '22:00', '07:00', 'Europe/Berlin'), (2, TRUE, NULL, NULL, 'UTC'), \
+                    (3, FALSE, '12:00', '13:00', 'UTC')",
+        ] {
+            sqlx::query(stmt).execute(&pool).await.unwrap();
+        }
+
+        migrate_sqlite(&pool).await.unwrap();
+        type Schedule = (i64, Option<i64>, bool, String, String, String, Option<i32>);
+        let schedules: Vec<Schedule> = sqlx::query_as(
+            "SELECT user_id, client_id, enabled, start_time, end_time, timezone, \
+                 override_priority FROM quiet_hours ORDER BY user_id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let schedule = |user_id, enabled, start: &str, end: &str, timezone: &str| {
+            (
+                user_id,
+                None,
+                enabled,
+                start.to_string(),
+                end.to_string(),
+                timezone.to_string(),
+                Some(8),
+            )
+        };
+        assert_eq!(
+            schedules,
+            [
+                schedule(1, true, "22:00", "07:00", "Europe/Berlin"),
+                schedule(3, false, "12:00", "13:00", "UTC"),
+            ]
+        );
+        let (enabled,): (bool,) =
+            sqlx::query_as("SELECT enabled FROM email_preferences WHERE user_id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(enabled);
+    }
+
     #[test]
     fn test_split_sql_with_triggers() {
         let sql = r#"
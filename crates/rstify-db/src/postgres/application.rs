@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::Application;
+use rstify_core::repositories::ApplicationRepository;
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgApplicationRepo {
+    pool: PgPool,
+}
+
+impl PgApplicationRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApplicationRepository for PgApplicationRepo {
+    async fn create(
+        &self,
+        user_id: i64,
+        group_id: Option<i64>,
+        name: &str,
+        description: Option<&str>,
+        token: &str,
+        default_priority: i32,
+    ) -> Result<Application, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "INSERT INTO applications (user_id, group_id, name, description, token, default_priority) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(group_id)
+        .bind(name)
+        .bind(description)
+        .bind(token)
+        .bind(default_priority)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("Application '{}' already exists", name))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Application>, CoreError> {
+        sqlx::query_as::<_, Application>("SELECT * FROM applications WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<Option<Application>, CoreError> {
+        sqlx::query_as::<_, Application>("SELECT * FROM applications WHERE token = $1")
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_user_and_name(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> Result<Option<Application>, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE user_id = $1 AND name = $2 ORDER BY id LIMIT 1",
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_by_user(&self, user_id: i64) -> Result<Vec<Application>, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE user_id = $1 ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_accessible(&self, user_id: i64) -> Result<Vec<Application>, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE user_id = $1 \
+             OR group_id IN (SELECT group_id FROM group_members WHERE user_id = $1) \
+             ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        name: Option<&str>,
+        description: Option<&str>,
+        default_priority: Option<i32>,
+        retention_days: Option<Option<i32>>,
+    ) -> Result<Application, CoreError> {
+        let current = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Application {} not found", id)))?;
+
+        let new_name = name.unwrap_or(&current.name);
+        let new_desc = description.or(current.description.as_deref());
+        let new_priority = default_priority.unwrap_or(current.default_priority);
+        let new_retention = retention_days.unwrap_or(current.retention_days);
+
+        sqlx::query_as::<_, Application>(
+            "UPDATE applications SET name = $1, description = $2, default_priority = $3, retention_days = $4, updated_at = utc_now() WHERE id = $5 RETURNING *",
+        )
+        .bind(new_name)
+        .bind(new_desc)
+        .bind(new_priority)
+        .bind(new_retention)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_with_retention(&self) -> Result<Vec<Application>, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE retention_days IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set_group(&self, id: i64, group_id: Option<i64>) -> Result<Application, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "UPDATE applications SET group_id = $1, updated_at = utc_now() WHERE id = $2 RETURNING *",
+        )
+        .bind(group_id)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_image(&self, id: i64, image: Option<&str>) -> Result<Application, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "UPDATE applications SET image = $1, updated_at = utc_now() WHERE id = $2 RETURNING *",
+        )
+        .bind(image)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM applications WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Application {} not found", id)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{AuditEntry, AuditFilter, NewAuditEntry};
+use rstify_core::repositories::AuditRepository;
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgAuditRepo {
+    pool: PgPool,
+}
+
+impl PgAuditRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PgAuditRepo {
+    async fn record(&self, entry: &NewAuditEntry) -> Result<(), CoreError> {
+        sqlx::query(
+            "INSERT INTO audit_log (actor_id, actor_name, action, target_type, target_id, ip, before, after) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(entry.actor_id)
+        .bind(&entry.actor_name)
+        .bind(&entry.action)
+        .bind(&entry.target_type)
+        .bind(&entry.target_id)
+        .bind(&entry.ip)
+        .bind(entry.before.as_ref().map(|v| v.to_string()))
+        .bind(entry.after.as_ref().map(|v| v.to_string()))
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list(&self, filter: &AuditFilter, limit: i64) -> Result<Vec<AuditEntry>, CoreError> {
+        let mut qb =
+            sqlx::QueryBuilder::<sqlx::Postgres>::new("SELECT * FROM audit_log WHERE 1 = 1");
+
+        if let Some(actor_id) = filter.actor_id {
+            qb.push(" AND actor_id = ");
+            qb.push_bind(actor_id);
+        }
+        if let Some(ref action) = filter.action {
+            if action.ends_with('.') {
+                qb.push(" AND substr(action, 1, length(");
+                qb.push_bind(action.clone());
+                qb.push(")) = ");
+                qb.push_bind(action.clone());
+            } else {
+                qb.push(" AND action = ");
+                qb.push_bind(action.clone());
+            }
+        }
+        if let Some(ref target_type) = filter.target_type {
+            qb.push(" AND target_type = ");
+            qb.push_bind(target_type.clone());
+        }
+        if let Some(ref target_id) = filter.target_id {
+            qb.push(" AND target_id = ");
+            qb.push_bind(target_id.clone());
+        }
+        if let Some(ref since) = filter.since {
+            qb.push(" AND created_at >= ");
+            qb.push_bind(since.clone());
+        }
+        if let Some(ref until) = filter.until {
+            qb.push(" AND created_at <= ");
+            qb.push_bind(until.clone());
+        }
+        if let Some(before_id) = filter.before_id {
+            qb.push(" AND id < ");
+            qb.push_bind(before_id);
+        }
+        qb.push(" ORDER BY id DESC LIMIT ");
+        qb.push_bind(limit);
+
+        qb.build_query_as::<AuditEntry>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn purge_older_than(&self, days: i64) -> Result<u64, CoreError> {
+        let result =
+            sqlx::query("DELETE FROM audit_log WHERE created_at < utc_now(-$1 * INTERVAL '1 day')")
+                .bind(days)
+                .execute(&self.pool)
+                .await
+                .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+}
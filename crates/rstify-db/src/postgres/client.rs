@@ -0,0 +1,473 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{Client, PushDelivery, QuietHours, Subscription, SuppressedPush};
+use rstify_core::repositories::{ClientRepository, NewPushDelivery};
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgClientRepo {
+    pool: PgPool,
+}
+
+impl PgClientRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ClientRepository for PgClientRepo {
+    async fn create(
+        &self,
+        user_id: i64,
+        name: &str,
+        token: &str,
+        scopes: &str,
+        expires_at: Option<&str>,
+        allowed_ips: Option<&str>,
+    ) -> Result<Client, CoreError> {
+        sqlx::query_as::<_, Client>(
+            "INSERT INTO clients (user_id, name, token, scopes, expires_at, allowed_ips) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(token)
+        .bind(scopes)
+        .bind(expires_at)
+        .bind(allowed_ips)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("Client '{}' already exists", name))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Client>, CoreError> {
+        sqlx::query_as::<_, Client>("SELECT * FROM clients WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<Option<Client>, CoreError> {
+        sqlx::query_as::<_, Client>("SELECT * FROM clients WHERE token = $1")
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_by_user(&self, user_id: i64) -> Result<Vec<Client>, CoreError> {
+        sqlx::query_as::<_, Client>("SELECT * FROM clients WHERE user_id = $1 ORDER BY id")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        name: Option<&str>,
+        scopes: Option<&str>,
+        expires_at: Option<Option<&str>>,
+        allowed_ips: Option<Option<&str>>,
+    ) -> Result<Client, CoreError> {
+        let current = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Client {} not found", id)))?;
+
+        let new_name = name.unwrap_or(&current.name);
+        let new_scopes = scopes.unwrap_or(&current.scopes);
+        let new_expires_at = expires_at.unwrap_or(current.expires_at.as_deref());
+        let new_allowed_ips = allowed_ips.unwrap_or(current.allowed_ips.as_deref());
+        // A changed expiry re-arms the pre-expiry warning.
+        let expiry_notified_at = if new_expires_at == current.expires_at.as_deref() {
+            current.expiry_notified_at.as_deref()
+        } else {
+            None
+        };
+
+        sqlx::query_as::<_, Client>(
+            "UPDATE clients SET name = $1, scopes = $2, expires_at = $3, allowed_ips = $4, \
+             expiry_notified_at = $5 WHERE id = $6 RETURNING *",
+        )
+        .bind(new_name)
+        .bind(new_scopes)
+        .bind(new_expires_at)
+        .bind(new_allowed_ips)
+        .bind(expiry_notified_at)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn touch_last_used(&self, id: i64, ip: Option<&str>) -> Result<(), CoreError> {
+        // Throttled to one write per minute per address so an active token
+        // doesn't turn every authenticated request into a database write.
+        sqlx::query(
+            "UPDATE clients SET last_used_at = utc_now(), last_used_ip = $1 \
+             WHERE id = $2 AND (last_used_at IS NULL \
+                OR last_used_at < utc_now(INTERVAL '-60 seconds') \
+                OR last_used_ip IS DISTINCT FROM $3)",
+        )
+        .bind(ip)
+        .bind(id)
+        .bind(ip)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn update_fcm_token(
+        &self,
+        id: i64,
+        fcm_token: Option<&str>,
+    ) -> Result<Client, CoreError> {
+        sqlx::query_as::<_, Client>("UPDATE clients SET fcm_token = $1 WHERE id = $2 RETURNING *")
+            .bind(fcm_token)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_fcm_tokens_by_user(&self, user_id: i64) -> Result<Vec<String>, CoreError> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT fcm_token FROM clients WHERE user_id = $1 AND fcm_token IS NOT NULL",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    async fn clear_fcm_token(&self, fcm_token: &str) -> Result<u64, CoreError> {
+        let result = sqlx::query("UPDATE clients SET fcm_token = NULL WHERE fcm_token = $1")
+            .bind(fcm_token)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn update_web_push(
+        &self,
+        id: i64,
+        subscription: Option<(&str, &str, &str)>,
+    ) -> Result<Client, CoreError> {
+        let (endpoint, p256dh, auth) = match subscription {
+            Some((e, p, a)) => (Some(e), Some(p), Some(a)),
+            None => (None, None, None),
+        };
+        sqlx::query_as::<_, Client>(
+            "UPDATE clients SET webpush_endpoint = $1, webpush_p256dh = $2, webpush_auth = $3 \
+             WHERE id = $4 RETURNING *",
+        )
+        .bind(endpoint)
+        .bind(p256dh)
+        .bind(auth)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn clear_web_push_endpoint(&self, endpoint: &str) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            "UPDATE clients SET webpush_endpoint = NULL, webpush_p256dh = NULL, webpush_auth = NULL \
+             WHERE webpush_endpoint = $1",
+        )
+        .bind(endpoint)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM clients WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Client {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn delete_expired(&self) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            "DELETE FROM clients WHERE expires_at IS NOT NULL AND expires_at <= utc_now()",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn list_expiring_unnotified(&self, days: i64) -> Result<Vec<Client>, CoreError> {
+        sqlx::query_as::<_, Client>(
+            "SELECT * FROM clients \
+             WHERE expires_at IS NOT NULL AND expiry_notified_at IS NULL \
+               AND expires_at > utc_now() \
+               AND expires_at <= utc_now($1 * INTERVAL '1 day')",
+        )
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn mark_expiry_notified(&self, id: i64) -> Result<(), CoreError> {
+        sqlx::query("UPDATE clients SET expiry_notified_at = utc_now() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn upsert_subscription(
+        &self,
+        client_id: i64,
+        topic_pattern: &str,
+        notify_policy: Option<&str>,
+        notify_priority_min: Option<i32>,
+    ) -> Result<Subscription, CoreError> {
+        sqlx::query_as::<_, Subscription>(
+            "INSERT INTO subscriptions (client_id, topic_pattern, notify_policy, notify_priority_min) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (client_id, topic_pattern) DO UPDATE SET \
+             notify_policy = excluded.notify_policy, \
+             notify_priority_min = excluded.notify_priority_min \
+             RETURNING *",
+        )
+        .bind(client_id)
+        .bind(topic_pattern)
+        .bind(notify_policy)
+        .bind(notify_priority_min)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_subscriptions(&self, client_id: i64) -> Result<Vec<Subscription>, CoreError> {
+        sqlx::query_as::<_, Subscription>(
+            "SELECT * FROM subscriptions WHERE client_id = $1 ORDER BY topic_pattern",
+        )
+        .bind(client_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete_subscription(&self, client_id: i64, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM subscriptions WHERE id = $1 AND client_id = $2")
+            .bind(id)
+            .bind(client_id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!(
+                "Subscription {} not found",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn list_push_subscriptions(
+        &self,
+        topic: &str,
+    ) -> Result<Vec<(Subscription, Client)>, CoreError> {
+        const MATCHING: &str = "FROM subscriptions s JOIN clients c ON c.id = s.client_id \
+             WHERE (c.fcm_token IS NOT NULL OR c.webpush_endpoint IS NOT NULL) \
+             AND (s.topic_pattern = $1 OR s.topic_pattern LIKE '%*%')";
+        let subscriptions =
+            sqlx::query_as::<_, Subscription>(&format!("SELECT s.* {} ORDER BY s.id", MATCHING))
+                .bind(topic)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(crate::map_sqlx_err)?;
+        let clients: std::collections::HashMap<i64, Client> =
+            sqlx::query_as::<_, Client>(&format!(
+                "SELECT * FROM clients WHERE id IN (SELECT s.client_id {})",
+                MATCHING
+            ))
+            .bind(topic)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?
+            .into_iter()
+            .map(|c| (c.id, c))
+            .collect();
+        Ok(subscriptions
+            .into_iter()
+            .filter_map(|s| clients.get(&s.client_id).map(|c| (s, c.clone())))
+            .collect())
+    }
+
+    async fn record_push_delivery(&self, delivery: &NewPushDelivery<'_>) -> Result<(), CoreError> {
+        let column = if delivery.channel == "webpush" {
+            "webpush_endpoint"
+        } else {
+            "fcm_token"
+        };
+        sqlx::query(&format!(
+            "INSERT INTO push_delivery_log \
+             (client_id, channel, message_id, outcome, status_code, error_code, error, attempts) \
+             SELECT id, $1, $2, $3, $4, $5, $6, $7 FROM clients WHERE {} = $8",
+            column
+        ))
+        .bind(delivery.channel)
+        .bind(delivery.message_id)
+        .bind(delivery.outcome)
+        .bind(delivery.status_code)
+        .bind(delivery.error_code)
+        .bind(delivery.error)
+        .bind(delivery.attempts)
+        .bind(delivery.token)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list_push_deliveries(
+        &self,
+        client_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<PushDelivery>, CoreError> {
+        sqlx::query_as::<_, PushDelivery>(
+            "SELECT * FROM push_delivery_log WHERE client_id = $1 \
+             ORDER BY attempted_at DESC, id DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(client_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn purge_push_deliveries_older_than(&self, days: i64) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            "DELETE FROM push_delivery_log WHERE attempted_at < utc_now(-$1 * INTERVAL '1 day')",
+        )
+        .bind(days)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn list_quiet_hours(&self, user_id: i64) -> Result<Vec<QuietHours>, CoreError> {
+        sqlx::query_as::<_, QuietHours>("SELECT * FROM quiet_hours WHERE user_id = $1 ORDER BY id")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_quiet_hours(&self, id: i64) -> Result<Option<QuietHours>, CoreError> {
+        sqlx::query_as::<_, QuietHours>("SELECT * FROM quiet_hours WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn save_quiet_hours(&self, schedule: &QuietHours) -> Result<QuietHours, CoreError> {
+        let query = if schedule.id == 0 {
+            sqlx::query_as::<_, QuietHours>(
+                "INSERT INTO quiet_hours \
+                 (user_id, client_id, enabled, start_time, end_time, days, timezone, override_priority) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            )
+        } else {
+            sqlx::query_as::<_, QuietHours>(
+                "UPDATE quiet_hours SET user_id = $1, client_id = $2, enabled = $3, start_time = $4, \
+                 end_time = $5, days = $6, timezone = $7, override_priority = $8, \
+                 updated_at = utc_now() WHERE id = $9 RETURNING *",
+            )
+        };
+        let mut query = query
+            .bind(schedule.user_id)
+            .bind(schedule.client_id)
+            .bind(schedule.enabled)
+            .bind(&schedule.start_time)
+            .bind(&schedule.end_time)
+            .bind(&schedule.days)
+            .bind(&schedule.timezone)
+            .bind(schedule.override_priority);
+        if schedule.id != 0 {
+            query = query.bind(schedule.id);
+        }
+        query
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?
+            .ok_or_else(|| CoreError::NotFound(format!("Quiet hours {} not found", schedule.id)))
+    }
+
+    async fn delete_quiet_hours(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM quiet_hours WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Quiet hours {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn record_suppressed_push(
+        &self,
+        client_id: i64,
+        message_id: i64,
+    ) -> Result<(), CoreError> {
+        sqlx::query("INSERT INTO suppressed_pushes (client_id, message_id) VALUES ($1, $2)")
+            .bind(client_id)
+            .bind(message_id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list_suppressed_pushes(&self) -> Result<Vec<SuppressedPush>, CoreError> {
+        sqlx::query_as::<_, SuppressedPush>("SELECT * FROM suppressed_pushes ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete_suppressed_pushes(
+        &self,
+        client_id: i64,
+        up_to_id: i64,
+    ) -> Result<u64, CoreError> {
+        let result = sqlx::query("DELETE FROM suppressed_pushes WHERE client_id = $1 AND id <= $2")
+            .bind(client_id)
+            .bind(up_to_id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+}
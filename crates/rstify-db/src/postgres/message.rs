@@ -0,0 +1,865 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    Attachment, Message, MessageEscalation, WebhookConfig, ACK_EXTRA, ESCALATION_EXTRA,
+};
+use rstify_core::repositories::{MessageRepository, NewMessage};
+use sqlx::PgPool;
+
+/// A message's `extras` as a JSON object; anything else counts as empty.
+const EXTRAS_OBJECT: &str = "(CASE WHEN jsonb_typeof(extras::jsonb) = 'object' \
+     THEN extras::jsonb ELSE '{}'::jsonb END)";
+
+#[derive(Clone)]
+pub struct PgMessageRepo {
+    pool: PgPool,
+}
+
+impl PgMessageRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MessageRepository for PgMessageRepo {
+    async fn create(&self, msg: NewMessage<'_>) -> Result<Message, CoreError> {
+        sqlx::query_as::<_, Message>(
+            r#"INSERT INTO messages
+                (application_id, topic_id, user_id, title, message, priority, tags, click_url, icon_url, actions, extras, content_type, scheduled_for, source, inbox, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, COALESCE($16, utc_now()))
+                RETURNING *"#,
+        )
+        .bind(msg.application_id)
+        .bind(msg.topic_id)
+        .bind(msg.user_id)
+        .bind(msg.title)
+        .bind(msg.message)
+        .bind(msg.priority)
+        .bind(msg.tags)
+        .bind(msg.click_url)
+        .bind(msg.icon_url)
+        .bind(msg.actions)
+        .bind(msg.extras)
+        .bind(msg.content_type)
+        .bind(msg.scheduled_for)
+        .bind(msg.source)
+        .bind(msg.inbox)
+        .bind(msg.created_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Message>, CoreError> {
+        sqlx::query_as::<_, Message>("SELECT * FROM messages WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_by_application(
+        &self,
+        app_id: i64,
+        limit: i64,
+        since: i64,
+    ) -> Result<Vec<Message>, CoreError> {
+        sqlx::query_as::<_, Message>(
+            "SELECT * FROM messages WHERE application_id = $1 AND id > $2 ORDER BY id DESC LIMIT $3",
+        )
+        .bind(app_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_by_user_apps(
+        &self,
+        user_id: i64,
+        limit: i64,
+        since: i64,
+        inbox: Option<bool>,
+    ) -> Result<Vec<Message>, CoreError> {
+        let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            r#"SELECT m.* FROM messages m
+               JOIN applications a ON m.application_id = a.id
+               WHERE a.user_id = "#,
+        );
+        qb.push_bind(user_id);
+        qb.push(" AND m.id > ");
+        qb.push_bind(since);
+        if let Some(true) = inbox {
+            qb.push(" AND m.inbox");
+        } else if let Some(false) = inbox {
+            qb.push(" AND NOT m.inbox");
+        }
+        qb.push(" ORDER BY m.id DESC LIMIT ");
+        qb.push_bind(limit);
+
+        qb.build_query_as::<Message>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_inbox(
+        &self,
+        user_id: i64,
+        topic_ids: &[i64],
+        limit: i64,
+        since: i64,
+        inbox: Option<bool>,
+    ) -> Result<Vec<Message>, CoreError> {
+        let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            r#"SELECT * FROM (
+                 SELECT m.* FROM messages m
+                 JOIN applications a ON m.application_id = a.id
+                 WHERE a.user_id = "#,
+        );
+        qb.push_bind(user_id);
+        if !topic_ids.is_empty() {
+            qb.push(" UNION ALL SELECT m.* FROM messages m WHERE m.topic_id IN (");
+            let mut sep = qb.separated(", ");
+            for id in topic_ids {
+                sep.push_bind(*id);
+            }
+            qb.push(")");
+        }
+        qb.push(") m WHERE id > ");
+        qb.push_bind(since);
+        match inbox {
+            Some(true) => {
+                qb.push(" AND inbox");
+            }
+            Some(false) => {
+                qb.push(" AND NOT inbox");
+            }
+            None => {}
+        }
+        qb.push(" ORDER BY id DESC LIMIT ");
+        qb.push_bind(limit);
+
+        qb.build_query_as::<Message>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_by_topic(
+        &self,
+        topic_id: i64,
+        limit: i64,
+        since: i64,
+    ) -> Result<Vec<Message>, CoreError> {
+        sqlx::query_as::<_, Message>(
+            "SELECT * FROM messages WHERE topic_id = $1 AND id > $2 ORDER BY id DESC LIMIT $3",
+        )
+        .bind(topic_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        title: Option<&str>,
+        message: Option<&str>,
+        priority: Option<i32>,
+        extras: Option<&str>,
+    ) -> Result<Message, CoreError> {
+        let current = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Message {} not found", id)))?;
+
+        let new_title = title.or(current.title.as_deref());
+        let new_message = message.unwrap_or(&current.message);
+        let new_priority = priority.unwrap_or(current.priority);
+        let new_extras = extras.map(|s| s.to_string()).or(current.extras);
+
+        sqlx::query_as::<_, Message>(
+            "UPDATE messages SET title = $1, message = $2, priority = $3, extras = $4 WHERE id = $5 RETURNING *",
+        )
+        .bind(new_title)
+        .bind(new_message)
+        .bind(new_priority)
+        .bind(&new_extras)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn search(
+        &self,
+        user_id: i64,
+        query: Option<&str>,
+        tag: Option<&str>,
+        priority_min: Option<i32>,
+        priority_max: Option<i32>,
+        since: Option<&str>,
+        until: Option<&str>,
+        app_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Message>, CoreError> {
+        let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "SELECT m.* FROM messages m WHERE (m.application_id IN (SELECT id FROM applications WHERE user_id = ",
+        );
+        qb.push_bind(user_id);
+        qb.push(") OR m.user_id = ");
+        qb.push_bind(user_id);
+        qb.push(")");
+
+        if let Some(q) = query {
+            // plainto_tsquery treats the input as plain words (no operator
+            // syntax), so malformed queries can't error.
+            if !q.trim().is_empty() {
+                qb.push(" AND m.search_vector @@ plainto_tsquery('simple', ");
+                qb.push_bind(q.to_string());
+                qb.push(")");
+            }
+        }
+        if let Some(t) = tag {
+            // ILIKE keeps SQLite's case-insensitive LIKE semantics.
+            qb.push(" AND m.tags ILIKE ");
+            qb.push_bind(format!("%{t}%"));
+        }
+        if let Some(pmin) = priority_min {
+            qb.push(" AND m.priority >= ");
+            qb.push_bind(pmin);
+        }
+        if let Some(pmax) = priority_max {
+            qb.push(" AND m.priority <= ");
+            qb.push_bind(pmax);
+        }
+        if let Some(s) = since {
+            qb.push(" AND m.created_at >= ");
+            qb.push_bind(s.to_string());
+        }
+        if let Some(u) = until {
+            qb.push(" AND m.created_at <= ");
+            qb.push_bind(u.to_string());
+        }
+        if let Some(aid) = app_id {
+            qb.push(" AND m.application_id = ");
+            qb.push_bind(aid);
+        }
+        qb.push(" ORDER BY m.created_at DESC LIMIT ");
+        qb.push_bind(limit);
+
+        qb.build_query_as::<Message>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete_batch(&self, ids: &[i64], user_id: i64) -> Result<u64, CoreError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let result = sqlx::query(
+            "DELETE FROM messages WHERE id = ANY($1) AND (application_id IN (SELECT id FROM applications WHERE user_id = $2) OR user_id = $2)",
+        )
+        .bind(ids)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_by_id(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM messages WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Message {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn delete_all_for_user(&self, user_id: i64) -> Result<(), CoreError> {
+        // Delete messages from user's applications AND messages posted by user to topics
+        sqlx::query(
+            r#"DELETE FROM messages WHERE
+                application_id IN (SELECT id FROM applications WHERE user_id = $1)
+                OR (user_id = $1 AND topic_id IS NOT NULL)"#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn delete_all_for_application(&self, app_id: i64) -> Result<(), CoreError> {
+        sqlx::query("DELETE FROM messages WHERE application_id = $1")
+            .bind(app_id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list_scheduled_due(&self) -> Result<Vec<Message>, CoreError> {
+        sqlx::query_as::<_, Message>(
+            "SELECT * FROM messages WHERE scheduled_for IS NOT NULL AND scheduled_for <= utc_now() AND delivered_at IS NULL",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn count(&self) -> Result<i64, CoreError> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM messages")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(count)
+    }
+
+    async fn count_since(&self, since: &str) -> Result<i64, CoreError> {
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM messages WHERE created_at >= $1")
+                .bind(since)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(crate::map_sqlx_err)?;
+        Ok(count)
+    }
+
+    async fn set_expires_at(&self, id: i64, expires_at: &str) -> Result<(), CoreError> {
+        sqlx::query("UPDATE messages SET expires_at = $1 WHERE id = $2")
+            .bind(expires_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn delete_expired_messages(&self) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            "DELETE FROM messages WHERE expires_at IS NOT NULL AND expires_at <= utc_now()",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_past_retention(&self) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            r#"DELETE FROM messages m USING applications a
+               WHERE m.application_id = a.id
+                 AND a.retention_days IS NOT NULL
+                 AND m.created_at < utc_now(-a.retention_days * INTERVAL '1 day')"#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn claim_scheduled_due(&self) -> Result<Vec<Message>, CoreError> {
+        // The delivered_at condition is re-checked on rows locked by a
+        // concurrent claim, so each message is returned to exactly one caller.
+        sqlx::query_as::<_, Message>(
+            "UPDATE messages SET delivered_at = utc_now() \
+             WHERE scheduled_for IS NOT NULL \
+               AND scheduled_for <= utc_now() \
+               AND delivered_at IS NULL \
+             RETURNING *",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn mark_delivered(&self, id: i64) -> Result<(), CoreError> {
+        sqlx::query("UPDATE messages SET delivered_at = utc_now() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    // Attachments
+    async fn create_attachment(
+        &self,
+        message_id: i64,
+        filename: &str,
+        content_type: Option<&str>,
+        size_bytes: i64,
+        storage_type: &str,
+        storage_path: &str,
+        sha256: Option<&str>,
+        expires_at: Option<&str>,
+    ) -> Result<Attachment, CoreError> {
+        sqlx::query_as::<_, Attachment>(
+            "INSERT INTO attachments (message_id, filename, content_type, size_bytes, storage_type, storage_path, sha256, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+        )
+        .bind(message_id)
+        .bind(filename)
+        .bind(content_type)
+        .bind(size_bytes)
+        .bind(storage_type)
+        .bind(storage_path)
+        .bind(sha256)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_attachments_by_message(
+        &self,
+        message_id: i64,
+    ) -> Result<Vec<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>("SELECT * FROM attachments WHERE message_id = $1")
+            .bind(message_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_attachments_by_messages(
+        &self,
+        message_ids: &[i64],
+    ) -> Result<Vec<Attachment>, CoreError> {
+        if message_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        sqlx::query_as::<_, Attachment>("SELECT * FROM attachments WHERE message_id = ANY($1)")
+            .bind(message_ids)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_attachment(&self, id: i64) -> Result<Option<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>("SELECT * FROM attachments WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_expired_attachments(&self) -> Result<Vec<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE expires_at IS NOT NULL AND expires_at <= utc_now()",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_attachments_by_storage_type(
+        &self,
+        storage_type: &str,
+    ) -> Result<Vec<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE storage_type = $1 ORDER BY id",
+        )
+        .bind(storage_type)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_attachment_storage(
+        &self,
+        id: i64,
+        storage_type: &str,
+        storage_path: &str,
+    ) -> Result<(), CoreError> {
+        sqlx::query("UPDATE attachments SET storage_type = $1, storage_path = $2 WHERE id = $3")
+            .bind(storage_type)
+            .bind(storage_path)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn delete_attachment(&self, id: i64) -> Result<(), CoreError> {
+        sqlx::query("DELETE FROM attachments WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list_pending_thumbnails(&self, limit: i64) -> Result<Vec<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE thumbnail_status = 'pending' ORDER BY id LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_attachment_thumbnail(
+        &self,
+        id: i64,
+        status: &str,
+        width: Option<i64>,
+        height: Option<i64>,
+        thumbnail_path: Option<&str>,
+    ) -> Result<(), CoreError> {
+        sqlx::query(
+            "UPDATE attachments SET thumbnail_status = $1, width = $2, height = $3, thumbnail_path = $4 WHERE id = $5",
+        )
+        .bind(status)
+        .bind(width)
+        .bind(height)
+        .bind(thumbnail_path)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn count_attachment_references(
+        &self,
+        storage_type: &str,
+        storage_path: &str,
+    ) -> Result<i64, CoreError> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM attachments WHERE storage_type = $1 AND storage_path = $2",
+        )
+        .bind(storage_type)
+        .bind(storage_path)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    // Webhook configs
+    async fn create_webhook_config(
+        &self,
+        user_id: i64,
+        name: &str,
+        token: &str,
+        webhook_type: &str,
+        target_topic_id: Option<i64>,
+        target_application_id: Option<i64>,
+        template: &str,
+        enabled: bool,
+        direction: &str,
+        target_url: Option<&str>,
+        http_method: Option<&str>,
+        headers: Option<&str>,
+        body_template: Option<&str>,
+        max_retries: Option<i32>,
+        retry_delay_secs: Option<i32>,
+        timeout_secs: Option<i32>,
+        follow_redirects: Option<bool>,
+        group_name: Option<&str>,
+        secret: Option<&str>,
+        notify_url: Option<&str>,
+    ) -> Result<WebhookConfig, CoreError> {
+        sqlx::query_as::<_, WebhookConfig>(
+            "INSERT INTO webhook_configs (user_id, name, token, webhook_type, target_topic_id, target_application_id, template, enabled, direction, target_url, http_method, headers, body_template, max_retries, retry_delay_secs, timeout_secs, follow_redirects, group_name, secret, notify_url) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(token)
+        .bind(webhook_type)
+        .bind(target_topic_id)
+        .bind(target_application_id)
+        .bind(template)
+        .bind(enabled)
+        .bind(direction)
+        .bind(target_url)
+        .bind(http_method.unwrap_or("POST"))
+        .bind(headers)
+        .bind(body_template)
+        .bind(max_retries.unwrap_or(3))
+        .bind(retry_delay_secs.unwrap_or(60))
+        .bind(timeout_secs.unwrap_or(15))
+        .bind(follow_redirects.unwrap_or(true))
+        .bind(group_name)
+        .bind(secret)
+        .bind(notify_url)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_webhook_config_by_id(&self, id: i64) -> Result<Option<WebhookConfig>, CoreError> {
+        sqlx::query_as::<_, WebhookConfig>("SELECT * FROM webhook_configs WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_webhook_config_by_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<WebhookConfig>, CoreError> {
+        sqlx::query_as::<_, WebhookConfig>("SELECT * FROM webhook_configs WHERE token = $1")
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_webhook_configs_by_user(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<WebhookConfig>, CoreError> {
+        sqlx::query_as::<_, WebhookConfig>(
+            "SELECT * FROM webhook_configs WHERE user_id = $1 ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_webhook_config(
+        &self,
+        id: i64,
+        name: Option<&str>,
+        template: Option<&str>,
+        enabled: Option<bool>,
+        target_url: Option<&str>,
+        http_method: Option<&str>,
+        headers: Option<&str>,
+        body_template: Option<&str>,
+        max_retries: Option<i32>,
+        retry_delay_secs: Option<i32>,
+        timeout_secs: Option<i32>,
+        follow_redirects: Option<bool>,
+        group_name: Option<&str>,
+        secret: Option<&str>,
+        notify_url: Option<&str>,
+    ) -> Result<WebhookConfig, CoreError> {
+        let current = self
+            .find_webhook_config_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Webhook config {} not found", id)))?;
+
+        let new_name = name.unwrap_or(&current.name);
+        let new_template = template.unwrap_or(&current.template);
+        let new_enabled = enabled.unwrap_or(current.enabled);
+        let new_http_method = http_method.unwrap_or(&current.http_method);
+        let new_max_retries = max_retries.unwrap_or(current.max_retries);
+        let new_retry_delay_secs = retry_delay_secs.unwrap_or(current.retry_delay_secs);
+        let new_timeout_secs = timeout_secs.unwrap_or(current.timeout_secs);
+        let new_follow_redirects = follow_redirects.unwrap_or(current.follow_redirects);
+        let new_group_name = group_name.map(|s| s.to_string()).or(current.group_name);
+        // For Option<String> fields, use provided value or fall back to current
+        let new_target_url = target_url.map(|s| s.to_string()).or(current.target_url);
+        let new_headers = headers.map(|s| s.to_string()).or(current.headers);
+        let new_body_template = body_template
+            .map(|s| s.to_string())
+            .or(current.body_template);
+        let new_secret = secret.map(|s| s.to_string()).or(current.secret);
+        // An empty notify URL clears it.
+        let new_notify_url = notify_url
+            .map(|s| s.to_string())
+            .or(current.notify_url)
+            .filter(|u| !u.is_empty());
+
+        sqlx::query_as::<_, WebhookConfig>(
+            "UPDATE webhook_configs SET name = $1, template = $2, enabled = $3, target_url = $4, http_method = $5, headers = $6, body_template = $7, max_retries = $8, retry_delay_secs = $9, timeout_secs = $10, follow_redirects = $11, group_name = $12, secret = $13, notify_url = $14 WHERE id = $15 RETURNING *",
+        )
+        .bind(new_name)
+        .bind(new_template)
+        .bind(new_enabled)
+        .bind(&new_target_url)
+        .bind(new_http_method)
+        .bind(&new_headers)
+        .bind(&new_body_template)
+        .bind(new_max_retries)
+        .bind(new_retry_delay_secs)
+        .bind(new_timeout_secs)
+        .bind(new_follow_redirects)
+        .bind(&new_group_name)
+        .bind(&new_secret)
+        .bind(&new_notify_url)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_outgoing_webhooks_for_topic(
+        &self,
+        topic_name: &str,
+    ) -> Result<Vec<WebhookConfig>, CoreError> {
+        sqlx::query_as::<_, WebhookConfig>(
+            r#"SELECT wc.* FROM webhook_configs wc
+               JOIN topics t ON wc.target_topic_id = t.id
+               WHERE wc.direction = 'outgoing'
+                 AND wc.enabled
+                 AND t.name = $1"#,
+        )
+        .bind(topic_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete_webhook_config(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM webhook_configs WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!(
+                "Webhook config {} not found",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn regenerate_webhook_token(
+        &self,
+        id: i64,
+        new_token: &str,
+    ) -> Result<WebhookConfig, CoreError> {
+        let result = sqlx::query("UPDATE webhook_configs SET token = $1 WHERE id = $2")
+            .bind(new_token)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!(
+                "Webhook config {} not found",
+                id
+            )));
+        }
+        self.find_webhook_config_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Webhook config {} not found", id)))
+    }
+
+    async fn start_escalation(
+        &self,
+        message_id: i64,
+        topic_id: i64,
+        policy: &str,
+        next_at: &str,
+    ) -> Result<(), CoreError> {
+        sqlx::query(
+            "INSERT INTO message_escalations (message_id, topic_id, policy, next_at) \
+             VALUES ($1, $2, $3, $4) ON CONFLICT (message_id) DO NOTHING",
+        )
+        .bind(message_id)
+        .bind(topic_id)
+        .bind(policy)
+        .bind(next_at)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn find_escalation(
+        &self,
+        message_id: i64,
+    ) -> Result<Option<MessageEscalation>, CoreError> {
+        sqlx::query_as::<_, MessageEscalation>(
+            "SELECT * FROM message_escalations WHERE message_id = $1",
+        )
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_due_escalations(&self, now: &str) -> Result<Vec<MessageEscalation>, CoreError> {
+        sqlx::query_as::<_, MessageEscalation>(
+            "SELECT * FROM message_escalations \
+             WHERE next_at IS NOT NULL AND next_at <= $1 \
+             ORDER BY next_at",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn claim_escalation_step(
+        &self,
+        message_id: i64,
+        step: i32,
+        next_at: Option<&str>,
+    ) -> Result<bool, CoreError> {
+        let result = sqlx::query(
+            "UPDATE message_escalations SET next_step = $1, next_at = $2 \
+             WHERE message_id = $3 AND next_step = $4 AND next_at IS NOT NULL",
+        )
+        .bind(step + 1)
+        .bind(next_at)
+        .bind(message_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn stop_escalation(&self, message_id: i64) -> Result<bool, CoreError> {
+        let result = sqlx::query(
+            "UPDATE message_escalations SET next_at = NULL \
+             WHERE message_id = $1 AND next_at IS NOT NULL",
+        )
+        .bind(message_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_escalation_step(&self, message_id: i64, step: &str) -> Result<(), CoreError> {
+        sqlx::query(&format!(
+            "UPDATE messages SET extras = jsonb_set({extras}, '{{{key}}}', jsonb_build_object('steps', \
+             COALESCE({extras} #> '{{{key},steps}}', '[]'::jsonb) || jsonb_build_array($1::jsonb)))::text \
+             WHERE id = $2",
+            extras = EXTRAS_OBJECT,
+            key = ESCALATION_EXTRA,
+        ))
+        .bind(step)
+        .bind(message_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn record_acknowledgement(&self, message_id: i64, ack: &str) -> Result<bool, CoreError> {
+        let result = sqlx::query(&format!(
+            "UPDATE messages SET extras = jsonb_set({extras}, '{{{key}}}', $1::jsonb)::text \
+             WHERE id = $2 AND {extras} -> '{key}' IS NULL",
+            extras = EXTRAS_OBJECT,
+            key = ACK_EXTRA,
+        ))
+        .bind(ack)
+        .bind(message_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+}
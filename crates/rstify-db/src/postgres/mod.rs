@@ -0,0 +1,144 @@
+//! PostgreSQL implementations of the repository traits, selected by a
+//! `postgres://` database URL (see [`crate::pool::Database`]).
+
+pub mod application;
+pub mod audit;
+pub mod client;
+pub mod email;
+pub mod group;
+pub mod message;
+pub mod settings;
+pub mod topic;
+pub mod user;
+pub mod webhook_delivery;
+pub mod webhook_variable;
+
+pub use application::PgApplicationRepo;
+pub use audit::PgAuditRepo;
+pub use client::PgClientRepo;
+pub use email::PgEmailRepo;
+pub use group::PgGroupRepo;
+pub use message::PgMessageRepo;
+pub use settings::PgSettingsRepo;
+pub use topic::PgTopicRepo;
+pub use user::PgUserRepo;
+pub use webhook_delivery::PgWebhookDeliveryRepo;
+pub use webhook_variable::PgWebhookVariableRepo;
+
+use rstify_core::repositories::Repositories;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::info;
+
+/// PostgreSQL has its own migration set. It starts from a consolidated schema
+/// equivalent to the SQLite migrations up to the point it was introduced.
+const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "001_initial",
+        include_str!("../../../../migrations/postgres/001_initial.sql"),
+    ),
+    (
+        "002_attachment_checksums",
+        include_str!("../../../../migrations/postgres/002_attachment_checksums.sql"),
+    ),
+    (
+        "003_attachment_thumbnails",
+        include_str!("../../../../migrations/postgres/003_attachment_thumbnails.sql"),
+    ),
+    (
+        "004_subscriptions",
+        include_str!("../../../../migrations/postgres/004_subscriptions.sql"),
+    ),
+    (
+        "005_push_delivery_log",
+        include_str!("../../../../migrations/postgres/005_push_delivery_log.sql"),
+    ),
+    (
+        "006_web_push",
+        include_str!("../../../../migrations/postgres/006_web_push.sql"),
+    ),
+    (
+        "007_email_preferences",
+        include_str!("../../../../migrations/postgres/007_email_preferences.sql"),
+    ),
+    (
+        "008_email_queue",
+        include_str!("../../../../migrations/postgres/008_email_queue.sql"),
+    ),
+    (
+        "009_webhook_notify_url",
+        include_str!("../../../../migrations/postgres/009_webhook_notify_url.sql"),
+    ),
+    (
+        "010_quiet_hours",
+        include_str!("../../../../migrations/postgres/010_quiet_hours.sql"),
+    ),
+    (
+        "011_escalations",
+        include_str!("../../../../migrations/postgres/011_escalations.sql"),
+    ),
+    (
+        "012_email_quiet_hours",
+        include_str!("../../../../migrations/postgres/012_email_quiet_hours.sql"),
+    ),
+    (
+        "013_escalation_acks",
+        include_str!("../../../../migrations/postgres/013_escalation_acks.sql"),
+    ),
+];
+
+pub(crate) async fn migrate(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Serialize concurrent server starts against the same database.
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('rstify_migrations'))")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS _migrations (
+            name TEXT PRIMARY KEY,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for (name, sql) in MIGRATIONS {
+        let applied: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM _migrations WHERE name = $1)")
+                .bind(name)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        if !applied {
+            // raw_sql runs the whole multi-statement file in one round trip, so
+            // dollar-quoted function bodies need no statement splitting.
+            sqlx::raw_sql(sql).execute(&mut *tx).await?;
+            sqlx::query("INSERT INTO _migrations (name) VALUES ($1)")
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+            info!("Applied migration: {}", name);
+        }
+    }
+
+    tx.commit().await?;
+    info!("Database migrations applied successfully");
+    Ok(())
+}
+
+pub(crate) fn repositories(pool: &PgPool) -> Repositories {
+    Repositories {
+        users: Arc::new(PgUserRepo::new(pool.clone())),
+        applications: Arc::new(PgApplicationRepo::new(pool.clone())),
+        clients: Arc::new(PgClientRepo::new(pool.clone())),
+        topics: Arc::new(PgTopicRepo::new(pool.clone())),
+        groups: Arc::new(PgGroupRepo::new(pool.clone())),
+        messages: Arc::new(PgMessageRepo::new(pool.clone())),
+        webhook_variables: Arc::new(PgWebhookVariableRepo::new(pool.clone())),
+        webhook_deliveries: Arc::new(PgWebhookDeliveryRepo::new(pool.clone())),
+        audit: Arc::new(PgAuditRepo::new(pool.clone())),
+        settings: Arc::new(PgSettingsRepo::new(pool.clone())),
+        emails: Arc::new(PgEmailRepo::new(pool.clone())),
+    }
+}
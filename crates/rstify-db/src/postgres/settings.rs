@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::repositories::SettingsRepository;
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgSettingsRepo {
+    pool: PgPool,
+}
+
+impl PgSettingsRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for PgSettingsRepo {
+    async fn list(&self) -> Result<Vec<(String, String)>, CoreError> {
+        sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, CoreError> {
+        sqlx::query_scalar("SELECT value FROM settings WHERE key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), CoreError> {
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES ($1, $2) \
+             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+}
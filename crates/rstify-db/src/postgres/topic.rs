@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{Topic, TopicPermission};
+use rstify_core::repositories::TopicRepository;
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgTopicRepo {
+    pool: PgPool,
+}
+
+impl PgTopicRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TopicRepository for PgTopicRepo {
+    async fn create(
+        &self,
+        name: &str,
+        owner_id: Option<i64>,
+        group_id: Option<i64>,
+        description: Option<&str>,
+        everyone_read: bool,
+        everyone_write: bool,
+    ) -> Result<Topic, CoreError> {
+        sqlx::query_as::<_, Topic>(
+            "INSERT INTO topics (name, owner_id, group_id, description, everyone_read, everyone_write) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        )
+        .bind(name)
+        .bind(owner_id)
+        .bind(group_id)
+        .bind(description)
+        .bind(everyone_read)
+        .bind(everyone_write)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("Topic '{}' already exists", name))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Topic>, CoreError> {
+        sqlx::query_as::<_, Topic>("SELECT * FROM topics WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Topic>, CoreError> {
+        sqlx::query_as::<_, Topic>("SELECT * FROM topics WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_all(&self) -> Result<Vec<Topic>, CoreError> {
+        sqlx::query_as::<_, Topic>("SELECT * FROM topics ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_visible(&self, user_id: i64) -> Result<Vec<Topic>, CoreError> {
+        sqlx::query_as::<_, Topic>(
+            r#"SELECT DISTINCT t.* FROM topics t
+            LEFT JOIN topic_permissions tp ON tp.can_read AND (
+                tp.user_id = $1
+                OR tp.group_id IN (SELECT group_id FROM group_members WHERE user_id = $1)
+            )
+            WHERE t.everyone_read
+               OR t.owner_id = $1
+               OR t.group_id IN (SELECT group_id FROM group_members WHERE user_id = $1)
+               OR (tp.id IS NOT NULL AND (
+                   t.name = tp.topic_pattern
+                   OR t.name LIKE REPLACE(REPLACE(tp.topic_pattern, '*', '%'), '?', '_')
+               ))
+            ORDER BY t.id"#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        description: Option<&str>,
+        everyone_read: Option<bool>,
+        everyone_write: Option<bool>,
+        notify_policy: Option<&str>,
+        notify_priority_min: Option<i32>,
+        notify_condition: Option<&str>,
+        notify_digest_interval: Option<i32>,
+        store_policy: Option<&str>,
+        store_interval: Option<i32>,
+        inbox_override: Option<&str>,
+        inbox_priority_min: Option<i32>,
+    ) -> Result<Topic, CoreError> {
+        let current = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Topic {} not found", id)))?;
+
+        let new_desc = description.or(current.description.as_deref());
+        let new_read = everyone_read.unwrap_or(current.everyone_read);
+        let new_write = everyone_write.unwrap_or(current.everyone_write);
+        let new_notify_policy = notify_policy.unwrap_or(&current.notify_policy);
+        let new_notify_priority_min = notify_priority_min.or(current.notify_priority_min);
+        let new_notify_condition = notify_condition
+            .map(|s| s.to_string())
+            .or(current.notify_condition);
+        let new_notify_digest_interval = notify_digest_interval.or(current.notify_digest_interval);
+        let new_store_policy = store_policy.unwrap_or(&current.store_policy);
+        let new_store_interval = store_interval.or(current.store_interval);
+        // None = keep current; Some("") = clear back to the global threshold.
+        let new_inbox_override = match inbox_override {
+            Some("") => None,
+            Some(s) => Some(s.to_string()),
+            None => current.inbox_override,
+        };
+        let new_inbox_priority_min = match inbox_override {
+            Some("") => None,
+            _ => inbox_priority_min.or(current.inbox_priority_min),
+        };
+
+        sqlx::query_as::<_, Topic>(
+            "UPDATE topics SET description = $1, everyone_read = $2, everyone_write = $3, \
+             notify_policy = $4, notify_priority_min = $5, notify_condition = $6, \
+             notify_digest_interval = $7, store_policy = $8, store_interval = $9, \
+             inbox_override = $10, inbox_priority_min = $11 \
+             WHERE id = $12 RETURNING *",
+        )
+        .bind(new_desc)
+        .bind(new_read)
+        .bind(new_write)
+        .bind(new_notify_policy)
+        .bind(new_notify_priority_min)
+        .bind(new_notify_condition)
+        .bind(new_notify_digest_interval)
+        .bind(new_store_policy)
+        .bind(new_store_interval)
+        .bind(new_inbox_override)
+        .bind(new_inbox_priority_min)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set_group(&self, id: i64, group_id: Option<i64>) -> Result<Topic, CoreError> {
+        sqlx::query_as::<_, Topic>("UPDATE topics SET group_id = $1 WHERE id = $2 RETURNING *")
+            .bind(group_id)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set_escalation_policy(
+        &self,
+        id: i64,
+        policy: Option<&str>,
+    ) -> Result<Topic, CoreError> {
+        sqlx::query_as::<_, Topic>(
+            "UPDATE topics SET escalation_policy = $1 WHERE id = $2 RETURNING *",
+        )
+        .bind(policy)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?
+        .ok_or_else(|| CoreError::NotFound(format!("Topic {} not found", id)))
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), CoreError> {
+        // Nullify webhook references before deleting (FK has no ON DELETE SET NULL)
+        sqlx::query("UPDATE webhook_configs SET target_topic_id = NULL WHERE target_topic_id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+
+        let result = sqlx::query("DELETE FROM topics WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Topic {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<i64, CoreError> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM topics")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(count)
+    }
+
+    async fn create_permission(
+        &self,
+        user_id: Option<i64>,
+        group_id: Option<i64>,
+        topic_pattern: &str,
+        can_read: bool,
+        can_write: bool,
+    ) -> Result<TopicPermission, CoreError> {
+        sqlx::query_as::<_, TopicPermission>(
+            "INSERT INTO topic_permissions (user_id, group_id, topic_pattern, can_read, can_write) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(group_id)
+        .bind(topic_pattern)
+        .bind(can_read)
+        .bind(can_write)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_permissions_for_user(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<TopicPermission>, CoreError> {
+        sqlx::query_as::<_, TopicPermission>(
+            "SELECT * FROM topic_permissions WHERE user_id = $1 \
+             OR group_id IN (SELECT group_id FROM group_members WHERE user_id = $1) \
+             ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_all_permissions(&self) -> Result<Vec<TopicPermission>, CoreError> {
+        sqlx::query_as::<_, TopicPermission>("SELECT * FROM topic_permissions ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete_permission(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM topic_permissions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Permission {} not found", id)));
+        }
+        Ok(())
+    }
+}
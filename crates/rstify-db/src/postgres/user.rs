@@ -0,0 +1,195 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{EmailPreferences, User};
+use rstify_core::repositories::UserRepository;
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgUserRepo {
+    pool: PgPool,
+}
+
+impl PgUserRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepo {
+    async fn create(
+        &self,
+        username: &str,
+        password_hash: &str,
+        email: Option<&str>,
+        is_admin: bool,
+    ) -> Result<User, CoreError> {
+        let result = sqlx::query_as::<_, User>(
+            "INSERT INTO users (username, password_hash, email, is_admin) VALUES ($1, $2, $3, $4) RETURNING *",
+        )
+        .bind(username)
+        .bind(password_hash)
+        .bind(email)
+        .bind(is_admin)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("User '{}' already exists", username))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })?;
+        Ok(result)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<User>, CoreError> {
+        sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, CoreError> {
+        sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_all(&self) -> Result<Vec<User>, CoreError> {
+        sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        username: Option<&str>,
+        email: Option<&str>,
+        is_admin: Option<bool>,
+    ) -> Result<User, CoreError> {
+        let current = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("User {} not found", id)))?;
+
+        let new_username = username.unwrap_or(&current.username);
+        let new_email = email.or(current.email.as_deref());
+        let new_admin = is_admin.unwrap_or(current.is_admin);
+
+        sqlx::query_as::<_, User>(
+            "UPDATE users SET username = $1, email = $2, is_admin = $3, updated_at = utc_now() WHERE id = $4 RETURNING *",
+        )
+        .bind(new_username)
+        .bind(new_email)
+        .bind(new_admin)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_password(&self, id: i64, password_hash: &str) -> Result<(), CoreError> {
+        sqlx::query("UPDATE users SET password_hash = $1, updated_at = utc_now() WHERE id = $2")
+            .bind(password_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn record_login_failure(&self, id: i64) -> Result<i64, CoreError> {
+        sqlx::query_scalar(
+            "UPDATE users SET failed_logins = failed_logins + 1 WHERE id = $1 RETURNING failed_logins",
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn lock_until(&self, id: i64, until: &str) -> Result<(), CoreError> {
+        sqlx::query("UPDATE users SET locked_until = $1 WHERE id = $2")
+            .bind(until)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn clear_login_failures(&self, id: i64) -> Result<(), CoreError> {
+        sqlx::query("UPDATE users SET failed_logins = 0, locked_until = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("User {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<i64, CoreError> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(count)
+    }
+
+    async fn email_preferences(&self, user_id: i64) -> Result<Option<EmailPreferences>, CoreError> {
+        sqlx::query_as::<_, EmailPreferences>("SELECT * FROM email_preferences WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set_email_preferences(
+        &self,
+        prefs: &EmailPreferences,
+    ) -> Result<EmailPreferences, CoreError> {
+        sqlx::query_as::<_, EmailPreferences>(
+            "INSERT INTO email_preferences \
+             (user_id, enabled, min_priority, topics, application_ids, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, utc_now()) \
+             ON CONFLICT (user_id) DO UPDATE SET enabled = EXCLUDED.enabled, \
+             min_priority = EXCLUDED.min_priority, topics = EXCLUDED.topics, \
+             application_ids = EXCLUDED.application_ids, updated_at = EXCLUDED.updated_at \
+             RETURNING *",
+        )
+        .bind(prefs.user_id)
+        .bind(prefs.enabled)
+        .bind(prefs.min_priority)
+        .bind(&prefs.topics)
+        .bind(&prefs.application_ids)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_email_enabled(&self) -> Result<Vec<EmailPreferences>, CoreError> {
+        sqlx::query_as::<_, EmailPreferences>(
+            "SELECT * FROM email_preferences WHERE enabled = TRUE ORDER BY user_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+}
@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{WebhookDeliveryLog, WebhookHealth};
+use rstify_core::repositories::{NewWebhookDelivery, WebhookDeliveryRepository};
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgWebhookDeliveryRepo {
+    pool: PgPool,
+}
+
+impl PgWebhookDeliveryRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookDeliveryRepository for PgWebhookDeliveryRepo {
+    async fn record(&self, delivery: &NewWebhookDelivery<'_>) -> Result<(), CoreError> {
+        sqlx::query(
+            "INSERT INTO webhook_delivery_log (webhook_config_id, message_id, status_code, response_body_preview, duration_ms, success) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(delivery.webhook_config_id)
+        .bind(delivery.message_id)
+        .bind(delivery.status_code)
+        .bind(delivery.response_body_preview)
+        .bind(delivery.duration_ms)
+        .bind(delivery.success)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list_for_config(
+        &self,
+        webhook_config_id: i64,
+        success: Option<bool>,
+        since: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<WebhookDeliveryLog>, CoreError> {
+        let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "SELECT * FROM webhook_delivery_log WHERE webhook_config_id = ",
+        );
+        qb.push_bind(webhook_config_id);
+        if let Some(success) = success {
+            qb.push(" AND success = ");
+            qb.push_bind(success);
+        }
+        if let Some(since) = since {
+            qb.push(" AND attempted_at >= ");
+            qb.push_bind(since.to_string());
+        }
+        qb.push(" ORDER BY attempted_at DESC LIMIT ");
+        qb.push_bind(limit);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset);
+
+        qb.build_query_as::<WebhookDeliveryLog>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn health(
+        &self,
+        webhook_config_ids: &[i64],
+        window: i64,
+    ) -> Result<Vec<WebhookHealth>, CoreError> {
+        if webhook_config_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        sqlx::query_as::<_, WebhookHealth>(
+            r#"SELECT
+                webhook_config_id,
+                MAX(attempted_at) as last_delivery_at,
+                (SELECT success FROM webhook_delivery_log w2 WHERE w2.webhook_config_id = w1.webhook_config_id ORDER BY attempted_at DESC LIMIT 1) as last_delivery_success,
+                CAST(SUM(CASE WHEN success THEN 1 ELSE 0 END) AS DOUBLE PRECISION) / COUNT(*) as recent_success_rate
+            FROM (
+                SELECT webhook_config_id, attempted_at, success,
+                    ROW_NUMBER() OVER (PARTITION BY webhook_config_id ORDER BY attempted_at DESC) as rn
+                FROM webhook_delivery_log
+                WHERE webhook_config_id = ANY($1)
+            ) w1
+            WHERE rn <= $2
+            GROUP BY webhook_config_id"#,
+        )
+        .bind(webhook_config_ids)
+        .bind(window)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn recent_durations(
+        &self,
+        webhook_config_ids: &[i64],
+        per_webhook: i64,
+    ) -> Result<Vec<(i64, i64)>, CoreError> {
+        if webhook_config_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        sqlx::query_as::<_, (i64, i64)>(
+            r#"SELECT webhook_config_id, duration_ms FROM (
+                SELECT webhook_config_id, duration_ms,
+                    ROW_NUMBER() OVER (PARTITION BY webhook_config_id ORDER BY attempted_at DESC) as rn
+                FROM webhook_delivery_log
+                WHERE webhook_config_id = ANY($1)
+            ) d
+            WHERE rn <= $2
+            ORDER BY webhook_config_id, rn"#,
+        )
+        .bind(webhook_config_ids)
+        .bind(per_webhook)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn purge_older_than(&self, days: i64) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            "DELETE FROM webhook_delivery_log WHERE attempted_at < utc_now(-$1 * INTERVAL '1 day')",
+        )
+        .bind(days)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+}
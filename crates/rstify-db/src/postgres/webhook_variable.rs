@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::WebhookVariable;
+use rstify_core::repositories::WebhookVariableRepository;
+use sqlx::PgPool;
+
+#[derive(Clone)]
+pub struct PgWebhookVariableRepo {
+    pool: PgPool,
+}
+
+impl PgWebhookVariableRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookVariableRepository for PgWebhookVariableRepo {
+    async fn list_webhook_variables(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<WebhookVariable>, CoreError> {
+        sqlx::query_as::<_, WebhookVariable>(
+            "SELECT * FROM webhook_variables WHERE user_id = $1 ORDER BY key",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn create_webhook_variable(
+        &self,
+        user_id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<WebhookVariable, CoreError> {
+        sqlx::query_as::<_, WebhookVariable>(
+            "INSERT INTO webhook_variables (user_id, key, value) VALUES ($1, $2, $3) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(key)
+        .bind(value)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("Webhook variable '{}' already exists", key))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })
+    }
+
+    async fn update_webhook_variable(
+        &self,
+        id: i64,
+        key: Option<&str>,
+        value: Option<&str>,
+    ) -> Result<WebhookVariable, CoreError> {
+        let current =
+            sqlx::query_as::<_, WebhookVariable>("SELECT * FROM webhook_variables WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(crate::map_sqlx_err)?
+                .ok_or_else(|| CoreError::NotFound(format!("Variable {} not found", id)))?;
+
+        let new_key = key.unwrap_or(&current.key);
+        let new_value = value.unwrap_or(&current.value);
+
+        sqlx::query_as::<_, WebhookVariable>(
+            "UPDATE webhook_variables SET key = $1, value = $2 WHERE id = $3 RETURNING *",
+        )
+        .bind(new_key)
+        .bind(new_value)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn delete_webhook_variable(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM webhook_variables WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Variable {} not found", id)));
+        }
+        Ok(())
+    }
+}
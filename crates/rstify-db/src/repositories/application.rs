@@ -20,15 +20,17 @@ impl ApplicationRepository for SqliteApplicationRepo {
     async fn create(
         &self,
         user_id: i64,
+        group_id: Option<i64>,
         name: &str,
         description: Option<&str>,
         token: &str,
         default_priority: i32,
     ) -> Result<Application, CoreError> {
         sqlx::query_as::<_, Application>(
-            "INSERT INTO applications (user_id, name, description, token, default_priority) VALUES (?, ?, ?, ?, ?) RETURNING *",
+            "INSERT INTO applications (user_id, group_id, name, description, token, default_priority) VALUES (?, ?, ?, ?, ?, ?) RETURNING *",
         )
         .bind(user_id)
+        .bind(group_id)
         .bind(name)
         .bind(description)
         .bind(token)
@@ -60,6 +62,21 @@ impl ApplicationRepository for SqliteApplicationRepo {
             .map_err(crate::map_sqlx_err)
     }
 
+    async fn find_by_user_and_name(
+        &self,
+        user_id: i64,
+        name: &str,
+    ) -> Result<Option<Application>, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE user_id = ? AND name = ? ORDER BY id LIMIT 1",
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
     async fn list_by_user(&self, user_id: i64) -> Result<Vec<Application>, CoreError> {
         sqlx::query_as::<_, Application>("SELECT * FROM applications WHERE user_id = ? ORDER BY id")
             .bind(user_id)
@@ -68,6 +85,18 @@ impl ApplicationRepository for SqliteApplicationRepo {
             .map_err(crate::map_sqlx_err)
     }
 
+    async fn list_accessible(&self, user_id: i64) -> Result<Vec<Application>, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE user_id = ?1 \
+             OR group_id IN (SELECT group_id FROM group_members WHERE user_id = ?1) \
+             ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
     async fn update(
         &self,
         id: i64,
@@ -108,6 +137,17 @@ impl ApplicationRepository for SqliteApplicationRepo {
         .map_err(crate::map_sqlx_err)
     }
 
+    async fn set_group(&self, id: i64, group_id: Option<i64>) -> Result<Application, CoreError> {
+        sqlx::query_as::<_, Application>(
+            "UPDATE applications SET group_id = ?, updated_at = datetime('now') WHERE id = ? RETURNING *",
+        )
+        .bind(group_id)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
     async fn update_image(&self, id: i64, image: Option<&str>) -> Result<Application, CoreError> {
         sqlx::query_as::<_, Application>(
             "UPDATE applications SET image = ?, updated_at = datetime('now') WHERE id = ? RETURNING *",
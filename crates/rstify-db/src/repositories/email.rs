@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::EmailDelivery;
+use rstify_core::repositories::{EmailRepository, NewEmail};
+use sqlx::SqlitePool;
+
+#[derive(Clone)]
+pub struct SqliteEmailRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteEmailRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EmailRepository for SqliteEmailRepo {
+    async fn enqueue(&self, email: &NewEmail<'_>) -> Result<EmailDelivery, CoreError> {
+        sqlx::query_as::<_, EmailDelivery>(
+            "INSERT INTO email_queue (recipient, subject, text_body, html_body) \
+             VALUES (?, ?, ?, ?) RETURNING *",
+        )
+        .bind(email.recipient)
+        .bind(email.subject)
+        .bind(email.text_body)
+        .bind(email.html_body)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_due(&self, limit: i64) -> Result<Vec<EmailDelivery>, CoreError> {
+        sqlx::query_as::<_, EmailDelivery>(
+            "SELECT * FROM email_queue WHERE status = 'pending' AND next_attempt_at <= datetime('now') \
+             ORDER BY next_attempt_at, id LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn mark_sent(&self, id: i64, attempts: i32) -> Result<(), CoreError> {
+        sqlx::query(
+            "UPDATE email_queue SET status = 'sent', attempts = ?, last_error = NULL, \
+             sent_at = datetime('now') WHERE id = ?",
+        )
+        .bind(attempts)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn mark_retry(
+        &self,
+        id: i64,
+        attempts: i32,
+        error: &str,
+        delay_secs: i64,
+    ) -> Result<(), CoreError> {
+        sqlx::query(
+            "UPDATE email_queue SET attempts = ?, last_error = ?, \
+             next_attempt_at = datetime('now', '+' || ? || ' seconds') WHERE id = ?",
+        )
+        .bind(attempts)
+        .bind(error)
+        .bind(delay_secs)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn mark_failed(&self, id: i64, attempts: i32, error: &str) -> Result<(), CoreError> {
+        sqlx::query(
+            "UPDATE email_queue SET status = 'failed', attempts = ?, last_error = ? \
+             WHERE id = ?",
+        )
+        .bind(attempts)
+        .bind(error)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<EmailDelivery>, CoreError> {
+        let mut qb = sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT * FROM email_queue");
+        if let Some(status) = status {
+            qb.push(" WHERE status = ");
+            qb.push_bind(status.to_string());
+        }
+        qb.push(" ORDER BY id DESC LIMIT ");
+        qb.push_bind(limit);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset);
+        qb.build_query_as::<EmailDelivery>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<EmailDelivery>, CoreError> {
+        sqlx::query_as::<_, EmailDelivery>("SELECT * FROM email_queue WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn purge_older_than(&self, days: i64) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            "DELETE FROM email_queue WHERE status <> 'pending' AND created_at < datetime('now', '-' || ? || ' days')",
+        )
+        .bind(days)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,203 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::models::{Group, GroupMember};
+use rstify_core::repositories::GroupRepository;
+use sqlx::SqlitePool;
+
+const MEMBER_SELECT: &str = "SELECT gm.group_id, gm.user_id, u.username, gm.role, gm.created_at \
+     FROM group_members gm JOIN users u ON u.id = gm.user_id";
+
+#[derive(Clone)]
+pub struct SqliteGroupRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteGroupRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn find_member(
+        &self,
+        group_id: i64,
+        user_id: i64,
+    ) -> Result<Option<GroupMember>, CoreError> {
+        sqlx::query_as::<_, GroupMember>(&format!(
+            "{MEMBER_SELECT} WHERE gm.group_id = ? AND gm.user_id = ?"
+        ))
+        .bind(group_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+}
+
+#[async_trait]
+impl GroupRepository for SqliteGroupRepo {
+    async fn create(&self, name: &str, description: Option<&str>) -> Result<Group, CoreError> {
+        sqlx::query_as::<_, Group>(
+            "INSERT INTO user_groups (name, description) VALUES (?, ?) RETURNING *",
+        )
+        .bind(name)
+        .bind(description)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("Group '{}' already exists", name))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Group>, CoreError> {
+        sqlx::query_as::<_, Group>("SELECT * FROM user_groups WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_all(&self) -> Result<Vec<Group>, CoreError> {
+        sqlx::query_as::<_, Group>("SELECT * FROM user_groups ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_for_user(&self, user_id: i64) -> Result<Vec<Group>, CoreError> {
+        sqlx::query_as::<_, Group>(
+            "SELECT g.* FROM user_groups g \
+             JOIN group_members gm ON gm.group_id = g.id \
+             WHERE gm.user_id = ? ORDER BY g.name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<Group, CoreError> {
+        let current = self
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("Group {} not found", id)))?;
+
+        let new_name = name.unwrap_or(&current.name);
+        let new_desc = description.or(current.description.as_deref());
+
+        sqlx::query_as::<_, Group>(
+            "UPDATE user_groups SET name = ?, description = ? WHERE id = ? RETURNING *",
+        )
+        .bind(new_name)
+        .bind(new_desc)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if crate::is_unique_violation(&e) {
+                CoreError::AlreadyExists(format!("Group '{}' already exists", new_name))
+            } else {
+                CoreError::Database(e.to_string())
+            }
+        })
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM user_groups WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound(format!("Group {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn list_members(&self, group_id: i64) -> Result<Vec<GroupMember>, CoreError> {
+        sqlx::query_as::<_, GroupMember>(&format!(
+            "{MEMBER_SELECT} WHERE gm.group_id = ? ORDER BY u.username"
+        ))
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_memberships_for_user(&self, user_id: i64) -> Result<Vec<GroupMember>, CoreError> {
+        sqlx::query_as::<_, GroupMember>(&format!(
+            "{MEMBER_SELECT} WHERE gm.user_id = ? ORDER BY gm.group_id"
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn add_member(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        role: &str,
+    ) -> Result<GroupMember, CoreError> {
+        sqlx::query("INSERT INTO group_members (group_id, user_id, role) VALUES (?, ?, ?)")
+            .bind(group_id)
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                if crate::is_unique_violation(&e) {
+                    CoreError::AlreadyExists("User is already a member of this group".to_string())
+                } else {
+                    CoreError::Database(e.to_string())
+                }
+            })?;
+        self.find_member(group_id, user_id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound("Group member not found".to_string()))
+    }
+
+    async fn update_member_role(
+        &self,
+        group_id: i64,
+        user_id: i64,
+        role: &str,
+    ) -> Result<GroupMember, CoreError> {
+        let result =
+            sqlx::query("UPDATE group_members SET role = ? WHERE group_id = ? AND user_id = ?")
+                .bind(role)
+                .bind(group_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await
+                .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound("Group member not found".to_string()));
+        }
+        self.find_member(group_id, user_id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound("Group member not found".to_string()))
+    }
+
+    async fn remove_member(&self, group_id: i64, user_id: i64) -> Result<(), CoreError> {
+        let result = sqlx::query("DELETE FROM group_members WHERE group_id = ? AND user_id = ?")
+            .bind(group_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        if result.rows_affected() == 0 {
+            return Err(CoreError::NotFound("Group member not found".to_string()));
+        }
+        Ok(())
+    }
+}
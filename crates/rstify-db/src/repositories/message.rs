@@ -1,9 +1,15 @@
 use async_trait::async_trait;
 use rstify_core::error::CoreError;
-use rstify_core::models::{Attachment, Message, WebhookConfig};
+use rstify_core::models::{
+    Attachment, Message, MessageEscalation, WebhookConfig, ACK_EXTRA, ESCALATION_EXTRA,
+};
 use rstify_core::repositories::{MessageRepository, NewMessage};
 use sqlx::SqlitePool;
 
+/// A message's `extras` as a JSON object; anything else counts as empty.
+const EXTRAS_OBJECT: &str = "COALESCE(CASE WHEN json_valid(extras) THEN \
+     CASE WHEN json_type(extras) = 'object' THEN extras END END, '{}')";
+
 /// Turn a user search string into a safe FTS5 MATCH expression: each
 /// whitespace-separated term becomes a quoted string literal (embedded quotes
 /// doubled), joined by spaces (implicit AND). Quoting means the input can never
@@ -31,8 +37,8 @@ impl MessageRepository for SqliteMessageRepo {
     async fn create(&self, msg: NewMessage<'_>) -> Result<Message, CoreError> {
         sqlx::query_as::<_, Message>(
             r#"INSERT INTO messages
-                (application_id, topic_id, user_id, title, message, priority, tags, click_url, icon_url, actions, extras, content_type, scheduled_for, source, inbox)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                (application_id, topic_id, user_id, title, message, priority, tags, click_url, icon_url, actions, extras, content_type, scheduled_for, source, inbox, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, COALESCE(?, datetime('now')))
                 RETURNING *"#,
         )
         .bind(msg.application_id)
@@ -50,6 +56,7 @@ impl MessageRepository for SqliteMessageRepo {
         .bind(msg.scheduled_for)
         .bind(msg.source)
         .bind(msg.inbox)
+        .bind(msg.created_at)
         .fetch_one(&self.pool)
         .await
         .map_err(crate::map_sqlx_err)
@@ -365,6 +372,36 @@ impl MessageRepository for SqliteMessageRepo {
         Ok(result.rows_affected())
     }
 
+    async fn delete_past_retention(&self) -> Result<u64, CoreError> {
+        let result = sqlx::query(
+            r#"DELETE FROM messages WHERE application_id IN (
+                SELECT id FROM applications WHERE retention_days IS NOT NULL
+            ) AND created_at < datetime('now', '-' || (
+                SELECT retention_days FROM applications WHERE applications.id = messages.application_id
+            ) || ' days')"#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn claim_scheduled_due(&self) -> Result<Vec<Message>, CoreError> {
+        // A single UPDATE ... RETURNING claims the rows atomically.
+        sqlx::query_as::<_, Message>(
+            "UPDATE messages SET delivered_at = datetime('now') \
+             WHERE id IN ( \
+                 SELECT id FROM messages \
+                 WHERE scheduled_for IS NOT NULL \
+                   AND scheduled_for <= datetime('now') \
+                   AND delivered_at IS NULL \
+             ) RETURNING *",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
     async fn mark_delivered(&self, id: i64) -> Result<(), CoreError> {
         sqlx::query("UPDATE messages SET delivered_at = datetime('now') WHERE id = ?")
             .bind(id)
@@ -383,10 +420,11 @@ impl MessageRepository for SqliteMessageRepo {
         size_bytes: i64,
         storage_type: &str,
         storage_path: &str,
+        sha256: Option<&str>,
         expires_at: Option<&str>,
     ) -> Result<Attachment, CoreError> {
         sqlx::query_as::<_, Attachment>(
-            "INSERT INTO attachments (message_id, filename, content_type, size_bytes, storage_type, storage_path, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING *",
+            "INSERT INTO attachments (message_id, filename, content_type, size_bytes, storage_type, storage_path, sha256, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) RETURNING *",
         )
         .bind(message_id)
         .bind(filename)
@@ -394,6 +432,7 @@ impl MessageRepository for SqliteMessageRepo {
         .bind(size_bytes)
         .bind(storage_type)
         .bind(storage_path)
+        .bind(sha256)
         .bind(expires_at)
         .fetch_one(&self.pool)
         .await
@@ -450,6 +489,35 @@ impl MessageRepository for SqliteMessageRepo {
         .map_err(crate::map_sqlx_err)
     }
 
+    async fn list_attachments_by_storage_type(
+        &self,
+        storage_type: &str,
+    ) -> Result<Vec<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE storage_type = ? ORDER BY id",
+        )
+        .bind(storage_type)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_attachment_storage(
+        &self,
+        id: i64,
+        storage_type: &str,
+        storage_path: &str,
+    ) -> Result<(), CoreError> {
+        sqlx::query("UPDATE attachments SET storage_type = ?, storage_path = ? WHERE id = ?")
+            .bind(storage_type)
+            .bind(storage_path)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
     async fn delete_attachment(&self, id: i64) -> Result<(), CoreError> {
         sqlx::query("DELETE FROM attachments WHERE id = ?")
             .bind(id)
@@ -459,6 +527,53 @@ impl MessageRepository for SqliteMessageRepo {
         Ok(())
     }
 
+    async fn list_pending_thumbnails(&self, limit: i64) -> Result<Vec<Attachment>, CoreError> {
+        sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE thumbnail_status = 'pending' ORDER BY id LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn update_attachment_thumbnail(
+        &self,
+        id: i64,
+        status: &str,
+        width: Option<i64>,
+        height: Option<i64>,
+        thumbnail_path: Option<&str>,
+    ) -> Result<(), CoreError> {
+        sqlx::query(
+            "UPDATE attachments SET thumbnail_status = ?, width = ?, height = ?, thumbnail_path = ? WHERE id = ?",
+        )
+        .bind(status)
+        .bind(width)
+        .bind(height)
+        .bind(thumbnail_path)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn count_attachment_references(
+        &self,
+        storage_type: &str,
+        storage_path: &str,
+    ) -> Result<i64, CoreError> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM attachments WHERE storage_type = ? AND storage_path = ?",
+        )
+        .bind(storage_type)
+        .bind(storage_path)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
     // Webhook configs
     async fn create_webhook_config(
         &self,
@@ -481,9 +596,10 @@ impl MessageRepository for SqliteMessageRepo {
         follow_redirects: Option<bool>,
         group_name: Option<&str>,
         secret: Option<&str>,
+        notify_url: Option<&str>,
     ) -> Result<WebhookConfig, CoreError> {
         sqlx::query_as::<_, WebhookConfig>(
-            "INSERT INTO webhook_configs (user_id, name, token, webhook_type, target_topic_id, target_application_id, template, enabled, direction, target_url, http_method, headers, body_template, max_retries, retry_delay_secs, timeout_secs, follow_redirects, group_name, secret) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING *",
+            "INSERT INTO webhook_configs (user_id, name, token, webhook_type, target_topic_id, target_application_id, template, enabled, direction, target_url, http_method, headers, body_template, max_retries, retry_delay_secs, timeout_secs, follow_redirects, group_name, secret, notify_url) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING *",
         )
         .bind(user_id)
         .bind(name)
@@ -504,6 +620,7 @@ impl MessageRepository for SqliteMessageRepo {
         .bind(follow_redirects.unwrap_or(true))
         .bind(group_name)
         .bind(secret)
+        .bind(notify_url)
         .fetch_one(&self.pool)
         .await
         .map_err(crate::map_sqlx_err)
@@ -557,6 +674,7 @@ impl MessageRepository for SqliteMessageRepo {
         follow_redirects: Option<bool>,
         group_name: Option<&str>,
         secret: Option<&str>,
+        notify_url: Option<&str>,
     ) -> Result<WebhookConfig, CoreError> {
         let current = self
             .find_webhook_config_by_id(id)
@@ -579,9 +697,14 @@ impl MessageRepository for SqliteMessageRepo {
             .map(|s| s.to_string())
             .or(current.body_template);
         let new_secret = secret.map(|s| s.to_string()).or(current.secret);
+        // An empty notify URL clears it.
+        let new_notify_url = notify_url
+            .map(|s| s.to_string())
+            .or(current.notify_url)
+            .filter(|u| !u.is_empty());
 
         sqlx::query_as::<_, WebhookConfig>(
-            "UPDATE webhook_configs SET name = ?, template = ?, enabled = ?, target_url = ?, http_method = ?, headers = ?, body_template = ?, max_retries = ?, retry_delay_secs = ?, timeout_secs = ?, follow_redirects = ?, group_name = ?, secret = ? WHERE id = ? RETURNING *",
+            "UPDATE webhook_configs SET name = ?, template = ?, enabled = ?, target_url = ?, http_method = ?, headers = ?, body_template = ?, max_retries = ?, retry_delay_secs = ?, timeout_secs = ?, follow_redirects = ?, group_name = ?, secret = ?, notify_url = ? WHERE id = ? RETURNING *",
         )
         .bind(new_name)
         .bind(new_template)
@@ -596,12 +719,30 @@ impl MessageRepository for SqliteMessageRepo {
         .bind(new_follow_redirects)
         .bind(&new_group_name)
         .bind(&new_secret)
+        .bind(&new_notify_url)
         .bind(id)
         .fetch_one(&self.pool)
         .await
         .map_err(crate::map_sqlx_err)
     }
 
+    async fn list_outgoing_webhooks_for_topic(
+        &self,
+        topic_name: &str,
+    ) -> Result<Vec<WebhookConfig>, CoreError> {
+        sqlx::query_as::<_, WebhookConfig>(
+            r#"SELECT wc.* FROM webhook_configs wc
+               JOIN topics t ON wc.target_topic_id = t.id
+               WHERE wc.direction = 'outgoing'
+                 AND wc.enabled = 1
+                 AND t.name = ?"#,
+        )
+        .bind(topic_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
     async fn delete_webhook_config(&self, id: i64) -> Result<(), CoreError> {
         let result = sqlx::query("DELETE FROM webhook_configs WHERE id = ?")
             .bind(id)
@@ -638,6 +779,115 @@ impl MessageRepository for SqliteMessageRepo {
             .await?
             .ok_or_else(|| CoreError::NotFound(format!("Webhook config {} not found", id)))
     }
+
+    async fn start_escalation(
+        &self,
+        message_id: i64,
+        topic_id: i64,
+        policy: &str,
+        next_at: &str,
+    ) -> Result<(), CoreError> {
+        sqlx::query(
+            "INSERT INTO message_escalations (message_id, topic_id, policy, next_at) \
+             VALUES (?, ?, ?, ?) ON CONFLICT (message_id) DO NOTHING",
+        )
+        .bind(message_id)
+        .bind(topic_id)
+        .bind(policy)
+        .bind(next_at)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn find_escalation(
+        &self,
+        message_id: i64,
+    ) -> Result<Option<MessageEscalation>, CoreError> {
+        sqlx::query_as::<_, MessageEscalation>(
+            "SELECT * FROM message_escalations WHERE message_id = ?",
+        )
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_due_escalations(&self, now: &str) -> Result<Vec<MessageEscalation>, CoreError> {
+        sqlx::query_as::<_, MessageEscalation>(
+            "SELECT * FROM message_escalations \
+             WHERE next_at IS NOT NULL AND next_at <= ? \
+             ORDER BY next_at",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn claim_escalation_step(
+        &self,
+        message_id: i64,
+        step: i32,
+        next_at: Option<&str>,
+    ) -> Result<bool, CoreError> {
+        let result = sqlx::query(
+            "UPDATE message_escalations SET next_step = ?, next_at = ? \
+             WHERE message_id = ? AND next_step = ? AND next_at IS NOT NULL",
+        )
+        .bind(step + 1)
+        .bind(next_at)
+        .bind(message_id)
+        .bind(step)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn stop_escalation(&self, message_id: i64) -> Result<bool, CoreError> {
+        let result = sqlx::query(
+            "UPDATE message_escalations SET next_at = NULL \
+             WHERE message_id = ? AND next_at IS NOT NULL",
+        )
+        .bind(message_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_escalation_step(&self, message_id: i64, step: &str) -> Result<(), CoreError> {
+        let steps = format!("'$.\"{}\".steps'", ESCALATION_EXTRA);
+        sqlx::query(&format!(
+            "UPDATE messages SET extras = json_set({extras}, {steps}, \
+             json_insert(COALESCE(json_extract({extras}, {steps}), '[]'), '$[#]', json(?))) \
+             WHERE id = ?",
+            extras = EXTRAS_OBJECT,
+        ))
+        .bind(step)
+        .bind(message_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn record_acknowledgement(&self, message_id: i64, ack: &str) -> Result<bool, CoreError> {
+        let key = format!("'$.\"{}\"'", ACK_EXTRA);
+        let result = sqlx::query(&format!(
+            "UPDATE messages SET extras = json_set({extras}, {key}, json(?)) \
+             WHERE id = ? AND json_extract({extras}, {key}) IS NULL",
+            extras = EXTRAS_OBJECT,
+        ))
+        .bind(ack)
+        .bind(message_id)
+        .execute(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?;
+        Ok(result.rows_affected() > 0)
+    }
 }
 
 #[cfg(test)]
@@ -1,13 +1,23 @@
 pub mod application;
+pub mod audit;
 pub mod client;
+pub mod email;
+pub mod group;
 pub mod message;
+pub mod settings;
 pub mod topic;
 pub mod user;
+pub mod webhook_delivery;
 pub mod webhook_variable;
 
 pub use application::SqliteApplicationRepo;
+pub use audit::SqliteAuditRepo;
 pub use client::SqliteClientRepo;
+pub use email::SqliteEmailRepo;
+pub use group::SqliteGroupRepo;
 pub use message::SqliteMessageRepo;
+pub use settings::SqliteSettingsRepo;
 pub use topic::SqliteTopicRepo;
 pub use user::SqliteUserRepo;
+pub use webhook_delivery::SqliteWebhookDeliveryRepo;
 pub use webhook_variable::SqliteWebhookVariableRepo;
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use rstify_core::repositories::SettingsRepository;
+use sqlx::SqlitePool;
+
+#[derive(Clone)]
+pub struct SqliteSettingsRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteSettingsRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for SqliteSettingsRepo {
+    async fn list(&self) -> Result<Vec<(String, String)>, CoreError> {
+        sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>, CoreError> {
+        sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), CoreError> {
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+}
@@ -21,15 +21,17 @@ impl TopicRepository for SqliteTopicRepo {
         &self,
         name: &str,
         owner_id: Option<i64>,
+        group_id: Option<i64>,
         description: Option<&str>,
         everyone_read: bool,
         everyone_write: bool,
     ) -> Result<Topic, CoreError> {
         sqlx::query_as::<_, Topic>(
-            "INSERT INTO topics (name, owner_id, description, everyone_read, everyone_write) VALUES (?, ?, ?, ?, ?) RETURNING *",
+            "INSERT INTO topics (name, owner_id, group_id, description, everyone_read, everyone_write) VALUES (?, ?, ?, ?, ?, ?) RETURNING *",
         )
         .bind(name)
         .bind(owner_id)
+        .bind(group_id)
         .bind(description)
         .bind(everyone_read)
         .bind(everyone_write)
@@ -70,9 +72,13 @@ impl TopicRepository for SqliteTopicRepo {
     async fn list_visible(&self, user_id: i64) -> Result<Vec<Topic>, CoreError> {
         sqlx::query_as::<_, Topic>(
             r#"SELECT DISTINCT t.* FROM topics t
-            LEFT JOIN topic_permissions tp ON tp.user_id = ? AND tp.can_read = 1
+            LEFT JOIN topic_permissions tp ON tp.can_read = 1 AND (
+                tp.user_id = ?1
+                OR tp.group_id IN (SELECT group_id FROM group_members WHERE user_id = ?1)
+            )
             WHERE t.everyone_read = 1
-               OR t.owner_id = ?
+               OR t.owner_id = ?1
+               OR t.group_id IN (SELECT group_id FROM group_members WHERE user_id = ?1)
                OR (tp.id IS NOT NULL AND (
                    t.name = tp.topic_pattern
                    OR t.name LIKE REPLACE(REPLACE(tp.topic_pattern, '*', '%'), '?', '_')
@@ -80,7 +86,6 @@ impl TopicRepository for SqliteTopicRepo {
             ORDER BY t.id"#,
         )
         .bind(user_id)
-        .bind(user_id)
         .fetch_all(&self.pool)
         .await
         .map_err(crate::map_sqlx_err)
@@ -152,6 +157,31 @@ impl TopicRepository for SqliteTopicRepo {
         .map_err(crate::map_sqlx_err)
     }
 
+    async fn set_group(&self, id: i64, group_id: Option<i64>) -> Result<Topic, CoreError> {
+        sqlx::query_as::<_, Topic>("UPDATE topics SET group_id = ? WHERE id = ? RETURNING *")
+            .bind(group_id)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set_escalation_policy(
+        &self,
+        id: i64,
+        policy: Option<&str>,
+    ) -> Result<Topic, CoreError> {
+        sqlx::query_as::<_, Topic>(
+            "UPDATE topics SET escalation_policy = ? WHERE id = ? RETURNING *",
+        )
+        .bind(policy)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)?
+        .ok_or_else(|| CoreError::NotFound(format!("Topic {} not found", id)))
+    }
+
     async fn delete(&self, id: i64) -> Result<(), CoreError> {
         // Nullify webhook references before deleting (FK has no ON DELETE SET NULL)
         sqlx::query("UPDATE webhook_configs SET target_topic_id = NULL WHERE target_topic_id = ?")
@@ -181,15 +211,17 @@ impl TopicRepository for SqliteTopicRepo {
 
     async fn create_permission(
         &self,
-        user_id: i64,
+        user_id: Option<i64>,
+        group_id: Option<i64>,
         topic_pattern: &str,
         can_read: bool,
         can_write: bool,
     ) -> Result<TopicPermission, CoreError> {
         sqlx::query_as::<_, TopicPermission>(
-            "INSERT INTO topic_permissions (user_id, topic_pattern, can_read, can_write) VALUES (?, ?, ?, ?) RETURNING *",
+            "INSERT INTO topic_permissions (user_id, group_id, topic_pattern, can_read, can_write) VALUES (?, ?, ?, ?, ?) RETURNING *",
         )
         .bind(user_id)
+        .bind(group_id)
         .bind(topic_pattern)
         .bind(can_read)
         .bind(can_write)
@@ -203,7 +235,9 @@ impl TopicRepository for SqliteTopicRepo {
         user_id: i64,
     ) -> Result<Vec<TopicPermission>, CoreError> {
         sqlx::query_as::<_, TopicPermission>(
-            "SELECT * FROM topic_permissions WHERE user_id = ? ORDER BY id",
+            "SELECT * FROM topic_permissions WHERE user_id = ?1 \
+             OR group_id IN (SELECT group_id FROM group_members WHERE user_id = ?1) \
+             ORDER BY id",
         )
         .bind(user_id)
         .fetch_all(&self.pool)
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use rstify_core::error::CoreError;
-use rstify_core::models::User;
+use rstify_core::models::{EmailPreferences, User};
 use rstify_core::repositories::UserRepository;
 use sqlx::SqlitePool;
 
@@ -106,6 +106,35 @@ impl UserRepository for SqliteUserRepo {
         Ok(())
     }
 
+    async fn record_login_failure(&self, id: i64) -> Result<i64, CoreError> {
+        sqlx::query_scalar(
+            "UPDATE users SET failed_logins = failed_logins + 1 WHERE id = ? RETURNING failed_logins",
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn lock_until(&self, id: i64, until: &str) -> Result<(), CoreError> {
+        sqlx::query("UPDATE users SET locked_until = ? WHERE id = ?")
+            .bind(until)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
+    async fn clear_login_failures(&self, id: i64) -> Result<(), CoreError> {
+        sqlx::query("UPDATE users SET failed_logins = 0, locked_until = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)?;
+        Ok(())
+    }
+
     async fn delete(&self, id: i64) -> Result<(), CoreError> {
         let result = sqlx::query("DELETE FROM users WHERE id = ?")
             .bind(id)
@@ -125,4 +154,44 @@ impl UserRepository for SqliteUserRepo {
             .map_err(crate::map_sqlx_err)?;
         Ok(count)
     }
+
+    async fn email_preferences(&self, user_id: i64) -> Result<Option<EmailPreferences>, CoreError> {
+        sqlx::query_as::<_, EmailPreferences>("SELECT * FROM email_preferences WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(crate::map_sqlx_err)
+    }
+
+    async fn set_email_preferences(
+        &self,
+        prefs: &EmailPreferences,
+    ) -> Result<EmailPreferences, CoreError> {
+        sqlx::query_as::<_, EmailPreferences>(
+            "INSERT INTO email_preferences \
+             (user_id, enabled, min_priority, topics, application_ids, updated_at) \
+             VALUES (?, ?, ?, ?, ?, datetime('now')) \
+             ON CONFLICT (user_id) DO UPDATE SET enabled = EXCLUDED.enabled, \
+             min_priority = EXCLUDED.min_priority, topics = EXCLUDED.topics, \
+             application_ids = EXCLUDED.application_ids, updated_at = EXCLUDED.updated_at \
+             RETURNING *",
+        )
+        .bind(prefs.user_id)
+        .bind(prefs.enabled)
+        .bind(prefs.min_priority)
+        .bind(&prefs.topics)
+        .bind(&prefs.application_ids)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
+
+    async fn list_email_enabled(&self) -> Result<Vec<EmailPreferences>, CoreError> {
+        sqlx::query_as::<_, EmailPreferences>(
+            "SELECT * FROM email_preferences WHERE enabled = 1 ORDER BY user_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(crate::map_sqlx_err)
+    }
 }
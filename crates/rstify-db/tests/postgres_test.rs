@@ -0,0 +1,387 @@
+//! Exercises the PostgreSQL repositories against a live server.
+//!
+//! Skipped unless `RSTIFY_TEST_POSTGRES_URL` points at a scratch database; the
+//! test drops and recreates its `public` schema.
+
+use rstify_core::models::{AuditFilter, NewAuditEntry};
+use rstify_core::repositories::{NewMessage, NewWebhookDelivery, Repositories};
+use rstify_db::pool::Database;
+
+async fn fresh_database() -> Option<Database> {
+    let url = std::env::var("RSTIFY_TEST_POSTGRES_URL").ok()?;
+    let pool = sqlx::PgPool::connect(&url).await.expect("connect");
+    sqlx::raw_sql("DROP SCHEMA public CASCADE; CREATE SCHEMA public;")
+        .execute(&pool)
+        .await
+        .expect("reset schema");
+    pool.close().await;
+
+    let db = Database::connect(&url).await.expect("connect");
+    db.migrate().await.expect("migrate");
+    // A second run must be a no-op.
+    db.migrate().await.expect("re-migrate");
+    Some(db)
+}
+
+#[tokio::test]
+async fn postgres_repositories_round_trip() {
+    let Some(db) = fresh_database().await else {
+        eprintln!("RSTIFY_TEST_POSTGRES_URL not set; skipping");
+        return;
+    };
+    db.ping().await.expect("ping");
+    let repos: Repositories = db.repositories();
+
+    // Users and login lockout
+    let admin = repos
+        .users
+        .create("admin", "hash", None, true)
+        .await
+        .unwrap();
+    let user = repos
+        .users
+        .create("alice", "hash", Some("alice@example.com"), false)
+        .await
+        .unwrap();
+    assert!(admin.is_admin);
+    assert!(repos.users.create("alice", "x", None, false).await.is_err());
+    assert_eq!(repos.users.count().await.unwrap(), 2);
+    assert_eq!(repos.users.record_login_failure(user.id).await.unwrap(), 1);
+    assert_eq!(repos.users.record_login_failure(user.id).await.unwrap(), 2);
+    repos.users.clear_login_failures(user.id).await.unwrap();
+
+    // Groups
+    let group = repos.groups.create("ops", Some("on call")).await.unwrap();
+    repos
+        .groups
+        .add_member(group.id, user.id, "admin")
+        .await
+        .unwrap();
+    assert_eq!(repos.groups.list_for_user(user.id).await.unwrap().len(), 1);
+    assert_eq!(repos.groups.list_members(group.id).await.unwrap().len(), 1);
+
+    // Applications
+    let app = repos
+        .applications
+        .create(user.id, None, "backup", None, "AP_token", 5)
+        .await
+        .unwrap();
+    let found = repos
+        .applications
+        .find_by_user_and_name(user.id, "backup")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, app.id);
+    repos
+        .applications
+        .set_group(app.id, Some(group.id))
+        .await
+        .unwrap();
+
+    // Clients
+    let client = repos
+        .clients
+        .create(user.id, "phone", "CL_token", r#"["read"]"#, None, None)
+        .await
+        .unwrap();
+    repos
+        .clients
+        .touch_last_used(client.id, Some("10.0.0.1"))
+        .await
+        .unwrap();
+    let client = repos.clients.find_by_id(client.id).await.unwrap().unwrap();
+    assert_eq!(client.last_used_ip.as_deref(), Some("10.0.0.1"));
+    assert_eq!(repos.clients.delete_expired().await.unwrap(), 0);
+
+    // Topics and permissions
+    let topic = repos
+        .topics
+        .create("alerts", Some(user.id), None, None, false, false)
+        .await
+        .unwrap();
+    repos
+        .topics
+        .create_permission(None, Some(group.id), "alerts", true, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        repos
+            .topics
+            .list_permissions_for_user(user.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(repos.topics.list_visible(user.id).await.unwrap().len(), 1);
+
+    // Messages, search and scheduling
+    let msg = repos
+        .messages
+        .create(NewMessage {
+            application_id: Some(app.id),
+            user_id: Some(user.id),
+            title: Some("Backup finished"),
+            message: "Nightly backup completed",
+            priority: 5,
+            tags: Some(r#"["backup"]"#),
+            inbox: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    repos
+        .messages
+        .create(NewMessage {
+            topic_id: Some(topic.id),
+            message: "Disk almost full",
+            priority: 8,
+            scheduled_for: Some("2000-01-01 00:00:00"),
+            inbox: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let hits = repos
+        .messages
+        .search(
+            user.id,
+            Some("nightly"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].id, msg.id);
+    let tagged = repos
+        .messages
+        .search(
+            user.id,
+            None,
+            Some("BACKUP"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(repos.messages.claim_scheduled_due().await.unwrap().len(), 1);
+    assert!(repos
+        .messages
+        .claim_scheduled_due()
+        .await
+        .unwrap()
+        .is_empty());
+    let inbox = repos
+        .messages
+        .list_inbox(user.id, &[topic.id], 10, 0, Some(true))
+        .await
+        .unwrap();
+    assert_eq!(inbox.len(), 2);
+    assert_eq!(repos.messages.delete_past_retention().await.unwrap(), 0);
+
+    let attachment = repos
+        .messages
+        .create_attachment(msg.id, "log.txt", None, 3, "local", "ab/cd", None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        repos
+            .messages
+            .list_attachments_by_messages(&[msg.id])
+            .await
+            .unwrap()[0]
+            .id,
+        attachment.id
+    );
+    repos
+        .messages
+        .update_attachment_storage(attachment.id, "s3", "cd")
+        .await
+        .unwrap();
+    let moved = repos
+        .messages
+        .list_attachments_by_storage_type("s3")
+        .await
+        .unwrap();
+    assert_eq!(moved[0].storage_path, "cd");
+    repos
+        .messages
+        .create_attachment(msg.id, "log2.txt", None, 3, "s3", "cd", Some("cd"), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        repos
+            .messages
+            .count_attachment_references("s3", "cd")
+            .await
+            .unwrap(),
+        2
+    );
+    repos
+        .messages
+        .update_attachment_thumbnail(attachment.id, "pending", None, None, None)
+        .await
+        .unwrap();
+    let pending = repos.messages.list_pending_thumbnails(10).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    repos
+        .messages
+        .update_attachment_thumbnail(
+            attachment.id,
+            "ready",
+            Some(8),
+            Some(6),
+            Some("cd.thumb.jpg"),
+        )
+        .await
+        .unwrap();
+    assert!(repos
+        .messages
+        .list_pending_thumbnails(10)
+        .await
+        .unwrap()
+        .is_empty());
+    let row = repos
+        .messages
+        .find_attachment(attachment.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((row.width, row.height), (Some(8), Some(6)));
+
+    // Outgoing webhooks and their delivery log
+    let webhook = repos
+        .messages
+        .create_webhook_config(
+            user.id,
+            "relay",
+            "WH_token",
+            "json",
+            Some(topic.id),
+            None,
+            "",
+            true,
+            "outgoing",
+            Some("https://example.com/hook"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        repos
+            .messages
+            .list_outgoing_webhooks_for_topic("alerts")
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    for success in [true, false] {
+        repos
+            .webhook_deliveries
+            .record(&NewWebhookDelivery {
+                webhook_config_id: webhook.id,
+                status_code: Some(200),
+                duration_ms: 40,
+                success,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+    let health = repos
+        .webhook_deliveries
+        .health(&[webhook.id], 10)
+        .await
+        .unwrap();
+    assert_eq!(health[0].recent_success_rate, Some(0.5));
+    assert_eq!(
+        repos
+            .webhook_deliveries
+            .recent_durations(&[webhook.id], 20)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        repos
+            .webhook_deliveries
+            .list_for_config(webhook.id, Some(true), None, 10, 0)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        repos.webhook_deliveries.purge_older_than(30).await.unwrap(),
+        0
+    );
+
+    // Settings and audit
+    assert_eq!(
+        repos
+            .settings
+            .get("inbox_priority_threshold")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("5")
+    );
+    repos
+        .settings
+        .set("inbox_priority_threshold", "7")
+        .await
+        .unwrap();
+    assert_eq!(
+        repos
+            .settings
+            .get("inbox_priority_threshold")
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("7")
+    );
+    repos
+        .audit
+        .record(&NewAuditEntry {
+            actor_id: Some(admin.id),
+            action: "user.create".into(),
+            target_type: "user".into(),
+            target_id: Some(user.id.to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let filter = AuditFilter {
+        action: Some("user.".into()),
+        ..Default::default()
+    };
+    assert_eq!(repos.audit.list(&filter, 10).await.unwrap().len(), 1);
+    assert_eq!(repos.audit.purge_older_than(365).await.unwrap(), 0);
+
+    // Deleting a user cascades to everything they own.
+    repos.users.delete(user.id).await.unwrap();
+    assert!(repos.messages.find_by_id(msg.id).await.unwrap().is_none());
+}
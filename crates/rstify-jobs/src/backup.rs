@@ -0,0 +1,780 @@
+//! Online backups of a SQLite deployment and the matching offline restore.
+//!
+//! An archive is a gzipped tarball holding `manifest.json` first, then a
+//! `VACUUM INTO` snapshot of the database as `database.sqlite`, then the upload
+//! directory under `uploads/`. Restore reads the manifest before anything else
+//! so a backup from a newer schema is refused without touching the live files.
+
+use chrono::{NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rstify_core::error::CoreError;
+use rstify_core::models::BackupInfo;
+use rstify_db::pool::{self, Database};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tar::{Archive, Builder, EntryType, Header};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Bumped when the archive layout changes incompatibly.
+pub const BACKUP_FORMAT: u32 = 1;
+
+const NAME_PREFIX: &str = "rstify-backup-";
+const NAME_SUFFIX: &str = ".tar.gz";
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "database.sqlite";
+const UPLOADS_PREFIX: &str = "uploads/";
+/// Multipart uploads are staged here; nothing in it is referenced yet.
+const STAGING_DIR: &str = "incoming";
+const MAX_MANIFEST_SIZE: u64 = 1024 * 1024;
+
+/// Written first in every archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: u32,
+    /// Server version that took the backup.
+    pub version: String,
+    pub created_at: String,
+    /// Migrations applied to the snapshot, checked against this build on restore.
+    pub migrations: Vec<String>,
+    pub upload_files: u64,
+    pub upload_bytes: u64,
+}
+
+/// Settings for the periodic backup job.
+#[derive(Debug, Clone)]
+pub struct BackupSchedule {
+    pub upload_dir: PathBuf,
+    pub backup_dir: PathBuf,
+    pub interval: Duration,
+    /// Newest archives kept after each run.
+    pub keep: usize,
+}
+
+fn io_error(context: &str, e: io::Error) -> CoreError {
+    CoreError::Internal(format!("{}: {}", context, e))
+}
+
+/// Whether `name` is an archive name this module produces. Also what keeps
+/// names taken from requests inside the backup directory.
+pub fn is_backup_name(name: &str) -> bool {
+    name.strip_prefix(NAME_PREFIX)
+        .and_then(|rest| rest.strip_suffix(NAME_SUFFIX))
+        .is_some_and(|stamp| NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).is_ok())
+}
+
+fn backup_info(path: &Path) -> io::Result<BackupInfo> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stamp = &name[NAME_PREFIX.len()..name.len() - NAME_SUFFIX.len()];
+    let created_at = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+        .map(|t| t.and_utc().format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_default();
+    Ok(BackupInfo {
+        size_bytes: fs::metadata(path)?.len() as i64,
+        name,
+        created_at,
+    })
+}
+
+/// Archives in `backup_dir`, newest first. A missing directory has none.
+pub async fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, CoreError> {
+    let backup_dir = backup_dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let entries = match fs::read_dir(&backup_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error("failed to read backup directory", e)),
+        };
+        let mut backups = Vec::new();
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_backup_name(&name) && entry.file_type().is_ok_and(|t| t.is_file()) {
+                backups.push(backup_info(&entry.path()).map_err(|e| io_error(&name, e))?);
+            }
+        }
+        // The timestamp in the name sorts chronologically.
+        backups.sort_by(|a, b| b.name.cmp(&a.name));
+        Ok(backups)
+    })
+    .await
+    .map_err(|e| CoreError::Internal(e.to_string()))?
+}
+
+/// Delete all but the `keep` newest archives; returns how many were removed.
+pub async fn rotate_backups(backup_dir: &Path, keep: usize) -> Result<usize, CoreError> {
+    let mut removed = 0;
+    for old in list_backups(backup_dir).await?.into_iter().skip(keep) {
+        match tokio::fs::remove_file(backup_dir.join(&old.name)).await {
+            Ok(()) => removed += 1,
+            Err(e) => warn!("Failed to remove old backup {}: {}", old.name, e),
+        }
+    }
+    Ok(removed)
+}
+
+/// Take a backup of a running SQLite deployment into `backup_dir`. The archive
+/// is written under a hidden name and renamed once complete, so listings and
+/// rotation never see a partial one.
+pub async fn create_backup(
+    db: &Database,
+    upload_dir: &Path,
+    backup_dir: &Path,
+) -> Result<BackupInfo, CoreError> {
+    if db.sqlite_pool().is_none() {
+        return Err(CoreError::Validation(
+            "Backups are only supported for SQLite; use pg_dump for PostgreSQL".to_string(),
+        ));
+    }
+    tokio::fs::create_dir_all(backup_dir)
+        .await
+        .map_err(|e| io_error("failed to create backup directory", e))?;
+
+    let now = Utc::now();
+    let name = format!("{}{}{}", NAME_PREFIX, now.format(STAMP_FORMAT), NAME_SUFFIX);
+    let target = backup_dir.join(&name);
+    if tokio::fs::try_exists(&target).await.unwrap_or(false) {
+        return Err(CoreError::AlreadyExists(format!(
+            "Backup {} already exists",
+            name
+        )));
+    }
+    let snapshot = backup_dir.join(format!(".{}.sqlite", name));
+    let partial = backup_dir.join(format!(".{}.partial", name));
+    let _ = tokio::fs::remove_file(&snapshot).await;
+
+    let result = async {
+        db.snapshot_to(&snapshot)
+            .await
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+        let migrations = db
+            .applied_migrations()
+            .await
+            .map_err(|e| CoreError::Database(e.to_string()))?;
+
+        let upload_dir = upload_dir.to_path_buf();
+        let backup_dir = backup_dir.to_path_buf();
+        let (snapshot, partial, target) = (snapshot.clone(), partial.clone(), target.clone());
+        tokio::task::spawn_blocking(move || {
+            let files = upload_files(&upload_dir, &backup_dir)
+                .map_err(|e| io_error("failed to read upload directory", e))?;
+            let manifest = BackupManifest {
+                format: BACKUP_FORMAT,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                created_at: now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                migrations,
+                upload_files: files.len() as u64,
+                upload_bytes: files.iter().map(|f| f.size).sum(),
+            };
+            write_archive(&partial, &manifest, &snapshot, &upload_dir, &files)
+                .map_err(|e| io_error("failed to write backup", e))?;
+            fs::rename(&partial, &target).map_err(|e| io_error("failed to finish backup", e))?;
+            backup_info(&target).map_err(|e| io_error("failed to read backup", e))
+        })
+        .await
+        .map_err(|e| CoreError::Internal(e.to_string()))?
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&snapshot).await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&partial).await;
+    }
+    if let Ok(info) = &result {
+        info!("Created backup {} ({} bytes)", info.name, info.size_bytes);
+    }
+    result
+}
+
+struct UploadFile {
+    /// Path relative to the upload directory, `/`-separated.
+    relative: String,
+    size: u64,
+    mtime: u64,
+}
+
+/// Files under the upload directory worth keeping: not staged uploads, not
+/// hidden temporaries, not symlinks, and not the backups themselves.
+fn upload_files(upload_dir: &Path, backup_dir: &Path) -> io::Result<Vec<UploadFile>> {
+    let mut files = Vec::new();
+    if !upload_dir.is_dir() {
+        return Ok(files);
+    }
+    let backup_dir = backup_dir.canonicalize().ok();
+    let mut pending = vec![(upload_dir.to_path_buf(), String::new())];
+    while let Some((dir, prefix)) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') || (prefix.is_empty() && name == STAGING_DIR) {
+                continue;
+            }
+            let relative = format!("{}{}", prefix, name);
+            let meta = entry.metadata()?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if entry.path().canonicalize().ok() != backup_dir {
+                    pending.push((entry.path(), format!("{}/", relative)));
+                }
+            } else if file_type.is_file() {
+                let mtime = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |d| d.as_secs());
+                files.push(UploadFile {
+                    relative,
+                    size: meta.len(),
+                    mtime,
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(files)
+}
+
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
+
+/// A regular-file header; GNU format so large sizes and long paths fit.
+fn file_header(size: u64, mtime: u64) -> Header {
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Regular);
+    header.set_mode(0o644);
+    header.set_size(size);
+    header.set_mtime(mtime);
+    header
+}
+
+fn write_archive(
+    path: &Path,
+    manifest: &BackupManifest,
+    snapshot: &Path,
+    upload_dir: &Path,
+    files: &[UploadFile],
+) -> io::Result<()> {
+    let file = File::create(path)?;
+    let mut tar = Builder::new(GzEncoder::new(BufWriter::new(file), Compression::default()));
+    let mtime = Utc::now().timestamp() as u64;
+
+    let manifest = serde_json::to_vec_pretty(manifest)?;
+    tar.append_data(
+        &mut file_header(manifest.len() as u64, mtime),
+        MANIFEST_ENTRY,
+        &manifest[..],
+    )?;
+
+    let db = File::open(snapshot).map_err(|e| with_path(snapshot, e))?;
+    let size = db.metadata()?.len();
+    tar.append_data(&mut file_header(size, mtime), DATABASE_ENTRY, db.take(size))?;
+
+    for upload in files {
+        let path = upload_dir.join(&upload.relative);
+        let data = match File::open(&path) {
+            Ok(data) => data,
+            // Deleted (e.g. by attachment cleanup) since the directory was read.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                warn!("Skipping {}: removed while backing up", path.display());
+                continue;
+            }
+            Err(e) => return Err(with_path(&path, e)),
+        };
+        // The open handle's size, so the entry matches what is read even if
+        // the file changed since it was listed. Anything cut short past that
+        // is zero-filled rather than leaving a malformed archive.
+        let size = data.metadata()?.len();
+        tar.append_data(
+            &mut file_header(size, upload.mtime),
+            format!("{}{}", UPLOADS_PREFIX, upload.relative),
+            data.take(size).chain(io::repeat(0)).take(size),
+        )?;
+    }
+
+    let file = tar
+        .into_inner()?
+        .finish()?
+        .into_inner()
+        .map_err(|e| e.into_error())?;
+    file.sync_all()
+}
+
+/// Restore an archive over the database at `database_url` and into
+/// `upload_dir`. The server must be stopped. Restored uploads are added to
+/// what is there; blobs the restored database doesn't reference are swept by
+/// the attachment cleanup job. Nothing live is touched until the archive's
+/// database has been verified.
+pub async fn restore_backup(
+    archive: &Path,
+    database_url: &str,
+    upload_dir: &Path,
+) -> Result<BackupManifest, CoreError> {
+    let db_path = pool::sqlite_file(database_url).ok_or_else(|| {
+        CoreError::Validation("Restore needs a file-backed SQLite DATABASE_URL".to_string())
+    })?;
+    let sibling = |suffix: &str| {
+        let mut path = db_path.clone().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    };
+    let restoring = sibling(".restoring");
+    let restoring_uploads = sibling(".restoring-uploads");
+    let discard = || async {
+        let _ = tokio::fs::remove_file(&restoring).await;
+        let _ = tokio::fs::remove_dir_all(&restoring_uploads).await;
+    };
+    discard().await;
+
+    let archive_path = archive.to_path_buf();
+    let (staged, staged_uploads) = (restoring.clone(), restoring_uploads.clone());
+    let manifest = tokio::task::spawn_blocking(move || {
+        extract_archive(&archive_path, &staged, &staged_uploads)
+    })
+    .await
+    .map_err(|e| CoreError::Internal(e.to_string()))?;
+    let manifest = match manifest {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            discard().await;
+            return Err(e);
+        }
+    };
+
+    // The manifest could be edited; check the database itself too.
+    let verified = match pool::inspect_sqlite_file(&restoring).await {
+        Ok(applied) => check_migrations(&applied),
+        Err(e) => Err(CoreError::Validation(format!(
+            "The backup's database is unusable: {}",
+            e
+        ))),
+    };
+    if let Err(e) = verified {
+        discard().await;
+        return Err(e);
+    }
+
+    let (staged_uploads, target) = (restoring_uploads.clone(), upload_dir.to_path_buf());
+    let moved = tokio::task::spawn_blocking(move || move_tree(&staged_uploads, &target))
+        .await
+        .map_err(|e| CoreError::Internal(e.to_string()))?;
+    if let Err(e) = moved {
+        discard().await;
+        return Err(io_error("failed to restore uploads", e));
+    }
+    let _ = tokio::fs::remove_dir_all(&restoring_uploads).await;
+
+    for suffix in ["-wal", "-shm"] {
+        let _ = tokio::fs::remove_file(sibling(suffix)).await;
+    }
+    tokio::fs::rename(&restoring, &db_path)
+        .await
+        .map_err(|e| io_error("failed to replace the database", e))?;
+    info!(
+        "Restored backup from {} ({} upload files)",
+        manifest.created_at, manifest.upload_files
+    );
+    Ok(manifest)
+}
+
+/// Move every file under `from` to the same relative path under `to`,
+/// copying where a rename can't cross filesystems.
+fn move_tree(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            move_tree(&entry.path(), &target)?;
+        } else if fs::rename(entry.path(), &target).is_err() {
+            fs::copy(entry.path(), &target).map_err(|e| with_path(&target, e))?;
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Refuse databases carrying migrations this build doesn't know.
+fn check_migrations(applied: &[String]) -> Result<(), CoreError> {
+    let unknown = pool::unknown_migrations(applied);
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(CoreError::Validation(format!(
+            "The backup was made by a newer version of rstify (unknown migrations: {}); upgrade before restoring",
+            unknown.join(", ")
+        )))
+    }
+}
+
+fn extract_archive(
+    archive: &Path,
+    db_target: &Path,
+    upload_dir: &Path,
+) -> Result<BackupManifest, CoreError> {
+    let file = File::open(archive).map_err(|e| io_error("failed to open backup", e))?;
+    let mut tar = Archive::new(GzDecoder::new(BufReader::new(file)));
+    let corrupt = |e: io::Error| CoreError::Validation(format!("Not a valid backup: {}", e));
+    let mut entries = tar.entries().map_err(corrupt)?;
+
+    let manifest: BackupManifest = match entries.next().transpose().map_err(corrupt)? {
+        Some(mut entry)
+            if entry_path(&entry).as_deref() == Some(MANIFEST_ENTRY)
+                && entry.size() <= MAX_MANIFEST_SIZE =>
+        {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map_err(corrupt)?;
+            serde_json::from_slice(&data)
+                .map_err(|e| CoreError::Validation(format!("Invalid backup manifest: {}", e)))?
+        }
+        _ => {
+            return Err(CoreError::Validation(
+                "Not a valid backup: manifest.json missing".to_string(),
+            ))
+        }
+    };
+    if manifest.format > BACKUP_FORMAT {
+        return Err(CoreError::Validation(format!(
+            "Backup format {} is newer than this build supports ({})",
+            manifest.format, BACKUP_FORMAT
+        )));
+    }
+    check_migrations(&manifest.migrations)?;
+
+    let mut has_database = false;
+    for entry in entries {
+        let mut entry = entry.map_err(corrupt)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry_path(&entry)
+            .ok_or_else(|| CoreError::Validation("Not a valid backup: bad path".to_string()))?;
+        let target =
+            if path == DATABASE_ENTRY {
+                has_database = true;
+                db_target.to_path_buf()
+            } else if let Some(relative) = path.strip_prefix(UPLOADS_PREFIX) {
+                upload_dir.join(safe_relative_path(relative).ok_or_else(|| {
+                    CoreError::Validation(format!("Unsafe path in backup: {}", path))
+                })?)
+            } else {
+                warn!("Skipping unexpected backup entry {}", path);
+                continue;
+            };
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| io_error("failed to create directory", e))?;
+        }
+        let mut out = BufWriter::new(File::create(&target).map_err(|e| io_error(&path, e))?);
+        io::copy(&mut entry, &mut out).map_err(corrupt)?;
+        out.flush().map_err(|e| io_error(&path, e))?;
+    }
+    if !has_database {
+        return Err(CoreError::Validation(
+            "Not a valid backup: database missing".to_string(),
+        ));
+    }
+    Ok(manifest)
+}
+
+/// An entry's path as written by [`write_archive`], `None` if it isn't UTF-8.
+fn entry_path<R: Read>(entry: &tar::Entry<'_, R>) -> Option<String> {
+    let path = entry.path().ok()?;
+    path.to_str().map(str::to_string)
+}
+
+/// `relative` as a path that stays inside the directory it is joined to.
+fn safe_relative_path(relative: &str) -> Option<PathBuf> {
+    let path = Path::new(relative);
+    let safe = !relative.is_empty()
+        && !relative.contains('\\')
+        && path.components().all(|c| matches!(c, Component::Normal(_)));
+    safe.then(|| path.to_path_buf())
+}
+
+/// Background task that takes a backup every `schedule.interval` and prunes
+/// old ones.
+pub async fn run_scheduled_backups(
+    db: Database,
+    schedule: BackupSchedule,
+    cancel: CancellationToken,
+) {
+    info!(
+        "Backup worker started (every {}h, keeping {})",
+        schedule.interval.as_secs() / 3600,
+        schedule.keep
+    );
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Backup worker shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(schedule.interval) => {
+                if let Err(e) = create_backup(&db, &schedule.upload_dir, &schedule.backup_dir).await {
+                    error!("Scheduled backup failed: {}", e);
+                    continue;
+                }
+                match rotate_backups(&schedule.backup_dir, schedule.keep).await {
+                    Ok(count) if count > 0 => info!("Removed {} old backups", count),
+                    Err(e) => error!("Backup rotation error: {}", e),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_names() {
+        assert!(is_backup_name("rstify-backup-20260101-030000.tar.gz"));
+        assert!(!is_backup_name("rstify-backup-latest.tar.gz"));
+        assert!(!is_backup_name("../rstify-backup-20260101-030000.tar.gz"));
+        assert!(!is_backup_name(
+            ".rstify-backup-20260101-030000.tar.gz.partial"
+        ));
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rstify-backup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("uploads/incoming")).unwrap();
+        dir
+    }
+
+    async fn live_database(dir: &Path) -> (Database, String) {
+        let url = format!("sqlite://{}", dir.join("rstify.db").display());
+        let db = Database::connect(&url).await.unwrap();
+        db.migrate().await.unwrap();
+        (db, url)
+    }
+
+    async fn user_count(db: &Database) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(db.sqlite_pool().unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn backup_and_restore_round_trip() {
+        let dir = scratch("round-trip");
+        let uploads = dir.join("uploads");
+        let backups = dir.join("backups");
+        let (db, url) = live_database(&dir).await;
+        sqlx::query("INSERT INTO users (username, password_hash) VALUES ('alice', 'x')")
+            .execute(db.sqlite_pool().unwrap())
+            .await
+            .unwrap();
+        fs::create_dir_all(uploads.join("ab")).unwrap();
+        fs::write(uploads.join("ab/abcdef"), b"attachment").unwrap();
+        // Longer than a plain ustar name field.
+        let long = format!("{}/{}.bin", "d".repeat(60), "f".repeat(80));
+        fs::create_dir_all(uploads.join("d".repeat(60))).unwrap();
+        fs::write(uploads.join(&long), [7u8; 600]).unwrap();
+        fs::write(uploads.join("incoming/staged"), b"half-uploaded").unwrap();
+        fs::write(uploads.join(".tmp-upload"), b"temp").unwrap();
+
+        let info = create_backup(&db, &uploads, &backups).await.unwrap();
+        assert!(is_backup_name(&info.name));
+        let listed = list_backups(&backups).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, info.name);
+        assert_eq!(listed[0].size_bytes, info.size_bytes);
+
+        // Diverge from the backup, then stop the "server" and restore.
+        sqlx::query("DELETE FROM users")
+            .execute(db.sqlite_pool().unwrap())
+            .await
+            .unwrap();
+        fs::remove_dir_all(&uploads).unwrap();
+        db.sqlite_pool().unwrap().close().await;
+
+        let manifest = restore_backup(&backups.join(&info.name), &url, &uploads)
+            .await
+            .unwrap();
+        assert_eq!(manifest.upload_files, 2);
+        assert_eq!(manifest.migrations.len(), pool::SQLITE_MIGRATIONS.len());
+        assert_eq!(fs::read(uploads.join("ab/abcdef")).unwrap(), b"attachment");
+        assert_eq!(fs::read(uploads.join(&long)).unwrap(), [7u8; 600]);
+        assert!(!uploads.join("incoming/staged").exists());
+        assert!(!uploads.join(".tmp-upload").exists());
+
+        let restored = Database::connect(&url).await.unwrap();
+        assert_eq!(user_count(&restored).await, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn backups_from_newer_schemas_are_refused() {
+        let dir = scratch("newer");
+        let (db, url) = live_database(&dir).await;
+        let info = create_backup(&db, &dir.join("uploads"), &dir.join("backups"))
+            .await
+            .unwrap();
+        // A second database that has seen a migration this build doesn't know.
+        sqlx::query("INSERT INTO _migrations (name) VALUES ('999_from_the_future')")
+            .execute(db.sqlite_pool().unwrap())
+            .await
+            .unwrap();
+        let newer = create_backup(&db, &dir.join("uploads"), &dir.join("newer"))
+            .await
+            .unwrap();
+        db.sqlite_pool().unwrap().close().await;
+
+        let err = restore_backup(
+            &dir.join("newer").join(&newer.name),
+            &url,
+            &dir.join("uploads"),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("999_from_the_future"), "{}", err);
+        assert!(!dir.join("rstify.db.restoring").exists());
+
+        // The live database was left alone, and the older backup still restores.
+        let live = pool::inspect_sqlite_file(&dir.join("rstify.db"))
+            .await
+            .unwrap();
+        assert!(live.contains(&"999_from_the_future".to_string()));
+        restore_backup(
+            &dir.join("backups").join(&info.name),
+            &url,
+            &dir.join("uploads"),
+        )
+        .await
+        .unwrap();
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn rejected_archives_leave_uploads_alone() {
+        let dir = scratch("rejected");
+        let staging = dir.join("staging");
+        fs::create_dir_all(staging.join("uploads")).unwrap();
+        fs::write(staging.join("uploads/blob"), b"from the backup").unwrap();
+        fs::write(staging.join("snapshot"), b"not a database").unwrap();
+        let manifest = BackupManifest {
+            format: BACKUP_FORMAT,
+            version: String::new(),
+            created_at: String::new(),
+            migrations: Vec::new(),
+            upload_files: 1,
+            upload_bytes: 15,
+        };
+        let archive = dir.join("archive.tar.gz");
+        write_archive(
+            &archive,
+            &manifest,
+            &staging.join("snapshot"),
+            &staging.join("uploads"),
+            &upload_files(&staging.join("uploads"), &dir.join("backups")).unwrap(),
+        )
+        .unwrap();
+
+        let url = format!("sqlite://{}", dir.join("rstify.db").display());
+        let err = restore_backup(&archive, &url, &dir.join("uploads"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unusable"), "{}", err);
+        assert!(!dir.join("uploads/blob").exists());
+        assert!(!dir.join("rstify.db.restoring").exists());
+        assert!(!dir.join("rstify.db.restoring-uploads").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn uploads_removed_mid_backup_are_skipped() {
+        let dir = scratch("vanished");
+        let uploads = dir.join("uploads");
+        fs::write(uploads.join("kept"), b"still here").unwrap();
+        fs::write(dir.join("snapshot"), b"db").unwrap();
+        let listed = |relative: &str| UploadFile {
+            relative: relative.to_string(),
+            size: 10,
+            mtime: 0,
+        };
+        let manifest = BackupManifest {
+            format: BACKUP_FORMAT,
+            version: String::new(),
+            created_at: String::new(),
+            migrations: Vec::new(),
+            upload_files: 2,
+            upload_bytes: 20,
+        };
+        let archive = dir.join("archive.tar.gz");
+        write_archive(
+            &archive,
+            &manifest,
+            &dir.join("snapshot"),
+            &uploads,
+            &[listed("gone"), listed("kept")],
+        )
+        .unwrap();
+
+        let mut tar = Archive::new(GzDecoder::new(File::open(&archive).unwrap()));
+        let paths: Vec<String> = tar
+            .entries()
+            .unwrap()
+            .map(|e| entry_path(&e.unwrap()).unwrap())
+            .collect();
+        assert_eq!(paths, [MANIFEST_ENTRY, DATABASE_ENTRY, "uploads/kept"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn rotation_keeps_the_newest() {
+        let dir = scratch("rotation");
+        for stamp in ["20260101-000000", "20260102-000000", "20260103-000000"] {
+            fs::write(dir.join(format!("rstify-backup-{}.tar.gz", stamp)), b"x").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), b"not a backup").unwrap();
+
+        assert_eq!(rotate_backups(&dir, 2).await.unwrap(), 1);
+        let names: Vec<String> = list_backups(&dir)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "rstify-backup-20260103-000000.tar.gz",
+                "rstify-backup-20260102-000000.tar.gz"
+            ]
+        );
+        assert!(dir.join("notes.txt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unsafe_paths_are_rejected() {
+        assert!(safe_relative_path("ab/cd/abcdef.png").is_some());
+        assert!(safe_relative_path("../etc/passwd").is_none());
+        assert!(safe_relative_path("/etc/passwd").is_none());
+        assert!(safe_relative_path("a/../../b").is_none());
+        assert!(safe_relative_path("").is_none());
+    }
+}
@@ -0,0 +1,582 @@
+//! Built-in outgoing webhook kinds that post service-native payloads to chat
+//! services instead of the raw message JSON. The kind is the webhook's
+//! `webhook_type`; `custom` keeps the method and `body_template` the user set.
+
+use crate::email::{escape_html, render_html_fragment};
+use rstify_core::models::{AttachmentInfo, MessageAction, MessageResponse};
+use serde_json::{json, Value};
+
+/// Accepted `webhook_type` values for outgoing webhooks. `relay` forwards to
+/// another notification server through its `notify_url` (see [`crate::relay`]).
+pub const OUTGOING_WEBHOOK_TYPES: &[&str] =
+    &["custom", "slack", "discord", "matrix", "telegram", "relay"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatKind {
+    /// Incoming webhook URL; Block Kit blocks in a colored attachment.
+    Slack,
+    /// Channel webhook URL; one embed.
+    Discord,
+    /// Client-server `send/m.room.message` URL; HTML `m.room.message`.
+    Matrix,
+    /// Bot API `sendMessage` URL with a `chat_id` query parameter; MarkdownV2.
+    Telegram,
+}
+
+/// A request rendered for a chat service.
+#[derive(Debug)]
+pub struct ChatRequest {
+    pub method: &'static str,
+    pub url: String,
+    pub body: String,
+}
+
+impl ChatKind {
+    /// The chat kind for a `webhook_type`, or `None` for a custom webhook.
+    pub fn from_webhook_type(webhook_type: &str) -> Option<Self> {
+        match webhook_type {
+            "slack" => Some(Self::Slack),
+            "discord" => Some(Self::Discord),
+            "matrix" => Some(Self::Matrix),
+            "telegram" => Some(Self::Telegram),
+            _ => None,
+        }
+    }
+
+    /// Check a literal target URL has what the service needs.
+    pub fn validate_url(self, url: &str) -> Result<(), String> {
+        match self {
+            Self::Telegram if !url.contains("chat_id=") => Err(
+                "Telegram webhooks need a chat_id query parameter, e.g. .../sendMessage?chat_id=123"
+                    .to_string(),
+            ),
+            Self::Matrix if !url.contains("/send/m.room.message") => Err(
+                "Matrix webhooks need a .../rooms/{roomId}/send/m.room.message URL".to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Build the request for one message. `webhook_id` keeps Matrix
+    /// transaction IDs distinct between webhooks posting to the same room.
+    pub fn request(self, url: &str, webhook_id: i64, message: &MessageResponse) -> ChatRequest {
+        match self {
+            Self::Slack => ChatRequest {
+                method: "POST",
+                url: url.to_string(),
+                body: slack_payload(message).to_string(),
+            },
+            Self::Discord => ChatRequest {
+                method: "POST",
+                url: url.to_string(),
+                body: discord_payload(message).to_string(),
+            },
+            Self::Matrix => ChatRequest {
+                method: "PUT",
+                url: matrix_url(url, webhook_id, message),
+                body: matrix_payload(message).to_string(),
+            },
+            Self::Telegram => ChatRequest {
+                method: "POST",
+                url: url.to_string(),
+                body: telegram_payload(url, message).to_string(),
+            },
+        }
+    }
+}
+
+/// RGB color for a priority: red for high, orange and blue for normal, grey
+/// for low.
+pub fn priority_color(priority: i32) -> u32 {
+    match priority {
+        8.. => 0xE53935,
+        6..=7 => 0xFB8C00,
+        4..=5 => 0x1E88E5,
+        _ => 0x9E9E9E,
+    }
+}
+
+fn hex_color(priority: i32) -> String {
+    format!("#{:06X}", priority_color(priority))
+}
+
+/// Links a chat service can open: the click URL and `view` actions.
+fn links(message: &MessageResponse) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    if let Some(ref url) = message.click_url {
+        links.push(("Open".to_string(), url.clone()));
+    }
+    for action in message.actions.iter().flatten() {
+        if let MessageAction::View { label, url, .. } = action {
+            links.push((label.clone(), url.clone()));
+        }
+    }
+    links
+}
+
+/// Attachments with absolute URLs (signed links need `BASE_URL`); others
+/// can only be named.
+pub(crate) fn attachment_url(attachment: &AttachmentInfo) -> Option<&str> {
+    (attachment.url.starts_with("https://") || attachment.url.starts_with("http://"))
+        .then_some(attachment.url.as_str())
+}
+
+fn is_image(attachment: &AttachmentInfo) -> bool {
+    attachment
+        .content_type
+        .as_deref()
+        .is_some_and(|t| t.starts_with("image/"))
+}
+
+/// "Priority 8 · backups · disk, urgent"
+fn summary_line(message: &MessageResponse) -> String {
+    let mut parts = vec![format!("Priority {}", message.priority)];
+    if let Some(ref topic) = message.topic {
+        parts.push(topic.clone());
+    }
+    if let Some(tags) = message.tags.as_ref().filter(|t| !t.is_empty()) {
+        parts.push(tags.join(", "));
+    }
+    parts.join(" · ")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn slack_payload(message: &MessageResponse) -> Value {
+    let title = message.title.as_deref().filter(|t| !t.is_empty());
+    let mut blocks = Vec::new();
+    if let Some(title) = title {
+        blocks.push(json!({
+            "type": "header",
+            "text": {"type": "plain_text", "text": truncate(title, 150)},
+        }));
+    }
+    blocks.push(json!({
+        "type": "section",
+        "text": {"type": "mrkdwn", "text": truncate(&slack_escape(&message.message), 3000)},
+    }));
+
+    let attachments = message.attachments.as_deref().unwrap_or_default();
+    for attachment in attachments.iter().filter(|a| is_image(a)) {
+        if let Some(url) = attachment_url(attachment) {
+            blocks.push(json!({
+                "type": "image",
+                "image_url": url,
+                "alt_text": attachment.name,
+            }));
+        }
+    }
+    let files: Vec<String> = attachments
+        .iter()
+        .map(|a| match attachment_url(a) {
+            Some(url) => format!("<{}|{}>", url, slack_escape(&a.name)),
+            None => slack_escape(&a.name),
+        })
+        .collect();
+    if !files.is_empty() {
+        blocks.push(json!({
+            "type": "section",
+            "text": {"type": "mrkdwn", "text": truncate(&format!("*Attachments:* {}", files.join(", ")), 3000)},
+        }));
+    }
+
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{"type": "mrkdwn", "text": slack_escape(&summary_line(message))}],
+    }));
+
+    let buttons: Vec<Value> = links(message)
+        .into_iter()
+        .take(25)
+        .map(|(label, url)| {
+            json!({
+                "type": "button",
+                "text": {"type": "plain_text", "text": truncate(&label, 75)},
+                "url": url,
+            })
+        })
+        .collect();
+    if !buttons.is_empty() {
+        blocks.push(json!({"type": "actions", "elements": buttons}));
+    }
+
+    let fallback = match title {
+        Some(title) => format!("{}: {}", title, message.message),
+        None => message.message.clone(),
+    };
+    json!({
+        "text": truncate(&fallback, 3000),
+        "attachments": [{"color": hex_color(message.priority), "blocks": blocks}],
+    })
+}
+
+/// Escape text so Discord's markdown shows it literally inside a link label.
+fn discord_label(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+fn discord_payload(message: &MessageResponse) -> Value {
+    let mut embed = json!({
+        "description": truncate(&message.message, 4096),
+        "color": priority_color(message.priority),
+        "footer": {"text": truncate(&summary_line(message), 2048)},
+        "timestamp": message.date,
+    });
+    if let Some(title) = message.title.as_deref().filter(|t| !t.is_empty()) {
+        embed["title"] = json!(truncate(title, 256));
+    }
+    if let Some(ref url) = message.click_url {
+        embed["url"] = json!(url);
+    }
+
+    let mut fields = Vec::new();
+    let attachments = message.attachments.as_deref().unwrap_or_default();
+    if let Some(url) = attachments
+        .iter()
+        .filter(|a| is_image(a))
+        .find_map(attachment_url)
+    {
+        embed["image"] = json!({"url": url});
+    }
+    if !attachments.is_empty() {
+        let files: Vec<String> = attachments
+            .iter()
+            .map(|a| match attachment_url(a) {
+                Some(url) => format!("[{}]({})", discord_label(&a.name), url),
+                None => a.name.clone(),
+            })
+            .collect();
+        fields.push(json!({"name": "Attachments", "value": truncate(&files.join("\n"), 1024)}));
+    }
+    let actions: Vec<String> = links(message)
+        .into_iter()
+        .filter(|(label, _)| label != "Open" || message.click_url.is_none())
+        .map(|(label, url)| format!("[{}]({})", discord_label(&label), url))
+        .collect();
+    if !actions.is_empty() {
+        fields.push(json!({"name": "Actions", "value": truncate(&actions.join(" · "), 1024)}));
+    }
+    if !fields.is_empty() {
+        embed["fields"] = json!(fields);
+    }
+    json!({"embeds": [embed]})
+}
+
+/// Append a transaction ID to a `.../send/m.room.message` URL. Retries of one
+/// message reuse it, so the homeserver drops duplicates.
+fn matrix_url(url: &str, webhook_id: i64, message: &MessageResponse) -> String {
+    let base = url.trim_end_matches('/');
+    if !base.ends_with("/send/m.room.message") {
+        return url.to_string();
+    }
+    let stamp: String = message
+        .date
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect();
+    format!("{}/rstify-{}-{}-{}", base, webhook_id, message.id, stamp)
+}
+
+fn matrix_payload(message: &MessageResponse) -> Value {
+    let title = message.title.as_deref().filter(|t| !t.is_empty());
+    let color = hex_color(message.priority);
+
+    let mut plain = String::new();
+    let mut html = String::new();
+    if let Some(title) = title {
+        plain.push_str(&format!("{}\n", title));
+        html.push_str(&format!(
+            "<h4><font data-mx-color=\"{}\">●</font> {}</h4>\n",
+            color,
+            escape_html(title)
+        ));
+    }
+    plain.push_str(&message.message);
+    html.push_str(&render_html_fragment(
+        &message.message,
+        message.is_markdown(),
+    ));
+
+    let attachments = message.attachments.as_deref().unwrap_or_default();
+    if !attachments.is_empty() {
+        plain.push_str("\n\nAttachments:");
+        let mut items = Vec::new();
+        for attachment in attachments {
+            match attachment_url(attachment) {
+                Some(url) => {
+                    plain.push_str(&format!("\n- {}: {}", attachment.name, url));
+                    items.push(format!(
+                        "<li><a href=\"{}\">{}</a></li>",
+                        escape_html(url),
+                        escape_html(&attachment.name)
+                    ));
+                }
+                None => {
+                    plain.push_str(&format!("\n- {}", attachment.name));
+                    items.push(format!("<li>{}</li>", escape_html(&attachment.name)));
+                }
+            }
+        }
+        html.push_str(&format!("<ul>{}</ul>\n", items.concat()));
+    }
+
+    let links = links(message);
+    if !links.is_empty() {
+        plain.push('\n');
+        let mut anchors = Vec::new();
+        for (label, url) in links {
+            plain.push_str(&format!("\n{}: {}", label, url));
+            anchors.push(format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(&url),
+                escape_html(&label)
+            ));
+        }
+        html.push_str(&format!("<p>{}</p>\n", anchors.join(" · ")));
+    }
+
+    let summary = summary_line(message);
+    plain.push_str(&format!("\n\n{}", summary));
+    html.push_str(&format!(
+        "<p><sub><font data-mx-color=\"{}\">{}</font></sub></p>",
+        color,
+        escape_html(&summary)
+    ));
+
+    json!({
+        // Notices don't trigger other bots.
+        "msgtype": "m.notice",
+        "body": plain,
+        "format": "org.matrix.custom.html",
+        "formatted_body": html,
+    })
+}
+
+/// Escape text for Telegram's MarkdownV2.
+fn telegram_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape a URL inside a MarkdownV2 `(...)` link target.
+fn telegram_escape_url(url: &str) -> String {
+    url.replace('\\', "\\\\").replace(')', "\\)")
+}
+
+/// A colored circle standing in for the priority color Telegram can't show.
+fn priority_marker(priority: i32) -> &'static str {
+    match priority {
+        8.. => "🔴",
+        6..=7 => "🟠",
+        4..=5 => "🔵",
+        _ => "⚪",
+    }
+}
+
+fn telegram_payload(url: &str, message: &MessageResponse) -> Value {
+    let mut text = String::new();
+    let marker = priority_marker(message.priority);
+    match message.title.as_deref().filter(|t| !t.is_empty()) {
+        Some(title) => text.push_str(&format!("{} *{}*\n", marker, telegram_escape(title))),
+        None => text.push_str(&format!("{} ", marker)),
+    }
+    text.push_str(&telegram_escape(&truncate(&message.message, 3500)));
+
+    let attachments = message.attachments.as_deref().unwrap_or_default();
+    if !attachments.is_empty() {
+        text.push_str("\n\n📎 ");
+        let files: Vec<String> = attachments
+            .iter()
+            .map(|a| match attachment_url(a) {
+                Some(url) => format!(
+                    "[{}]({})",
+                    telegram_escape(&a.name),
+                    telegram_escape_url(url)
+                ),
+                None => telegram_escape(&a.name),
+            })
+            .collect();
+        text.push_str(&files.join(", "));
+    }
+    text.push_str(&format!(
+        "\n\n_{}_",
+        telegram_escape(&summary_line(message))
+    ));
+
+    let mut payload = json!({
+        "text": text,
+        "parse_mode": "MarkdownV2",
+    });
+    if let Some(chat_id) = url::Url::parse(url).ok().and_then(|u| {
+        u.query_pairs()
+            .find(|(k, _)| k == "chat_id")
+            .map(|(_, v)| v.into_owned())
+    }) {
+        payload["chat_id"] = json!(chat_id);
+    }
+    let buttons: Vec<Value> = links(message)
+        .into_iter()
+        .map(|(label, url)| json!([{"text": label, "url": url}]))
+        .collect();
+    if !buttons.is_empty() {
+        payload["reply_markup"] = json!({"inline_keyboard": buttons});
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> MessageResponse {
+        MessageResponse {
+            id: 42,
+            appid: None,
+            topic: Some("backups".to_string()),
+            title: Some("Backup failed".to_string()),
+            message: "Disk <full> at 99.5%".to_string(),
+            priority: 8,
+            tags: Some(vec!["disk".to_string()]),
+            click_url: Some("https://status.example.com".to_string()),
+            icon_url: None,
+            actions: Some(vec![
+                MessageAction::View {
+                    label: "Logs".to_string(),
+                    url: "https://logs.example.com".to_string(),
+                    clear: None,
+                },
+                MessageAction::Broadcast {
+                    label: "Ignore".to_string(),
+                    intent: None,
+                    extras: None,
+                    clear: None,
+                },
+            ]),
+            extras: None,
+            content_type: None,
+            source: None,
+            inbox: true,
+            attachments: Some(vec![AttachmentInfo {
+                id: 1,
+                name: "df.png".to_string(),
+                content_type: Some("image/png".to_string()),
+                size: 10,
+                url: "https://push.example.com/api/attachments/1?sig=x".to_string(),
+                sha256: None,
+                width: None,
+                height: None,
+                thumbnail_url: None,
+            }]),
+            date: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_priority_color() {
+        assert_eq!(priority_color(10), 0xE53935);
+        assert_eq!(priority_color(5), 0x1E88E5);
+        assert_eq!(priority_color(0), 0x9E9E9E);
+        assert_eq!(hex_color(7), "#FB8C00");
+    }
+
+    #[test]
+    fn test_slack_payload() {
+        let payload = slack_payload(&message());
+        let attachment = &payload["attachments"][0];
+        assert_eq!(attachment["color"], "#E53935");
+        let blocks = attachment["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["text"]["text"], "Backup failed");
+        assert_eq!(blocks[1]["text"]["text"], "Disk &lt;full&gt; at 99.5%");
+        assert_eq!(blocks[2]["type"], "image");
+        let actions = blocks.last().unwrap()["elements"].as_array().unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[1]["url"], "https://logs.example.com");
+    }
+
+    #[test]
+    fn test_discord_payload() {
+        let payload = discord_payload(&message());
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["title"], "Backup failed");
+        assert_eq!(embed["color"], 0xE53935);
+        assert_eq!(embed["url"], "https://status.example.com");
+        assert_eq!(
+            embed["image"]["url"],
+            "https://push.example.com/api/attachments/1?sig=x"
+        );
+        assert_eq!(
+            embed["fields"][1]["value"],
+            "[Logs](https://logs.example.com)"
+        );
+    }
+
+    #[test]
+    fn test_matrix_request() {
+        let req = ChatKind::Matrix.request(
+            "https://matrix.example.org/_matrix/client/v3/rooms/!r:example.org/send/m.room.message",
+            3,
+            &message(),
+        );
+        assert_eq!(req.method, "PUT");
+        assert!(req
+            .url
+            .ends_with("/send/m.room.message/rstify-3-42-20260101000000"));
+        let body: Value = serde_json::from_str(&req.body).unwrap();
+        assert_eq!(body["format"], "org.matrix.custom.html");
+        let html = body["formatted_body"].as_str().unwrap();
+        assert!(html.contains("data-mx-color=\"#E53935\""));
+        assert!(html.contains("Disk &lt;full&gt;"));
+        assert!(html.contains("<a href=\"https://logs.example.com\">Logs</a>"));
+    }
+
+    #[test]
+    fn test_telegram_payload() {
+        let payload = telegram_payload(
+            "https://api.telegram.org/bot1:abc/sendMessage?chat_id=-100123",
+            &message(),
+        );
+        assert_eq!(payload["chat_id"], "-100123");
+        assert_eq!(payload["parse_mode"], "MarkdownV2");
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.starts_with("🔴 *Backup failed*\nDisk <full\\> at 99\\.5%"));
+        assert!(text.contains("[df\\.png](https://push.example.com/api/attachments/1?sig=x)"));
+        assert_eq!(
+            payload["reply_markup"]["inline_keyboard"][0][0]["url"],
+            "https://status.example.com"
+        );
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(ChatKind::Telegram
+            .validate_url("https://api.telegram.org/bot1:abc/sendMessage")
+            .is_err());
+        assert!(ChatKind::Matrix
+            .validate_url(
+                "https://matrix.example.org/_matrix/client/v3/rooms/!r/send/m.room.message"
+            )
+            .is_ok());
+        assert!(ChatKind::Slack
+            .validate_url("https://hooks.slack.com/services/x")
+            .is_ok());
+    }
+}
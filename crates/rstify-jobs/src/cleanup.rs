@@ -1,17 +1,20 @@
-use sqlx::SqlitePool;
+use rstify_core::error::CoreError;
+use rstify_core::repositories::Repositories;
+use rstify_storage::{blob_key, BlobStore, BlobStores};
 use std::collections::HashSet;
 use std::time::{Duration, SystemTime};
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
-/// Grace period before an unreferenced file is treated as an orphan. Protects an
-/// in-flight upload (file written just before its DB row is inserted).
+/// Grace period before an unreferenced blob is treated as an orphan. Protects an
+/// in-flight upload: re-uploading content whose blob was left orphaned rewrites
+/// (and so re-dates) that blob before the sweep could see the new row.
 const ORPHAN_GRACE: Duration = Duration::from_secs(3600);
 
 /// Background task that cleans up expired and orphaned attachments.
 pub async fn run_attachment_cleanup(
-    pool: SqlitePool,
-    upload_dir: Option<String>,
+    repos: Repositories,
+    stores: Option<BlobStores>,
     cancel: CancellationToken,
 ) {
     info!("Attachment cleanup worker started");
@@ -23,15 +26,19 @@ pub async fn run_attachment_cleanup(
                 break;
             }
             _ = tokio::time::sleep(std::time::Duration::from_secs(3600)) => {
-                if let Err(e) = cleanup_expired(&pool).await {
+                if let Err(e) = cleanup_expired(&repos, stores.as_ref()).await {
                     error!("Attachment cleanup error: {}", e);
                 }
-                // Sweep files left on disk when a message (and its attachment
+                // Sweep blobs left behind when a message (and its attachment
                 // rows via ON DELETE CASCADE) was deleted.
-                if let Some(ref dir) = upload_dir {
-                    match cleanup_orphan_files(&pool, dir, ORPHAN_GRACE).await {
-                        Ok(n) if n > 0 => info!("Removed {} orphaned attachment file(s)", n),
-                        Err(e) => error!("Orphan file sweep error: {}", e),
+                for store in stores.iter().flat_map(BlobStores::all) {
+                    match cleanup_orphan_blobs(&repos, store.as_ref(), ORPHAN_GRACE).await {
+                        Ok(n) if n > 0 => info!(
+                            "Removed {} orphaned attachment blob(s) from the {} store",
+                            n,
+                            store.kind()
+                        ),
+                        Err(e) => error!("Orphan sweep error ({} store): {}", store.kind(), e),
                         _ => {}
                     }
                 }
@@ -40,86 +47,61 @@ pub async fn run_attachment_cleanup(
     }
 }
 
-/// Remove attachment files on disk no longer referenced by any `local` row.
-async fn cleanup_orphan_files(
-    pool: &SqlitePool,
-    upload_dir: &str,
+/// Remove blobs in `store` no longer referenced by any attachment row of its type.
+async fn cleanup_orphan_blobs(
+    repos: &Repositories,
+    store: &dyn BlobStore,
     grace: Duration,
-) -> Result<u64, sqlx::Error> {
-    let rows: Vec<(String,)> =
-        sqlx::query_as("SELECT storage_path FROM attachments WHERE storage_type = 'local'")
-            .fetch_all(pool)
-            .await?;
-    let referenced: HashSet<String> = rows
-        .into_iter()
-        .filter_map(|(p,)| {
-            std::path::Path::new(&p)
-                .file_name()
-                .map(|n| n.to_string_lossy().into_owned())
+) -> Result<u64, CoreError> {
+    let referenced: HashSet<String> = repos
+        .messages
+        .list_attachments_by_storage_type(store.kind())
+        .await?
+        .iter()
+        .flat_map(|a| {
+            std::iter::once(blob_key(&a.storage_path).to_string()).chain(a.thumbnail_path.clone())
         })
         .collect();
 
-    Ok(sweep_orphan_files(upload_dir, &referenced, grace).await)
+    sweep_orphan_blobs(store, &referenced, grace).await
 }
 
-/// Delete top-level files in `dir` whose name is not in `referenced` and that are
-/// older than `grace`. Subdirectories (e.g. `icons/`) are skipped. Never fails —
-/// per-file errors are logged so one bad file can't abort the sweep.
-async fn sweep_orphan_files(dir: &str, referenced: &HashSet<String>, grace: Duration) -> u64 {
-    let mut read_dir = match tokio::fs::read_dir(dir).await {
-        Ok(d) => d,
-        Err(e) => {
-            warn!("Orphan sweep: cannot read upload dir {}: {}", dir, e);
-            return 0;
-        }
-    };
-
+/// Delete blobs whose key is not in `referenced` and that are older than `grace`.
+/// Per-blob delete errors are logged so one bad blob can't abort the sweep.
+async fn sweep_orphan_blobs(
+    store: &dyn BlobStore,
+    referenced: &HashSet<String>,
+    grace: Duration,
+) -> Result<u64, CoreError> {
     let now = SystemTime::now();
     let mut removed = 0u64;
 
-    loop {
-        let entry = match read_dir.next_entry().await {
-            Ok(Some(e)) => e,
-            Ok(None) => break,
-            Err(e) => {
-                warn!("Orphan sweep: error reading dir entry: {}", e);
-                break;
-            }
-        };
-        // Only top-level files; skip subdirectories like icons/.
-        match entry.file_type().await {
-            Ok(ft) if ft.is_file() => {}
-            _ => continue,
+    for entry in store.list().await? {
+        if referenced.contains(&entry.key) {
+            continue;
         }
-        // Grace period: skip anything recently written (possible in-flight upload)
-        // or with a future mtime (clock skew).
-        if let Ok(meta) = entry.metadata().await {
-            if let Ok(modified) = meta.modified() {
-                if now
-                    .duration_since(modified)
-                    .map(|a| a < grace)
-                    .unwrap_or(true)
-                {
-                    continue;
-                }
-            }
+        // Grace period: skip anything recently written (possible in-flight upload),
+        // with a future mtime (clock skew), or of unknown age.
+        let old_enough = entry
+            .modified
+            .and_then(|m| now.duration_since(m).ok())
+            .is_some_and(|age| age >= grace);
+        if !old_enough {
+            continue;
         }
-        let name = entry.file_name().to_string_lossy().into_owned();
-        if !referenced.contains(&name) {
-            match tokio::fs::remove_file(entry.path()).await {
-                Ok(()) => {
-                    removed += 1;
-                    info!("Removed orphaned attachment file {}", name);
-                }
-                Err(e) => warn!("Failed to remove orphan file {}: {}", name, e),
+        match store.delete(&entry.key).await {
+            Ok(()) => {
+                removed += 1;
+                info!("Removed orphaned attachment blob {}", entry.key);
             }
+            Err(e) => warn!("Failed to remove orphan blob {}: {}", entry.key, e),
         }
     }
-    removed
+    Ok(removed)
 }
 
 /// Background task that cleans up expired messages
-pub async fn run_message_cleanup(pool: SqlitePool, cancel: CancellationToken) {
+pub async fn run_message_cleanup(repos: Repositories, cancel: CancellationToken) {
     info!("Message cleanup worker started");
 
     loop {
@@ -129,7 +111,7 @@ pub async fn run_message_cleanup(pool: SqlitePool, cancel: CancellationToken) {
                 break;
             }
             _ = tokio::time::sleep(std::time::Duration::from_secs(300)) => {
-                match cleanup_expired_messages(&pool).await {
+                match repos.messages.delete_expired_messages().await {
                     Ok(count) if count > 0 => info!("Cleaned up {} expired messages", count),
                     Err(e) => error!("Message cleanup error: {}", e),
                     _ => {}
@@ -139,17 +121,8 @@ pub async fn run_message_cleanup(pool: SqlitePool, cancel: CancellationToken) {
     }
 }
 
-async fn cleanup_expired_messages(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query(
-        "DELETE FROM messages WHERE expires_at IS NOT NULL AND expires_at <= datetime('now')",
-    )
-    .execute(pool)
-    .await?;
-    Ok(result.rows_affected())
-}
-
 /// Background task that enforces per-app message retention policies
-pub async fn run_retention_cleanup(pool: SqlitePool, cancel: CancellationToken) {
+pub async fn run_retention_cleanup(repos: Repositories, cancel: CancellationToken) {
     info!("Retention cleanup worker started");
 
     loop {
@@ -159,7 +132,7 @@ pub async fn run_retention_cleanup(pool: SqlitePool, cancel: CancellationToken)
                 break;
             }
             _ = tokio::time::sleep(std::time::Duration::from_secs(3600)) => {
-                match cleanup_retention(&pool).await {
+                match repos.messages.delete_past_retention().await {
                     Ok(count) if count > 0 => info!("Retention cleanup removed {} old messages", count),
                     Err(e) => error!("Retention cleanup error: {}", e),
                     _ => {}
@@ -169,21 +142,12 @@ pub async fn run_retention_cleanup(pool: SqlitePool, cancel: CancellationToken)
     }
 }
 
-async fn cleanup_retention(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query(
-        r#"DELETE FROM messages WHERE application_id IN (
-            SELECT id FROM applications WHERE retention_days IS NOT NULL
-        ) AND created_at < datetime('now', '-' || (
-            SELECT retention_days FROM applications WHERE applications.id = messages.application_id
-        ) || ' days')"#,
-    )
-    .execute(pool)
-    .await?;
-    Ok(result.rows_affected())
-}
+/// How long webhook and push delivery log entries are kept.
+const DELIVERY_LOG_RETENTION_DAYS: i64 = 30;
 
-/// Background task that cleans up old webhook delivery logs (older than 30 days)
-pub async fn run_delivery_log_cleanup(pool: SqlitePool, cancel: CancellationToken) {
+/// Background task that cleans up old webhook and push delivery logs (older
+/// than 30 days)
+pub async fn run_delivery_log_cleanup(repos: Repositories, cancel: CancellationToken) {
     info!("Delivery log cleanup worker started");
 
     loop {
@@ -193,44 +157,138 @@ pub async fn run_delivery_log_cleanup(pool: SqlitePool, cancel: CancellationToke
                 break;
             }
             _ = tokio::time::sleep(std::time::Duration::from_secs(86400)) => {
-                match cleanup_old_delivery_logs(&pool).await {
+                match repos.webhook_deliveries.purge_older_than(DELIVERY_LOG_RETENTION_DAYS).await {
                     Ok(count) if count > 0 => info!("Cleaned up {} old delivery log entries", count),
                     Err(e) => error!("Delivery log cleanup error: {}", e),
                     _ => {}
                 }
+                match repos.clients.purge_push_deliveries_older_than(DELIVERY_LOG_RETENTION_DAYS).await {
+                    Ok(count) if count > 0 => info!("Cleaned up {} old push delivery log entries", count),
+                    Err(e) => error!("Push delivery log cleanup error: {}", e),
+                    _ => {}
+                }
+                match repos.emails.purge_older_than(DELIVERY_LOG_RETENTION_DAYS).await {
+                    Ok(count) if count > 0 => info!("Cleaned up {} old email queue entries", count),
+                    Err(e) => error!("Email queue cleanup error: {}", e),
+                    _ => {}
+                }
             }
         }
     }
 }
 
-async fn cleanup_old_delivery_logs(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query(
-        "DELETE FROM webhook_delivery_log WHERE attempted_at < datetime('now', '-30 days')",
-    )
-    .execute(pool)
-    .await?;
-    Ok(result.rows_affected())
+/// Background task that purges audit log entries older than the
+/// `audit_retention_days` setting.
+pub async fn run_audit_log_cleanup(repos: Repositories, cancel: CancellationToken) {
+    info!("Audit log cleanup worker started");
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Audit log cleanup worker shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(std::time::Duration::from_secs(86400)) => {
+                match purge_old_audit_entries(&repos).await {
+                    Ok(count) if count > 0 => info!("Purged {} old audit log entries", count),
+                    Err(e) => error!("Audit log cleanup error: {}", e),
+                    _ => {}
+                }
+            }
+        }
+    }
 }
 
-async fn cleanup_expired(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-    let expired = sqlx::query_as::<_, rstify_core::models::Attachment>(
-        "SELECT * FROM attachments WHERE expires_at IS NOT NULL AND expires_at <= datetime('now')",
-    )
-    .fetch_all(pool)
-    .await?;
+/// Delete audit entries past the retention window. A retention of 0 (or an
+/// unparsable value) keeps the log forever.
+pub async fn purge_old_audit_entries(repos: &Repositories) -> Result<u64, CoreError> {
+    let days = repos.settings.get("audit_retention_days").await?;
+    match days.and_then(|d| d.trim().parse::<i64>().ok()) {
+        Some(d) if d > 0 => repos.audit.purge_older_than(d).await,
+        _ => Ok(0),
+    }
+}
 
-    for attachment in expired {
-        // Delete file from disk
-        if attachment.storage_type == "local" {
-            if let Err(e) = tokio::fs::remove_file(&attachment.storage_path).await {
-                warn!("Failed to delete file {}: {}", attachment.storage_path, e);
+/// How far ahead of a client token's expiry its owner is warned.
+const CLIENT_EXPIRY_WARNING_DAYS: i64 = 3;
+
+/// Background task that warns owners about client tokens nearing expiry and
+/// purges tokens once they have expired.
+pub async fn run_client_token_cleanup(repos: Repositories, cancel: CancellationToken) {
+    info!("Client token cleanup worker started");
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Client token cleanup worker shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(std::time::Duration::from_secs(3600)) => {
+                match notify_expiring_client_tokens(&repos).await {
+                    Ok(count) if count > 0 => info!("Sent {} client token expiry warning(s)", count),
+                    Err(e) => error!("Client token expiry warning error: {}", e),
+                    _ => {}
+                }
+                match purge_expired_client_tokens(&repos).await {
+                    Ok(count) if count > 0 => info!("Purged {} expired client token(s)", count),
+                    Err(e) => error!("Client token purge error: {}", e),
+                    _ => {}
+                }
             }
         }
+    }
+}
+
+/// Delete client tokens whose expiry has passed.
+pub async fn purge_expired_client_tokens(repos: &Repositories) -> Result<u64, CoreError> {
+    repos.clients.delete_expired().await
+}
+
+/// Post an inbox warning for each client token expiring within the warning
+/// window that hasn't been warned about yet. Returns the number of warnings.
+pub async fn notify_expiring_client_tokens(repos: &Repositories) -> Result<u64, CoreError> {
+    let expiring = repos
+        .clients
+        .list_expiring_unnotified(CLIENT_EXPIRY_WARNING_DAYS)
+        .await?;
+
+    let mut sent = 0u64;
+    for client in expiring {
+        let message = format!(
+            "Client token '{}' expires at {} UTC. Create a new token and update the \
+             device or integration using it before then.",
+            client.name,
+            client.expires_at.as_deref().unwrap_or_default()
+        );
+        crate::notify::notify_user(
+            repos,
+            client.user_id,
+            "Client token expiring soon",
+            &message,
+            7,
+        )
+        .await?;
+        repos.clients.mark_expiry_notified(client.id).await?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+async fn cleanup_expired(
+    repos: &Repositories,
+    stores: Option<&BlobStores>,
+) -> Result<(), CoreError> {
+    let expired = repos.messages.list_expired_attachments().await?;
 
-        sqlx::query("DELETE FROM attachments WHERE id = ?")
-            .bind(attachment.id)
-            .execute(pool)
-            .await?;
+    for attachment in expired {
+        // Drop the row first: the blob goes only once nothing references it.
+        repos.messages.delete_attachment(attachment.id).await?;
+
+        if let Some(stores) = stores {
+            if let Err(e) = stores.release(repos.messages.as_ref(), &attachment).await {
+                warn!("Failed to delete blob {}: {}", attachment.storage_path, e);
+            }
+        }
 
         info!("Cleaned up expired attachment {}", attachment.id);
     }
@@ -241,6 +299,7 @@ async fn cleanup_expired(pool: &SqlitePool) -> Result<(), sqlx::Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstify_storage::LocalBlobStore;
 
     #[tokio::test]
     async fn sweep_removes_orphans_keeps_referenced_and_subdirs() {
@@ -264,7 +323,10 @@ mod tests {
         referenced.insert("keep.bin".to_string());
 
         // grace = ZERO so the freshly written files are eligible.
-        let removed = sweep_orphan_files(dir.to_str().unwrap(), &referenced, Duration::ZERO).await;
+        let store = LocalBlobStore::new(&dir);
+        let removed = sweep_orphan_blobs(&store, &referenced, Duration::ZERO)
+            .await
+            .unwrap();
 
         assert_eq!(removed, 1, "exactly the orphan should be removed");
         assert!(dir.join("keep.bin").exists(), "referenced file kept");
@@ -283,12 +345,10 @@ mod tests {
 
         // Large grace: the just-written file is within the window, so it is kept
         // even though it is unreferenced (guards against racing an in-flight upload).
-        let removed = sweep_orphan_files(
-            dir.to_str().unwrap(),
-            &HashSet::new(),
-            Duration::from_secs(3600),
-        )
-        .await;
+        let store = LocalBlobStore::new(&dir);
+        let removed = sweep_orphan_blobs(&store, &HashSet::new(), Duration::from_secs(3600))
+            .await
+            .unwrap();
 
         assert_eq!(removed, 0);
         assert!(dir.join("fresh.bin").exists());
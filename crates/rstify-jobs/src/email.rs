@@ -1,67 +1,359 @@
+//! Outgoing mail. A [`Mailer`] holds one pooled SMTP transport for the life of
+//! the server; an [`EmailOutbox`] queues messages in the database so the
+//! [`run_email_worker`] loop can retry them when the relay is unavailable.
+
 use lettre::message::header::ContentType;
+use lettre::message::{Mailbox, MultiPart};
 use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::PoolConfig;
 use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use rstify_core::error::CoreError;
+use rstify_core::models::EmailDelivery;
+use rstify_core::repositories::{EmailRepository, NewEmail};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
+/// Attempts before a queued email is given up on.
+pub const MAX_ATTEMPTS: i32 = 5;
+
+const BATCH_SIZE: i64 = 50;
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 3600;
+const POOL_MAX_SIZE: u32 = 4;
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587).
+    StartTls,
+    /// TLS from the first byte (usually port 465).
+    Tls,
+    /// No encryption, for a relay on localhost or a trusted network.
+    Plain,
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "starttls" => Ok(Self::StartTls),
+            "tls" | "ssl" => Ok(Self::Tls),
+            "none" | "plain" => Ok(Self::Plain),
+            other => Err(format!(
+                "unknown SMTP security '{}' (expected starttls, tls or none)",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct EmailConfig {
     pub host: String,
     pub port: u16,
-    pub username: String,
-    pub password: String,
+    pub security: SmtpSecurity,
+    /// Log in with these when set; local relays often need no auth.
+    pub credentials: Option<(String, String)>,
     pub from: String,
 }
 
-impl EmailConfig {
-    pub fn new(host: String, port: u16, username: String, password: String, from: String) -> Self {
+/// Why a send failed, and whether trying again could help.
+#[derive(Debug, Clone)]
+pub struct SendError {
+    pub message: String,
+    /// The server rejected the message outright (5xx), or it could not be built.
+    pub permanent: bool,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl SendError {
+    fn permanent(message: impl Into<String>) -> Self {
         Self {
-            host,
-            port,
-            username,
-            password,
-            from,
+            message: message.into(),
+            permanent: true,
         }
     }
 }
 
-pub async fn send_email(config: &EmailConfig, to: &str, subject: &str, body: &str) {
-    let email = match Message::builder()
-        .from(
-            config
-                .from
-                .parse()
-                .unwrap_or_else(|_| "rstify@localhost".parse().unwrap()),
-        )
-        .to(match to.parse() {
-            Ok(addr) => addr,
-            Err(e) => {
-                warn!("Invalid email address '{}': {}", to, e);
-                return;
+/// A pooled SMTP transport, built once and shared by every send.
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(config: &EmailConfig) -> Result<Self, String> {
+        let builder = match config.security {
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
             }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            SmtpSecurity::Plain => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )),
+        }
+        .map_err(|e| format!("invalid SMTP host '{}': {}", config.host, e))?;
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_secs(30)))
+            .pool_config(PoolConfig::new().max_size(POOL_MAX_SIZE));
+        if let Some((username, password)) = &config.credentials {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        let from = config
+            .from
+            .parse()
+            .map_err(|e| format!("invalid SMTP_FROM '{}': {}", config.from, e))?;
+        Ok(Self {
+            transport: builder.build(),
+            from,
         })
-        .subject(subject)
-        .header(ContentType::TEXT_PLAIN)
-        .body(body.to_string())
-    {
-        Ok(email) => email,
-        Err(e) => {
-            error!("Failed to build email: {}", e);
-            return;
+    }
+
+    /// Send one email now. With an HTML body it goes out as
+    /// `multipart/alternative` with the text as the plain part.
+    pub async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        text: &str,
+        html: Option<&str>,
+    ) -> Result<(), SendError> {
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| SendError::permanent(format!("invalid address '{}': {}", to, e)))?;
+        let builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject);
+        let message = match html {
+            Some(html) => builder.multipart(MultiPart::alternative_plain_html(
+                text.to_string(),
+                html.to_string(),
+            )),
+            None => builder
+                .header(ContentType::TEXT_PLAIN)
+                .body(text.to_string()),
+        }
+        .map_err(|e| SendError::permanent(format!("failed to build email: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| SendError {
+                message: e.to_string(),
+                permanent: e.is_permanent(),
+            })
+    }
+}
+
+/// The persistent mail queue. Cheap to clone.
+#[derive(Clone)]
+pub struct EmailOutbox {
+    repo: Arc<dyn EmailRepository>,
+    mailer: Arc<Mailer>,
+    wake: Arc<Notify>,
+}
+
+impl EmailOutbox {
+    pub fn new(repo: Arc<dyn EmailRepository>, mailer: Mailer) -> Self {
+        Self {
+            repo,
+            mailer: Arc::new(mailer),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// The transport, for sends that report their result to the caller
+    /// instead of going through the queue.
+    pub fn mailer(&self) -> &Mailer {
+        &self.mailer
+    }
+
+    /// Queue an email and wake the worker.
+    pub async fn enqueue(
+        &self,
+        to: &str,
+        subject: &str,
+        text: &str,
+        html: Option<&str>,
+    ) -> Result<EmailDelivery, CoreError> {
+        let queued = self
+            .repo
+            .enqueue(&NewEmail {
+                recipient: to,
+                subject,
+                text_body: text,
+                html_body: html,
+            })
+            .await?;
+        self.wake.notify_one();
+        Ok(queued)
+    }
+}
+
+/// Delay before retry number `attempts`: 30s, doubling, capped at an hour.
+fn retry_delay_secs(attempts: i32) -> i64 {
+    let exp = (attempts - 1).clamp(0, 16) as u32;
+    (RETRY_BASE_SECS << exp).min(RETRY_MAX_SECS)
+}
+
+/// Try every email that is due. Returns how many were sent.
+pub async fn process_queue(outbox: &EmailOutbox) -> Result<u64, CoreError> {
+    let mut sent = 0u64;
+    loop {
+        let due = outbox.repo.list_due(BATCH_SIZE).await?;
+        if due.is_empty() {
+            return Ok(sent);
         }
-    };
+        for email in due {
+            let attempts = email.attempts + 1;
+            match outbox
+                .mailer
+                .send(
+                    &email.recipient,
+                    &email.subject,
+                    &email.text_body,
+                    email.html_body.as_deref(),
+                )
+                .await
+            {
+                Ok(()) => {
+                    info!("Email {} sent to {}", email.id, email.recipient);
+                    outbox.repo.mark_sent(email.id, attempts).await?;
+                    sent += 1;
+                }
+                Err(e) if e.permanent || attempts >= MAX_ATTEMPTS => {
+                    error!(
+                        "Giving up on email {} to {} after {} attempt(s): {}",
+                        email.id, email.recipient, attempts, e
+                    );
+                    outbox
+                        .repo
+                        .mark_failed(email.id, attempts, &e.message)
+                        .await?;
+                }
+                Err(e) => {
+                    let delay = retry_delay_secs(attempts);
+                    warn!(
+                        "Email {} to {} failed (attempt {}), retrying in {}s: {}",
+                        email.id, email.recipient, attempts, delay, e
+                    );
+                    outbox
+                        .repo
+                        .mark_retry(email.id, attempts, &e.message, delay)
+                        .await?;
+                }
+            }
+        }
+    }
+}
 
-    let creds = Credentials::new(config.username.clone(), config.password.clone());
+/// Background task that sends queued email. [`EmailOutbox::enqueue`] wakes
+/// it; it also polls for retries that have come due.
+pub async fn run_email_worker(outbox: EmailOutbox, cancel: CancellationToken) {
+    info!("Email worker started");
 
-    let mailer = match AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host) {
-        Ok(builder) => builder.port(config.port).credentials(creds).build(),
-        Err(e) => {
-            error!("Failed to create SMTP transport: {}", e);
-            return;
+    loop {
+        if let Err(e) = process_queue(&outbox).await {
+            error!("Email worker error: {}", e);
         }
-    };
 
-    match mailer.send(email).await {
-        Ok(_) => info!("Email sent to {}", to),
-        Err(e) => error!("Failed to send email to {}: {}", to, e),
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Email worker shutting down");
+                break;
+            }
+            _ = outbox.wake.notified() => {}
+            _ = tokio::time::sleep(Duration::from_secs(30)) => {}
+        }
+    }
+}
+
+/// Render message text as an HTML email body. Markdown is converted, with any
+/// raw HTML in it escaped; plain text keeps its line breaks.
+pub fn render_html(text: &str, markdown: bool) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><body style=\"font-family: sans-serif; line-height: 1.5;\">\n{}</body></html>\n",
+        render_html_fragment(text, markdown)
+    )
+}
+
+/// The HTML for message text on its own, without a surrounding document.
+pub fn render_html_fragment(text: &str, markdown: bool) -> String {
+    if markdown {
+        let events =
+            pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all()).map(|event| {
+                match event {
+                    pulldown_cmark::Event::Html(raw) | pulldown_cmark::Event::InlineHtml(raw) => {
+                        pulldown_cmark::Event::Text(raw)
+                    }
+                    other => other,
+                }
+            });
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events);
+        html
+    } else {
+        format!("<p>{}</p>", escape_html(text).replace('\n', "<br>\n"))
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smtp_security_from_str() {
+        assert_eq!("STARTTLS".parse(), Ok(SmtpSecurity::StartTls));
+        assert_eq!("ssl".parse(), Ok(SmtpSecurity::Tls));
+        assert_eq!("none".parse(), Ok(SmtpSecurity::Plain));
+        assert!("maybe".parse::<SmtpSecurity>().is_err());
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay_secs(1), 30);
+        assert_eq!(retry_delay_secs(2), 60);
+        assert_eq!(retry_delay_secs(4), 240);
+        assert_eq!(retry_delay_secs(20), 3600);
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html("**disk** full\n\n<script>x</script>", true);
+        assert!(html.contains("<strong>disk</strong>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+
+        let html = render_html("a < b\nnext", false);
+        assert!(html.contains("<p>a &lt; b<br>\nnext</p>"));
     }
 }
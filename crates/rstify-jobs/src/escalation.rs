@@ -0,0 +1,186 @@
+//! Escalation of unacknowledged high-priority topic messages.
+//!
+//! Publishing a message that meets its topic's [`EscalationPolicy`] starts an
+//! escalation ([`begin`]). This worker runs each step as it falls due, until
+//! someone acknowledges the message (`POST /api/messages/{id}/ack`) or the
+//! steps run out, and records each one in the message's extras under
+//! [`ESCALATION_EXTRA`].
+
+use chrono::{DateTime, Duration, Utc};
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    EscalationPolicy, EscalationStep, MessageEscalation, MessageResponse, Topic,
+};
+use rstify_core::repositories::Repositories;
+use serde_json::json;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+pub use rstify_core::models::{ACK_EXTRA, ESCALATION_EXTRA};
+
+/// Callback that carries out one step for a message and describes the
+/// outcome (e.g. "pushed to 2 devices") for the record. Push, email and
+/// topic access checks live with the API state, so the server supplies it.
+pub type EscalationStepFn = Arc<
+    dyn Fn(Topic, EscalationStep, MessageResponse) -> Pin<Box<dyn Future<Output = String> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Database timestamp format, as `datetime('now')` writes it.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn after(now: DateTime<Utc>, step: &EscalationStep) -> DateTime<Utc> {
+    now + Duration::minutes(step.after_minutes.into())
+}
+
+/// Start escalating a freshly delivered topic message if the topic has a
+/// policy and the message is urgent enough. Returns whether it did.
+pub async fn begin(
+    repos: &Repositories,
+    topic: &Topic,
+    message: &MessageResponse,
+    now: DateTime<Utc>,
+) -> Result<bool, CoreError> {
+    let Some(policy) = topic.escalation() else {
+        return Ok(false);
+    };
+    let Some(first) = policy.steps.first() else {
+        return Ok(false);
+    };
+    if message.id <= 0 || message.priority < policy.min_priority {
+        return Ok(false);
+    }
+    let snapshot =
+        serde_json::to_string(&policy).map_err(|e| CoreError::Internal(e.to_string()))?;
+    repos
+        .messages
+        .start_escalation(
+            message.id,
+            topic.id,
+            &snapshot,
+            &timestamp(after(now, first)),
+        )
+        .await?;
+    Ok(true)
+}
+
+pub async fn run_escalations(
+    repos: Repositories,
+    run_step: EscalationStepFn,
+    cancel: CancellationToken,
+) {
+    info!("Escalation worker started");
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Escalation worker shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(std::time::Duration::from_secs(30)) => {
+                if let Err(e) = run_due_escalations(&repos, &run_step, Utc::now()).await {
+                    error!("Escalation error: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Run every escalation step due at `now`. Returns the number of steps run.
+pub async fn run_due_escalations(
+    repos: &Repositories,
+    run_step: &EscalationStepFn,
+    now: DateTime<Utc>,
+) -> Result<usize, CoreError> {
+    let mut ran = 0;
+    for escalation in repos.messages.list_due_escalations(&timestamp(now)).await? {
+        if run_next_step(repos, run_step, &escalation, now).await? {
+            ran += 1;
+        }
+    }
+    Ok(ran)
+}
+
+async fn run_next_step(
+    repos: &Repositories,
+    run_step: &EscalationStepFn,
+    escalation: &MessageEscalation,
+    now: DateTime<Utc>,
+) -> Result<bool, CoreError> {
+    let index = escalation.next_step;
+    let step = serde_json::from_str::<EscalationPolicy>(&escalation.policy)
+        .ok()
+        .and_then(|policy| {
+            let step = policy.steps.get(index as usize)?.clone();
+            let next = policy.steps.get(index as usize + 1).map(|n| after(now, n));
+            Some((step, next))
+        });
+    let Some((step, next_at)) = step else {
+        // Unreadable or exhausted: finish it rather than retrying forever.
+        repos
+            .messages
+            .claim_escalation_step(escalation.message_id, index, None)
+            .await?;
+        return Ok(false);
+    };
+    // Claim before running, so an acknowledgement that lands first wins.
+    if !repos
+        .messages
+        .claim_escalation_step(
+            escalation.message_id,
+            index,
+            next_at.map(timestamp).as_deref(),
+        )
+        .await?
+    {
+        return Ok(false);
+    }
+    let Some(topic) = repos.topics.find_by_id(escalation.topic_id).await? else {
+        return Ok(false);
+    };
+    let Some(message) = repos.messages.find_by_id(escalation.message_id).await? else {
+        return Ok(false);
+    };
+
+    let outcome = run_step(
+        topic.clone(),
+        step.clone(),
+        message.to_response(Some(topic.name)),
+    )
+    .await;
+    info!(
+        "Escalated message {} (step {}): {}",
+        message.id,
+        index + 1,
+        outcome
+    );
+
+    let mut entry = json!({
+        "step": index + 1,
+        "action": step.action,
+        "at": now.to_rfc3339(),
+        "result": outcome,
+    });
+    if let Some(user_id) = step.user_id {
+        entry["user_id"] = json!(user_id);
+    }
+    if let Some(group_id) = step.group_id {
+        entry["group_id"] = json!(group_id);
+    }
+    if let Err(e) = repos
+        .messages
+        .record_escalation_step(message.id, &entry.to_string())
+        .await
+    {
+        warn!(
+            "Failed to record escalation step on message {}: {}",
+            message.id, e
+        );
+    }
+    Ok(true)
+}
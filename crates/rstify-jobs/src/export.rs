@@ -0,0 +1,792 @@
+//! JSON export and import of users, applications, clients, topics, grants,
+//! webhooks, webhook variables, messages and attachments, for moving data
+//! between instances.
+//!
+//! Imports remap every id and match existing records by name instead of
+//! duplicating them: users by username, topics by name, and a user's
+//! applications, clients and webhooks by name and variables by key. Messages
+//! have no name and are always added.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    Attachment, ExportApplication, ExportClient, ExportDocument, ExportFile, ExportMessage,
+    ExportPermission, ExportTopic, ExportUser, ExportWebhook, ExportWebhookVariable, ImportReport,
+    Message, User, EXPORT_FORMAT, EXPORT_VERSION,
+};
+use rstify_core::repositories::{NewMessage, Repositories};
+use rstify_storage::{blob_key, content_hash, BlobStores};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use tracing::warn;
+
+/// Icons are imported only with these extensions, matching what the icon
+/// upload endpoint accepts.
+const ICON_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+/// Attachment rows are looked up for this many messages at a time.
+const ATTACHMENT_BATCH: usize = 500;
+
+/// What to export.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportOptions {
+    /// Only this user's data; everything when `None`.
+    pub user_id: Option<i64>,
+    /// Embed attachment and icon content. Without it the document only
+    /// carries messages.
+    pub include_files: bool,
+}
+
+fn new_token(prefix: &str) -> String {
+    format!("{}_{}", prefix, uuid::Uuid::new_v4().simple())
+}
+
+/// A `skipped` entry for an application or client whose imported token was not
+/// carried over, so devices still using it need reconfiguring.
+fn token_not_kept(kind: &str, name: &str, reason: &str) -> String {
+    format!(
+        "token of {} '{}': {}; devices using the imported token must be reconfigured",
+        kind, name, reason
+    )
+}
+
+pub(crate) fn export_file(filename: &str, content_type: Option<&str>, data: &[u8]) -> ExportFile {
+    ExportFile {
+        filename: filename.to_string(),
+        content_type: content_type.map(str::to_string),
+        sha256: content_hash(data),
+        data: BASE64.encode(data),
+    }
+}
+
+/// Build an export document. The whole document, including embedded files,
+/// is held in memory.
+pub async fn export_data(
+    repos: &Repositories,
+    stores: &BlobStores,
+    upload_dir: &Path,
+    options: ExportOptions,
+) -> Result<ExportDocument, CoreError> {
+    let users: Vec<User> = match options.user_id {
+        Some(id) => vec![repos
+            .users
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("User {} not found", id)))?],
+        None => repos.users.list_all().await?,
+    };
+    let full = options.user_id.is_none();
+
+    let mut doc = ExportDocument {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        user: (!full).then(|| users[0].username.clone()),
+        users: Vec::new(),
+        applications: Vec::new(),
+        clients: Vec::new(),
+        topics: Vec::new(),
+        permissions: Vec::new(),
+        webhooks: Vec::new(),
+        webhook_variables: Vec::new(),
+        messages: Vec::new(),
+    };
+    let mut messages: BTreeMap<i64, Message> = BTreeMap::new();
+
+    for user in &users {
+        doc.users.push(ExportUser {
+            id: user.id,
+            username: user.username.clone(),
+            email: user.email.clone(),
+            is_admin: user.is_admin,
+            password_hash: full.then(|| user.password_hash.clone()),
+        });
+
+        for app in repos.applications.list_by_user(user.id).await? {
+            let icon = match (&app.image, options.include_files) {
+                (Some(image), true) => match tokio::fs::read(upload_dir.join(image)).await {
+                    Ok(data) => Some(export_file(image, None, &data)),
+                    Err(e) => {
+                        warn!("Icon of application {} not exported: {}", app.id, e);
+                        None
+                    }
+                },
+                _ => None,
+            };
+            for message in repos
+                .messages
+                .list_by_application(app.id, i64::MAX, 0)
+                .await?
+            {
+                messages.insert(message.id, message);
+            }
+            doc.applications.push(ExportApplication {
+                id: app.id,
+                user_id: app.user_id,
+                name: app.name,
+                description: app.description,
+                token: app.token,
+                default_priority: app.default_priority,
+                retention_days: app.retention_days,
+                icon,
+            });
+        }
+
+        for client in repos.clients.list_by_user(user.id).await? {
+            doc.clients.push(ExportClient {
+                user_id: client.user_id,
+                name: client.name,
+                token: client.token,
+                scopes: client.scopes,
+                expires_at: client.expires_at,
+                allowed_ips: client.allowed_ips,
+            });
+        }
+
+        for webhook in repos.messages.list_webhook_configs_by_user(user.id).await? {
+            doc.webhooks.push(ExportWebhook {
+                user_id: webhook.user_id,
+                name: webhook.name,
+                token: webhook.token,
+                webhook_type: webhook.webhook_type,
+                target_topic_id: webhook.target_topic_id,
+                target_application_id: webhook.target_application_id,
+                template: webhook.template,
+                enabled: webhook.enabled,
+                direction: webhook.direction,
+                target_url: webhook.target_url,
+                http_method: webhook.http_method,
+                headers: webhook.headers,
+                body_template: webhook.body_template,
+                max_retries: webhook.max_retries,
+                retry_delay_secs: webhook.retry_delay_secs,
+                timeout_secs: webhook.timeout_secs,
+                follow_redirects: webhook.follow_redirects,
+                group_name: webhook.group_name,
+                secret: webhook.secret,
+                notify_url: webhook.notify_url,
+            });
+        }
+
+        for variable in repos
+            .webhook_variables
+            .list_webhook_variables(user.id)
+            .await?
+        {
+            doc.webhook_variables.push(ExportWebhookVariable {
+                user_id: variable.user_id,
+                key: variable.key,
+                value: variable.value,
+            });
+        }
+    }
+
+    for topic in repos.topics.list_all().await? {
+        if !full && topic.owner_id != options.user_id {
+            continue;
+        }
+        for message in repos.messages.list_by_topic(topic.id, i64::MAX, 0).await? {
+            messages.insert(message.id, message);
+        }
+        doc.topics.push(ExportTopic {
+            id: topic.id,
+            name: topic.name,
+            owner_id: topic.owner_id,
+            description: topic.description,
+            everyone_read: topic.everyone_read,
+            everyone_write: topic.everyone_write,
+            notify_policy: topic.notify_policy,
+            notify_priority_min: topic.notify_priority_min,
+            notify_condition: topic.notify_condition,
+            notify_digest_interval: topic.notify_digest_interval,
+            store_policy: topic.store_policy,
+            store_interval: topic.store_interval,
+            inbox_override: topic.inbox_override,
+            inbox_priority_min: topic.inbox_priority_min,
+        });
+    }
+
+    if full {
+        // Group grants are left out along with the groups themselves.
+        for permission in repos.topics.list_all_permissions().await? {
+            if let Some(user_id) = permission.user_id {
+                doc.permissions.push(ExportPermission {
+                    user_id,
+                    topic_pattern: permission.topic_pattern,
+                    can_read: permission.can_read,
+                    can_write: permission.can_write,
+                });
+            }
+        }
+    }
+
+    let mut attachments: HashMap<i64, Vec<Attachment>> = HashMap::new();
+    if options.include_files {
+        let ids: Vec<i64> = messages.keys().copied().collect();
+        for chunk in ids.chunks(ATTACHMENT_BATCH) {
+            for attachment in repos.messages.list_attachments_by_messages(chunk).await? {
+                attachments
+                    .entry(attachment.message_id)
+                    .or_default()
+                    .push(attachment);
+            }
+        }
+    }
+
+    for (id, message) in messages {
+        let mut files = Vec::new();
+        for attachment in attachments.remove(&id).unwrap_or_default() {
+            let Some(store) = stores.for_type(&attachment.storage_type) else {
+                continue;
+            };
+            match store.get(blob_key(&attachment.storage_path)).await {
+                Ok(data) => files.push(export_file(
+                    &attachment.filename,
+                    attachment.content_type.as_deref(),
+                    &data,
+                )),
+                Err(e) => warn!("Attachment {} not exported: {}", attachment.id, e),
+            }
+        }
+        doc.messages.push(ExportMessage {
+            application_id: message.application_id,
+            topic_id: message.topic_id,
+            user_id: message.user_id,
+            title: message.title,
+            message: message.message,
+            priority: message.priority,
+            tags: message.tags,
+            click_url: message.click_url,
+            icon_url: message.icon_url,
+            actions: message.actions,
+            extras: message.extras,
+            content_type: message.content_type,
+            // A delivered scheduled message would otherwise be sent again.
+            scheduled_for: message
+                .scheduled_for
+                .filter(|_| message.delivered_at.is_none()),
+            source: message.source,
+            inbox: message.inbox,
+            created_at: message.created_at,
+            attachments: files,
+        });
+    }
+
+    Ok(doc)
+}
+
+/// Decode an embedded file and check it against its recorded hash.
+fn decode_file(file: &ExportFile) -> Result<Vec<u8>, String> {
+    let data = BASE64
+        .decode(file.data.as_bytes())
+        .map_err(|e| format!("invalid base64: {}", e))?;
+    if content_hash(&data) != file.sha256.to_ascii_lowercase() {
+        return Err("content does not match its sha256".to_string());
+    }
+    Ok(data)
+}
+
+/// Rewrite `app:<id>` scopes to the imported application ids, dropping scopes
+/// for applications that were not imported.
+fn remap_scopes(scopes: &str, apps: &HashMap<i64, i64>) -> String {
+    let Ok(list) = serde_json::from_str::<Vec<String>>(scopes) else {
+        return scopes.to_string();
+    };
+    let remapped: Vec<String> = list
+        .into_iter()
+        .filter_map(|scope| match scope.strip_prefix("app:") {
+            Some(id) => id
+                .parse::<i64>()
+                .ok()
+                .and_then(|id| apps.get(&id))
+                .map(|new| format!("app:{}", new)),
+            None => Some(scope),
+        })
+        .collect();
+    serde_json::to_string(&remapped).unwrap_or_else(|_| scopes.to_string())
+}
+
+/// Import a document. With `into_user`, everything in it is assigned to that
+/// user: users and grants are not created, and existing topics owned by
+/// someone else are left alone. Without it, users are created or matched by
+/// username.
+pub async fn import_data(
+    repos: &Repositories,
+    stores: &BlobStores,
+    upload_dir: &Path,
+    doc: &ExportDocument,
+    into_user: Option<i64>,
+) -> Result<ImportReport, CoreError> {
+    if doc.format != EXPORT_FORMAT {
+        return Err(CoreError::Validation(format!(
+            "Not an rstify export (format '{}')",
+            doc.format
+        )));
+    }
+    if doc.version > EXPORT_VERSION {
+        return Err(CoreError::Validation(format!(
+            "Export version {} is newer than this server supports ({})",
+            doc.version, EXPORT_VERSION
+        )));
+    }
+
+    let mut report = ImportReport::default();
+    let mut users: HashMap<i64, i64> = HashMap::new();
+    let mut apps: HashMap<i64, i64> = HashMap::new();
+    let mut topics: HashMap<i64, i64> = HashMap::new();
+
+    // --- Users ---
+    if let Some(target) = into_user {
+        for user in &doc.users {
+            users.insert(user.id, target);
+        }
+    } else {
+        for user in &doc.users {
+            if let Some(existing) = repos.users.find_by_username(&user.username).await? {
+                users.insert(user.id, existing.id);
+                report.users.matched += 1;
+                continue;
+            }
+            let Some(hash) = user.password_hash.as_deref() else {
+                report
+                    .skipped
+                    .push(format!("user '{}': no password hash", user.username));
+                continue;
+            };
+            let created = repos
+                .users
+                .create(&user.username, hash, user.email.as_deref(), user.is_admin)
+                .await?;
+            users.insert(user.id, created.id);
+            report.users.created += 1;
+        }
+    }
+    let owner = |id: i64| into_user.or_else(|| users.get(&id).copied());
+
+    // --- Applications ---
+    for app in &doc.applications {
+        let Some(user_id) = owner(app.user_id) else {
+            report.skipped.push(format!(
+                "application '{}': owner was not imported",
+                app.name
+            ));
+            continue;
+        };
+        if let Some(existing) = repos
+            .applications
+            .find_by_user_and_name(user_id, &app.name)
+            .await?
+        {
+            if existing.token != app.token {
+                report.skipped.push(token_not_kept(
+                    "application",
+                    &app.name,
+                    "an application of that name exists and keeps its own",
+                ));
+            }
+            apps.insert(app.id, existing.id);
+            report.applications.matched += 1;
+            continue;
+        }
+        let token = if repos
+            .applications
+            .find_by_token(&app.token)
+            .await?
+            .is_some()
+        {
+            report.skipped.push(token_not_kept(
+                "application",
+                &app.name,
+                "it is already in use, so a new one was issued",
+            ));
+            new_token("AP")
+        } else {
+            app.token.clone()
+        };
+        let created = repos
+            .applications
+            .create(
+                user_id,
+                None,
+                &app.name,
+                app.description.as_deref(),
+                &token,
+                app.default_priority,
+            )
+            .await?;
+        if app.retention_days.is_some() {
+            repos
+                .applications
+                .update(created.id, None, None, None, Some(app.retention_days))
+                .await?;
+        }
+        if let Some(icon) = &app.icon {
+            if let Err(reason) = import_icon(repos, upload_dir, created.id, icon).await {
+                report
+                    .skipped
+                    .push(format!("icon of application '{}': {}", app.name, reason));
+            }
+        }
+        apps.insert(app.id, created.id);
+        report.applications.created += 1;
+    }
+
+    // --- Topics ---
+    for topic in &doc.topics {
+        let owner_id = match topic.owner_id {
+            Some(id) => owner(id),
+            None => into_user,
+        };
+        if let Some(existing) = repos.topics.find_by_name(&topic.name).await? {
+            if into_user.is_some() && existing.owner_id != into_user {
+                report.skipped.push(format!(
+                    "topic '{}': already exists and belongs to someone else",
+                    topic.name
+                ));
+                continue;
+            }
+            topics.insert(topic.id, existing.id);
+            report.topics.matched += 1;
+            continue;
+        }
+        let created = repos
+            .topics
+            .create(
+                &topic.name,
+                owner_id,
+                None,
+                topic.description.as_deref(),
+                topic.everyone_read,
+                topic.everyone_write,
+            )
+            .await?;
+        repos
+            .topics
+            .update(
+                created.id,
+                None,
+                None,
+                None,
+                Some(&topic.notify_policy),
+                topic.notify_priority_min,
+                topic.notify_condition.as_deref(),
+                topic.notify_digest_interval,
+                Some(&topic.store_policy),
+                topic.store_interval,
+                topic.inbox_override.as_deref(),
+                topic.inbox_priority_min,
+            )
+            .await?;
+        topics.insert(topic.id, created.id);
+        report.topics.created += 1;
+    }
+
+    // --- Clients ---
+    for client in &doc.clients {
+        let Some(user_id) = owner(client.user_id) else {
+            report
+                .skipped
+                .push(format!("client '{}': owner was not imported", client.name));
+            continue;
+        };
+        let existing = repos.clients.list_by_user(user_id).await?;
+        if let Some(existing) = existing.iter().find(|c| c.name == client.name) {
+            if existing.token != client.token {
+                report.skipped.push(token_not_kept(
+                    "client",
+                    &client.name,
+                    "a client of that name exists and keeps its own",
+                ));
+            }
+            report.clients.matched += 1;
+            continue;
+        }
+        let token = if repos.clients.find_by_token(&client.token).await?.is_some() {
+            report.skipped.push(token_not_kept(
+                "client",
+                &client.name,
+                "it is already in use, so a new one was issued",
+            ));
+            new_token("CL")
+        } else {
+            client.token.clone()
+        };
+        repos
+            .clients
+            .create(
+                user_id,
+                &client.name,
+                &token,
+                &remap_scopes(&client.scopes, &apps),
+                client.expires_at.as_deref(),
+                client.allowed_ips.as_deref(),
+            )
+            .await?;
+        report.clients.created += 1;
+    }
+
+    // --- Grants (full imports only) ---
+    if into_user.is_none() && !doc.permissions.is_empty() {
+        let existing = repos.topics.list_all_permissions().await?;
+        for permission in &doc.permissions {
+            let Some(user_id) = users.get(&permission.user_id).copied() else {
+                report.skipped.push(format!(
+                    "permission on '{}': user was not imported",
+                    permission.topic_pattern
+                ));
+                continue;
+            };
+            if existing
+                .iter()
+                .any(|p| p.user_id == Some(user_id) && p.topic_pattern == permission.topic_pattern)
+            {
+                report.permissions.matched += 1;
+                continue;
+            }
+            repos
+                .topics
+                .create_permission(
+                    Some(user_id),
+                    None,
+                    &permission.topic_pattern,
+                    permission.can_read,
+                    permission.can_write,
+                )
+                .await?;
+            report.permissions.created += 1;
+        }
+    }
+
+    // --- Webhooks ---
+    for webhook in &doc.webhooks {
+        let Some(user_id) = owner(webhook.user_id) else {
+            report.skipped.push(format!(
+                "webhook '{}': owner was not imported",
+                webhook.name
+            ));
+            continue;
+        };
+        let target_topic_id = webhook.target_topic_id.map(|id| topics.get(&id).copied());
+        let target_application_id = webhook
+            .target_application_id
+            .map(|id| apps.get(&id).copied());
+        if matches!(target_topic_id, Some(None)) || matches!(target_application_id, Some(None)) {
+            report.skipped.push(format!(
+                "webhook '{}': its target was not imported",
+                webhook.name
+            ));
+            continue;
+        }
+        let existing = repos.messages.list_webhook_configs_by_user(user_id).await?;
+        if existing.iter().any(|w| w.name == webhook.name) {
+            report.webhooks.matched += 1;
+            continue;
+        }
+        let token = if repos
+            .messages
+            .find_webhook_config_by_token(&webhook.token)
+            .await?
+            .is_some()
+        {
+            new_token("WH")
+        } else {
+            webhook.token.clone()
+        };
+        repos
+            .messages
+            .create_webhook_config(
+                user_id,
+                &webhook.name,
+                &token,
+                &webhook.webhook_type,
+                target_topic_id.flatten(),
+                target_application_id.flatten(),
+                &webhook.template,
+                webhook.enabled,
+                &webhook.direction,
+                webhook.target_url.as_deref(),
+                Some(&webhook.http_method),
+                webhook.headers.as_deref(),
+                webhook.body_template.as_deref(),
+                Some(webhook.max_retries),
+                Some(webhook.retry_delay_secs),
+                Some(webhook.timeout_secs),
+                Some(webhook.follow_redirects),
+                webhook.group_name.as_deref(),
+                webhook.secret.as_deref(),
+                webhook.notify_url.as_deref(),
+            )
+            .await?;
+        report.webhooks.created += 1;
+    }
+
+    // --- Webhook variables ---
+    for variable in &doc.webhook_variables {
+        let Some(user_id) = owner(variable.user_id) else {
+            report.skipped.push(format!(
+                "webhook variable '{}': owner was not imported",
+                variable.key
+            ));
+            continue;
+        };
+        let existing = repos
+            .webhook_variables
+            .list_webhook_variables(user_id)
+            .await?;
+        if existing.iter().any(|v| v.key == variable.key) {
+            report.webhook_variables.matched += 1;
+            continue;
+        }
+        repos
+            .webhook_variables
+            .create_webhook_variable(user_id, &variable.key, &variable.value)
+            .await?;
+        report.webhook_variables.created += 1;
+    }
+
+    // --- Messages ---
+    let mut orphaned = 0;
+    for message in &doc.messages {
+        let application_id = message.application_id.map(|id| apps.get(&id).copied());
+        let topic_id = message.topic_id.map(|id| topics.get(&id).copied());
+        if matches!(application_id, Some(None)) || matches!(topic_id, Some(None)) {
+            orphaned += 1;
+            continue;
+        }
+        let created = repos
+            .messages
+            .create(NewMessage {
+                application_id: application_id.flatten(),
+                topic_id: topic_id.flatten(),
+                user_id: message.user_id.and_then(owner),
+                title: message.title.as_deref(),
+                message: &message.message,
+                priority: message.priority,
+                tags: message.tags.as_deref(),
+                click_url: message.click_url.as_deref(),
+                icon_url: message.icon_url.as_deref(),
+                actions: message.actions.as_deref(),
+                extras: message.extras.as_deref(),
+                content_type: message.content_type.as_deref(),
+                scheduled_for: message.scheduled_for.as_deref(),
+                source: message.source.as_deref(),
+                inbox: message.inbox,
+                created_at: Some(&message.created_at),
+            })
+            .await?;
+        report.messages += 1;
+
+        for file in &message.attachments {
+            match import_attachment(repos, stores, created.id, file).await {
+                Ok(()) => report.attachments += 1,
+                Err(CoreError::Validation(reason)) => report
+                    .skipped
+                    .push(format!("attachment '{}': {}", file.filename, reason)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    if orphaned > 0 {
+        report.skipped.push(format!(
+            "{} message(s): their application or topic was not imported",
+            orphaned
+        ));
+    }
+
+    Ok(report)
+}
+
+/// Store an imported attachment the way uploads are stored: content-addressed
+/// in the primary store, row first.
+async fn import_attachment(
+    repos: &Repositories,
+    stores: &BlobStores,
+    message_id: i64,
+    file: &ExportFile,
+) -> Result<(), CoreError> {
+    let data = decode_file(file).map_err(CoreError::Validation)?;
+    let store = stores.primary();
+    let _writes = stores.write_guard().await;
+    let attachment = repos
+        .messages
+        .create_attachment(
+            message_id,
+            &file.filename,
+            file.content_type.as_deref(),
+            data.len() as i64,
+            store.kind(),
+            &file.sha256,
+            Some(&file.sha256),
+            None,
+        )
+        .await?;
+    if let Err(e) = store
+        .put(&file.sha256, &data, file.content_type.as_deref())
+        .await
+    {
+        let _ = repos.messages.delete_attachment(attachment.id).await;
+        return Err(e);
+    }
+    if crate::thumbnails::is_thumbnailable(file.content_type.as_deref()) {
+        repos
+            .messages
+            .update_attachment_thumbnail(attachment.id, "pending", None, None, None)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn import_icon(
+    repos: &Repositories,
+    upload_dir: &Path,
+    application_id: i64,
+    icon: &ExportFile,
+) -> Result<(), String> {
+    let extension = Path::new(&icon.filename)
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .filter(|e| ICON_EXTENSIONS.contains(&e.as_str()))
+        .ok_or_else(|| "unsupported image type".to_string())?;
+    let data = decode_file(icon)?;
+    let relative = format!("icons/{}.{}", uuid::Uuid::new_v4(), extension);
+    let path = upload_dir.join(&relative);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tokio::fs::write(&path, &data)
+        .await
+        .map_err(|e| e.to_string())?;
+    repos
+        .applications
+        .update_image(application_id, Some(&relative))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_follow_imported_applications() {
+        let apps = HashMap::from([(7, 42)]);
+        assert_eq!(
+            remap_scopes(r#"["read","app:7","app:8"]"#, &apps),
+            r#"["read","app:42"]"#
+        );
+        assert_eq!(remap_scopes("not json", &apps), "not json");
+    }
+
+    #[test]
+    fn embedded_files_are_checked() {
+        let file = export_file("a.txt", Some("text/plain"), b"hello");
+        assert_eq!(decode_file(&file).unwrap(), b"hello");
+
+        let mut tampered = file.clone();
+        tampered.data = BASE64.encode(b"hellO");
+        assert!(decode_file(&tampered).is_err());
+    }
+}
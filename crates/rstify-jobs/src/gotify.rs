@@ -0,0 +1,560 @@
+//! Import from a Gotify server, either from its SQLite database or over its
+//! REST API.
+//!
+//! Gotify data is converted into an [`ExportDocument`] and imported with
+//! [`import_data`], so ids are remapped and existing records matched by name
+//! exactly as for rstify exports. Application and client tokens are kept, and
+//! bcrypt password hashes are accepted at login and upgraded to Argon2, so
+//! existing devices and accounts keep working.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rstify_core::error::CoreError;
+use rstify_core::models::{
+    ExportApplication, ExportClient, ExportDocument, ExportMessage, ExportUser, ImportReport,
+    EXPORT_FORMAT, EXPORT_VERSION,
+};
+use rstify_core::repositories::Repositories;
+use rstify_storage::BlobStores;
+use serde::Deserialize;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Row, SqlitePool};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::export::{export_file, import_data};
+
+/// Messages are fetched from the API this many at a time (Gotify's maximum).
+const PAGE_SIZE: usize = 200;
+/// Gotify clients have full access to their user's account.
+const CLIENT_SCOPES: &str = r#"["read","write"]"#;
+
+/// Where to read Gotify data from.
+#[derive(Debug, Clone)]
+pub enum GotifySource {
+    /// A copy of `gotify.db` and the `images` directory next to it.
+    Database { path: PathBuf, images_dir: PathBuf },
+    /// A running server. The account's own applications, clients and messages
+    /// are imported; an admin account also brings over the other users.
+    Api {
+        url: String,
+        username: String,
+        password: String,
+    },
+}
+
+/// Gotify data ready for [`import_data`].
+#[derive(Debug)]
+pub struct GotifyData {
+    pub doc: ExportDocument,
+    /// Users whose password could not be read (the API does not expose
+    /// hashes). They are created with a random password.
+    pub without_password: Vec<String>,
+}
+
+fn empty_document() -> ExportDocument {
+    ExportDocument {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        user: None,
+        users: Vec::new(),
+        applications: Vec::new(),
+        clients: Vec::new(),
+        topics: Vec::new(),
+        permissions: Vec::new(),
+        webhooks: Vec::new(),
+        webhook_variables: Vec::new(),
+        messages: Vec::new(),
+    }
+}
+
+/// Gotify dates as stored by its SQLite driver (`2006-01-02 15:04:05.999-07:00`)
+/// or returned by its API (RFC 3339), converted to rstify's UTC format.
+fn gotify_time(value: &str) -> Option<String> {
+    let value = value.trim();
+    let parsed = DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z"))
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%z"))
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").map(|t| t.and_utc())
+        })
+        .ok()?;
+    Some(parsed.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn message_time(value: &str) -> String {
+    gotify_time(value).unwrap_or_else(|| {
+        warn!(
+            "Unrecognized Gotify date '{}'; using the import time",
+            value
+        );
+        Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+}
+
+/// Gotify extras are JSON objects; store them as rstify does, as a JSON string.
+fn extras(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty() && v.trim() != "null")
+}
+
+fn db_error(e: sqlx::Error) -> CoreError {
+    CoreError::Validation(format!("Cannot read the Gotify database: {}", e))
+}
+
+/// Read a Gotify SQLite database, opened read-only.
+pub async fn read_database(path: &Path, images_dir: &Path) -> Result<GotifyData, CoreError> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let pool = SqlitePool::connect_with(options).await.map_err(db_error)?;
+    let result = read_tables(&pool, images_dir).await;
+    pool.close().await;
+    result
+}
+
+async fn read_tables(pool: &SqlitePool, images_dir: &Path) -> Result<GotifyData, CoreError> {
+    let mut doc = empty_document();
+
+    for row in sqlx::query(
+        "SELECT id, name, CAST(pass AS TEXT) AS pass, CAST(admin AS INTEGER) AS admin \
+         FROM users ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?
+    {
+        doc.users.push(ExportUser {
+            id: row.try_get("id").map_err(db_error)?,
+            username: row.try_get("name").map_err(db_error)?,
+            email: None,
+            is_admin: row.try_get::<i64, _>("admin").map_err(db_error)? != 0,
+            password_hash: row.try_get("pass").map_err(db_error)?,
+        });
+    }
+
+    // default_priority arrived in Gotify 2.2.
+    let has_default_priority: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('applications') \
+         WHERE name = 'default_priority'",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(db_error)?;
+    let priority_column = if has_default_priority {
+        "default_priority"
+    } else {
+        "0"
+    };
+    for row in sqlx::query(&format!(
+        "SELECT id, user_id, token, name, description, image, {} AS default_priority \
+         FROM applications ORDER BY id",
+        priority_column
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?
+    {
+        let image: Option<String> = row.try_get("image").map_err(db_error)?;
+        let icon = match image.filter(|i| !i.is_empty()) {
+            Some(image) => match tokio::fs::read(images_dir.join(&image)).await {
+                Ok(data) => Some(export_file(&image, None, &data)),
+                Err(e) => {
+                    warn!("Gotify icon {} not imported: {}", image, e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let description: Option<String> = row.try_get("description").map_err(db_error)?;
+        doc.applications.push(ExportApplication {
+            id: row.try_get("id").map_err(db_error)?,
+            user_id: row.try_get("user_id").map_err(db_error)?,
+            name: row.try_get("name").map_err(db_error)?,
+            description: description.filter(|d| !d.is_empty()),
+            token: row.try_get("token").map_err(db_error)?,
+            default_priority: row
+                .try_get::<i64, _>("default_priority")
+                .map_err(db_error)? as i32,
+            retention_days: None,
+            icon,
+        });
+    }
+
+    for row in sqlx::query("SELECT user_id, token, name FROM clients ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?
+    {
+        doc.clients.push(ExportClient {
+            user_id: row.try_get("user_id").map_err(db_error)?,
+            name: row.try_get("name").map_err(db_error)?,
+            token: row.try_get("token").map_err(db_error)?,
+            scopes: CLIENT_SCOPES.to_string(),
+            expires_at: None,
+            allowed_ips: None,
+        });
+    }
+
+    let owners: std::collections::HashMap<i64, i64> =
+        doc.applications.iter().map(|a| (a.id, a.user_id)).collect();
+    for row in sqlx::query(
+        "SELECT application_id, title, message, priority, CAST(extras AS TEXT) AS extras, \
+         CAST(date AS TEXT) AS date FROM messages ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?
+    {
+        let application_id: i64 = row.try_get("application_id").map_err(db_error)?;
+        let date: Option<String> = row.try_get("date").map_err(db_error)?;
+        doc.messages.push(gotify_message(
+            application_id,
+            owners.get(&application_id).copied(),
+            row.try_get("title").map_err(db_error)?,
+            row.try_get("message").map_err(db_error)?,
+            row.try_get::<i64, _>("priority").map_err(db_error)?,
+            extras(row.try_get("extras").map_err(db_error)?),
+            &date.unwrap_or_default(),
+        ));
+    }
+
+    Ok(GotifyData {
+        doc,
+        without_password: Vec::new(),
+    })
+}
+
+fn gotify_message(
+    application_id: i64,
+    user_id: Option<i64>,
+    title: Option<String>,
+    message: String,
+    priority: i64,
+    extras: Option<String>,
+    date: &str,
+) -> ExportMessage {
+    ExportMessage {
+        application_id: Some(application_id),
+        topic_id: None,
+        user_id,
+        title: title.filter(|t| !t.is_empty()),
+        message,
+        priority: priority as i32,
+        tags: None,
+        click_url: None,
+        icon_url: None,
+        actions: None,
+        extras,
+        content_type: None,
+        scheduled_for: None,
+        source: None,
+        inbox: true,
+        created_at: message_time(date),
+        attachments: Vec::new(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiUser {
+    id: i64,
+    name: String,
+    admin: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiApplication {
+    id: i64,
+    token: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    default_priority: i32,
+}
+
+#[derive(Deserialize)]
+struct ApiClient {
+    token: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ApiMessage {
+    id: i64,
+    message: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    priority: i64,
+    #[serde(default)]
+    extras: Option<serde_json::Value>,
+    date: String,
+}
+
+#[derive(Deserialize)]
+struct ApiMessagePage {
+    messages: Vec<ApiMessage>,
+}
+
+struct Api {
+    http: reqwest::Client,
+    base: String,
+    username: String,
+    password: String,
+}
+
+impl Api {
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base, path.trim_start_matches('/'))
+    }
+
+    async fn send(&self, path: &str) -> Result<reqwest::Response, CoreError> {
+        let response = self
+            .http
+            .get(self.url(path))
+            .basic_auth(&self.username, Some(&self.password))
+            .send()
+            .await
+            .map_err(|e| CoreError::Validation(format!("Gotify request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(CoreError::Validation(format!(
+                "Gotify returned {} for {}",
+                response.status(),
+                path
+            )));
+        }
+        Ok(response)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, CoreError> {
+        self.send(path)
+            .await?
+            .json()
+            .await
+            .map_err(|e| CoreError::Validation(format!("Unexpected Gotify response: {}", e)))
+    }
+}
+
+/// Read one account's data from a running Gotify server.
+pub async fn fetch_api(url: &str, username: &str, password: &str) -> Result<GotifyData, CoreError> {
+    let api = Api {
+        http: reqwest::Client::new(),
+        base: url.trim_end_matches('/').to_string(),
+        username: username.to_string(),
+        password: password.to_string(),
+    };
+    let mut doc = empty_document();
+    let mut without_password = Vec::new();
+
+    // The password is known for this account, so it keeps working.
+    let me: ApiUser = api.get("current/user").await?;
+    let hash = rstify_auth::password::hash_password(password.to_string())
+        .await
+        .map_err(|e| CoreError::Internal(e.to_string()))?;
+    doc.users.push(ExportUser {
+        id: me.id,
+        username: me.name.clone(),
+        email: None,
+        is_admin: me.admin,
+        password_hash: Some(hash),
+    });
+    if me.admin {
+        for user in api.get::<Vec<ApiUser>>("user").await? {
+            if user.id == me.id {
+                continue;
+            }
+            let random = uuid::Uuid::new_v4().simple().to_string();
+            let hash = rstify_auth::password::hash_password(random)
+                .await
+                .map_err(|e| CoreError::Internal(e.to_string()))?;
+            without_password.push(user.name.clone());
+            doc.users.push(ExportUser {
+                id: user.id,
+                username: user.name,
+                email: None,
+                is_admin: user.admin,
+                password_hash: Some(hash),
+            });
+        }
+    }
+
+    for app in api.get::<Vec<ApiApplication>>("application").await? {
+        // The default icon is served from static/, uploaded ones from image/.
+        let icon = if app.image.starts_with("image/") {
+            match api.send(&app.image).await {
+                Ok(response) => match response.bytes().await {
+                    Ok(data) => Some(export_file(&app.image, None, &data)),
+                    Err(e) => {
+                        warn!("Gotify icon {} not imported: {}", app.image, e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    warn!("Gotify icon {} not imported: {}", app.image, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Pages run newest first; `since` is exclusive.
+        let mut messages = Vec::new();
+        let mut since = 0;
+        loop {
+            let page: ApiMessagePage = api
+                .get(&format!(
+                    "application/{}/message?limit={}&since={}",
+                    app.id, PAGE_SIZE, since
+                ))
+                .await?;
+            let count = page.messages.len();
+            if let Some(last) = page.messages.last() {
+                since = last.id;
+            }
+            messages.extend(page.messages);
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        for message in messages.into_iter().rev() {
+            doc.messages.push(gotify_message(
+                app.id,
+                Some(me.id),
+                message.title,
+                message.message,
+                message.priority,
+                message
+                    .extras
+                    .map(|e| e.to_string())
+                    .and_then(|e| extras(Some(e))),
+                &message.date,
+            ));
+        }
+
+        doc.applications.push(ExportApplication {
+            id: app.id,
+            user_id: me.id,
+            name: app.name,
+            description: Some(app.description).filter(|d| !d.is_empty()),
+            token: app.token,
+            default_priority: app.default_priority,
+            retention_days: None,
+            icon,
+        });
+    }
+
+    for client in api.get::<Vec<ApiClient>>("client").await? {
+        doc.clients.push(ExportClient {
+            user_id: me.id,
+            name: client.name,
+            token: client.token,
+            scopes: CLIENT_SCOPES.to_string(),
+            expires_at: None,
+            allowed_ips: None,
+        });
+    }
+
+    Ok(GotifyData {
+        doc,
+        without_password,
+    })
+}
+
+/// Read from `source` and import into this instance.
+pub async fn import_gotify(
+    repos: &Repositories,
+    stores: &BlobStores,
+    upload_dir: &Path,
+    source: &GotifySource,
+) -> Result<(ImportReport, Vec<String>), CoreError> {
+    let data = match source {
+        GotifySource::Database { path, images_dir } => read_database(path, images_dir).await?,
+        GotifySource::Api {
+            url,
+            username,
+            password,
+        } => fetch_api(url, username, password).await?,
+    };
+    let report = import_data(repos, stores, upload_dir, &data.doc, None).await?;
+    Ok((report, data.without_password))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gotify_dates_become_utc() {
+        assert_eq!(
+            gotify_time("2024-03-01 10:20:30.123456789+01:00").as_deref(),
+            Some("2024-03-01 09:20:30")
+        );
+        assert_eq!(
+            gotify_time("2024-03-01T10:20:30.5Z").as_deref(),
+            Some("2024-03-01 10:20:30")
+        );
+        assert_eq!(
+            gotify_time("2024-03-01 10:20:30").as_deref(),
+            Some("2024-03-01 10:20:30")
+        );
+        assert_eq!(gotify_time("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn reads_a_gotify_database() {
+        let dir = std::env::temp_dir().join(format!("rstify-gotify-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("images")).unwrap();
+        std::fs::write(dir.join("images/icon.png"), b"png").unwrap();
+        let path = dir.join("gotify.db");
+
+        // The schema Gotify's ORM creates, trimmed to the columns read here.
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await.unwrap();
+        for statement in [
+            "CREATE TABLE users (id integer primary key, name varchar(180), pass blob, admin bool)",
+            "CREATE TABLE applications (id integer primary key, token varchar(180), \
+             user_id integer, name text, description text, internal bool, image text, \
+             default_priority integer)",
+            "CREATE TABLE clients (id integer primary key, token varchar(180), \
+             user_id integer, name text)",
+            "CREATE TABLE messages (id integer primary key, application_id integer, \
+             message text, title text, priority integer, extras blob, date datetime)",
+            "INSERT INTO users VALUES (1, 'admin', \
+             X'2432612431302461626364', 1)",
+            "INSERT INTO applications VALUES (1, 'AGi5.tYpG-w_3Fe', 1, 'backup', '', 0, \
+             'icon.png', 4)",
+            "INSERT INTO clients VALUES (1, 'CvXq0v2qN9M.7aD', 1, 'phone')",
+            "INSERT INTO messages VALUES (1, 1, 'done', 'Backup', 5, \
+             X'7b22636c69656e743a3a646973706c6179223a7b7d7d', \
+             '2024-03-01 10:20:30.5+01:00')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool.close().await;
+
+        let data = read_database(&path, &dir.join("images")).await.unwrap();
+        let doc = data.doc;
+        assert_eq!(doc.users[0].username, "admin");
+        assert_eq!(doc.users[0].password_hash.as_deref(), Some("$2a$10$abcd"));
+        assert!(doc.users[0].is_admin);
+        assert_eq!(doc.applications[0].token, "AGi5.tYpG-w_3Fe");
+        assert_eq!(doc.applications[0].default_priority, 4);
+        assert_eq!(doc.applications[0].description, None);
+        assert_eq!(
+            doc.applications[0].icon.as_ref().unwrap().filename,
+            "icon.png"
+        );
+        assert_eq!(doc.clients[0].token, "CvXq0v2qN9M.7aD");
+        let message = &doc.messages[0];
+        assert_eq!(message.created_at, "2024-03-01 09:20:30");
+        assert_eq!(message.extras.as_deref(), Some(r#"{"client::display":{}}"#));
+        assert_eq!(message.user_id, Some(1));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
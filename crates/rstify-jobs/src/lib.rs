@@ -1,34 +1,60 @@
+pub mod backup;
+pub mod chat;
 pub mod cleanup;
 pub mod email;
+pub mod escalation;
+pub mod export;
+pub mod gotify;
+pub mod notify;
+pub mod notify_url;
 pub mod outgoing_webhooks;
+pub mod quiet_hours;
+pub mod relay;
 pub mod scheduled;
 pub mod ssrf;
+pub mod thumbnails;
 
+use backup::BackupSchedule;
+use rstify_core::repositories::Repositories;
+use rstify_db::pool::Database;
+use rstify_storage::BlobStores;
 use scheduled::BroadcastFn;
-use sqlx::SqlitePool;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
 #[derive(Clone)]
 pub struct JobRunner {
-    pool: SqlitePool,
+    repos: Repositories,
     cancel: CancellationToken,
     broadcast: Option<BroadcastFn>,
-    upload_dir: Option<String>,
+    blob_stores: Option<BlobStores>,
+    /// Largest attachment the thumbnail worker will read.
+    max_upload_size: usize,
+    thumbnail_wake: Arc<Notify>,
+    backups: Option<(Database, BackupSchedule)>,
+    email: Option<email::EmailOutbox>,
+    quiet_hours_push: Option<quiet_hours::SummaryPushFn>,
+    escalation_step: Option<escalation::EscalationStepFn>,
     /// Handles of the spawned job loops, so shutdown can wait for them to finish
     /// instead of dropping them and killing in-flight work.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
 }
 
 impl JobRunner {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(repos: Repositories) -> Self {
         Self {
-            pool,
+            repos,
             cancel: CancellationToken::new(),
             broadcast: None,
-            upload_dir: None,
+            blob_stores: None,
+            max_upload_size: usize::MAX,
+            thumbnail_wake: Arc::new(Notify::new()),
+            backups: None,
+            email: None,
+            quiet_hours_push: None,
+            escalation_step: None,
             handles: Arc::new(Mutex::new(Vec::new())),
         }
     }
@@ -45,46 +71,136 @@ impl JobRunner {
         self
     }
 
-    /// Set the upload directory so the attachment cleanup job can sweep orphaned
-    /// files left behind when messages (and their attachment rows) are deleted.
-    pub fn with_upload_dir(mut self, upload_dir: String) -> Self {
-        self.upload_dir = Some(upload_dir);
+    /// Set the attachment stores so the cleanup job can delete expired blobs and
+    /// sweep orphans left behind when messages (and their attachment rows) are deleted.
+    pub fn with_blob_stores(mut self, stores: BlobStores) -> Self {
+        self.blob_stores = Some(stores);
         self
     }
 
+    /// Limit the attachments the thumbnail worker reads to the upload size limit.
+    pub fn with_max_upload_size(mut self, max_upload_size: usize) -> Self {
+        self.max_upload_size = max_upload_size;
+        self
+    }
+
+    /// Take backups of `db` on a schedule, rotating old ones.
+    pub fn with_backups(mut self, db: Database, schedule: BackupSchedule) -> Self {
+        self.backups = Some((db, schedule));
+        self
+    }
+
+    /// Send queued email through `outbox`, retrying failures.
+    pub fn with_email(mut self, outbox: email::EmailOutbox) -> Self {
+        self.email = Some(outbox);
+        self
+    }
+
+    /// Push each device a summary of what quiet hours held back, once its
+    /// window ends.
+    pub fn with_quiet_hours_summaries(mut self, push: quiet_hours::SummaryPushFn) -> Self {
+        self.quiet_hours_push = Some(push);
+        self
+    }
+
+    /// Escalate unacknowledged messages under their topics' policies, running
+    /// each step through `run_step`.
+    pub fn with_escalations(mut self, run_step: escalation::EscalationStepFn) -> Self {
+        self.escalation_step = Some(run_step);
+        self
+    }
+
+    /// Notified after an upload queues a thumbnail, so the worker runs promptly.
+    pub fn thumbnail_trigger(&self) -> Arc<Notify> {
+        self.thumbnail_wake.clone()
+    }
+
     pub async fn start(&self) {
         let mut handles = self.handles.lock().await;
 
-        let pool = self.pool.clone();
+        let repos = self.repos.clone();
         let cancel = self.cancel.clone();
         let broadcast = self.broadcast.clone();
         handles.push(tokio::spawn(async move {
-            scheduled::run_scheduled_delivery(pool, cancel, broadcast).await;
+            scheduled::run_scheduled_delivery(repos, cancel, broadcast).await;
+        }));
+
+        let repos = self.repos.clone();
+        let cancel = self.cancel.clone();
+        let blob_stores = self.blob_stores.clone();
+        handles.push(tokio::spawn(async move {
+            cleanup::run_attachment_cleanup(repos, blob_stores, cancel).await;
+        }));
+
+        if let Some(stores) = self.blob_stores.clone() {
+            let repos = self.repos.clone();
+            let cancel = self.cancel.clone();
+            let max_upload_size = self.max_upload_size;
+            let wake = self.thumbnail_wake.clone();
+            handles.push(tokio::spawn(async move {
+                thumbnails::run_thumbnail_worker(repos, stores, max_upload_size, wake, cancel)
+                    .await;
+            }));
+        }
+
+        if let Some(outbox) = self.email.clone() {
+            let cancel = self.cancel.clone();
+            handles.push(tokio::spawn(async move {
+                email::run_email_worker(outbox, cancel).await;
+            }));
+        }
+
+        if let Some(push) = self.quiet_hours_push.clone() {
+            let repos = self.repos.clone();
+            let cancel = self.cancel.clone();
+            handles.push(tokio::spawn(async move {
+                quiet_hours::run_quiet_hours_summaries(repos, push, cancel).await;
+            }));
+        }
+
+        if let Some(run_step) = self.escalation_step.clone() {
+            let repos = self.repos.clone();
+            let cancel = self.cancel.clone();
+            handles.push(tokio::spawn(async move {
+                escalation::run_escalations(repos, run_step, cancel).await;
+            }));
+        }
+
+        if let Some((db, schedule)) = self.backups.clone() {
+            let cancel = self.cancel.clone();
+            handles.push(tokio::spawn(async move {
+                backup::run_scheduled_backups(db, schedule, cancel).await;
+            }));
+        }
+
+        let repos = self.repos.clone();
+        let cancel = self.cancel.clone();
+        handles.push(tokio::spawn(async move {
+            cleanup::run_message_cleanup(repos, cancel).await;
         }));
 
-        let pool = self.pool.clone();
+        let repos = self.repos.clone();
         let cancel = self.cancel.clone();
-        let upload_dir = self.upload_dir.clone();
         handles.push(tokio::spawn(async move {
-            cleanup::run_attachment_cleanup(pool, upload_dir, cancel).await;
+            cleanup::run_retention_cleanup(repos, cancel).await;
         }));
 
-        let pool = self.pool.clone();
+        let repos = self.repos.clone();
         let cancel = self.cancel.clone();
         handles.push(tokio::spawn(async move {
-            cleanup::run_message_cleanup(pool, cancel).await;
+            cleanup::run_delivery_log_cleanup(repos, cancel).await;
         }));
 
-        let pool = self.pool.clone();
+        let repos = self.repos.clone();
         let cancel = self.cancel.clone();
         handles.push(tokio::spawn(async move {
-            cleanup::run_retention_cleanup(pool, cancel).await;
+            cleanup::run_client_token_cleanup(repos, cancel).await;
         }));
 
-        let pool = self.pool.clone();
+        let repos = self.repos.clone();
         let cancel = self.cancel.clone();
         handles.push(tokio::spawn(async move {
-            cleanup::run_delivery_log_cleanup(pool, cancel).await;
+            cleanup::run_audit_log_cleanup(repos, cancel).await;
         }));
     }
 
@@ -115,10 +231,12 @@ mod tests {
 
     #[tokio::test]
     async fn shutdown_cancels_and_joins_promptly() {
-        // In-memory pool; every job loop selects on the cancel token before doing
-        // any query, so no schema is needed.
-        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
-        let runner = JobRunner::new(pool);
+        // In-memory database; every job loop selects on the cancel token before
+        // doing any query, so no schema is needed.
+        let db = rstify_db::pool::Database::connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let runner = JobRunner::new(db.repositories());
         runner.start().await;
 
         // Shutting down immediately must cancel all loops and join well under the
@@ -0,0 +1,59 @@
+//! Inbox notifications raised by the server itself (token expiry warnings,
+//! security events). They are stored as messages of a per-user system
+//! application, so every client sees them in the inbox like any app message.
+
+use rstify_core::error::CoreError;
+use rstify_core::repositories::{NewMessage, Repositories};
+
+/// Name of the per-user application that carries server notifications.
+pub const SYSTEM_APP_NAME: &str = "rstify";
+
+/// Find the user's system application, creating it on first use.
+async fn system_app_id(repos: &Repositories, user_id: i64) -> Result<i64, CoreError> {
+    if let Some(app) = repos
+        .applications
+        .find_by_user_and_name(user_id, SYSTEM_APP_NAME)
+        .await?
+    {
+        return Ok(app.id);
+    }
+
+    let token = format!("AP_{}", uuid::Uuid::new_v4().simple());
+    let app = repos
+        .applications
+        .create(
+            user_id,
+            None,
+            SYSTEM_APP_NAME,
+            Some("Server notifications"),
+            &token,
+            5,
+        )
+        .await?;
+    Ok(app.id)
+}
+
+/// Store a notification in the user's inbox. Returns the new message id.
+pub async fn notify_user(
+    repos: &Repositories,
+    user_id: i64,
+    title: &str,
+    message: &str,
+    priority: i32,
+) -> Result<i64, CoreError> {
+    let app_id = system_app_id(repos, user_id).await?;
+    let msg = repos
+        .messages
+        .create(NewMessage {
+            application_id: Some(app_id),
+            user_id: Some(user_id),
+            title: Some(title),
+            message,
+            priority,
+            source: Some("system"),
+            inbox: true,
+            ..Default::default()
+        })
+        .await?;
+    Ok(msg.id)
+}
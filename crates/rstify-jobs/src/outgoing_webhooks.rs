@@ -1,6 +1,10 @@
+use crate::chat::ChatKind;
+use crate::email::EmailOutbox;
+use crate::notify_url::{email_content, NotifyUrl};
+use crate::relay;
 use crate::ssrf;
-use rstify_core::models::MessageResponse;
-use sqlx::SqlitePool;
+use rstify_core::models::{MessageResponse, WebhookConfig};
+use rstify_core::repositories::{NewWebhookDelivery, Repositories};
 use std::collections::HashMap;
 use tracing::{error, info, warn};
 
@@ -45,42 +49,61 @@ fn substitute_vars(text: &str, vars: &[(String, String)]) -> String {
 }
 
 /// Load user-defined webhook variables and apply {{env.KEY}} substitution.
-async fn apply_env_vars(pool: &SqlitePool, user_id: i64, text: &str) -> String {
+async fn apply_env_vars(repos: &Repositories, user_id: i64, text: &str) -> String {
     if !text.contains("{{env.") {
         return text.to_string();
     }
 
-    let vars = sqlx::query_as::<_, (String, String)>(
-        "SELECT key, value FROM webhook_variables WHERE user_id = ?",
-    )
-    .bind(user_id)
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
+    let vars: Vec<(String, String)> = repos
+        .webhook_variables
+        .list_webhook_variables(user_id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| (v.key, v.value))
+        .collect();
 
     substitute_vars(text, &vars)
 }
 
+/// Substitute the owner's `{{env.KEY}}` variables into a notification URL and
+/// parse it. A placeholder with no matching variable is an error, since the
+/// URL can't be parsed around it.
+pub async fn resolve_notify_url(
+    repos: &Repositories,
+    user_id: i64,
+    raw: &str,
+) -> Result<NotifyUrl, String> {
+    let substituted = apply_env_vars(repos, user_id, raw).await;
+    if let Some(start) = substituted.find("{{env.") {
+        let name = substituted[start + 6..]
+            .split("}}")
+            .next()
+            .unwrap_or_default();
+        return Err(format!(
+            "Unknown webhook variable '{name}' in notification URL"
+        ));
+    }
+    NotifyUrl::parse(&substituted)
+}
+
 /// Fire outgoing webhooks for a given topic when a message is published.
 /// Called from broadcast callback or directly from publish handlers.
+/// `email` sends `mailto://` notification URLs; without it they fail.
+/// `signed` is the message with signed attachment links, sent only where
+/// they are needed (see [`shares_links`]).
 pub async fn fire_outgoing_webhooks(
-    pool: &SqlitePool,
+    repos: &Repositories,
+    email: Option<&EmailOutbox>,
     topic_name: &str,
     message: &MessageResponse,
+    signed: &MessageResponse,
 ) {
     // Find all enabled outgoing webhook configs targeting this topic
-    let configs = match sqlx::query_as::<_, OutgoingWebhookRow>(
-        r#"SELECT wc.id, wc.user_id, wc.target_url, wc.http_method, wc.headers, wc.body_template,
-                  wc.max_retries, wc.retry_delay_secs, wc.timeout_secs, wc.follow_redirects
-           FROM webhook_configs wc
-           JOIN topics t ON wc.target_topic_id = t.id
-           WHERE wc.direction = 'outgoing'
-             AND wc.enabled = 1
-             AND t.name = ?"#,
-    )
-    .bind(topic_name)
-    .fetch_all(pool)
-    .await
+    let configs = match repos
+        .messages
+        .list_outgoing_webhooks_for_topic(topic_name)
+        .await
     {
         Ok(configs) => configs,
         Err(e) => {
@@ -89,99 +112,28 @@ pub async fn fire_outgoing_webhooks(
         }
     };
 
-    let message_json = serde_json::to_string(message).unwrap_or_else(|e| {
-        error!(
-            "Failed to serialize message {} for outgoing webhooks: {}",
-            message.id, e
-        );
-        "{}".to_string()
-    });
-
     for config in configs {
-        let Some(ref target_url_tmpl) = config.target_url else {
-            warn!("Outgoing webhook {} has no target_url", config.id);
-            continue;
-        };
-
-        // Substitute env vars into the URL, then validate the *final* URL for
-        // SSRF and pin the resolved address before building the client. A blocked
-        // URL is logged as a failed delivery rather than sent.
-        let target_url = apply_env_vars(pool, config.user_id, target_url_tmpl).await;
-        let client =
-            match build_pinned_client(&target_url, config.follow_redirects, config.timeout_secs)
-                .await
-            {
-                Ok(c) => c,
-                Err(e) => {
-                    warn!(
-                        "Outgoing webhook {} blocked by SSRF guard: {}",
-                        config.id, e
-                    );
-                    log_delivery(
-                        pool,
-                        config.id,
-                        Some(message.id),
-                        None,
-                        Some(&format!("blocked by SSRF guard: {e}")),
-                        0,
-                        false,
-                    )
-                    .await;
-                    continue;
-                }
-            };
-
-        let body = if let Some(ref tmpl) = config.body_template {
-            // Template substitution with JSON-safe escaping for string values
-            // to prevent message content from breaking JSON structure
-            let substituted = tmpl
-                .replace("{{message}}", &json_escape(&message.message))
-                .replace(
-                    "{{title}}",
-                    &json_escape(message.title.as_deref().unwrap_or("")),
-                )
-                .replace(
-                    "{{topic}}",
-                    &json_escape(message.topic.as_deref().unwrap_or("")),
-                )
-                .replace("{{priority}}", &message.priority.to_string())
-                .replace("{{json}}", &message_json);
-            apply_env_vars(pool, config.user_id, &substituted).await
-        } else {
-            message_json.clone()
-        };
-
-        let mut req = match config.http_method.as_str() {
-            "GET" => client.get(&target_url),
-            "PUT" => client.put(&target_url).body(body.clone()),
-            "PATCH" => client.patch(&target_url).body(body.clone()),
-            "DELETE" => client.delete(&target_url),
-            _ => client.post(&target_url).body(body.clone()),
-        };
-
-        // Add custom headers (with env var substitution)
-        if let Some(ref headers_json) = config.headers {
-            let headers_substituted = apply_env_vars(pool, config.user_id, headers_json).await;
-            if let Ok(headers) =
-                serde_json::from_str::<HashMap<String, String>>(&headers_substituted)
-            {
-                for (key, value) in &headers {
-                    req = req.header(key, value);
-                }
+        // A blocked or unparseable destination is logged as a failed delivery
+        // rather than sent.
+        let (req, target_url) = match prepare(repos, &config, message, signed).await {
+            Ok(Outgoing::Http { req, url }) => (*req, url),
+            Ok(Outgoing::Email { to }) => {
+                queue_email(repos, email, &config, &to, message).await;
+                continue;
             }
-        }
-
-        // Default content-type for body methods (only if user didn't set one)
-        if config.http_method != "GET" && !headers_contain_content_type(config.headers.as_deref()) {
-            req = req.header("Content-Type", "application/json");
-        }
+            Err(e) => {
+                warn!("Outgoing webhook {} not sent: {}", config.id, e);
+                log_delivery(repos, config.id, Some(message.id), None, Some(&e), 0, false).await;
+                continue;
+            }
+        };
 
         let url = target_url.clone();
         let max_retries = config.max_retries;
         let retry_delay = config.retry_delay_secs;
         let id = config.id;
         let msg_id = message.id;
-        let log_pool = pool.clone();
+        let log_repos = repos.clone();
 
         tokio::spawn(async move {
             for attempt in 0..=max_retries {
@@ -207,7 +159,7 @@ pub async fn fire_outgoing_webhooks(
                             .map(|t| t.chars().take(512).collect::<String>());
 
                         log_delivery(
-                            &log_pool,
+                            &log_repos,
                             id,
                             Some(msg_id),
                             Some(status),
@@ -237,7 +189,7 @@ pub async fn fire_outgoing_webhooks(
                     }
                     Err(e) => {
                         log_delivery(
-                            &log_pool,
+                            &log_repos,
                             id,
                             Some(msg_id),
                             None,
@@ -267,59 +219,194 @@ pub async fn fire_outgoing_webhooks(
     }
 }
 
-async fn log_delivery(
-    pool: &SqlitePool,
-    webhook_config_id: i64,
-    message_id: Option<i64>,
-    status_code: Option<i32>,
-    response_body_preview: Option<&str>,
-    duration_ms: i64,
-    success: bool,
-) {
-    if let Err(e) = sqlx::query(
-        "INSERT INTO webhook_delivery_log (webhook_config_id, message_id, status_code, response_body_preview, duration_ms, success) VALUES (?, ?, ?, ?, ?, ?)",
-    )
-    .bind(webhook_config_id)
-    .bind(message_id)
-    .bind(status_code)
-    .bind(response_body_preview)
-    .bind(duration_ms)
-    .bind(success)
-    .execute(pool)
-    .await
-    {
-        error!("Failed to log webhook delivery: {}", e);
-    }
-}
-
-/// Detailed response from a webhook test fire.
-pub struct DetailedWebhookResponse {
-    pub status: u16,
-    pub response_headers: HashMap<String, String>,
-    pub response_body: Option<String>,
-    pub duration_ms: u64,
+/// What one outgoing webhook sends for a message.
+enum Outgoing {
+    Http {
+        req: Box<reqwest::RequestBuilder>,
+        url: String,
+    },
+    /// A `mailto://` notification URL.
+    Email { to: Vec<String> },
 }
 
-/// Fire a single outgoing webhook synchronously (for test endpoint).
-pub async fn fire_single_outgoing_webhook(
-    pool: &SqlitePool,
-    config: &rstify_core::models::WebhookConfig,
+/// Resolve the destination, check it against the SSRF guard and build the
+/// request. A notification URL, when set, replaces `target_url` and the
+/// webhook type; configured headers are added either way.
+async fn prepare(
+    repos: &Repositories,
+    config: &WebhookConfig,
     message: &MessageResponse,
-) -> Result<DetailedWebhookResponse, String> {
-    let target_url = config
-        .target_url
-        .as_deref()
-        .ok_or("No target_url configured")?;
-
+    signed: &MessageResponse,
+) -> Result<Outgoing, String> {
+    let notify = match config.notify_url.as_deref().filter(|u| !u.is_empty()) {
+        Some(raw) => Some(resolve_notify_url(repos, config.user_id, raw).await?),
+        None => None,
+    };
+    if let Some(NotifyUrl::Mailto { to }) = notify {
+        return Ok(Outgoing::Email { to });
+    }
+    let message = if shares_links(config, notify.as_ref()) {
+        signed
+    } else {
+        message
+    };
     let message_json = serde_json::to_string(message).unwrap_or_else(|e| {
         error!(
-            "Failed to serialize message {} for webhook test fire: {}",
+            "Failed to serialize message {} for outgoing webhooks: {}",
             message.id, e
         );
         "{}".to_string()
     });
+    let message_json = message_json.as_str();
+    // Relays count hops so two servers relaying to each other stop after
+    // a few rounds instead of looping.
+    let hops = match notify.as_ref() {
+        Some(n) if n.is_relay() => Some(relay::next_hop(message)?),
+        _ if config.webhook_type == "relay" => {
+            return Err("relay webhooks need an ntfy, gotify or rstify notification URL".into())
+        }
+        _ => None,
+    };
+
+    // Substitute env vars into the URL, then validate the *final* URL for
+    // SSRF and pin the resolved address before building the client.
+    let target_url = match notify.as_ref().and_then(|n| n.target_url()) {
+        Some(url) => url.to_string(),
+        None => {
+            let tmpl = config
+                .target_url
+                .as_deref()
+                .ok_or("No target_url configured")?;
+            apply_env_vars(repos, config.user_id, tmpl).await
+        }
+    };
+    let client = build_pinned_client(&target_url, config.follow_redirects, config.timeout_secs)
+        .await
+        .map_err(|e| format!("blocked by SSRF guard: {e}"))?;
+
+    let (method, url, body, notify_headers) = match notify
+        .as_ref()
+        .and_then(|n| n.request(config.id, message, message_json))
+    {
+        Some(req) => (req.method.to_string(), req.url, req.body, req.headers),
+        None => {
+            let (method, url, body) =
+                render_request(repos, config, target_url, message, message_json).await;
+            (method, url, body, Vec::new())
+        }
+    };
+    let mut req = build_request(&client, &method, &url, body);
+    for (key, value) in &notify_headers {
+        req = req.header(key, value);
+    }
+    if let Some(hops) = hops {
+        req = req.header(relay::HOPS_HEADER, hops.to_string());
+    }
+
+    // Add custom headers (with env var substitution)
+    if let Some(ref headers_json) = config.headers {
+        let headers_substituted = apply_env_vars(repos, config.user_id, headers_json).await;
+        if let Ok(headers) = serde_json::from_str::<HashMap<String, String>>(&headers_substituted) {
+            for (key, value) in &headers {
+                req = req.header(key, value);
+            }
+        }
+    }
+
+    // Default content-type for body methods (only if user didn't set one)
+    if method != "GET"
+        && !headers_contain_content_type(config.headers.as_deref())
+        && !notify_headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+    {
+        req = req.header("Content-Type", "application/json");
+    }
+    Ok(Outgoing::Http {
+        req: Box::new(req),
+        url,
+    })
+}
+
+/// Whether a webhook gets signed attachment links: chat services and relays
+/// show attachments to people who can't sign in here. Every other webhook
+/// gets the links as stored, which need a token to open.
+fn shares_links(config: &WebhookConfig, notify: Option<&NotifyUrl>) -> bool {
+    match notify {
+        Some(notify) => notify.is_relay() || matches!(notify, NotifyUrl::Chat { .. }),
+        None => ChatKind::from_webhook_type(&config.webhook_type).is_some(),
+    }
+}
+
+/// Queue a message for a `mailto://` webhook. The email queue retries on its
+/// own, so the webhook's retry settings don't apply.
+async fn queue_email(
+    repos: &Repositories,
+    email: Option<&EmailOutbox>,
+    config: &WebhookConfig,
+    to: &[String],
+    message: &MessageResponse,
+) {
+    let Some(outbox) = email else {
+        warn!(
+            "Outgoing webhook {} sends email but SMTP is not configured",
+            config.id
+        );
+        log_delivery(
+            repos,
+            config.id,
+            Some(message.id),
+            None,
+            Some("SMTP is not configured"),
+            0,
+            false,
+        )
+        .await;
+        return;
+    };
+    let (subject, text, html) = email_content(message);
+    let start = std::time::Instant::now();
+    let mut errors = Vec::new();
+    for address in to {
+        if let Err(e) = outbox.enqueue(address, &subject, &text, Some(&html)).await {
+            errors.push(format!("{address}: {e}"));
+        }
+    }
+    let preview = if errors.is_empty() {
+        format!("queued email to {}", to.join(", "))
+    } else {
+        errors.join("; ")
+    };
+    log_delivery(
+        repos,
+        config.id,
+        Some(message.id),
+        None,
+        Some(&preview),
+        start.elapsed().as_millis() as i64,
+        errors.is_empty(),
+    )
+    .await;
+}
+
+/// The method, URL and body to send for one message. Chat kinds render the
+/// service's own payload; custom webhooks use the configured method and
+/// `body_template`, or the message JSON.
+async fn render_request(
+    repos: &Repositories,
+    config: &WebhookConfig,
+    target_url: String,
+    message: &MessageResponse,
+    message_json: &str,
+) -> (String, String, String) {
+    if let Some(kind) = ChatKind::from_webhook_type(&config.webhook_type) {
+        let req = kind.request(&target_url, config.id, message);
+        return (req.method.to_string(), req.url, req.body);
+    }
 
     let body = if let Some(ref tmpl) = config.body_template {
+        // Template substitution with JSON-safe escaping for string values
+        // to prevent message content from breaking JSON structure
         let substituted = tmpl
             .replace("{{message}}", &json_escape(&message.message))
             .replace(
@@ -331,39 +418,72 @@ pub async fn fire_single_outgoing_webhook(
                 &json_escape(message.topic.as_deref().unwrap_or("")),
             )
             .replace("{{priority}}", &message.priority.to_string())
-            .replace("{{json}}", &message_json);
-        apply_env_vars(pool, config.user_id, &substituted).await
+            .replace("{{json}}", message_json);
+        apply_env_vars(repos, config.user_id, &substituted).await
     } else {
-        message_json
+        message_json.to_string()
     };
+    (config.http_method.clone(), target_url, body)
+}
 
-    // Substitute env vars into the URL, then validate for SSRF and pin the
-    // resolved address before building the client.
-    let target_url = apply_env_vars(pool, config.user_id, target_url).await;
-    let client = build_pinned_client(&target_url, config.follow_redirects, config.timeout_secs)
-        .await
-        .map_err(|e| format!("blocked by SSRF guard: {e}"))?;
+fn build_request(
+    client: &reqwest::Client,
+    method: &str,
+    url: &str,
+    body: String,
+) -> reqwest::RequestBuilder {
+    match method {
+        "GET" => client.get(url),
+        "PUT" => client.put(url).body(body),
+        "PATCH" => client.patch(url).body(body),
+        "DELETE" => client.delete(url),
+        _ => client.post(url).body(body),
+    }
+}
 
-    let mut req = match config.http_method.as_str() {
-        "GET" => client.get(&target_url),
-        "PUT" => client.put(&target_url).body(body.clone()),
-        "PATCH" => client.patch(&target_url).body(body.clone()),
-        "DELETE" => client.delete(&target_url),
-        _ => client.post(&target_url).body(body.clone()),
+async fn log_delivery(
+    repos: &Repositories,
+    webhook_config_id: i64,
+    message_id: Option<i64>,
+    status_code: Option<i32>,
+    response_body_preview: Option<&str>,
+    duration_ms: i64,
+    success: bool,
+) {
+    let delivery = NewWebhookDelivery {
+        webhook_config_id,
+        message_id,
+        status_code,
+        response_body_preview,
+        duration_ms,
+        success,
     };
-
-    if let Some(ref headers_json) = config.headers {
-        let headers_substituted = apply_env_vars(pool, config.user_id, headers_json).await;
-        if let Ok(headers) = serde_json::from_str::<HashMap<String, String>>(&headers_substituted) {
-            for (key, value) in &headers {
-                req = req.header(key, value);
-            }
-        }
+    if let Err(e) = repos.webhook_deliveries.record(&delivery).await {
+        error!("Failed to log webhook delivery: {}", e);
     }
+}
 
-    if config.http_method != "GET" && !headers_contain_content_type(config.headers.as_deref()) {
-        req = req.header("Content-Type", "application/json");
-    }
+/// Detailed response from a webhook test fire.
+pub struct DetailedWebhookResponse {
+    pub status: u16,
+    pub response_headers: HashMap<String, String>,
+    pub response_body: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Fire a single outgoing webhook synchronously (for test endpoint).
+/// `mailto://` webhooks send straight through the mailer so the result is
+/// known; a sent email reports status 200.
+pub async fn fire_single_outgoing_webhook(
+    repos: &Repositories,
+    email: Option<&EmailOutbox>,
+    config: &WebhookConfig,
+    message: &MessageResponse,
+) -> Result<DetailedWebhookResponse, String> {
+    let req = match prepare(repos, config, message, message).await? {
+        Outgoing::Http { req, .. } => *req,
+        Outgoing::Email { to } => return send_test_email(repos, email, config, &to, message).await,
+    };
 
     let start = std::time::Instant::now();
     let result = req.send().await;
@@ -390,7 +510,7 @@ pub async fn fire_single_outgoing_webhook(
                 .map(|b| b.chars().take(512).collect());
 
             log_delivery(
-                pool,
+                repos,
                 config.id,
                 None,
                 Some(status as i32),
@@ -409,7 +529,7 @@ pub async fn fire_single_outgoing_webhook(
         }
         Err(e) => {
             log_delivery(
-                pool,
+                repos,
                 config.id,
                 None,
                 None,
@@ -423,6 +543,50 @@ pub async fn fire_single_outgoing_webhook(
     }
 }
 
+async fn send_test_email(
+    repos: &Repositories,
+    email: Option<&EmailOutbox>,
+    config: &WebhookConfig,
+    to: &[String],
+    message: &MessageResponse,
+) -> Result<DetailedWebhookResponse, String> {
+    let outbox = email.ok_or("SMTP is not configured")?;
+    let (subject, text, html) = email_content(message);
+    let start = std::time::Instant::now();
+    let mut result = Ok(());
+    for address in to {
+        result = outbox
+            .mailer()
+            .send(address, &subject, &text, Some(&html))
+            .await
+            .map_err(|e| format!("{address}: {}", e.message));
+        if result.is_err() {
+            break;
+        }
+    }
+    let duration_ms = start.elapsed().as_millis() as i64;
+    let body = result
+        .clone()
+        .err()
+        .unwrap_or_else(|| format!("sent email to {}", to.join(", ")));
+    log_delivery(
+        repos,
+        config.id,
+        None,
+        None,
+        Some(&body),
+        duration_ms,
+        result.is_ok(),
+    )
+    .await;
+    result.map(|()| DetailedWebhookResponse {
+        status: 200,
+        response_headers: HashMap::new(),
+        response_body: Some(body),
+        duration_ms: duration_ms as u64,
+    })
+}
+
 /// Escape a string for safe embedding inside a JSON string value.
 /// Produces the inner content of a JSON string (without surrounding quotes),
 /// so `"{{message}}"` in a template becomes a valid JSON string after substitution.
@@ -448,20 +612,6 @@ fn headers_contain_content_type(headers_json: Option<&str>) -> bool {
         .any(|k| k.eq_ignore_ascii_case("content-type"))
 }
 
-#[derive(sqlx::FromRow)]
-struct OutgoingWebhookRow {
-    id: i64,
-    user_id: i64,
-    target_url: Option<String>,
-    http_method: String,
-    headers: Option<String>,
-    body_template: Option<String>,
-    max_retries: i32,
-    retry_delay_secs: i32,
-    timeout_secs: i32,
-    follow_redirects: bool,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,183 @@
+//! End-of-window summaries for pushes held back by quiet hours.
+//!
+//! Publishing records a [`SuppressedPush`] for each device it skipped. Once
+//! none of the device's schedules is active any more, this worker sends it a
+//! single push listing what it missed and clears the backlog.
+
+use chrono::{DateTime, Utc};
+use rstify_core::error::CoreError;
+use rstify_core::models::{Client, MessageResponse, SuppressedPush};
+use rstify_core::repositories::Repositories;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Callback that pushes a summary to one device over whatever push channels
+/// it has. Push delivery lives with the API state, so the server supplies it.
+pub type SummaryPushFn =
+    Arc<dyn Fn(Client, MessageResponse) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Held messages listed by name in a summary; the rest are only counted.
+const LISTED: usize = 5;
+
+pub async fn run_quiet_hours_summaries(
+    repos: Repositories,
+    push: SummaryPushFn,
+    cancel: CancellationToken,
+) {
+    info!("Quiet hours summary worker started");
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Quiet hours summary worker shutting down");
+                break;
+            }
+            _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => {
+                if let Err(e) = send_due_summaries(&repos, &push, Utc::now()).await {
+                    error!("Quiet hours summary error: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Summarize held pushes for every device whose quiet hours are over at
+/// `now`. Returns the number of summaries sent.
+pub async fn send_due_summaries(
+    repos: &Repositories,
+    push: &SummaryPushFn,
+    now: DateTime<Utc>,
+) -> Result<usize, CoreError> {
+    let mut by_client: BTreeMap<i64, Vec<SuppressedPush>> = BTreeMap::new();
+    for held in repos.clients.list_suppressed_pushes().await? {
+        by_client.entry(held.client_id).or_default().push(held);
+    }
+
+    let mut sent = 0;
+    for (client_id, held) in by_client {
+        let last_id = held.iter().map(|h| h.id).max().unwrap_or_default();
+        let Some(client) = repos.clients.find_by_id(client_id).await? else {
+            continue;
+        };
+        let schedules = repos.clients.list_quiet_hours(client.user_id).await?;
+        if rstify_core::policy::is_quiet(&schedules, Some(client.id), now) {
+            continue;
+        }
+
+        let mut messages = Vec::new();
+        for h in &held {
+            match repos.messages.find_by_id(h.message_id).await {
+                Ok(Some(msg)) => messages.push(msg.to_response(None)),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to load held message {}: {}", h.message_id, e),
+            }
+        }
+        if let Some(summary) = summary(&messages, now) {
+            push(client, summary).await;
+            sent += 1;
+        }
+        repos
+            .clients
+            .delete_suppressed_pushes(client_id, last_id)
+            .await?;
+    }
+    Ok(sent)
+}
+
+/// One message standing for everything held back, at the highest held
+/// priority. `None` when nothing is left (e.g. the messages were deleted).
+pub fn summary(messages: &[MessageResponse], now: DateTime<Utc>) -> Option<MessageResponse> {
+    let priority = messages.iter().map(|m| m.priority).max()?;
+    let title = match messages.len() {
+        1 => "1 notification during quiet hours".to_string(),
+        n => format!("{} notifications during quiet hours", n),
+    };
+    let mut lines: Vec<String> = messages
+        .iter()
+        .take(LISTED)
+        .map(|m| {
+            let text = m
+                .title
+                .as_deref()
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or_else(|| m.message.lines().next().unwrap_or_default());
+            format!("- {}", text)
+        })
+        .collect();
+    if messages.len() > LISTED {
+        lines.push(format!("and {} more", messages.len() - LISTED));
+    }
+    Some(MessageResponse {
+        id: 0,
+        appid: None,
+        topic: None,
+        title: Some(title),
+        message: lines.join("\n"),
+        priority,
+        tags: None,
+        click_url: None,
+        icon_url: None,
+        actions: None,
+        extras: None,
+        content_type: None,
+        source: Some("quiet_hours".to_string()),
+        inbox: true,
+        attachments: None,
+        date: now.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(title: Option<&str>, text: &str, priority: i32) -> MessageResponse {
+        MessageResponse {
+            id: 1,
+            appid: None,
+            topic: None,
+            title: title.map(str::to_string),
+            message: text.to_string(),
+            priority,
+            tags: None,
+            click_url: None,
+            icon_url: None,
+            actions: None,
+            extras: None,
+            content_type: None,
+            source: None,
+            inbox: true,
+            attachments: None,
+            date: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn summary_lists_titles_at_the_highest_priority() {
+        let held = [
+            message(Some("Backup done"), "ok", 3),
+            message(None, "disk at 91%\nsda1", 7),
+        ];
+        let summary = summary(&held, Utc::now()).unwrap();
+        assert_eq!(
+            summary.title.as_deref(),
+            Some("2 notifications during quiet hours")
+        );
+        assert_eq!(summary.message, "- Backup done\n- disk at 91%");
+        assert_eq!(summary.priority, 7);
+        assert_eq!(summary.id, 0);
+    }
+
+    #[test]
+    fn long_backlogs_are_counted() {
+        let held: Vec<_> = (0..8)
+            .map(|i| message(Some(&format!("m{i}")), "x", 5))
+            .collect();
+        let summary = summary(&held, Utc::now()).unwrap();
+        assert!(summary.message.ends_with("- m4\nand 3 more"));
+        assert!(super::summary(&[], Utc::now()).is_none());
+    }
+}
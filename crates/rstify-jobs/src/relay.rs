@@ -0,0 +1,108 @@
+//! Federation relay: re-publishing messages to another rstify, Gotify or ntfy
+//! server through a `relay` outgoing webhook.
+//!
+//! Each relayed request carries the message's hop count in [`HOPS_HEADER`].
+//! A receiving rstify records it in the message extras under [`HOPS_EXTRA`],
+//! so a relay that would push the count past [`MAX_HOPS`] is dropped instead
+//! of bouncing between servers forever.
+
+use rstify_core::models::MessageResponse;
+use serde_json::json;
+
+/// Request header carrying how many relays a message has already crossed.
+pub const HOPS_HEADER: &str = "X-Rstify-Hops";
+
+/// Most relays one message may cross. Enough for site → region → central.
+pub const MAX_HOPS: u32 = 3;
+
+/// Extras key holding `{"hops": n}` on relayed messages.
+pub const HOPS_EXTRA: &str = "rstify::relay";
+
+/// Relays the message has crossed to get here (0 if published locally).
+pub fn message_hops(message: &MessageResponse) -> u32 {
+    message
+        .extras
+        .as_ref()
+        .and_then(|e| e.get(HOPS_EXTRA))
+        .and_then(|r| r.get("hops"))
+        .and_then(|h| h.as_u64())
+        .map_or(0, |h| h.min(u32::MAX as u64) as u32)
+}
+
+/// The hop count from a received [`HOPS_HEADER`] value. Garbage is ignored
+/// rather than rejected, like other optional publish headers.
+pub fn parse_hops_header(value: &str) -> Option<u32> {
+    value.trim().parse().ok()
+}
+
+/// Extras JSON recording a received hop count.
+pub fn hops_extras(hops: u32) -> String {
+    json!({ HOPS_EXTRA: { "hops": hops } }).to_string()
+}
+
+/// The hop count to send with a relayed message, or an error when the
+/// message has already crossed [`MAX_HOPS`] relays.
+pub fn next_hop(message: &MessageResponse) -> Result<u32, String> {
+    let hops = message_hops(message);
+    if hops >= MAX_HOPS {
+        return Err(format!(
+            "not relayed: message already crossed {hops} relays (limit {MAX_HOPS}); check for a relay loop"
+        ));
+    }
+    Ok(hops + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(extras: Option<serde_json::Value>) -> MessageResponse {
+        MessageResponse {
+            id: 1,
+            appid: None,
+            topic: Some("alerts".to_string()),
+            title: None,
+            message: "hi".to_string(),
+            priority: 5,
+            tags: None,
+            click_url: None,
+            icon_url: None,
+            actions: None,
+            extras,
+            content_type: None,
+            source: None,
+            inbox: false,
+            attachments: None,
+            date: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn local_messages_start_at_zero_hops() {
+        assert_eq!(message_hops(&message(None)), 0);
+        assert_eq!(next_hop(&message(None)), Ok(1));
+    }
+
+    #[test]
+    fn recorded_hops_round_trip_through_extras() {
+        let extras: serde_json::Value = serde_json::from_str(&hops_extras(2)).unwrap();
+        let relayed = message(Some(extras));
+        assert_eq!(message_hops(&relayed), 2);
+        assert_eq!(next_hop(&relayed), Ok(3));
+    }
+
+    #[test]
+    fn relays_stop_at_the_hop_limit() {
+        let extras: serde_json::Value = serde_json::from_str(&hops_extras(MAX_HOPS)).unwrap();
+        assert!(next_hop(&message(Some(extras)))
+            .unwrap_err()
+            .contains("relay loop"));
+    }
+
+    #[test]
+    fn parses_hop_headers_leniently() {
+        assert_eq!(parse_hops_header(" 2 "), Some(2));
+        assert_eq!(parse_hops_header("-1"), None);
+        assert_eq!(parse_hops_header("many"), None);
+    }
+}
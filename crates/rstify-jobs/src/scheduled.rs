@@ -1,5 +1,6 @@
-use rstify_core::models::{Message, MessageResponse};
-use sqlx::SqlitePool;
+use rstify_core::error::CoreError;
+use rstify_core::models::MessageResponse;
+use rstify_core::repositories::Repositories;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -16,7 +17,7 @@ pub type BroadcastFn = Arc<
 
 /// Background task that checks for scheduled messages due for delivery
 pub async fn run_scheduled_delivery(
-    pool: SqlitePool,
+    repos: Repositories,
     cancel: CancellationToken,
     broadcast: Option<BroadcastFn>,
 ) {
@@ -29,7 +30,7 @@ pub async fn run_scheduled_delivery(
                 break;
             }
             _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => {
-                if let Err(e) = deliver_scheduled(&pool, &broadcast).await {
+                if let Err(e) = deliver_scheduled(&repos, &broadcast).await {
                     error!("Scheduled delivery error: {}", e);
                 }
             }
@@ -38,26 +39,12 @@ pub async fn run_scheduled_delivery(
 }
 
 async fn deliver_scheduled(
-    pool: &SqlitePool,
+    repos: &Repositories,
     broadcast: &Option<BroadcastFn>,
-) -> Result<(), sqlx::Error> {
-    // Use a transaction with UPDATE ... RETURNING to atomically claim messages,
-    // preventing double-delivery if multiple workers run concurrently.
-    let mut tx = pool.begin().await?;
-
-    let messages = sqlx::query_as::<_, Message>(
-        "UPDATE messages SET delivered_at = datetime('now') \
-         WHERE id IN ( \
-             SELECT id FROM messages \
-             WHERE scheduled_for IS NOT NULL \
-               AND scheduled_for <= datetime('now') \
-               AND delivered_at IS NULL \
-         ) RETURNING *",
-    )
-    .fetch_all(&mut *tx)
-    .await?;
-
-    tx.commit().await?;
+) -> Result<(), CoreError> {
+    // Claim atomically, preventing double-delivery if multiple workers run
+    // concurrently.
+    let messages = repos.messages.claim_scheduled_due().await?;
 
     for msg in messages {
         info!("Delivered scheduled message {}", msg.id);
@@ -65,12 +52,12 @@ async fn deliver_scheduled(
         // Broadcast to subscribers if callback provided
         if let Some(broadcast) = broadcast {
             let topic_name = if let Some(topic_id) = msg.topic_id {
-                let row: Option<(String,)> = sqlx::query_as("SELECT name FROM topics WHERE id = ?")
-                    .bind(topic_id)
-                    .fetch_optional(pool)
+                repos
+                    .topics
+                    .find_by_id(topic_id)
                     .await
-                    .unwrap_or(None);
-                row.map(|r| r.0)
+                    .unwrap_or(None)
+                    .map(|t| t.name)
             } else {
                 None
             };
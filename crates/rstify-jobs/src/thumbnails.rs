@@ -0,0 +1,274 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder, ImageReader, Limits};
+use rstify_core::error::CoreError;
+use rstify_core::models::Attachment;
+use rstify_core::repositories::Repositories;
+use rstify_storage::{blob_key, BlobStores};
+use std::io::Cursor;
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Longest edge of a generated thumbnail, in pixels.
+pub const THUMBNAIL_MAX_EDGE: u32 = 320;
+/// Images with more pixels than this are not decoded (~40 megapixels).
+const MAX_SOURCE_PIXELS: u64 = 40_000_000;
+/// Upper bound on decoder allocations, independent of the pixel check.
+const MAX_DECODE_ALLOC: u64 = 512 * 1024 * 1024;
+const JPEG_QUALITY: u8 = 80;
+const BATCH_SIZE: i64 = 20;
+
+/// Content types the worker can decode. Uploads of these are marked pending.
+pub fn is_thumbnailable(content_type: Option<&str>) -> bool {
+    matches!(
+        content_type,
+        Some("image/png" | "image/jpeg" | "image/gif" | "image/webp")
+    )
+}
+
+/// An encoded preview and the dimensions of the image it was made from.
+#[derive(Debug)]
+pub struct Thumbnail {
+    pub data: Vec<u8>,
+    pub content_type: &'static str,
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug)]
+pub enum ThumbnailError {
+    /// Decodable, but over the pixel limit; carries the source dimensions.
+    TooLarge(u32, u32),
+    Invalid(String),
+}
+
+/// Decode `data` and encode a preview no larger than [`THUMBNAIL_MAX_EDGE`]:
+/// WebP (lossless) when the image has transparency, JPEG otherwise. CPU-bound,
+/// so call it from a blocking task.
+pub fn make_thumbnail(data: &[u8]) -> Result<Thumbnail, ThumbnailError> {
+    let reader = || -> Result<ImageReader<Cursor<&[u8]>>, ThumbnailError> {
+        let mut reader = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(|e| ThumbnailError::Invalid(e.to_string()))?;
+        let mut limits = Limits::default();
+        limits.max_alloc = Some(MAX_DECODE_ALLOC);
+        reader.limits(limits);
+        Ok(reader)
+    };
+
+    // Read the header first so oversized images are refused before decoding.
+    let (width, height) = reader()?
+        .into_dimensions()
+        .map_err(|e| ThumbnailError::Invalid(e.to_string()))?;
+    if u64::from(width) * u64::from(height) > MAX_SOURCE_PIXELS {
+        return Err(ThumbnailError::TooLarge(width, height));
+    }
+
+    let image = reader()?
+        .decode()
+        .map_err(|e| ThumbnailError::Invalid(e.to_string()))?;
+    let preview = if width > THUMBNAIL_MAX_EDGE || height > THUMBNAIL_MAX_EDGE {
+        image.thumbnail(THUMBNAIL_MAX_EDGE, THUMBNAIL_MAX_EDGE)
+    } else {
+        image
+    };
+
+    let mut out = Vec::new();
+    let (content_type, extension) = if preview.color().has_alpha() {
+        let rgba = preview.to_rgba8();
+        WebPEncoder::new_lossless(&mut out)
+            .write_image(&rgba, rgba.width(), rgba.height(), ExtendedColorType::Rgba8)
+            .map_err(|e| ThumbnailError::Invalid(e.to_string()))?;
+        ("image/webp", "webp")
+    } else {
+        let rgb = preview.to_rgb8();
+        JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)
+            .write_image(&rgb, rgb.width(), rgb.height(), ExtendedColorType::Rgb8)
+            .map_err(|e| ThumbnailError::Invalid(e.to_string()))?;
+        ("image/jpeg", "jpg")
+    };
+
+    Ok(Thumbnail {
+        data: out,
+        content_type,
+        extension,
+        width,
+        height,
+    })
+}
+
+/// Background task that generates thumbnails for pending image attachments.
+/// Uploads wake it through `wake`; it also polls in case a wake-up was missed.
+pub async fn run_thumbnail_worker(
+    repos: Repositories,
+    stores: BlobStores,
+    max_source_bytes: usize,
+    wake: Arc<Notify>,
+    cancel: CancellationToken,
+) {
+    info!("Thumbnail worker started");
+
+    loop {
+        match process_pending_thumbnails(&repos, &stores, max_source_bytes).await {
+            Ok(n) if n > 0 => info!("Generated {} thumbnail(s)", n),
+            Err(e) => error!("Thumbnail worker error: {}", e),
+            _ => {}
+        }
+
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Thumbnail worker shutting down");
+                break;
+            }
+            _ = wake.notified() => {}
+            _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => {}
+        }
+    }
+}
+
+/// Work through every pending attachment. Returns how many thumbnails were
+/// generated; attachments that can't be previewed are marked and skipped.
+pub async fn process_pending_thumbnails(
+    repos: &Repositories,
+    stores: &BlobStores,
+    max_source_bytes: usize,
+) -> Result<u64, CoreError> {
+    let mut generated = 0u64;
+    loop {
+        let pending = repos.messages.list_pending_thumbnails(BATCH_SIZE).await?;
+        if pending.is_empty() {
+            return Ok(generated);
+        }
+        for attachment in pending {
+            if generate(repos, stores, max_source_bytes, &attachment).await? {
+                generated += 1;
+            }
+        }
+    }
+}
+
+/// Generate and record one thumbnail. Every outcome updates the status, so an
+/// attachment is never picked up twice.
+async fn generate(
+    repos: &Repositories,
+    stores: &BlobStores,
+    max_source_bytes: usize,
+    attachment: &Attachment,
+) -> Result<bool, CoreError> {
+    let mark = |status: &'static str, dims: Option<(u32, u32)>| {
+        let (width, height) = dims.map_or((None, None), |(w, h)| {
+            (Some(i64::from(w)), Some(i64::from(h)))
+        });
+        repos
+            .messages
+            .update_attachment_thumbnail(attachment.id, status, width, height, None)
+    };
+
+    if attachment.size_bytes > max_source_bytes as i64 {
+        mark("skipped", None).await?;
+        return Ok(false);
+    }
+    let Some(store) = stores.for_type(&attachment.storage_type) else {
+        warn!(
+            "No '{}' store for attachment {}; not generating a thumbnail",
+            attachment.storage_type, attachment.id
+        );
+        mark("failed", None).await?;
+        return Ok(false);
+    };
+    let key = blob_key(&attachment.storage_path);
+    let data = match store.get(key).await {
+        Ok(data) => data,
+        Err(e) => {
+            warn!(
+                "Cannot read attachment {} for thumbnail: {}",
+                attachment.id, e
+            );
+            mark("failed", None).await?;
+            return Ok(false);
+        }
+    };
+
+    let result = tokio::task::spawn_blocking(move || make_thumbnail(&data))
+        .await
+        .map_err(|e| CoreError::Internal(format!("Thumbnail task failed: {e}")))?;
+    let thumbnail = match result {
+        Ok(thumbnail) => thumbnail,
+        Err(ThumbnailError::TooLarge(w, h)) => {
+            mark("skipped", Some((w, h))).await?;
+            return Ok(false);
+        }
+        Err(ThumbnailError::Invalid(e)) => {
+            warn!("Cannot thumbnail attachment {}: {}", attachment.id, e);
+            mark("failed", None).await?;
+            return Ok(false);
+        }
+    };
+
+    // Derived from the content-addressed key, so identical attachments share it.
+    let thumbnail_key = format!("{}.thumb.{}", key, thumbnail.extension);
+    store
+        .put(
+            &thumbnail_key,
+            &thumbnail.data,
+            Some(thumbnail.content_type),
+        )
+        .await?;
+    repos
+        .messages
+        .update_attachment_thumbnail(
+            attachment.id,
+            "ready",
+            Some(i64::from(thumbnail.width)),
+            Some(i64::from(thumbnail.height)),
+            Some(&thumbnail_key),
+        )
+        .await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn encode_png(image: image::DynamicImage) -> Vec<u8> {
+        let mut out = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut out), image::ImageFormat::Png)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn downscales_opaque_images_to_jpeg() {
+        let png = encode_png(RgbImage::from_pixel(1000, 500, Rgb([200, 10, 10])).into());
+        let thumb = make_thumbnail(&png).unwrap();
+        assert_eq!((thumb.width, thumb.height), (1000, 500));
+        assert_eq!(thumb.content_type, "image/jpeg");
+
+        let preview = image::load_from_memory(&thumb.data).unwrap();
+        assert_eq!((preview.width(), preview.height()), (320, 160));
+    }
+
+    #[test]
+    fn keeps_transparency_and_small_sizes() {
+        let png = encode_png(RgbaImage::from_pixel(64, 48, Rgba([0, 0, 0, 0])).into());
+        let thumb = make_thumbnail(&png).unwrap();
+        assert_eq!(thumb.content_type, "image/webp");
+
+        let preview = image::load_from_memory(&thumb.data).unwrap();
+        assert_eq!((preview.width(), preview.height()), (64, 48));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(matches!(
+            make_thumbnail(b"not an image"),
+            Err(ThumbnailError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,302 @@
+//! One-off maintenance commands, run as `rstify-server <command>` instead of
+//! starting the server.
+
+use rstify_core::repositories::Repositories;
+use rstify_db::pool::{self, Database};
+use rstify_jobs::backup::{create_backup, restore_backup};
+use rstify_jobs::export::{export_data, import_data, ExportOptions};
+use rstify_jobs::gotify::{import_gotify, GotifySource};
+use rstify_storage::migrate::migrate_attachments;
+use rstify_storage::LocalBlobStore;
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Run a command that must not find the database open, before connecting to
+/// it. Returns `None` for any other command.
+pub async fn run_offline(config: &Config, args: &[String]) -> Option<anyhow::Result<()>> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        "restore" => Some(restore(config, rest).await),
+        _ => None,
+    }
+}
+
+/// Run the command named by `args[0]`. Returns `None` when `args` names no
+/// command, in which case the server starts normally.
+pub async fn run(config: &Config, db: &Database, args: &[String]) -> Option<anyhow::Result<()>> {
+    let (command, rest) = args.split_first()?;
+    Some(match command.as_str() {
+        "migrate-attachments" => migrate_local_attachments(config, &db.repositories(), rest).await,
+        "backup" => backup(config, db, rest).await,
+        "export" => export(config, &db.repositories(), rest).await,
+        "import" => import(config, &db.repositories(), rest).await,
+        "import-gotify" => gotify(config, &db.repositories(), rest).await,
+        other => Err(anyhow::anyhow!(
+            "unknown command '{}' (available: migrate-attachments, backup, restore, export, \
+             import, import-gotify)",
+            other
+        )),
+    })
+}
+
+/// `backup`: write an archive of the database and uploads to `BACKUP_DIR`.
+/// Safe to run while the server is up.
+async fn backup(config: &Config, db: &Database, args: &[String]) -> anyhow::Result<()> {
+    if !args.is_empty() {
+        anyhow::bail!("usage: rstify-server backup");
+    }
+    let info = create_backup(
+        db,
+        Path::new(&config.server.upload_dir),
+        Path::new(&config.backup.dir),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("{}", e))?;
+    info!(
+        "Backup written to {} ({} bytes)",
+        Path::new(&config.backup.dir).join(&info.name).display(),
+        info.size_bytes
+    );
+    Ok(())
+}
+
+/// Look up `--user NAME` for export and import.
+async fn user_id(repos: &Repositories, username: Option<&str>) -> anyhow::Result<Option<i64>> {
+    let Some(username) = username else {
+        return Ok(None);
+    };
+    let user = repos
+        .users
+        .find_by_username(username)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?
+        .ok_or_else(|| anyhow::anyhow!("no user named '{}'", username))?;
+    Ok(Some(user.id))
+}
+
+/// `export [--user NAME] [--no-attachments] <file>`: write all data, or one
+/// user's, as a JSON export document.
+async fn export(config: &Config, repos: &Repositories, args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "usage: rstify-server export [--user NAME] [--no-attachments] <file>";
+    let mut username = None;
+    let mut include_files = true;
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--user" => username = Some(args.next().ok_or_else(|| anyhow::anyhow!(USAGE))?),
+            "--no-attachments" => include_files = false,
+            _ if file.is_none() && !arg.starts_with("--") => file = Some(arg),
+            _ => anyhow::bail!(USAGE),
+        }
+    }
+    let file = file.ok_or_else(|| anyhow::anyhow!(USAGE))?;
+    let options = ExportOptions {
+        user_id: user_id(repos, username.map(String::as_str)).await?,
+        include_files,
+    };
+
+    let stores = crate::blob_stores(config)?;
+    let doc = export_data(
+        repos,
+        &stores,
+        Path::new(&config.server.upload_dir),
+        options,
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let json = serde_json::to_vec(&doc)?;
+    tokio::fs::write(file, &json).await?;
+    info!(
+        "Exported {} user(s), {} application(s), {} topic(s) and {} message(s) to {}",
+        doc.users.len(),
+        doc.applications.len(),
+        doc.topics.len(),
+        doc.messages.len(),
+        file
+    );
+    Ok(())
+}
+
+/// `import [--user NAME] <file>`: import an export document, either as a whole
+/// or into one existing user.
+async fn import(config: &Config, repos: &Repositories, args: &[String]) -> anyhow::Result<()> {
+    let (username, file) = match args {
+        [file] => (None, file),
+        [flag, name, file] if flag == "--user" => (Some(name.as_str()), file),
+        _ => anyhow::bail!("usage: rstify-server import [--user NAME] <file>"),
+    };
+    let into_user = user_id(repos, username).await?;
+    let doc = serde_json::from_slice(&tokio::fs::read(file).await?)?;
+
+    let stores = crate::blob_stores(config)?;
+    let report = import_data(
+        repos,
+        &stores,
+        Path::new(&config.server.upload_dir),
+        &doc,
+        into_user,
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("{}", e))?;
+    info!(
+        "Imported {} user(s), {} application(s), {} topic(s), {} message(s) and {} attachment(s); \
+         {} user(s), {} application(s) and {} topic(s) already existed",
+        report.users.created,
+        report.applications.created,
+        report.topics.created,
+        report.messages,
+        report.attachments,
+        report.users.matched,
+        report.applications.matched,
+        report.topics.matched
+    );
+    for reason in &report.skipped {
+        warn!("Skipped {}", reason);
+    }
+    Ok(())
+}
+
+/// `import-gotify --db <gotify.db> [--images DIR]` or
+/// `import-gotify --url URL --user NAME [--password PASS]`: bring over users,
+/// applications, clients and messages from Gotify, keeping their tokens. The
+/// password may also come from `GOTIFY_PASSWORD`.
+async fn gotify(config: &Config, repos: &Repositories, args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str =
+        "usage: rstify-server import-gotify --db <gotify.db> [--images DIR]\n       \
+                         rstify-server import-gotify --url URL --user NAME [--password PASS]";
+    let mut options = std::collections::HashMap::new();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let name = match flag.as_str() {
+            "--db" | "--images" | "--url" | "--user" | "--password" => flag.trim_start_matches('-'),
+            _ => anyhow::bail!(USAGE),
+        };
+        let value = args.next().ok_or_else(|| anyhow::anyhow!(USAGE))?;
+        options.insert(name, value.clone());
+    }
+
+    let source = match (options.remove("db"), options.remove("url")) {
+        (Some(db), None) => {
+            let path = std::path::PathBuf::from(db);
+            let images_dir = match options.remove("images") {
+                Some(dir) => dir.into(),
+                None => path.with_file_name("images"),
+            };
+            GotifySource::Database { path, images_dir }
+        }
+        (None, Some(url)) => GotifySource::Api {
+            url,
+            username: options
+                .remove("user")
+                .ok_or_else(|| anyhow::anyhow!(USAGE))?,
+            password: match options.remove("password") {
+                Some(password) => password,
+                None => std::env::var("GOTIFY_PASSWORD")
+                    .map_err(|_| anyhow::anyhow!("pass --password or set GOTIFY_PASSWORD"))?,
+            },
+        },
+        _ => anyhow::bail!(USAGE),
+    };
+    if !options.is_empty() {
+        anyhow::bail!(USAGE);
+    }
+
+    let stores = crate::blob_stores(config)?;
+    let (report, without_password) = import_gotify(
+        repos,
+        &stores,
+        Path::new(&config.server.upload_dir),
+        &source,
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("{}", e))?;
+    info!(
+        "Imported from Gotify: {} user(s), {} application(s), {} client(s) and {} message(s); \
+         {} user(s), {} application(s) and {} client(s) already existed",
+        report.users.created,
+        report.applications.created,
+        report.clients.created,
+        report.messages,
+        report.users.matched,
+        report.applications.matched,
+        report.clients.matched
+    );
+    for reason in &report.skipped {
+        warn!("Skipped {}", reason);
+    }
+    for username in &without_password {
+        warn!(
+            "User '{}' was created with a random password; set a new one in Users",
+            username
+        );
+    }
+    Ok(())
+}
+
+/// `restore <archive> [--force]`: replace the database and add the archived
+/// uploads. The server must be stopped; `--force` is required to overwrite an
+/// existing database.
+async fn restore(config: &Config, args: &[String]) -> anyhow::Result<()> {
+    let (archive, force) = match args {
+        [archive] => (archive, false),
+        [archive, flag] if flag == "--force" => (archive, true),
+        _ => anyhow::bail!("usage: rstify-server restore <archive> [--force]"),
+    };
+    let db_path = pool::sqlite_file(&config.database.url)
+        .ok_or_else(|| anyhow::anyhow!("restore needs a file-backed SQLite DATABASE_URL"))?;
+    if db_path.exists() && !force {
+        anyhow::bail!(
+            "{} exists; stop the server and pass --force to replace it",
+            db_path.display()
+        );
+    }
+    let manifest = restore_backup(
+        Path::new(archive),
+        &config.database.url,
+        Path::new(&config.server.upload_dir),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!("{}", e))?;
+    info!(
+        "Restored backup taken {} by rstify {} ({} migrations, {} upload files); \
+         remaining migrations run on the next start",
+        manifest.created_at,
+        manifest.version,
+        manifest.migrations.len(),
+        manifest.upload_files
+    );
+    Ok(())
+}
+
+/// `migrate-attachments [--keep-local]`: copy local attachment files into the
+/// configured object store and repoint their rows.
+async fn migrate_local_attachments(
+    config: &Config,
+    repos: &Repositories,
+    args: &[String],
+) -> anyhow::Result<()> {
+    let keep_local = match args {
+        [] => false,
+        [flag] if flag == "--keep-local" => true,
+        _ => anyhow::bail!("usage: rstify-server migrate-attachments [--keep-local]"),
+    };
+    let stores = crate::blob_stores(config)?;
+    let local = LocalBlobStore::new(&config.server.upload_dir);
+    let report = migrate_attachments(repos, &local, stores.primary().as_ref(), !keep_local)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    info!(
+        "Attachment migration finished: {} moved, {} missing, {} failed",
+        report.moved, report.missing, report.failed
+    );
+    if report.failed > 0 {
+        anyhow::bail!(
+            "{} attachment(s) could not be migrated; rerun to retry",
+            report.failed
+        );
+    }
+    Ok(())
+}
@@ -28,6 +28,11 @@ pub struct ServerConfig {
     pub listen_addr: String,
     pub upload_dir: String,
     pub max_attachment_size: usize,
+    /// Combined size of all files uploaded with one multipart message.
+    pub max_upload_total: usize,
+    /// Public URL the server is reached at (no trailing slash), used for links
+    /// sent outside the API such as signed attachment URLs in emails.
+    pub base_url: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -44,15 +49,45 @@ pub struct AuthConfig {
 pub struct FcmConfig {
     pub project_id: String,
     pub service_account_path: String,
+    /// Overrides the FCM API endpoint, e.g. to point at a mock.
+    pub api_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebPushConfig {
+    /// VAPID `sub` claim: a `mailto:` or `https:` contact for push services.
+    pub subject: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct SmtpConfig {
     pub host: String,
     pub port: u16,
+    pub security: rstify_jobs::email::SmtpSecurity,
+    /// Empty when the server needs no login.
     pub username: String,
     pub password: String,
     pub from: String,
+    /// Cap on preference-driven emails per user per hour.
+    pub max_per_hour: u32,
+}
+
+/// Attachment storage. Files stay under `UPLOAD_DIR` unless an S3-compatible
+/// bucket is configured.
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    pub s3: Option<rstify_storage::S3Config>,
+    /// Redirect downloads to presigned bucket URLs instead of proxying bytes.
+    pub presigned_downloads: bool,
+}
+
+/// Backup archives. Scheduled backups run only when an interval is set.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub dir: String,
+    pub interval_hours: Option<u64>,
+    /// Archives kept when scheduled backups rotate.
+    pub keep: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -79,7 +114,10 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
     pub fcm: Option<FcmConfig>,
+    pub web_push: Option<WebPushConfig>,
     pub smtp: Option<SmtpConfig>,
+    pub storage: StorageConfig,
+    pub backup: BackupConfig,
     pub rate_limit: RateLimitConfig,
     pub cors: CorsConfig,
     /// Allow outgoing webhooks / attachment fetches to target private/LAN/reserved
@@ -119,10 +157,39 @@ impl Config {
         let upload_dir = lookup("UPLOAD_DIR").unwrap_or_else(|| "./uploads".into());
         let max_attachment_size =
             parse_optional::<usize>(&lookup, "RSTIFY_MAX_ATTACHMENT_SIZE", 25 * 1024 * 1024)?;
+        let max_upload_total = parse_optional::<usize>(
+            &lookup,
+            "RSTIFY_MAX_UPLOAD_TOTAL",
+            max_attachment_size.saturating_mul(4),
+        )?;
+        let base_url = match lookup("BASE_URL").map(|u| u.trim().trim_end_matches('/').to_string())
+        {
+            Some(url) if url.is_empty() => None,
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => Some(url),
+            Some(url) => {
+                return Err(ConfigError {
+                    field: "BASE_URL".into(),
+                    message: format!("'{}' is not an http(s) URL", url),
+                })
+            }
+            None => None,
+        };
 
         // --- Database ---
         let database_url = lookup("DATABASE_URL").unwrap_or_else(|| "sqlite://rstify.db".into());
 
+        // --- Backups ---
+        let backup_dir = lookup("BACKUP_DIR").unwrap_or_else(|| "./backups".into());
+        let backup_interval_hours =
+            Some(parse_optional::<u64>(&lookup, "BACKUP_INTERVAL_HOURS", 0)?).filter(|&h| h > 0);
+        let backup_keep = parse_optional::<usize>(&lookup, "BACKUP_KEEP", 7)?;
+        if backup_keep == 0 {
+            return Err(ConfigError {
+                field: "BACKUP_KEEP".into(),
+                message: "must keep at least one backup".into(),
+            });
+        }
+
         // --- Rate limit ---
         let burst = parse_optional::<u32>(&lookup, "RATE_LIMIT_BURST", 60)?;
         let rps = parse_optional::<f64>(&lookup, "RATE_LIMIT_RPS", 10.0)?;
@@ -150,37 +217,101 @@ impl Config {
             (Some(project_id), Some(service_account_path)) => Some(FcmConfig {
                 project_id,
                 service_account_path,
+                api_url: lookup("FCM_API_URL"),
             }),
             _ => None,
         };
 
+        // --- Web Push (on unless WEB_PUSH_ENABLED=false) ---
+        let web_push = lookup("WEB_PUSH_ENABLED")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true)
+            .then(|| WebPushConfig {
+                subject: lookup("VAPID_SUBJECT")
+                    .or_else(|| base_url.clone())
+                    .unwrap_or_else(|| "mailto:admin@localhost".to_string()),
+            });
+
         // --- SMTP (optional, present when SMTP_HOST is set) ---
         let smtp = if let Some(host) = lookup("SMTP_HOST") {
             let port = parse_optional::<u16>(&lookup, "SMTP_PORT", 587)?;
+            // Port 465 is implicit TLS; everything else upgrades with STARTTLS.
+            let default_security = if port == 465 {
+                rstify_jobs::email::SmtpSecurity::Tls
+            } else {
+                rstify_jobs::email::SmtpSecurity::StartTls
+            };
+            let security = parse_optional(&lookup, "SMTP_TLS", default_security)?;
             let username = lookup("SMTP_USER").unwrap_or_default();
             let password = lookup("SMTP_PASS").unwrap_or_default();
             let from = lookup("SMTP_FROM").unwrap_or_else(|| format!("rstify@{}", host));
+            let max_per_hour = parse_optional::<u32>(&lookup, "EMAIL_MAX_PER_HOUR", 20)?;
             Some(SmtpConfig {
                 host,
                 port,
+                security,
                 username,
                 password,
                 from,
+                max_per_hour,
             })
         } else {
             None
         };
 
+        // --- Attachment storage (local unless ATTACHMENT_STORAGE=s3) ---
+        let storage_backend = lookup("ATTACHMENT_STORAGE").unwrap_or_else(|| "local".into());
+        let s3 = match storage_backend.as_str() {
+            "local" => None,
+            "s3" => {
+                let region = lookup("S3_REGION").unwrap_or_else(|| "us-east-1".into());
+                let endpoint = lookup("S3_ENDPOINT")
+                    .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+                Some(rstify_storage::S3Config {
+                    endpoint,
+                    bucket: required(&lookup, "S3_BUCKET")?,
+                    region,
+                    access_key_id: required(&lookup, "S3_ACCESS_KEY_ID")?,
+                    secret_access_key: required(&lookup, "S3_SECRET_ACCESS_KEY")?,
+                    prefix: lookup("S3_PREFIX").unwrap_or_default(),
+                    path_style: lookup("S3_PATH_STYLE")
+                        .map(|v| v == "true" || v == "1")
+                        .unwrap_or(false),
+                })
+            }
+            other => {
+                return Err(ConfigError {
+                    field: "ATTACHMENT_STORAGE".into(),
+                    message: format!("unknown backend '{}' (expected local or s3)", other),
+                })
+            }
+        };
+        let presigned_downloads = lookup("S3_PRESIGNED_DOWNLOADS")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(true);
+
         Ok(Config {
             server: ServerConfig {
                 listen_addr,
                 upload_dir,
                 max_attachment_size,
+                max_upload_total,
+                base_url,
             },
             database: DatabaseConfig { url: database_url },
             auth: AuthConfig { jwt_secret },
             fcm,
+            web_push,
             smtp,
+            storage: StorageConfig {
+                s3,
+                presigned_downloads,
+            },
+            backup: BackupConfig {
+                dir: backup_dir,
+                interval_hours: backup_interval_hours,
+                keep: backup_keep,
+            },
             rate_limit: RateLimitConfig {
                 burst,
                 rps,
@@ -192,6 +323,16 @@ impl Config {
     }
 }
 
+/// Look up a variable that must be set (and non-empty) for the chosen feature.
+fn required(lookup: &dyn Fn(&str) -> Option<String>, key: &str) -> Result<String, ConfigError> {
+    lookup(key)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| ConfigError {
+            field: key.into(),
+            message: format!("{} is required", key),
+        })
+}
+
 /// Parse an optional numeric env var, returning a ConfigError on invalid values
 /// instead of silently falling back to a default.
 fn parse_optional<T>(
@@ -322,6 +463,40 @@ mod tests {
         let fcm = config.fcm.unwrap();
         assert_eq!(fcm.project_id, "my-project");
         assert_eq!(fcm.service_account_path, "/path/to/sa.json");
+        assert!(fcm.api_url.is_none());
+
+        // Endpoint override
+        let mut m = minimal_valid_map();
+        m.insert("FCM_PROJECT_ID", "my-project");
+        m.insert("FCM_SERVICE_ACCOUNT_PATH", "/path/to/sa.json");
+        m.insert("FCM_API_URL", "http://127.0.0.1:9099");
+        let config = Config::from_map(make_lookup(m)).unwrap();
+        assert_eq!(
+            config.fcm.unwrap().api_url.as_deref(),
+            Some("http://127.0.0.1:9099")
+        );
+    }
+
+    // --- Web Push is on by default; the subject falls back to BASE_URL ---
+    #[test]
+    fn test_web_push_config() {
+        let config = Config::from_map(make_lookup(minimal_valid_map())).unwrap();
+        assert_eq!(config.web_push.unwrap().subject, "mailto:admin@localhost");
+
+        let mut m = minimal_valid_map();
+        m.insert("BASE_URL", "https://push.example.com");
+        let config = Config::from_map(make_lookup(m)).unwrap();
+        assert_eq!(config.web_push.unwrap().subject, "https://push.example.com");
+
+        let mut m = minimal_valid_map();
+        m.insert("VAPID_SUBJECT", "mailto:ops@example.com");
+        let config = Config::from_map(make_lookup(m)).unwrap();
+        assert_eq!(config.web_push.unwrap().subject, "mailto:ops@example.com");
+
+        let mut m = minimal_valid_map();
+        m.insert("WEB_PUSH_ENABLED", "false");
+        let config = Config::from_map(make_lookup(m)).unwrap();
+        assert!(config.web_push.is_none());
     }
 
     // --- SMTP config when host set ---
@@ -333,9 +508,11 @@ mod tests {
         let smtp = config.smtp.unwrap();
         assert_eq!(smtp.host, "mail.example.com");
         assert_eq!(smtp.port, 587);
+        assert_eq!(smtp.security, rstify_jobs::email::SmtpSecurity::StartTls);
         assert_eq!(smtp.username, "");
         assert_eq!(smtp.password, "");
         assert_eq!(smtp.from, "rstify@mail.example.com");
+        assert_eq!(smtp.max_per_hour, 20);
     }
 
     // --- SMTP absent when host not set ---
@@ -350,6 +527,7 @@ mod tests {
     fn test_max_attachment_size_default() {
         let config = Config::from_map(make_lookup(minimal_valid_map())).unwrap();
         assert_eq!(config.server.max_attachment_size, 25 * 1024 * 1024);
+        assert_eq!(config.server.max_upload_total, 100 * 1024 * 1024);
     }
 
     // --- Custom values override defaults ---
@@ -367,22 +545,46 @@ mod tests {
         m.insert("SMTP_USER", "user@test.com");
         m.insert("SMTP_PASS", "secret");
         m.insert("SMTP_FROM", "noreply@test.com");
+        m.insert("EMAIL_MAX_PER_HOUR", "5");
 
         let config = Config::from_map(make_lookup(m)).unwrap();
         assert_eq!(config.server.listen_addr, "127.0.0.1:9090");
         assert_eq!(config.server.upload_dir, "/var/uploads");
         assert_eq!(config.server.max_attachment_size, 10485760);
+        assert_eq!(config.server.max_upload_total, 4 * 10485760);
         assert_eq!(config.database.url, "sqlite:///data/app.db");
         assert_eq!(config.rate_limit.burst, 100);
         assert_eq!(config.rate_limit.rps, 20.5);
 
         let smtp = config.smtp.unwrap();
         assert_eq!(smtp.port, 465);
+        assert_eq!(smtp.security, rstify_jobs::email::SmtpSecurity::Tls);
         assert_eq!(smtp.username, "user@test.com");
         assert_eq!(smtp.password, "secret");
+        assert_eq!(smtp.max_per_hour, 5);
         assert_eq!(smtp.from, "noreply@test.com");
     }
 
+    // --- BASE_URL is optional, trimmed and must be http(s) ---
+    #[test]
+    fn test_base_url() {
+        let config = Config::from_map(make_lookup(minimal_valid_map())).unwrap();
+        assert!(config.server.base_url.is_none());
+
+        let mut m = minimal_valid_map();
+        m.insert("BASE_URL", "https://push.example.com/");
+        let config = Config::from_map(make_lookup(m)).unwrap();
+        assert_eq!(
+            config.server.base_url.as_deref(),
+            Some("https://push.example.com")
+        );
+
+        let mut m = minimal_valid_map();
+        m.insert("BASE_URL", "push.example.com");
+        let err = Config::from_map(make_lookup(m)).unwrap_err();
+        assert_eq!(err.field, "BASE_URL");
+    }
+
     // --- Invalid SMTP_PORT fails ---
     #[test]
     fn test_invalid_smtp_port_fails() {
@@ -393,6 +595,23 @@ mod tests {
         assert_eq!(err.field, "SMTP_PORT");
     }
 
+    // --- SMTP_TLS overrides the port default ---
+    #[test]
+    fn test_smtp_tls_mode() {
+        let mut m = minimal_valid_map();
+        m.insert("SMTP_HOST", "localhost");
+        m.insert("SMTP_PORT", "25");
+        m.insert("SMTP_TLS", "none");
+        let smtp = Config::from_map(make_lookup(m)).unwrap().smtp.unwrap();
+        assert_eq!(smtp.security, rstify_jobs::email::SmtpSecurity::Plain);
+
+        let mut m = minimal_valid_map();
+        m.insert("SMTP_HOST", "localhost");
+        m.insert("SMTP_TLS", "sometimes");
+        let err = Config::from_map(make_lookup(m)).unwrap_err();
+        assert_eq!(err.field, "SMTP_TLS");
+    }
+
     // --- Invalid RSTIFY_MAX_ATTACHMENT_SIZE fails ---
     #[test]
     fn test_invalid_max_attachment_size_fails() {
@@ -402,6 +621,61 @@ mod tests {
         assert_eq!(err.field, "RSTIFY_MAX_ATTACHMENT_SIZE");
     }
 
+    // --- Attachment storage defaults to local; S3 needs bucket and credentials ---
+    #[test]
+    fn test_storage_config() {
+        let config = Config::from_map(make_lookup(minimal_valid_map())).unwrap();
+        assert!(config.storage.s3.is_none());
+        assert!(config.storage.presigned_downloads);
+
+        let mut m = minimal_valid_map();
+        m.insert("ATTACHMENT_STORAGE", "s3");
+        m.insert("S3_BUCKET", "rstify");
+        let err = Config::from_map(make_lookup(m.clone())).unwrap_err();
+        assert_eq!(err.field, "S3_ACCESS_KEY_ID");
+
+        m.insert("S3_ACCESS_KEY_ID", "key");
+        m.insert("S3_SECRET_ACCESS_KEY", "secret");
+        m.insert("S3_REGION", "eu-west-1");
+        let s3 = Config::from_map(make_lookup(m.clone()))
+            .unwrap()
+            .storage
+            .s3
+            .unwrap();
+        assert_eq!(s3.endpoint, "https://s3.eu-west-1.amazonaws.com");
+        assert!(!s3.path_style);
+
+        m.insert("ATTACHMENT_STORAGE", "ftp");
+        let err = Config::from_map(make_lookup(m)).unwrap_err();
+        assert_eq!(err.field, "ATTACHMENT_STORAGE");
+    }
+
+    // --- Backups are manual unless an interval is set ---
+    #[test]
+    fn test_backup_config() {
+        let config = Config::from_map(make_lookup(minimal_valid_map())).unwrap();
+        assert_eq!(config.backup.dir, "./backups");
+        assert_eq!(config.backup.interval_hours, None);
+        assert_eq!(config.backup.keep, 7);
+
+        let mut m = minimal_valid_map();
+        m.insert("BACKUP_DIR", "/var/backups/rstify");
+        m.insert("BACKUP_INTERVAL_HOURS", "24");
+        m.insert("BACKUP_KEEP", "14");
+        let backup = Config::from_map(make_lookup(m.clone())).unwrap().backup;
+        assert_eq!(backup.dir, "/var/backups/rstify");
+        assert_eq!(backup.interval_hours, Some(24));
+        assert_eq!(backup.keep, 14);
+
+        m.insert("BACKUP_INTERVAL_HOURS", "0");
+        let backup = Config::from_map(make_lookup(m.clone())).unwrap().backup;
+        assert_eq!(backup.interval_hours, None);
+
+        m.insert("BACKUP_KEEP", "0");
+        let err = Config::from_map(make_lookup(m)).unwrap_err();
+        assert_eq!(err.field, "BACKUP_KEEP");
+    }
+
     // --- ConfigError Display ---
     #[test]
     fn test_config_error_display() {
@@ -1,3 +1,4 @@
+mod commands;
 mod config;
 mod telemetry;
 
@@ -7,6 +8,7 @@ use rstify_api::state::AppState;
 use rstify_auth::password::hash_password;
 use rstify_db::pool::Database;
 use rstify_jobs::JobRunner;
+use rstify_storage::{BlobStore, BlobStores, LocalBlobStore, S3BlobStore};
 use std::sync::Arc;
 use std::time::Duration;
 use tower_http::cors::CorsLayer;
@@ -26,27 +28,31 @@ async fn main() -> anyhow::Result<()> {
         warn!("WEBHOOK_ALLOW_PRIVATE_TARGETS=true — outgoing webhooks and attachment fetches may reach private/LAN/reserved addresses. Enable only on a trusted single-user instance.");
     }
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = commands::run_offline(&config, &args).await {
+        return result;
+    }
+
     let db = Database::connect(&config.database.url).await?;
     db.migrate().await?;
 
-    let pool = db.pool().clone();
+    let repos = db.repositories();
+
+    if let Some(result) = commands::run(&config, &db, &args).await {
+        return result;
+    }
 
     // Seed default admin user if no users exist (like Gotify)
-    let user_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
-        .fetch_one(&pool)
-        .await?;
-    if user_count.0 == 0 {
+    if repos.users.count().await? == 0 {
         // Generate a random initial password instead of the well-known admin/admin,
         // and print it once so the operator can log in and change it.
         let initial_password = uuid::Uuid::new_v4().simple().to_string();
         let password_hash = hash_password(initial_password.clone())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to hash default password: {}", e))?;
-        sqlx::query("INSERT INTO users (username, password_hash, is_admin) VALUES (?, ?, ?)")
-            .bind("admin")
-            .bind(&password_hash)
-            .bind(true)
-            .execute(&pool)
+        repos
+            .users
+            .create("admin", &password_hash, None, true)
             .await?;
         warn!(
             "Created initial admin user — username: admin, password: {} — \
@@ -56,21 +62,27 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Load inbox_priority_threshold setting from DB (default 5)
-    let inbox_threshold_value: i32 = sqlx::query_scalar(
-        "SELECT CAST(value AS INTEGER) FROM settings WHERE key = 'inbox_priority_threshold'",
-    )
-    .fetch_optional(&pool)
-    .await
-    .ok()
-    .flatten()
-    .unwrap_or(5);
+    let inbox_threshold_value: i32 = repos
+        .settings
+        .get("inbox_priority_threshold")
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
 
     let mut state = AppState::new(
-        pool.clone(),
+        db.clone(),
         config.auth.jwt_secret.clone(),
         config.server.upload_dir.clone(),
         config.server.max_attachment_size,
     );
+    let blob_stores = blob_stores(&config)?;
+    state = state
+        .with_blob_stores(blob_stores.clone(), config.storage.presigned_downloads)
+        .with_max_upload_total(config.server.max_upload_total)
+        .with_base_url(config.server.base_url.clone())
+        .with_backup_dir(config.backup.dir.clone());
     state
         .inbox_threshold
         .store(inbox_threshold_value, std::sync::atomic::Ordering::Relaxed);
@@ -81,6 +93,10 @@ async fn main() -> anyhow::Result<()> {
             fcm_cfg.project_id.clone(),
             &fcm_cfg.service_account_path,
         ) {
+            let fcm_config = match fcm_cfg.api_url {
+                Some(ref url) => fcm_config.with_api_url(url),
+                None => fcm_config,
+            };
             info!(
                 "FCM push notifications enabled (project: {})",
                 fcm_cfg.project_id
@@ -93,23 +109,63 @@ async fn main() -> anyhow::Result<()> {
         info!("FCM push notifications disabled (set FCM_PROJECT_ID and FCM_SERVICE_ACCOUNT_PATH to enable)");
     }
 
-    // Wire SMTP email config if configured
+    // Web Push for browsers; the VAPID key is created on first start.
+    if let Some(ref web_push_cfg) = config.web_push {
+        match rstify_api::webpush::VapidKeys::load_or_create(state.settings_repo.as_ref()).await {
+            Ok(keys) => {
+                info!("Web Push notifications enabled");
+                state = state.with_web_push(rstify_api::webpush::WebPushClient::new(
+                    keys,
+                    web_push_cfg.subject.clone(),
+                ));
+            }
+            Err(e) => tracing::error!("Web Push disabled: {}", e),
+        }
+    }
+
+    // Outgoing mail goes through a pooled transport and a persistent queue,
+    // drained by the job runner below.
+    let email_outbox = match config.smtp {
+        Some(ref smtp_cfg) => {
+            let email_config = rstify_jobs::email::EmailConfig {
+                host: smtp_cfg.host.clone(),
+                port: smtp_cfg.port,
+                security: smtp_cfg.security,
+                credentials: (!smtp_cfg.username.is_empty())
+                    .then(|| (smtp_cfg.username.clone(), smtp_cfg.password.clone())),
+                from: smtp_cfg.from.clone(),
+            };
+            match rstify_jobs::email::Mailer::new(&email_config) {
+                Ok(mailer) => {
+                    info!(
+                        "SMTP email notifications enabled (host: {}, security: {:?})",
+                        smtp_cfg.host, smtp_cfg.security
+                    );
+                    Some(rstify_jobs::email::EmailOutbox::new(
+                        repos.emails.clone(),
+                        mailer,
+                    ))
+                }
+                Err(e) => {
+                    tracing::error!("SMTP configuration invalid — email disabled: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    if let Some(ref outbox) = email_outbox {
+        state = state.with_email(outbox.clone());
+    }
     if let Some(ref smtp_cfg) = config.smtp {
-        let email_config = rstify_jobs::email::EmailConfig::new(
-            smtp_cfg.host.clone(),
-            smtp_cfg.port,
-            smtp_cfg.username.clone(),
-            smtp_cfg.password.clone(),
-            smtp_cfg.from.clone(),
-        );
-        state = state.with_email_config(email_config);
-        info!("SMTP email notifications enabled (host: {})", smtp_cfg.host);
+        state = state.with_email_rate_cap(smtp_cfg.max_per_hour);
     }
 
     // Background jobs and the ad-hoc cleanup loops below share one cancellation
     // token so shutdown stops everything cleanly.
-    let job_runner = JobRunner::new(pool.clone());
+    let job_runner = JobRunner::new(repos.clone());
     let cancel = job_runner.cancel_token();
+    state = state.with_thumbnail_trigger(job_runner.thumbnail_trigger());
 
     // Periodic connection cleanup (cancellable + joined on shutdown).
     let connections_for_cleanup = state.connections.clone();
@@ -127,50 +183,90 @@ async fn main() -> anyhow::Result<()> {
     // Create broadcast callback for scheduled message delivery. This runs at SEND
     // time (not creation), so it fires the full delivery — broadcast + outgoing
     // webhooks + push — mirroring the immediate path's deliver_message().
-    let connections = state.connections.clone();
-    let fcm_for_scheduled = state.fcm.clone();
-    let client_repo_for_scheduled = state.client_repo.clone();
-    let topic_repo_for_scheduled = state.topic_repo.clone();
-    let pool_for_scheduled = pool.clone();
+    let state_for_scheduled = state.clone();
+    let repos_for_scheduled = repos.clone();
     let broadcast_fn: rstify_jobs::scheduled::BroadcastFn = Arc::new(move |msg, topic_name| {
-        let connections = connections.clone();
-        let fcm = fcm_for_scheduled.clone();
-        let client_repo = client_repo_for_scheduled.clone();
-        let topic_repo = topic_repo_for_scheduled.clone();
-        let pool = pool_for_scheduled.clone();
+        let state = state_for_scheduled.clone();
+        let repos = repos_for_scheduled.clone();
         Box::pin(async move {
             if let Some(ref name) = topic_name {
-                connections.broadcast_to_topic(name, msg.clone()).await;
+                state
+                    .connections
+                    .broadcast_to_topic(name, msg.clone())
+                    .await;
 
                 // Outgoing webhooks fire now (delivery time), matching immediate sends.
-                rstify_jobs::outgoing_webhooks::fire_outgoing_webhooks(&pool, name, &msg).await;
-
-                // FCM for scheduled topic messages (respecting notification policy)
-                if let Some(ref fcm) = fcm {
-                    if let Ok(Some(topic)) =
-                        rstify_core::repositories::TopicRepository::find_by_name(&topic_repo, name)
-                            .await
-                    {
-                        if msg.inbox && rstify_core::policy::should_notify(&topic, &msg) {
-                            if let Some(owner_id) = topic.owner_id {
-                                fcm.notify_user(
-                                    &client_repo,
-                                    owner_id,
-                                    &msg,
-                                    msg.icon_url.as_deref(),
-                                )
-                                .await;
-                            }
-                        }
-                    }
+                let signed = rstify_api::helpers::publish::outgoing_webhook_message(&state, &msg);
+                rstify_jobs::outgoing_webhooks::fire_outgoing_webhooks(
+                    &repos,
+                    state.email.as_ref(),
+                    name,
+                    &msg,
+                    &signed,
+                )
+                .await;
+
+                // Push to subscribed devices and the owner, and start any
+                // escalation, as for immediate sends.
+                if let Ok(Some(topic)) = state.topic_repo.find_by_name(name).await {
+                    rstify_api::helpers::publish::push_topic_message(&state, &topic, &msg).await;
+                    rstify_api::escalation::begin(&state, &topic, &msg).await;
                 }
             }
         })
     });
 
+    // Devices coming out of quiet hours get one push summarizing what was held.
+    let state_for_summaries = state.clone();
+    let summary_fn: rstify_jobs::quiet_hours::SummaryPushFn = Arc::new(move |client, summary| {
+        let state = state_for_summaries.clone();
+        Box::pin(async move {
+            rstify_api::helpers::publish::push_to_clients(&state, &[client], &summary).await;
+        })
+    });
+
+    // Unacknowledged messages escalate under their topic's policy.
+    let state_for_escalations = state.clone();
+    let escalation_fn: rstify_jobs::escalation::EscalationStepFn =
+        Arc::new(move |topic, step, msg| {
+            let state = state_for_escalations.clone();
+            Box::pin(
+                async move { rstify_api::escalation::run_step(&state, &topic, &step, &msg).await },
+            )
+        });
+
     let job_runner = job_runner
         .with_broadcast(broadcast_fn)
-        .with_upload_dir(config.server.upload_dir.clone());
+        .with_quiet_hours_summaries(summary_fn)
+        .with_escalations(escalation_fn)
+        .with_blob_stores(blob_stores)
+        .with_max_upload_size(config.server.max_attachment_size);
+    let job_runner = match config.backup.interval_hours {
+        Some(hours) if db.sqlite_pool().is_some() => {
+            info!(
+                "Scheduled backups enabled (every {}h to {})",
+                hours, config.backup.dir
+            );
+            job_runner.with_backups(
+                db.clone(),
+                rstify_jobs::backup::BackupSchedule {
+                    upload_dir: config.server.upload_dir.clone().into(),
+                    backup_dir: config.backup.dir.clone().into(),
+                    interval: Duration::from_secs(hours * 3600),
+                    keep: config.backup.keep,
+                },
+            )
+        }
+        Some(_) => {
+            warn!("BACKUP_INTERVAL_HOURS is ignored for PostgreSQL; use pg_dump");
+            job_runner
+        }
+        None => job_runner,
+    };
+    let job_runner = match email_outbox {
+        Some(outbox) => job_runner.with_email(outbox),
+        None => job_runner,
+    };
 
     // Build rate limiter. Keys on the real TCP peer IP unless a trusted proxy is
     // declared (RATE_LIMIT_TRUST_PROXY), preventing X-Forwarded-For spoofing.
@@ -252,6 +348,24 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Attachment stores from config: the S3 bucket when configured (with the upload
+/// directory still serving attachments written before the switch), else local.
+fn blob_stores(config: &config::Config) -> anyhow::Result<BlobStores> {
+    let Some(ref s3) = config.storage.s3 else {
+        info!("Attachment storage: local ({})", config.server.upload_dir);
+        return Ok(BlobStores::local(&config.server.upload_dir));
+    };
+    let store = S3BlobStore::new(s3.clone())
+        .map_err(|e| anyhow::anyhow!("Invalid S3 configuration: {}", e))?;
+    info!(
+        "Attachment storage: s3 (bucket {} at {})",
+        s3.bucket, s3.endpoint
+    );
+    let primary: Arc<dyn BlobStore> = Arc::new(store);
+    let local: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new(&config.server.upload_dir));
+    Ok(BlobStores::new(primary, local))
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -0,0 +1,321 @@
+//! Attachment blob storage.
+//!
+//! Attachment rows record which backend holds their bytes (`storage_type`) and
+//! the key within it (`storage_path`). [`BlobStore`] is implemented for the local
+//! filesystem, S3-compatible object stores and an in-memory fake for tests.
+
+pub mod local;
+pub mod memory;
+pub mod migrate;
+pub mod s3;
+
+pub use local::LocalBlobStore;
+pub use memory::MemoryBlobStore;
+pub use s3::{S3BlobStore, S3Config};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
+use rstify_core::error::CoreError;
+use rstify_core::models::Attachment;
+use rstify_core::repositories::MessageRepository;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+/// One stored blob, as returned by [`BlobStore::list`].
+#[derive(Debug, Clone)]
+pub struct BlobEntry {
+    pub key: String,
+    /// Last modification time, when the backend reports one.
+    pub modified: Option<SystemTime>,
+}
+
+/// Response headers a presigned download should carry.
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadHeaders<'a> {
+    pub filename: &'a str,
+    pub content_type: &'a str,
+}
+
+/// Blob content as it is read from the backend.
+pub type BlobStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// An inclusive byte range within a blob, as in an HTTP `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Number of bytes covered; never zero.
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Value recorded in `attachments.storage_type` for blobs written here.
+    fn kind(&self) -> &'static str;
+
+    async fn put(
+        &self,
+        key: &str,
+        data: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<(), CoreError>;
+    /// Store the contents of a staged file, which may be moved in the process.
+    /// The default reads it into memory; stores that can do better override it.
+    async fn put_file(
+        &self,
+        key: &str,
+        path: &Path,
+        content_type: Option<&str>,
+    ) -> Result<(), CoreError> {
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| CoreError::Internal(format!("Failed to read staged file: {e}")))?;
+        self.put(key, &data, content_type).await
+    }
+    /// `CoreError::NotFound` when the key does not exist.
+    async fn get(&self, key: &str) -> Result<Vec<u8>, CoreError>;
+    /// Stream a blob, or the given range of it, without holding it all in
+    /// memory. The range must lie within the blob. The default reads the whole
+    /// blob; stores that can stream override it.
+    async fn open(&self, key: &str, range: Option<ByteRange>) -> Result<BlobStream, CoreError> {
+        let mut data = self.get(key).await?;
+        if let Some(range) = range {
+            let end = (range.end as usize + 1).min(data.len());
+            data = data
+                .get(range.start as usize..end)
+                .unwrap_or_default()
+                .to_vec();
+        }
+        Ok(Box::pin(stream::once(async { Ok(Bytes::from(data)) })))
+    }
+    /// Deleting a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<(), CoreError>;
+    /// Top-level blobs only; nested keys (e.g. `icons/`) are not listed.
+    async fn list(&self) -> Result<Vec<BlobEntry>, CoreError>;
+
+    /// A time-limited URL the client can download from directly, or `None` if the
+    /// backend can't serve downloads itself.
+    fn presigned_get(
+        &self,
+        _key: &str,
+        _headers: DownloadHeaders<'_>,
+        _expires_in: Duration,
+    ) -> Option<String> {
+        None
+    }
+}
+
+/// A `Content-Disposition` value that survives any filename: an ASCII
+/// `filename` fallback for old clients plus the exact name as an RFC 5987
+/// `filename*`.
+pub fn content_disposition(disposition: &str, filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut encoded = String::with_capacity(filename.len());
+    for byte in filename.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    format!(
+        "{}; filename=\"{}\"; filename*=UTF-8''{}",
+        disposition, fallback, encoded
+    )
+}
+
+/// The key of an attachment within its store. Rows written before pluggable
+/// storage hold the full local path, so only the final segment is used.
+pub fn blob_key(storage_path: &str) -> &str {
+    storage_path
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(storage_path)
+}
+
+/// Hex SHA-256 of attachment content, used as its blob key so identical uploads
+/// share one blob.
+pub fn content_hash(data: &[u8]) -> String {
+    let mut hasher = ContentHasher::default();
+    hasher.update(data);
+    hasher.finish()
+}
+
+/// Incremental [`content_hash`], for content that arrives in chunks.
+#[derive(Default)]
+pub struct ContentHasher(Sha256);
+
+impl ContentHasher {
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.0.update(chunk);
+    }
+
+    pub fn finish(self) -> String {
+        s3::hex(&self.0.finalize())
+    }
+}
+
+/// The store new attachments are written to, plus the local store so that
+/// attachments written before switching backends remain readable.
+#[derive(Clone)]
+pub struct BlobStores {
+    primary: Arc<dyn BlobStore>,
+    local: Arc<dyn BlobStore>,
+    /// Held shared while an attachment row and its blob are written, and
+    /// exclusively by [`release`](Self::release) between counting references
+    /// and deleting, so a concurrent upload of the same content can't have its
+    /// blob deleted under its new row.
+    writes: Arc<RwLock<()>>,
+}
+
+impl BlobStores {
+    pub fn new(primary: Arc<dyn BlobStore>, local: Arc<dyn BlobStore>) -> Self {
+        Self {
+            primary,
+            local,
+            writes: Arc::new(RwLock::new(())),
+        }
+    }
+
+    /// Keep everything on the local filesystem under `upload_dir`.
+    pub fn local(upload_dir: &str) -> Self {
+        let local: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new(upload_dir));
+        Self::new(local.clone(), local)
+    }
+
+    pub fn primary(&self) -> &Arc<dyn BlobStore> {
+        &self.primary
+    }
+
+    /// The store holding blobs of the given `storage_type`, if it is configured.
+    pub fn for_type(&self, storage_type: &str) -> Option<&Arc<dyn BlobStore>> {
+        if self.primary.kind() == storage_type {
+            Some(&self.primary)
+        } else if self.local.kind() == storage_type {
+            Some(&self.local)
+        } else {
+            None
+        }
+    }
+
+    /// Hold while creating an attachment row and writing its blob.
+    pub async fn write_guard(&self) -> RwLockReadGuard<'_, ()> {
+        self.writes.read().await
+    }
+
+    /// Delete the blob (and thumbnail) behind an attachment whose row has already
+    /// been deleted, unless other attachments still reference it. Returns whether
+    /// it was deleted.
+    pub async fn release(
+        &self,
+        messages: &dyn MessageRepository,
+        attachment: &Attachment,
+    ) -> Result<bool, CoreError> {
+        let Some(store) = self.for_type(&attachment.storage_type) else {
+            return Ok(false);
+        };
+        let _writes = self.writes.write().await;
+        let references = messages
+            .count_attachment_references(&attachment.storage_type, &attachment.storage_path)
+            .await?;
+        if references > 0 {
+            return Ok(false);
+        }
+        store.delete(blob_key(&attachment.storage_path)).await?;
+        if let Some(thumbnail) = &attachment.thumbnail_path {
+            store.delete(thumbnail).await?;
+        }
+        Ok(true)
+    }
+
+    /// Each distinct configured store, primary first.
+    pub fn all(&self) -> Vec<&Arc<dyn BlobStore>> {
+        if Arc::ptr_eq(&self.primary, &self.local) || self.primary.kind() == self.local.kind() {
+            vec![&self.primary]
+        } else {
+            vec![&self.primary, &self.local]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_key_strips_legacy_directories() {
+        assert_eq!(blob_key("./uploads/abc_report.pdf"), "abc_report.pdf");
+        assert_eq!(blob_key("/var/uploads/abc_report.pdf"), "abc_report.pdf");
+        assert_eq!(blob_key("abc_report.pdf"), "abc_report.pdf");
+    }
+
+    #[test]
+    fn content_hash_is_hex_sha256() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn content_disposition_encodes_non_ascii_names() {
+        assert_eq!(
+            content_disposition("attachment", "report.pdf"),
+            "attachment; filename=\"report.pdf\"; filename*=UTF-8''report.pdf"
+        );
+        assert_eq!(
+            content_disposition("inline", "Übersicht 2026.pdf"),
+            "inline; filename=\"_bersicht_2026.pdf\"; filename*=UTF-8''%C3%9Cbersicht%202026.pdf"
+        );
+    }
+
+    #[tokio::test]
+    async fn default_open_slices_the_range() {
+        use futures_util::TryStreamExt;
+
+        let store = MemoryBlobStore::new();
+        store.put("k", b"0123456789", None).await.unwrap();
+        let range = ByteRange { start: 2, end: 5 };
+        let chunks: Vec<Bytes> = store
+            .open("k", Some(range))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), b"2345");
+        assert_eq!(range.length(), 4);
+    }
+
+    #[test]
+    fn for_type_falls_back_to_local() {
+        let stores = BlobStores::new(
+            Arc::new(MemoryBlobStore::new()),
+            Arc::new(LocalBlobStore::new("/tmp/unused")),
+        );
+        assert_eq!(stores.for_type("memory").unwrap().kind(), "memory");
+        assert_eq!(stores.for_type("local").unwrap().kind(), "local");
+        assert!(stores.for_type("s3").is_none());
+        assert_eq!(stores.all().len(), 2);
+        assert_eq!(BlobStores::local("/tmp/unused").all().len(), 1);
+    }
+}
@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use crate::{BlobEntry, BlobStore, BlobStream, ByteRange};
+
+/// Blobs as files directly under the upload directory.
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, CoreError> {
+        // Keys are generated server-side, but never let one escape the root.
+        if key.is_empty() || key.contains(['/', '\\']) || key == "." || key == ".." {
+            return Err(CoreError::Validation(format!("Invalid blob key '{}'", key)));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    fn kind(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        data: &[u8],
+        _content_type: Option<&str>,
+    ) -> Result<(), CoreError> {
+        let path = self.path(key)?;
+        tokio::fs::create_dir_all(&self.root)
+            .await
+            .map_err(|e| CoreError::Internal(format!("Failed to create upload dir: {e}")))?;
+        // Content-addressed blobs can be rewritten while being read, so write
+        // to a temporary file and rename it into place.
+        static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+        let tmp = self.root.join(format!(
+            ".{}.{}-{}.tmp",
+            key,
+            std::process::id(),
+            NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+        ));
+        if let Err(e) = tokio::fs::write(&tmp, data).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(CoreError::Internal(format!("Failed to write file: {e}")));
+        }
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| CoreError::Internal(format!("Failed to move file into place: {e}")))
+    }
+
+    async fn put_file(
+        &self,
+        key: &str,
+        staged: &Path,
+        content_type: Option<&str>,
+    ) -> Result<(), CoreError> {
+        let path = self.path(key)?;
+        tokio::fs::create_dir_all(&self.root)
+            .await
+            .map_err(|e| CoreError::Internal(format!("Failed to create upload dir: {e}")))?;
+        // A rename is atomic and free when staging shares the filesystem;
+        // otherwise fall back to copying the bytes.
+        if tokio::fs::rename(staged, &path).await.is_ok() {
+            return Ok(());
+        }
+        let data = tokio::fs::read(staged)
+            .await
+            .map_err(|e| CoreError::Internal(format!("Failed to read staged file: {e}")))?;
+        self.put(key, &data, content_type).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, CoreError> {
+        let path = self.path(key)?;
+        tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+            ErrorKind::NotFound => CoreError::NotFound(format!("Blob '{}' not found", key)),
+            _ => CoreError::Internal(format!("Failed to read file: {e}")),
+        })
+    }
+
+    async fn open(&self, key: &str, range: Option<ByteRange>) -> Result<BlobStream, CoreError> {
+        let path = self.path(key)?;
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => CoreError::NotFound(format!("Blob '{}' not found", key)),
+                _ => CoreError::Internal(format!("Failed to open file: {e}")),
+            })?;
+        let Some(range) = range else {
+            return Ok(Box::pin(ReaderStream::new(file)));
+        };
+        file.seek(SeekFrom::Start(range.start))
+            .await
+            .map_err(|e| CoreError::Internal(format!("Failed to seek file: {e}")))?;
+        Ok(Box::pin(ReaderStream::new(file.take(range.length()))))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CoreError> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(CoreError::Internal(format!("Failed to delete file: {e}"))),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<BlobEntry>, CoreError> {
+        let mut read_dir = match tokio::fs::read_dir(&self.root).await {
+            Ok(d) => d,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(CoreError::Internal(format!(
+                    "Cannot read upload dir {}: {e}",
+                    self.root.display()
+                )))
+            }
+        };
+
+        let mut entries = Vec::new();
+        while let Some(entry) = read_dir
+            .next_entry()
+            .await
+            .map_err(|e| CoreError::Internal(format!("Error reading dir entry: {e}")))?
+        {
+            // Only top-level files; subdirectories like icons/ hold other data.
+            match entry.file_type().await {
+                Ok(ft) if ft.is_file() => {}
+                _ => continue,
+            }
+            let modified = entry.metadata().await.ok().and_then(|m| m.modified().ok());
+            entries.push(BlobEntry {
+                key: entry.file_name().to_string_lossy().into_owned(),
+                modified,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn round_trip_and_list_skips_subdirs() {
+        let dir = std::env::temp_dir().join(format!("rstify-local-blob-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let store = LocalBlobStore::new(&dir);
+
+        store.put("a.bin", b"hello", None).await.unwrap();
+        tokio::fs::create_dir_all(dir.join("icons")).await.unwrap();
+        assert_eq!(store.get("a.bin").await.unwrap(), b"hello");
+
+        let keys: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys, vec!["a.bin".to_string()]);
+
+        store.delete("a.bin").await.unwrap();
+        store.delete("a.bin").await.unwrap();
+        assert!(matches!(
+            store.get("a.bin").await,
+            Err(CoreError::NotFound(_))
+        ));
+        assert!(store.get("../etc/passwd").await.is_err());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn open_streams_ranges() {
+        use futures_util::TryStreamExt;
+
+        let dir = std::env::temp_dir().join(format!("rstify-local-range-{}", std::process::id()));
+        let store = LocalBlobStore::new(&dir);
+        store.put("r.bin", b"0123456789", None).await.unwrap();
+
+        let read = |range| {
+            let store = &store;
+            async move {
+                let chunks: Vec<bytes::Bytes> = store
+                    .open("r.bin", range)
+                    .await
+                    .unwrap()
+                    .try_collect()
+                    .await
+                    .unwrap();
+                chunks.concat()
+            }
+        };
+        assert_eq!(read(None).await, b"0123456789");
+        assert_eq!(read(Some(ByteRange { start: 7, end: 9 })).await, b"789");
+        assert!(matches!(
+            store.open("missing", None).await,
+            Err(CoreError::NotFound(_))
+        ));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use rstify_core::error::CoreError;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::{BlobEntry, BlobStore, DownloadHeaders};
+
+/// Process-local store for tests. Optionally hands out fake presigned URLs under
+/// a base URL so redirect handling can be exercised without a real object store.
+#[derive(Default)]
+pub struct MemoryBlobStore {
+    blobs: Mutex<HashMap<String, (Vec<u8>, SystemTime)>>,
+    presign_base: Option<String>,
+}
+
+impl MemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_presign_base(mut self, base: impl Into<String>) -> Self {
+        self.presign_base = Some(base.into());
+        self
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.blobs.lock().unwrap().contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl BlobStore for MemoryBlobStore {
+    fn kind(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        data: &[u8],
+        _content_type: Option<&str>,
+    ) -> Result<(), CoreError> {
+        self.blobs
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (data.to_vec(), SystemTime::now()));
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, CoreError> {
+        self.blobs
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|(data, _)| data.clone())
+            .ok_or_else(|| CoreError::NotFound(format!("Blob '{}' not found", key)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CoreError> {
+        self.blobs.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<BlobEntry>, CoreError> {
+        Ok(self
+            .blobs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, (_, modified))| BlobEntry {
+                key: key.clone(),
+                modified: Some(*modified),
+            })
+            .collect())
+    }
+
+    fn presigned_get(
+        &self,
+        key: &str,
+        _headers: DownloadHeaders<'_>,
+        expires_in: Duration,
+    ) -> Option<String> {
+        self.presign_base
+            .as_ref()
+            .map(|base| format!("{}/{}?expires={}", base, key, expires_in.as_secs()))
+    }
+}
@@ -0,0 +1,105 @@
+//! Move attachments from one store to another, e.g. local files into S3.
+
+use rstify_core::error::CoreError;
+use rstify_core::models::Attachment;
+use rstify_core::repositories::Repositories;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::{blob_key, BlobStore};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub moved: u64,
+    /// Rows whose blob was already gone from the source store.
+    pub missing: u64,
+    pub failed: u64,
+}
+
+/// Copy every attachment held by `from` into `to` and repoint its row. Each row is
+/// updated as soon as its blob is copied, so an interrupted run can simply be
+/// repeated. With `delete_source`, the source blob is removed after the row moves.
+pub async fn migrate_attachments(
+    repos: &Repositories,
+    from: &dyn BlobStore,
+    to: &dyn BlobStore,
+    delete_source: bool,
+) -> Result<MigrationReport, CoreError> {
+    if from.kind() == to.kind() {
+        return Err(CoreError::Validation(format!(
+            "Source and destination are both '{}' stores",
+            from.kind()
+        )));
+    }
+
+    let attachments = repos
+        .messages
+        .list_attachments_by_storage_type(from.kind())
+        .await?;
+    info!(
+        "Migrating {} attachment(s) from {} to {}",
+        attachments.len(),
+        from.kind(),
+        to.kind()
+    );
+
+    // Identical attachments share a blob: copy each blob once, then repoint
+    // every row that references it.
+    let mut blobs: Vec<(String, Vec<Attachment>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for attachment in attachments {
+        let key = blob_key(&attachment.storage_path).to_string();
+        let slot = *index.entry(key.clone()).or_insert_with(|| {
+            blobs.push((key, Vec::new()));
+            blobs.len() - 1
+        });
+        blobs[slot].1.push(attachment);
+    }
+
+    let mut report = MigrationReport::default();
+    for (key, rows) in blobs {
+        let first = &rows[0];
+        let count = rows.len() as u64;
+        let data = match from.get(&key).await {
+            Ok(data) => data,
+            Err(CoreError::NotFound(_)) => {
+                warn!(
+                    "Attachment {} has no blob '{}' in the {} store; skipping",
+                    first.id,
+                    key,
+                    from.kind()
+                );
+                report.missing += count;
+                continue;
+            }
+            Err(e) => {
+                warn!("Failed to read attachment {}: {}", first.id, e);
+                report.failed += count;
+                continue;
+            }
+        };
+
+        if let Err(e) = to.put(&key, &data, first.content_type.as_deref()).await {
+            warn!("Failed to copy attachment {}: {}", first.id, e);
+            report.failed += count;
+            continue;
+        }
+        for row in &rows {
+            repos
+                .messages
+                .update_attachment_storage(row.id, to.kind(), &key)
+                .await?;
+        }
+        report.moved += count;
+
+        if delete_source {
+            if let Err(e) = from.delete(&key).await {
+                warn!(
+                    "Copied attachment {} but could not delete source: {}",
+                    first.id, e
+                );
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,126 @@
+use rstify_core::repositories::NewMessage;
+use rstify_db::pool::Database;
+use rstify_storage::migrate::{migrate_attachments, MigrationReport};
+use rstify_storage::{BlobStore, LocalBlobStore, MemoryBlobStore};
+
+#[tokio::test]
+async fn migrates_local_attachments_and_repoints_rows() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    db.migrate().await.unwrap();
+    let repos = db.repositories();
+
+    let user = repos
+        .users
+        .create("alice", "hash", None, false)
+        .await
+        .unwrap();
+    let app = repos
+        .applications
+        .create(user.id, None, "backup", None, "AP_migrate", 5)
+        .await
+        .unwrap();
+    let msg = repos
+        .messages
+        .create(NewMessage {
+            application_id: Some(app.id),
+            user_id: Some(user.id),
+            message: "with files",
+            priority: 5,
+            inbox: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let dir = std::env::temp_dir().join(format!("rstify-migrate-{}", std::process::id()));
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    let local = LocalBlobStore::new(&dir);
+    local.put("a_one.txt", b"one", None).await.unwrap();
+
+    // A legacy row holding the full path, and one whose file has gone missing.
+    let legacy_path = dir.join("a_one.txt").to_string_lossy().into_owned();
+    let moved = repos
+        .messages
+        .create_attachment(
+            msg.id,
+            "one.txt",
+            None,
+            3,
+            "local",
+            &legacy_path,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    // A second attachment sharing the same blob.
+    let shared = repos
+        .messages
+        .create_attachment(
+            msg.id,
+            "copy.txt",
+            None,
+            3,
+            "local",
+            "a_one.txt",
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    repos
+        .messages
+        .create_attachment(
+            msg.id,
+            "gone.txt",
+            None,
+            3,
+            "local",
+            "b_gone.txt",
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let remote = MemoryBlobStore::new();
+    let report = migrate_attachments(&repos, &local, &remote, true)
+        .await
+        .unwrap();
+    assert_eq!(
+        report,
+        MigrationReport {
+            moved: 2,
+            missing: 1,
+            failed: 0
+        }
+    );
+
+    let row = repos
+        .messages
+        .find_attachment(moved.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.storage_type, "memory");
+    assert_eq!(row.storage_path, "a_one.txt");
+    let row = repos
+        .messages
+        .find_attachment(shared.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.storage_type, "memory");
+    assert_eq!(remote.get("a_one.txt").await.unwrap(), b"one");
+    assert_eq!(remote.len(), 1);
+    assert!(!dir.join("a_one.txt").exists(), "source removed");
+
+    // Rerunning only revisits what is still local.
+    let again = migrate_attachments(&repos, &local, &remote, true)
+        .await
+        .unwrap();
+    assert_eq!(again.moved, 0);
+    assert_eq!(again.missing, 1);
+
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+}
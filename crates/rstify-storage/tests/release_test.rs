@@ -0,0 +1,65 @@
+use rstify_core::repositories::NewMessage;
+use rstify_db::pool::Database;
+use rstify_storage::{BlobStore, BlobStores, MemoryBlobStore};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn release_waits_for_an_in_flight_upload_of_the_same_content() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    db.migrate().await.unwrap();
+    let repos = db.repositories();
+
+    let user = repos
+        .users
+        .create("alice", "hash", None, false)
+        .await
+        .unwrap();
+    let app = repos
+        .applications
+        .create(user.id, None, "files", None, "AP_release", 5)
+        .await
+        .unwrap();
+    let msg = repos
+        .messages
+        .create(NewMessage {
+            application_id: Some(app.id),
+            user_id: Some(user.id),
+            message: "with files",
+            priority: 5,
+            inbox: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let store = Arc::new(MemoryBlobStore::new());
+    let stores = BlobStores::new(store.clone(), store.clone());
+    store.put("shared", b"data", None).await.unwrap();
+    let old = repos
+        .messages
+        .create_attachment(msg.id, "a.txt", None, 4, "memory", "shared", None, None)
+        .await
+        .unwrap();
+    repos.messages.delete_attachment(old.id).await.unwrap();
+
+    // An upload of the same content is mid-way when the old row is released.
+    let guard = stores.write_guard().await;
+    let release = tokio::spawn({
+        let stores = stores.clone();
+        let messages = repos.messages.clone();
+        async move { stores.release(messages.as_ref(), &old).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!release.is_finished(), "release waits for the upload");
+    repos
+        .messages
+        .create_attachment(msg.id, "b.txt", None, 4, "memory", "shared", None, None)
+        .await
+        .unwrap();
+    store.put("shared", b"data", None).await.unwrap();
+    drop(guard);
+
+    assert!(!release.await.unwrap().unwrap(), "blob is still referenced");
+    assert_eq!(store.get("shared").await.unwrap(), b"data");
+}
@@ -0,0 +1,174 @@
+//! Drives `S3BlobStore` against a minimal in-process S3 stand-in that speaks the
+//! subset of the API the store uses (object PUT/GET/DELETE and ListObjectsV2).
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use rstify_core::error::CoreError;
+use rstify_storage::{BlobStore, DownloadHeaders, S3BlobStore, S3Config};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+fn signed(headers: &HeaderMap) -> bool {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("AWS4-HMAC-SHA256 Credential=test-key/"))
+        && headers.contains_key("x-amz-date")
+        && headers.contains_key("x-amz-content-sha256")
+}
+
+async fn object(
+    State(objects): State<Objects>,
+    Path((bucket, key)): Path<(String, String)>,
+    method: axum::http::Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    if bucket != "rstify" || !signed(&headers) {
+        return (StatusCode::FORBIDDEN, Vec::new());
+    }
+    let mut objects = objects.lock().unwrap();
+    match method.as_str() {
+        "PUT" => {
+            objects.insert(key, body.to_vec());
+            (StatusCode::OK, Vec::new())
+        }
+        "GET" => match objects.get(&key) {
+            Some(data) => (StatusCode::OK, data.clone()),
+            None => (
+                StatusCode::NOT_FOUND,
+                b"<Error><Code>NoSuchKey</Code></Error>".to_vec(),
+            ),
+        },
+        "DELETE" => {
+            objects.remove(&key);
+            (StatusCode::NO_CONTENT, Vec::new())
+        }
+        _ => (StatusCode::METHOD_NOT_ALLOWED, Vec::new()),
+    }
+}
+
+/// ListObjectsV2 with one key per page, to exercise continuation.
+async fn list(
+    State(objects): State<Objects>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if !signed(&headers) || query.get("list-type").map(String::as_str) != Some("2") {
+        return (StatusCode::BAD_REQUEST, String::new());
+    }
+    let prefix = query.get("prefix").cloned().unwrap_or_default();
+    let after = query.get("continuation-token").cloned().unwrap_or_default();
+    let objects = objects.lock().unwrap();
+    let mut keys = objects
+        .keys()
+        .filter(|k| k.starts_with(&prefix) && !k[prefix.len()..].contains('/'))
+        .filter(|k| after.is_empty() || k.as_str() > after.as_str());
+    let mut xml = String::from("<ListBucketResult>");
+    if let Some(key) = keys.next() {
+        xml.push_str(&format!(
+            "<Contents><Key>{}</Key><LastModified>2020-01-01T00:00:00.000Z</LastModified></Contents>",
+            key.replace('&', "&amp;")
+        ));
+        if keys.next().is_some() {
+            xml.push_str(&format!(
+                "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+                key.replace('&', "&amp;")
+            ));
+        }
+    }
+    xml.push_str("</ListBucketResult>");
+    (StatusCode::OK, xml)
+}
+
+async fn fake_s3() -> (String, Objects) {
+    let objects: Objects = Arc::default();
+    let app = Router::new()
+        .route("/{bucket}/", get(list))
+        .route("/{bucket}/{*key}", get(object).put(object).delete(object))
+        .with_state(objects.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), objects)
+}
+
+fn store(endpoint: String) -> S3BlobStore {
+    S3BlobStore::new(S3Config {
+        endpoint,
+        bucket: "rstify".into(),
+        region: "us-east-1".into(),
+        access_key_id: "test-key".into(),
+        secret_access_key: "test-secret".into(),
+        prefix: "attachments".into(),
+        path_style: true,
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn s3_store_round_trip() {
+    let (endpoint, objects) = fake_s3().await;
+    let store = store(endpoint.clone());
+
+    store
+        .put("a_report.txt", b"hello", Some("text/plain"))
+        .await
+        .unwrap();
+    store.put("b_image.png", b"png", None).await.unwrap();
+    objects
+        .lock()
+        .unwrap()
+        .insert("elsewhere/c.txt".into(), b"not ours".to_vec());
+    assert!(objects
+        .lock()
+        .unwrap()
+        .contains_key("attachments/a_report.txt"));
+
+    assert_eq!(store.get("a_report.txt").await.unwrap(), b"hello");
+    assert!(matches!(
+        store.get("missing.txt").await,
+        Err(CoreError::NotFound(_))
+    ));
+
+    let mut keys: Vec<String> = store
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|e| {
+            assert!(e.modified.is_some());
+            e.key
+        })
+        .collect();
+    keys.sort();
+    assert_eq!(keys, vec!["a_report.txt", "b_image.png"]);
+
+    store.delete("a_report.txt").await.unwrap();
+    assert!(!objects
+        .lock()
+        .unwrap()
+        .contains_key("attachments/a_report.txt"));
+
+    let url = store
+        .presigned_get(
+            "b_image.png",
+            DownloadHeaders {
+                filename: "image.png",
+                content_type: "image/png",
+            },
+            Duration::from_secs(300),
+        )
+        .unwrap();
+    assert!(url.starts_with(&format!("{}/rstify/attachments/b_image.png?", endpoint)));
+    assert!(url.contains("X-Amz-Expires=300"));
+    assert!(url.contains("response-content-type=image%2Fpng"));
+    assert!(url.contains("X-Amz-Signature="));
+}
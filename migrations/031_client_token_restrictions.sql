@@ -0,0 +1,9 @@
+-- Optional expiry and network restrictions for client tokens, plus last-use tracking.
+ALTER TABLE clients ADD COLUMN expires_at TEXT;
+-- JSON array of CIDRs (e.g. ["10.0.0.0/8","192.168.1.7"]); NULL = any address.
+ALTER TABLE clients ADD COLUMN allowed_ips TEXT;
+ALTER TABLE clients ADD COLUMN last_used_at TEXT;
+ALTER TABLE clients ADD COLUMN last_used_ip TEXT;
+-- Set once the owner has been warned about the upcoming expiry.
+ALTER TABLE clients ADD COLUMN expiry_notified_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_clients_expires_at ON clients(expires_at) WHERE expires_at IS NOT NULL;
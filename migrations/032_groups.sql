@@ -0,0 +1,38 @@
+-- Groups (teams) that can own topics and applications and hold topic grants.
+-- Named user_groups because GROUPS is an SQLite keyword.
+CREATE TABLE IF NOT EXISTS user_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL,
+    description TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+-- role is 'admin' (manages members and group-owned resources) or 'member'.
+CREATE TABLE IF NOT EXISTS group_members (
+    group_id INTEGER NOT NULL REFERENCES user_groups(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (group_id, user_id)
+);
+CREATE INDEX IF NOT EXISTS idx_group_members_user_id ON group_members(user_id);
+ALTER TABLE topics ADD COLUMN group_id INTEGER REFERENCES user_groups(id) ON DELETE SET NULL;
+ALTER TABLE applications ADD COLUMN group_id INTEGER REFERENCES user_groups(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_topics_group_id ON topics(group_id) WHERE group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_applications_group_id ON applications(group_id) WHERE group_id IS NOT NULL;
+-- A topic permission is granted to exactly one user or one group. SQLite cannot
+-- relax NOT NULL in place, so rebuild the table.
+CREATE TABLE topic_permissions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    group_id INTEGER REFERENCES user_groups(id) ON DELETE CASCADE,
+    topic_pattern TEXT NOT NULL,
+    can_read BOOLEAN DEFAULT FALSE,
+    can_write BOOLEAN DEFAULT FALSE,
+    CHECK ((user_id IS NULL) != (group_id IS NULL))
+);
+INSERT INTO topic_permissions_new (id, user_id, topic_pattern, can_read, can_write)
+    SELECT id, user_id, topic_pattern, can_read, can_write FROM topic_permissions;
+DROP TABLE topic_permissions;
+ALTER TABLE topic_permissions_new RENAME TO topic_permissions;
+CREATE INDEX IF NOT EXISTS idx_topic_permissions_user_id ON topic_permissions(user_id);
+CREATE INDEX IF NOT EXISTS idx_topic_permissions_group_id ON topic_permissions(group_id);
@@ -0,0 +1,4 @@
+-- Per-account brute-force protection: consecutive failed logins and the time
+-- until which further attempts are refused. Both reset on a successful login.
+ALTER TABLE users ADD COLUMN failed_logins INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN locked_until TEXT;
@@ -0,0 +1,12 @@
+-- Devices (clients) subscribe to topics or topic patterns to get push for
+-- topics they can read but don't own. A NULL notify_policy follows the
+-- topic's own policy.
+CREATE TABLE IF NOT EXISTS subscriptions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    topic_pattern TEXT NOT NULL,
+    notify_policy TEXT,
+    notify_priority_min INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (client_id, topic_pattern)
+);
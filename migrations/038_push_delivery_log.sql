@@ -0,0 +1,16 @@
+-- One row per push attempt to a device. `outcome` is `sent`, `failed` or
+-- `pruned` (FCM rejected the token for good and it was removed).
+CREATE TABLE IF NOT EXISTS push_delivery_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    message_id INTEGER,
+    outcome TEXT NOT NULL,
+    status_code INTEGER,
+    error_code TEXT,
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    attempted_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_push_delivery_client ON push_delivery_log(client_id, attempted_at DESC);
+CREATE INDEX idx_push_delivery_age ON push_delivery_log(attempted_at);
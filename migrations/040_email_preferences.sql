@@ -0,0 +1,14 @@
+-- Per-user opt-in to email for messages in their own inbox. NULL topics means
+-- the user's own topics; NULL application_ids means all their applications.
+-- Both hold JSON arrays. Quiet hours are "HH:MM" in the user's timezone.
+CREATE TABLE IF NOT EXISTS email_preferences (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    min_priority INTEGER NOT NULL DEFAULT 5,
+    topics TEXT,
+    application_ids TEXT,
+    quiet_start TEXT,
+    quiet_end TEXT,
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,27 @@
+-- Do-not-disturb schedules. A NULL client_id covers all of the user's devices
+-- and their email; otherwise only that device. Times are "HH:MM" in the
+-- schedule's timezone, days a JSON array of weekdays ("mon".."sun", NULL =
+-- every day). Messages at or above override_priority still go out.
+CREATE TABLE IF NOT EXISTS quiet_hours (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    client_id INTEGER REFERENCES clients(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    days TEXT,
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    override_priority INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_quiet_hours_user ON quiet_hours(user_id);
+
+-- Pushes held back by quiet hours, summarized when the window ends.
+CREATE TABLE IF NOT EXISTS suppressed_pushes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_suppressed_pushes_client ON suppressed_pushes(client_id);
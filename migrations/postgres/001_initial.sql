@@ -0,0 +1,250 @@
+-- PostgreSQL schema, equivalent to SQLite migrations 001-034.
+-- Timestamps are stored as UTC text ('YYYY-MM-DD HH24:MI:SS'), the same format
+-- SQLite's datetime() produces, so both backends share one model layer.
+
+CREATE FUNCTION utc_now(shift INTERVAL DEFAULT INTERVAL '0') RETURNS TEXT
+    LANGUAGE SQL STABLE
+    AS $$ SELECT to_char((now() AT TIME ZONE 'UTC') + shift, 'YYYY-MM-DD HH24:MI:SS') $$;
+
+CREATE TABLE users (
+    id BIGSERIAL PRIMARY KEY,
+    username TEXT UNIQUE NOT NULL,
+    password_hash TEXT NOT NULL,
+    email TEXT,
+    is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    updated_at TEXT NOT NULL DEFAULT utc_now(),
+    failed_logins BIGINT NOT NULL DEFAULT 0,
+    locked_until TEXT
+);
+
+CREATE TABLE user_groups (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    description TEXT,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+
+CREATE TABLE group_members (
+    group_id BIGINT NOT NULL REFERENCES user_groups(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    PRIMARY KEY (group_id, user_id)
+);
+CREATE INDEX idx_group_members_user_id ON group_members(user_id);
+
+CREATE TABLE applications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES user_groups(id) ON DELETE SET NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    token TEXT UNIQUE NOT NULL,
+    default_priority INTEGER NOT NULL DEFAULT 5,
+    image TEXT,
+    retention_days INTEGER,
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    updated_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_applications_user_id ON applications(user_id);
+CREATE INDEX idx_applications_group_id ON applications(group_id) WHERE group_id IS NOT NULL;
+
+CREATE TABLE clients (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token TEXT UNIQUE NOT NULL,
+    fcm_token TEXT,
+    scopes TEXT NOT NULL DEFAULT '["read","write"]',
+    expires_at TEXT,
+    allowed_ips TEXT,
+    last_used_at TEXT,
+    last_used_ip TEXT,
+    expiry_notified_at TEXT,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_clients_user_id ON clients(user_id);
+CREATE INDEX idx_clients_fcm_token ON clients(fcm_token) WHERE fcm_token IS NOT NULL;
+CREATE INDEX idx_clients_expires_at ON clients(expires_at) WHERE expires_at IS NOT NULL;
+
+CREATE TABLE topics (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT UNIQUE NOT NULL,
+    owner_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    group_id BIGINT REFERENCES user_groups(id) ON DELETE SET NULL,
+    description TEXT,
+    everyone_read BOOLEAN NOT NULL DEFAULT TRUE,
+    everyone_write BOOLEAN NOT NULL DEFAULT TRUE,
+    cache_duration TEXT,
+    notify_policy TEXT NOT NULL DEFAULT 'always',
+    notify_priority_min INTEGER DEFAULT 0,
+    notify_condition TEXT,
+    notify_digest_interval INTEGER,
+    store_policy TEXT NOT NULL DEFAULT 'all',
+    store_interval INTEGER,
+    inbox_override TEXT,
+    inbox_priority_min INTEGER,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_topics_group_id ON topics(group_id) WHERE group_id IS NOT NULL;
+
+CREATE TABLE topic_permissions (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES user_groups(id) ON DELETE CASCADE,
+    topic_pattern TEXT NOT NULL,
+    can_read BOOLEAN NOT NULL DEFAULT FALSE,
+    can_write BOOLEAN NOT NULL DEFAULT FALSE,
+    CHECK ((user_id IS NULL) != (group_id IS NULL))
+);
+CREATE INDEX idx_topic_permissions_user_id ON topic_permissions(user_id);
+CREATE INDEX idx_topic_permissions_group_id ON topic_permissions(group_id);
+
+-- search_vector replaces SQLite's messages_fts table. The 'simple' configuration
+-- does no stemming, matching FTS5's default unicode61 tokenizer.
+CREATE TABLE messages (
+    id BIGSERIAL PRIMARY KEY,
+    application_id BIGINT REFERENCES applications(id) ON DELETE CASCADE,
+    topic_id BIGINT REFERENCES topics(id) ON DELETE CASCADE,
+    user_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+    title TEXT,
+    message TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 5,
+    tags TEXT,
+    click_url TEXT,
+    icon_url TEXT,
+    actions TEXT,
+    extras TEXT,
+    content_type TEXT DEFAULT 'text/plain',
+    scheduled_for TEXT,
+    delivered_at TEXT,
+    expires_at TEXT,
+    source TEXT,
+    inbox BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    search_vector TSVECTOR GENERATED ALWAYS AS (
+        to_tsvector('simple', coalesce(title, '') || ' ' || message)
+    ) STORED,
+    CHECK (
+        (application_id IS NOT NULL AND topic_id IS NULL) OR
+        (application_id IS NULL AND topic_id IS NOT NULL)
+    )
+);
+CREATE INDEX idx_messages_application_id ON messages(application_id);
+CREATE INDEX idx_messages_topic_id ON messages(topic_id);
+CREATE INDEX idx_messages_user_id ON messages(user_id);
+CREATE INDEX idx_messages_created_at ON messages(created_at);
+CREATE INDEX idx_messages_expires_at ON messages(expires_at) WHERE expires_at IS NOT NULL;
+CREATE INDEX idx_messages_scheduled ON messages(scheduled_for, delivered_at) WHERE scheduled_for IS NOT NULL;
+CREATE INDEX idx_messages_search_vector ON messages USING GIN (search_vector);
+
+CREATE TABLE attachments (
+    id BIGSERIAL PRIMARY KEY,
+    message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    filename TEXT NOT NULL,
+    content_type TEXT,
+    size_bytes BIGINT NOT NULL,
+    storage_type TEXT NOT NULL,
+    storage_path TEXT NOT NULL,
+    expires_at TEXT,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_attachments_message_id ON attachments(message_id);
+CREATE INDEX idx_attachments_expires_at ON attachments(expires_at) WHERE expires_at IS NOT NULL;
+
+CREATE TABLE webhook_configs (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token TEXT UNIQUE NOT NULL,
+    webhook_type TEXT NOT NULL,
+    target_topic_id BIGINT REFERENCES topics(id),
+    target_application_id BIGINT REFERENCES applications(id),
+    template TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    direction TEXT NOT NULL DEFAULT 'incoming',
+    target_url TEXT,
+    http_method TEXT NOT NULL DEFAULT 'POST',
+    headers TEXT,
+    body_template TEXT,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    retry_delay_secs INTEGER NOT NULL DEFAULT 10,
+    timeout_secs INTEGER NOT NULL DEFAULT 15,
+    follow_redirects BOOLEAN NOT NULL DEFAULT TRUE,
+    group_name TEXT,
+    secret TEXT,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_webhook_configs_user_id ON webhook_configs(user_id);
+CREATE INDEX idx_webhook_configs_direction ON webhook_configs(direction, enabled);
+
+CREATE TABLE webhook_delivery_log (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_config_id BIGINT NOT NULL REFERENCES webhook_configs(id) ON DELETE CASCADE,
+    message_id BIGINT,
+    status_code INTEGER,
+    response_body_preview TEXT,
+    duration_ms BIGINT NOT NULL,
+    attempted_at TEXT NOT NULL DEFAULT utc_now(),
+    success BOOLEAN NOT NULL DEFAULT FALSE
+);
+CREATE INDEX idx_delivery_log_webhook ON webhook_delivery_log(webhook_config_id, attempted_at DESC);
+CREATE INDEX idx_delivery_log_age ON webhook_delivery_log(attempted_at);
+
+CREATE TABLE webhook_variables (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    UNIQUE (user_id, key)
+);
+
+CREATE TABLE up_registrations (
+    id BIGSERIAL PRIMARY KEY,
+    token TEXT NOT NULL UNIQUE,
+    user_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+    endpoint TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+
+CREATE TABLE mqtt_bridges (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    remote_url TEXT NOT NULL,
+    subscribe_topics TEXT NOT NULL DEFAULT '[]',
+    publish_topics TEXT DEFAULT '[]',
+    username TEXT,
+    password TEXT,
+    qos INTEGER DEFAULT 0,
+    topic_prefix TEXT,
+    auto_create_topics BOOLEAN NOT NULL DEFAULT TRUE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+INSERT INTO settings (key, value) VALUES
+    ('inbox_priority_threshold', '5'),
+    ('audit_retention_days', '365');
+
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    actor_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    actor_name TEXT,
+    action TEXT NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id TEXT,
+    ip TEXT,
+    before TEXT,
+    after TEXT,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
+CREATE INDEX idx_audit_log_actor_id ON audit_log(actor_id);
+CREATE INDEX idx_audit_log_target ON audit_log(target_type, target_id);
@@ -0,0 +1,5 @@
+-- Content-addressed attachments: new blobs are keyed by their SHA-256, so
+-- identical uploads share one blob. A blob's references are the attachment
+-- rows pointing at it; rows written before this migration have no checksum.
+ALTER TABLE attachments ADD COLUMN sha256 TEXT;
+CREATE INDEX idx_attachments_storage ON attachments(storage_type, storage_path);
@@ -0,0 +1,8 @@
+-- Image attachments get a downscaled preview generated in the background.
+-- thumbnail_status: 'pending' until the worker runs, then 'ready', 'skipped'
+-- (too large to decode) or 'failed'; NULL for attachments that aren't images.
+ALTER TABLE attachments ADD COLUMN width BIGINT;
+ALTER TABLE attachments ADD COLUMN height BIGINT;
+ALTER TABLE attachments ADD COLUMN thumbnail_path TEXT;
+ALTER TABLE attachments ADD COLUMN thumbnail_status TEXT;
+CREATE INDEX idx_attachments_thumbnail_pending ON attachments(id) WHERE thumbnail_status = 'pending';
@@ -0,0 +1,12 @@
+-- Devices (clients) subscribe to topics or topic patterns to get push for
+-- topics they can read but don't own. A NULL notify_policy follows the
+-- topic's own policy.
+CREATE TABLE subscriptions (
+    id BIGSERIAL PRIMARY KEY,
+    client_id BIGINT NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    topic_pattern TEXT NOT NULL,
+    notify_policy TEXT,
+    notify_priority_min INTEGER,
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    UNIQUE (client_id, topic_pattern)
+);
@@ -0,0 +1,8 @@
+-- Browser Web Push subscription of a client (RFC 8030 endpoint plus the
+-- RFC 8291 keys its payloads are encrypted to), alongside fcm_token.
+ALTER TABLE clients ADD COLUMN webpush_endpoint TEXT;
+ALTER TABLE clients ADD COLUMN webpush_p256dh TEXT;
+ALTER TABLE clients ADD COLUMN webpush_auth TEXT;
+
+-- `fcm` or `webpush`.
+ALTER TABLE push_delivery_log ADD COLUMN channel TEXT NOT NULL DEFAULT 'fcm';
@@ -0,0 +1,18 @@
+-- Outgoing mail. Rows wait as 'pending' until sent, are retried with backoff
+-- on temporary failures, and end as 'sent' or 'failed'. Kept as the delivery
+-- log until purged.
+CREATE TABLE email_queue (
+    id BIGSERIAL PRIMARY KEY,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    html_body TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TEXT NOT NULL DEFAULT utc_now(),
+    created_at TEXT NOT NULL DEFAULT utc_now(),
+    sent_at TEXT
+);
+
+CREATE INDEX idx_email_queue_due ON email_queue(status, next_attempt_at);
@@ -0,0 +1,3 @@
+-- Apprise-style notification URL (discord://, ntfy://, mailto://, ...) for
+-- outgoing webhooks. When set it decides the transport instead of target_url.
+ALTER TABLE webhook_configs ADD COLUMN IF NOT EXISTS notify_url TEXT;
@@ -0,0 +1,18 @@
+-- Escalation policy for unacknowledged high-priority messages, as JSON:
+-- {"min_priority": 8, "steps": [{"after_minutes": 5, "action": "push"}, ...]}.
+ALTER TABLE topics ADD COLUMN escalation_policy TEXT;
+
+-- Messages being escalated. The policy is copied at publish time so later
+-- edits don't affect escalations already under way. next_at is NULL once the
+-- message is acknowledged or the steps have run out.
+CREATE TABLE message_escalations (
+    message_id BIGINT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    topic_id BIGINT NOT NULL REFERENCES topics(id) ON DELETE CASCADE,
+    policy TEXT NOT NULL,
+    next_step INTEGER NOT NULL DEFAULT 0,
+    next_at TEXT,
+    acked_at TEXT,
+    acked_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT utc_now()
+);
+CREATE INDEX idx_message_escalations_next_at ON message_escalations(next_at);
@@ -0,0 +1,14 @@
+-- Email quiet windows move to quiet_hours. A user-wide schedule covers email
+-- as before and now holds back the user's pushes too, so messages at priority
+-- 8 and up break through to keep urgent pushes arriving. Windows saved while
+-- email was off come over as disabled schedules.
+INSERT INTO quiet_hours (user_id, client_id, enabled, start_time, end_time, timezone, override_priority)
+SELECT user_id, NULL, enabled, quiet_start, quiet_end, timezone, 8
+FROM email_preferences
+WHERE quiet_start IS NOT NULL
+  AND quiet_end IS NOT NULL
+  AND quiet_start <> quiet_end;
+
+ALTER TABLE email_preferences DROP COLUMN quiet_start;
+ALTER TABLE email_preferences DROP COLUMN quiet_end;
+ALTER TABLE email_preferences DROP COLUMN timezone;
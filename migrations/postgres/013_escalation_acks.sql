@@ -0,0 +1,4 @@
+-- Acknowledgements are recorded in the message's extras only. Acknowledged
+-- escalations already have no next step, which is all that stops them.
+ALTER TABLE message_escalations DROP COLUMN acked_at;
+ALTER TABLE message_escalations DROP COLUMN acked_by;
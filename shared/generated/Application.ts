@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type Application = { id: number, user_id: number, name: string, description: string | null, token: string, default_priority: number, image: string | null, created_at: string, updated_at: string, retention_days: number | null, };
+export type Application = { id: number, user_id: number, 
+/**
+ * Owning group; its admins can manage the application.
+ */
+group_id: number | null, name: string, description: string | null, token: string, default_priority: number, image: string | null, created_at: string, updated_at: string, retention_days: number | null, };
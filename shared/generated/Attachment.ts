@@ -1,3 +1,20 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type Attachment = { id: number, message_id: number, filename: string, content_type: string | null, size_bytes: number, storage_type: string, storage_path: string, expires_at: string | null, created_at: string, };
+export type Attachment = { id: number, message_id: number, filename: string, content_type: string | null, size_bytes: number, storage_type: string, storage_path: string, 
+/**
+ * Hex SHA-256 of the content; `None` for attachments stored before
+ * content addressing.
+ */
+sha256: string | null, 
+/**
+ * Pixel dimensions of image attachments, once the thumbnail worker has read them.
+ */
+width: number | null, height: number | null, 
+/**
+ * Key of the preview blob, stored beside the original in the same store.
+ */
+thumbnail_path: string | null, 
+/**
+ * `pending`, `ready`, `skipped` or `failed`; `None` if not an image.
+ */
+thumbnail_status: string | null, expires_at: string | null, created_at: string, };
@@ -3,4 +3,12 @@
 /**
  * Lightweight attachment info included in message responses
  */
-export type AttachmentInfo = { id: number, name: string, type: string | null, size: number, url: string, };
+export type AttachmentInfo = { id: number, name: string, type: string | null, size: number, url: string, 
+/**
+ * Hex SHA-256 of the content, usable as a cache key across messages.
+ */
+sha256: string | null, width: number | null, height: number | null, 
+/**
+ * Downscaled preview for images, once it has been generated.
+ */
+thumbnail_url: string | null, };
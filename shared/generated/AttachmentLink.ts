@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A signed link that downloads one attachment without an auth token.
+ */
+export type AttachmentLink = { 
+/**
+ * Absolute when the server's `BASE_URL` is set, otherwise a path.
+ */
+url: string, expires_at: string, };
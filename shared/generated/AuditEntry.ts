@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type AuditEntry = { id: number, 
+/**
+ * `None` once the acting user has been deleted, or for anonymous actions
+ * such as a failed login for an unknown username.
+ */
+actor_id: number | null, 
+/**
+ * Username at the time of the action.
+ */
+actor_name: string | null, 
+/**
+ * Dotted verb, e.g. `user.create`, `setting.update`, `auth.login_failed`.
+ */
+action: string, target_type: string, target_id: string | null, ip: string | null, 
+/**
+ * Changed fields before the action; absent for creations.
+ */
+before: JsonValue | null, 
+/**
+ * Changed fields after the action; absent for deletions.
+ */
+after: JsonValue | null, created_at: string, };
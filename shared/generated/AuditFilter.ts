@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Filters for listing the audit log; all optional and combined with AND.
+ */
+export type AuditFilter = { actor_id: number | null, 
+/**
+ * Exact action, or a prefix ending in `.` (e.g. `user.`).
+ */
+action: string | null, target_type: string | null, target_id: string | null, 
+/**
+ * Inclusive lower bound on `created_at` (`YYYY-MM-DD HH:MM:SS`, UTC).
+ */
+since: string | null, 
+/**
+ * Inclusive upper bound on `created_at`.
+ */
+until: string | null, 
+/**
+ * Only entries with an id lower than this, for paging backwards.
+ */
+before_id: number | null, limit: number | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A backup archive in the backup directory.
+ */
+export type BackupInfo = { 
+/**
+ * File name, e.g. `rstify-backup-20260101-030000.tar.gz`.
+ */
+name: string, size_bytes: number, created_at: string, };
@@ -2,6 +2,19 @@
 
 export type Client = { id: number, user_id: number, name: string, token: string, fcm_token: string | null, 
 /**
- * JSON array of scopes: "read", "write", "admin", "app:<id>"
+ * JSON array of scopes: "read", "write", "admin", "app:<id>",
+ * "topic:read:<pattern>", "topic:write:<pattern>"
  */
-scopes: string, created_at: string, };
+scopes: string, created_at: string, 
+/**
+ * When set, the token stops authenticating at this time and is later purged.
+ */
+expires_at: string | null, 
+/**
+ * JSON array of CIDRs the token may be used from; `None` = any address.
+ */
+allowed_ips: string | null, last_used_at: string | null, last_used_ip: string | null, 
+/**
+ * Web Push endpoint of the browser subscription, if any.
+ */
+webpush_endpoint: string | null, };
@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type CreateApplication = { name: string, description: string | null, default_priority: number | null, };
+export type CreateApplication = { name: string, 
+/**
+ * Create the application on behalf of a group the caller administers.
+ */
+group_id: number | null, description: string | null, default_priority: number | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to mint a signed attachment link.
+ */
+export type CreateAttachmentLink = { 
+/**
+ * Lifetime in seconds; defaults to a day and is capped at 30 days.
+ */
+expires_in: number | null, };
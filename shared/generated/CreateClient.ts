@@ -4,4 +4,12 @@ export type CreateClient = { name: string,
 /**
  * Scopes for this client token. Default: ["read", "write"]
  */
-scopes: Array<string> | null, };
+scopes: Array<string> | null, 
+/**
+ * Expiry as RFC 3339, `YYYY-MM-DD HH:MM:SS` (UTC) or a duration like `30d`.
+ */
+expires_at: string | null, 
+/**
+ * CIDRs or addresses the token may be used from. Omit for no restriction.
+ */
+allowed_ips: Array<string> | null, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Body of `POST /current/user/quiet-hours`, and of `PUT` on one schedule,
+ * which replaces it.
+ */
+export type CreateQuietHours = { 
+/**
+ * Limit the schedule to one of your devices; omit for all of them.
+ */
+client_id: number | null, 
+/**
+ * Default true.
+ */
+enabled: boolean | null, start_time: string, end_time: string, 
+/**
+ * Weekdays the window starts on, e.g. `["mon", "tue"]`; omit for every day.
+ */
+days: Array<string> | null, 
+/**
+ * Default `UTC`.
+ */
+timezone: string | null, 
+/**
+ * Let messages at or above this priority through.
+ */
+override_priority: number | null, };
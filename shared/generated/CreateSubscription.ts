@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CreateSubscription = { 
+/**
+ * Topic name or pattern (`alerts.*`, `alerts.**`).
+ */
+topic: string, notify_policy: string | null, notify_priority_min: number | null, };
@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type CreateTopic = { name: string, description: string | null, everyone_read: boolean | null, everyone_write: boolean | null, };
+export type CreateTopic = { name: string, 
+/**
+ * Create the topic on behalf of a group the caller administers.
+ */
+group_id: number | null, description: string | null, everyone_read: boolean | null, everyone_write: boolean | null, };
@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type CreateTopicPermission = { user_id: number, topic_pattern: string, can_read: boolean | null, can_write: boolean | null, };
+export type CreateTopicPermission = { 
+/**
+ * Grantee: set exactly one of `user_id` and `group_id`.
+ */
+user_id: number | null, group_id: number | null, topic_pattern: string, can_read: boolean | null, can_write: boolean | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One queued or sent email.
+ */
+export type EmailDelivery = { id: number, recipient: string, subject: string, 
+/**
+ * `pending`, `sent` or `failed`.
+ */
+status: string, attempts: number, last_error: string | null, next_attempt_at: string, created_at: string, sent_at: string | null, };
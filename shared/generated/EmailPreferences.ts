@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A user's opt-in to email for messages in their own inbox.
+ */
+export type EmailPreferences = { user_id: number, enabled: boolean, 
+/**
+ * Messages below this priority are not emailed.
+ */
+min_priority: number, 
+/**
+ * JSON array of topic names or patterns; `None` = the user's own topics.
+ */
+topics: string | null, 
+/**
+ * JSON array of application ids; `None` = all of the user's applications.
+ */
+application_ids: string | null, updated_at: string, };
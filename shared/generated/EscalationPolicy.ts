@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EscalationStep } from "./EscalationStep";
+
+/**
+ * What a topic does with high-priority messages nobody acknowledges:
+ * each step fires in turn, `after_minutes` after the one before, until the
+ * message is acknowledged or the steps run out. Stored as JSON on the topic.
+ */
+export type EscalationPolicy = { 
+/**
+ * Messages below this priority never escalate. Default 8.
+ */
+min_priority: number, 
+/**
+ * An empty list removes the policy.
+ */
+steps: Array<EscalationStep>, };
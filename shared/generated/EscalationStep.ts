@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EscalationStep = { 
+/**
+ * Minutes without acknowledgement before this step runs, counted from
+ * the previous step (or from publishing, for the first).
+ */
+after_minutes: number, 
+/**
+ * `push` or `email`.
+ */
+action: string, 
+/**
+ * Send to this user instead of the message's usual recipients.
+ */
+user_id: number | null, 
+/**
+ * Send to every member of this group instead.
+ */
+group_id: number | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExportFile } from "./ExportFile";
+
+export type ExportApplication = { id: number, user_id: number, name: string, description: string | null, token: string, default_priority: number, retention_days: number | null, icon: ExportFile | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ExportClient = { user_id: number, name: string, token: string, 
+/**
+ * JSON array of scopes; `app:<id>` scopes refer to exported application ids.
+ */
+scopes: string, expires_at: string | null, allowed_ips: string | null, };
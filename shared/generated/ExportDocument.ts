@@ -0,0 +1,32 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExportApplication } from "./ExportApplication";
+import type { ExportClient } from "./ExportClient";
+import type { ExportMessage } from "./ExportMessage";
+import type { ExportPermission } from "./ExportPermission";
+import type { ExportTopic } from "./ExportTopic";
+import type { ExportUser } from "./ExportUser";
+import type { ExportWebhook } from "./ExportWebhook";
+import type { ExportWebhookVariable } from "./ExportWebhookVariable";
+
+/**
+ * A portable copy of an instance's data, or of one user's. Ids are those of
+ * the exporting instance and only link records within the document; imports
+ * assign new ones.
+ */
+export type ExportDocument = { 
+/**
+ * Always `rstify-export`.
+ */
+format: string, version: number, exported_at: string, 
+/**
+ * Username, when the document holds a single user's data.
+ */
+user: string | null, users: Array<ExportUser>, applications: Array<ExportApplication>, clients: Array<ExportClient>, topics: Array<ExportTopic>, 
+/**
+ * Per-user topic grants; only in full exports.
+ */
+permissions: Array<ExportPermission>, webhooks: Array<ExportWebhook>, webhook_variables: Array<ExportWebhookVariable>, 
+/**
+ * Oldest first.
+ */
+messages: Array<ExportMessage>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A file carried inline, base64-encoded.
+ */
+export type ExportFile = { filename: string, content_type: string | null, 
+/**
+ * Hex SHA-256 of the content, checked on import.
+ */
+sha256: string, data: string, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExportFile } from "./ExportFile";
+
+export type ExportMessage = { application_id: number | null, topic_id: number | null, user_id: number | null, title: string | null, message: string, priority: number, tags: string | null, click_url: string | null, icon_url: string | null, actions: string | null, extras: string | null, content_type: string | null, 
+/**
+ * Only for scheduled messages not yet delivered.
+ */
+scheduled_for: string | null, source: string | null, inbox: boolean, created_at: string, attachments: Array<ExportFile>, };
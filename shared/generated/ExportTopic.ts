@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ExportTopic = { id: number, name: string, owner_id: number | null, description: string | null, everyone_read: boolean, everyone_write: boolean, notify_policy: string, notify_priority_min: number | null, notify_condition: string | null, notify_digest_interval: number | null, store_policy: string, store_interval: number | null, inbox_override: string | null, inbox_priority_min: number | null, };
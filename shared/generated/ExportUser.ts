@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ExportUser = { id: number, username: string, email: string | null, is_admin: boolean, 
+/**
+ * Only in full exports, so migrated users keep their passwords.
+ */
+password_hash: string | null, };
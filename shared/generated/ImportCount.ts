@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Records created and records that already existed (matched by name).
+ */
+export type ImportCount = { created: number, matched: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImportCount } from "./ImportCount";
+
+/**
+ * What an import did.
+ */
+export type ImportReport = { users: ImportCount, applications: ImportCount, clients: ImportCount, topics: ImportCount, permissions: ImportCount, webhooks: ImportCount, webhook_variables: ImportCount, messages: number, attachments: number, 
+/**
+ * Records left out, with the reason.
+ */
+skipped: Array<string>, };
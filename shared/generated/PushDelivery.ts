@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One push notification attempt to a device.
+ */
+export type PushDelivery = { id: number, client_id: number, 
+/**
+ * `fcm` or `webpush`.
+ */
+channel: string, message_id: number | null, 
+/**
+ * `sent`, `failed`, or `pruned` when the token was rejected for good
+ * and removed from the client.
+ */
+outcome: string, status_code: number | null, 
+/**
+ * FCM error code, e.g. `UNREGISTERED` or `QUOTA_EXCEEDED`.
+ */
+error_code: string | null, error: string | null, attempts: number, attempted_at: string, };
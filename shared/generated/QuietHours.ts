@@ -0,0 +1,34 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A do-not-disturb schedule. Push (and, for user-wide schedules, email) is
+ * held back while it is active; held pushes are summarized when it ends.
+ */
+export type QuietHours = { id: number, user_id: number, 
+/**
+ * The one device this applies to; `None` = all of the user's devices
+ * and their email.
+ */
+client_id: number | null, enabled: boolean, 
+/**
+ * Start of the window, `HH:MM` in `timezone`.
+ */
+start_time: string, 
+/**
+ * End of the window, `HH:MM` in `timezone`. Before `start_time` means
+ * the window runs past midnight.
+ */
+end_time: string, 
+/**
+ * JSON array of the weekdays the window starts on (`mon`..`sun`);
+ * `None` = every day.
+ */
+days: string | null, 
+/**
+ * IANA timezone name, e.g. `Europe/Berlin`.
+ */
+timezone: string, 
+/**
+ * Messages at or above this priority break through; `None` = none do.
+ */
+override_priority: number | null, created_at: string, updated_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WebPushKeys } from "./WebPushKeys";
+
+/**
+ * A browser `PushSubscription`, in the shape of its `toJSON()`.
+ */
+export type RegisterWebPush = { endpoint: string, keys: WebPushKeys, };
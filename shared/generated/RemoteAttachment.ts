@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An attachment given by URL rather than uploaded
+ */
+export type RemoteAttachment = { url: string, name: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SendTestEmail = { 
+/**
+ * Defaults to the caller's own address.
+ */
+to: string | null, };
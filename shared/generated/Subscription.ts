@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A device's subscription to a topic or topic pattern, for push.
+ */
+export type Subscription = { id: number, client_id: number, topic_pattern: string, 
+/**
+ * `always`, `never` or `threshold`; `None` follows the topic's policy.
+ */
+notify_policy: string | null, 
+/**
+ * Minimum priority for `threshold`.
+ */
+notify_priority_min: number | null, created_at: string, };
@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type TopicPermission = { id: number, user_id: number, topic_pattern: string, can_read: boolean, can_write: boolean, };
+export type TopicPermission = { id: number, 
+/**
+ * Exactly one of `user_id` and `group_id` is set.
+ */
+user_id: number | null, group_id: number | null, topic_pattern: string, can_read: boolean, can_write: boolean, };
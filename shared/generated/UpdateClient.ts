@@ -4,4 +4,12 @@ export type UpdateClient = { name: string | null,
 /**
  * Update scopes for this client token
  */
-scopes: Array<string> | null, };
+scopes: Array<string> | null, 
+/**
+ * New expiry; an empty string removes it.
+ */
+expires_at: string | null, 
+/**
+ * New allow-list; an empty list removes the restriction.
+ */
+allowed_ips: Array<string> | null, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Replaces a user's email preferences; omitted fields take their defaults.
+ * Unknown fields are rejected, so the quiet window older clients send
+ * (now a quiet hours schedule) isn't silently lost.
+ */
+export type UpdateEmailPreferences = { enabled: boolean, 
+/**
+ * Default 5.
+ */
+min_priority: number | null, 
+/**
+ * Topic names or patterns to email about; omit for the user's own topics.
+ */
+topics: Array<string> | null, 
+/**
+ * Applications to email about; omit for all of the user's applications.
+ */
+application_ids: Array<number> | null, };
@@ -4,4 +4,8 @@ import type { JsonValue } from "./serde_json/JsonValue";
 /**
  * Update an existing message
  */
-export type UpdateMessage = { title: string | null, message: string | null, priority: number | null, extras: JsonValue | null, };
+export type UpdateMessage = { title: string | null, message: string | null, priority: number | null, 
+/**
+ * Replaces the extras, except the `rstify::` keys rstify maintains.
+ */
+extras: JsonValue | null, };
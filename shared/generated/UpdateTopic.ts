@@ -1,3 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EscalationPolicy } from "./EscalationPolicy";
 
-export type UpdateTopic = { description: string | null, everyone_read: boolean | null, everyone_write: boolean | null, notify_policy: string | null, notify_priority_min: number | null, notify_condition: string | null, notify_digest_interval: number | null, store_policy: string | null, store_interval: number | null, inbox_override: string | null, inbox_priority_min: number | null, };
+export type UpdateTopic = { 
+/**
+ * Hand the topic over to a group the caller administers.
+ */
+group_id: number | null, description: string | null, everyone_read: boolean | null, everyone_write: boolean | null, notify_policy: string | null, notify_priority_min: number | null, notify_condition: string | null, notify_digest_interval: number | null, store_policy: string | null, store_interval: number | null, inbox_override: string | null, inbox_priority_min: number | null, 
+/**
+ * Replace the escalation policy; one with no steps removes it.
+ */
+escalation_policy: EscalationPolicy | null, };
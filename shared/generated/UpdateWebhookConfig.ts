@@ -1,4 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { JsonValue } from "./serde_json/JsonValue";
 
-export type UpdateWebhookConfig = { name: string | null, template: JsonValue | null, enabled: boolean | null, targetUrl: string | null, httpMethod: string | null, headers: JsonValue | null, bodyTemplate: string | null, maxRetries: number | null, retryDelaySecs: number | null, timeoutSecs: number | null, followRedirects: boolean | null, groupName: string | null, secret: string | null, };
+export type UpdateWebhookConfig = { name: string | null, template: JsonValue | null, enabled: boolean | null, targetUrl: string | null, httpMethod: string | null, headers: JsonValue | null, bodyTemplate: string | null, maxRetries: number | null, retryDelaySecs: number | null, timeoutSecs: number | null, followRedirects: boolean | null, groupName: string | null, secret: string | null, notifyUrl: string | null, };
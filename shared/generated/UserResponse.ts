@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type UserResponse = { id: number, username: string, email: string | null, is_admin: boolean, created_at: string, updated_at: string, };
+export type UserResponse = { id: number, username: string, email: string | null, is_admin: boolean, created_at: string, updated_at: string, failed_logins: number, 
+/**
+ * Set while the account is (or was last) locked after repeated failed logins.
+ */
+locked_until: string | null, };
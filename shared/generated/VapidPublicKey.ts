@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The server's VAPID public key, for `pushManager.subscribe()`.
+ */
+export type VapidPublicKey = { 
+/**
+ * Uncompressed P-256 point, base64url without padding.
+ */
+public_key: string, };
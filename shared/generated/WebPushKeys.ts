@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WebPushKeys = { 
+/**
+ * The browser's P-256 public key, base64url.
+ */
+p256dh: string, 
+/**
+ * The 16-byte authentication secret, base64url.
+ */
+auth: string, };
@@ -1,3 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type WebhookConfig = { id: number, user_id: number, name: string, token: string, webhook_type: string, target_topic_id: number | null, target_application_id: number | null, template: string, enabled: boolean, created_at: string, direction: string, target_url: string | null, http_method: string, headers: string | null, body_template: string | null, max_retries: number, retry_delay_secs: number, timeout_secs: number, follow_redirects: boolean, group_name: string | null, secret: string | null, };
+export type WebhookConfig = { id: number, user_id: number, name: string, token: string, webhook_type: string, target_topic_id: number | null, target_application_id: number | null, template: string, enabled: boolean, created_at: string, direction: string, target_url: string | null, http_method: string, headers: string | null, body_template: string | null, max_retries: number, retry_delay_secs: number, timeout_secs: number, follow_redirects: boolean, group_name: string | null, secret: string | null, 
+/**
+ * Apprise-style destination such as `discord://id/token`; when set it
+ * replaces `target_url` and picks the transport.
+ */
+notify_url: string | null, };
@@ -1,35 +1,72 @@
 // Auto-generated barrel export — do not hand-edit
 // Re-run: just generate-types
 
+export * from "./AddGroupMember";
 export * from "./Application";
 export * from "./Attachment";
 export * from "./AttachmentInfo";
+export * from "./AttachmentLink";
+export * from "./AuditEntry";
+export * from "./AuditFilter";
+export * from "./BackupInfo";
 export * from "./ChangePassword";
 export * from "./Client";
 export * from "./CreateAppMessage";
 export * from "./CreateApplication";
+export * from "./CreateAttachmentLink";
 export * from "./CreateClient";
+export * from "./CreateGroup";
+export * from "./CreateQuietHours";
+export * from "./CreateSubscription";
 export * from "./CreateTopic";
 export * from "./CreateTopicMessage";
 export * from "./CreateTopicPermission";
 export * from "./CreateUser";
 export * from "./CreateWebhookConfig";
 export * from "./CreateWebhookVariable";
+export * from "./EmailDelivery";
+export * from "./EmailPreferences";
+export * from "./EmailTestResult";
+export * from "./EscalationPolicy";
+export * from "./EscalationStep";
+export * from "./ExportApplication";
+export * from "./ExportClient";
+export * from "./ExportDocument";
+export * from "./ExportFile";
+export * from "./ExportMessage";
+export * from "./ExportPermission";
+export * from "./ExportTopic";
+export * from "./ExportUser";
+export * from "./ExportWebhook";
+export * from "./ExportWebhookVariable";
+export * from "./Group";
+export * from "./GroupMember";
 export * from "./HealthResponse";
+export * from "./ImportCount";
+export * from "./ImportReport";
 export * from "./LoginRequest";
 export * from "./LoginResponse";
 export * from "./MessageAction";
 export * from "./MessageResponse";
 export * from "./PagedMessages";
 export * from "./Paging";
+export * from "./PushDelivery";
+export * from "./QuietHours";
 export * from "./RegisterFcmToken";
+export * from "./RegisterWebPush";
+export * from "./RemoteAttachment";
+export * from "./SendTestEmail";
 export * from "./Setting";
 export * from "./StatsResponse";
+export * from "./Subscription";
 export * from "./TestWebhookPayload";
 export * from "./Topic";
 export * from "./TopicPermission";
 export * from "./UpdateApplication";
 export * from "./UpdateClient";
+export * from "./UpdateEmailPreferences";
+export * from "./UpdateGroup";
+export * from "./UpdateGroupMember";
 export * from "./UpdateMessage";
 export * from "./UpdateSetting";
 export * from "./UpdateTopic";
@@ -37,7 +74,9 @@ export * from "./UpdateUser";
 export * from "./UpdateWebhookConfig";
 export * from "./UpdateWebhookVariable";
 export * from "./UserResponse";
+export * from "./VapidPublicKey";
 export * from "./VersionResponse";
+export * from "./WebPushKeys";
 export * from "./WebhookConfig";
 export * from "./WebhookConfigWithHealth";
 export * from "./WebhookDeliveryLog";
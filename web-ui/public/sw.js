@@ -0,0 +1,36 @@
+// Shows Web Push notifications while no rstify tab is open.
+
+self.addEventListener('push', (event) => {
+  let msg = {};
+  try {
+    msg = event.data ? event.data.json() : {};
+  } catch {
+    msg = { message: event.data ? event.data.text() : '' };
+  }
+  const title = msg.title || msg.topic || 'rstify';
+  event.waitUntil(
+    self.registration.showNotification(title, {
+      body: msg.message || '',
+      icon: msg.icon_url || '/icon-512.png',
+      tag: msg.id ? `rstify-${msg.id}` : undefined,
+      requireInteraction: (msg.priority || 0) >= 8,
+      data: { url: msg.click_url || (msg.topic ? '/topics' : '/messages') },
+    }),
+  );
+});
+
+self.addEventListener('notificationclick', (event) => {
+  event.notification.close();
+  const url = event.notification.data?.url || '/';
+  event.waitUntil(
+    self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then((windows) => {
+      for (const win of windows) {
+        if (new URL(win.url).origin === self.location.origin && 'focus' in win) {
+          win.navigate(url);
+          return win.focus();
+        }
+      }
+      return self.clients.openWindow(url);
+    }),
+  );
+});
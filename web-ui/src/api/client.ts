@@ -10,7 +10,10 @@ import type {
   WebhookVariable, CreateWebhookVariable, UpdateWebhookVariable,
   StatsResponse, LoginResponse,
   HealthResponse, VersionResponse,
-  Setting,
+  Setting, RegisterWebPush, VapidPublicKey,
+  EmailPreferences, UpdateEmailPreferences,
+  QuietHours, CreateQuietHours,
+  EscalationPolicy,
 } from 'shared';
 
 const BASE = '';
@@ -62,9 +65,30 @@ export const api = {
   deleteUser(id: number): Promise<void> {
     return request(`/user/${id}`, { method: 'DELETE' });
   },
+  unlockUser(id: number): Promise<UserResponse> {
+    return request(`/user/${id}/unlock`, { method: 'POST' });
+  },
   getCurrentUser(): Promise<UserResponse> {
     return request('/current/user');
   },
+  getEmailPreferences(): Promise<EmailPreferences> {
+    return request('/current/user/email-preferences');
+  },
+  updateEmailPreferences(data: UpdateEmailPreferences): Promise<EmailPreferences> {
+    return request('/current/user/email-preferences', { method: 'PUT', body: JSON.stringify(data) });
+  },
+  listQuietHours(): Promise<QuietHours[]> {
+    return request('/current/user/quiet-hours');
+  },
+  createQuietHours(data: CreateQuietHours): Promise<QuietHours> {
+    return request('/current/user/quiet-hours', { method: 'POST', body: JSON.stringify(data) });
+  },
+  updateQuietHours(id: number, data: CreateQuietHours): Promise<QuietHours> {
+    return request(`/current/user/quiet-hours/${id}`, { method: 'PUT', body: JSON.stringify(data) });
+  },
+  deleteQuietHours(id: number): Promise<void> {
+    return request(`/current/user/quiet-hours/${id}`, { method: 'DELETE' });
+  },
   changePassword(currentPassword: string, newPassword: string): Promise<void> {
     return request('/current/user/password', {
       method: 'POST',
@@ -123,6 +147,12 @@ export const api = {
   deleteClient(id: number): Promise<void> {
     return request(`/client/${id}`, { method: 'DELETE' });
   },
+  getVapidPublicKey(): Promise<VapidPublicKey> {
+    return request('/api/web-push/vapid-key');
+  },
+  registerWebPush(id: number, data: RegisterWebPush): Promise<Client> {
+    return request(`/client/${id}/web-push`, { method: 'PUT', body: JSON.stringify(data) });
+  },
 
   // Topics
   listTopics(): Promise<Topic[]> {
@@ -131,7 +161,7 @@ export const api = {
   createTopic(data: CreateTopic): Promise<Topic> {
     return request('/api/topics', { method: 'POST', body: JSON.stringify(data) });
   },
-  updateTopic(name: string, data: { description?: string; everyone_read?: boolean; everyone_write?: boolean; notify_policy?: string; notify_priority_min?: number; notify_condition?: string; notify_digest_interval?: number; store_policy?: string; store_interval?: number; inbox_override?: string | null; inbox_priority_min?: number | null }): Promise<Topic> {
+  updateTopic(name: string, data: { description?: string; everyone_read?: boolean; everyone_write?: boolean; notify_policy?: string; notify_priority_min?: number; notify_condition?: string; notify_digest_interval?: number; store_policy?: string; store_interval?: number; inbox_override?: string | null; inbox_priority_min?: number | null; escalation_policy?: EscalationPolicy }): Promise<Topic> {
     return request(`/api/topics/${encodeURIComponent(name)}`, { method: 'PUT', body: JSON.stringify(data) });
   },
   deleteTopic(name: string): Promise<void> {
@@ -153,6 +183,9 @@ export const api = {
   deleteMessage(id: number): Promise<void> {
     return request(`/message/${id}`, { method: 'DELETE' });
   },
+  acknowledgeMessage(id: number): Promise<MessageResponse> {
+    return request(`/api/messages/${id}/ack`, { method: 'POST' });
+  },
 
   // Webhooks
   listWebhooks(): Promise<WebhookConfigWithHealth[]> {
@@ -21,7 +21,9 @@ export default function EditWebhookForm({ webhook, variables, existingGroups, on
   const [enabled, setEnabled] = useState(webhook.enabled);
   const [groupName, setGroupName] = useState(webhook.group_name || '');
   const [secret, setSecret] = useState('');
-  const [targetUrl, setTargetUrl] = useState(webhook.target_url || '');
+  // Webhooks created from a notification URL keep editing that URL.
+  const usesNotifyUrl = !!webhook.notify_url;
+  const [targetUrl, setTargetUrl] = useState(webhook.notify_url || webhook.target_url || '');
   const [method, setMethod] = useState(webhook.http_method || 'POST');
   const [headerRows, setHeaderRows] = useState<HeaderRow[]>(headersJsonToRows(webhook.headers));
   const [bodyTemplate, setBodyTemplate] = useState(webhook.body_template || '');
@@ -60,7 +62,8 @@ export default function EditWebhookForm({ webhook, variables, existingGroups, on
         enabled,
         groupName: groupName || null,
         secret: secret || null,
-        targetUrl: isOutgoing ? targetUrl : null,
+        targetUrl: isOutgoing && !usesNotifyUrl ? targetUrl : null,
+        notifyUrl: isOutgoing && usesNotifyUrl ? targetUrl.trim() : null,
         httpMethod: isOutgoing ? method : null,
         headers: isOutgoing ? rowsToHeadersObject(headerRows) : null,
         bodyTemplate: isOutgoing ? bodyTemplate || null : null,
@@ -133,13 +136,15 @@ export default function EditWebhookForm({ webhook, variables, existingGroups, on
       {isOutgoing && (
         <>
           <div>
-            <label className={labelCls}>Send request to</label>
-            <div className="flex gap-1 mb-1.5">
-              {METHODS.map(m => (
-                <button key={m} type="button" onClick={() => setMethod(m)} className={chipCls(method === m)}>{m}</button>
-              ))}
-            </div>
-            <input value={targetUrl} onChange={e => setTargetUrl(e.target.value)} className={inputCls} placeholder="https://example.com/hook — {{env.KEY}} works here" />
+            <label className={labelCls}>{usesNotifyUrl ? 'Notification URL' : 'Send request to'}</label>
+            {!usesNotifyUrl && (
+              <div className="flex gap-1 mb-1.5">
+                {METHODS.map(m => (
+                  <button key={m} type="button" onClick={() => setMethod(m)} className={chipCls(method === m)}>{m}</button>
+                ))}
+              </div>
+            )}
+            <input value={targetUrl} onChange={e => setTargetUrl(e.target.value)} className={inputCls} placeholder={usesNotifyUrl ? 'discord://id/token — {{env.KEY}} works here' : 'https://example.com/hook — {{env.KEY}} works here'} />
           </div>
 
           <HeadersEditor rows={headerRows} onChange={setHeaderRows} hasBody={hasBody} />
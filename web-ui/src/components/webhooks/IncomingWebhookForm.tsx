@@ -56,6 +56,7 @@ export default function IncomingWebhookForm({ topics, apps, existingGroups, onSu
         template: null,
         groupName: groupName || null,
         secret: secret || null,
+        notifyUrl: null,
       });
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed to create webhook');
@@ -7,6 +7,17 @@ import TemplateHint from './TemplateHint';
 
 const METHODS = ['GET', 'POST', 'PUT', 'PATCH', 'DELETE'];
 
+/** Built-in chat formats; the server renders the service's own payload. */
+const FORMATS: { value: string; label: string; placeholder: string }[] = [
+  { value: 'custom', label: 'Custom', placeholder: 'https://example.com/hook — {{env.KEY}} works here' },
+  { value: 'slack', label: 'Slack', placeholder: 'https://hooks.slack.com/services/…' },
+  { value: 'discord', label: 'Discord', placeholder: 'https://discord.com/api/webhooks/…' },
+  { value: 'matrix', label: 'Matrix', placeholder: 'https://matrix.example.org/_matrix/client/v3/rooms/!room:example.org/send/m.room.message' },
+  { value: 'telegram', label: 'Telegram', placeholder: 'https://api.telegram.org/bot{{env.TG_TOKEN}}/sendMessage?chat_id=123' },
+  { value: 'url', label: 'Notification URL', placeholder: 'discord://id/{{env.TOKEN}}, ntfys://ntfy.sh/alerts, mailto://ops@example.com' },
+  { value: 'relay', label: 'Relay', placeholder: 'rstifys://{{env.CENTRAL_TOKEN}}@central.example.com/alerts' },
+];
+
 /**
  * Focused create form for outgoing webhooks: trigger topic + target request,
  * with delivery tuning behind a disclosure so the common path stays short.
@@ -21,6 +32,7 @@ export default function OutgoingWebhookForm({ topics, variables, existingGroups,
 }) {
   const [name, setName] = useState('');
   const [topicId, setTopicId] = useState('');
+  const [format, setFormat] = useState('custom');
   const [targetUrl, setTargetUrl] = useState('');
   const [method, setMethod] = useState('POST');
   const [headerRows, setHeaderRows] = useState<HeaderRow[]>([]);
@@ -35,7 +47,13 @@ export default function OutgoingWebhookForm({ topics, variables, existingGroups,
   const [loading, setLoading] = useState(false);
   const bodyRef = useRef<HTMLTextAreaElement>(null);
 
-  const hasBody = method !== 'GET' && method !== 'DELETE';
+  const isCustom = format === 'custom';
+  // Apprise-style URLs pick the service from the scheme; relays are
+  // notification URLs limited to other notification servers.
+  const isRelay = format === 'relay';
+  const isNotifyUrl = format === 'url' || isRelay;
+  const hasBody = isCustom && method !== 'GET' && method !== 'DELETE';
+  const formatInfo = FORMATS.find(f => f.value === format) ?? FORMATS[0];
 
   const insertToken = (token: string) => {
     const el = bodyRef.current;
@@ -65,12 +83,12 @@ export default function OutgoingWebhookForm({ topics, variables, existingGroups,
     try {
       await onSubmit({
         name,
-        webhookType: 'custom',
+        webhookType: isRelay ? 'relay' : isNotifyUrl ? 'custom' : format,
         direction: 'outgoing',
         targetTopicId: Number(topicId),
         targetApplicationId: null,
-        targetUrl: targetUrl.trim(),
-        httpMethod: method,
+        targetUrl: isNotifyUrl ? null : targetUrl.trim(),
+        httpMethod: isCustom ? method : 'POST',
         headers: rowsToHeadersObject(headerRows),
         bodyTemplate: bodyTemplate || null,
         maxRetries,
@@ -81,6 +99,7 @@ export default function OutgoingWebhookForm({ topics, variables, existingGroups,
         template: null,
         groupName: groupName || null,
         secret: null,
+        notifyUrl: isNotifyUrl ? targetUrl.trim() : null,
       });
     } catch (err) {
       setError(friendlyWebhookError(err instanceof Error ? err.message : 'Failed to create webhook'));
@@ -108,17 +127,35 @@ export default function OutgoingWebhookForm({ topics, variables, existingGroups,
         )}
       </div>
 
+      <div>
+        <label className={labelCls}>Format</label>
+        <div className="flex gap-1">
+          {FORMATS.map(f => (
+            <button key={f.value} type="button" onClick={() => setFormat(f.value)} className={chipCls(format === f.value)}>{f.label}</button>
+          ))}
+        </div>
+        {isRelay && (
+          <p className="text-xs text-slate-400 mt-1">Forwards each message to another server with rstify:// or rstifys://, ntfy:// or ntfys://, or gotify:// or gotifys://, keeping title, priority, tags, actions and attachments. Messages that already crossed 3 relays are not forwarded again.</p>
+        )}
+        {isNotifyUrl && !isRelay && (
+          <p className="text-xs text-slate-400 mt-1">One URL names the service and destination: discord://, slack://, ntfy:// or ntfys://, gotify:// or gotifys://, rstify:// or rstifys://, json:// or jsons://, and mailto:// (sent with the server SMTP settings).</p>
+        )}
+        {!isCustom && !isNotifyUrl && (
+          <p className="text-xs text-slate-400 mt-1">Messages are sent in the {formatInfo.label} format, colored by priority, with attachments and link actions.{format === 'matrix' && ' Add an Authorization: Bearer header with the bot access token.'}</p>
+        )}
+      </div>
+
       <div>
         <label className={labelCls}>Send request to</label>
-        <div className="flex gap-2">
+        {isCustom && (
           <div className="flex gap-1">
             {METHODS.map(m => (
               <button key={m} type="button" onClick={() => setMethod(m)} className={chipCls(method === m)}>{m}</button>
             ))}
           </div>
-        </div>
+        )}
         <input
-          placeholder="https://example.com/hook — {{env.KEY}} works here"
+          placeholder={formatInfo.placeholder}
           value={targetUrl}
           onChange={e => setTargetUrl(e.target.value)}
           className={`${inputCls} mt-1.5`}
@@ -210,6 +210,7 @@ export default function Applications() {
         onSubmit={async () => {
           await api.createApplication({
             name,
+            group_id: null,
             description: description || null,
             default_priority: parseInt(defaultPriority) || 0,
           });
@@ -228,6 +229,7 @@ export default function Applications() {
         onSubmit={async () => {
           if (!editApp) return;
           await api.updateApplication(editApp.id, {
+            group_id: null,
             name,
             description: description || null,
             default_priority: parseInt(defaultPriority) || 0,
@@ -180,7 +180,7 @@ export default function Clients() {
         onClose={() => setShowCreate(false)}
         onSubmit={async () => {
           if (scopes.length === 0) throw new Error('At least one scope is required');
-          await api.createClient({ name, scopes });
+          await api.createClient({ name, scopes, expires_at: null, allowed_ips: null });
           await crud.reload();
         }}
         submitLabel="Create"
@@ -195,7 +195,7 @@ export default function Clients() {
         onSubmit={async () => {
           if (!editClient) return;
           if (scopes.length === 0) throw new Error('At least one scope is required');
-          await api.updateClient(editClient.id, { name, scopes });
+          await api.updateClient(editClient.id, { name, scopes, expires_at: null, allowed_ips: null });
           await crud.reload();
         }}
         submitLabel="Save"
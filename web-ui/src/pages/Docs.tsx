@@ -361,13 +361,23 @@ function ConfigDoc() {
         </tbody>
       </table>
 
+      <h4>Web Push</h4>
+      <table className="w-full text-left">
+        <thead><tr><th>Variable</th><th>Default</th><th>Description</th></tr></thead>
+        <tbody>
+          <tr><td><code>WEB_PUSH_ENABLED</code></td><td><code>true</code></td><td>Browser notifications via VAPID Web Push</td></tr>
+          <tr><td><code>VAPID_SUBJECT</code></td><td><code>BASE_URL</code></td><td>Contact URL sent to push services</td></tr>
+        </tbody>
+      </table>
+
       <h4>SMTP Email</h4>
       <table className="w-full text-left">
         <thead><tr><th>Variable</th><th>Default</th><th>Description</th></tr></thead>
         <tbody>
           <tr><td><code>SMTP_HOST</code></td><td><em>unset</em></td><td>SMTP server hostname</td></tr>
           <tr><td><code>SMTP_PORT</code></td><td><code>587</code></td><td>SMTP server port</td></tr>
-          <tr><td><code>SMTP_USER</code></td><td><em>empty</em></td><td>SMTP username</td></tr>
+          <tr><td><code>SMTP_TLS</code></td><td><em>auto</em></td><td><code>starttls</code>, <code>tls</code> or <code>none</code>; <code>tls</code> on port 465, else <code>starttls</code></td></tr>
+          <tr><td><code>SMTP_USER</code></td><td><em>empty</em></td><td>SMTP username; leave empty for no login</td></tr>
           <tr><td><code>SMTP_PASS</code></td><td><em>empty</em></td><td>SMTP password</td></tr>
           <tr><td><code>SMTP_FROM</code></td><td><em>auto</em></td><td>Sender email address</td></tr>
         </tbody>
@@ -389,6 +389,7 @@ function ThreadMessage({
           )}
           <MessageAttachments message={m} onDeleted={(attId) => onAttachmentDeleted(m.id, attId)} />
           <MessageActions message={m} />
+          <MessageAck message={m} />
           <p className="text-caption text-slate-400 mt-3">{formatLocalTime(m.date)}</p>
         </div>
         <button onClick={() => onDelete(m)} className="flex-shrink-0 text-slate-300 hover:text-error opacity-0 group-hover:opacity-100 focus:opacity-100 transition" aria-label="Delete message">
@@ -420,7 +421,7 @@ function MessageAttachments({ message, onDeleted }: { message: MessageResponse;
         <div key={att.id} className="flex items-start gap-2">
           {isImageType(att.type) ? (
             <a href={att.url} target="_blank" rel="noopener noreferrer">
-              <img src={att.url} alt={att.name} className="max-w-xs max-h-48 rounded-xl border border-slate-200 dark:border-white/10 cursor-pointer hover:opacity-90" />
+              <img src={att.thumbnail_url ?? att.url} alt={att.name} width={att.width ?? undefined} height={att.height ?? undefined} loading="lazy" className="max-w-xs max-h-48 rounded-xl border border-slate-200 dark:border-white/10 cursor-pointer hover:opacity-90" />
             </a>
           ) : (
             <a href={att.url} target="_blank" rel="noopener noreferrer" className="inline-flex items-center gap-2 text-sm text-primary hover:underline bg-slate-50 dark:bg-surface-elevated px-3 py-1.5 rounded-lg">
@@ -469,6 +470,43 @@ function MessageActions({ message }: { message: MessageResponse }) {
   );
 }
 
+/** Acknowledgement state for messages a topic escalates. */
+function MessageAck({ message }: { message: MessageResponse }) {
+  const { toast } = useToast();
+  const [current, setCurrent] = useState(message);
+  const [loading, setLoading] = useState(false);
+  const ex = current.extras && typeof current.extras === 'object' && !Array.isArray(current.extras)
+    ? current.extras as Record<string, any>
+    : null;
+  const ack = ex?.['rstify::ack'];
+  const steps: any[] = ex?.['rstify::escalation']?.steps ?? [];
+
+  if (ack) {
+    return <p className="text-caption text-slate-400 mt-3">Acknowledged by {ack.username} {formatTimeAgo(ack.at)}</p>;
+  }
+  if (steps.length === 0 && current.priority < 8) return null;
+
+  const handleAck = async () => {
+    setLoading(true);
+    try {
+      setCurrent(await api.acknowledgeMessage(current.id));
+    } catch (err) {
+      toast(err instanceof Error ? err.message : 'Acknowledge failed', 'error');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <div className="flex items-center gap-3 mt-3">
+      <button onClick={handleAck} disabled={loading} className="px-3 py-1 text-xs font-semibold text-white bg-primary rounded-pill hover:bg-brand-600 disabled:opacity-50 transition">
+        {loading ? 'Acknowledging...' : 'Acknowledge'}
+      </button>
+      {steps.length > 0 && <span className="text-caption text-slate-400">Escalated {steps.length} {steps.length === 1 ? 'time' : 'times'}</span>}
+    </div>
+  );
+}
+
 function getMessageActions(message: MessageResponse): any[] | null {
   if (message.actions && message.actions.length > 0) return message.actions;
   const extras = message.extras;
@@ -50,6 +50,8 @@ export default function Permissions() {
   };
 
   const getUserName = (id: number) => usersCrud.items.find((u) => u.id === id)?.username || `User #${id}`;
+  const getGranteeName = (p: TopicPermission) =>
+    p.user_id != null ? getUserName(p.user_id) : `Group #${p.group_id}`;
 
   const userOptions = usersCrud.items.map((u) => ({ value: String(u.id), label: u.username }));
 
@@ -82,7 +84,7 @@ export default function Permissions() {
           />
         }
         columns={[
-          { key: 'user_id', header: 'User', render: (p) => getUserName(p.user_id) },
+          { key: 'user_id', header: 'User', render: (p) => getGranteeName(p) },
           { key: 'topic_pattern', header: 'Topic Pattern' },
           { key: 'can_read', header: 'Read', render: (p) => (p.can_read ? 'Yes' : 'No') },
           { key: 'can_write', header: 'Write', render: (p) => (p.can_write ? 'Yes' : 'No') },
@@ -101,6 +103,7 @@ export default function Permissions() {
           if (!topicPattern.trim()) throw new Error('Topic pattern is required');
           await api.createPermission({
             user_id: Number(userId),
+            group_id: null,
             topic_pattern: topicPattern,
             can_read: canRead,
             can_write: canWrite,
@@ -152,7 +155,7 @@ export default function Permissions() {
         onClose={() => setDeletePerm(null)}
         onConfirm={handleDelete}
         title="Delete Permission"
-        message={`Remove ${getUserName(deletePerm?.user_id ?? 0)}'s access to "${deletePerm?.topic_pattern}"?`}
+        message={`Remove ${deletePerm ? getGranteeName(deletePerm) : ''}'s access to "${deletePerm?.topic_pattern}"?`}
       />
     </div>
   );
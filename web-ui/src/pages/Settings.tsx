@@ -1,7 +1,7 @@
 import { useState, useEffect } from 'react';
 import { useAuth } from '../hooks/useAuth';
 import { api } from '../api/client';
-import type { Setting } from 'shared';
+import type { Client, EmailPreferences, QuietHours, Setting } from 'shared';
 import { useToast } from '../components/Toast';
 import { useAsyncAction } from '../hooks/useAsyncAction';
 
@@ -30,6 +30,15 @@ export default function Settings() {
           <PasswordChangeForm />
         </div>
 
+        {/* Browser notifications */}
+        <WebPushForm />
+
+        {/* Email notifications */}
+        <EmailPreferencesForm hasEmail={!!user?.email} />
+
+        {/* Do-not-disturb schedules */}
+        <QuietHoursForm />
+
         {/* Admin: inbox threshold */}
         {user?.is_admin && <InboxThresholdForm />}
       </div>
@@ -86,6 +95,290 @@ function InboxThresholdForm() {
   );
 }
 
+const WEB_PUSH_CLIENT_KEY = 'rstify_webpush_client';
+
+function urlBase64ToUint8Array(value: string): Uint8Array {
+  const padded = (value + '='.repeat((4 - (value.length % 4)) % 4)).replace(/-/g, '+').replace(/_/g, '/');
+  return Uint8Array.from(atob(padded), c => c.charCodeAt(0));
+}
+
+function WebPushForm() {
+  const { toast } = useToast();
+  const supported = 'serviceWorker' in navigator && 'PushManager' in window && 'Notification' in window;
+  const [enabled, setEnabled] = useState(() => localStorage.getItem(WEB_PUSH_CLIENT_KEY) !== null);
+  const toggleAction = useAsyncAction<boolean>();
+
+  const enable = async () => {
+    if (await Notification.requestPermission() !== 'granted') {
+      throw new Error('Notification permission was denied');
+    }
+    const registration = await navigator.serviceWorker.register('/sw.js');
+    const { public_key } = await api.getVapidPublicKey();
+    const subscription = await registration.pushManager.subscribe({
+      userVisibleOnly: true,
+      applicationServerKey: urlBase64ToUint8Array(public_key),
+    });
+    const json = subscription.toJSON();
+    const client = await api.createClient({ name: 'Web browser', scopes: null, expires_at: null, allowed_ips: null });
+    await api.registerWebPush(client.id, {
+      endpoint: json.endpoint ?? subscription.endpoint,
+      keys: { p256dh: json.keys?.p256dh ?? '', auth: json.keys?.auth ?? '' },
+    });
+    localStorage.setItem(WEB_PUSH_CLIENT_KEY, String(client.id));
+    return true;
+  };
+
+  const disable = async () => {
+    const registration = await navigator.serviceWorker.getRegistration('/sw.js');
+    const subscription = await registration?.pushManager.getSubscription();
+    await subscription?.unsubscribe();
+    const clientId = localStorage.getItem(WEB_PUSH_CLIENT_KEY);
+    if (clientId) {
+      await api.deleteClient(Number(clientId)).catch(() => undefined);
+    }
+    localStorage.removeItem(WEB_PUSH_CLIENT_KEY);
+    return false;
+  };
+
+  const handleToggle = async () => {
+    const result = await toggleAction.execute(enabled ? disable : enable);
+    if (result === undefined) return;
+    setEnabled(result);
+    toast(result ? 'Browser notifications enabled' : 'Browser notifications disabled', 'success');
+  };
+
+  return (
+    <div className="bg-white dark:bg-surface-card rounded-2xl border border-slate-200 dark:border-white/10 p-5">
+      <h3 className="text-lg font-semibold dark:text-white mb-3">Browser Notifications</h3>
+      {supported ? (
+        <div className="space-y-3">
+          {toggleAction.error && <div className="bg-error/10 text-error px-4 py-2.5 rounded-xl text-sm">{toggleAction.error}</div>}
+          <p className="text-sm text-slate-600 dark:text-slate-400">
+            Show your messages as system notifications in this browser, even when rstify is closed.
+          </p>
+          <button
+            onClick={handleToggle}
+            disabled={toggleAction.loading}
+            className="px-5 py-2 text-sm font-semibold text-white bg-primary rounded-pill disabled:opacity-50 hover:bg-brand-600 transition"
+          >
+            {toggleAction.loading ? 'Working...' : enabled ? 'Disable' : 'Enable'}
+          </button>
+        </div>
+      ) : (
+        <p className="text-sm text-slate-600 dark:text-slate-400">This browser does not support push notifications.</p>
+      )}
+    </div>
+  );
+}
+
+function EmailPreferencesForm({ hasEmail }: { hasEmail: boolean }) {
+  const { toast } = useToast();
+  const [prefs, setPrefs] = useState<EmailPreferences | null>(null);
+  const [topics, setTopics] = useState('');
+  const saveAction = useAsyncAction<EmailPreferences>();
+
+  useEffect(() => {
+    api.getEmailPreferences().then(p => {
+      setPrefs(p);
+      setTopics(p.topics ? (JSON.parse(p.topics) as string[]).join(', ') : '');
+    }).catch(e => console.error('Failed to load email preferences', e));
+  }, []);
+
+  if (!prefs) return null;
+  const update = (changes: Partial<EmailPreferences>) => setPrefs({ ...prefs, ...changes });
+
+  const handleSave = async () => {
+    const topicList = topics.split(',').map(t => t.trim()).filter(Boolean);
+    const result = await saveAction.execute(() => api.updateEmailPreferences({
+      enabled: prefs.enabled,
+      min_priority: prefs.min_priority,
+      topics: topicList.length ? topicList : null,
+      application_ids: prefs.application_ids ? JSON.parse(prefs.application_ids) : null,
+    }));
+    if (result) {
+      setPrefs(result);
+      toast('Email preferences saved', 'success');
+    }
+  };
+
+  return (
+    <div className="bg-white dark:bg-surface-card rounded-2xl border border-slate-200 dark:border-white/10 p-5">
+      <h3 className="text-lg font-semibold dark:text-white mb-3">Email Notifications</h3>
+      {!hasEmail ? (
+        <p className="text-sm text-slate-600 dark:text-slate-400">Add an email address to your account to receive messages by email.</p>
+      ) : (
+        <div className="space-y-3">
+          {saveAction.error && <div className="bg-error/10 text-error px-4 py-2.5 rounded-xl text-sm">{saveAction.error}</div>}
+          <label className="flex items-center gap-2 text-sm text-slate-700 dark:text-slate-300">
+            <input type="checkbox" checked={prefs.enabled} onChange={e => update({ enabled: e.target.checked })} />
+            Email me messages from my inbox
+          </label>
+          <div>
+            <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">Minimum priority</label>
+            <input type="number" min={0} max={10} value={prefs.min_priority} onChange={e => update({ min_priority: Number(e.target.value) })} className={inputCls} />
+          </div>
+          <div>
+            <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">Topics</label>
+            <input type="text" placeholder="Your own topics" value={topics} onChange={e => setTopics(e.target.value)} className={inputCls} />
+            <p className="text-xs text-gray-400 mt-1">Comma-separated names or patterns such as alerts.*</p>
+          </div>
+          <p className="text-xs text-gray-400">Quiet hours for all devices hold back email too.</p>
+          <button
+            onClick={handleSave}
+            disabled={saveAction.loading}
+            className="px-5 py-2 text-sm font-semibold text-white bg-primary rounded-pill disabled:opacity-50 hover:bg-brand-600 transition"
+          >
+            {saveAction.loading ? 'Saving...' : 'Save'}
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
+
+const WEEKDAYS = ['mon', 'tue', 'wed', 'thu', 'fri', 'sat', 'sun'];
+
+function QuietHoursForm() {
+  const { toast } = useToast();
+  const [schedules, setSchedules] = useState<QuietHours[]>([]);
+  const [clients, setClients] = useState<Client[]>([]);
+  const [clientId, setClientId] = useState('');
+  const [start, setStart] = useState('22:00');
+  const [end, setEnd] = useState('07:00');
+  const [days, setDays] = useState<string[]>([]);
+  const [timezone, setTimezone] = useState(Intl.DateTimeFormat().resolvedOptions().timeZone || 'UTC');
+  const [override, setOverride] = useState('');
+  const saveAction = useAsyncAction<QuietHours>();
+
+  const load = () => {
+    api.listQuietHours().then(setSchedules).catch(e => console.error('Failed to load quiet hours', e));
+  };
+  useEffect(() => {
+    load();
+    api.listClients().then(setClients).catch(e => console.error('Failed to load clients', e));
+  }, []);
+
+  const clientName = (id: number | null) =>
+    id === null ? 'All devices and email' : clients.find(c => c.id === id)?.name ?? `Client ${id}`;
+
+  const toggleDay = (day: string) =>
+    setDays(days.includes(day) ? days.filter(d => d !== day) : [...days, day]);
+
+  const handleAdd = async () => {
+    const result = await saveAction.execute(() => api.createQuietHours({
+      client_id: clientId ? Number(clientId) : null,
+      enabled: true,
+      start_time: start,
+      end_time: end,
+      days: days.length ? days : null,
+      timezone,
+      override_priority: override ? Number(override) : null,
+    }));
+    if (result) {
+      toast('Quiet hours added', 'success');
+      setDays([]);
+      load();
+    }
+  };
+
+  const handleToggle = async (s: QuietHours) => {
+    try {
+      await api.updateQuietHours(s.id, {
+        client_id: s.client_id,
+        enabled: !s.enabled,
+        start_time: s.start_time,
+        end_time: s.end_time,
+        days: s.days ? JSON.parse(s.days) : null,
+        timezone: s.timezone,
+        override_priority: s.override_priority,
+      });
+      load();
+    } catch (e) {
+      toast(e instanceof Error ? e.message : 'Failed to update quiet hours', 'error');
+    }
+  };
+
+  const handleDelete = async (id: number) => {
+    try {
+      await api.deleteQuietHours(id);
+      load();
+    } catch (e) {
+      toast(e instanceof Error ? e.message : 'Failed to delete quiet hours', 'error');
+    }
+  };
+
+  return (
+    <div className="bg-white dark:bg-surface-card rounded-2xl border border-slate-200 dark:border-white/10 p-5">
+      <h3 className="text-lg font-semibold dark:text-white mb-1">Quiet Hours</h3>
+      <p className="text-xs text-gray-400 mb-3">Pushes are held back during these windows and summarized in one notification when they end.</p>
+      {schedules.length > 0 && (
+        <ul className="space-y-2 mb-4">
+          {schedules.map(s => (
+            <li key={s.id} className="flex items-center justify-between gap-2 text-sm text-slate-700 dark:text-slate-300">
+              <span className={s.enabled ? '' : 'opacity-50'}>
+                {s.start_time} to {s.end_time} ({s.timezone})
+                {s.days && <> on {(JSON.parse(s.days) as string[]).join(', ')}</>}
+                {' '}&middot; {clientName(s.client_id)}
+                {s.override_priority !== null && <> &middot; priority {s.override_priority}+ breaks through</>}
+              </span>
+              <span className="flex gap-2 shrink-0">
+                <button onClick={() => handleToggle(s)} className="text-xs text-primary hover:underline">{s.enabled ? 'Pause' : 'Resume'}</button>
+                <button onClick={() => handleDelete(s.id)} className="text-xs text-error hover:underline">Delete</button>
+              </span>
+            </li>
+          ))}
+        </ul>
+      )}
+      <div className="space-y-3">
+        {saveAction.error && <div className="bg-error/10 text-error px-4 py-2.5 rounded-xl text-sm">{saveAction.error}</div>}
+        <div className="grid grid-cols-2 gap-3">
+          <div>
+            <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">From</label>
+            <input type="time" value={start} onChange={e => setStart(e.target.value)} className={inputCls} />
+          </div>
+          <div>
+            <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">Until</label>
+            <input type="time" value={end} onChange={e => setEnd(e.target.value)} className={inputCls} />
+          </div>
+        </div>
+        <div className="flex flex-wrap gap-3 text-sm text-slate-700 dark:text-slate-300">
+          {WEEKDAYS.map(day => (
+            <label key={day} className="flex items-center gap-1">
+              <input type="checkbox" checked={days.includes(day)} onChange={() => toggleDay(day)} />
+              {day}
+            </label>
+          ))}
+        </div>
+        <p className="text-xs text-gray-400">Days the window starts on. Leave all unchecked for every day.</p>
+        <div>
+          <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">Device</label>
+          <select value={clientId} onChange={e => setClientId(e.target.value)} className={inputCls}>
+            <option value="">All devices and email</option>
+            {clients.map(c => <option key={c.id} value={c.id}>{c.name}</option>)}
+          </select>
+        </div>
+        <div className="grid grid-cols-2 gap-3">
+          <div>
+            <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">Timezone</label>
+            <input type="text" value={timezone} onChange={e => setTimezone(e.target.value)} className={inputCls} />
+          </div>
+          <div>
+            <label className="block text-sm font-medium text-slate-700 dark:text-slate-300 mb-1">Break through at priority</label>
+            <input type="number" min={0} max={10} placeholder="Never" value={override} onChange={e => setOverride(e.target.value)} className={inputCls} />
+          </div>
+        </div>
+        <button
+          onClick={handleAdd}
+          disabled={saveAction.loading}
+          className="px-5 py-2 text-sm font-semibold text-white bg-primary rounded-pill disabled:opacity-50 hover:bg-brand-600 transition"
+        >
+          {saveAction.loading ? 'Saving...' : 'Add quiet hours'}
+        </button>
+      </div>
+    </div>
+  );
+}
+
 function PasswordChangeForm() {
   const [currentPassword, setCurrentPassword] = useState('');
   const [newPassword, setNewPassword] = useState('');
@@ -1,7 +1,7 @@
 import { useState, useCallback } from 'react';
 import { useNavigate } from 'react-router-dom';
 import { api } from '../api/client';
-import type { Topic, MessageResponse } from 'shared';
+import type { Topic, MessageResponse, EscalationPolicy, EscalationStep } from 'shared';
 import DataTable from '../components/DataTable';
 import EmptyState from '../components/EmptyState';
 import Modal from '../components/Modal';
@@ -90,6 +90,7 @@ export default function Topics() {
         onSubmit={async () => {
           await api.createTopic({
             name: createName,
+            group_id: null,
             description: createDescription || null,
             everyone_read: createEveryoneRead,
             everyone_write: createEveryoneWrite,
@@ -145,6 +146,9 @@ function EditTopicForm({ topic, onSubmit, onClose }: {
     inbox_override: topic.inbox_override ?? null,
     inbox_priority_min: topic.inbox_priority_min ?? null,
   });
+  const [escalation, setEscalation] = useState<EscalationPolicy>(() =>
+    topic.escalation_policy ? JSON.parse(topic.escalation_policy) : { min_priority: 8, steps: [] });
+  const [escalationChanged, setEscalationChanged] = useState(false);
   const [showPolicies, setShowPolicies] = useState(false);
   const [error, setError] = useState('');
   const [loading, setLoading] = useState(false);
@@ -166,6 +170,8 @@ function EditTopicForm({ topic, onSubmit, onClose }: {
         // '' explicitly clears the override (server treats null as keep-current)
         inbox_override: form.inbox_override || '',
         inbox_priority_min: form.inbox_override === 'threshold' ? (form.inbox_priority_min ?? 5) : null,
+        // No steps removes the policy; unchanged policies aren't re-sent.
+        escalation_policy: escalationChanged ? escalation : undefined,
       });
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed');
@@ -174,6 +180,14 @@ function EditTopicForm({ topic, onSubmit, onClose }: {
     }
   };
 
+  const updateEscalation = (changes: Partial<EscalationPolicy>) => {
+    setEscalation(e => ({ ...e, ...changes }));
+    setEscalationChanged(true);
+  };
+  const updateStep = (index: number, changes: Partial<EscalationStep>) =>
+    updateEscalation({ steps: escalation.steps.map((s, i) => i === index ? { ...s, ...changes } : s) });
+  const stepTarget = (step: EscalationStep) => step.user_id !== null ? 'user' : step.group_id !== null ? 'group' : '';
+
   const labelClass = "block text-sm font-medium dark:text-gray-300 mb-1";
   const inputClass = "w-full border dark:border-gray-600 rounded px-3 py-2 text-sm dark:bg-gray-700 dark:text-white";
   const radioClass = "flex items-center gap-2 text-sm dark:text-gray-300";
@@ -270,6 +284,54 @@ function EditTopicForm({ topic, onSubmit, onClose }: {
               />
             </div>
           )}
+          <div>
+            <label className={labelClass}>Escalation</label>
+            <p className="text-xs text-gray-400 mb-2">Steps run in turn until someone acknowledges the message. Without a user or group, pushes go to the usual devices and email to the topic owner.</p>
+            <div className="mb-2">
+              <label className={labelClass}>Escalate messages from priority</label>
+              <input type="number" min={0} max={10} value={escalation.min_priority} onChange={e => updateEscalation({ min_priority: parseInt(e.target.value) || 0 })} className={inputClass} />
+            </div>
+            {escalation.steps.map((step, i) => (
+              <div key={i} className="grid grid-cols-4 gap-2 mb-2 items-center">
+                <input type="number" min={1} title="Minutes" value={step.after_minutes} onChange={e => updateStep(i, { after_minutes: parseInt(e.target.value) || 1 })} className={inputClass} />
+                <select value={step.action} onChange={e => updateStep(i, { action: e.target.value })} className={inputClass}>
+                  <option value="push">Push</option>
+                  <option value="email">Email</option>
+                </select>
+                <select
+                  value={stepTarget(step)}
+                  onChange={e => updateStep(i, e.target.value === 'user' ? { user_id: 0, group_id: null } : e.target.value === 'group' ? { user_id: null, group_id: 0 } : { user_id: null, group_id: null })}
+                  className={inputClass}
+                >
+                  <option value="">Usual recipients</option>
+                  <option value="user">User ID</option>
+                  <option value="group">Group ID</option>
+                </select>
+                <div className="flex gap-1 items-center">
+                  {stepTarget(step) && (
+                    <input
+                      type="number"
+                      min={1}
+                      value={(step.user_id ?? step.group_id) || ''}
+                      onChange={e => {
+                        const id = parseInt(e.target.value) || 0;
+                        updateStep(i, stepTarget(step) === 'user' ? { user_id: id } : { group_id: id });
+                      }}
+                      className={inputClass}
+                    />
+                  )}
+                  <button type="button" onClick={() => updateEscalation({ steps: escalation.steps.filter((_, j) => j !== i) })} className="text-error text-sm">&times;</button>
+                </div>
+              </div>
+            ))}
+            <button
+              type="button"
+              onClick={() => updateEscalation({ steps: [...escalation.steps, { after_minutes: 5, action: 'push', user_id: null, group_id: null }] })}
+              className="text-sm text-primary hover:text-brand-700"
+            >
+              + Add step
+            </button>
+          </div>
         </div>
       )}
 
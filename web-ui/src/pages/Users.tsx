@@ -43,6 +43,8 @@ export default function Users() {
     setEditUser(u);
   };
 
+  const isLocked = (u: UserResponse) => !!u.locked_until && new Date(u.locked_until) > new Date();
+
   const handleDelete = async () => {
     if (!deleteUser) return;
     const ok = await crud.mutate(() => api.deleteUser(deleteUser.id));
@@ -81,10 +83,32 @@ export default function Users() {
           { key: 'username', header: 'Username' },
           { key: 'email', header: 'Email', render: (u) => u.email || '-' },
           { key: 'is_admin', header: 'Admin', render: (u) => (u.is_admin ? 'Yes' : 'No') },
+          {
+            key: 'locked_until',
+            header: 'Status',
+            render: (u) =>
+              isLocked(u) ? (
+                <span className="text-error" title={`Locked until ${formatLocalTime(u.locked_until!)}`}>
+                  Locked
+                </span>
+              ) : u.failed_logins > 0 ? (
+                `${u.failed_logins} failed login${u.failed_logins === 1 ? '' : 's'}`
+              ) : (
+                'Active'
+              ),
+          },
           { key: 'created_at', header: 'Created', render: (u) => formatLocalTime(u.created_at) },
         ]}
         actions={(u) => (
           <div className="flex gap-2 justify-end">
+            {(isLocked(u) || u.failed_logins > 0) && (
+              <button
+                onClick={() => crud.mutate(() => api.unlockUser(u.id))}
+                className="text-primary hover:text-brand-700 text-sm font-medium"
+              >
+                Unlock
+              </button>
+            )}
             <button onClick={() => openEdit(u)} className="text-primary hover:text-brand-700 text-sm font-medium">
               Edit
             </button>
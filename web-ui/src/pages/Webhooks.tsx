@@ -77,6 +77,7 @@ export default function Webhooks() {
       template: null,
       groupName: w.group_name ?? null,
       secret: null,
+      notifyUrl: w.notify_url ?? null,
     }).then(() => {}));
     if (ok) toast('Webhook duplicated', 'success');
   };
@@ -87,6 +88,7 @@ export default function Webhooks() {
       targetUrl: null, httpMethod: null, headers: null,
       bodyTemplate: null, maxRetries: null, retryDelaySecs: null,
       timeoutSecs: null, followRedirects: null, groupName: null, secret: null,
+      notifyUrl: null,
     }).then(() => {}));
   };
 